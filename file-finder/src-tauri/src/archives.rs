@@ -0,0 +1,602 @@
+// Entries inside archives, indexed as virtual children of the archive: "backup.zip!/src/main.rs".
+// ZIP and tar (plain or gzipped) are read here; 7z goes through the 7-Zip command line when it is
+// installed. Off by default, since a download folder full of archives can add many entries.
+// Inner entries are extracted on demand, to a temporary folder for opening or to a chosen folder.
+
+use chrono::{Local, NaiveDate, NaiveDateTime, TimeZone};
+use flate2::read::GzDecoder;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use specta::Type;
+use std::collections::{BTreeMap, HashSet};
+use std::fs::{self, File};
+use std::io::{self, BufReader, Read};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::OnceLock;
+use tauri::State;
+use tracing::warn;
+
+use crate::error::{AppError, ErrorCode};
+use crate::zip::ZipArchive;
+use crate::{db, file_ops, settings, AppState};
+
+pub const SETTINGS_KEY: &str = "archive_indexing";
+
+// Between the archive's own path and the path inside it, whatever the platform separator
+pub const SEPARATOR: &str = "!/";
+
+// 7-Zip's executable names: full, standalone and the Linux/macOS 7zz build
+const SEVEN_ZIP_PROGRAMS: &[&str] = &["7z", "7za", "7zz", "C:\\Program Files\\7-Zip\\7z.exe"];
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Type)]
+#[serde(default)]
+pub struct ArchiveIndexing {
+    pub enabled: bool,
+    // Entries indexed per archive; the rest of a huge archive is left out
+    pub max_entries: u32,
+    // Compressed tarballs have no directory and are read through to list them; larger ones are skipped
+    pub max_tar_mb: u32,
+}
+
+impl Default for ArchiveIndexing {
+    fn default() -> Self {
+        Self { enabled: false, max_entries: 10_000, max_tar_mb: 512 }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Format {
+    Zip,
+    Tar,
+    TarGz,
+    SevenZip,
+}
+
+fn format_of(path: &str) -> Option<Format> {
+    let lower = path.to_lowercase();
+    if lower.ends_with(".zip") {
+        Some(Format::Zip)
+    } else if lower.ends_with(".tar.gz") || lower.ends_with(".tgz") {
+        Some(Format::TarGz)
+    } else if lower.ends_with(".tar") {
+        Some(Format::Tar)
+    } else if lower.ends_with(".7z") {
+        Some(Format::SevenZip)
+    } else {
+        None
+    }
+}
+
+// "archive.zip!/inner/path" -> ("archive.zip", "inner/path")
+pub fn split(path: &str) -> Option<(&str, &str)> {
+    let (archive, inner) = path.split_once(SEPARATOR)?;
+    format_of(archive).map(|_| (archive, inner))
+}
+
+struct InnerEntry {
+    // '/'-separated, without a trailing slash
+    path: String,
+    modified_at: Option<i64>,
+}
+
+// Inner paths that would land outside the extraction folder ("../", absolute) are dropped
+fn clean_inner_path(raw: &str) -> Option<String> {
+    let raw = raw.replace('\\', "/");
+    let mut parts = Vec::new();
+    for part in raw.split('/') {
+        match part {
+            "" | "." => {}
+            ".." => return None,
+            _ if part.contains(':') && parts.is_empty() => return None,
+            _ => parts.push(part),
+        }
+    }
+    // macOS resource forks zipped alongside the real files
+    if parts.first() == Some(&"__MACOSX") || parts.is_empty() {
+        return None;
+    }
+    Some(parts.join("/"))
+}
+
+fn dos_time(date: u16, time: u16) -> Option<i64> {
+    let day = NaiveDate::from_ymd_opt(1980 + (date >> 9) as i32, ((date >> 5) & 15) as u32, (date & 31) as u32)?;
+    let moment = day.and_hms_opt((time >> 11) as u32, ((time >> 5) & 63) as u32, ((time & 31) * 2) as u32)?;
+    Local.from_local_datetime(&moment).earliest().map(|local| local.timestamp())
+}
+
+struct TarHeader {
+    path: String,
+    modified_at: Option<i64>,
+    is_dir: bool,
+    is_file: bool,
+}
+
+fn octal(field: &[u8]) -> u64 {
+    let text = String::from_utf8_lossy(field);
+    u64::from_str_radix(text.trim_matches(|c: char| c == '\0' || c == ' '), 8).unwrap_or(0)
+}
+
+fn c_string(field: &[u8]) -> String {
+    let end = field.iter().position(|&b| b == 0).unwrap_or(field.len());
+    String::from_utf8_lossy(&field[..end]).to_string()
+}
+
+// The `path` record of a PAX extended header ("<length> path=<value>\n")
+fn pax_path(records: &[u8]) -> Option<String> {
+    let text = String::from_utf8_lossy(records);
+    text.lines().find_map(|line| {
+        let (_, record) = line.split_once(' ')?;
+        record.strip_prefix("path=").map(|path| path.to_string())
+    })
+}
+
+// Walk a tar stream; `visit` gets each entry with its data and returns false to stop
+fn walk_tar(mut reader: impl Read, mut visit: impl FnMut(&TarHeader, &mut dyn Read) -> Result<bool, String>) -> Result<(), String> {
+    let mut block = [0u8; 512];
+    // GNU long names and PAX headers name the entry that follows them
+    let mut long_name: Option<String> = None;
+    loop {
+        if reader.read_exact(&mut block).is_err() || block.iter().all(|&b| b == 0) {
+            return Ok(());
+        }
+        let size = octal(&block[124..136]);
+        let padding = (512 - size % 512) % 512;
+        let kind = block[156];
+        let mut data = (&mut reader).take(size);
+
+        match kind {
+            b'L' | b'x' => {
+                let mut records = Vec::new();
+                data.read_to_end(&mut records).map_err(|e| e.to_string())?;
+                long_name = if kind == b'L' { Some(c_string(&records)) } else { pax_path(&records) }.or(long_name);
+            }
+            b'g' => {
+                io::copy(&mut data, &mut io::sink()).map_err(|e| e.to_string())?;
+            }
+            _ => {
+                let mut path = c_string(&block[0..100]);
+                // ustar splits long paths into a prefix and a name
+                if &block[257..262] == b"ustar" {
+                    let prefix = c_string(&block[345..500]);
+                    if !prefix.is_empty() {
+                        path = format!("{}/{}", prefix, path);
+                    }
+                }
+                let header = TarHeader {
+                    path: long_name.take().unwrap_or(path),
+                    modified_at: Some(octal(&block[136..148]) as i64).filter(|&time| time > 0),
+                    is_dir: kind == b'5',
+                    is_file: matches!(kind, b'0' | 0 | b'7'),
+                };
+                let keep_going = visit(&header, &mut data)?;
+                if !keep_going {
+                    return Ok(());
+                }
+                io::copy(&mut data, &mut io::sink()).map_err(|e| e.to_string())?;
+            }
+        }
+        io::copy(&mut (&mut reader).take(padding), &mut io::sink()).map_err(|e| e.to_string())?;
+    }
+}
+
+fn tar_reader(archive: &Path, format: Format) -> Result<Box<dyn Read>, String> {
+    let file = BufReader::new(File::open(archive).map_err(|e| format!("Could not read {}: {}", archive.display(), e))?);
+    Ok(match format {
+        Format::TarGz => Box::new(GzDecoder::new(file)),
+        _ => Box::new(file),
+    })
+}
+
+// Looked up once per run
+fn seven_zip() -> Option<&'static str> {
+    static PROGRAM: OnceLock<Option<&'static str>> = OnceLock::new();
+    *PROGRAM.get_or_init(|| {
+        SEVEN_ZIP_PROGRAMS.iter().copied().find(|program| {
+            Command::new(program).arg("i").output().is_ok_and(|output| output.status.success())
+        })
+    })
+}
+
+// `7z l -slt` prints one "Key = value" block per entry
+fn list_seven_zip(archive: &Path) -> Result<Vec<(String, Option<i64>)>, String> {
+    let program = seven_zip().ok_or("7-Zip is not installed")?;
+    let output = Command::new(program)
+        .args(["l", "-slt", "-ba", "-sccUTF-8"])
+        // Ends the switches, so an archive name starting with `-` isn't read as one
+        .arg("--")
+        .arg(archive)
+        .output()
+        .map_err(|e| e.to_string())?;
+    if !output.status.success() {
+        return Err(format!("7-Zip could not list {}", archive.display()));
+    }
+    let listing = String::from_utf8_lossy(&output.stdout).replace("\r\n", "\n");
+    let mut entries = Vec::new();
+    for block in listing.split("\n\n") {
+        let field = |key: &str| {
+            block.lines().find_map(|line| line.trim_end().strip_prefix(key)?.strip_prefix(" = ").map(str::to_string))
+        };
+        let Some(path) = field("Path") else {
+            continue;
+        };
+        let modified_at = field("Modified")
+            .and_then(|time| NaiveDateTime::parse_from_str(time.get(..19)?, "%Y-%m-%d %H:%M:%S").ok())
+            .and_then(|time| Local.from_local_datetime(&time).earliest())
+            .map(|time| time.timestamp());
+        entries.push((path, modified_at));
+    }
+    Ok(entries)
+}
+
+// Files and folders inside an archive, with folders implied by file paths added
+fn list(archive: &Path, settings: &ArchiveIndexing) -> Result<Vec<InnerEntry>, String> {
+    let archive_str = archive.to_string_lossy();
+    let format = format_of(&archive_str).ok_or("Not an archive")?;
+    let limit = settings.max_entries as usize;
+    // Path -> modification time; sorted so parents come before their children
+    let mut found: BTreeMap<String, Option<i64>> = BTreeMap::new();
+    let mut add = |raw: &str, modified_at: Option<i64>| {
+        let Some(path) = clean_inner_path(raw) else {
+            return;
+        };
+        let mut parent = path.as_str();
+        while let Some((up, _)) = parent.rsplit_once('/') {
+            found.entry(up.to_string()).or_insert(None);
+            parent = up;
+        }
+        found.insert(path, modified_at);
+    };
+
+    match format {
+        Format::Zip => {
+            let zip = ZipArchive::open(archive)?;
+            for entry in zip.entries.iter().take(limit) {
+                add(&entry.name, dos_time(entry.modified.0, entry.modified.1));
+            }
+        }
+        Format::Tar | Format::TarGz => {
+            let size = fs::metadata(archive).map_err(|e| e.to_string())?.len();
+            if size > settings.max_tar_mb as u64 * 1024 * 1024 {
+                return Err(format!("{} is larger than {} MB", archive.display(), settings.max_tar_mb));
+            }
+            let mut count = 0;
+            walk_tar(tar_reader(archive, format)?, |header, _| {
+                add(&header.path, header.modified_at);
+                count += 1;
+                Ok(count < limit)
+            })?;
+        }
+        Format::SevenZip => {
+            for (path, modified_at) in list_seven_zip(archive)?.into_iter().take(limit) {
+                add(&path, modified_at);
+            }
+        }
+    }
+    Ok(found.into_iter().map(|(path, modified_at)| InnerEntry { path, modified_at }).collect())
+}
+
+// Virtual entries for the archives among `paths` that have none yet, as (path, name, modified_at)
+// rows for the indexer. Archives that fail to list are logged and skipped.
+pub fn expand(paths: &HashSet<String>, settings: &ArchiveIndexing) -> Vec<(String, String, Option<i64>)> {
+    let expanded: HashSet<&str> = paths.iter().filter_map(|path| split(path).map(|(archive, _)| archive)).collect();
+    let mut rows = Vec::new();
+    for archive in paths.iter().filter(|path| !path.contains(SEPARATOR) && format_of(path).is_some()) {
+        if expanded.contains(archive.as_str()) || !Path::new(archive).is_file() {
+            continue;
+        }
+        let archive_modified = fs::metadata(archive)
+            .ok()
+            .and_then(|metadata| metadata.modified().ok())
+            .and_then(|time| time.duration_since(std::time::UNIX_EPOCH).ok())
+            .map(|elapsed| elapsed.as_secs() as i64);
+        match list(Path::new(archive), settings) {
+            Ok(entries) => {
+                for entry in entries {
+                    let name = entry.path.rsplit('/').next().unwrap_or(&entry.path).to_string();
+                    let path = format!("{}{}{}", archive, SEPARATOR, entry.path);
+                    rows.push((path, name, entry.modified_at.or(archive_modified)));
+                }
+            }
+            Err(e) => warn!("Skipping archive {}: {}", archive, e),
+        }
+    }
+    rows
+}
+
+fn write_file(target: &Path, data: &mut dyn Read) -> Result<(), String> {
+    if let Some(parent) = target.parent() {
+        fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+    let mut file = File::create(target).map_err(|e| format!("Could not create {}: {}", target.display(), e))?;
+    io::copy(data, &mut file).map_err(|e| format!("Could not extract {}: {}", target.display(), e))?;
+    Ok(())
+}
+
+// Extract `inner` (a file, or a folder with everything in it) into `dest_dir`, named after its last
+// component. Returns where it went.
+fn extract(archive: &Path, inner: &str, dest_dir: &Path) -> Result<PathBuf, String> {
+    let archive_str = archive.to_string_lossy();
+    let format = format_of(&archive_str).ok_or("Not an archive")?;
+    let inner = clean_inner_path(inner).ok_or("Invalid path inside the archive")?;
+    let name = inner.rsplit('/').next().unwrap_or(&inner).to_string();
+    let target = match dest_dir.join(&name) {
+        free if fs::symlink_metadata(&free).is_err() => free,
+        _ => file_ops::free_name(dest_dir, name.as_ref()).ok_or_else(|| format!("No free name for {} in {}", name, dest_dir.display()))?,
+    };
+    // Where a member lands: the part of its path below `inner`, under `target`
+    let destination = |member: &str| -> Option<PathBuf> {
+        let member = clean_inner_path(member)?;
+        if member == inner {
+            return Some(target.clone());
+        }
+        let below = member.strip_prefix(&inner)?.strip_prefix('/')?;
+        Some(target.join(below))
+    };
+
+    let mut found = false;
+    match format {
+        Format::Zip => {
+            let mut zip = ZipArchive::open(archive)?;
+            let members: Vec<(String, bool)> = zip.entries.iter().map(|entry| (entry.name.clone(), entry.is_dir)).collect();
+            for (member, is_dir) in members {
+                let Some(out) = destination(&member) else {
+                    continue;
+                };
+                found = true;
+                if is_dir {
+                    fs::create_dir_all(&out).map_err(|e| e.to_string())?;
+                } else {
+                    write_file(&out, &mut zip.reader(&member)?)?;
+                }
+            }
+        }
+        Format::Tar | Format::TarGz => {
+            walk_tar(tar_reader(archive, format)?, |header, data| {
+                let Some(out) = destination(&header.path) else {
+                    return Ok(true);
+                };
+                found = true;
+                if header.is_dir {
+                    fs::create_dir_all(&out).map_err(|e| e.to_string())?;
+                } else if header.is_file {
+                    write_file(&out, data)?;
+                }
+                // A single file is done; a folder's members can be anywhere in the stream
+                Ok(header.is_dir || out != target)
+            })?;
+        }
+        Format::SevenZip => {
+            let program = seven_zip().ok_or("7-Zip is not installed")?;
+            // 7-Zip recreates the inner folders, so extract to a staging folder and move the result up
+            let staging = dest_dir.join(format!(".file-finder-extract-{}", std::process::id()));
+            let status = Command::new(program)
+                .arg("x")
+                .arg(format!("-o{}", staging.display()))
+                // -spd: a member named `*` or `[a].txt` is that file, not a wildcard
+                .args(["-y", "-bd", "-spd"])
+                // Member names come from the archive and can start with `-`
+                .arg("--")
+                .arg(archive)
+                .arg(&inner)
+                .stdout(std::process::Stdio::null())
+                .status()
+                .map_err(|e| e.to_string())?;
+            let extracted = staging.join(&inner);
+            if status.success() && extracted.exists() {
+                fs::rename(&extracted, &target).map_err(|e| e.to_string())?;
+                found = true;
+            }
+            let _ = fs::remove_dir_all(&staging);
+        }
+    }
+    if !found {
+        return Err(format!("{} is not in {}", inner, archive.display()));
+    }
+    Ok(target)
+}
+
+// Extracted copies of inner entries that were opened, one folder per archive entry
+fn open_cache_dir(path: &str) -> PathBuf {
+    let digest = Sha256::digest(path.as_bytes());
+    let hex: String = digest.iter().take(12).map(|byte| format!("{:02x}", byte)).collect();
+    std::env::temp_dir().join("file-finder-archives").join(hex)
+}
+
+// A real file for an archive entry, so it can be handed to another program. Re-extracted every
+// time, since the archive may have changed.
+pub fn extract_for_opening(path: &str) -> Result<PathBuf, String> {
+    let (archive, inner) = split(path).ok_or_else(|| format!("{} is not inside an archive", path))?;
+    let dir = open_cache_dir(path);
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+    extract(Path::new(archive), inner, &dir)
+}
+
+pub fn load(conn: &rusqlite::Connection) -> ArchiveIndexing {
+    settings::load(conn, SETTINGS_KEY)
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn get_archive_indexing(state: State<'_, AppState>) -> Result<ArchiveIndexing, AppError> {
+    db::write(&state.db, |db| Ok(load(db)))
+}
+
+// Applies from the next indexing run, which also lists archives indexed before it was turned on
+#[tauri::command]
+#[specta::specta]
+pub async fn set_archive_indexing(settings: ArchiveIndexing, state: State<'_, AppState>) -> Result<ArchiveIndexing, AppError> {
+    db::write(&state.db, |db| {
+        settings::save(db, SETTINGS_KEY, &settings)?;
+        Ok(settings)
+    })
+}
+
+// Extract an entry found inside an archive ("a.zip!/docs/x.txt") into `dest_dir`. A name already
+// taken there gets a number. Returns the extracted path.
+#[tauri::command]
+#[specta::specta]
+pub async fn extract_archive_entry(path: String, dest_dir: String) -> Result<String, AppError> {
+    let (archive, inner) = split(&path).ok_or_else(|| format!("{} is not inside an archive", path))?;
+    let dest = Path::new(&dest_dir);
+    if !dest.is_dir() {
+        return Err(AppError::new(ErrorCode::InvalidInput, format!("{} is not a folder", dest_dir)));
+    }
+    let target = db::blocking(|| extract(Path::new(archive), inner, dest))?;
+    Ok(target.to_string_lossy().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // A ustar header block for an entry of `size` bytes
+    fn header(name: &str, kind: u8, size: usize) -> [u8; 512] {
+        let mut block = [0u8; 512];
+        block[..name.len()].copy_from_slice(name.as_bytes());
+        block[124..135].copy_from_slice(format!("{:011o}", size).as_bytes());
+        block[136..147].copy_from_slice(format!("{:011o}", 1_700_000_000).as_bytes());
+        block[156] = kind;
+        block[257..263].copy_from_slice(b"ustar\0");
+        block
+    }
+
+    // Append an entry and its data, padded to whole blocks
+    fn push(tar: &mut Vec<u8>, header: [u8; 512], data: &[u8]) {
+        tar.extend_from_slice(&header);
+        tar.extend_from_slice(data);
+        tar.resize(tar.len().next_multiple_of(512), 0);
+    }
+
+    fn file(tar: &mut Vec<u8>, name: &str, data: &[u8]) {
+        push(tar, header(name, b'0', data.len()), data);
+    }
+
+    fn end(tar: &mut Vec<u8>) {
+        tar.extend_from_slice(&[0u8; 1024]);
+    }
+
+    fn paths(tar: &[u8]) -> Vec<String> {
+        let mut paths = Vec::new();
+        walk_tar(tar, |header, _| {
+            paths.push(header.path.clone());
+            Ok(true)
+        })
+        .unwrap();
+        paths
+    }
+
+    #[test]
+    fn parent_references_are_rejected() {
+        assert_eq!(clean_inner_path("../etc/passwd"), None);
+        assert_eq!(clean_inner_path("docs/../../secret"), None);
+        assert_eq!(clean_inner_path("docs\\..\\secret"), None);
+    }
+
+    #[test]
+    fn drive_letters_are_rejected() {
+        assert_eq!(clean_inner_path("C:/Windows/win.ini"), None);
+        assert_eq!(clean_inner_path("C:\\Windows\\win.ini"), None);
+        assert_eq!(clean_inner_path("c:evil.txt"), None);
+        // Only a leading component can name a drive
+        assert_eq!(clean_inner_path("notes/10:30.txt").as_deref(), Some("notes/10:30.txt"));
+    }
+
+    #[test]
+    fn inner_paths_are_made_relative_and_tidy() {
+        assert_eq!(clean_inner_path("/etc/passwd").as_deref(), Some("etc/passwd"));
+        assert_eq!(clean_inner_path("./src//main.rs").as_deref(), Some("src/main.rs"));
+        assert_eq!(clean_inner_path("src\\lib\\").as_deref(), Some("src/lib"));
+        assert_eq!(clean_inner_path("__MACOSX/._report.pdf"), None);
+        assert_eq!(clean_inner_path("./"), None);
+    }
+
+    #[test]
+    fn tar_entries_are_listed_with_their_data() {
+        let mut tar = Vec::new();
+        push(&mut tar, header("src/", b'5', 0), b"");
+        file(&mut tar, "src/main.rs", b"fn main() {}");
+        end(&mut tar);
+
+        let mut seen = Vec::new();
+        walk_tar(tar.as_slice(), |header, data| {
+            let mut text = String::new();
+            data.read_to_string(&mut text).unwrap();
+            seen.push((header.path.clone(), header.is_dir, header.is_file, header.modified_at, text));
+            Ok(true)
+        })
+        .unwrap();
+        assert_eq!(
+            seen,
+            vec![
+                ("src/".to_string(), true, false, Some(1_700_000_000), String::new()),
+                ("src/main.rs".to_string(), false, true, Some(1_700_000_000), "fn main() {}".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn gnu_long_names_name_the_next_entry() {
+        let long_name = format!("{}/report.txt", "a".repeat(150));
+        let mut tar = Vec::new();
+        let mut record = long_name.clone().into_bytes();
+        record.push(0);
+        push(&mut tar, header("././@LongLink", b'L', record.len()), &record);
+        file(&mut tar, "aaaa/report.txt", b"x");
+        file(&mut tar, "short.txt", b"y");
+        end(&mut tar);
+        assert_eq!(paths(&tar), vec![long_name, "short.txt".to_string()]);
+    }
+
+    #[test]
+    fn pax_path_records_name_the_next_entry() {
+        let long_name = format!("{}/notes.md", "b".repeat(120));
+        // Each record starts with its own length, digits included
+        let record = format!("20 mtime=1700000000\n{} path={}\n", long_name.len() + 10, long_name);
+        let mut tar = Vec::new();
+        push(&mut tar, header("PaxHeader/notes.md", b'x', record.len()), record.as_bytes());
+        file(&mut tar, "bbbb/notes.md", b"x");
+        end(&mut tar);
+        assert_eq!(paths(&tar), vec![long_name]);
+    }
+
+    #[test]
+    fn ustar_prefixes_are_joined_to_the_name() {
+        let mut block = header("report.txt", b'0', 0);
+        block[345..363].copy_from_slice(b"projects/2023/q4xx");
+        let mut tar = Vec::new();
+        push(&mut tar, block, b"");
+        end(&mut tar);
+        assert_eq!(paths(&tar), vec!["projects/2023/q4xx/report.txt".to_string()]);
+    }
+
+    #[test]
+    fn a_zero_block_ends_the_archive() {
+        let mut tar = Vec::new();
+        file(&mut tar, "first.txt", b"1");
+        end(&mut tar);
+        file(&mut tar, "after-the-end.txt", b"2");
+        assert_eq!(paths(&tar), vec!["first.txt".to_string()]);
+
+        // A stream cut short ends the walk the same way
+        let mut truncated = Vec::new();
+        file(&mut truncated, "only.txt", b"1");
+        assert_eq!(paths(&truncated), vec!["only.txt".to_string()]);
+    }
+
+    #[test]
+    fn visit_can_stop_the_walk() {
+        let mut tar = Vec::new();
+        file(&mut tar, "one.txt", b"1");
+        file(&mut tar, "two.txt", b"2");
+        end(&mut tar);
+        let mut seen = Vec::new();
+        walk_tar(tar.as_slice(), |header, _| {
+            seen.push(header.path.clone());
+            Ok(false)
+        })
+        .unwrap();
+        assert_eq!(seen, vec!["one.txt".to_string()]);
+    }
+}
@@ -0,0 +1,308 @@
+// Questions answered from the user's own documents. Candidate files come from the semantic index
+// and a keyword search; their text is split into line ranges that are embedded once per file
+// version and kept in `content_chunks`. The passages closest to the question go to the LLM as
+// numbered sources, and the answer cites them, so every claim points at a path and lines.
+
+use rusqlite::{params, Connection, Result as SqlResult};
+use serde::Serialize;
+use specta::Type;
+use std::collections::HashSet;
+use std::path::Path;
+use tauri::State;
+use tracing::warn;
+
+use crate::error::{AppError, ErrorCode};
+use crate::llm::{self, LlmConfig};
+use crate::semantic::{self, from_blob, normalize, to_blob};
+use crate::{db, nl, run_search, text_extract, AppState, SearchOptions};
+
+// Files whose passages are considered for one question
+const SEMANTIC_CANDIDATES: usize = 8;
+const KEYWORD_CANDIDATES: usize = 4;
+// Name search results looked at per question word
+const KEYWORD_RESULTS: usize = 20;
+// Text read from each file; passages past this are not searched
+const FILE_CHARS: usize = 100_000;
+const CHUNK_LINES: usize = 40;
+const CHUNK_CHARS: usize = 1_500;
+// Passages sent to the model
+const CONTEXT_PASSAGES: usize = 6;
+const SNIPPET_CHARS: usize = 240;
+const EMBED_BATCH: usize = 32;
+
+#[derive(Debug, Serialize, Clone, Type)]
+pub struct Citation {
+    // The number the answer cites it by, e.g. [2]
+    pub number: u32,
+    pub path: String,
+    // 1-based and inclusive; for PDFs, lines of the extracted text
+    pub start_line: u32,
+    pub end_line: u32,
+    pub snippet: String,
+    // Whether the answer refers to it
+    pub cited: bool,
+}
+
+#[derive(Debug, Serialize, Clone, Type)]
+pub struct FileAnswer {
+    pub question: String,
+    pub answer: String,
+    // Every passage the model saw, in the order it was numbered
+    pub sources: Vec<Citation>,
+}
+
+struct Chunk {
+    start_line: u32,
+    end_line: u32,
+    text: String,
+    vector: Vec<f32>,
+}
+
+pub fn create_table(conn: &Connection) -> SqlResult<()> {
+    // vector holds little-endian f32s, normalized to unit length
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS content_chunks (
+            path TEXT NOT NULL,
+            model TEXT NOT NULL,
+            modified_at INTEGER,
+            start_line INTEGER NOT NULL,
+            end_line INTEGER NOT NULL,
+            text TEXT NOT NULL,
+            vector BLOB NOT NULL,
+            PRIMARY KEY (path, model, start_line)
+        )",
+        [],
+    )?;
+    Ok(())
+}
+
+// Runs of up to CHUNK_LINES lines and CHUNK_CHARS characters, with their line numbers
+fn split_chunks(text: &str) -> Vec<(u32, u32, String)> {
+    let mut chunks = Vec::new();
+    let mut current = String::new();
+    let mut start = 1;
+    let mut lines_in_chunk = 0;
+    for (i, line) in text.lines().enumerate() {
+        let number = i as u32 + 1;
+        if lines_in_chunk == 0 {
+            start = number;
+        }
+        let line: String = line.chars().take(CHUNK_CHARS).collect();
+        current.push_str(&line);
+        current.push('\n');
+        lines_in_chunk += 1;
+        if lines_in_chunk == CHUNK_LINES || current.chars().count() >= CHUNK_CHARS {
+            chunks.push((start, number, std::mem::take(&mut current)));
+            lines_in_chunk = 0;
+        }
+    }
+    if lines_in_chunk > 0 {
+        chunks.push((start, start + lines_in_chunk as u32 - 1, current));
+    }
+    chunks.retain(|(_, _, text)| !text.trim().is_empty());
+    chunks
+}
+
+fn cached_chunks(conn: &Connection, path: &str, model: &str, modified_at: Option<i64>) -> SqlResult<Vec<Chunk>> {
+    let mut stmt = conn.prepare(
+        "SELECT start_line, end_line, text, vector FROM content_chunks
+         WHERE path = ?1 AND model = ?2 AND modified_at IS ?3 ORDER BY start_line",
+    )?;
+    let rows = stmt.query_map(params![path, model, modified_at], |row| {
+        Ok(Chunk {
+            start_line: row.get(0)?,
+            end_line: row.get(1)?,
+            text: row.get(2)?,
+            vector: from_blob(&row.get::<_, Vec<u8>>(3)?),
+        })
+    })?;
+    rows.collect()
+}
+
+// Embedded passages of one file, from the cache while the file is unchanged
+async fn file_chunks(path: &str, config: &LlmConfig, state: &AppState) -> Result<Vec<Chunk>, AppError> {
+    let modified = text_extract::modified_at(Path::new(path));
+    let cached = db::write(&state.db, |db| Ok(cached_chunks(db, path, &config.embedding_model, modified)?))?;
+    if !cached.is_empty() {
+        return Ok(cached);
+    }
+
+    let text = text_extract::read_text(Path::new(path), FILE_CHARS)?.text;
+    let pieces = split_chunks(&text);
+    let mut chunks = Vec::with_capacity(pieces.len());
+    for batch in pieces.chunks(EMBED_BATCH) {
+        let texts: Vec<String> = batch.iter().map(|(_, _, text)| text.clone()).collect();
+        let vectors = llm::embed(config, &texts, config.request_timeout()).await?;
+        for ((start_line, end_line, text), vector) in batch.iter().zip(vectors) {
+            chunks.push(Chunk {
+                start_line: *start_line,
+                end_line: *end_line,
+                text: text.clone(),
+                vector: normalize(vector),
+            });
+        }
+    }
+
+    db::write(&state.db, |db| {
+        db.execute(
+            "DELETE FROM content_chunks WHERE path = ?1 AND model = ?2",
+            params![path, config.embedding_model],
+        )?;
+        for chunk in &chunks {
+            db.execute(
+                "INSERT INTO content_chunks (path, model, modified_at, start_line, end_line, text, vector)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+                params![path, config.embedding_model, modified, chunk.start_line, chunk.end_line, chunk.text, to_blob(&chunk.vector)],
+            )?;
+        }
+        Ok(chunks)
+    })
+}
+
+// Files worth reading for the question: nearest by meaning, then by name
+fn candidate_files(question: &str, question_vector: &[f32], config: &LlmConfig, state: &AppState) -> Result<Vec<String>, String> {
+    let mut paths: Vec<String> = match semantic::nearest(question_vector, SEMANTIC_CANDIDATES, config, state) {
+        Ok(nearest) => nearest.into_iter().map(|(path, _)| path).collect(),
+        // Answers still work from name matches before the semantic index is built
+        Err(e) => {
+            warn!("ask_my_files: no semantic candidates: {}", e);
+            vec![]
+        }
+    };
+    let parsed = {
+        let groups = state.extension_groups.read().map_err(|e| e.to_string())?;
+        nl::fallback_parse(question, &groups)
+    };
+    let (filters, words): (Vec<&str>, Vec<&str>) = parsed.split_whitespace().partition(|word| word.contains(':'));
+    let options = SearchOptions {
+        natural_language: Some(false),
+        ..SearchOptions::default()
+    };
+
+    // A phrase rarely appears in a file name whole, so each word is searched on its own and files
+    // matching more of them come first
+    let mut hits: Vec<(String, usize)> = Vec::new();
+    for word in words.iter().filter(|word| word.chars().count() >= 3) {
+        let query = filters.iter().chain([word]).copied().collect::<Vec<_>>().join(" ");
+        for entry in run_search(&query, &options, state)?.results.into_iter().take(KEYWORD_RESULTS) {
+            match hits.iter_mut().find(|(path, _)| *path == entry.path) {
+                Some((_, count)) => *count += 1,
+                None => hits.push((entry.path, 1)),
+            }
+        }
+    }
+    hits.sort_by_key(|(_, count)| std::cmp::Reverse(*count));
+    for (path, _) in hits.into_iter().take(KEYWORD_CANDIDATES) {
+        if !paths.contains(&path) {
+            paths.push(path);
+        }
+    }
+    Ok(paths)
+}
+
+fn prompt(question: &str, sources: &[(&str, &Chunk)]) -> String {
+    let mut prompt = String::from(
+        "Answer the question using only the numbered sources below, which are excerpts from the user's files. \
+         After each statement, cite the sources it comes from in square brackets, like [2] or [1, 3]. \
+         If the sources don't answer the question, say so instead of guessing.\n\n",
+    );
+    for (i, (path, chunk)) in sources.iter().enumerate() {
+        prompt.push_str(&format!(
+            "[{}] {} (lines {}-{})\n{}\n\n",
+            i + 1,
+            path,
+            chunk.start_line,
+            chunk.end_line,
+            chunk.text.trim()
+        ));
+    }
+    prompt.push_str(&format!("Question: {}\nAnswer:", question));
+    prompt
+}
+
+// Source numbers the answer cites, from brackets like [2] and [1, 3]
+fn cited_numbers(answer: &str) -> HashSet<u32> {
+    let mut cited = HashSet::new();
+    for (open, _) in answer.match_indices('[') {
+        let Some(close) = answer[open..].find(']') else {
+            continue;
+        };
+        let inside = &answer[open + 1..open + close];
+        let numbers: Vec<u32> = inside.split(',').filter_map(|part| part.trim().parse().ok()).collect();
+        if numbers.len() == inside.split(',').count() {
+            cited.extend(numbers);
+        }
+    }
+    cited
+}
+
+// Answer a question from the indexed documents, citing the passages used
+#[tauri::command]
+#[specta::specta]
+pub async fn ask_my_files(question: String, state: State<'_, AppState>) -> Result<FileAnswer, AppError> {
+    answer_question(&question, &state).await
+}
+
+pub async fn answer_question(question: &str, state: &AppState) -> Result<FileAnswer, AppError> {
+    let question = question.trim().to_string();
+    if question.is_empty() {
+        return Err(AppError::new(ErrorCode::InvalidInput, "Ask a question"));
+    }
+    let config = state.llm.read().map_err(|e| e.to_string())?.clone();
+    if !config.enabled {
+        return Err(AppError::new(ErrorCode::LlmDisabled, "Answering questions needs the LLM backend; enable it in the LLM settings"));
+    }
+
+    let question_vector = semantic::embed_text(&config, &question).await?;
+    let candidates = candidate_files(&question, &question_vector, &config, state)?;
+    // Passages of files that have left the index are dead weight
+    db::write(&state.db, |db| {
+        db.execute("DELETE FROM content_chunks WHERE path NOT IN (SELECT path FROM files)", [])?;
+        Ok(())
+    })?;
+
+    let mut scored: Vec<(f32, String, Chunk)> = Vec::new();
+    for path in candidates {
+        // Binary files and PDFs without text have nothing to contribute
+        let chunks = match file_chunks(&path, &config, state).await {
+            Ok(chunks) => chunks,
+            Err(e) => {
+                warn!("ask_my_files: skipping {}: {}", path, e);
+                continue;
+            }
+        };
+        for chunk in chunks {
+            let similarity = chunk.vector.iter().zip(&question_vector).map(|(a, b)| a * b).sum();
+            scored.push((similarity, path.clone(), chunk));
+        }
+    }
+    if scored.is_empty() {
+        return Err("Found no documents with readable text for this question".into());
+    }
+    scored.sort_by(|a, b| b.0.total_cmp(&a.0));
+    scored.truncate(CONTEXT_PASSAGES);
+
+    let sources: Vec<(&str, &Chunk)> = scored.iter().map(|(_, path, chunk)| (path.as_str(), chunk)).collect();
+    let answer = llm::generate(&config, &prompt(&question, &sources), config.request_timeout()).await?;
+    let cited = cited_numbers(&answer);
+
+    Ok(FileAnswer {
+        question,
+        sources: sources
+            .iter()
+            .enumerate()
+            .map(|(i, (path, chunk))| {
+                let number = i as u32 + 1;
+                Citation {
+                    number,
+                    path: path.to_string(),
+                    start_line: chunk.start_line,
+                    end_line: chunk.end_line,
+                    snippet: chunk.text.split_whitespace().collect::<Vec<_>>().join(" ").chars().take(SNIPPET_CHARS).collect(),
+                    cited: cited.contains(&number),
+                }
+            })
+            .collect(),
+        answer,
+    })
+}
@@ -0,0 +1,242 @@
+// Automatic file categories. Every indexed file gets a primary category from its extension
+// group (image, code, ...), an optional secondary category from keywords in its name and
+// folders (finance, legal, ...), and auto tags such as the year it mentions. Rules are purely
+// path-based, so categorizing a file needs no disk access and a whole index takes seconds.
+
+use rusqlite::{params, Connection, OptionalExtension, Result as SqlResult};
+use serde::Serialize;
+use specta::Type;
+use std::collections::HashMap;
+use std::path::Path;
+use tauri::State;
+use tracing::{info, warn};
+
+use crate::error::AppError;
+use crate::file_types::{self, ExtensionGroups};
+use crate::{db, invalidate_search_cache, AppState};
+
+// Rows categorized per transaction by the background job
+const BATCH_SIZE: usize = 2000;
+
+// Primary category of files whose extension is in no group
+const OTHER: &str = "other";
+
+// Secondary categories and the words in a file name or folder that suggest them, most specific first
+const KEYWORD_RULES: &[(&str, &[&str])] = &[
+    ("finance", &["invoice", "receipt", "tax", "taxes", "bank", "statement", "payslip", "salary", "budget", "expense", "expenses"]),
+    ("legal", &["contract", "agreement", "nda", "lease", "license", "terms"]),
+    ("career", &["resume", "cv", "offer", "interview"]),
+    ("travel", &["ticket", "boarding", "itinerary", "booking", "passport", "visa"]),
+    ("screenshot", &["screenshot", "capture"]),
+    ("backup", &["backup", "bak", "old", "archive"]),
+    ("build", &["target", "dist", "build", "node_modules", "bin", "obj"]),
+    ("test", &["test", "tests", "spec", "specs", "fixture", "fixtures"]),
+    ("download", &["downloads", "download"]),
+];
+
+#[derive(Debug, Serialize, Clone, PartialEq, Type)]
+pub struct FileCategory {
+    pub primary: String,
+    pub secondary: Option<String>,
+    pub auto_tags: Vec<String>,
+    // 0..1: how much of the category comes from evidence rather than fallbacks
+    pub confidence: f64,
+}
+
+pub fn create_tables(conn: &Connection) -> SqlResult<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS file_categories (
+            path TEXT PRIMARY KEY,
+            primary_category TEXT NOT NULL,
+            secondary_category TEXT,
+            confidence REAL NOT NULL,
+            categorized_at INTEGER NOT NULL
+        )",
+        [],
+    )?;
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS file_auto_tags (
+            path TEXT NOT NULL,
+            tag TEXT NOT NULL,
+            PRIMARY KEY (path, tag)
+        )",
+        [],
+    )?;
+    conn.execute("CREATE INDEX IF NOT EXISTS idx_file_categories_primary ON file_categories(primary_category)", [])?;
+    conn.execute("CREATE INDEX IF NOT EXISTS idx_file_auto_tags_tag ON file_auto_tags(tag)", [])?;
+    Ok(())
+}
+
+// Extension -> group, built once per run instead of merging the groups for every file
+struct Categorizer {
+    groups: HashMap<String, String>,
+}
+
+impl Categorizer {
+    fn new(groups: &ExtensionGroups) -> Self {
+        let mut lookup = HashMap::new();
+        for (group, extensions) in groups.merged() {
+            for extension in extensions {
+                lookup.entry(extension.to_lowercase()).or_insert_with(|| group.clone());
+            }
+        }
+        Self { groups: lookup }
+    }
+
+    fn categorize(&self, path: &str) -> FileCategory {
+        let path = Path::new(path);
+        let name = path.file_name().map(|n| n.to_string_lossy().to_lowercase()).unwrap_or_default();
+        let group = path
+            .extension()
+            .and_then(|ext| self.groups.get(&ext.to_string_lossy().to_lowercase()))
+            .cloned();
+
+        // Words of the file name first, then of the nearest folders, so the name wins ties
+        let mut words: Vec<String> = words_of(path.file_stem().map(|s| s.to_string_lossy()).unwrap_or_default().as_ref());
+        let name_word_count = words.len();
+        for folder in path.parent().into_iter().flat_map(|p| p.iter().rev().take(3)) {
+            words.extend(words_of(&folder.to_string_lossy()));
+        }
+
+        let mut secondary = None;
+        let mut secondary_in_name = false;
+        let mut auto_tags = Vec::new();
+        for (category, keywords) in KEYWORD_RULES {
+            if let Some(position) = words.iter().position(|word| keywords.contains(&word.as_str())) {
+                auto_tags.push(category.to_string());
+                if secondary.is_none() {
+                    secondary = Some(category.to_string());
+                    secondary_in_name = position < name_word_count;
+                }
+            }
+        }
+        if let Some(year) = words.iter().find(|word| is_year(word)) {
+            auto_tags.push(year.clone());
+        }
+        if name.starts_with('.') {
+            auto_tags.push("hidden".to_string());
+        }
+
+        let mut confidence: f64 = if group.is_some() { 0.6 } else { 0.2 };
+        if secondary.is_some() {
+            confidence += if secondary_in_name { 0.3 } else { 0.15 };
+        }
+        FileCategory {
+            primary: group.unwrap_or_else(|| OTHER.to_string()),
+            secondary,
+            auto_tags,
+            confidence: confidence.min(1.0),
+        }
+    }
+}
+
+fn words_of(text: &str) -> Vec<String> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(|word| word.to_lowercase())
+        .collect()
+}
+
+fn is_year(word: &str) -> bool {
+    word.len() == 4 && word.parse::<u32>().is_ok_and(|year| (1970..=2100).contains(&year))
+}
+
+fn store(conn: &Connection, path: &str, category: &FileCategory, now: i64) -> SqlResult<()> {
+    conn.execute(
+        "INSERT OR REPLACE INTO file_categories (path, primary_category, secondary_category, confidence, categorized_at)
+         VALUES (?1, ?2, ?3, ?4, ?5)",
+        params![path, category.primary, category.secondary, category.confidence, now],
+    )?;
+    conn.execute("DELETE FROM file_auto_tags WHERE path = ?1", [path])?;
+    for tag in &category.auto_tags {
+        conn.execute("INSERT OR IGNORE INTO file_auto_tags (path, tag) VALUES (?1, ?2)", params![path, tag])?;
+    }
+    Ok(())
+}
+
+// Categorize indexed files that have no category yet and drop categories of files that left
+// the index. Returns the number of files categorized.
+pub fn categorize_pending(conn: &mut Connection) -> SqlResult<usize> {
+    let categorizer = Categorizer::new(&file_types::load(conn));
+    let now = chrono::Utc::now().timestamp();
+    let mut categorized = 0;
+
+    loop {
+        let pending: Vec<String> = {
+            let mut stmt = conn.prepare_cached(
+                "SELECT f.path FROM files f LEFT JOIN file_categories c ON c.path = f.path
+                 WHERE c.path IS NULL LIMIT ?1",
+            )?;
+            let rows = stmt.query_map([BATCH_SIZE as i64], |row| row.get(0))?.filter_map(|r| r.ok()).collect();
+            rows
+        };
+        if pending.is_empty() {
+            break;
+        }
+        let tx = conn.transaction()?;
+        for path in &pending {
+            store(&tx, path, &categorizer.categorize(path), now)?;
+        }
+        tx.commit()?;
+        categorized += pending.len();
+    }
+
+    conn.execute("DELETE FROM file_categories WHERE path NOT IN (SELECT path FROM files)", [])?;
+    conn.execute("DELETE FROM file_auto_tags WHERE path NOT IN (SELECT path FROM file_categories)", [])?;
+    Ok(categorized)
+}
+
+// Used at startup so categorizing an existing index doesn't block the window
+pub fn categorize_in_background(db_path: &Path) {
+    let db_path = db_path.to_path_buf();
+    std::thread::spawn(move || {
+        let result = db::open(&db_path).and_then(|mut conn| categorize_pending(&mut conn));
+        match result {
+            Ok(0) => {}
+            Ok(count) => info!("Categorized {} files", count),
+            Err(e) => warn!("Failed to categorize files: {}", e),
+        }
+    });
+}
+
+fn stored_category(conn: &Connection, path: &str) -> SqlResult<Option<FileCategory>> {
+    let row = conn
+        .query_row(
+            "SELECT primary_category, secondary_category, confidence FROM file_categories WHERE path = ?1",
+            [path],
+            |row| Ok((row.get::<_, String>(0)?, row.get::<_, Option<String>>(1)?, row.get::<_, f64>(2)?)),
+        )
+        .optional()?;
+    let Some((primary, secondary, confidence)) = row else {
+        return Ok(None);
+    };
+    let mut stmt = conn.prepare("SELECT tag FROM file_auto_tags WHERE path = ?1 ORDER BY rowid")?;
+    let auto_tags = stmt.query_map([path], |row| row.get(0))?.filter_map(|r| r.ok()).collect();
+    Ok(Some(FileCategory {
+        primary,
+        secondary,
+        auto_tags,
+        confidence,
+    }))
+}
+
+// (Re)categorize one path now, e.g. after the extension groups changed, and store the result
+#[tauri::command]
+#[specta::specta]
+pub async fn categorize_path(path: String, state: State<'_, AppState>) -> Result<FileCategory, AppError> {
+    let groups = state.extension_groups.read().map_err(|e| e.to_string())?;
+    let categorizer = Categorizer::new(&groups);
+    drop(groups);
+    let category = categorizer.categorize(&path);
+    db::write(&state.db, |db| Ok(store(db, &path, &category, chrono::Utc::now().timestamp())?))?;
+    // Cached `tag:` and `category:` results may include or miss this path
+    invalidate_search_cache(&state);
+    Ok(category)
+}
+
+// Stored category of a path; None until the background job (or categorize_path) has seen it
+#[tauri::command]
+#[specta::specta]
+pub async fn get_category(path: String, state: State<'_, AppState>) -> Result<Option<FileCategory>, AppError> {
+    db::write(&state.db, |db| stored_category(db, &path).map_err(AppError::from))
+}
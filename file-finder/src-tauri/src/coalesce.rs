@@ -0,0 +1,46 @@
+// Keystroke coalescing for search_files. Frontends send one search per keystroke, so each search
+// waits `debounce_ms` (see planner::SearchBudget) before it runs. If a newer search from the same
+// window arrived meanwhile, it is answered as superseded without reaching SQL, the LLM rewrite or
+// the matchers, and only the latest one runs. Results of a search that was replaced while it ran
+// are dropped the same way. The headless server coalesces its own request queue (see headless.rs).
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+
+// The flag of each window's latest search, set once a newer one arrives
+#[derive(Default)]
+pub struct SearchQueue {
+    latest: Mutex<HashMap<String, Arc<AtomicBool>>>,
+}
+
+pub struct Ticket {
+    superseded: Arc<AtomicBool>,
+}
+
+impl SearchQueue {
+    // Register a search from `window`, superseding the one before it
+    pub fn enter(&self, window: &str) -> Result<Ticket, String> {
+        let superseded = Arc::new(AtomicBool::new(false));
+        let mut latest = self.latest.lock().map_err(|e| e.to_string())?;
+        if let Some(previous) = latest.insert(window.to_string(), superseded.clone()) {
+            previous.store(true, Ordering::Relaxed);
+        }
+        Ok(Ticket { superseded })
+    }
+
+    // Supersede the latest entry of `window` without registering a new one
+    pub fn cancel(&self, window: &str) {
+        if let Ok(mut latest) = self.latest.lock() {
+            if let Some(previous) = latest.remove(window) {
+                previous.store(true, Ordering::Relaxed);
+            }
+        }
+    }
+}
+
+impl Ticket {
+    pub fn superseded(&self) -> bool {
+        self.superseded.load(Ordering::Relaxed)
+    }
+}
@@ -0,0 +1,98 @@
+// Versioned command surface. Frontends and scripts call get_api_version once at startup to
+// learn which version the backend speaks and which commands are deprecated. Commands whose
+// return type changed keep their old shape under a `_v1` name, so an older frontend only has to
+// rename the call, not handle the new structs.
+//
+// Version history:
+//   1 - search_files returned FileEntry[], debug_search_scores returned [name, score, path] tuples
+//   2 - search_files returns SearchResponse, debug_search_scores returns SearchScore objects
+//   3 - commands fail with AppError objects ({code, message, recoverable}) instead of strings;
+//       the `_v1` shims still fail with plain strings, as version 1 expects
+
+use serde::Serialize;
+use specta::Type;
+use std::collections::HashSet;
+use std::sync::Mutex;
+use tauri::State;
+use tracing::warn;
+
+use crate::error::AppError;
+use crate::{db, AppState, FileEntry, SearchOptions};
+
+pub const API_VERSION: u32 = 3;
+// Oldest version whose commands are all still served, directly or through a shim
+pub const MIN_SUPPORTED_VERSION: u32 = 1;
+
+#[derive(Debug, Serialize, Clone, Type)]
+pub struct DeprecatedCommand {
+    pub command: String,
+    pub replacement: String,
+    // API version that replaced it
+    pub since: u32,
+}
+
+#[derive(Debug, Serialize, Clone, Type)]
+pub struct ApiVersionInfo {
+    pub version: u32,
+    pub min_supported: u32,
+    // Whether the version the client was written against can be served
+    pub compatible: bool,
+    pub deprecated: Vec<DeprecatedCommand>,
+}
+
+fn deprecated_commands() -> Vec<DeprecatedCommand> {
+    [("search_files_v1", "search_files"), ("debug_search_scores_v1", "debug_search_scores")]
+        .into_iter()
+        .map(|(command, replacement)| DeprecatedCommand {
+            command: command.to_string(),
+            replacement: replacement.to_string(),
+            since: 2,
+        })
+        .collect()
+}
+
+pub fn version_info(client_version: Option<u32>) -> ApiVersionInfo {
+    ApiVersionInfo {
+        version: API_VERSION,
+        min_supported: MIN_SUPPORTED_VERSION,
+        compatible: client_version.is_none_or(|v| (MIN_SUPPORTED_VERSION..=API_VERSION).contains(&v)),
+        deprecated: deprecated_commands(),
+    }
+}
+
+// Log each deprecated command the first time it is used, not on every keystroke
+fn warn_deprecated(command: &str) {
+    static WARNED: Mutex<Option<HashSet<String>>> = Mutex::new(None);
+    let Ok(mut warned) = WARNED.lock() else {
+        return;
+    };
+    if warned.get_or_insert_with(HashSet::new).insert(command.to_string()) {
+        if let Some(entry) = deprecated_commands().iter().find(|entry| entry.command == command) {
+            warn!("{} is deprecated since API version {}; use {}", command, entry.since, entry.replacement);
+        }
+    }
+}
+
+// Handshake: the API version this backend speaks, and whether `client_version` is still served
+#[tauri::command]
+#[specta::specta]
+pub async fn get_api_version(client_version: Option<u32>) -> Result<ApiVersionInfo, AppError> {
+    Ok(version_info(client_version))
+}
+
+// Version 1 search_files: the results without the degraded flag
+#[tauri::command]
+#[specta::specta]
+pub async fn search_files_v1(query: String, options: Option<SearchOptions>, state: State<'_, AppState>) -> Result<Vec<FileEntry>, String> {
+    warn_deprecated("search_files_v1");
+    Ok(db::blocking(|| crate::run_search(&query, &options.unwrap_or_default(), &state))?.results)
+}
+
+// Version 1 debug_search_scores: (name, score, path) tuples
+#[tauri::command]
+#[specta::specta]
+pub async fn debug_search_scores_v1(query: String, state: State<'_, AppState>) -> Result<Vec<(String, i64, String)>, String> {
+    warn_deprecated("debug_search_scores_v1");
+    let scores = crate::debug_search_scores(state, query).await.map_err(|e| e.message)?;
+    Ok(scores.into_iter().map(|s| (s.name, s.score, s.path)).collect())
+}
@@ -0,0 +1,157 @@
+// Optional `config.toml` next to the database, for setups kept in version control or written
+// by scripts. It is read at startup and again whenever it changes. Its values overwrite the
+// same settings in the database, so the app and its commands keep working from one place.
+// Keys the file leaves out keep their current value. A file that doesn't parse is ignored and
+// reported, and the last good one stays in effect.
+//
+//     roots = ["~/code", "D:\\Projects"]
+//     hotkey = "CommandOrControl+Alt+Space"
+//
+//     [exclusions]
+//     dir_names = ["node_modules", "target", ".venv"]
+//
+//     [llm]
+//     enabled = true
+//     endpoint = "http://localhost:11434"
+//     model = "llama3.2"
+
+use serde::Deserialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+use tauri::{AppHandle, Manager};
+use tracing::{info, warn};
+
+use crate::{exclusions, llm, overlay, settings, spawn_folder_indexing, AppState};
+
+pub const FILE_NAME: &str = "config.toml";
+
+// How often the file is checked for changes
+const POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+#[derive(Debug, Deserialize, Default, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+struct ConfigFile {
+    // Folders to index; ones not indexed yet are indexed when the file is read
+    roots: Vec<String>,
+    // Shortcut for the quick-search overlay
+    hotkey: Option<String>,
+    exclusions: ExclusionsSection,
+    llm: LlmSection,
+}
+
+#[derive(Debug, Deserialize, Default, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+struct ExclusionsSection {
+    dir_names: Option<Vec<String>>,
+    paths: Option<Vec<String>>,
+}
+
+#[derive(Debug, Deserialize, Default, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+struct LlmSection {
+    enabled: Option<bool>,
+    endpoint: Option<String>,
+    model: Option<String>,
+    embedding_model: Option<String>,
+    api_key: Option<String>,
+}
+
+pub fn path(db_path: &Path) -> PathBuf {
+    db_path.with_file_name(FILE_NAME)
+}
+
+fn expand_home(folder: &str) -> PathBuf {
+    match (folder.strip_prefix('~'), dirs::home_dir()) {
+        (Some(rest), Some(home)) => home.join(rest.trim_start_matches(['/', '\\'])),
+        _ => PathBuf::from(folder),
+    }
+}
+
+fn read(path: &Path) -> Result<ConfigFile, String> {
+    let text = fs::read_to_string(path).map_err(|e| e.to_string())?;
+    toml::from_str(&text).map_err(|e| e.to_string())
+}
+
+// Write the file's values into the settings and apply them to the running app
+fn apply(app: &AppHandle, file: &ConfigFile) -> Result<(), String> {
+    let state = app.state::<AppState>();
+    let new_roots: Vec<PathBuf> = {
+        let db = state.db.lock().map_err(|e| e.to_string())?;
+
+        let mut rules = exclusions::load(&db);
+        if let Some(dir_names) = &file.exclusions.dir_names {
+            rules.dir_names = dir_names.clone();
+        }
+        if let Some(paths) = &file.exclusions.paths {
+            rules.paths = paths.clone();
+        }
+        settings::save(&db, exclusions::SETTINGS_KEY, &rules).map_err(|e| e.message)?;
+
+        let mut config = llm::load(&db);
+        let section = &file.llm;
+        if let Some(enabled) = section.enabled {
+            config.enabled = enabled;
+        }
+        if let Some(endpoint) = &section.endpoint {
+            config.endpoint = endpoint.clone();
+        }
+        if let Some(model) = &section.model {
+            config.model = model.clone();
+        }
+        if let Some(embedding_model) = &section.embedding_model {
+            config.embedding_model = embedding_model.clone();
+        }
+        if let Some(api_key) = &section.api_key {
+            config.api_key = Some(api_key.clone()).filter(|key| !key.is_empty());
+        }
+        settings::save(&db, llm::SETTINGS_KEY, &config.sanitized()).map_err(|e| e.message)?;
+        state.reload_settings(&db)?;
+
+        let mut stmt = db.prepare("SELECT path FROM indexed_directories").map_err(|e| e.to_string())?;
+        let indexed: Vec<PathBuf> = stmt
+            .query_map([], |row| row.get::<_, String>(0))
+            .map_err(|e| e.to_string())?
+            .filter_map(|r| r.ok())
+            .map(PathBuf::from)
+            .collect();
+        file.roots
+            .iter()
+            .map(|root| expand_home(root.trim()))
+            .filter(|root| !indexed.contains(root))
+            .collect()
+    };
+
+    // The shortcut is saved and registered by the overlay, which takes the lock itself
+    if let Some(hotkey) = &file.hotkey {
+        overlay::set_shortcut(app, hotkey)?;
+    }
+    for root in new_roots {
+        if root.is_dir() {
+            info!("Indexing {} from {}", root.display(), FILE_NAME);
+            spawn_folder_indexing(app.clone(), root, None, None, None);
+        } else {
+            warn!("Skipping root {} from {}: not a folder", root.display(), FILE_NAME);
+        }
+    }
+    Ok(())
+}
+
+// Apply the file at startup, then again each time it is saved
+pub fn watch(app: AppHandle) {
+    let path = path(&app.state::<AppState>().db_path);
+    std::thread::spawn(move || {
+        let mut last_modified: Option<SystemTime> = None;
+        loop {
+            let modified = fs::metadata(&path).and_then(|m| m.modified()).ok();
+            if modified.is_some() && modified != last_modified {
+                last_modified = modified;
+                match read(&path).and_then(|file| apply(&app, &file)) {
+                    Ok(()) => info!("Applied {}", path.display()),
+                    Err(e) => warn!("Ignoring {}: {}", path.display(), e),
+                }
+            }
+            std::thread::sleep(POLL_INTERVAL);
+        }
+    });
+}
@@ -0,0 +1,160 @@
+// Content search: `content:todo billing` finds files whose text contains "todo", merged with the
+// files whose name matches "billing". There is no full-text index, so text and code files are
+// read newest first until the search budget runs out; each hit carries the first matching line.
+
+use rusqlite::params_from_iter;
+use rusqlite::types::Value;
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use std::path::Path;
+
+use crate::file_types::TypeFilter;
+use crate::search_sql::SqlFilter;
+use crate::{planner, text_extract, AppState, FileEntry, SearchOptions};
+
+pub const PREFIX: &str = "content:";
+
+// Files read per search at most; the time budget usually stops the scan sooner
+const MAX_SCANNED: usize = 5_000;
+const MAX_HITS: usize = 100;
+// Text read from each file
+const FILE_CHARS: usize = 200_000;
+const SNIPPET_CHARS: usize = 160;
+
+// Extensions read besides the code group
+const TEXT_EXTENSIONS: &[&str] = &["txt", "md", "pdf", "csv", "log", "rst", "tex", "ini", "cfg", "conf", "org"];
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Type)]
+#[serde(rename_all = "snake_case")]
+pub enum MatchSource {
+    Name,
+    Content,
+    Both,
+    // Found through the file's note rather than its name or text
+    Note,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Type)]
+pub struct ContentSnippet {
+    // 1-based; for PDFs, a line of the extracted text, for note matches a line of the note
+    pub line: u32,
+    pub text: String,
+}
+
+// Split `content:` tokens out of a query. Every term must appear in a file's text.
+pub fn extract_terms(query: &str) -> (String, Vec<String>) {
+    let mut terms: Vec<String> = Vec::new();
+    let mut rest: Vec<&str> = Vec::new();
+
+    for token in query.split_whitespace() {
+        let term = token
+            .get(..PREFIX.len())
+            .filter(|head| head.eq_ignore_ascii_case(PREFIX))
+            .map(|_| &token[PREFIX.len()..])
+            .filter(|term| !term.is_empty());
+        match term {
+            Some(term) => terms.push(term.to_string()),
+            None => rest.push(token),
+        }
+    }
+
+    if terms.is_empty() {
+        return (query.to_string(), terms);
+    }
+    (rest.join(" "), terms)
+}
+
+// First line containing the first term, if the text contains every term
+fn find_in_text(text: &str, terms: &[String], case_sensitive: bool) -> Option<ContentSnippet> {
+    let fold = |s: &str| if case_sensitive { s.to_string() } else { s.to_lowercase() };
+    let haystack = fold(text);
+    if !terms.iter().all(|term| haystack.contains(&fold(term))) {
+        return None;
+    }
+    let first = fold(&terms[0]);
+    let (index, line) = text.lines().enumerate().find(|(_, line)| fold(line).contains(&first))?;
+    Some(ContentSnippet {
+        line: index as u32 + 1,
+        text: line.trim().chars().take(SNIPPET_CHARS).collect(),
+    })
+}
+
+// Files whose text contains all `terms`, newest first. `filter` holds the query's other
+// filters, as ranked_search builds them.
+pub fn find(
+    terms: &[String],
+    filter: &SqlFilter,
+    search_opts: &SearchOptions,
+    state: &AppState,
+    planner: &planner::SearchPlanner,
+) -> Result<Vec<FileEntry>, String> {
+    let readable = {
+        let groups = state.extension_groups.read().map_err(|e| e.to_string())?;
+        let mut extensions = groups.merged().remove("code").unwrap_or_default();
+        extensions.extend(TEXT_EXTENSIONS.iter().map(|extension| extension.to_string()));
+        TypeFilter { extensions }
+    };
+
+    let candidates: Vec<(String, String, Option<i64>)> = {
+        let db = state.readers.get()?;
+        let mut stmt = db
+            .prepare_cached(&format!(
+                "SELECT path, name, modified_at FROM files WHERE {}{} ORDER BY modified_at DESC LIMIT ?",
+                readable.sql_clause(),
+                filter.sql
+            ))
+            .map_err(|e| e.to_string())?;
+        let rows = stmt
+            .query_map(params_from_iter(filter.bind(vec![], vec![Value::Integer(MAX_SCANNED as i64)])), |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))
+            .map_err(|e| e.to_string())?;
+        rows.filter_map(|r| r.ok()).collect()
+    };
+
+    let mut hits = Vec::new();
+    for (path, name, modified_at) in candidates {
+        if planner.cancelled() || planner.out_of_time() || hits.len() == MAX_HITS {
+            break;
+        }
+        // Binary files with a text extension and PDFs without text are skipped
+        let Ok(extracted) = text_extract::read_text(Path::new(&path), FILE_CHARS) else {
+            continue;
+        };
+        if let Some(snippet) = find_in_text(&extracted.text, terms, search_opts.case_sensitive) {
+            hits.push(FileEntry {
+                path,
+                name,
+                last_accessed: None,
+                access_count: 0,
+                modified_at,
+                age_bucket: None,
+                in_backup: false,
+                match_source: Some(MatchSource::Content),
+                content_snippet: Some(snippet),
+            });
+        }
+    }
+    Ok(hits)
+}
+
+// Name results keep their order, with the ones whose text also matched first; files found only
+// by their text follow
+pub fn merge(name_results: Vec<FileEntry>, content_hits: Vec<FileEntry>) -> Vec<FileEntry> {
+    let mut content_hits = content_hits;
+    let mut both = Vec::new();
+    let mut name_only = Vec::new();
+    for mut entry in name_results {
+        match content_hits.iter().position(|hit| hit.path == entry.path) {
+            Some(position) => {
+                let hit = content_hits.remove(position);
+                entry.match_source = Some(MatchSource::Both);
+                entry.content_snippet = hit.content_snippet;
+                both.push(entry);
+            }
+            None => {
+                entry.match_source = Some(MatchSource::Name);
+                name_only.push(entry);
+            }
+        }
+    }
+    both.into_iter().chain(name_only).chain(content_hits).collect()
+}
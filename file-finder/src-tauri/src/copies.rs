@@ -0,0 +1,188 @@
+// "Find this file elsewhere": locate byte-identical copies of one file by content hash.
+// Hashes are cached in `file_hashes` keyed by path and invalidated by size/mtime changes.
+
+use rayon::prelude::*;
+use rusqlite::{params, Connection, OptionalExtension, Result as SqlResult};
+use sha2::{Digest, Sha256};
+use std::fs::{self, File};
+use std::io::{self, BufReader};
+use std::path::Path;
+use std::time::UNIX_EPOCH;
+use tauri::State;
+use tracing::{debug, warn};
+
+use crate::error::{AppError, ErrorCode};
+use crate::{db, escape_like, AppState, FileEntry, LIKE_ESCAPE};
+
+// Upper bound on same-extension candidates we stat per lookup
+const MAX_CANDIDATES: usize = 20000;
+
+pub fn create_table(conn: &Connection) -> SqlResult<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS file_hashes (
+            path TEXT PRIMARY KEY,
+            size INTEGER NOT NULL,
+            modified_at INTEGER,
+            hash TEXT NOT NULL
+        )",
+        [],
+    )?;
+    conn.execute("CREATE INDEX IF NOT EXISTS idx_file_hashes_hash ON file_hashes(hash)", [])?;
+    Ok(())
+}
+
+pub fn hash_file(path: &Path) -> io::Result<String> {
+    let mut reader = BufReader::with_capacity(64 * 1024, File::open(path)?);
+    let mut hasher = Sha256::new();
+    io::copy(&mut reader, &mut hasher)?;
+    Ok(hasher
+        .finalize()
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect())
+}
+
+fn modified_secs(metadata: &fs::Metadata) -> Option<i64> {
+    metadata
+        .modified()
+        .ok()
+        .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+        .map(|duration| duration.as_secs() as i64)
+}
+
+// A cached hash is only trusted while the file's size and mtime are unchanged
+fn cached_hash(conn: &Connection, path: &str, size: u64, modified_at: Option<i64>) -> Option<String> {
+    conn.query_row(
+        "SELECT hash FROM file_hashes WHERE path = ?1 AND size = ?2 AND modified_at IS ?3",
+        params![path, size as i64, modified_at],
+        |row| row.get(0),
+    )
+    .optional()
+    .unwrap_or(None)
+}
+
+fn store_hash(conn: &Connection, path: &str, size: u64, modified_at: Option<i64>, hash: &str) -> SqlResult<()> {
+    conn.execute(
+        "INSERT OR REPLACE INTO file_hashes (path, size, modified_at, hash) VALUES (?1, ?2, ?3, ?4)",
+        params![path, size as i64, modified_at, hash],
+    )?;
+    Ok(())
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn find_copies(path: String, state: State<'_, AppState>) -> Result<Vec<FileEntry>, AppError> {
+    let target = Path::new(&path);
+    let metadata = fs::metadata(target)?;
+    if !metadata.is_file() {
+        return Err(AppError::new(ErrorCode::InvalidInput, format!("Not a file: {}", path)));
+    }
+    let size = metadata.len();
+    if size == 0 {
+        // Every empty file is "identical", which is not useful
+        return Ok(vec![]);
+    }
+    let target_modified = modified_secs(&metadata);
+
+    let cached_target_hash = db::write(&state.db, |db| Ok(cached_hash(db, &path, size, target_modified)))?;
+    let target_hash = match cached_target_hash {
+        Some(hash) => hash,
+        None => hash_file(target)?,
+    };
+
+    // Copies usually keep their extension; previously hashed files with the same hash are included too
+    let candidates: Vec<(String, String, Option<i64>)> = db::write(&state.db, |db| {
+        store_hash(db, &path, size, target_modified, &target_hash)?;
+
+        let name_filter = if target.extension().is_some() {
+            format!("name LIKE '%.' || ?1 {}", LIKE_ESCAPE)
+        } else {
+            "(name NOT LIKE '%.%' AND ?1 = '')".to_string()
+        };
+        let extension = target
+            .extension()
+            .map(|ext| escape_like(&ext.to_string_lossy()))
+            .unwrap_or_default();
+
+        let sql = format!(
+            "SELECT path, name, modified_at FROM files WHERE {} AND path != ?2
+             UNION
+             SELECT f.path, f.name, f.modified_at FROM files f JOIN file_hashes h ON h.path = f.path
+             WHERE h.hash = ?3 AND f.path != ?2
+             LIMIT ?4",
+            name_filter
+        );
+        let mut stmt = db.prepare(&sql)?;
+        let rows = stmt
+            .query_map(params![extension, path, target_hash, MAX_CANDIDATES as i64], |row| {
+                Ok((row.get(0)?, row.get(1)?, row.get(2)?))
+            })?
+            .filter_map(|r| r.ok())
+            .collect();
+        Ok(rows)
+    })?; // Release the lock before touching the filesystem
+
+    // Only files of exactly the same size can be identical
+    let same_size: Vec<(String, String, Option<i64>)> = candidates
+        .into_par_iter()
+        .filter_map(|(candidate, name, _)| {
+            let meta = fs::metadata(&candidate).ok().filter(|m| m.is_file() && m.len() == size)?;
+            Some((candidate, name, modified_secs(&meta)))
+        })
+        .collect();
+    debug!("find_copies: {} same-size candidates for {}", same_size.len(), path);
+
+    let (cached, uncached): (Vec<_>, Vec<_>) = db::write(&state.db, |db| {
+        Ok(same_size
+            .into_iter()
+            .map(|(candidate, name, modified_at)| {
+                let hash = cached_hash(db, &candidate, size, modified_at);
+                (candidate, name, modified_at, hash)
+            })
+            .partition(|(_, _, _, hash)| hash.is_some()))
+    })?;
+
+    let fresh: Vec<(String, String, Option<i64>, Option<String>)> = uncached
+        .into_par_iter()
+        .map(|(candidate, name, modified_at, _)| {
+            let hash = hash_file(Path::new(&candidate)).ok();
+            (candidate, name, modified_at, hash)
+        })
+        .collect();
+
+    db::write(&state.db, |db| {
+        for (candidate, _, modified_at, hash) in &fresh {
+            if let Some(hash) = hash {
+                if let Err(e) = store_hash(db, candidate, size, *modified_at, hash) {
+                    warn!("Failed to cache hash for {}: {}", candidate, e);
+                }
+            }
+        }
+        Ok(())
+    })?;
+
+    let mut copies: Vec<FileEntry> = cached
+        .into_iter()
+        .chain(fresh)
+        .filter(|(_, _, _, hash)| hash.as_deref() == Some(target_hash.as_str()))
+        .map(|(path, name, modified_at, _)| FileEntry {
+            path,
+            name,
+            last_accessed: None,
+            access_count: 0,
+            modified_at,
+            age_bucket: None,
+            in_backup: false,
+            match_source: None,
+            content_snippet: None,
+        })
+        .collect();
+
+    copies.sort_by_key(|entry| std::cmp::Reverse(entry.modified_at));
+    db::write(&state.db, |db| {
+        crate::mirrors::mark_backup_entries(db, &mut copies);
+        Ok(())
+    })?;
+    crate::assign_age_buckets(&mut copies);
+    Ok(copies)
+}
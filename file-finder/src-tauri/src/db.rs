@@ -0,0 +1,106 @@
+// Database connections. The database runs in WAL mode, so reads don't wait for writes: the main
+// connection in `AppState.db` takes the writes (favorites, tags, settings, open history), each
+// indexing run and background job opens its own connection, and searches read through a small
+// pool of read connections. A search therefore never waits for a favorite to be saved, and an
+// indexing run doesn't hold up either. Commands run their database work through `blocking` (or
+// `write` for the main connection), which moves the other tasks off the runtime thread while
+// the work, or the wait for the lock, lasts.
+
+use rusqlite::{Connection, Result as SqlResult};
+use std::ops::Deref;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::Duration;
+
+use crate::error::AppError;
+use crate::{locale, search_sql};
+
+// How long a connection waits for another one's write to finish before failing
+const BUSY_TIMEOUT: Duration = Duration::from_secs(5);
+
+// Read connections kept open between searches; more are opened when searches overlap
+const MAX_IDLE_READERS: usize = 4;
+
+// Compiled statements each connection keeps for `prepare_cached`, enough for the prebuilt
+// search queries and the filtered variants recently used
+pub const STATEMENT_CACHE_CAPACITY: usize = 64;
+
+// Open a connection with the settings every connection needs
+pub fn open(path: &Path) -> SqlResult<Connection> {
+    let conn = Connection::open(path)?;
+    conn.busy_timeout(BUSY_TIMEOUT)?;
+    conn.set_prepared_statement_cache_capacity(STATEMENT_CACHE_CAPACITY);
+    locale::register(&conn)?;
+    Ok(conn)
+}
+
+// Switch the database to WAL, which it keeps from then on
+pub fn enable_wal(conn: &Connection) -> SqlResult<()> {
+    conn.pragma_update(None, "journal_mode", "WAL")?;
+    // Safe with WAL: a crash can lose the last commits but not corrupt the database
+    conn.pragma_update(None, "synchronous", "NORMAL")?;
+    Ok(())
+}
+
+pub struct ReadPool {
+    db_path: PathBuf,
+    idle: Mutex<Vec<Connection>>,
+}
+
+// A read connection, returned to the pool when dropped
+pub struct Reader<'a> {
+    conn: Option<Connection>,
+    pool: &'a ReadPool,
+}
+
+impl ReadPool {
+    pub fn new(db_path: PathBuf) -> Self {
+        Self { db_path, idle: Mutex::new(Vec::new()) }
+    }
+
+    pub fn get(&self) -> Result<Reader<'_>, String> {
+        let idle = self.idle.lock().map_err(|e| e.to_string())?.pop();
+        let conn = match idle {
+            Some(conn) => conn,
+            None => {
+                let conn = open(&self.db_path).map_err(|e| e.to_string())?;
+                search_sql::warm(&conn);
+                conn
+            }
+        };
+        Ok(Reader { conn: Some(conn), pool: self })
+    }
+}
+
+impl Deref for Reader<'_> {
+    type Target = Connection;
+
+    fn deref(&self) -> &Connection {
+        self.conn.as_ref().expect("reader used after release")
+    }
+}
+
+impl Drop for Reader<'_> {
+    fn drop(&mut self) {
+        if let (Some(conn), Ok(mut idle)) = (self.conn.take(), self.pool.idle.lock()) {
+            if idle.len() < MAX_IDLE_READERS {
+                idle.push(conn);
+            }
+        }
+    }
+}
+
+// Run blocking database work from an async command without stalling the runtime thread it
+// was called on
+pub fn blocking<T>(work: impl FnOnce() -> T) -> T {
+    tokio::task::block_in_place(work)
+}
+
+// Lock the main connection and run `work` on it through `blocking`, so a command waiting for
+// another write doesn't hold up the runtime thread either
+pub fn write<T>(db: &Mutex<Connection>, work: impl FnOnce(&mut Connection) -> Result<T, AppError>) -> Result<T, AppError> {
+    blocking(|| {
+        let mut conn = db.lock().map_err(|e| e.to_string())?;
+        work(&mut conn)
+    })
+}
@@ -0,0 +1,63 @@
+// Dragging results out of the window as real files, so they can be dropped into a file manager,
+// a mail composer or an upload dialog. The webview can only drag text and URLs, so the drag is
+// started natively from the backend (OLE on Windows, NSDraggingSession on macOS, GTK on Linux)
+// while the mouse button is still held down on the row.
+
+use std::path::PathBuf;
+use tauri::WebviewWindow;
+use tracing::warn;
+
+use crate::error::{AppError, ErrorCode};
+use crate::{archives, icons};
+
+// Shown under the cursor when the OS has no icon for the first file
+const FALLBACK_IMAGE: &[u8] = include_bytes!("../icons/32x32.png");
+
+// Start dragging `paths` out of `window`. Entries inside archives are extracted first, so the
+// drop target gets a file it can read. Returns once the drag has started.
+#[tauri::command]
+#[specta::specta]
+pub async fn start_drag(paths: Vec<String>, window: WebviewWindow) -> Result<(), AppError> {
+    if paths.is_empty() {
+        return Err(AppError::new(ErrorCode::InvalidInput, "Nothing to drag"));
+    }
+    let mut files = Vec::with_capacity(paths.len());
+    for path in &paths {
+        let file = if archives::split(path).is_some() {
+            archives::extract_for_opening(path)?
+        } else {
+            PathBuf::from(path)
+        };
+        if !file.exists() {
+            return Err(AppError::new(ErrorCode::NotFound, format!("File not found: {}", path)));
+        }
+        files.push(file);
+    }
+
+    let image = icons::get_file_icon(paths[0].clone()).await.unwrap_or_else(|_| FALLBACK_IMAGE.to_vec());
+
+    // Drag sessions belong to the UI thread on every platform
+    let target = window.clone();
+    window
+        .run_on_main_thread(move || {
+            #[cfg(target_os = "linux")]
+            let handle = target.gtk_window();
+            #[cfg(not(target_os = "linux"))]
+            let handle = Ok::<_, tauri::Error>(target);
+
+            let result = handle.map_err(|e| e.to_string()).and_then(|handle| {
+                drag::start_drag(
+                    &handle,
+                    drag::DragItem::Files(files),
+                    drag::Image::Raw(image),
+                    |_, _| {},
+                    drag::Options::default(),
+                )
+                .map_err(|e| e.to_string())
+            });
+            if let Err(e) = result {
+                warn!("Failed to start drag: {}", e);
+            }
+        })
+        .map_err(AppError::from)
+}
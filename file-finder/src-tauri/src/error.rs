@@ -0,0 +1,92 @@
+// Errors commands return to the frontend, serialized as `{ code, message, recoverable }`. The
+// code tells the UI what kind of failure it is, so it can offer to index a folder, start the LLM
+// server or retry instead of only showing the message. `recoverable` is set for failures that go
+// away without changing the request: the index gets built, the LLM comes back, the lock is
+// released. The code is set where the failure happens: commands raise the ones they know of
+// themselves (a missing saved search, an empty index), the LLM client marks an unreachable server,
+// and SQLite and I/O errors passed on with `?` are mapped by their kind. Helpers that still
+// return `Result<_, String>` only report internal failures.
+
+use rusqlite::ErrorCode as SqliteCode;
+use serde::Serialize;
+use specta::Type;
+use std::io;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Type)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorCode {
+    // Nothing indexed yet where the request needs it
+    IndexEmpty,
+    // The LLM backend is turned off in the settings
+    LlmDisabled,
+    // The LLM server can't be reached or didn't answer in time
+    LlmOffline,
+    PermissionDenied,
+    // Another connection holds the database lock
+    DbLocked,
+    NotFound,
+    // The request itself is wrong, e.g. an empty name
+    InvalidInput,
+    Internal,
+}
+
+impl ErrorCode {
+    fn recoverable(self) -> bool {
+        matches!(self, Self::IndexEmpty | Self::LlmDisabled | Self::LlmOffline | Self::DbLocked)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Type, thiserror::Error)]
+#[error("{message}")]
+pub struct AppError {
+    pub code: ErrorCode,
+    pub message: String,
+    pub recoverable: bool,
+}
+
+impl AppError {
+    pub fn new(code: ErrorCode, message: impl Into<String>) -> Self {
+        Self { code, message: message.into(), recoverable: code.recoverable() }
+    }
+}
+
+impl From<String> for AppError {
+    fn from(message: String) -> Self {
+        Self::new(ErrorCode::Internal, message)
+    }
+}
+
+impl From<&str> for AppError {
+    fn from(message: &str) -> Self {
+        message.to_string().into()
+    }
+}
+
+impl From<rusqlite::Error> for AppError {
+    fn from(e: rusqlite::Error) -> Self {
+        let code = match e.sqlite_error_code() {
+            Some(SqliteCode::DatabaseBusy | SqliteCode::DatabaseLocked) => ErrorCode::DbLocked,
+            Some(SqliteCode::PermissionDenied | SqliteCode::ReadOnly) => ErrorCode::PermissionDenied,
+            _ if matches!(e, rusqlite::Error::QueryReturnedNoRows) => ErrorCode::NotFound,
+            _ => ErrorCode::Internal,
+        };
+        Self::new(code, e.to_string())
+    }
+}
+
+impl From<io::Error> for AppError {
+    fn from(e: io::Error) -> Self {
+        let code = match e.kind() {
+            io::ErrorKind::PermissionDenied => ErrorCode::PermissionDenied,
+            io::ErrorKind::NotFound => ErrorCode::NotFound,
+            _ => ErrorCode::Internal,
+        };
+        Self::new(code, e.to_string())
+    }
+}
+
+impl From<tauri::Error> for AppError {
+    fn from(e: tauri::Error) -> Self {
+        e.to_string().into()
+    }
+}
@@ -0,0 +1,148 @@
+// Directories the indexer never descends into. Each OS ships its own defaults, which
+// are stored as editable rules in the settings table rather than compiled in.
+
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use std::path::{Path, PathBuf};
+use tauri::State;
+
+use crate::error::AppError;
+use crate::{db, settings, AppState};
+
+pub const SETTINGS_KEY: &str = "exclusion_rules";
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Type)]
+#[serde(default)]
+pub struct ExclusionRules {
+    // Directory names skipped wherever they appear, e.g. node_modules
+    pub dir_names: Vec<String>,
+    // Absolute directories skipped with everything below them; a leading ~ is the home directory
+    pub paths: Vec<String>,
+}
+
+impl Default for ExclusionRules {
+    fn default() -> Self {
+        let mut dir_names = vec!["node_modules", "target"];
+        let mut paths: Vec<&str> = Vec::new();
+
+        if cfg!(target_os = "windows") {
+            dir_names.extend(["AppData", "$Recycle.Bin", "System Volume Information"]);
+            paths.extend(["C:\\Windows", "C:\\ProgramData\\Microsoft"]);
+        } else if cfg!(target_os = "macos") {
+            dir_names.push("Backups.backupdb");
+            paths.extend([
+                "/System",
+                "/private/var",
+                "/dev",
+                "/Volumes/TimeMachine",
+                "/Volumes/com.apple.TimeMachine.localsnapshots",
+                "~/Library/Caches",
+                "~/Library/Containers",
+                "~/Library/Logs",
+            ]);
+        } else {
+            dir_names.push("lost+found");
+            paths.extend(["/proc", "/sys", "/dev", "/run", "/snap", "/var/cache", "/var/lib/docker"]);
+        }
+
+        Self {
+            dir_names: dir_names.into_iter().map(String::from).collect(),
+            paths: paths.into_iter().map(String::from).collect(),
+        }
+    }
+}
+
+impl ExclusionRules {
+    // Resolve rules once before a walk so the per-entry check stays cheap
+    pub fn compile(&self) -> CompiledExclusions {
+        let home = dirs::home_dir();
+        let paths = self
+            .paths
+            .iter()
+            .map(|rule| rule.trim())
+            .filter(|rule| !rule.is_empty())
+            .map(|rule| match (rule.strip_prefix('~'), &home) {
+                (Some(rest), Some(home)) => home.join(rest.trim_start_matches(['/', '\\'])),
+                _ => PathBuf::from(rule),
+            })
+            .map(|path| fold_path(&path))
+            .collect();
+        let dir_names = self
+            .dir_names
+            .iter()
+            .map(|name| name.trim())
+            .filter(|name| !name.is_empty())
+            .map(fold_name)
+            .collect();
+        CompiledExclusions { dir_names, paths }
+    }
+}
+
+pub struct CompiledExclusions {
+    dir_names: Vec<String>,
+    paths: Vec<PathBuf>,
+}
+
+impl CompiledExclusions {
+    pub fn is_excluded(&self, path: &Path, file_name: &str, is_dir: bool) -> bool {
+        if !is_dir {
+            return false;
+        }
+        let name = fold_name(file_name);
+        if self.dir_names.contains(&name) {
+            return true;
+        }
+        if self.paths.is_empty() {
+            return false;
+        }
+        let path = fold_path(path);
+        self.paths.iter().any(|excluded| path.starts_with(excluded))
+    }
+}
+
+// Windows file names are case-insensitive, so compare them folded there
+fn fold_name(name: &str) -> String {
+    if cfg!(target_os = "windows") {
+        name.to_lowercase()
+    } else {
+        name.to_string()
+    }
+}
+
+fn fold_path(path: &Path) -> PathBuf {
+    if cfg!(target_os = "windows") {
+        PathBuf::from(path.to_string_lossy().to_lowercase())
+    } else {
+        path.to_path_buf()
+    }
+}
+
+pub fn load(conn: &rusqlite::Connection) -> ExclusionRules {
+    settings::load(conn, SETTINGS_KEY)
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn get_exclusion_rules(state: State<'_, AppState>) -> Result<ExclusionRules, AppError> {
+    db::write(&state.db, |db| Ok(load(db)))
+}
+
+// New rules apply from the next indexing run
+#[tauri::command]
+#[specta::specta]
+pub async fn set_exclusion_rules(rules: ExclusionRules, state: State<'_, AppState>) -> Result<ExclusionRules, AppError> {
+    db::write(&state.db, |db| {
+        settings::save(db, SETTINGS_KEY, &rules)?;
+        Ok(rules)
+    })
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn reset_exclusion_rules(state: State<'_, AppState>) -> Result<ExclusionRules, AppError> {
+    let rules = ExclusionRules::default();
+    db::write(&state.db, |db| {
+        settings::save(db, SETTINGS_KEY, &rules)?;
+        Ok(rules)
+    })
+}
@@ -0,0 +1,293 @@
+// Side-by-side ranking experiments. While one is running, every search is ranked with both the
+// live config ("a") and a candidate ("b"); one of the two lists is shown at random, and opening a
+// file records where it ranked in each list so the candidate can be judged on real clicks.
+
+use rusqlite::{params, Connection, Result as SqlResult};
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use std::collections::VecDeque;
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+use tauri::State;
+
+use crate::error::{AppError, ErrorCode};
+use crate::ranking::RankingConfig;
+use crate::{db, settings, AppState, FileEntry};
+
+pub const SETTINGS_KEY: &str = "ranking_experiment";
+
+// Recent searches kept in memory for attributing clicks
+const MAX_IMPRESSIONS: usize = 50;
+// Opening a file long after the search is not treated as a click on it
+const CLICK_WINDOW_SECS: u64 = 600;
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Type)]
+pub struct RankingExperiment {
+    pub name: String,
+    // Compared against the live ranking config
+    pub candidate: RankingConfig,
+    pub started_at: i64,
+}
+
+#[derive(Debug, Serialize, Clone, Copy, PartialEq, Eq, Type)]
+#[serde(rename_all = "snake_case")]
+pub enum Variant {
+    A,
+    B,
+}
+
+impl Variant {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Variant::A => "a",
+            Variant::B => "b",
+        }
+    }
+}
+
+struct Impression {
+    at: Instant,
+    query: String,
+    shown: Variant,
+    paths_a: Vec<String>,
+    paths_b: Vec<String>,
+}
+
+#[derive(Default)]
+pub struct ExperimentState {
+    pub active: Option<RankingExperiment>,
+    impressions: VecDeque<Impression>,
+}
+
+#[derive(Debug, Serialize, Clone, Type)]
+pub struct VariantStats {
+    pub variant: Variant,
+    // Clicks on searches where this variant's list was shown
+    pub shown_clicks: i64,
+    // Clicks on this variant's first result
+    pub top_result_clicks: i64,
+    // Mean of 1/rank of the clicked file in this variant's list (0 when absent)
+    pub mean_reciprocal_rank: f64,
+}
+
+#[derive(Debug, Serialize, Clone, Type)]
+pub struct ExperimentReport {
+    pub experiment: Option<RankingExperiment>,
+    // Name the report covers: the running experiment or the one asked for
+    pub name: Option<String>,
+    pub clicks: i64,
+    // Clicks where both variants had the same first result
+    pub tied_top_results: i64,
+    pub variants: Vec<VariantStats>,
+}
+
+pub fn create_table(conn: &Connection) -> SqlResult<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS ranking_experiment_clicks (
+            id INTEGER PRIMARY KEY,
+            experiment TEXT NOT NULL,
+            query TEXT NOT NULL,
+            path TEXT NOT NULL,
+            shown_variant TEXT NOT NULL,
+            rank_a INTEGER,
+            rank_b INTEGER,
+            top_a TEXT,
+            top_b TEXT,
+            clicked_at INTEGER NOT NULL
+        )",
+        [],
+    )?;
+    Ok(())
+}
+
+pub fn load(conn: &Connection) -> ExperimentState {
+    ExperimentState {
+        active: settings::load::<Option<RankingExperiment>>(conn, SETTINGS_KEY),
+        impressions: VecDeque::new(),
+    }
+}
+
+// Candidate config to rank with alongside the live one, if an experiment is running
+pub fn candidate(state: &AppState) -> Result<Option<RankingConfig>, String> {
+    let experiment = state.ranking_experiment.lock().map_err(|e| e.to_string())?;
+    Ok(experiment.active.as_ref().map(|active| active.candidate.clone()))
+}
+
+// Remember both rankings of one search and return the list to show
+pub fn record_impression(
+    state: &AppState,
+    query: &str,
+    results_a: Vec<FileEntry>,
+    results_b: Vec<FileEntry>,
+) -> Result<Vec<FileEntry>, String> {
+    // No rand dependency: the clock's low bits are random enough to split searches
+    let nanos = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.subsec_nanos()).unwrap_or(0);
+    let shown = if (nanos / 1000).is_multiple_of(2) { Variant::A } else { Variant::B };
+
+    let paths = |results: &[FileEntry]| results.iter().map(|entry| entry.path.clone()).collect();
+    let impression = Impression {
+        at: Instant::now(),
+        query: query.to_string(),
+        shown,
+        paths_a: paths(&results_a),
+        paths_b: paths(&results_b),
+    };
+
+    let mut experiment = state.ranking_experiment.lock().map_err(|e| e.to_string())?;
+    if experiment.impressions.len() >= MAX_IMPRESSIONS {
+        experiment.impressions.pop_front();
+    }
+    experiment.impressions.push_back(impression);
+
+    Ok(match shown {
+        Variant::A => results_a,
+        Variant::B => results_b,
+    })
+}
+
+// Called when a file is opened: attribute it to the latest search that listed it
+pub fn record_click(state: &AppState, path: &str) -> Result<(), String> {
+    let (name, impression) = {
+        let mut experiment = state.ranking_experiment.lock().map_err(|e| e.to_string())?;
+        let name = match &experiment.active {
+            Some(active) => active.name.clone(),
+            None => return Ok(()),
+        };
+        let position = experiment.impressions.iter().rposition(|impression| {
+            impression.at.elapsed().as_secs() < CLICK_WINDOW_SECS
+                && (impression.paths_a.iter().any(|p| p == path) || impression.paths_b.iter().any(|p| p == path))
+        });
+        // Each search counts once, for the first file opened from it
+        match position.and_then(|position| experiment.impressions.remove(position)) {
+            Some(impression) => (name, impression),
+            None => return Ok(()),
+        }
+    };
+
+    let rank = |paths: &[String]| paths.iter().position(|p| p == path).map(|i| i as i64 + 1);
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    db.execute(
+        "INSERT INTO ranking_experiment_clicks
+            (experiment, query, path, shown_variant, rank_a, rank_b, top_a, top_b, clicked_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+        params![
+            name,
+            impression.query,
+            path,
+            impression.shown.as_str(),
+            rank(&impression.paths_a),
+            rank(&impression.paths_b),
+            impression.paths_a.first(),
+            impression.paths_b.first(),
+            chrono::Utc::now().timestamp(),
+        ],
+    )
+    .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn start_ranking_experiment(name: String, candidate: RankingConfig, state: State<'_, AppState>) -> Result<RankingExperiment, AppError> {
+    let name = name.trim().to_string();
+    if name.is_empty() {
+        return Err(AppError::new(ErrorCode::InvalidInput, "Experiment name cannot be empty"));
+    }
+    let experiment = RankingExperiment {
+        name,
+        candidate: candidate.sanitized(),
+        started_at: chrono::Utc::now().timestamp(),
+    };
+    db::write(&state.db, |db| settings::save(db, SETTINGS_KEY, &Some(experiment.clone())))?;
+    {
+        let mut state_experiment = state.ranking_experiment.lock().map_err(|e| e.to_string())?;
+        state_experiment.active = Some(experiment.clone());
+        state_experiment.impressions.clear();
+    }
+
+    // Cached results were ranked without the experiment
+    state.search_cache.write().map_err(|e| e.to_string())?.clear();
+    Ok(experiment)
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn stop_ranking_experiment(state: State<'_, AppState>) -> Result<ExperimentReport, AppError> {
+    let name = {
+        db::write(&state.db, |db| settings::save(db, SETTINGS_KEY, &None::<RankingExperiment>))?;
+        let mut experiment = state.ranking_experiment.lock().map_err(|e| e.to_string())?;
+        experiment.impressions.clear();
+        experiment.active.take().map(|active| active.name)
+    };
+    state.search_cache.write().map_err(|e| e.to_string())?.clear();
+
+    // Final numbers for the experiment that just ended
+    Ok(db::blocking(|| report(name, &state))?)
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn get_ranking_experiment_report(name: Option<String>, state: State<'_, AppState>) -> Result<ExperimentReport, AppError> {
+    let name = match name {
+        Some(name) => Some(name),
+        None => state
+            .ranking_experiment
+            .lock()
+            .map_err(|e| e.to_string())?
+            .active
+            .as_ref()
+            .map(|active| active.name.clone()),
+    };
+    Ok(db::blocking(|| report(name, &state))?)
+}
+
+fn report(name: Option<String>, state: &AppState) -> Result<ExperimentReport, String> {
+    let active = state.ranking_experiment.lock().map_err(|e| e.to_string())?.active.clone();
+    let mut report = ExperimentReport {
+        experiment: active,
+        name: name.clone(),
+        clicks: 0,
+        tied_top_results: 0,
+        variants: vec![],
+    };
+    let Some(name) = name else {
+        return Ok(report);
+    };
+
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    let (clicks, tied): (i64, i64) = db
+        .query_row(
+            "SELECT COUNT(*), COALESCE(SUM(top_a IS NOT NULL AND top_a = top_b), 0)
+             FROM ranking_experiment_clicks WHERE experiment = ?1",
+            [&name],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .map_err(|e| e.to_string())?;
+    report.clicks = clicks;
+    report.tied_top_results = tied;
+
+    for (variant, rank_column) in [(Variant::A, "rank_a"), (Variant::B, "rank_b")] {
+        let stats = db
+            .query_row(
+                &format!(
+                    "SELECT COALESCE(SUM(shown_variant = ?2), 0),
+                            COALESCE(SUM({0} = 1), 0),
+                            COALESCE(AVG(COALESCE(1.0 / {0}, 0.0)), 0.0)
+                     FROM ranking_experiment_clicks WHERE experiment = ?1",
+                    rank_column
+                ),
+                params![name, variant.as_str()],
+                |row| {
+                    Ok(VariantStats {
+                        variant,
+                        shown_clicks: row.get(0)?,
+                        top_result_clicks: row.get(1)?,
+                        mean_reciprocal_rank: row.get(2)?,
+                    })
+                },
+            )
+            .map_err(|e| e.to_string())?;
+        report.variants.push(stats);
+    }
+
+    Ok(report)
+}
@@ -0,0 +1,327 @@
+// "Why is this file ranked here?" The query is taken apart the way ranked_search does it
+// (filters, pattern type, prefilter) and the file is scored with the same scoring functions,
+// recording each rule that applied. The actual search is run too, so the rank shown is real.
+
+use chrono::Local;
+use rusqlite::types::Value;
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::Serialize;
+use specta::Type;
+use std::path::Path;
+use tauri::State;
+
+use crate::error::AppError;
+use crate::ranking::RankingConfig;
+use crate::search_sql::SqlFilter;
+use crate::{
+    analyze_regex_pattern, boost_lists, compile_search_regex, db, experiments, fuzzy_score, globs, like_prefilter,
+    literal_score, matched_pattern_score, content_search, mirrors, nl, path_scope, pins, tags, time_filter, watched_search, AppState, Boosts, FuzzyQuery, PatternInfo, PatternType,
+    ScoreStep, ScoreTrace, SearchOptions, GLOB_RULE, PREFIX_RULE, REGEX_RULE,
+};
+
+// Results directly above the explained one that are scored for comparison
+const RANKED_ABOVE: usize = 3;
+
+#[derive(Debug, Serialize, Clone, Type)]
+pub struct RankedAbove {
+    pub path: String,
+    pub score: Option<i64>,
+}
+
+#[derive(Debug, Serialize, Clone, Type)]
+pub struct SearchExplanation {
+    pub query: String,
+    // The text that was matched once filter tokens were taken out
+    pub search_text: String,
+    // Filters taken from the query, e.g. "type: jpg, png"
+    pub filters: Vec<String>,
+    // How the search text was matched: literal text, glob, prefix or regex
+    pub strategy: String,
+    // What a natural-language query was rewritten into before searching
+    pub rewritten_query: Option<String>,
+    // 1-based position in the results, None if it is not in them
+    pub rank: Option<u32>,
+    pub result_count: u32,
+    pub score: Option<i64>,
+    // Each rule that applied, with the score after it
+    pub steps: Vec<ScoreStep>,
+    // The results just above it, best first, with their scores
+    pub ranked_above: Vec<RankedAbove>,
+    // Why it is missing, and anything else that affected the result list
+    pub notes: Vec<String>,
+}
+
+// Everything needed to score one file for a query
+struct Scorer<'a> {
+    search_text: &'a str,
+    pattern_info: PatternInfo,
+    options: &'a SearchOptions,
+    ranking: &'a RankingConfig,
+    boosts: Boosts,
+}
+
+impl Scorer<'_> {
+    fn fuzzy(&self, query: &str, path: &str, name: &str, trace: &mut ScoreTrace) -> Option<i64> {
+        let fuzzy_query = FuzzyQuery::new(query, self.options.case_sensitive);
+        fuzzy_score(path, name, &fuzzy_query, &self.boosts, self.options, self.ranking, trace)
+    }
+
+    fn pattern(&self, path: &str, name: &str, needle: &str, base: i64, rule: &str, trace: &mut ScoreTrace) -> i64 {
+        matched_pattern_score(path, name, needle, base, rule, self.options, &self.boosts, self.ranking, trace)
+    }
+
+    // Score the way ranked_search would for this pattern type, or None if the file doesn't match.
+    // `notes` gets the reason a file is not matched or only matched by a fallback.
+    fn score(&self, db: &Connection, state: &AppState, path: &str, name: &str, trace: &mut ScoreTrace, notes: &mut Vec<String>) -> Result<Option<i64>, String> {
+        let info = &self.pattern_info;
+        let passes_prefilter = match &info.sql_like_pattern {
+            Some(like_pattern) if info.can_use_sql_optimization => {
+                let (name_match, sql_pattern) = like_prefilter(info, like_pattern, self.search_text, self.options);
+                db.query_row(
+                    &format!("SELECT 1 FROM files WHERE path = ?2 AND {}", name_match.sql()),
+                    params![sql_pattern, path],
+                    |_| Ok(()),
+                )
+                .optional()
+                .map_err(|e| e.to_string())?
+                .is_some()
+            }
+            _ => true,
+        };
+
+        match info.pattern_type {
+            PatternType::SimplePrefix => {
+                if !passes_prefilter {
+                    notes.push("The name does not start with the prefix".to_string());
+                    return Ok(None);
+                }
+                let prefix = info.prefix.as_deref().unwrap_or("");
+                Ok(Some(self.pattern(path, name, prefix, self.ranking.prefix_match, PREFIX_RULE, trace)))
+            }
+            PatternType::SimpleGlob | PatternType::Glob => {
+                let glob = globs::GlobQuery::new(&info.regex_pattern, self.options.case_sensitive)?;
+                if !passes_prefilter || !glob.is_match(name, path) {
+                    notes.push("The name does not match the glob".to_string());
+                    return Ok(None);
+                }
+                Ok(Some(self.pattern(path, name, self.search_text, self.ranking.regex_match, GLOB_RULE, trace)))
+            }
+            PatternType::PrefixSuffix | PatternType::ComplexRegex => {
+                let Some(regex) = compile_search_regex(self.search_text, info, state)? else {
+                    notes.push("The query is not a valid regex, so it was matched fuzzily".to_string());
+                    return Ok(self.fuzzy(self.search_text, path, name, trace));
+                };
+                if regex.is_match(name) || regex.is_match(path) {
+                    return Ok(Some(self.pattern(path, name, self.search_text, self.ranking.regex_match, REGEX_RULE, trace)));
+                }
+                // Mirrors ranked_search's fallback for regexes that match fewer than 20 files
+                let clean_query = self.search_text.replace("^", "").replace(".*", "").replace("$", "").replace(r"\.", ".");
+                let fallback = if clean_query.chars().count() >= 3 { self.fuzzy(&clean_query, path, name, trace) } else { None };
+                match fallback {
+                    Some(score) => {
+                        let divided = score / self.ranking.fuzzy_fallback_divisor;
+                        trace.note("Regex did not match; found by the fuzzy fallback, which runs when the regex matches fewer than 20 files", divided);
+                        Ok(Some(divided))
+                    }
+                    None => {
+                        notes.push("Neither the name nor the path matches the regex".to_string());
+                        Ok(None)
+                    }
+                }
+            }
+            PatternType::LiteralSearch => {
+                if info.can_use_sql_optimization {
+                    if !passes_prefilter {
+                        notes.push("The name does not contain the query text".to_string());
+                        return Ok(None);
+                    }
+                    return Ok(Some(literal_score(
+                        path, name, self.search_text, self.options, &self.boosts, self.ranking, trace,
+                    )));
+                }
+                let score = self.fuzzy(self.search_text, path, name, trace);
+                if score.is_none() {
+                    notes.push("The name and path are not even a fuzzy match".to_string());
+                }
+                Ok(score)
+            }
+        }
+    }
+}
+
+fn strategy(info: &PatternInfo) -> String {
+    match info.pattern_type {
+        PatternType::SimplePrefix => format!("Prefix: names starting with '{}'", info.prefix.as_deref().unwrap_or("")),
+        PatternType::SimpleGlob | PatternType::Glob => format!("Glob '{}'", info.regex_pattern),
+        PatternType::PrefixSuffix | PatternType::ComplexRegex => format!("Regex '{}' on names and paths", info.regex_pattern),
+        PatternType::LiteralSearch if info.can_use_sql_optimization => {
+            "Text: names containing the query, scored by where it appears".to_string()
+        }
+        PatternType::LiteralSearch => "Fuzzy text: names and folders matched loosely, scored by closeness".to_string(),
+    }
+}
+
+// Explain where `path` ranks for `query` and which scoring rules put it there
+#[tauri::command]
+#[specta::specta]
+pub async fn explain_search(
+    query: String,
+    path: String,
+    options: Option<SearchOptions>,
+    state: State<'_, AppState>,
+) -> Result<SearchExplanation, AppError> {
+    let options = options.unwrap_or_default();
+    let ranking = state.ranking.read().map_err(|e| e.to_string())?.clone();
+    let mut notes = Vec::new();
+
+    // Explain the query that actually ran
+    let rewrite = nl::rewrite(&query, &options, &state).await?;
+    let search_query = match &rewrite {
+        Some(rewrite) => {
+            if rewrite.source == nl::RewriteSource::Fallback {
+                notes.push("The LLM was unavailable, so the natural-language query was simplified without it".to_string());
+            }
+            rewrite.query.clone()
+        }
+        None => query.clone(),
+    };
+
+    // Same order of token extraction as ranked_search
+    let (search_text, root_scope) = mirrors::extract_scope(&search_query);
+    let (search_text, path_scope) = db::write(&state.db, |db| Ok(path_scope::extract_path_scope(&search_text, db)?))?;
+    let (search_text, type_filter, tag_filter) = {
+        let groups = state.extension_groups.read().map_err(|e| e.to_string())?;
+        let (text, type_filter) = groups.extract_type_filter(&search_text);
+        let (text, tag_filter) = tags::extract_tag_filter(&text, &groups);
+        (text, type_filter, tag_filter)
+    };
+    let (search_text, time_filter) = time_filter::extract_time_filter(&search_text, Local::now());
+    let (search_text, content_terms) = content_search::extract_terms(&search_text);
+    let mut filters: Vec<(String, SqlFilter)> = Vec::new();
+    let sql_filter = |clause: &str| {
+        let mut filter = SqlFilter::default();
+        filter.and(clause);
+        filter
+    };
+    if root_scope == mirrors::RootScope::Backups {
+        filters.push(("in: backups".to_string(), SqlFilter::new(root_scope.sql_clause())));
+    }
+    if let Some(scope) = &path_scope {
+        let mut bound = SqlFilter::default();
+        bound.and_bound(scope.sql_clause());
+        filters.push((scope.describe().join(" or "), bound));
+    }
+    if let Some(scope) = options.scope.as_deref().map(path_scope::PathScope::under) {
+        let mut bound = SqlFilter::default();
+        bound.and_bound(scope.sql_clause());
+        filters.push((scope.describe().join(" or "), bound));
+    }
+    if let Some(filter) = &type_filter {
+        filters.push((format!("type: {}", filter.extensions.join(", ")), sql_filter(&filter.sql_clause())));
+    }
+    if let Some(filter) = &tag_filter {
+        filters.push((filter.describe().join("; "), sql_filter(&filter.sql_clause())));
+    }
+    if let Some(filter) = &time_filter {
+        let mut bound = SqlFilter::default();
+        bound.and_bound(filter.sql_clause());
+        filters.push((filter.describe(), bound));
+    }
+    if !content_terms.is_empty() {
+        // Matched by reading the file, not in SQL
+        filters.push((format!("content: {}", content_terms.join(", ")), SqlFilter::default()));
+        notes.push("Files whose text matches the content: terms are listed after name matches, newest first".to_string());
+    }
+
+    let pattern_info = analyze_regex_pattern(&search_text);
+    let mut explanation = SearchExplanation {
+        query: query.clone(),
+        search_text: search_text.clone(),
+        filters: filters.iter().map(|(label, _)| label.clone()).collect(),
+        strategy: strategy(&pattern_info),
+        rewritten_query: rewrite.map(|rewrite| rewrite.query),
+        rank: None,
+        result_count: 0,
+        score: None,
+        steps: vec![],
+        ranked_above: vec![],
+        notes: vec![],
+    };
+
+    // The real result list, ranked with the live weights and bypassing the cache
+    let (results, degraded, _) = watched_search(&search_query, &options, &ranking, &state)?;
+    explanation.result_count = results.len() as u32;
+    let position = results.iter().position(|entry| entry.path == path);
+    explanation.rank = position.map(|i| i as u32 + 1);
+    if degraded {
+        notes.push("The search ran out of time, so some matches may be missing from the results".to_string());
+    }
+    if experiments::candidate(&state)?.is_some() {
+        notes.push("A ranking experiment is running; the app may show this search ranked with its candidate weights".to_string());
+    }
+
+    db::write(&state.db, |db| {
+        if let Some(prefix) = pins::pin_for(db, &search_query, &path)? {
+            notes.push(format!("Pinned for searches starting with \"{}\", so it is listed before the scored results", prefix));
+        }
+        let name: Option<String> = db
+            .query_row("SELECT name FROM files WHERE path = ?1", [&path], |row| row.get(0))
+            .optional()?;
+        let Some(name) = name else {
+            notes.push(if Path::new(&path).exists() {
+                "The file is not in the index. Re-index its folder.".to_string()
+            } else {
+                "The file is not in the index and does not exist".to_string()
+            });
+            explanation.notes = notes;
+            return Ok(explanation);
+        };
+
+        // Filters run in SQL, so a file they exclude is never scored
+        if root_scope == mirrors::RootScope::Primary && path_scope.is_none() && !filters_pass(db, &path, &SqlFilter::new(root_scope.sql_clause()))? {
+            notes.push("The file is in a backup mirror; add in:backups to search mirrors".to_string());
+        }
+        for (label, filter) in &filters {
+            if !filters_pass(db, &path, filter)? {
+                notes.push(format!("Excluded by the filter {}", label));
+            }
+        }
+
+        let boosts = boost_lists(db)?;
+        let scorer = Scorer {
+            search_text: &search_text,
+            pattern_info,
+            options: &options,
+            ranking: &ranking,
+            boosts,
+        };
+        if search_text.trim().is_empty() {
+            notes.push("Only filters were given, so results are the most recently modified matching files".to_string());
+        } else {
+            let mut trace = ScoreTrace::on();
+            explanation.score = scorer.score(db, &state, &path, &name, &mut trace, &mut notes)?;
+            explanation.steps = trace.into_steps();
+            if let Some(position) = position {
+                for entry in &results[position.saturating_sub(RANKED_ABOVE)..position] {
+                    let score = scorer.score(db, &state, &entry.path, &entry.name, &mut ScoreTrace::off(), &mut Vec::new())?;
+                    explanation.ranked_above.push(RankedAbove { path: entry.path.clone(), score });
+                }
+            } else if explanation.score.is_some() {
+                notes.push(format!(
+                    "It matches, but only the best {} results are kept and its score did not make the cut",
+                    results.len()
+                ));
+            }
+        }
+        explanation.notes = notes;
+        Ok(explanation)
+    })
+}
+
+fn filters_pass(db: &Connection, path: &str, filter: &SqlFilter) -> Result<bool, String> {
+    let params = filter.bind(vec![Value::Text(path.to_string())], vec![]);
+    db.query_row(&format!("SELECT 1 FROM files WHERE path = ?1{}", filter.sql), rusqlite::params_from_iter(params), |_| Ok(()))
+        .optional()
+        .map(|found| found.is_some())
+        .map_err(|e| e.to_string())
+}
@@ -0,0 +1,253 @@
+// Favorite groups and manual ordering. Each favorite sits in one group, or in none (ungrouped),
+// at a `sort_order` position within it; groups have an order of their own. Positions are kept
+// dense (0, 1, 2, ...) by renumbering a group whenever something moves into or out of it.
+
+use rusqlite::{params, Connection, OptionalExtension, Result as SqlResult, Transaction};
+use serde::Serialize;
+use specta::Type;
+use tauri::State;
+
+use crate::error::{AppError, ErrorCode};
+use crate::{db, AppState};
+
+#[derive(Debug, Serialize, Clone, Type)]
+pub struct Favorite {
+    pub path: String,
+    pub name: String,
+    pub favorited_at: i64,
+}
+
+#[derive(Debug, Serialize, Clone, Type)]
+pub struct FavoriteGroup {
+    pub id: i64,
+    pub name: String,
+    pub favorites: Vec<Favorite>,
+}
+
+#[derive(Debug, Serialize, Clone, Type)]
+pub struct FavoriteGroups {
+    // Favorites not in any group, listed before the groups
+    pub ungrouped: Vec<Favorite>,
+    pub groups: Vec<FavoriteGroup>,
+}
+
+pub fn create_tables(conn: &Connection) -> SqlResult<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS favorite_groups (
+            id INTEGER PRIMARY KEY,
+            name TEXT UNIQUE NOT NULL,
+            sort_order INTEGER NOT NULL,
+            created_at INTEGER NOT NULL
+        )",
+        [],
+    )?;
+    // Ignore errors if the columns already exist
+    let _ = conn.execute("ALTER TABLE favorite_files ADD COLUMN group_id INTEGER", []);
+    if conn.execute("ALTER TABLE favorite_files ADD COLUMN sort_order INTEGER NOT NULL DEFAULT 0", []).is_ok() {
+        // Column is new - keep the old newest-first order as the manual order
+        conn.execute(
+            "UPDATE favorite_files SET sort_order = (
+                SELECT COUNT(*) FROM favorite_files f
+                WHERE f.favorited_at > favorite_files.favorited_at
+                   OR (f.favorited_at = favorite_files.favorited_at AND f.id < favorite_files.id)
+            )",
+            [],
+        )?;
+    }
+    Ok(())
+}
+
+// Position after the last favorite of a group (None for ungrouped)
+pub fn next_sort_order(conn: &Connection, group_id: Option<i64>) -> SqlResult<i64> {
+    conn.query_row(
+        "SELECT COALESCE(MAX(sort_order) + 1, 0) FROM favorite_files WHERE group_id IS ?1",
+        [group_id],
+        |row| row.get(0),
+    )
+}
+
+fn group_paths(conn: &Connection, group_id: Option<i64>) -> SqlResult<Vec<String>> {
+    let mut stmt = conn.prepare("SELECT path FROM favorite_files WHERE group_id IS ?1 ORDER BY sort_order, id")?;
+    let paths = stmt.query_map([group_id], |row| row.get(0))?.filter_map(|r| r.ok()).collect();
+    Ok(paths)
+}
+
+// Write `paths` as the contents of a group, in this order
+fn renumber(tx: &Transaction, group_id: Option<i64>, paths: &[String]) -> SqlResult<()> {
+    let mut stmt = tx.prepare("UPDATE favorite_files SET group_id = ?1, sort_order = ?2 WHERE path = ?3")?;
+    for (position, path) in paths.iter().enumerate() {
+        stmt.execute(params![group_id, position as i64, path])?;
+    }
+    Ok(())
+}
+
+fn group_exists(conn: &Connection, id: i64) -> Result<bool, String> {
+    conn.query_row("SELECT 1 FROM favorite_groups WHERE id = ?1", [id], |_| Ok(()))
+        .optional()
+        .map(|found| found.is_some())
+        .map_err(|e| e.to_string())
+}
+
+fn list_favorites(conn: &Connection) -> SqlResult<Vec<(Option<i64>, Favorite)>> {
+    let mut stmt = conn.prepare("SELECT group_id, path, name, favorited_at FROM favorite_files ORDER BY sort_order, id")?;
+    let favorites = stmt
+        .query_map([], |row| {
+            Ok((
+                row.get(0)?,
+                Favorite {
+                    path: row.get(1)?,
+                    name: row.get(2)?,
+                    favorited_at: row.get(3)?,
+                },
+            ))
+        })?
+        .filter_map(|r| r.ok())
+        .collect();
+    Ok(favorites)
+}
+
+// Every favorite, ungrouped first and then by group, each in its manual order
+#[tauri::command]
+#[specta::specta]
+pub async fn get_favorite_groups(state: State<'_, AppState>) -> Result<FavoriteGroups, AppError> {
+    db::write(&state.db, |db| {
+        let mut stmt = db
+            .prepare("SELECT id, name FROM favorite_groups ORDER BY sort_order, id")?;
+        let mut groups: Vec<FavoriteGroup> = stmt
+            .query_map([], |row| {
+                Ok(FavoriteGroup {
+                    id: row.get(0)?,
+                    name: row.get(1)?,
+                    favorites: vec![],
+                })
+            })?
+            .filter_map(|r| r.ok())
+            .collect();
+
+        let mut ungrouped = Vec::new();
+        for (group_id, favorite) in list_favorites(db)? {
+            match group_id.and_then(|id| groups.iter_mut().find(|group| group.id == id)) {
+                Some(group) => group.favorites.push(favorite),
+                // Also favorites whose group row is gone
+                None => ungrouped.push(favorite),
+            }
+        }
+        Ok(FavoriteGroups { ungrouped, groups })
+    })
+}
+
+// New empty group, after the existing ones
+#[tauri::command]
+#[specta::specta]
+pub async fn create_favorite_group(name: String, state: State<'_, AppState>) -> Result<FavoriteGroup, AppError> {
+    let name = name.trim().to_string();
+    if name.is_empty() {
+        return Err(AppError::new(ErrorCode::InvalidInput, "Group name cannot be empty"));
+    }
+    db::write(&state.db, |db| {
+        db.execute(
+            "INSERT INTO favorite_groups (name, sort_order, created_at)
+             VALUES (?1, (SELECT COALESCE(MAX(sort_order) + 1, 0) FROM favorite_groups), ?2)",
+            params![name, chrono::Utc::now().timestamp()],
+        )
+        .map_err(|e| match e {
+            rusqlite::Error::SqliteFailure(failure, _) if failure.code == rusqlite::ErrorCode::ConstraintViolation => {
+                AppError::new(ErrorCode::InvalidInput, format!("A group named {} already exists", name))
+            }
+            e => e.into(),
+        })?;
+        Ok(FavoriteGroup { id: db.last_insert_rowid(), name, favorites: vec![] })
+    })
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn rename_favorite_group(id: i64, name: String, state: State<'_, AppState>) -> Result<(), AppError> {
+    let name = name.trim().to_string();
+    if name.is_empty() {
+        return Err(AppError::new(ErrorCode::InvalidInput, "Group name cannot be empty"));
+    }
+    db::write(&state.db, |db| {
+        let updated = db
+            .execute("UPDATE favorite_groups SET name = ?1 WHERE id = ?2", params![name, id])?;
+        if updated == 0 {
+            return Err(AppError::new(ErrorCode::NotFound, format!("Favorite group {} not found", id)));
+        }
+        Ok(())
+    })
+}
+
+// Delete a group; its favorites stay, at the end of the ungrouped list
+#[tauri::command]
+#[specta::specta]
+pub async fn delete_favorite_group(id: i64, state: State<'_, AppState>) -> Result<bool, AppError> {
+    db::write(&state.db, |db| {
+        let tx = db.transaction()?;
+        let mut ungrouped = group_paths(&tx, None)?;
+        ungrouped.extend(group_paths(&tx, Some(id))?);
+        renumber(&tx, None, &ungrouped)?;
+        let deleted = tx.execute("DELETE FROM favorite_groups WHERE id = ?1", [id])?;
+        tx.commit()?;
+        Ok(deleted > 0)
+    })
+}
+
+// Put a favorite into a group (None for ungrouped) at `position`, or at the end. Moving within
+// its own group reorders it.
+#[tauri::command]
+#[specta::specta]
+pub async fn move_favorite(
+    path: String,
+    group_id: Option<i64>,
+    position: Option<u32>,
+    state: State<'_, AppState>,
+) -> Result<(), AppError> {
+    db::write(&state.db, |db| {
+        if let Some(id) = group_id {
+            if !group_exists(db, id)? {
+                return Err(AppError::new(ErrorCode::NotFound, format!("Favorite group {} not found", id)));
+            }
+        }
+        let tx = db.transaction()?;
+        let old_group: Option<i64> = tx
+            .query_row("SELECT group_id FROM favorite_files WHERE path = ?1", [&path], |row| row.get(0))
+            .optional()?
+            .ok_or_else(|| format!("{} is not a favorite", path))?;
+
+        if old_group != group_id {
+            let mut left = group_paths(&tx, old_group)?;
+            left.retain(|p| p != &path);
+            renumber(&tx, old_group, &left)?;
+        }
+        let mut target = group_paths(&tx, group_id)?;
+        target.retain(|p| p != &path);
+        let position = position.map_or(target.len(), |p| (p as usize).min(target.len()));
+        target.insert(position, path);
+        renumber(&tx, group_id, &target)?;
+        tx.commit().map_err(AppError::from)
+    })
+}
+
+// Give the groups the order of `ids`. Groups missing from `ids` keep their relative order after them.
+#[tauri::command]
+#[specta::specta]
+pub async fn reorder_favorite_groups(ids: Vec<i64>, state: State<'_, AppState>) -> Result<(), AppError> {
+    db::write(&state.db, |db| {
+        let tx = db.transaction()?;
+        let existing: Vec<i64> = {
+            let mut stmt = tx.prepare("SELECT id FROM favorite_groups ORDER BY sort_order, id")?;
+            let ids = stmt.query_map([], |row| row.get(0))?.filter_map(|r| r.ok()).collect();
+            ids
+        };
+        let mut order: Vec<i64> = Vec::with_capacity(existing.len());
+        for id in ids.into_iter().chain(existing.iter().copied()) {
+            if existing.contains(&id) && !order.contains(&id) {
+                order.push(id);
+            }
+        }
+        for (position, id) in order.iter().enumerate() {
+            tx.execute("UPDATE favorite_groups SET sort_order = ?1 WHERE id = ?2", params![position as i64, id])?;
+        }
+        tx.commit().map_err(AppError::from)
+    })
+}
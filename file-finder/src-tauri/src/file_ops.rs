@@ -0,0 +1,612 @@
+// File operations from the results list: create, rename, move and copy. The change is made on disk
+// first and then in the database, in one transaction, so the index, recents, favorites, tags and
+// everything derived from a file follow it instead of pointing at a path that no longer exists.
+// A rename is undone if the database can't be updated. Large copies report progress as
+// `transfer-progress` events.
+
+use rusqlite::{params, Connection, OptionalExtension, Transaction};
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use std::ffi::OsStr;
+use std::fs::{self, File};
+use std::io::{self, ErrorKind, Read, Write};
+use std::path::{Path, PathBuf, MAIN_SEPARATOR};
+use tauri::{AppHandle, Emitter, State};
+use tracing::{info, warn};
+use walkdir::WalkDir;
+
+use crate::error::{AppError, ErrorCode};
+use crate::{assign_age_buckets, db, escape_like, exclusions, hidden, invalidate_index, mirrors, native_path, transliterate, trigram, AppState, FileEntry, LIKE_ESCAPE};
+
+pub const PROGRESS_EVENT: &str = "transfer-progress";
+
+// Files at least this big report progress while they are copied, once per step
+const PROGRESS_MIN_BYTES: u64 = 16 * 1024 * 1024;
+const PROGRESS_STEP_BYTES: u64 = 4 * 1024 * 1024;
+const COPY_BUFFER_BYTES: usize = 1024 * 1024;
+
+// What to do when the destination folder already has an item with the same name
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default, PartialEq, Eq, Type)]
+#[serde(rename_all = "snake_case")]
+pub enum CollisionPolicy {
+    #[default]
+    Skip,
+    Overwrite,
+    // Keep both, the new one as "name (2).ext"
+    Rename,
+}
+
+#[derive(Debug, Serialize, Clone, Type)]
+pub struct TransferOutcome {
+    pub path: String,
+    pub success: bool,
+    // Not transferred because of a name collision under the skip policy
+    pub skipped: bool,
+    pub destination: Option<String>,
+    pub error: Option<String>,
+}
+
+// Payload of a transfer-progress event: bytes of `path` copied so far
+#[derive(Debug, Serialize, Clone)]
+pub struct TransferProgress {
+    pub path: String,
+    pub copied: u64,
+    pub total: u64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Type)]
+#[serde(rename_all = "snake_case")]
+pub enum EntryKind {
+    File,
+    Folder,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Transfer {
+    Move,
+    Copy,
+}
+
+// Tables keyed by a file's path
+const PATH_TABLES: &[&str] = &[
+    "files",
+    "recent_files",
+    "favorite_files",
+    "file_tags",
+    "file_notes",
+    "pinned_results",
+    "file_categories",
+    "file_auto_tags",
+    "file_summaries",
+    "file_hashes",
+    "file_embeddings",
+    "content_chunks",
+];
+
+// `path` itself and, for a folder, everything under it
+fn path_clause(column: &str) -> String {
+    format!("({0} = ?1 OR {0} LIKE ?2 {1})", column, LIKE_ESCAPE)
+}
+
+fn under(path: &str) -> String {
+    format!("{}%", escape_like(&format!("{}{}", path.trim_end_matches(MAIN_SEPARATOR), MAIN_SEPARATOR)))
+}
+
+// Drop every row for `path` and what is under it
+fn forget(tx: &Transaction, path: &str) -> rusqlite::Result<()> {
+    let path_under = under(path);
+    for table in PATH_TABLES {
+        tx.execute(&format!("DELETE FROM {} WHERE {}", table, path_clause("path")), params![path, path_under])?;
+    }
+    Ok(())
+}
+
+// Point every row for `old` (and what was under it) at `new`. Rows already at `new` are stale,
+// or belong to an item that was just overwritten, and are dropped first.
+fn repath(tx: &Transaction, old_path: &Path, new_path: &Path) -> rusqlite::Result<()> {
+    let old_display = native_path::display(old_path);
+    let new_display = native_path::display(new_path);
+    let (old, new) = (old_display.as_str(), new_display.as_str());
+    let old_under = under(old);
+    forget(tx, new)?;
+    repath_raw(tx, old_path, new_path)?;
+    for table in PATH_TABLES {
+        tx.execute(
+            &format!("UPDATE {} SET path = ?3 || substr(path, length(?1) + 1) WHERE {}", table, path_clause("path")),
+            params![old, old_under, new],
+        )?;
+    }
+    // A renamed indexed root takes its files along
+    tx.execute(
+        &format!(
+            "UPDATE files SET root_directory = ?3 || substr(root_directory, length(?1) + 1) WHERE {}",
+            path_clause("root_directory")
+        ),
+        params![old, old_under, new],
+    )?;
+    tx.execute(
+        &format!(
+            "UPDATE recent_files SET root_directory = ?3 || substr(root_directory, length(?1) + 1) WHERE {}",
+            path_clause("root_directory")
+        ),
+        params![old, old_under, new],
+    )?;
+    tx.execute(
+        &format!("UPDATE indexed_directories SET path = ?3 || substr(path, length(?1) + 1) WHERE {}", path_clause("path")),
+        params![old, old_under, new],
+    )?;
+    Ok(())
+}
+
+// Exact paths kept for names the display form doesn't spell out (see native_path.rs) move along
+// with their rows. When `new` itself needs one, every row under it gets one.
+fn repath_raw(tx: &Transaction, old: &Path, new: &Path) -> rusqlite::Result<()> {
+    let old_str = native_path::display(old);
+    let new_needs_raw = native_path::split(new).1.is_some();
+    let rows: Vec<(i64, PathBuf)> = tx
+        .prepare(&format!("SELECT id, path, path_raw FROM files WHERE {} AND (path_raw IS NOT NULL OR ?3)", path_clause("path")))?
+        .query_map(params![old_str, under(&old_str), new_needs_raw], |row| {
+            Ok((row.get(0)?, native_path::stored(&row.get::<_, String>(1)?, row.get(2)?)))
+        })?
+        .collect::<rusqlite::Result<_>>()?;
+    for (id, path) in rows {
+        let Ok(rest) = path.strip_prefix(old) else {
+            continue;
+        };
+        // Joining an empty path would add a trailing separator
+        let moved = if rest.as_os_str().is_empty() { new.to_path_buf() } else { new.join(rest) };
+        tx.execute("UPDATE files SET path_raw = ?2 WHERE id = ?1", params![id, native_path::split(&moved).1])?;
+    }
+    Ok(())
+}
+
+// The entry's own name changed; what is under it keeps its names
+fn rename_entry(tx: &Transaction, path: &str, name: &str) -> rusqlite::Result<()> {
+    let romanized = transliterate::romanize(name);
+    tx.execute("UPDATE files SET name = ?2, name_romanized = ?3 WHERE path = ?1", params![path, name, romanized])?;
+    tx.execute("UPDATE recent_files SET name = ?2 WHERE path = ?1", params![path, name])?;
+    tx.execute("UPDATE favorite_files SET name = ?2 WHERE path = ?1", params![path, name])?;
+    Ok(())
+}
+
+// The indexed root a path lies under, the deepest one if roots are nested
+pub fn indexed_root(conn: &Connection, path: &Path) -> rusqlite::Result<Option<String>> {
+    let mut stmt = conn.prepare("SELECT path FROM indexed_directories")?;
+    let roots: Vec<String> = stmt.query_map([], |row| row.get(0))?.filter_map(|r| r.ok()).collect();
+    Ok(roots.into_iter().filter(|root| path.starts_with(root)).max_by_key(|root| root.len()))
+}
+
+// Moved rows belong to the root they landed under; outside every root they leave the index
+// (recents, favorites and tags still follow them, recents without a root)
+fn rehome(tx: &Transaction, path: &Path) -> rusqlite::Result<()> {
+    let path_str = native_path::display(path);
+    let path_under = under(&path_str);
+    let root = indexed_root(tx, path)?;
+    tx.execute(
+        &format!("UPDATE recent_files SET root_directory = ?3 WHERE {}", path_clause("path")),
+        params![path_str, path_under, root],
+    )?;
+    match root {
+        Some(root) => {
+            tx.execute(
+                &format!("UPDATE files SET root_directory = ?3 WHERE {}", path_clause("path")),
+                params![path_str, path_under, root],
+            )?;
+        }
+        None => {
+            tx.execute(&format!("DELETE FROM files WHERE {}", path_clause("path")), params![path_str, path_under])?;
+        }
+    }
+    Ok(())
+}
+
+// Add a copied file or folder to the index of the root it landed under, skipping hidden folders
+// (unless the root includes them) and exclusions as indexing does
+fn index_tree(tx: &Transaction, path: &Path) -> rusqlite::Result<()> {
+    let Some(root) = indexed_root(tx, path)? else {
+        return Ok(());
+    };
+    let exclusions = exclusions::load(tx).compile();
+    let include_hidden = hidden::stored_include_hidden(tx, &root);
+    let now = chrono::Utc::now().timestamp();
+    let mut stmt = tx.prepare(
+        "INSERT OR IGNORE INTO files (path, name, root_directory, indexed_at, modified_at, name_romanized, path_raw) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+    )?;
+    let entries = WalkDir::new(path).follow_links(false).into_iter().filter_entry(|e| {
+        let file_name = e.file_name().to_string_lossy();
+        let is_dir = e.file_type().is_dir();
+        let hidden = e.depth() > 0 && is_dir && hidden::skip_dir(&file_name, include_hidden);
+        !hidden && !exclusions.is_excluded(e.path(), &file_name, is_dir)
+    });
+    for entry in entries.filter_map(|e| e.ok()) {
+        let (path_str, raw) = native_path::split(entry.path());
+        let name = entry.file_name().to_string_lossy();
+        let modified_at = entry
+            .metadata()
+            .ok()
+            .and_then(|m| m.modified().ok())
+            .and_then(|time| time.duration_since(std::time::UNIX_EPOCH).ok())
+            .map(|elapsed| elapsed.as_secs() as i64);
+        stmt.execute(params![path_str, name, root, now, modified_at, transliterate::romanize(&name), raw])?;
+    }
+    drop(stmt);
+    trigram::sync(tx)?;
+    Ok(())
+}
+
+fn entry_for(conn: &Connection, path: &Path) -> FileEntry {
+    let path_str = native_path::display(path);
+    let (last_accessed, access_count) = conn
+        .query_row(
+            "SELECT last_accessed, access_count FROM recent_files WHERE path = ?1",
+            [&path_str],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .optional()
+        .ok()
+        .flatten()
+        .map_or((None, 0), |(last, count)| (Some(last), count));
+    let modified_at = fs::metadata(path)
+        .and_then(|m| m.modified())
+        .ok()
+        .and_then(|time| time.duration_since(std::time::UNIX_EPOCH).ok())
+        .map(|elapsed| elapsed.as_secs() as i64);
+    let mut entries = [FileEntry {
+        name: path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_else(|| path_str.clone()),
+        path: path_str,
+        last_accessed,
+        access_count,
+        modified_at,
+        age_bucket: None,
+        in_backup: false,
+        match_source: None,
+        content_snippet: None,
+    }];
+    assign_age_buckets(&mut entries);
+    mirrors::mark_backup_entries(conn, &mut entries);
+    let [entry] = entries;
+    entry
+}
+
+fn check_name(name: &str) -> Result<(), String> {
+    if name.is_empty() || name == "." || name == ".." {
+        return Err("The name is empty".to_string());
+    }
+    if name.contains(['/', '\\']) {
+        return Err("The name can't contain / or \\".to_string());
+    }
+    Ok(())
+}
+
+// "report.pdf" -> "report (2).pdf", the first name not taken in `dir`. The stem and extension
+// keep their exact bytes, so a name that isn't valid Unicode stays itself. None if every number
+// is taken.
+pub fn free_name(dir: &Path, name: &OsStr) -> Option<PathBuf> {
+    let name_path = Path::new(name);
+    let stem = name_path.file_stem().unwrap_or(name);
+    (2..u32::MAX)
+        .map(|n| {
+            let mut numbered = stem.to_os_string();
+            numbered.push(format!(" ({})", n));
+            if let Some(extension) = name_path.extension() {
+                numbered.push(".");
+                numbered.push(extension);
+            }
+            dir.join(numbered)
+        })
+        .find(|candidate| fs::symlink_metadata(candidate).is_err())
+}
+
+fn remove_path(path: &Path) -> io::Result<()> {
+    if fs::symlink_metadata(path)?.is_dir() {
+        fs::remove_dir_all(path)
+    } else {
+        fs::remove_file(path)
+    }
+}
+
+// Where `source` goes in `dest_dir`, or None when it is skipped
+fn target_for(source: &Path, dest_dir: &Path, policy: CollisionPolicy, transfer: Transfer) -> Result<Option<PathBuf>, String> {
+    let name = source.file_name().ok_or("Can't move or copy a root folder")?;
+    if dest_dir.starts_with(source) {
+        return Err("Can't put a folder inside itself".to_string());
+    }
+    let target = dest_dir.join(name);
+    if fs::symlink_metadata(&target).is_err() {
+        return Ok(Some(target));
+    }
+    // Copying an item into its own folder makes a numbered duplicate; anything else would
+    // replace the item with itself
+    if fs::canonicalize(&target).ok() == fs::canonicalize(source).ok() {
+        if transfer == Transfer::Copy && policy == CollisionPolicy::Rename {
+            return numbered(dest_dir, name).map(Some);
+        }
+        return Err(format!("It is already in {}", dest_dir.display()));
+    }
+    match policy {
+        CollisionPolicy::Skip => Ok(None),
+        CollisionPolicy::Overwrite => {
+            remove_path(&target).map_err(|e| format!("Could not replace {}: {}", target.display(), e))?;
+            Ok(Some(target))
+        }
+        CollisionPolicy::Rename => numbered(dest_dir, name).map(Some),
+    }
+}
+
+fn numbered(dest_dir: &Path, name: &OsStr) -> Result<PathBuf, String> {
+    free_name(dest_dir, name).ok_or_else(|| format!("No free name for {} in {}", Path::new(name).display(), dest_dir.display()))
+}
+
+fn copy_file(source: &Path, target: &Path, progress: &mut dyn FnMut(&Path, u64, u64)) -> io::Result<()> {
+    let metadata = fs::metadata(source)?;
+    let total = metadata.len();
+    if total < PROGRESS_MIN_BYTES {
+        fs::copy(source, target)?;
+    } else {
+        let mut reader = File::open(source)?;
+        let mut writer = File::create(target)?;
+        let mut buffer = vec![0; COPY_BUFFER_BYTES];
+        let (mut copied, mut reported) = (0, 0);
+        progress(source, 0, total);
+        loop {
+            let read = reader.read(&mut buffer)?;
+            if read == 0 {
+                break;
+            }
+            writer.write_all(&buffer[..read])?;
+            copied += read as u64;
+            if copied - reported >= PROGRESS_STEP_BYTES || copied == total {
+                progress(source, copied, total);
+                reported = copied;
+            }
+        }
+        fs::set_permissions(target, metadata.permissions())?;
+    }
+    // Copies keep the original's modification time, as file managers do
+    if let Ok(modified) = metadata.modified() {
+        File::options().write(true).open(target)?.set_modified(modified)?;
+    }
+    Ok(())
+}
+
+fn copy_tree(source: &Path, target: &Path, progress: &mut dyn FnMut(&Path, u64, u64)) -> io::Result<()> {
+    // Links are copied as links where the platform allows it, otherwise what they point to is
+    #[cfg(unix)]
+    {
+        if fs::symlink_metadata(source)?.is_symlink() {
+            return std::os::unix::fs::symlink(fs::read_link(source)?, target);
+        }
+    }
+    if !fs::metadata(source)?.is_dir() {
+        return copy_file(source, target, progress);
+    }
+    fs::create_dir(target)?;
+    for entry in fs::read_dir(source)? {
+        let entry = entry?;
+        copy_tree(&entry.path(), &target.join(entry.file_name()), progress)?;
+    }
+    Ok(())
+}
+
+// Move `source` to `target`; across file systems by copying and then removing the original
+fn move_path(source: &Path, target: &Path, progress: &mut dyn FnMut(&Path, u64, u64)) -> Result<(), String> {
+    match fs::rename(source, target) {
+        Ok(()) => Ok(()),
+        Err(e) if e.kind() == ErrorKind::CrossesDevices => {
+            if let Err(e) = copy_tree(source, target, progress) {
+                let _ = remove_path(target);
+                return Err(e.to_string());
+            }
+            remove_path(source).map_err(|e| format!("Copied to {} but could not remove the original: {}", target.display(), e))
+        }
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+// Update the database for one finished move or copy
+fn record(conn: &mut Connection, transfer: Transfer, source: &Path, target: &Path) -> rusqlite::Result<()> {
+    let tx = conn.transaction()?;
+    let target_str = native_path::display(target);
+    match transfer {
+        Transfer::Move => {
+            repath(&tx, source, target)?;
+            rename_entry(&tx, &target_str, &target.file_name().unwrap_or_default().to_string_lossy())?;
+            rehome(&tx, target)?;
+        }
+        Transfer::Copy => {
+            forget(&tx, &target_str)?;
+            index_tree(&tx, target)?;
+        }
+    }
+    tx.commit()
+}
+
+// The path on disk of a path the UI passed in, which may be a display form (see native_path.rs)
+fn on_disk(state: &AppState, path: &str) -> Result<PathBuf, String> {
+    db::blocking(|| state.readers.get().and_then(|db| native_path::resolve(&db, path).map_err(|e| e.to_string())))
+}
+
+async fn transfer(
+    transfer: Transfer,
+    paths: Vec<String>,
+    dest_dir: String,
+    policy: CollisionPolicy,
+    app: AppHandle,
+    state: State<'_, AppState>,
+) -> Result<Vec<TransferOutcome>, String> {
+    let dest = on_disk(&state, &dest_dir)?;
+    let dest = dest.as_path();
+    if !dest.is_dir() {
+        return Err(format!("Not a folder: {}", dest_dir));
+    }
+    let mut progress = |path: &Path, copied: u64, total: u64| {
+        let payload = TransferProgress { path: path.to_string_lossy().to_string(), copied, total };
+        if let Err(e) = app.emit(PROGRESS_EVENT, payload) {
+            warn!("Failed to send {} event: {}", PROGRESS_EVENT, e);
+        }
+    };
+
+    let mut outcomes = Vec::with_capacity(paths.len());
+    for path in paths {
+        let result = on_disk(&state, &path).and_then(|source| {
+            fs::symlink_metadata(&source).map_err(|e| format!("{}: {}", path, e))?;
+            Ok(target_for(&source, dest, policy, transfer)?.map(|target| (source, target)))
+        });
+        let (source, target) = match result {
+            Ok(Some(pair)) => pair,
+            Ok(None) | Err(_) => {
+                outcomes.push(TransferOutcome {
+                    skipped: matches!(result, Ok(None)),
+                    success: false,
+                    destination: None,
+                    error: result.err(),
+                    path,
+                });
+                continue;
+            }
+        };
+
+        let done = match transfer {
+            Transfer::Move => move_path(&source, &target, &mut progress),
+            Transfer::Copy => copy_tree(&source, &target, &mut progress).map_err(|e| {
+                let _ = remove_path(&target);
+                e.to_string()
+            }),
+        };
+        // A cross-device move whose original couldn't be removed left a copy behind
+        let landed = done.is_ok() || fs::symlink_metadata(&target).is_ok();
+        if landed {
+            let recorded_as = if done.is_ok() { transfer } else { Transfer::Copy };
+            db::write(&state.db, |db| {
+                if let Err(e) = record(db, recorded_as, &source, &target) {
+                    warn!("Transferred {} but could not update the index: {}", path, e);
+                }
+                Ok(())
+            })?;
+        }
+        if let Err(e) = &done {
+            warn!("Could not transfer {} to {}: {}", path, dest_dir, e);
+        }
+        outcomes.push(TransferOutcome {
+            success: done.is_ok(),
+            skipped: false,
+            destination: landed.then(|| native_path::display(&target)),
+            error: done.err(),
+            path,
+        });
+    }
+
+    if outcomes.iter().any(|outcome| outcome.destination.is_some()) {
+        invalidate_index(&state);
+    }
+    Ok(outcomes)
+}
+
+// Move files and folders into `dest_dir`. Name collisions are skipped unless `on_collision` says
+// otherwise; each path succeeds or fails on its own.
+#[tauri::command]
+#[specta::specta]
+pub async fn move_files(
+    paths: Vec<String>,
+    dest_dir: String,
+    on_collision: Option<CollisionPolicy>,
+    app: AppHandle,
+    state: State<'_, AppState>,
+) -> Result<Vec<TransferOutcome>, AppError> {
+    Ok(transfer(Transfer::Move, paths, dest_dir, on_collision.unwrap_or_default(), app, state).await?)
+}
+
+// Copy files and folders into `dest_dir`; copies under an indexed root are indexed right away
+#[tauri::command]
+#[specta::specta]
+pub async fn copy_files(
+    paths: Vec<String>,
+    dest_dir: String,
+    on_collision: Option<CollisionPolicy>,
+    app: AppHandle,
+    state: State<'_, AppState>,
+) -> Result<Vec<TransferOutcome>, AppError> {
+    Ok(transfer(Transfer::Copy, paths, dest_dir, on_collision.unwrap_or_default(), app, state).await?)
+}
+
+// Create an empty file or a folder in `parent_dir` and return its entry. Under an indexed root it
+// is indexed right away, so searching for it finds it.
+#[tauri::command]
+#[specta::specta]
+pub async fn create_entry(parent_dir: String, name: String, kind: EntryKind, state: State<'_, AppState>) -> Result<FileEntry, AppError> {
+    let name = name.trim().to_string();
+    check_name(&name)?;
+    let parent = on_disk(&state, &parent_dir)?;
+    if !parent.is_dir() {
+        return Err(AppError::new(ErrorCode::InvalidInput, format!("Not a folder: {}", parent_dir)));
+    }
+    let path = parent.join(&name);
+    let created = match kind {
+        EntryKind::File => File::create_new(&path).map(|_| ()),
+        EntryKind::Folder => fs::create_dir(&path),
+    };
+    created.map_err(|e| match e.kind() {
+        ErrorKind::AlreadyExists => format!("{} already exists", path.display()),
+        _ => format!("Could not create {}: {}", path.display(), e),
+    })?;
+
+    let entry = db::write(&state.db, |db| {
+        let path_str = native_path::display(&path);
+        let indexed = db.transaction().and_then(|tx| {
+            forget(&tx, &path_str)?;
+            index_tree(&tx, &path)?;
+            tx.commit()
+        });
+        if let Err(e) = indexed {
+            warn!("Created {} but could not index it: {}", path.display(), e);
+        }
+        Ok(entry_for(db, &path))
+    })?;
+    invalidate_index(&state);
+
+    info!("Created {}", path.display());
+    Ok(entry)
+}
+
+// Rename a file or folder in place and return its new entry
+#[tauri::command]
+#[specta::specta]
+pub async fn rename_file(path: String, new_name: String, state: State<'_, AppState>) -> Result<FileEntry, AppError> {
+    let new_name = new_name.trim().to_string();
+    check_name(&new_name)?;
+    let source = on_disk(&state, &path)?;
+    let source = source.as_path();
+    fs::symlink_metadata(source).map_err(|e| format!("{}: {}", path, e))?;
+    let target = source.with_file_name(&new_name);
+    if target == source {
+        return db::write(&state.db, |db| Ok(entry_for(db, source)));
+    }
+    // A change of case only is the same file on case-insensitive file systems
+    let same_file = fs::canonicalize(&target).ok().is_some_and(|existing| fs::canonicalize(source).ok() == Some(existing));
+    if fs::symlink_metadata(&target).is_ok() && !same_file {
+        return Err(AppError::new(ErrorCode::InvalidInput, format!("{} already exists", target.display())));
+    }
+
+    let target_str = native_path::display(&target);
+    let entry = db::write(&state.db, |db| {
+        fs::rename(source, &target).map_err(|e| format!("Could not rename {}: {}", path, e))?;
+        let updated = db
+            .transaction()
+            .and_then(|tx| {
+                repath(&tx, source, &target)?;
+                rename_entry(&tx, &target_str, &new_name)?;
+                tx.commit()
+            });
+        if let Err(e) = updated {
+            if let Err(undo) = fs::rename(&target, source) {
+                warn!("Could not rename {} back after a database error: {}", target_str, undo);
+            }
+            return Err(format!("Could not update the index for {}: {}", path, e).into());
+        }
+        Ok(entry_for(db, &target))
+    })?;
+    invalidate_index(&state);
+
+    info!("Renamed {} to {}", path, target_str);
+    Ok(entry)
+}
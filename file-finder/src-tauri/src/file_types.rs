@@ -0,0 +1,186 @@
+// Extension groups used as a search facet: `type:image vacation` only matches image files.
+// Built-in groups can be extended or overridden with user groups stored in the settings table.
+
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use std::collections::BTreeMap;
+use tauri::State;
+
+use crate::error::AppError;
+use crate::{db, escape_like, settings, AppState, LIKE_ESCAPE};
+
+pub const SETTINGS_KEY: &str = "extension_groups";
+
+const BUILTIN_GROUPS: &[(&str, &[&str])] = &[
+    ("image", &["jpg", "jpeg", "png", "gif", "bmp", "webp", "heic", "heif", "tif", "tiff", "svg", "ico", "raw", "cr2", "nef"]),
+    ("video", &["mp4", "avi", "mkv", "mov", "wmv", "webm", "m4v", "flv"]),
+    ("audio", &["mp3", "wav", "flac", "aac", "ogg", "m4a", "wma", "opus"]),
+    ("archive", &["zip", "rar", "7z", "tar", "gz", "tgz", "bz2", "xz"]),
+    ("doc", &["pdf", "doc", "docx", "odt", "rtf", "txt", "md", "pages"]),
+    ("spreadsheet", &["xls", "xlsx", "ods", "csv", "numbers"]),
+    ("presentation", &["ppt", "pptx", "odp", "key"]),
+    ("code", &[
+        "py", "java", "js", "jsx", "ts", "tsx", "rs", "go", "c", "h", "cpp", "cc", "hpp", "cs", "rb", "php",
+        "swift", "kt", "kts", "scala", "sh", "ps1", "html", "css", "json", "yaml", "yml", "toml", "xml", "sql",
+    ]),
+];
+
+// Alternative spellings accepted after `type:`
+const ALIASES: &[(&str, &str)] = &[
+    ("img", "image"),
+    ("photo", "image"),
+    ("picture", "image"),
+    ("movie", "video"),
+    ("music", "audio"),
+    ("document", "doc"),
+    ("sheet", "spreadsheet"),
+    ("slides", "presentation"),
+    ("source", "code"),
+    ("src", "code"),
+];
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq, Type)]
+#[serde(default)]
+pub struct ExtensionGroups {
+    // User-defined groups; a group with a built-in name replaces the built-in list
+    pub groups: BTreeMap<String, Vec<String>>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TypeFilter {
+    pub extensions: Vec<String>,
+}
+
+impl ExtensionGroups {
+    // Built-in groups merged with the user's, as shown in the settings UI
+    pub fn merged(&self) -> BTreeMap<String, Vec<String>> {
+        let mut merged: BTreeMap<String, Vec<String>> = BUILTIN_GROUPS
+            .iter()
+            .map(|(name, extensions)| (name.to_string(), extensions.iter().map(|e| e.to_string()).collect()))
+            .collect();
+        for (name, extensions) in &self.groups {
+            merged.insert(name.to_lowercase(), extensions.clone());
+        }
+        merged
+    }
+
+    // Group name for what the user typed, resolving aliases and plurals (images, docs).
+    // Names that are not a group come back lowercased with only the alias applied.
+    pub fn canonical_name(&self, group: &str) -> String {
+        let group = group.to_lowercase();
+        let canonical = ALIASES
+            .iter()
+            .find(|(alias, _)| *alias == group)
+            .map(|(_, name)| name.to_string())
+            .unwrap_or(group);
+        let merged = self.merged();
+        [canonical.clone(), canonical.trim_end_matches('s').to_string()]
+            .into_iter()
+            .find(|name| merged.contains_key(name))
+            .unwrap_or(canonical)
+    }
+
+    fn extensions_for(&self, group: &str) -> Vec<String> {
+        let name = self.canonical_name(group);
+        match self.merged().remove(&name) {
+            Some(extensions) => extensions,
+            // Not a group - treat it as a single extension, e.g. type:pdf
+            None => vec![name],
+        }
+    }
+
+    // Name of the group an extension belongs to, for display
+    pub fn group_of(&self, extension: &str) -> Option<String> {
+        let extension = extension.to_lowercase();
+        self.merged()
+            .into_iter()
+            .find(|(_, extensions)| extensions.iter().any(|e| e.eq_ignore_ascii_case(&extension)))
+            .map(|(name, _)| name)
+    }
+
+    // Split `type:` tokens out of a query. Returns the remaining query and the combined filter.
+    pub fn extract_type_filter(&self, query: &str) -> (String, Option<TypeFilter>) {
+        let mut extensions: Vec<String> = Vec::new();
+        let mut rest: Vec<&str> = Vec::new();
+
+        for token in query.split_whitespace() {
+            let value = token.strip_prefix("type:").or_else(|| token.strip_prefix("TYPE:"));
+            match value {
+                Some(value) if !value.is_empty() => {
+                    for group in value.split(',').filter(|g| !g.is_empty()) {
+                        for extension in self.extensions_for(group) {
+                            let extension = normalize_extension(&extension);
+                            if !extension.is_empty() && !extensions.contains(&extension) {
+                                extensions.push(extension);
+                            }
+                        }
+                    }
+                }
+                _ => rest.push(token),
+            }
+        }
+
+        if extensions.is_empty() {
+            return (query.to_string(), None);
+        }
+        (rest.join(" "), Some(TypeFilter { extensions }))
+    }
+}
+
+fn normalize_extension(extension: &str) -> String {
+    extension.trim().trim_start_matches('.').to_lowercase()
+}
+
+impl TypeFilter {
+    // SQL condition on the `name` column; extensions are bound as escaped literals
+    pub fn sql_clause(&self) -> String {
+        let conditions: Vec<String> = self
+            .extensions
+            .iter()
+            .map(|extension| format!("name LIKE '%.{}' {}", escape_like(extension).replace('\'', "''"), LIKE_ESCAPE))
+            .collect();
+        format!("({})", conditions.join(" OR "))
+    }
+
+    pub fn matches(&self, name: &str) -> bool {
+        match name.rsplit_once('.') {
+            Some((_, extension)) => self.extensions.iter().any(|e| e.eq_ignore_ascii_case(extension)),
+            None => false,
+        }
+    }
+}
+
+pub fn load(conn: &rusqlite::Connection) -> ExtensionGroups {
+    settings::load(conn, SETTINGS_KEY)
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn get_extension_groups(state: State<'_, AppState>) -> Result<BTreeMap<String, Vec<String>>, AppError> {
+    let groups = state.extension_groups.read().map_err(|e| e.to_string())?;
+    Ok(groups.merged())
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn set_extension_groups(groups: ExtensionGroups, state: State<'_, AppState>) -> Result<BTreeMap<String, Vec<String>>, AppError> {
+    let groups = ExtensionGroups {
+        groups: groups
+            .groups
+            .into_iter()
+            .map(|(name, extensions)| {
+                let extensions = extensions.iter().map(|e| normalize_extension(e)).filter(|e| !e.is_empty()).collect();
+                (name.trim().to_lowercase(), extensions)
+            })
+            .filter(|(name, _)| !name.is_empty())
+            .collect(),
+    };
+    db::write(&state.db, |db| settings::save(db, SETTINGS_KEY, &groups))?;
+    let merged = groups.merged();
+    *state.extension_groups.write().map_err(|e| e.to_string())? = groups;
+
+    // Cached results were filtered with the old groups
+    state.search_cache.write().map_err(|e| e.to_string())?.clear();
+
+    Ok(merged)
+}
@@ -0,0 +1,75 @@
+// Frecency: how much a file's history says about it, from how often and how recently it was
+// opened. Each open counts less the longer ago it was (halving every `frecency_half_life_days`),
+// and favorites count like a file opened `favorite_frecency` times just now, without aging.
+// The result is squashed into 0..1 and scaled to at most `frecency_bonus`, added to the match
+// score by every search path. So history can lift a file over similar matches, but a file
+// opened once months ago no longer beats a clearly better match.
+
+use rusqlite::{Connection, Result as SqlResult};
+use std::collections::HashMap;
+
+use crate::ranking::RankingConfig;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Usage {
+    pub access_count: i64,
+    pub last_accessed: Option<i64>,
+    pub favorite: bool,
+}
+
+impl Usage {
+    // "opened 3 times, last 2 days ago, favorite", for score traces
+    pub fn describe(&self, now: i64) -> String {
+        let mut parts = Vec::new();
+        if let Some(at) = self.last_accessed.filter(|_| self.access_count > 0) {
+            let days = (now - at).max(0) / (24 * 60 * 60);
+            let times = if self.access_count == 1 { "once".to_string() } else { format!("{} times", self.access_count) };
+            let when = match days {
+                0 => "today".to_string(),
+                1 => "yesterday".to_string(),
+                days => format!("{} days ago", days),
+            };
+            parts.push(format!("opened {}, last {}", times, when));
+        }
+        if self.favorite {
+            parts.push("favorite".to_string());
+        }
+        parts.join(", ")
+    }
+}
+
+// History of every opened or favorite file, by path
+pub fn load(conn: &Connection) -> SqlResult<HashMap<String, Usage>> {
+    let mut usage: HashMap<String, Usage> = HashMap::new();
+    let mut stmt = conn.prepare_cached("SELECT path, access_count, last_accessed FROM recent_files")?;
+    let rows = stmt.query_map([], |row| Ok((row.get::<_, String>(0)?, row.get::<_, Option<i64>>(1)?, row.get(2)?)))?;
+    for (path, access_count, last_accessed) in rows.filter_map(|r| r.ok()) {
+        let entry = usage.entry(path).or_default();
+        entry.access_count = access_count.unwrap_or(1);
+        entry.last_accessed = Some(last_accessed);
+    }
+    let mut stmt = conn.prepare_cached("SELECT path FROM favorite_files")?;
+    let rows = stmt.query_map([], |row| row.get::<_, String>(0))?;
+    for path in rows.filter_map(|r| r.ok()) {
+        usage.entry(path).or_default().favorite = true;
+    }
+    Ok(usage)
+}
+
+// 0 without history, 0.5 for a file opened once just now, approaching 1 with heavy recent use
+pub fn weight(usage: &Usage, now: i64, ranking: &RankingConfig) -> f64 {
+    let half_life = ranking.frecency_half_life_days.max(1) as f64 * 24.0 * 60.0 * 60.0;
+    let mut raw = 0.0;
+    if let Some(at) = usage.last_accessed.filter(|_| usage.access_count > 0) {
+        let age = (now - at).max(0) as f64;
+        raw += (1.0 + (usage.access_count as f64).ln()) * 0.5_f64.powf(age / half_life);
+    }
+    if usage.favorite {
+        raw += 1.0 + (ranking.favorite_frecency.max(1) as f64).ln();
+    }
+    raw / (1.0 + raw)
+}
+
+pub fn bonus(usage: &Usage, now: i64, ranking: &RankingConfig) -> i64 {
+    (weight(usage, now, ranking) * ranking.frecency_bonus as f64).round() as i64
+}
@@ -0,0 +1,73 @@
+// Fuzzy matching for fuzzy search, all on nucleo's matcher. It's used directly rather than through
+// the `Nucleo` streaming worker: a search gets its candidates from SQL in one batch and ranks them
+// with fuzzy_score, so there's nothing to stream.
+//
+// First pass: CandidateFilter picks out the candidates whose path (or romanized name) holds the
+// query's words as subsequences, and only those are scored. Every candidate is looked at; before,
+// only the first 300 were, so whether a file could match depended on the order SQLite returned its
+// rows in. Second pass: fuzzy_score's fallback scores names and paths with FuzzyScorer.
+//
+// Patterns are compiled once per search and matched from the rayon workers, each with a matcher of
+// its own, so concurrent searches don't wait on each other.
+
+use nucleo::pattern::{Atom, AtomKind, CaseMatching, Normalization, Pattern};
+use nucleo::{Config, Matcher, Utf32Str};
+use std::cell::RefCell;
+
+thread_local! {
+    // A matcher carries scratch buffers, so each thread reuses its own
+    static MATCHER: RefCell<Matcher> = RefCell::new(Matcher::new(Config::DEFAULT.match_paths()));
+}
+
+// The words of the query, each matched on its own. fuzzy_score treats `-`, `_` and `.` alike,
+// so punctuation can't be required here, and dropping it keeps fzf's `!`, `^`, `'` and `$`
+// operators out of the pattern.
+fn pattern(query: &str) -> String {
+    query.split(|c: char| !c.is_alphanumeric()).filter(|word| !word.is_empty()).collect::<Vec<_>>().join(" ")
+}
+
+pub struct CandidateFilter {
+    // None when the query has no words, which every candidate passes
+    pattern: Option<Pattern>,
+}
+
+impl CandidateFilter {
+    pub fn new(query: &str, case_sensitive: bool) -> Self {
+        let pattern = pattern(query);
+        if pattern.is_empty() {
+            return Self { pattern: None };
+        }
+        let case_matching = if case_sensitive { CaseMatching::Respect } else { CaseMatching::Ignore };
+        Self { pattern: Some(Pattern::parse(&pattern, case_matching, Normalization::Smart)) }
+    }
+
+    pub fn matches(&self, path: &str) -> bool {
+        let Some(pattern) = &self.pattern else {
+            return true;
+        };
+        MATCHER.with(|matcher| {
+            let mut chars = Vec::new();
+            pattern.score(Utf32Str::new(path, &mut chars), &mut matcher.borrow_mut()).is_some()
+        })
+    }
+}
+
+// fzf-style score of the whole query against a name or path, None if it isn't a subsequence.
+// Case is smart (an uppercase letter in the query must match) unless the search is case-sensitive.
+pub struct FuzzyScorer {
+    atom: Atom,
+}
+
+impl FuzzyScorer {
+    pub fn new(query: &str, case_sensitive: bool) -> Self {
+        let case_matching = if case_sensitive { CaseMatching::Respect } else { CaseMatching::Smart };
+        Self { atom: Atom::new(query, case_matching, Normalization::Smart, AtomKind::Fuzzy, false) }
+    }
+
+    pub fn score(&self, haystack: &str) -> Option<i64> {
+        MATCHER.with(|matcher| {
+            let mut chars = Vec::new();
+            self.atom.score(Utf32Str::new(haystack, &mut chars), &mut matcher.borrow_mut()).map(i64::from)
+        })
+    }
+}
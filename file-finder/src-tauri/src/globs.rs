@@ -0,0 +1,227 @@
+// Shell-style glob queries (`**`, `{a,b}`, `[0-9]`, `?`) backed by globset.
+// Patterns without a path separator match file names; patterns with one match full paths.
+
+use globset::{GlobBuilder, GlobMatcher};
+
+use crate::escape_like;
+
+// Brace expansion for the SQL prefilter is capped; beyond this the group becomes a wildcard
+const MAX_LIKE_ALTERNATIVES: usize = 16;
+
+pub struct GlobQuery {
+    matcher: GlobMatcher,
+    match_paths: bool,
+    // LIKE patterns for the file name, any of which a match must satisfy
+    pub name_like_patterns: Vec<String>,
+    // Longest literal directory segment of a path glob, for a `path LIKE` prefilter
+    pub path_literal: Option<String>,
+}
+
+// True for queries that read as globs rather than regexes. Regex idioms such as `log.*`
+// keep their existing regex handling; `*.*` and `src/**/*.rs` are treated as globs.
+pub fn is_glob_pattern(query: &str) -> bool {
+    if !query.contains(['*', '?', '[', '{']) || query.contains(['^', '$', '+', '(', ')', '|', '\\']) {
+        return false;
+    }
+    let chars: Vec<char> = query.chars().collect();
+    let regex_idiom = chars.windows(2).enumerate().any(|(i, pair)| {
+        pair[0] == '.' && matches!(pair[1], '*' | '+' | '?') && (i == 0 || chars[i - 1] != '*')
+    });
+    !regex_idiom
+}
+
+impl GlobQuery {
+    pub fn new(query: &str, case_sensitive: bool) -> Result<GlobQuery, String> {
+        let pattern = query.trim();
+        let match_paths = pattern.contains('/');
+
+        // Relative path globs may start anywhere in the tree: "src/**/*.rs" finds any src folder
+        let full_pattern = if match_paths && !pattern.starts_with('/') && !pattern.starts_with("**") {
+            format!("**/{}", pattern)
+        } else {
+            pattern.to_string()
+        };
+
+        let glob = GlobBuilder::new(&full_pattern)
+            .case_insensitive(!case_sensitive)
+            .literal_separator(true)
+            .backslash_escape(false)
+            .build()
+            .map_err(|e| format!("Invalid glob '{}': {}", pattern, e.kind()))?;
+
+        let (dirs, file_part) = match pattern.rfind('/') {
+            Some(pos) => (&pattern[..pos], &pattern[pos + 1..]),
+            None => ("", pattern),
+        };
+        let path_literal = dirs
+            .split('/')
+            .flat_map(|segment| segment.split(['*', '?', '[', ']', '{', '}', ',']))
+            .max_by_key(|literal| literal.chars().count())
+            .filter(|literal| !literal.is_empty())
+            .map(|literal| format!("%{}%", escape_like(literal)));
+
+        Ok(GlobQuery {
+            matcher: glob.compile_matcher(),
+            match_paths,
+            name_like_patterns: like_patterns(file_part),
+            path_literal,
+        })
+    }
+
+    pub fn is_match(&self, name: &str, path: &str) -> bool {
+        if !self.match_paths {
+            return self.matcher.is_match(name);
+        }
+        if cfg!(target_os = "windows") {
+            self.matcher.is_match(path.replace('\\', "/"))
+        } else {
+            self.matcher.is_match(path)
+        }
+    }
+}
+
+// Translate the file-name part of a glob into LIKE patterns, expanding {a,b} groups
+fn like_patterns(glob: &str) -> Vec<String> {
+    let mut expanded = vec![String::new()];
+    let mut chars = glob.chars().peekable();
+
+    while let Some(ch) = chars.next() {
+        match ch {
+            '{' => {
+                let mut group = String::new();
+                let mut depth = 1;
+                for inner in chars.by_ref() {
+                    match inner {
+                        '{' => depth += 1,
+                        '}' => depth -= 1,
+                        _ => {}
+                    }
+                    if depth == 0 {
+                        break;
+                    }
+                    group.push(inner);
+                }
+                let options: Vec<&str> = group.split(',').collect();
+                let nested = group.contains(['{', '*', '?', '[']);
+                if nested || expanded.len() * options.len() > MAX_LIKE_ALTERNATIVES {
+                    expanded.iter_mut().for_each(|p| p.push('%'));
+                } else {
+                    expanded = expanded
+                        .iter()
+                        .flat_map(|prefix| options.iter().map(move |option| format!("{}{}", prefix, escape_like(option))))
+                        .collect();
+                }
+            }
+            '[' => {
+                // A character class matches exactly one character
+                for inner in chars.by_ref() {
+                    if inner == ']' {
+                        break;
+                    }
+                }
+                expanded.iter_mut().for_each(|p| p.push('_'));
+            }
+            '*' => {
+                while chars.peek() == Some(&'*') {
+                    chars.next();
+                }
+                expanded
+                    .iter_mut()
+                    .filter(|p| !p.ends_with('%') || p.ends_with("\\%"))
+                    .for_each(|p| p.push('%'));
+            }
+            '?' => expanded.iter_mut().for_each(|p| p.push('_')),
+            c => {
+                let literal = escape_like(&c.to_string());
+                expanded.iter_mut().for_each(|p| p.push_str(&literal));
+            }
+        }
+    }
+
+    if expanded.iter().all(|p| p.is_empty()) {
+        return vec!["%".to_string()];
+    }
+    expanded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn glob(pattern: &str) -> GlobQuery {
+        GlobQuery::new(pattern, false).unwrap()
+    }
+
+    #[test]
+    fn globs_and_regexes_are_told_apart() {
+        assert!(is_glob_pattern("*.rs"));
+        assert!(is_glob_pattern("*.*"));
+        assert!(is_glob_pattern("src/**/*.rs"));
+        assert!(is_glob_pattern("report_{2023,2024}.pdf"));
+        assert!(is_glob_pattern("img[0-9].png"));
+        assert!(!is_glob_pattern("log.*"));
+        assert!(!is_glob_pattern("^report.*\\.pdf$"));
+        assert!(!is_glob_pattern("a+b*"));
+        assert!(!is_glob_pattern("report"));
+    }
+
+    #[test]
+    fn name_globs_match_the_file_name() {
+        let rust = glob("*.rs");
+        assert!(rust.is_match("main.rs", "/project/src/main.rs"));
+        assert!(rust.is_match("MAIN.RS", "/project/src/MAIN.RS"));
+        assert!(!rust.is_match("main.rs.bak", "/project/src/main.rs.bak"));
+        assert!(!GlobQuery::new("*.rs", true).unwrap().is_match("MAIN.RS", "/project/src/MAIN.RS"));
+    }
+
+    #[test]
+    fn path_globs_match_any_folder_of_that_name() {
+        let sources = glob("src/**/*.rs");
+        assert!(sources.is_match("lib.rs", "/home/me/project/src/lib.rs"));
+        assert!(sources.is_match("mod.rs", "/home/me/project/src/net/http/mod.rs"));
+        assert!(!sources.is_match("lib.rs", "/home/me/project/srcx/lib.rs"));
+        assert!(!sources.is_match("notes.md", "/home/me/project/src/notes.md"));
+    }
+
+    #[test]
+    fn path_globs_prefilter_on_their_longest_literal_folder() {
+        assert_eq!(glob("src/**/*.rs").path_literal.as_deref(), Some("%src%"));
+        assert_eq!(glob("photos/20*/img_*.jpg").path_literal.as_deref(), Some("%photos%"));
+        assert_eq!(glob("*.rs").path_literal, None);
+        assert_eq!(glob("**/*.rs").path_literal, None);
+    }
+
+    #[test]
+    fn invalid_globs_are_errors() {
+        let error = GlobQuery::new("[a", false).err().unwrap();
+        assert!(error.starts_with("Invalid glob '[a'"), "{}", error);
+    }
+
+    #[test]
+    fn wildcards_become_like_wildcards() {
+        assert_eq!(like_patterns("*.rs"), vec!["%.rs"]);
+        assert_eq!(like_patterns("img??.png"), vec!["img__.png"]);
+        assert_eq!(like_patterns("img[0-9].png"), vec!["img_.png"]);
+        assert_eq!(like_patterns("**report*"), vec!["%report%"]);
+        assert_eq!(like_patterns(""), vec!["%"]);
+    }
+
+    #[test]
+    fn literal_like_characters_are_escaped() {
+        assert_eq!(like_patterns("my_file*"), vec!["my\\_file%"]);
+        assert_eq!(like_patterns("100%*"), vec!["100\\%%"]);
+    }
+
+    #[test]
+    fn brace_groups_expand_into_alternatives() {
+        assert_eq!(like_patterns("report.{pdf,docx}"), vec!["report.pdf", "report.docx"]);
+        assert_eq!(like_patterns("{a,b}{c,d}"), vec!["ac", "ad", "bc", "bd"]);
+    }
+
+    #[test]
+    fn large_or_nested_groups_become_a_wildcard() {
+        assert_eq!(like_patterns("{a,b,c,d,e}{f,g,h,i}.txt"), vec!["a%.txt", "b%.txt", "c%.txt", "d%.txt", "e%.txt"]);
+        assert_eq!(like_patterns("x{a,{b,c}}.txt"), vec!["x%.txt"]);
+        assert_eq!(like_patterns("x{a*,b}.txt"), vec!["x%.txt"]);
+    }
+}
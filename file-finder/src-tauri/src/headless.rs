@@ -0,0 +1,269 @@
+// Headless mode (`--serve-stdio`): line-delimited JSON-RPC 2.0 over stdin/stdout, so editors and
+// other tools can embed the finder as a subprocess without the Tauri window. Requests are answered
+// in order. Like an LSP completion session, a client can send one search per keystroke: a search
+// still waiting when a newer one arrives is answered as cancelled instead of being run.
+// Logging goes to stderr; stdout carries nothing but protocol messages.
+
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::collections::VecDeque;
+use std::io::{BufRead, Write};
+use std::path::PathBuf;
+use std::sync::{mpsc, Arc};
+use tracing::{info, warn};
+
+use crate::{AppState, SearchOptions};
+
+pub const FLAG: &str = "--serve-stdio";
+
+// JSON-RPC error codes
+const PARSE_ERROR: i64 = -32700;
+const INVALID_REQUEST: i64 = -32600;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+// The operation itself failed; the message is the error the UI would show
+const SERVER_ERROR: i64 = -32000;
+// Same code LSP uses for requests dropped in favour of a newer one
+const REQUEST_CANCELLED: i64 = -32800;
+
+const METHODS: &[&str] = &["initialize", "search", "open", "index", "status", "shutdown", "$/cancelRequest"];
+
+#[derive(Deserialize)]
+struct Request {
+    // Absent for notifications, which get no response
+    id: Option<Value>,
+    method: String,
+    #[serde(default)]
+    params: Value,
+}
+
+#[derive(Deserialize)]
+struct SearchParams {
+    query: String,
+    #[serde(default)]
+    options: Option<SearchOptions>,
+}
+
+#[derive(Deserialize)]
+struct OpenParams {
+    path: String,
+}
+
+#[derive(Deserialize)]
+struct IndexParams {
+    // None re-indexes the home directory from scratch, like "Start Indexing"
+    #[serde(default)]
+    path: Option<String>,
+    #[serde(default)]
+    mirror: Option<bool>,
+    // Levels below the folder to index, 0 for all; None keeps what the folder had
+    #[serde(default)]
+    max_depth: Option<u32>,
+    // Index hidden folders too; None keeps what the folder had
+    #[serde(default)]
+    include_hidden: Option<bool>,
+}
+
+#[derive(Deserialize)]
+struct CancelParams {
+    id: Value,
+}
+
+struct RpcError {
+    code: i64,
+    message: String,
+}
+
+impl RpcError {
+    fn new(code: i64, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+        }
+    }
+}
+
+// Write one message as a single line. Also called from indexing tasks, so the lock keeps lines whole.
+fn send(message: &Value) {
+    let mut stdout = std::io::stdout().lock();
+    if writeln!(stdout, "{}", message).and_then(|_| stdout.flush()).is_err() {
+        warn!("headless: failed to write to stdout");
+    }
+}
+
+fn respond(id: Value, result: Result<Value, RpcError>) {
+    let message = match result {
+        Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+        Err(error) => json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "error": { "code": error.code, "message": error.message },
+        }),
+    };
+    send(&message);
+}
+
+fn notify(method: &str, params: Value) {
+    send(&json!({ "jsonrpc": "2.0", "method": method, "params": params }));
+}
+
+fn parse_request(line: &str) -> Result<Request, RpcError> {
+    let value: Value = serde_json::from_str(line).map_err(|e| RpcError::new(PARSE_ERROR, e.to_string()))?;
+    serde_json::from_value(value).map_err(|e| RpcError::new(INVALID_REQUEST, e.to_string()))
+}
+
+fn params<T: DeserializeOwned>(params: Value) -> Result<T, RpcError> {
+    // Omitted params are treated as an empty object so all-optional params can be left out
+    let params = if params.is_null() { json!({}) } else { params };
+    serde_json::from_value(params).map_err(|e| RpcError::new(INVALID_PARAMS, e.to_string()))
+}
+
+fn to_value<T: serde::Serialize>(value: T) -> Result<Value, RpcError> {
+    serde_json::to_value(value).map_err(|e| RpcError::new(SERVER_ERROR, e.to_string()))
+}
+
+fn server_error(message: String) -> RpcError {
+    RpcError::new(SERVER_ERROR, message)
+}
+
+// Serve requests until stdin closes or the client sends `shutdown`
+pub fn serve_stdio(state: AppState) {
+    info!("Serving JSON-RPC on stdin/stdout");
+    // Shared with indexing tasks, which invalidate the search cache when they finish
+    let state = Arc::new(state);
+
+    // Reading on its own thread lets the loop see what queued up while a search was running
+    let (sender, receiver) = mpsc::channel::<String>();
+    std::thread::spawn(move || {
+        for line in std::io::stdin().lock().lines() {
+            let Ok(line) = line else { break };
+            if sender.send(line).is_err() {
+                break;
+            }
+        }
+    });
+
+    let mut queue: VecDeque<Request> = VecDeque::new();
+    loop {
+        if queue.is_empty() {
+            match receiver.recv() {
+                Ok(line) => enqueue(&mut queue, &line),
+                Err(_) => break,
+            }
+        }
+        for line in receiver.try_iter() {
+            enqueue(&mut queue, &line);
+        }
+
+        let Some(request) = queue.pop_front() else {
+            continue;
+        };
+        let superseded = request.method == "search"
+            && queue.iter().any(|queued| queued.method == "search" && queued.id.is_some());
+        if superseded {
+            if let Some(id) = request.id {
+                respond(id, Err(RpcError::new(REQUEST_CANCELLED, "Superseded by a newer search")));
+            }
+            continue;
+        }
+
+        let shutdown = request.method == "shutdown";
+        let result = handle(&request.method, request.params, &state);
+        if let Some(id) = request.id {
+            respond(id, result);
+        } else if let Err(error) = result {
+            warn!("headless: notification '{}' failed: {}", request.method, error.message);
+        }
+        if shutdown {
+            break;
+        }
+    }
+    crate::local_llm::shutdown();
+}
+
+// Parse an incoming line. Malformed lines are answered right away, and cancellations take
+// effect immediately by answering the matching queued request.
+fn enqueue(queue: &mut VecDeque<Request>, line: &str) {
+    if line.trim().is_empty() {
+        return;
+    }
+    let request = match parse_request(line) {
+        Ok(request) => request,
+        Err(error) => {
+            respond(Value::Null, Err(error));
+            return;
+        }
+    };
+
+    if request.method == "$/cancelRequest" {
+        // Requests that are already answered are not in the queue, so cancelling them does nothing
+        if let Ok(CancelParams { id }) = params(request.params) {
+            if let Some(position) = queue.iter().position(|queued| queued.id.as_ref() == Some(&id)) {
+                queue.remove(position);
+                respond(id, Err(RpcError::new(REQUEST_CANCELLED, "Request cancelled")));
+            }
+        }
+        return;
+    }
+    queue.push_back(request);
+}
+
+fn handle(method: &str, raw_params: Value, state: &Arc<AppState>) -> Result<Value, RpcError> {
+    match method {
+        "initialize" => Ok(json!({
+            "name": env!("CARGO_PKG_NAME"),
+            "version": env!("CARGO_PKG_VERSION"),
+            "apiVersion": crate::compat::API_VERSION,
+            "methods": METHODS,
+        })),
+        "search" => {
+            let SearchParams { query, options } = params(raw_params)?;
+            let options = options.unwrap_or_default();
+            let response = tauri::async_runtime::block_on(crate::natural_search(&query, &options, state)).map_err(server_error)?;
+            to_value(response)
+        }
+        "open" => {
+            let OpenParams { path } = params(raw_params)?;
+            crate::record_open(&path, state).map_err(|e| server_error(e.message))?;
+            let native = state.readers.get().and_then(|db| crate::native_path::resolve(&db, &path).map_err(|e| e.to_string())).map_err(server_error)?;
+            opener::open(crate::native_path::openable(native)).map_err(|e| server_error(e.to_string()))?;
+            Ok(Value::Null)
+        }
+        "index" => {
+            let IndexParams { path, mirror, max_depth, include_hidden } = params(raw_params)?;
+            start_index(path, mirror, max_depth, include_hidden, state.clone())
+        }
+        "status" => to_value(crate::index_status(state).map_err(|e| server_error(e.message))?),
+        "shutdown" => Ok(Value::Null),
+        _ => Err(RpcError::new(METHOD_NOT_FOUND, format!("Unknown method: {}", method))),
+    }
+}
+
+// Indexing runs in the background like in the UI; `indexFinished` is sent when it is done
+fn start_index(
+    path: Option<String>,
+    mirror: Option<bool>,
+    max_depth: Option<u32>,
+    include_hidden: Option<bool>,
+    state: Arc<AppState>,
+) -> Result<Value, RpcError> {
+    let (root, clear_existing) = match path {
+        Some(path) => {
+            let root = PathBuf::from(&path);
+            if !root.is_dir() {
+                return Err(RpcError::new(INVALID_PARAMS, format!("Not a folder: {}", path)));
+            }
+            (root, false)
+        }
+        None => (dirs::home_dir().ok_or_else(|| server_error("Could not find home directory".to_string()))?, true),
+    };
+
+    let root_str = root.to_string_lossy().to_string();
+    tauri::async_runtime::spawn_blocking(move || {
+        crate::index_directory(&state.db_path, &root, clear_existing, mirror, max_depth, include_hidden);
+        crate::invalidate_index(&state);
+        notify("indexFinished", json!({ "path": root.to_string_lossy() }));
+    });
+    Ok(json!({ "path": root_str }))
+}
@@ -0,0 +1,40 @@
+// Hidden folders (names starting with a dot) while indexing and ranking. By default a root skips
+// them, apart from a few that hold project files people look for, like `.github` workflows. A
+// root indexed with `include_hidden` walks into all of them except `.git`, whose object store is
+// never worth listing. Files inside hidden folders rank below the same match elsewhere instead
+// of being left out, so `.config/nvim/init.lua` is found without crowding out visible files.
+
+use rusqlite::Connection;
+use std::path::{Component, Path};
+
+// Hidden folders indexed even when a root leaves hidden folders out, and not down-ranked
+const ALWAYS_INDEXED: &[&str] = &[".github", ".gitlab", ".circleci", ".devcontainer", ".husky"];
+
+// Hidden folders skipped even when a root includes hidden folders
+const NEVER_INDEXED: &[&str] = &[".git"];
+
+fn is_hidden_name(name: &str) -> bool {
+    name.starts_with('.') && name != "." && name != ".." && !ALWAYS_INDEXED.contains(&name)
+}
+
+// Whether the walker should leave out the folder `name`. Dotfiles are always indexed.
+pub fn skip_dir(name: &str, include_hidden: bool) -> bool {
+    if NEVER_INDEXED.contains(&name) {
+        return true;
+    }
+    !include_hidden && is_hidden_name(name)
+}
+
+// Whether `path` is inside a hidden folder, for the ranking penalty
+pub fn in_hidden_folder(path: &str) -> bool {
+    Path::new(path)
+        .parent()
+        .is_some_and(|parent| parent.components().any(|c| matches!(c, Component::Normal(name) if is_hidden_name(&name.to_string_lossy()))))
+}
+
+// Whether a root was last indexed with hidden folders, false for roots that never set it
+pub fn stored_include_hidden(conn: &Connection, root: &str) -> bool {
+    conn.query_row("SELECT include_hidden FROM indexed_directories WHERE path = ?1", [root], |row| row.get::<_, Option<i32>>(0))
+        .unwrap_or(None)
+        == Some(1)
+}
@@ -0,0 +1,248 @@
+// System file icons for result rows. Icons are asked from the OS (shell icon on Windows,
+// NSWorkspace on macOS, the freedesktop icon theme on Linux), converted to PNG and cached on
+// disk, per extension for ordinary files and per path for files that carry their own icon.
+
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::error::{AppError, ErrorCode};
+
+// Edge length in pixels of the icons we ask for
+const ICON_SIZE: u32 = 32;
+
+// Executables, shortcuts and bundles have an icon of their own rather than one per type
+const PER_FILE_EXTENSIONS: &[&str] = &["exe", "ico", "lnk", "url", "app", "appimage", "desktop"];
+
+fn cache_dir() -> PathBuf {
+    dirs::data_local_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join("file-finder")
+        .join("icons")
+}
+
+fn extension_of(path: &Path) -> Option<String> {
+    path.extension().map(|ext| ext.to_string_lossy().to_lowercase())
+}
+
+// Name of the cached PNG for this path
+fn cache_key(path: &Path) -> String {
+    if path.is_dir() {
+        return "folder".to_string();
+    }
+    match extension_of(path) {
+        Some(ext) if !PER_FILE_EXTENSIONS.contains(&ext.as_str()) && ext.chars().all(|c| c.is_ascii_alphanumeric()) => {
+            format!("ext-{}", ext)
+        }
+        Some(_) => {
+            let digest = Sha256::digest(path.to_string_lossy().as_bytes());
+            let hex: String = digest.iter().take(12).map(|byte| format!("{:02x}", byte)).collect();
+            format!("file-{}", hex)
+        }
+        None => "no-ext".to_string(),
+    }
+}
+
+// PNG bytes of the system icon for a file or folder. Errors mean no icon could be found,
+// and the row keeps its extension badge.
+#[tauri::command]
+#[specta::specta]
+pub async fn get_file_icon(path: String) -> Result<Vec<u8>, AppError> {
+    let source = PathBuf::from(&path);
+    if !source.exists() {
+        return Err(AppError::new(ErrorCode::NotFound, format!("File not found: {}", path)));
+    }
+
+    let cached = cache_dir().join(format!("{}-{}.png", cache_key(&source), ICON_SIZE));
+    if let Ok(bytes) = fs::read(&cached) {
+        return Ok(bytes);
+    }
+
+    fs::create_dir_all(cache_dir())?;
+    extract_icon(&source, &cached)?;
+    Ok(fs::read(&cached).map_err(|e| format!("No icon for {}: {}", path, e))?)
+}
+
+#[cfg(target_os = "windows")]
+fn extract_icon(source: &Path, target: &Path) -> Result<(), String> {
+    use std::os::windows::process::CommandExt;
+
+    // The icon Explorer shows for the file, as returned by SHGetFileInfo
+    const SCRIPT: &str = "Add-Type -AssemblyName System.Drawing; \
+        $icon = [System.Drawing.Icon]::ExtractAssociatedIcon($env:FILE_FINDER_ICON_SOURCE); \
+        $icon.ToBitmap().Save($env:FILE_FINDER_ICON_TARGET, [System.Drawing.Imaging.ImageFormat]::Png)";
+    // Keep PowerShell from flashing a console window
+    const CREATE_NO_WINDOW: u32 = 0x0800_0000;
+
+    let status = std::process::Command::new("powershell")
+        .args(["-NoProfile", "-NonInteractive", "-Command", SCRIPT])
+        .env("FILE_FINDER_ICON_SOURCE", source)
+        .env("FILE_FINDER_ICON_TARGET", target)
+        .creation_flags(CREATE_NO_WINDOW)
+        .status()
+        .map_err(|e| e.to_string())?;
+    if !status.success() {
+        return Err(format!("Could not extract the icon of {}", source.display()));
+    }
+    Ok(())
+}
+
+#[cfg(target_os = "macos")]
+fn extract_icon(source: &Path, target: &Path) -> Result<(), String> {
+    // NSWorkspace through the JavaScript for Automation bridge, so no Objective-C bindings are needed
+    let script = format!(
+        "ObjC.import('AppKit');
+         const env = $.NSProcessInfo.processInfo.environment;
+         const image = $.NSWorkspace.sharedWorkspace.iconForFile(env.objectForKey('FILE_FINDER_ICON_SOURCE'));
+         image.setSize($.NSMakeSize({0}, {0}));
+         const rep = $.NSBitmapImageRep.imageRepWithData(image.TIFFRepresentation);
+         const png = rep.representationUsingTypeProperties($.NSBitmapImageFileTypePNG, $());
+         png.writeToFileAtomically(env.objectForKey('FILE_FINDER_ICON_TARGET'), true);",
+        ICON_SIZE
+    );
+    let status = std::process::Command::new("osascript")
+        .args(["-l", "JavaScript", "-e", &script])
+        .env("FILE_FINDER_ICON_SOURCE", source)
+        .env("FILE_FINDER_ICON_TARGET", target)
+        .status()
+        .map_err(|e| e.to_string())?;
+    if !status.success() {
+        return Err(format!("Could not extract the icon of {}", source.display()));
+    }
+    Ok(())
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+fn extract_icon(source: &Path, target: &Path) -> Result<(), String> {
+    let names = linux::icon_names(source);
+    let icon = linux::find_theme_icon(&names)
+        .ok_or_else(|| format!("No PNG icon in the icon theme for {}", source.display()))?;
+    fs::copy(icon, target).map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+// freedesktop.org lookups: extension -> MIME type (shared-mime-info) -> icon names -> themed PNG
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+pub mod linux {
+    use std::collections::HashSet;
+    use std::fs;
+    use std::path::{Path, PathBuf};
+    use walkdir::WalkDir;
+
+    use super::{extension_of, ICON_SIZE};
+
+    pub fn data_dirs() -> Vec<PathBuf> {
+        let mut dirs: Vec<PathBuf> = dirs::data_dir().into_iter().collect();
+        let system = std::env::var("XDG_DATA_DIRS").unwrap_or_else(|_| "/usr/local/share:/usr/share".to_string());
+        dirs.extend(system.split(':').filter(|dir| !dir.is_empty()).map(PathBuf::from));
+        dirs
+    }
+
+    // MIME type registered for `*.ext` in the globs2 files
+    pub fn mime_type(ext: &str) -> Option<String> {
+        let pattern = format!("*.{}", ext);
+        data_dirs().into_iter().find_map(|dir| {
+            let globs = fs::read_to_string(dir.join("mime").join("globs2")).ok()?;
+            globs.lines().find_map(|line| {
+                // weight:mime/type:glob
+                let mut fields = line.splitn(3, ':');
+                let (_, mime, glob) = (fields.next()?, fields.next()?, fields.next()?);
+                let glob = glob.split(':').next()?;
+                glob.eq_ignore_ascii_case(&pattern).then(|| mime.to_string())
+            })
+        })
+    }
+
+    fn generic_icon(mime: &str) -> Option<String> {
+        data_dirs().into_iter().find_map(|dir| {
+            let icons = fs::read_to_string(dir.join("mime").join("generic-icons")).ok()?;
+            icons.lines().find_map(|line| {
+                let (entry, icon) = line.split_once(':')?;
+                (entry == mime).then(|| icon.to_string())
+            })
+        })
+    }
+
+    // Icon names to try, most specific first
+    pub fn icon_names(path: &Path) -> Vec<String> {
+        if path.is_dir() {
+            return vec!["folder".to_string(), "inode-directory".to_string()];
+        }
+        let mut names = Vec::new();
+        if let Some(mime) = extension_of(path).and_then(|ext| mime_type(&ext)) {
+            names.push(mime.replace('/', "-"));
+            if let Some(generic) = generic_icon(&mime) {
+                names.push(generic);
+            }
+            if let Some((media, _)) = mime.split_once('/') {
+                names.push(format!("{}-x-generic", media));
+            }
+        }
+        names.push("text-x-generic".to_string());
+        names.push("application-x-generic".to_string());
+
+        let mut seen = HashSet::new();
+        names.retain(|name| seen.insert(name.clone()));
+        names
+    }
+
+    // Theme from the desktop settings, with common fallbacks and hicolor last as the spec requires
+    fn themes() -> Vec<String> {
+        let mut themes = Vec::new();
+        let configured = std::process::Command::new("gsettings")
+            .args(["get", "org.gnome.desktop.interface", "icon-theme"])
+            .output()
+            .ok()
+            .filter(|output| output.status.success())
+            .map(|output| String::from_utf8_lossy(&output.stdout).trim().trim_matches('\'').to_string());
+        if let Some(theme) = configured.filter(|theme| !theme.is_empty()) {
+            themes.push(theme);
+        }
+        for theme in ["Adwaita", "breeze", "hicolor"] {
+            if !themes.iter().any(|t| t == theme) {
+                themes.push(theme.to_string());
+            }
+        }
+        themes
+    }
+
+    // Pixel size from a theme subdirectory such as "48x48" or "48x48@2"
+    fn dir_size(path: &Path) -> Option<u32> {
+        path.components().find_map(|component| {
+            let name = component.as_os_str().to_str()?;
+            let (width, _) = name.split_once('x')?;
+            width.parse().ok()
+        })
+    }
+
+    // Best PNG for the first name any theme has: the earliest name wins, then the size closest to ICON_SIZE
+    pub fn find_theme_icon(names: &[String]) -> Option<PathBuf> {
+        let icon_dirs: Vec<PathBuf> = data_dirs().into_iter().map(|dir| dir.join("icons")).collect();
+        for theme in themes() {
+            let mut best: Option<(usize, u32, PathBuf)> = None;
+            for root in icon_dirs.iter().map(|dir| dir.join(&theme)).filter(|dir| dir.is_dir()) {
+                for entry in WalkDir::new(&root).max_depth(3).into_iter().filter_map(|e| e.ok()) {
+                    let file_name = entry.file_name().to_string_lossy();
+                    let Some(stem) = file_name.strip_suffix(".png") else {
+                        continue;
+                    };
+                    let Some(rank) = names.iter().position(|name| name == stem) else {
+                        continue;
+                    };
+                    let relative = entry.path().strip_prefix(&root).unwrap_or(entry.path());
+                    let distance = dir_size(relative).map_or(u32::MAX, |size| size.abs_diff(ICON_SIZE));
+                    let better = best
+                        .as_ref()
+                        .is_none_or(|(best_rank, best_distance, _)| (rank, distance) < (*best_rank, *best_distance));
+                    if better {
+                        best = Some((rank, distance, entry.into_path()));
+                    }
+                }
+            }
+            if let Some((_, _, path)) = best {
+                return Some(path);
+            }
+        }
+        None
+    }
+}
@@ -0,0 +1,152 @@
+// Incremental matching while typing. A plain word search keeps the candidates it read when it
+// read all of them, i.e. the planner didn't stop early. The next search whose word contains that
+// word (`repo` → `report`), with the same filters and options, can only match a subset of them, so
+// it filters and re-scores those in memory instead of scanning the index again. Any other query,
+// a shorter one included, runs a full search, and indexing clears the set (see
+// invalidate_search_cache).
+
+use std::sync::Mutex;
+
+use crate::search_sql::{NameMatch, SqlFilter};
+use crate::{locale, transliterate, SearchOptions};
+
+type File = (String, String, Option<i64>);
+
+// Candidates of the last plain word search that read all of them
+pub struct CandidateSet {
+    // Filters, options and name comparison of the search
+    key: String,
+    // The word as compared with names (see `comparable`)
+    word: String,
+    files: Vec<File>,
+}
+
+pub type LastCandidates = Mutex<Option<CandidateSet>>;
+
+pub fn key(name_match: NameMatch, filter: &SqlFilter, options: &SearchOptions) -> String {
+    format!("{:?}|{:?}|{:?}", name_match, filter, options)
+}
+
+// The word in the form the SQL compares names with
+fn comparable(name_match: NameMatch, word: &str) -> String {
+    match name_match {
+        NameMatch::Like | NameMatch::FoldedLike | NameMatch::Romanized => locale::fold(word),
+        NameMatch::Glob => word.to_string(),
+    }
+}
+
+// Same result as the prefilter's `%word%` condition on the name
+fn name_matches(name_match: NameMatch, name: &str, word: &str) -> bool {
+    match name_match {
+        NameMatch::Like => name.to_ascii_lowercase().contains(word),
+        NameMatch::FoldedLike => locale::fold(name).contains(word),
+        NameMatch::Glob => name.contains(word),
+        // Romanized the way indexing stores it in `name_romanized`
+        NameMatch::Romanized => {
+            locale::fold(name).contains(word)
+                || transliterate::romanize(name).is_some_and(|romanized| romanized.to_ascii_lowercase().contains(word))
+        }
+    }
+}
+
+// Candidates for `word` taken from the last set, if it covers them
+pub fn reuse(last: &LastCandidates, key: &str, name_match: NameMatch, word: &str) -> Option<Vec<File>> {
+    let word = comparable(name_match, word);
+    let last = last.lock().ok()?;
+    let set = last.as_ref().filter(|set| set.key == key && word.contains(&set.word))?;
+    Some(set.files.iter().filter(|(_, name, _)| name_matches(name_match, name, &word)).cloned().collect())
+}
+
+// Keep the candidates of a search that read all of them
+pub fn remember(last: &LastCandidates, key: String, name_match: NameMatch, word: &str, files: &[File]) {
+    let word = comparable(name_match, word);
+    if let Ok(mut last) = last.lock() {
+        *last = Some(CandidateSet { key, word, files: files.to_vec() });
+    }
+}
+
+pub fn clear(last: &LastCandidates) {
+    if let Ok(mut last) = last.lock() {
+        *last = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn files(names: &[&str]) -> Vec<File> {
+        names.iter().map(|name| (format!("/docs/{}", name), name.to_string(), None)).collect()
+    }
+
+    fn names(files: Option<Vec<File>>) -> Option<Vec<String>> {
+        files.map(|files| files.into_iter().map(|(_, name, _)| name).collect())
+    }
+
+    fn plain_key(name_match: NameMatch) -> String {
+        key(name_match, &SqlFilter::default(), &SearchOptions::default())
+    }
+
+    #[test]
+    fn a_longer_word_filters_the_last_candidates() {
+        let last = LastCandidates::default();
+        let key = plain_key(NameMatch::Like);
+        remember(&last, key.clone(), NameMatch::Like, "repo", &files(&["report.txt", "repo.md", "REPORTS.pdf"]));
+        assert_eq!(
+            names(reuse(&last, &key, NameMatch::Like, "Report")),
+            Some(vec!["report.txt".to_string(), "REPORTS.pdf".to_string()])
+        );
+        // The word only has to contain the last one
+        assert_eq!(names(reuse(&last, &key, NameMatch::Like, "xrepo")), Some(vec![]));
+    }
+
+    #[test]
+    fn shorter_or_unrelated_words_search_again() {
+        let last = LastCandidates::default();
+        let key = plain_key(NameMatch::Like);
+        remember(&last, key.clone(), NameMatch::Like, "repo", &files(&["report.txt"]));
+        assert_eq!(names(reuse(&last, &key, NameMatch::Like, "rep")), None);
+        assert_eq!(names(reuse(&last, &key, NameMatch::Like, "notes")), None);
+    }
+
+    #[test]
+    fn other_filters_or_options_search_again() {
+        let last = LastCandidates::default();
+        remember(&last, plain_key(NameMatch::Like), NameMatch::Like, "repo", &files(&["report.txt"]));
+
+        let mut filter = SqlFilter::default();
+        filter.and_bound(("modified_at >= ?".to_string(), vec![1_700_000_000]));
+        let filtered = key(NameMatch::Like, &filter, &SearchOptions::default());
+        assert_eq!(names(reuse(&last, &filtered, NameMatch::Like, "report")), None);
+
+        let options = SearchOptions { filename_only: !SearchOptions::default().filename_only, ..SearchOptions::default() };
+        let other_options = key(NameMatch::Like, &SqlFilter::default(), &options);
+        assert_eq!(names(reuse(&last, &other_options, NameMatch::Like, "report")), None);
+    }
+
+    #[test]
+    fn folded_names_match_folded_words() {
+        let last = LastCandidates::default();
+        let key = plain_key(NameMatch::FoldedLike);
+        remember(&last, key.clone(), NameMatch::FoldedLike, "stra", &files(&["Straße.txt", "Strand.jpg"]));
+        assert_eq!(names(reuse(&last, &key, NameMatch::FoldedLike, "STRASSE")), Some(vec!["Straße.txt".to_string()]));
+    }
+
+    #[test]
+    fn glob_names_keep_their_case() {
+        let last = LastCandidates::default();
+        let key = plain_key(NameMatch::Glob);
+        remember(&last, key.clone(), NameMatch::Glob, "Rep", &files(&["Report.txt", "report.txt"]));
+        assert_eq!(names(reuse(&last, &key, NameMatch::Glob, "Repo")), Some(vec!["Report.txt".to_string()]));
+        assert_eq!(names(reuse(&last, &key, NameMatch::Glob, "repo")), None);
+    }
+
+    #[test]
+    fn clearing_forgets_the_candidates() {
+        let last = LastCandidates::default();
+        let key = plain_key(NameMatch::Like);
+        remember(&last, key.clone(), NameMatch::Like, "repo", &files(&["report.txt"]));
+        clear(&last);
+        assert_eq!(names(reuse(&last, &key, NameMatch::Like, "report")), None);
+    }
+}
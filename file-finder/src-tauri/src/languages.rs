@@ -0,0 +1,235 @@
+// Languages of natural-language queries. Detection looks at the script (kana and kanji mean
+// Japanese) and otherwise counts the words each vocabulary knows, so "Fotos vom letzten Monat"
+// is German and "fotos del mes pasado" Spanish. Each vocabulary lists the words the fallback
+// parser drops or turns into filters; date words are translated into the English phrases the
+// date grammar reads.
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Language {
+    English,
+    German,
+    Spanish,
+    Japanese,
+}
+
+pub struct Vocabulary {
+    // Words that carry no search meaning in a request for files
+    stop_words: &'static [&'static str],
+    // Words a sentence-like request tends to start with (end with, in Japanese)
+    lead_words: &'static [&'static str],
+    // Words marking a word as text to look for in files
+    content_words: &'static [&'static str],
+    // Whether that word comes before the marker, as in "Dateien, die TODO enthalten"
+    content_after_term: bool,
+    // File-type words and the extension group they stand for
+    type_words: &'static [(&'static str, &'static str)],
+    // Date words and their English; two-word entries are matched first
+    date_words: &'static [(&'static str, &'static str)],
+}
+
+const ENGLISH: Vocabulary = Vocabulary {
+    stop_words: &[
+        "a", "about", "all", "an", "and", "any", "are", "at", "by", "can", "did", "do", "find", "for", "from", "get", "give",
+        "had", "has", "have", "i", "in", "is", "it", "list", "look", "me", "mine", "my", "of", "on", "or", "our", "please",
+        "related", "search", "show", "some", "that", "the", "their", "them", "these", "this", "those", "to", "was", "we",
+        "what", "where", "which", "with", "you", "your", "file", "files", "folder", "folders", "stuff", "thing", "things",
+    ],
+    lead_words: &["find", "show", "where", "what", "which", "get", "list", "give", "search", "look", "any", "all"],
+    content_words: &["containing", "contains", "contain", "mentioning", "mentions", "mention", "saying", "says"],
+    content_after_term: false,
+    // English type words go through the extension groups and their aliases
+    type_words: &[],
+    date_words: &[],
+};
+
+const GERMAN: Vocabulary = Vocabulary {
+    stop_words: &[
+        "der", "die", "das", "den", "dem", "des", "ein", "eine", "einen", "einem", "einer", "eines", "und", "oder", "mit",
+        "von", "vom", "zum", "zur", "zu", "für", "über", "aus", "bei", "am", "an", "auf", "ich", "mich", "mir", "mein",
+        "meine", "meinen", "meiner", "meinem", "wir", "unser", "unsere", "bitte", "zeig", "zeige", "zeigen", "finde",
+        "finden", "such", "suche", "suchen", "gib", "alle", "alles", "datei", "dateien", "ordner", "sachen", "etwas",
+        "welche", "ist", "sind", "war", "hat", "habe", "haben", "wo", "was", "sich", "mal",
+    ],
+    lead_words: &["zeig", "zeige", "zeigen", "finde", "finden", "such", "suche", "suchen", "gib", "wo", "welche", "alle"],
+    content_words: &["enthält", "enthalten", "erwähnt", "erwähnen"],
+    content_after_term: true,
+    type_words: &[
+        ("fotos", "image"), ("foto", "image"), ("bilder", "image"), ("bild", "image"), ("filme", "video"), ("film", "video"),
+        ("musik", "audio"), ("lieder", "audio"), ("dokumente", "doc"), ("dokument", "doc"), ("unterlagen", "doc"),
+        ("tabellen", "spreadsheet"), ("tabelle", "spreadsheet"), ("präsentationen", "presentation"),
+        ("präsentation", "presentation"), ("folien", "presentation"), ("archive", "archive"), ("quellcode", "code"),
+    ],
+    date_words: &[
+        ("heute", "today"), ("gestern", "yesterday"), ("kürzlich", "recently"), ("neulich", "recently"),
+        ("letzte", "last"), ("letzten", "last"), ("letzter", "last"), ("letztes", "last"), ("vergangene", "past"),
+        ("vergangenen", "past"), ("diese", "this"), ("diesen", "this"), ("dieser", "this"), ("dieses", "this"),
+        ("tag", "day"), ("tage", "days"), ("tagen", "days"), ("woche", "week"), ("wochen", "weeks"), ("monat", "month"),
+        ("monate", "months"), ("monaten", "months"), ("jahr", "year"), ("jahre", "years"), ("jahren", "years"),
+        ("seit", "since"), ("nach", "after"), ("bis", "until"), ("im", "in"), ("januar", "january"), ("februar", "february"),
+        ("märz", "march"), ("mai", "may"), ("juni", "june"), ("juli", "july"), ("oktober", "october"), ("dezember", "december"),
+    ],
+};
+
+const SPANISH: Vocabulary = Vocabulary {
+    stop_words: &[
+        "el", "la", "los", "las", "un", "una", "unos", "unas", "y", "o", "de", "del", "al", "con", "por", "para", "sobre",
+        "mi", "mis", "me", "yo", "nuestro", "nuestros", "muestra", "muéstrame", "muestrame", "mostrar", "busca", "buscar",
+        "búscame", "encuentra", "encontrar", "dame", "todos", "todas", "archivo", "archivos", "carpeta", "carpetas", "que",
+        "qué", "donde", "dónde", "cual", "cuál", "cuales", "cuáles", "es", "son", "era", "tiene", "tengo", "lo", "cosas",
+        "algo", "favor",
+    ],
+    lead_words: &["muestra", "muéstrame", "muestrame", "busca", "buscar", "búscame", "encuentra", "dame", "dónde", "donde", "cuáles", "todos"],
+    content_words: &["contiene", "contienen", "conteniendo", "menciona", "mencionan", "mencionando"],
+    content_after_term: false,
+    type_words: &[
+        ("fotos", "image"), ("foto", "image"), ("imágenes", "image"), ("imagenes", "image"), ("imagen", "image"),
+        ("vídeos", "video"), ("películas", "video"), ("música", "audio"), ("musica", "audio"), ("canciones", "audio"),
+        ("documentos", "doc"), ("documento", "doc"), ("presentaciones", "presentation"), ("diapositivas", "presentation"),
+        ("código", "code"),
+    ],
+    date_words: &[
+        ("semana pasada", "last week"), ("mes pasado", "last month"), ("año pasado", "last year"),
+        ("esta semana", "this week"), ("este mes", "this month"), ("este año", "this year"),
+        ("hoy", "today"), ("ayer", "yesterday"), ("reciente", "recently"), ("recientes", "recently"),
+        ("recientemente", "recently"), ("último", "last"), ("última", "last"), ("últimos", "last"), ("últimas", "last"),
+        ("día", "day"), ("días", "days"), ("dias", "days"), ("semana", "week"), ("semanas", "weeks"), ("mes", "month"),
+        ("meses", "months"), ("año", "year"), ("años", "years"), ("desde", "since"), ("antes", "before"),
+        ("después", "after"), ("despues", "after"), ("en", "in"), ("enero", "january"), ("febrero", "february"),
+        ("marzo", "march"), ("abril", "april"), ("mayo", "may"), ("junio", "june"), ("julio", "july"), ("agosto", "august"),
+        ("septiembre", "september"), ("octubre", "october"), ("noviembre", "november"), ("diciembre", "december"),
+    ],
+};
+
+const JAPANESE: Vocabulary = Vocabulary {
+    stop_words: &[
+        "ファイル", "フォルダ", "探して", "探す", "見せて", "見せる", "表示", "表示して", "ください", "下さい", "全部", "すべて",
+        "私", "僕", "関する", "関連", "もの", "検索", "検索して", "見つけて", "欲しい", "ある", "いる", "した", "して", "です",
+        "ます", "どこ", "どれ",
+    ],
+    lead_words: &["探して", "見せて", "表示して", "検索して", "見つけて", "ください", "下さい"],
+    content_words: &["含む", "含まれる", "含んだ"],
+    content_after_term: true,
+    type_words: &[
+        ("写真", "image"), ("画像", "image"), ("動画", "video"), ("映像", "video"), ("音楽", "audio"), ("文書", "doc"),
+        ("書類", "doc"), ("ドキュメント", "doc"), ("資料", "doc"), ("表計算", "spreadsheet"), ("スプレッドシート", "spreadsheet"),
+        ("プレゼン", "presentation"), ("スライド", "presentation"), ("コード", "code"), ("ソースコード", "code"),
+    ],
+    date_words: &[
+        ("今日", "today"), ("昨日", "yesterday"), ("最近", "recently"), ("今週", "this week"), ("先週", "last week"),
+        ("今月", "this month"), ("先月", "last month"), ("今年", "this year"), ("去年", "last year"), ("昨年", "last year"),
+    ],
+};
+
+// Japanese has no spaces between words; particles and punctuation are where queries are split, and
+// "file" and "folder", which usually end a request ("TODOを含むファイル")
+const JAPANESE_SEPARATORS: &[&str] = &[
+    "ファイル", "フォルダ", "について", "から", "まで", "の", "を", "が", "は", "に", "で", "と", "や", "も", "へ", "、", "。", "！", "？", "「", "」", "　",
+];
+
+impl Language {
+    pub fn name(self) -> &'static str {
+        match self {
+            Language::English => "English",
+            Language::German => "German",
+            Language::Spanish => "Spanish",
+            Language::Japanese => "Japanese",
+        }
+    }
+
+    pub fn vocabulary(self) -> &'static Vocabulary {
+        match self {
+            Language::English => &ENGLISH,
+            Language::German => &GERMAN,
+            Language::Spanish => &SPANISH,
+            Language::Japanese => &JAPANESE,
+        }
+    }
+}
+
+impl Vocabulary {
+    // English stop words count in every language; queries often mix in "files" or "pdf"
+    pub fn is_stop_word(&self, word: &str) -> bool {
+        self.stop_words.contains(&word) || ENGLISH.stop_words.contains(&word)
+    }
+
+    pub fn is_lead_word(&self, word: &str) -> bool {
+        self.lead_words.contains(&word)
+    }
+
+    pub fn is_content_word(&self, word: &str) -> bool {
+        self.content_words.contains(&word) || ENGLISH.content_words.contains(&word)
+    }
+
+    pub fn content_after_term(&self) -> bool {
+        self.content_after_term
+    }
+
+    pub fn type_group(&self, word: &str) -> Option<&'static str> {
+        self.type_words.iter().find(|(known, _)| *known == word).map(|(_, group)| *group)
+    }
+
+    // The words with date words replaced by their English, each flagged with whether it was
+    // translated. Untranslated words come back as they were.
+    pub fn translate_dates(&self, words: &[String]) -> Vec<(String, bool)> {
+        let lookup = |phrase: &str| self.date_words.iter().find(|(known, _)| *known == phrase).map(|(_, english)| *english);
+        let mut translated = Vec::new();
+        let mut i = 0;
+        while i < words.len() {
+            let pair = words.get(i + 1).and_then(|next| lookup(&format!("{} {}", words[i], next)));
+            let (english, used) = match pair {
+                Some(english) => (Some(english), 2),
+                None => (lookup(&words[i]), 1),
+            };
+            match english {
+                Some(english) => translated.extend(english.split_whitespace().map(|word| (word.to_string(), true))),
+                None => translated.push((words[i].clone(), false)),
+            }
+            i += used;
+        }
+        translated
+    }
+}
+
+fn is_japanese(c: char) -> bool {
+    // Hiragana, katakana and CJK ideographs
+    matches!(c, '\u{3040}'..='\u{30ff}' | '\u{4e00}'..='\u{9fff}')
+}
+
+pub fn detect(text: &str) -> Language {
+    if text.chars().any(is_japanese) {
+        return Language::Japanese;
+    }
+    let words = words(text, Language::English);
+    let known = |vocabulary: &Vocabulary, word: &str| {
+        vocabulary.stop_words.contains(&word)
+            || vocabulary.content_words.contains(&word)
+            || vocabulary.type_group(word).is_some()
+            || vocabulary.date_words.iter().any(|(known, _)| *known == word)
+    };
+    let score = |vocabulary: &Vocabulary| words.iter().filter(|word| known(vocabulary, word)).count();
+    let lowered = text.to_lowercase();
+    let german = score(&GERMAN) + 2 * lowered.chars().filter(|c| matches!(c, 'ä' | 'ö' | 'ü' | 'ß')).count();
+    let spanish = score(&SPANISH) + 2 * lowered.chars().filter(|c| matches!(c, 'ñ' | '¿' | '¡' | 'á' | 'é' | 'í' | 'ó' | 'ú')).count();
+    let english = score(&ENGLISH);
+    if german > english && german >= spanish {
+        Language::German
+    } else if spanish > english && spanish > german {
+        Language::Spanish
+    } else {
+        Language::English
+    }
+}
+
+// The words of a query, lowercase and without surrounding punctuation
+pub fn words(text: &str, language: Language) -> Vec<String> {
+    let mut text = text.to_lowercase();
+    if language == Language::Japanese {
+        for separator in JAPANESE_SEPARATORS {
+            text = text.replace(separator, " ");
+        }
+    }
+    text.split_whitespace()
+        .map(|word| word.trim_matches(|c: char| !c.is_alphanumeric()).to_string())
+        .filter(|word| !word.is_empty())
+        .collect()
+}
@@ -1,24 +1,110 @@
-use rusqlite::{params, Connection, Result as SqlResult};
+use rusqlite::types::Value;
+use rusqlite::{params, Connection, OptionalExtension, Result as SqlResult};
 use serde::{Deserialize, Serialize};
+use specta::Type;
 use std::fs;
 use std::path::{Path, PathBuf};
-use std::sync::Mutex;
-use std::time::{SystemTime, Instant};
-use tauri::State;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Mutex, RwLock};
+use std::time::{Duration, Instant, SystemTime};
+use tauri::{AppHandle, Emitter, Manager, State, WebviewWindow};
+use tracing::{debug, info, warn};
 use walkdir::WalkDir;
-use fuzzy_matcher::FuzzyMatcher;
-use fuzzy_matcher::skim::SkimMatcherV2;
-use chrono::Utc;
-use regex::Regex;
+use chrono::{DateTime, Datelike, Local, Utc};
+use regex::{Regex, RegexBuilder};
 use std::collections::{HashSet, HashMap};
 use rayon::prelude::*;
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+mod archives;
+mod ask;
+mod categorizer;
+mod coalesce;
+mod compat;
+mod config_file;
+mod content_search;
+mod copies;
+mod db;
+mod drag_out;
+mod error;
+mod exclusions;
+mod experiments;
+mod explain;
+mod favorites;
+mod file_ops;
+mod file_types;
+mod frecency;
+mod fuzzy;
+mod globs;
+mod headless;
+mod hidden;
+mod icons;
+mod incremental;
+mod languages;
+mod llm;
+mod local_llm;
+mod locale;
+mod logging;
+mod metrics;
+mod mirrors;
+mod name_index;
+mod native_path;
+mod nl;
+mod notes;
+mod open_with;
+mod overlay;
+mod path_scope;
+mod pins;
+mod planner;
+mod preview;
+mod projects;
+mod properties;
+mod ranking;
+mod recents;
+mod refine;
+mod saved_searches;
+mod search_sql;
+mod semantic;
+mod settings;
+mod smart_collections;
+mod snapshot;
+mod summarize;
+mod tags;
+#[cfg(any(test, feature = "testing"))]
+#[doc(hidden)]
+pub mod testing;
+mod text_extract;
+mod thumbnails;
+mod time_filter;
+mod top_k;
+mod transliterate;
+mod trash;
+mod tray;
+mod trigram;
+mod zip;
+
+use error::{AppError, ErrorCode};
+use ranking::RankingConfig;
+use search_sql::{Candidates, NameMatch, SqlFilter};
+
+#[derive(Debug, Serialize, Deserialize, Clone, Type)]
+#[serde(default)]
 pub struct SearchOptions {
     pub search_folders: bool,
     pub enable_fuzzy: bool,
     pub strict_mode: bool,
     pub filename_only: bool,
+    pub case_sensitive: bool,
+    // Also match romanized CJK names (pinyin/romaji), e.g. "zhongguo" -> 中国.txt
+    pub transliterate: bool,
+    // Treat the query as natural language (true), as typed (false), or decide from an `nl:`
+    // prefix and how sentence-like it reads (unset)
+    pub natural_language: Option<bool>,
+    // Skip the search cache for this search, e.g. right after files were edited outside the app
+    pub force_refresh: bool,
+    // Return where the time went with the results (see metrics::SearchTimings)
+    pub debug: bool,
+    // Only search under this folder, on top of any `@root`/`in:` tokens (saved searches use it)
+    pub scope: Option<String>,
 }
 
 impl Default for SearchOptions {
@@ -28,10 +114,80 @@ impl Default for SearchOptions {
             enable_fuzzy: true,
             strict_mode: false,
             filename_only: false,
+            case_sensitive: false,
+            transliterate: true,
+            natural_language: None,
+            force_refresh: false,
+            debug: false,
+            scope: None,
         }
     }
 }
 
+impl SearchOptions {
+    // Folder names and full paths count as matches, not just file names
+    pub fn matches_folders(&self) -> bool {
+        self.search_folders && !self.filename_only
+    }
+}
+
+// Lowercase for comparison unless the search is case-sensitive
+fn fold_case(s: &str, case_sensitive: bool) -> String {
+    if case_sensitive {
+        s.to_string()
+    } else {
+        locale::fold(s)
+    }
+}
+
+// Escape LIKE wildcards (% and _) and the escape character itself in user input.
+// Every LIKE built from user input must use `ESCAPE '\'` (see LIKE_ESCAPE).
+fn escape_like(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len() + 4);
+    for ch in s.chars() {
+        if matches!(ch, '%' | '_' | '\\') {
+            escaped.push('\\');
+        }
+        escaped.push(ch);
+    }
+    escaped
+}
+
+// ESCAPE clause matching escape_like()
+const LIKE_ESCAPE: &str = "ESCAPE '\\'";
+
+// Convert a LIKE pattern (% and _ wildcards, escaped by escape_like) into an equivalent
+// GLOB pattern. SQLite's LIKE ignores ASCII case, GLOB does not, so case-sensitive
+// searches use GLOB.
+fn like_to_glob(pattern: &str) -> String {
+    // Literal characters that are special in GLOB are wrapped in a character class
+    fn push_literal(glob: &mut String, ch: char) {
+        if matches!(ch, '*' | '?' | '[') {
+            glob.push('[');
+            glob.push(ch);
+            glob.push(']');
+        } else {
+            glob.push(ch);
+        }
+    }
+
+    let mut glob = String::with_capacity(pattern.len() + 4);
+    let mut chars = pattern.chars();
+    while let Some(ch) = chars.next() {
+        match ch {
+            '\\' => {
+                if let Some(escaped) = chars.next() {
+                    push_literal(&mut glob, escaped);
+                }
+            }
+            '%' => glob.push('*'),
+            '_' => glob.push('?'),
+            c => push_literal(&mut glob, c),
+        }
+    }
+    glob
+}
+
 // Helper function to check if a file path is in a library/build directory
 fn is_library_file(path: &str) -> bool {
     let path_l = path.to_lowercase();
@@ -67,34 +223,119 @@ fn is_library_file(path: &str) -> bool {
     path_l.contains("\\windows\\syswow64\\") || path_l.contains("/windows/syswow64/")
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, Type)]
 pub struct FileEntry {
     pub path: String,
     pub name: String,
     pub last_accessed: Option<i64>,
     pub access_count: i32,
     pub modified_at: Option<i64>,
+    // Freshness of modified_at, filled in just before results are returned
+    #[serde(default)]
+    pub age_bucket: Option<AgeBucket>,
+    // Lives under a root indexed in mirror mode
+    #[serde(default)]
+    pub in_backup: bool,
+    // In `content:` searches, whether the name, the text or both matched
+    #[serde(default)]
+    pub match_source: Option<content_search::MatchSource>,
+    // The first line of the file's text that matched
+    #[serde(default)]
+    pub content_snippet: Option<content_search::ContentSnippet>,
 }
 
-pub struct AppState {
-    db: Mutex<Connection>,
-    // Simple cache for recent search results (query -> (timestamp, results))
-    search_cache: Mutex<HashMap<String, (Instant, Vec<FileEntry>)>>,
-    // Regex compilation cache for performance (pattern -> compiled regex)
-    regex_cache: Mutex<HashMap<String, Regex>>,
+#[derive(Debug, Serialize, Deserialize, Clone, Default, Type)]
+pub struct SearchResponse {
+    pub results: Vec<FileEntry>,
+    // The search hit its time budget or was rerun with the cheap plan; results may be incomplete
+    pub degraded: bool,
+    // What a natural-language query was rewritten into before searching
+    pub rewritten_query: Option<String>,
+    // The rewrite taken apart into keywords and filters, so a bad parse can be corrected
+    pub plan: Option<nl::QueryPlan>,
+    // A newer search from the same window replaced this one; there are no results
+    pub superseded: bool,
+    // Per-stage timings and counts, for searches run with `debug`
+    pub timings: Option<metrics::SearchTimings>,
 }
 
-// Fuzzy matching helper function
-fn fuzzy_match_score(text: &str, pattern: &str) -> f32 {
-    let matcher = SkimMatcherV2::default();
-    if let Some(score) = matcher.fuzzy_match(text, pattern) {
-        // Normalize score to 0.0-1.0 range
-        (score as f32 / 100.0).min(1.0).max(0.0)
-    } else {
-        0.0
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Type)]
+#[serde(rename_all = "snake_case")]
+pub enum AgeBucket {
+    Today,
+    ThisWeek,
+    ThisMonth,
+    Older,
+}
+
+impl AgeBucket {
+    // Buckets follow local calendar boundaries: midnight, Monday, and the 1st of the month
+    pub fn for_timestamp(timestamp: i64, now: DateTime<Local>) -> AgeBucket {
+        let today = now.date_naive();
+        let start_of = time_filter::start_of;
+
+        let start_of_week = today - chrono::Duration::days(today.weekday().num_days_from_monday() as i64);
+        let start_of_month = today.with_day(1).unwrap_or(today);
+
+        if timestamp >= start_of(today) {
+            AgeBucket::Today
+        } else if timestamp >= start_of(start_of_week) {
+            AgeBucket::ThisWeek
+        } else if timestamp >= start_of(start_of_month) {
+            AgeBucket::ThisMonth
+        } else {
+            AgeBucket::Older
+        }
+    }
+}
+
+fn assign_age_buckets(entries: &mut [FileEntry]) {
+    let now = Local::now();
+    for entry in entries {
+        entry.age_bucket = entry.modified_at.map(|ts| AgeBucket::for_timestamp(ts, now));
     }
 }
 
+pub struct AppState {
+    // Writes, and reads outside of searches (see db.rs)
+    db: Mutex<Connection>,
+    // Read connections for searches
+    readers: db::ReadPool,
+    // Indexing runs and background jobs open their own connections to this file
+    db_path: PathBuf,
+    // Simple cache for recent search results (query -> (timestamp, results)).
+    // Read-mostly state is behind RwLocks so concurrent searches only serialize on the database.
+    search_cache: RwLock<HashMap<String, (Instant, Vec<FileEntry>)>>,
+    // Regex compilation cache for performance (pattern -> compiled regex)
+    regex_cache: RwLock<HashMap<String, Regex>>,
+    // Scoring weights, persisted in the settings table
+    ranking: RwLock<RankingConfig>,
+    // User extension groups for `type:` filters, persisted in the settings table
+    extension_groups: RwLock<file_types::ExtensionGroups>,
+    // Running ranking experiment and the recent searches it ranked both ways
+    ranking_experiment: Mutex<experiments::ExperimentState>,
+    // Time and candidate budget for one search, persisted in the settings table
+    search_budget: RwLock<planner::SearchBudget>,
+    // LLM server settings, persisted in the settings table
+    llm: RwLock<llm::LlmConfig>,
+    // Result limit, cache lifetime and reindex schedule, persisted in the settings table
+    general: RwLock<settings::GeneralSettings>,
+    // Embeddings for semantic search, loaded from the database on first use
+    semantic_index: RwLock<Option<semantic::VectorIndex>>,
+    // LLM rewrites of natural-language queries (model and text -> query)
+    nl_rewrites: RwLock<HashMap<String, String>>,
+    // Search timings and cache hits since startup, for get_performance_stats
+    metrics: Mutex<metrics::Metrics>,
+    // Memory-mapped name index for prefix lookups, mapped again after each sync
+    name_index: RwLock<name_index::NameIndex>,
+    // Latest search_files call of each window, for dropping the ones typed over
+    search_queue: coalesce::SearchQueue,
+    // Candidates of the last plain word search, for the next keystroke (see incremental.rs)
+    last_candidates: incremental::LastCandidates,
+    // Latest prefetch_thumbnails batch of each window
+    thumbnail_prefetch: coalesce::SearchQueue,
+}
+
 #[derive(Debug, Clone)]
 struct PatternInfo {
     pattern_type: PatternType,
@@ -108,6 +349,7 @@ struct PatternInfo {
 #[derive(Debug, Clone)]
 enum PatternType {
     SimpleGlob,      // file* or *.ext
+    Glob,            // src/**/*.{rs,toml}, data[0-9].csv
     SimplePrefix,    // prefix.*
     PrefixSuffix,    // prefix.*suffix
     ComplexRegex,    // [a-z]+\d{2,4}
@@ -119,7 +361,8 @@ fn analyze_regex_pattern(query: &str) -> PatternInfo {
     let trimmed = query.trim();
     
     // Handle slash-wrapped regex
-    let actual_pattern = if trimmed.starts_with('/') && trimmed.ends_with('/') && trimmed.len() > 2 {
+    let slash_wrapped = trimmed.starts_with('/') && trimmed.ends_with('/') && trimmed.len() > 2;
+    let actual_pattern = if slash_wrapped {
         &trimmed[1..trimmed.len()-1]
     } else {
         trimmed
@@ -135,7 +378,7 @@ fn analyze_regex_pattern(query: &str) -> PatternInfo {
                 prefix: None,
                 suffix: Some(extension.to_string()),
                 can_use_sql_optimization: true,
-                sql_like_pattern: Some(format!("%.{}", extension)),
+                sql_like_pattern: Some(format!("%.{}", escape_like(extension))),
                 regex_pattern: actual_pattern.to_string(),
             };
         } else if actual_pattern.ends_with('*') && actual_pattern.matches('*').count() == 1 {
@@ -146,22 +389,34 @@ fn analyze_regex_pattern(query: &str) -> PatternInfo {
                 prefix: Some(prefix.to_string()),
                 suffix: None,
                 can_use_sql_optimization: true,
-                sql_like_pattern: Some(format!("{}%", prefix)),
+                sql_like_pattern: Some(format!("{}%", escape_like(prefix))),
                 regex_pattern: actual_pattern.to_string(),
             };
         }
     }
     
+    // Richer globs are matched with globset; the SQL prefilter comes from the glob itself
+    if !slash_wrapped && globs::is_glob_pattern(actual_pattern) {
+        return PatternInfo {
+            pattern_type: PatternType::Glob,
+            prefix: None,
+            suffix: None,
+            can_use_sql_optimization: true,
+            sql_like_pattern: None,
+            regex_pattern: actual_pattern.to_string(),
+        };
+    }
+
     // Check for optimizable regex prefix patterns
     if let Some(prefix) = extract_regex_prefix(actual_pattern) {
-        if actual_pattern.ends_with(".*") && prefix.len() >= 2 {
+        if actual_pattern.ends_with(".*") && prefix.chars().count() >= 2 {
             // Simple prefix.* pattern
             return PatternInfo {
                 pattern_type: PatternType::SimplePrefix,
                 prefix: Some(prefix.clone()),
                 suffix: None,
                 can_use_sql_optimization: true,
-                sql_like_pattern: Some(format!("{}%", prefix)),
+                sql_like_pattern: Some(format!("{}%", escape_like(&prefix))),
                 regex_pattern: actual_pattern.to_string(),
             };
         } else if let Some(suffix) = extract_regex_suffix(actual_pattern, &prefix) {
@@ -171,7 +426,7 @@ fn analyze_regex_pattern(query: &str) -> PatternInfo {
                 prefix: Some(prefix.clone()),
                 suffix: Some(suffix),
                 can_use_sql_optimization: true,
-                sql_like_pattern: Some(format!("{}%", prefix)),
+                sql_like_pattern: Some(format!("{}%", escape_like(&prefix))),
                 regex_pattern: actual_pattern.to_string(),
             };
         }
@@ -193,7 +448,7 @@ fn analyze_regex_pattern(query: &str) -> PatternInfo {
                     suffix: None,
                     can_use_sql_optimization: true,
                     // Use the concatenated version for better matching
-                    sql_like_pattern: Some(format!("%{}%", concatenated)),
+                    sql_like_pattern: Some(format!("%{}%", escape_like(&concatenated))),
                     regex_pattern: actual_pattern.to_string(),
                 };
             }
@@ -204,7 +459,7 @@ fn analyze_regex_pattern(query: &str) -> PatternInfo {
             prefix: None,
             suffix: None,
             can_use_sql_optimization: true,
-            sql_like_pattern: Some(format!("%{}%", actual_pattern)),
+            sql_like_pattern: Some(format!("%{}%", escape_like(actual_pattern))),
             regex_pattern: actual_pattern.to_string(),
         };
     }
@@ -222,7 +477,7 @@ fn analyze_regex_pattern(query: &str) -> PatternInfo {
 
 // Extract prefix from regex patterns like "^prefix.*" or "prefix.*"
 fn extract_regex_prefix(pattern: &str) -> Option<String> {
-    let pattern = if pattern.starts_with('^') { &pattern[1..] } else { pattern };
+    let pattern = pattern.strip_prefix('^').unwrap_or(pattern);
     
     // Find the first regex metacharacter
     let mut prefix = String::new();
@@ -233,7 +488,7 @@ fn extract_regex_prefix(pattern: &str) -> Option<String> {
         prefix.push(ch);
     }
     
-    if prefix.len() >= 2 && !prefix.is_empty() {
+    if prefix.chars().count() >= 2 {
         Some(prefix)
     } else {
         None
@@ -242,7 +497,7 @@ fn extract_regex_prefix(pattern: &str) -> Option<String> {
 
 // Extract suffix from prefix.*suffix patterns
 fn extract_regex_suffix(pattern: &str, prefix: &str) -> Option<String> {
-    let pattern = if pattern.starts_with('^') { &pattern[1..] } else { pattern };
+    let pattern = pattern.strip_prefix('^').unwrap_or(pattern);
     let after_prefix = &pattern[prefix.len()..];
     
     if after_prefix.starts_with(".*") && after_prefix.len() > 2 {
@@ -258,66 +513,57 @@ fn extract_regex_suffix(pattern: &str, prefix: &str) -> Option<String> {
     }
 }
 
-/// Convert a glob pattern to a regular expression
-/// Supports:
-/// - * matches any sequence of characters
-/// - ? matches any single character
-/// - [abc] matches any character in the set
-/// - [a-z] matches any character in the range
-/// - Everything else is treated literally
-fn build_glob_regex(pattern: &str) -> String {
-    let mut regex = String::with_capacity(pattern.len() * 2);
-    regex.push('^'); // Anchor to start
-    
-    let mut chars = pattern.chars().peekable();
-    while let Some(ch) = chars.next() {
-        match ch {
-            '*' => regex.push_str(".*"),
-            '?' => regex.push('.'),
-            '[' => {
-                // Handle character classes like [abc] or [a-z]
-                regex.push('[');
-                while let Some(inner) = chars.next() {
-                    if inner == ']' {
-                        regex.push(']');
-                        break;
-                    }
-                    // Escape regex special characters within character class
-                    match inner {
-                        '^' | '-' | '\\' => {
-                            regex.push('\\');
-                            regex.push(inner);
-                        }
-                        _ => regex.push(inner),
-                    }
-                }
-            }
-            // Escape regex special characters
-            '.' | '+' | '(' | ')' | '{' | '}' | '|' | '^' | '$' | '\\' => {
-                regex.push('\\');
-                regex.push(ch);
-            }
-            _ => regex.push(ch),
+// Fill name_romanized for existing rows whose names contain non-ASCII characters
+fn backfill_romanized_names(conn: &Connection) -> SqlResult<()> {
+    let rows: Vec<(i64, String)> = {
+        let mut stmt = conn.prepare("SELECT id, name FROM files WHERE name GLOB '*[^ -~]*'")?;
+        let rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+            .filter_map(|r| r.ok())
+            .collect();
+        rows
+    };
+
+    let mut updated = 0;
+    for (id, name) in rows {
+        if let Some(romanized) = transliterate::romanize(&name) {
+            conn.execute("UPDATE files SET name_romanized = ?1 WHERE id = ?2", params![romanized, id])?;
+            updated += 1;
         }
     }
-    
-    regex.push('$'); // Anchor to end
-    regex
+    info!("Backfilled romanized names for {} files", updated);
+    Ok(())
+}
+
+fn default_db_path() -> PathBuf {
+    dirs::data_local_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join("file-finder")
+        .join("index.db")
 }
 
 impl AppState {
     fn new() -> SqlResult<Self> {
-        let db_path = dirs::data_local_dir()
-            .unwrap_or_else(|| PathBuf::from("."))
-            .join("file-finder")
-            .join("index.db");
+        let db_path = default_db_path();
 
         // Create directory if it doesn't exist
         if let Some(parent) = db_path.parent() {
             fs::create_dir_all(parent).ok();
         }
 
-        let conn = Connection::open(db_path)?;
+        let state = Self::open(db_path.clone())?;
+        trigram::sync_in_background(&db_path);
+        name_index::sync_in_background(&db_path);
+        categorizer::categorize_in_background(&db_path);
+        snapshot::schedule(&db_path);
+        recents::schedule(&db_path);
+        Ok(state)
+    }
+
+    // Open (and migrate) the database at `db_path` without starting background jobs
+    fn open(db_path: PathBuf) -> SqlResult<Self> {
+        let conn = db::open(&db_path)?;
+        db::enable_wal(&conn)?;
+        search_sql::init();
 
         // Create tables
         conn.execute(
@@ -338,6 +584,15 @@ impl AppState {
             [],
         ); // Ignore error if column already exists
 
+        // Exact bytes of paths that aren't valid Unicode (see native_path.rs)
+        native_path::add_column(&conn);
+
+        // Romanized (pinyin/romaji) form of CJK names, NULL for names with nothing to romanize
+        if conn.execute("ALTER TABLE files ADD COLUMN name_romanized TEXT", []).is_ok() {
+            // Column is new - backfill it for already indexed non-ASCII names
+            backfill_romanized_names(&conn)?;
+        }
+
         conn.execute(
             "CREATE TABLE IF NOT EXISTS indexed_directories (
                 id INTEGER PRIMARY KEY,
@@ -386,6 +641,12 @@ impl AppState {
             [],
         )?;
 
+        // Index for the recently modified feed
+        conn.execute(
+            "CREATE INDEX IF NOT EXISTS idx_files_modified ON files(modified_at DESC)",
+            [],
+        )?;
+
         // Add index for fast prefix searches on filename
         conn.execute(
             "CREATE INDEX IF NOT EXISTS idx_files_name_prefix ON files(name)",
@@ -418,7 +679,7 @@ impl AppState {
         ).unwrap_or(false);
 
         if !has_root_directory {
-            println!("Migrating database: adding root_directory column");
+            info!("Migrating database: adding root_directory column");
             // Add the column with a default value
             conn.execute(
                 "ALTER TABLE files ADD COLUMN root_directory TEXT NOT NULL DEFAULT ''",
@@ -432,75 +693,210 @@ impl AppState {
             )?;
         }
 
+        settings::create_table(&conn)?;
+        saved_searches::create_table(&conn)?;
+        smart_collections::create_table(&conn)?;
+        favorites::create_tables(&conn)?;
+        pins::create_table(&conn)?;
+        notes::create_tables(&conn)?;
+        open_with::create_table(&conn)?;
+        copies::create_table(&conn)?;
+        mirrors::add_column(&conn);
+        // Per-root depth limit, NULL for unlimited; ignore error if the column already exists
+        let _ = conn.execute("ALTER TABLE indexed_directories ADD COLUMN max_depth INTEGER", []);
+        // Per-root hidden-folder option, NULL for off; ignore error if the column already exists
+        let _ = conn.execute("ALTER TABLE indexed_directories ADD COLUMN include_hidden INTEGER", []);
+        recents::add_root_column(&conn)?;
+        categorizer::create_tables(&conn)?;
+        tags::create_tables(&conn)?;
+        semantic::create_table(&conn)?;
+        summarize::create_table(&conn)?;
+        ask::create_table(&conn)?;
+        projects::create_table(&conn)?;
+        trigram::create_table(&conn)?;
+        name_index::create_table(&conn)?;
+        let ranking = ranking::load(&conn);
+        let extension_groups = file_types::load(&conn);
+        experiments::create_table(&conn)?;
+        let ranking_experiment = experiments::load(&conn);
+        let search_budget = planner::load(&conn);
+        let llm = llm::load(&conn);
+        let general = settings::general(&conn);
+        locale::apply(&locale::load(&conn));
+        logging::apply(&logging::load(&conn));
+
         Ok(AppState {
             db: Mutex::new(conn),
-            search_cache: Mutex::new(HashMap::new()),
-            regex_cache: Mutex::new(HashMap::new()),
+            readers: db::ReadPool::new(db_path.clone()),
+            db_path,
+            search_cache: RwLock::new(HashMap::new()),
+            regex_cache: RwLock::new(HashMap::new()),
+            ranking: RwLock::new(ranking),
+            extension_groups: RwLock::new(extension_groups),
+            ranking_experiment: Mutex::new(ranking_experiment),
+            search_budget: RwLock::new(search_budget),
+            llm: RwLock::new(llm),
+            general: RwLock::new(general),
+            semantic_index: RwLock::new(None),
+            nl_rewrites: RwLock::new(HashMap::new()),
+            metrics: Mutex::new(metrics::Metrics::default()),
+            name_index: RwLock::new(name_index::NameIndex::default()),
+            search_queue: coalesce::SearchQueue::default(),
+            last_candidates: Mutex::new(None),
+            thumbnail_prefetch: coalesce::SearchQueue::default(),
         })
     }
+
+    // Refresh the settings-backed state after the settings table was replaced (snapshot restore,
+    // update_settings)
+    fn reload_settings(&self, conn: &Connection) -> Result<(), String> {
+        *self.ranking.write().map_err(|e| e.to_string())? = ranking::load(conn);
+        *self.extension_groups.write().map_err(|e| e.to_string())? = file_types::load(conn);
+        *self.ranking_experiment.lock().map_err(|e| e.to_string())? = experiments::load(conn);
+        *self.search_budget.write().map_err(|e| e.to_string())? = planner::load(conn);
+        *self.llm.write().map_err(|e| e.to_string())? = llm::load(conn);
+        *self.general.write().map_err(|e| e.to_string())? = settings::general(conn);
+        locale::apply(&locale::load(conn));
+        logging::apply(&logging::load(conn));
+        Ok(())
+    }
 }
 
 #[tauri::command]
-async fn start_indexing(_state: State<'_, AppState>) -> Result<String, String> {
-    println!("start_indexing command called");
+#[specta::specta]
+async fn start_indexing(app: AppHandle) -> Result<String, AppError> {
+    debug!("start_indexing command called");
     let home_dir = dirs::home_dir().ok_or("Could not find home directory")?;
-    println!("Home directory: {:?}", home_dir);
+    debug!("Home directory: {:?}", home_dir);
 
     // Spawn a background task for indexing
-    tauri::async_runtime::spawn(async move {
-        println!("Starting background indexing task...");
-        index_directory(&home_dir, true).await;
-        println!("Background indexing task completed");
+    let db_path = app.state::<AppState>().db_path.clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        debug!("Starting background indexing task...");
+        index_directory(&db_path, &home_dir, true, None, None, None);
+        debug!("Background indexing task completed");
+        finish_indexing(&app, &home_dir);
     });
 
     Ok("Indexing started in background".to_string())
 }
 
 #[tauri::command]
-async fn index_custom_folder(path: String, _state: State<'_, AppState>) -> Result<String, String> {
-    println!("index_custom_folder command called with path: {}", path);
+#[specta::specta]
+async fn index_custom_folder(
+    path: String,
+    mirror: Option<bool>,
+    max_depth: Option<u32>,
+    include_hidden: Option<bool>,
+    app: AppHandle,
+) -> Result<String, AppError> {
+    debug!("index_custom_folder command called with path: {}", path);
     let folder_path = PathBuf::from(&path);
     
     if !folder_path.exists() {
-        return Err("Folder does not exist".to_string());
+        return Err(AppError::new(ErrorCode::NotFound, "Folder does not exist"));
     }
     
     if !folder_path.is_dir() {
-        return Err("Path is not a directory".to_string());
+        return Err(AppError::new(ErrorCode::InvalidInput, "Path is not a directory"));
     }
 
-    // Spawn a background task for indexing (don't clear existing files)
-    tauri::async_runtime::spawn(async move {
-        println!("Starting background indexing for custom folder...");
-        index_directory(&folder_path, false).await;
-        println!("Background indexing for custom folder completed");
+    spawn_folder_indexing(app, folder_path, mirror, max_depth, include_hidden);
+    Ok(format!("Indexing folder: {}", path))
+}
+
+// Index one more folder in the background, keeping the files already indexed
+fn spawn_folder_indexing(app: AppHandle, folder_path: PathBuf, mirror: Option<bool>, max_depth: Option<u32>, include_hidden: Option<bool>) {
+    let db_path = app.state::<AppState>().db_path.clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        debug!("Starting background indexing for custom folder...");
+        index_directory(&db_path, &folder_path, false, mirror, max_depth, include_hidden);
+        debug!("Background indexing for custom folder completed");
+        finish_indexing(&app, &folder_path);
     });
+}
 
-    Ok(format!("Indexing folder: {}", path))
+// Cached results predate the files that were just indexed
+fn invalidate_search_cache(state: &AppState) {
+    match state.search_cache.write() {
+        Ok(mut cache) => cache.clear(),
+        Err(e) => warn!("Failed to clear search cache: {}", e),
+    }
+    incremental::clear(&state.last_candidates);
 }
 
-async fn index_directory(path: &Path, clear_existing: bool) {
-    let db_path = dirs::data_local_dir()
-        .unwrap_or_else(|| PathBuf::from("."))
-        .join("file-finder")
-        .join("index.db");
+// Files were added, renamed or removed: drop everything built from the old rows. The semantic
+// index still holds removed paths and is reloaded on its next search; the trigram and name
+// indexes are brought up to date in the background, which is quick when indexing already did it.
+fn invalidate_index(state: &AppState) {
+    invalidate_search_cache(state);
+    match state.semantic_index.write() {
+        Ok(mut index) => *index = None,
+        Err(e) => warn!("Failed to drop semantic index: {}", e),
+    }
+    trigram::sync_in_background(&state.db_path);
+    name_index::sync_in_background(&state.db_path);
+}
+
+// Called when a background indexing run is done so the window can rerun its current search
+fn finish_indexing(app: &AppHandle, root: &Path) {
+    invalidate_index(&app.state::<AppState>());
+    tray::refresh_tooltip(app);
+    if let Err(e) = app.emit("index-finished", root.to_string_lossy()) {
+        warn!("Failed to send index-finished event: {}", e);
+    }
+}
+
+// Set from the tray menu. Running indexing passes wait before their next file until it is cleared.
+static INDEXING_PAUSED: AtomicBool = AtomicBool::new(false);
+
+fn indexing_paused() -> bool {
+    INDEXING_PAUSED.load(Ordering::Relaxed)
+}
+
+fn set_indexing_paused(paused: bool) {
+    INDEXING_PAUSED.store(paused, Ordering::Relaxed);
+}
+
+fn wait_while_indexing_paused() {
+    while indexing_paused() {
+        std::thread::sleep(std::time::Duration::from_millis(250));
+    }
+}
 
-    let mut conn = match Connection::open(db_path) {
+// Depth limit a root was last indexed with, None for unlimited
+fn stored_max_depth(conn: &Connection, root: &str) -> Option<u32> {
+    conn.query_row("SELECT max_depth FROM indexed_directories WHERE path = ?1", [root], |row| row.get(0))
+        .unwrap_or(None)
+}
+
+// `mirror` marks the root as a backup mirror; None keeps its current mode. `max_depth` limits how
+// many levels below `path` are indexed, 0 for all of them; None keeps the limit the folder was
+// last indexed with. `include_hidden` walks into hidden folders (see hidden.rs); None keeps the
+// folder's setting. Blocking, so it runs on its own thread.
+fn index_directory(
+    db_path: &Path,
+    path: &Path,
+    clear_existing: bool,
+    mirror: Option<bool>,
+    max_depth: Option<u32>,
+    include_hidden: Option<bool>,
+) {
+    let mut conn = match db::open(db_path) {
         Ok(c) => c,
         Err(e) => {
-            eprintln!("Failed to open database: {}", e);
+            warn!("Failed to open database: {}", e);
             return;
         }
     };
 
-    // Optimize database for bulk inserts
+    // Optimize database for bulk inserts; the journal stays WAL so searches keep reading meanwhile
     if let Err(e) = conn.execute_batch(
         "PRAGMA synchronous = OFF;
-         PRAGMA journal_mode = MEMORY;
          PRAGMA cache_size = 10000;
          PRAGMA temp_store = MEMORY;"
     ) {
-        eprintln!("Failed to optimize database: {}", e);
+        warn!("Failed to optimize database: {}", e);
     }
 
     // Get or create directory entry
@@ -514,18 +910,18 @@ async fn index_directory(path: &Path, clear_existing: bool) {
     ).unwrap_or(false);
     
     if clear_existing {
-        // Full reindex - clear all files from this directory
+        // Full reindex - clear all files from this directory (their trigram rows go with them)
         if let Err(e) = conn.execute("DELETE FROM files WHERE root_directory = ?1", [&root_dir_str]) {
-            eprintln!("Failed to clear existing files for directory: {}", e);
+            warn!("Failed to clear existing files for directory: {}", e);
             return;
         }
-        println!("Cleared existing index for directory: {}, starting fresh...", root_dir_str);
+        info!("Cleared existing index for directory: {}, starting fresh...", root_dir_str);
     } else if already_indexed {
         // Incremental update - keep existing files, only add new ones
-        println!("Directory already indexed: {}, will add new files only...", root_dir_str);
+        info!("Directory already indexed: {}, will add new files only...", root_dir_str);
     } else {
         // First time indexing this directory
-        println!("First time indexing directory: {}", root_dir_str);
+        info!("First time indexing directory: {}", root_dir_str);
     }
 
     let now = SystemTime::now()
@@ -541,11 +937,19 @@ async fn index_directory(path: &Path, clear_existing: bool) {
         root_dir_str.clone()
     };
     
+    let is_mirror = mirror.unwrap_or_else(|| mirrors::is_mirror(&conn, &root_dir_str));
+    let max_depth = match max_depth {
+        Some(0) => None,
+        Some(depth) => Some(depth),
+        None => stored_max_depth(&conn, &root_dir_str),
+    };
+    let include_hidden = include_hidden.unwrap_or_else(|| hidden::stored_include_hidden(&conn, &root_dir_str));
     if let Err(e) = conn.execute(
-        "INSERT OR REPLACE INTO indexed_directories (path, name, indexed_at, is_active) VALUES (?1, ?2, ?3, 1)",
-        params![&root_dir_str, &dir_name, now],
+        "INSERT OR REPLACE INTO indexed_directories (path, name, indexed_at, is_active, is_mirror, max_depth, include_hidden)
+         VALUES (?1, ?2, ?3, 1, ?4, ?5, ?6)",
+        params![&root_dir_str, &dir_name, now, is_mirror as i32, max_depth, include_hidden as i32],
     ) {
-        eprintln!("Failed to save indexed directory: {}", e);
+        warn!("Failed to save indexed directory: {}", e);
     }
     
     // Set all other directories as inactive
@@ -553,103 +957,111 @@ async fn index_directory(path: &Path, clear_existing: bool) {
         "UPDATE indexed_directories SET is_active = 0 WHERE path != ?1",
         [&root_dir_str],
     ) {
-        eprintln!("Failed to update directory status: {}", e);
+        warn!("Failed to update directory status: {}", e);
     }
 
-    println!("Collecting files...");
+    let exclusions = exclusions::load(&conn).compile();
+
+    debug!("Collecting files...");
     
     // Use HashSet for in-memory duplicate detection
     let mut seen_paths: HashSet<String> = HashSet::new();
     
     // If incremental update, load existing paths from database
     if !clear_existing && already_indexed {
-        println!("Loading existing files from database...");
+        debug!("Loading existing files from database...");
         match conn.prepare("SELECT path FROM files WHERE root_directory = ?1") {
             Ok(mut stmt) => {
                 match stmt.query_map([&root_dir_str], |row| row.get::<_, String>(0)) {
                     Ok(rows) => {
-                        for path_result in rows {
-                            if let Ok(path) = path_result {
-                                seen_paths.insert(path);
-                            }
+                        for path in rows.flatten() {
+                            seen_paths.insert(path);
                         }
-                        println!("Loaded {} existing files, will skip them...", seen_paths.len());
+                        debug!("Loaded {} existing files, will skip them...", seen_paths.len());
                     }
-                    Err(e) => eprintln!("Failed to query existing paths: {}", e)
+                    Err(e) => warn!("Failed to query existing paths: {}", e)
                 }
             }
-            Err(e) => eprintln!("Failed to prepare query: {}", e)
+            Err(e) => warn!("Failed to prepare query: {}", e)
         }
     }
     
+    // Exact paths of the entries whose names aren't valid Unicode, by display path
+    let mut raw_paths: HashMap<String, Vec<u8>> = HashMap::new();
+
     // Collect all entries first (this is I/O bound and relatively fast)
-    let entries: Vec<(String, String, Option<i64>)> = WalkDir::new(path)
+    let mut entries: Vec<(String, String, Option<i64>)> = WalkDir::new(path)
         .follow_links(false)
+        .max_depth(max_depth.map_or(usize::MAX, |depth| depth as usize))
         .into_iter()
         .filter_entry(|e| {
             // Skip hidden directories and common ignore patterns, but allow dotfiles
             let file_name = e.file_name().to_string_lossy();
             let is_dir = e.file_type().is_dir();
             
-            // Skip hidden directories like .git, .vscode, etc. but allow dotfiles like .dockerignore, .gitignore
-            let should_skip_hidden = file_name.starts_with('.') && is_dir && 
-                !file_name.eq(".") && !file_name.eq("..");
+            // Hidden directories follow the root's setting; dotfiles like .gitignore are always kept.
+            // The root itself is walked even when its own name starts with a dot.
+            let should_skip_hidden = e.depth() > 0 && is_dir && hidden::skip_dir(&file_name, include_hidden);
             
-            !should_skip_hidden
-                && !file_name.eq("node_modules")
-                && !file_name.eq("target")
-                && !file_name.eq("AppData")
-                && !file_name.eq("Library")
+            !should_skip_hidden && !exclusions.is_excluded(e.path(), &file_name, is_dir)
         })
         .filter_map(|e| e.ok())
         .filter_map(|entry| {
+            wait_while_indexing_paused();
             // Index both files and directories
-            if let Some(path_str) = entry.path().to_str() {
-                // Check for duplicates using HashSet (O(1) lookup)
-                if seen_paths.contains(path_str) {
-                    return None; // Skip duplicate
-                }
-                
-                if let Some(name) = entry.file_name().to_str() {
-                    // Get file modification time
-                    let modified_at = entry.metadata()
-                        .ok()
-                        .and_then(|metadata| metadata.modified().ok())
-                        .and_then(|time| time.duration_since(std::time::UNIX_EPOCH).ok())
-                        .map(|duration| duration.as_secs() as i64);
-                    
-                    seen_paths.insert(path_str.to_string());
-                    return Some((path_str.to_string(), name.to_string(), modified_at));
-                }
+            let (path_str, raw) = native_path::split(entry.path());
+            // Check for duplicates using HashSet (O(1) lookup)
+            if seen_paths.contains(&path_str) {
+                return None; // Skip duplicate
             }
-            None
+
+            // Get file modification time
+            let modified_at = entry.metadata()
+                .ok()
+                .and_then(|metadata| metadata.modified().ok())
+                .and_then(|time| time.duration_since(std::time::UNIX_EPOCH).ok())
+                .map(|duration| duration.as_secs() as i64);
+
+            seen_paths.insert(path_str.clone());
+            if let Some(raw) = raw {
+                raw_paths.insert(path_str.clone(), raw);
+            }
+            Some((path_str, entry.file_name().to_string_lossy().to_string(), modified_at))
         })
         .collect();
 
+    // Entries inside archives, when enabled: new archives, and ones indexed before it was turned on
+    let archive_indexing = archives::load(&conn);
+    if archive_indexing.enabled {
+        let inner = archives::expand(&seen_paths, &archive_indexing);
+        info!("Found {} entries inside archives", inner.len());
+        entries.extend(inner);
+    }
+
     let total_count = entries.len();
     
     if total_count == 0 {
-        println!("No new files to index.");
+        info!("No new files to index.");
         return;
     }
     
-    println!("Found {} new items to insert into database...", total_count);
+    info!("Found {} new items to insert into database...", total_count);
 
     // Start a transaction for bulk insert
     let tx = match conn.transaction() {
         Ok(t) => t,
         Err(e) => {
-            eprintln!("Failed to start transaction: {}", e);
+            warn!("Failed to start transaction: {}", e);
             return;
         }
     };
 
     // Use prepared statement for better performance
     // INSERT OR IGNORE handles any edge case duplicates at DB level (extra safety)
-    let mut stmt = match tx.prepare("INSERT OR IGNORE INTO files (path, name, root_directory, indexed_at, modified_at) VALUES (?1, ?2, ?3, ?4, ?5)") {
+    let mut stmt = match tx.prepare("INSERT OR IGNORE INTO files (path, name, root_directory, indexed_at, modified_at, name_romanized, path_raw) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)") {
         Ok(s) => s,
         Err(e) => {
-            eprintln!("Failed to prepare statement: {}", e);
+            warn!("Failed to prepare statement: {}", e);
             return;
         }
     };
@@ -657,14 +1069,16 @@ async fn index_directory(path: &Path, clear_existing: bool) {
     // Insert all entries
     let mut inserted_count = 0;
     for (idx, (path_str, name, modified_at)) in entries.iter().enumerate() {
-        if let Ok(rows_changed) = stmt.execute(params![path_str, name, &root_dir_str, now, modified_at]) {
+        wait_while_indexing_paused();
+        let name_romanized = transliterate::romanize(name);
+        if let Ok(rows_changed) = stmt.execute(params![path_str, name, &root_dir_str, now, modified_at, name_romanized, raw_paths.get(path_str)]) {
             if rows_changed > 0 {
                 inserted_count += 1;
             }
         }
         
         if (idx + 1) % 10000 == 0 {
-            println!("Processed {} / {} items...", idx + 1, total_count);
+            debug!("Processed {} / {} items...", idx + 1, total_count);
         }
     }
 
@@ -672,675 +1086,1070 @@ async fn index_directory(path: &Path, clear_existing: bool) {
 
     // Commit the transaction
     if let Err(e) = tx.commit() {
-        eprintln!("Failed to commit transaction: {}", e);
+        warn!("Failed to commit transaction: {}", e);
         return;
     }
 
-    println!("Indexing complete! Added {} new files (skipped {} existing)", inserted_count, total_count - inserted_count);
+    info!("Indexing complete! Added {} new files (skipped {} existing)", inserted_count, total_count - inserted_count);
+
+    match trigram::sync(&conn) {
+        Ok(added) => info!("Trigram index: added {} files", added),
+        Err(e) => warn!("Failed to update trigram index: {}", e),
+    }
+
+    match name_index::sync(&conn, &name_index::dir(db_path)) {
+        Ok(added) => info!("Name index: added {} files", added),
+        Err(e) => warn!("Failed to update name index: {}", e),
+    }
+
+    match categorizer::categorize_pending(&mut conn) {
+        Ok(categorized) => info!("Categorized {} files", categorized),
+        Err(e) => warn!("Failed to categorize files: {}", e),
+    }
+
+    match projects::detect_in_root(&conn, &root_dir_str) {
+        Ok(found) => info!("Found {} projects in {}", found, root_dir_str),
+        Err(e) => warn!("Failed to detect projects: {}", e),
+    }
 }
 
 // Helper function to normalize strings by removing separators for better matching
-fn normalize_for_matching(s: &str) -> String {
-    s.chars()
+fn normalize_for_matching(s: &str, case_sensitive: bool) -> String {
+    let stripped: String = s.chars()
         .filter(|c| c.is_alphanumeric())
-        .collect::<String>()
-        .to_lowercase()
+        .collect();
+    fold_case(&stripped, case_sensitive)
+}
+
+// Scoring steps of one file for explain_search: the rule that applied and the score after it.
+// Searches pass ScoreTrace::off(), which records nothing and allocates nothing.
+#[derive(Debug, Serialize, Clone, Type)]
+pub struct ScoreStep {
+    pub rule: String,
+    pub score: i64,
 }
 
-fn fuzzy_search_files(files: Vec<(String, String)>, query: &str, recent: &[String], favorites: &[String], options: &SearchOptions) -> Vec<(i64, FileEntry)> {
-    // New smarter search:
-    // - Tokenize the query by whitespace
-    // - Prefer ordered substring matches in filename first, then in the joined path components
-    // - Give a strong boost for contiguous (exact substring) matches
-    // - Fall back to fuzzy matching only when ordered substring checks fail, and require a reasonable score threshold
-    let matcher = SkimMatcherV2::default();
-    let mut results: Vec<(i64, FileEntry)> = Vec::with_capacity(1000);
+pub struct ScoreTrace(Option<Vec<ScoreStep>>);
 
-    let query_trimmed = query.trim();
-    if query_trimmed.is_empty() {
-        return results;
+impl ScoreTrace {
+    fn off() -> Self {
+        Self(None)
     }
 
-    let tokens: Vec<String> = query_trimmed
-        .split_whitespace()
-        .map(|s| s.to_lowercase())
-        .collect();
+    fn on() -> Self {
+        Self(Some(Vec::new()))
+    }
 
-    // Normalized query (no separators) for matching "finduname" to "find-uname"
-    let query_normalized = normalize_for_matching(query_trimmed);
-
-    // Early termination for fuzzy search - only process first 300 files for performance
-    for (path, name) in files.into_iter().take(300) {
-        let name_l = name.to_lowercase();
-        let path_l = path.to_lowercase();
-        let name_normalized = normalize_for_matching(&name);
-
-        // Check if file is in a library/build directory (should be deprioritized)
-        let is_in_library_dir = is_library_file(&path);
-
-        // Helper: check if all tokens appear in order in a haystack string
-        let in_order_in = |haystack: &str| -> Option<i64> {
-            let mut pos: usize = 0;
-            let mut score_bonus: i64 = 0;
-            for tok in &tokens {
-                if let Some(found) = haystack[pos..].find(tok) {
-                    // found is relative to haystack[pos..]
-                    let abs = pos + found;
-                    // Closer to start => slightly higher score
-                    score_bonus += (1000i64.saturating_sub(abs as i64)).max(0);
-                    pos = abs + tok.len();
-                } else {
-                    return None;
-                }
-            }
-            Some(score_bonus)
+    fn note(&mut self, rule: &str, score: i64) {
+        if let Some(steps) = &mut self.0 {
+            steps.push(ScoreStep { rule: rule.to_string(), score });
+        }
+    }
+
+    fn into_steps(self) -> Vec<ScoreStep> {
+        self.0.unwrap_or_default()
+    }
+}
+
+// What lifts a result above its match score
+struct Boosts {
+    // Open history and favorites, by path
+    usage: HashMap<String, frecency::Usage>,
+    // When the history was read, for its decay
+    now: i64,
+    // Root of the project the user is working in
+    active_project: Option<String>,
+}
+
+impl Boosts {
+    fn none() -> Self {
+        Self { usage: HashMap::new(), now: 0, active_project: None }
+    }
+}
+
+// Hidden-folder penalty and frecency bonus, applied the same way for every search path, then the
+// active-project boost: a multiplier for fuzzy scores, an additive bonus for SQL-prefiltered ones
+fn apply_boosts(mut score: i64, path: &str, boosts: &Boosts, additive: bool, ranking: &RankingConfig, trace: &mut ScoreTrace) -> i64 {
+    if hidden::in_hidden_folder(path) {
+        score /= ranking.hidden_dir_divisor;
+        trace.note("In a hidden folder", score);
+    }
+    if let Some(usage) = boosts.usage.get(path) {
+        let bonus = frecency::bonus(usage, boosts.now, ranking);
+        if bonus > 0 {
+            score += bonus;
+            trace.note(&format!("Frecency ({})", usage.describe(boosts.now)), score);
+        }
+    }
+    if boosts.active_project.as_deref().is_some_and(|root| Path::new(path).starts_with(root)) {
+        score = ranking.apply_active_project_boost(score, additive);
+        trace.note("In the active project", score);
+    }
+    score
+}
+
+// Base rules of matched_pattern_score, per pattern type
+const PREFIX_RULE: &str = "Name starts with the prefix";
+const GLOB_RULE: &str = "Name matches the glob";
+const REGEX_RULE: &str = "Name or path matches the regex";
+
+// Score of a name matched by a prefix, glob or regex: the exact name (with or without
+// extension) beats any other match
+fn pattern_score(name_folded: &str, query_folded: &str, base: i64, base_rule: &str, ranking: &RankingConfig, trace: &mut ScoreTrace) -> i64 {
+    let score = ranking.exact_name_score(name_folded, query_folded, base);
+    if score == ranking.exact_name && name_folded == query_folded {
+        trace.note("Name is exactly the query", score);
+    } else if score == base {
+        trace.note(base_rule, score);
+    } else {
+        trace.note("Name without extension is exactly the query", score);
+    }
+    score
+}
+
+// Full score of a row matched by a prefix, glob or regex
+#[allow(clippy::too_many_arguments)]
+fn matched_pattern_score(
+    path: &str,
+    name: &str,
+    needle: &str,
+    base: i64,
+    base_rule: &str,
+    options: &SearchOptions,
+    boosts: &Boosts,
+    ranking: &RankingConfig,
+    trace: &mut ScoreTrace,
+) -> i64 {
+    let name_folded = fold_case(name, options.case_sensitive);
+    let needle_folded = fold_case(needle, options.case_sensitive);
+    let score = pattern_score(&name_folded, &needle_folded, base, base_rule, ranking, trace);
+    apply_boosts(score, path, boosts, true, ranking, trace)
+}
+
+// Full score of a SQL-prefiltered row for a plain text query
+#[allow(clippy::too_many_arguments)]
+fn literal_score(
+    path: &str,
+    name: &str,
+    query: &str,
+    options: &SearchOptions,
+    boosts: &Boosts,
+    ranking: &RankingConfig,
+    trace: &mut ScoreTrace,
+) -> i64 {
+    let score = literal_base_score(path, name, query, options, ranking, trace);
+    apply_boosts(score, path, boosts, true, ranking, trace)
+}
+
+// Where in the name or path the text of a plain text query is
+fn literal_base_score(path: &str, name: &str, query: &str, options: &SearchOptions, ranking: &RankingConfig, trace: &mut ScoreTrace) -> i64 {
+    let name_lower = fold_case(name, options.case_sensitive);
+    let path_lower = fold_case(path, options.case_sensitive);
+    let query_lower = fold_case(query, options.case_sensitive);
+
+    if name_lower.contains(&query_lower) {
+        let (contains_score, rule) = if name_lower.starts_with(&query_lower) {
+            (ranking.name_starts_with, "Name starts with the query")
+        } else {
+            (ranking.name_contains, "Name contains the query")
         };
+        return pattern_score(&name_lower, &query_lower, contains_score, rule, ranking, trace);
+    }
+    let romanized_match = options.transliterate && !options.case_sensitive
+        && transliterate::romanize(name).is_some_and(|r| r.contains(&query_lower));
+    if romanized_match {
+        trace.note("Romanized CJK name contains the query", ranking.romanized_match);
+        return ranking.romanized_match;
+    }
+    if options.matches_folders() && path_lower.contains(&query_lower) {
+        trace.note("Folder path contains the query", ranking.path_contains);
+        return ranking.path_contains;
+    }
+    // For multi-word queries, check if all words are present in the filename
+    let words: Vec<&str> = query_lower.split_whitespace().collect();
+    if words.len() > 1 && words.iter().all(|word| name_lower.contains(word)) {
+        trace.note("All query words are in the name", ranking.all_words_in_name);
+        ranking.all_words_in_name
+    } else if words.len() > 1 && words.iter().all(|word| path_lower.contains(word)) {
+        trace.note("All query words are in the path", ranking.all_words_in_path);
+        ranking.all_words_in_path
+    } else {
+        // SQL matched but we're not sure why
+        trace.note("Weak match (the prefilter matched, the scorer found no clear reason)", ranking.weak_match);
+        ranking.weak_match
+    }
+}
 
-        // 1) Try filename matching - use both token-based AND normalized matching
-        let mut matched_filename = false;
-        let mut best_score: i64 = 0;
-        
-        // Check for exact filename match first (highest priority)
-        let is_exact_match = name_l == query_trimmed.to_lowercase();
-        if is_exact_match {
-            best_score = 10000; // Exact match gets highest score
+// Query preprocessing shared by every file a fuzzy search scores
+struct FuzzyQuery {
+    scorer: fuzzy::FuzzyScorer,
+    trimmed: String,
+    folded: String,
+    tokens: Vec<String>,
+    // Normalized query (no separators) for matching "finduname" to "find-uname"
+    normalized: String,
+    has_extension: bool,
+}
+
+impl FuzzyQuery {
+    fn new(query: &str, case_sensitive: bool) -> Self {
+        let trimmed = query.trim();
+        Self {
+            scorer: fuzzy::FuzzyScorer::new(trimmed, case_sensitive),
+            trimmed: trimmed.to_string(),
+            folded: fold_case(trimmed, case_sensitive),
+            tokens: trimmed.split_whitespace().map(|s| fold_case(s, case_sensitive)).collect(),
+            normalized: normalize_for_matching(trimmed, case_sensitive),
+            has_extension: trimmed.contains('.'),
+        }
+    }
+
+    // Check if all tokens appear in order in a haystack string
+    fn in_order_in(&self, haystack: &str) -> Option<i64> {
+        let mut pos: usize = 0;
+        let mut score_bonus: i64 = 0;
+        for tok in &self.tokens {
+            let found = haystack[pos..].find(tok.as_str())?;
+            // found is a byte offset relative to haystack[pos..]
+            let abs = pos + found;
+            // Closer to start => slightly higher score. Measure in chars so
+            // multi-byte (e.g. CJK) names aren't penalized per byte.
+            let char_offset = haystack[..abs].chars().count() as i64;
+            score_bonus += 1000i64.saturating_sub(char_offset).max(0);
+            pos = abs + tok.len();
+        }
+        Some(score_bonus)
+    }
+}
+
+// Fuzzy-rank every candidate; nucleo narrows them down first (see fuzzy.rs)
+fn fuzzy_search_files(files: Vec<(String, String)>, query: &str, boosts: &Boosts, options: &SearchOptions, ranking: &RankingConfig) -> Vec<(i64, FileEntry)> {
+    if query.trim().is_empty() {
+        return vec![];
+    }
+    let fuzzy_query = FuzzyQuery::new(query, options.case_sensitive);
+
+    let filter = fuzzy::CandidateFilter::new(query, options.case_sensitive);
+    // fuzzy_score also matches romanized names, so those have to get past the filter too
+    let romanize = options.transliterate && !options.case_sensitive && fuzzy_query.trimmed.is_ascii();
+
+    files
+        .into_par_iter()
+        .filter(|(path, name)| filter.matches(path) || (romanize && transliterate::romanize(name).is_some_and(|romanized| filter.matches(&romanized))))
+        .filter_map(|(path, name)| {
+            let score = fuzzy_score(&path, &name, &fuzzy_query, boosts, options, ranking, &mut ScoreTrace::off())?;
+            Some((score, FileEntry { path, name, last_accessed: None, access_count: 0, modified_at: None, age_bucket: None, in_backup: false, match_source: None, content_snippet: None }))
+        })
+        .collect()
+}
+
+// fuzzy_search_files within a search, adding its time to the plan's stats
+fn timed_fuzzy_search(stats: &mut planner::PlanStats, files: Vec<(String, String)>, query: &str, boosts: &Boosts, options: &SearchOptions, ranking: &RankingConfig) -> Vec<(i64, FileEntry)> {
+    let started = Instant::now();
+    let results = fuzzy_search_files(files, query, boosts, options, ranking);
+    stats.fuzzy_time += started.elapsed();
+    results
+}
+
+// Fuzzy score of one file, or None if it doesn't match.
+// - Prefer ordered substring matches of the query tokens in the filename, then in the joined path components
+// - Give a strong boost for contiguous (exact substring) matches
+// - Fall back to fuzzy matching only when ordered substring checks fail, and require a reasonable score threshold
+#[allow(clippy::too_many_arguments)]
+fn fuzzy_score(
+    path: &str,
+    name: &str,
+    query: &FuzzyQuery,
+    boosts: &Boosts,
+    options: &SearchOptions,
+    ranking: &RankingConfig,
+    trace: &mut ScoreTrace,
+) -> Option<i64> {
+    let case_sensitive = options.case_sensitive;
+    let name_l = fold_case(name, case_sensitive);
+    let path_l = fold_case(path, case_sensitive);
+    let name_normalized = normalize_for_matching(name, case_sensitive);
+
+    // Check if file is in a library/build directory (should be deprioritized)
+    let is_in_library_dir = is_library_file(path);
+    let library_penalty = |score: i64, trace: &mut ScoreTrace| {
+        let divided = score / ranking.library_dir_divisor;
+        trace.note("In a library or build folder", divided);
+        divided
+    };
+
+    // 1) Try filename matching - use both token-based AND normalized matching
+    let mut matched_filename = false;
+    let mut best_score: i64 = 0;
+
+    // Check for exact filename match first (highest priority)
+    let is_exact_match = name_l == query.folded;
+    if is_exact_match {
+        best_score = ranking.fuzzy_exact_name; // Exact match gets highest score
+        trace.note("Name is exactly the query", best_score);
+        matched_filename = true;
+    }
+
+    // Only continue with other matching strategies if not an exact match
+    if !is_exact_match {
+        // 1a) Normalized filename matching (ignores spaces, hyphens, underscores, dots)
+        // This allows "gre word" to match "grewordlist.txt" and "finduname" to match "find-uname.py"
+        // BUT: If query contains a dot (file extension), skip normalized matching to avoid false matches
+        // (e.g., "lib.rs" normalized to "librs" would match "contextlib.rst" normalized to "contextlibrst")
+        if !query.has_extension && !query.normalized.is_empty() && name_normalized.contains(&query.normalized) {
+            let mut score: i64 = ranking.normalized_match; // High score for normalized match
+            // Bonus if it's at the start
+            if name_normalized.starts_with(&query.normalized) {
+                score += ranking.normalized_prefix_bonus;
+                trace.note("Name starts with the query, ignoring separators", score);
+            } else {
+                trace.note("Name contains the query, ignoring separators", score);
+            }
             matched_filename = true;
+            best_score = score;
         }
-        
-        let query_has_extension = query_trimmed.contains('.');
-        
-        // Only continue with other matching strategies if not an exact match
-        if !is_exact_match {
-            // 1a) Normalized filename matching (ignores spaces, hyphens, underscores, dots)
-            // This allows "gre word" to match "grewordlist.txt" and "finduname" to match "find-uname.py"
-            // BUT: If query contains a dot (file extension), skip normalized matching to avoid false matches
-            // (e.g., "lib.rs" normalized to "librs" would match "contextlib.rst" normalized to "contextlibrst")
-            if !query_has_extension && !query_normalized.is_empty() && name_normalized.contains(&query_normalized) {
-                let mut score: i64 = 2900; // High score for normalized match
-                // Bonus if it's at the start
-                if name_normalized.starts_with(&query_normalized) {
-                    score += 500;
+
+        // 1b) Token-based ordered substring matching (stricter but gives higher score)
+        // If query has extension, require the full query as a substring (not just tokens in order)
+        if query.has_extension {
+            // For queries with extensions (e.g., "lib.rs"), check substring match
+            let query_lower = &query.folded;
+            if name_l.contains(query_lower.as_str()) {
+                let mut score: i64 = ranking.extension_substring; // Base score for substring match with extension
+                let rule;
+
+                // Much higher score if the query matches the entire filename
+                if name_l == *query_lower {
+                    score = ranking.extension_full_name; // Almost as good as exact match
+                    rule = "Name is exactly the query";
+                }
+                // Bonus if at the start of filename
+                else if name_l.starts_with(query_lower.as_str()) {
+                    score += ranking.extension_prefix_bonus;
+                    rule = "Name starts with the query";
+                }
+                // Bonus if the match is at a word boundary (after a separator)
+                else if name_l.contains(&format!("/{}", query_lower)) ||
+                        name_l.contains(&format!("\\{}", query_lower)) ||
+                        name_l.contains(&format!("-{}", query_lower)) ||
+                        name_l.contains(&format!("_{}", query_lower)) {
+                    score += ranking.extension_boundary_bonus;
+                    rule = "Name contains the query after a separator";
+                } else {
+                    rule = "Name contains the query";
+                }
+
+                if score > best_score {
+                    best_score = score;
+                    trace.note(rule, score);
                 }
                 matched_filename = true;
-                best_score = score;
             }
-            
-            // 1b) Token-based ordered substring matching (stricter but gives higher score)
-            // If query has extension, require the full query as a substring (not just tokens in order)
-            if query_has_extension {
-                // For queries with extensions (e.g., "lib.rs"), check substring match
-                let query_lower = query_trimmed.to_lowercase();
-                if name_l.contains(&query_lower) {
-                    let mut score: i64 = 3000; // Base score for substring match with extension
-                    
-                    // Much higher score if the query matches the entire filename
-                    if name_l == query_lower {
-                        score = 9500; // Almost as good as exact match
-                    }
-                    // Bonus if at the start of filename
-                    else if name_l.starts_with(&query_lower) {
-                        score += 1500;
-                    }
-                    // Bonus if the match is at a word boundary (after a separator)
-                    else if name_l.contains(&format!("/{}", query_lower)) || 
-                            name_l.contains(&format!("\\{}", query_lower)) ||
-                            name_l.contains(&format!("-{}", query_lower)) ||
-                            name_l.contains(&format!("_{}", query_lower)) {
-                        score += 800;
-                    }
-                    
-                    if score > best_score {
-                        best_score = score;
-                    }
-                    matched_filename = true;
+        } else if let Some(bonus) = query.in_order_in(&name_l) {
+            // No extension in query, use token-based matching
+            // In strict mode, only allow exact or prefix matches
+            if !options.strict_mode || name_l.starts_with(&query.folded) {
+                let contiguous = name_l.contains(&query.folded);
+                let mut score: i64 = ranking.token_match + bonus;
+                if contiguous {
+                    score += ranking.contiguous_bonus;
                 }
-            } else if let Some(bonus) = in_order_in(&name_l) {
-                // No extension in query, use token-based matching
-                // Check strict mode
-                if options.strict_mode {
-                    // In strict mode, only allow exact or prefix matches
-                    let is_prefix = name_l.starts_with(&query_trimmed.to_lowercase());
-                    if is_prefix {
-                        let contiguous = name_l.contains(query_trimmed);
-                        let mut score: i64 = 3000 + bonus;
-                        if contiguous {
-                            score += 1200;
-                        }
-                        if score > best_score {
-                            best_score = score;
-                        }
-                        matched_filename = true;
-                    }
-                } else {
-                    // Not in strict mode, accept token match
-                    let contiguous = name_l.contains(query_trimmed);
-                    let mut score: i64 = 3000 + bonus;
-                    if contiguous {
-                        score += 1200;
-                    }
-                    if score > best_score {
-                        best_score = score;
-                    }
-                    matched_filename = true;
+                if score > best_score {
+                    best_score = score;
+                    trace.note(
+                        if contiguous { "Name contains the query as one piece (earlier is better)" } else { "Name contains the query words in order (earlier is better)" },
+                        score,
+                    );
                 }
+                matched_filename = true;
             }
         }
-        
-        // If we matched the filename via any method, add it to results
-        if matched_filename {
-            // Deprioritize library/build directories (but NOT for exact matches)
-            if is_in_library_dir && !is_exact_match {
-                best_score = best_score / 4;
+
+        // 1c) Romanized CJK names: "zhongguo" or "sakura" against 中国.txt / さくら.png
+        if !matched_filename && options.transliterate && !case_sensitive && query.trimmed.is_ascii() {
+            if let Some(bonus) = transliterate::romanize(name).and_then(|romanized| query.in_order_in(&romanized)) {
+                best_score = ranking.romanized_match + bonus;
+                trace.note("Romanized CJK name contains the query words in order", best_score);
+                matched_filename = true;
             }
-            // Boost for recent and favorite files
-            if recent.contains(&path) { best_score *= 2; }
-            if favorites.contains(&path) { best_score *= 3; } // Favorites get 3x boost
-            results.push((best_score, FileEntry { path: path.clone(), name, last_accessed: None, access_count: 0, modified_at: None }));
-            continue;
         }
+    }
+
+    // If we matched the filename via any method, add it to results
+    if matched_filename {
+        // Deprioritize library/build directories (but NOT for exact matches)
+        if is_in_library_dir && !is_exact_match {
+            best_score = library_penalty(best_score, trace);
+        }
+        // Frecency and active-project boosts
+        return Some(apply_boosts(best_score, path, boosts, false, ranking, trace));
+    }
+
+    // 2) Path components ordered substring (folder names) - skip if filename_only or !search_folders
+    if options.matches_folders() {
+        let components_joined = path_l.split(['/', '\\']).filter(|s| !s.is_empty()).collect::<Vec<&str>>().join("/");
+        if let Some(bonus) = query.in_order_in(&components_joined) {
+            let contiguous = components_joined.contains(&query.folded);
+            let mut score: i64 = ranking.path_component_match + bonus;
+            if contiguous { score += ranking.path_contiguous_bonus; }
+            trace.note(
+                if contiguous { "Folder path contains the query as one piece" } else { "Folder path contains the query words in order" },
+                score,
+            );
+            // Deprioritize library/build directories
+            if is_in_library_dir {
+                score = library_penalty(score, trace); // Significantly reduce score for library files
+            }
+            return Some(apply_boosts(score, path, boosts, false, ranking, trace));
+        }
+    }
 
-        // 2) Path components ordered substring (folder names) - skip if filename_only or !search_folders
-        if options.search_folders && !options.filename_only {
-            let components_joined = path_l.split(['/', '\\']).filter(|s| !s.is_empty()).collect::<Vec<&str>>().join("/");
-            if let Some(bonus) = in_order_in(&components_joined) {
-                let contiguous = components_joined.contains(&query_trimmed.to_lowercase());
-                let mut score: i64 = 2000 + bonus;
-                if contiguous { score += 800; }
+    // 3) Weak fuzzy fallback (lower priority) - only if fuzzy is enabled
+    // Skip fuzzy matching for queries with file extensions (e.g., "lib.rs")
+    // to avoid false matches like "contextlib.rst"
+    if options.enable_fuzzy && !options.strict_mode && !query.has_extension {
+        if let Some(fuzzy_score) = query.scorer.score(name) {
+            // require threshold to prevent everything matching; scale down for file-name fuzzy
+            if fuzzy_score >= ranking.fuzzy_name_min_score {
+                let mut score = fuzzy_score + ranking.fuzzy_name_bonus; // base bump
+                trace.note("Name is a fuzzy match (the query letters appear in order)", score);
                 // Deprioritize library/build directories
                 if is_in_library_dir {
-                    score = score / 4; // Significantly reduce score for library files
+                    score = library_penalty(score, trace); // Significantly reduce score for library files
                 }
-                if recent.contains(&path) { score *= 2; }
-                if favorites.contains(&path) { score *= 3; }
-                results.push((score, FileEntry { path: path.clone(), name, last_accessed: None, access_count: 0, modified_at: None }));
-                continue;
+                return Some(apply_boosts(score, path, boosts, false, ranking, trace));
             }
         }
 
-        // 3) Weak fuzzy fallback (lower priority) - only if fuzzy is enabled
-        // Skip fuzzy matching for queries with file extensions (e.g., "lib.rs")
-        // to avoid false matches like "contextlib.rst"
-        if options.enable_fuzzy && !options.strict_mode && !query_has_extension {
-            if let Some(fuzzy_score) = matcher.fuzzy_match(&name, query_trimmed) {
-                // require threshold to prevent everything matching; scale down for file-name fuzzy
-                if fuzzy_score >= 60 {
-                    let mut score = (fuzzy_score as i64) + 500; // base bump
+        // 4) Very last: fuzzy match against full path but with higher bar and lower weight
+        if !options.filename_only {
+            if let Some(full_score) = query.scorer.score(path) {
+                if full_score >= ranking.fuzzy_path_min_score {
+                    let mut score = full_score / ranking.fuzzy_path_divisor; // de-prioritize full-path fuzzy
+                    trace.note("Full path is a fuzzy match", score);
                     // Deprioritize library/build directories
                     if is_in_library_dir {
-                        score = score / 4; // Significantly reduce score for library files
-                    }
-                    if recent.contains(&path) { score *= 2; }
-                    if favorites.contains(&path) { score *= 3; }
-                    results.push((score, FileEntry { path: path.clone(), name, last_accessed: None, access_count: 0, modified_at: None }));
-                    continue;
-                }
-            }
-
-            // 4) Very last: fuzzy match against full path but with higher bar and lower weight
-            if !options.filename_only {
-                if let Some(full_score) = matcher.fuzzy_match(&path, query_trimmed) {
-                    if full_score >= 80 {
-                        let mut score = (full_score as i64) / 2; // de-prioritize full-path fuzzy
-                        // Deprioritize library/build directories
-                        if is_in_library_dir {
-                            score = score / 4; // Significantly reduce score for library files
-                        }
-                        if recent.contains(&path) { score *= 2; }
-                        if favorites.contains(&path) { score *= 3; }
-                        results.push((score, FileEntry { path: path.clone(), name, last_accessed: None, access_count: 0, modified_at: None }));
+                        score = library_penalty(score, trace); // Significantly reduce score for library files
                     }
+                    return Some(apply_boosts(score, path, boosts, false, ranking, trace));
                 }
             }
         }
     }
 
-    results
+    None
 }
 
 
-// Convert glob pattern to regex pattern
-fn glob_to_regex(glob: &str) -> String {
-    let mut regex = String::with_capacity(glob.len() * 2);
-    
-    // Add start anchor if the pattern doesn't start with *
-    if !glob.starts_with('*') {
-        regex.push('^');
-    }
-    
-    for ch in glob.chars() {
-        match ch {
-            '*' => regex.push_str(".*"),      // * matches any sequence of characters
-            '?' => regex.push('.'),           // ? matches any single character
-            '.' => regex.push_str("\\."),     // Escape literal dots
-            '+' => regex.push_str("\\+"),     // Escape literal plus
-            '^' => regex.push_str("\\^"),     // Escape literal caret
-            '$' => regex.push_str("\\$"),     // Escape literal dollar
-            '(' => regex.push_str("\\("),     // Escape literal parentheses
-            ')' => regex.push_str("\\)"),
-            '[' => regex.push_str("\\["),     // Escape literal brackets
-            ']' => regex.push_str("\\]"),
-            '{' => regex.push_str("\\{"),     // Escape literal braces
-            '}' => regex.push_str("\\}"),
-            '|' => regex.push_str("\\|"),     // Escape literal pipe
-            '\\' => regex.push_str("\\\\"),   // Escape literal backslash
-            c => regex.push(c),               // Regular characters pass through
-        }
-    }
-    
-    // Add end anchor if the pattern doesn't end with *
-    if !glob.ends_with('*') {
-        regex.push('$');
-    }
-    
-    // Make it case-insensitive by default and add debug output
-    let final_regex = format!("(?i){}", regex);
-    println!("Glob '{}' converted to regex: '{}'", glob, final_regex);
-    final_regex
-}
+// Limits for user-supplied regexes: compiled program size and lazy DFA cache.
+// Match time is bounded by the search budget (see planner.rs).
+const REGEX_SIZE_LIMIT: usize = 1 << 20;
+const REGEX_DFA_SIZE_LIMIT: usize = 2 << 20;
 
-#[tauri::command]
-async fn search_files(query: String, options: Option<SearchOptions>, state: State<'_, AppState>) -> Result<Vec<FileEntry>, String> {
-    let search_opts = options.unwrap_or_default();
-    if query.trim().is_empty() {
-        return Ok(vec![]);
-    }
+fn compile_user_regex(pattern: &str) -> Result<Regex, regex::Error> {
+    RegexBuilder::new(pattern)
+        .size_limit(REGEX_SIZE_LIMIT)
+        .dfa_size_limit(REGEX_DFA_SIZE_LIMIT)
+        .build()
+}
 
-    // Check cache first (for exact queries, cache for 30 seconds)
-    let cache_key = format!("{}:{:?}", query, search_opts);
-    {
-        let mut cache = state.search_cache.lock().map_err(|e| e.to_string())?;
-        
-        // Clean old entries (simple cleanup - remove entries older than 60 seconds)
-        cache.retain(|_, (timestamp, _)| timestamp.elapsed().as_secs() < 60);
-        
-        // Check for cached result
-        if let Some((timestamp, cached_results)) = cache.get(&cache_key) {
-            if timestamp.elapsed().as_secs() < 30 {
-                println!("CACHE HIT: Returning {} cached results for '{}'", cached_results.len(), query);
-                return Ok(cached_results.clone());
+// Compile the regex for PrefixSuffix/ComplexRegex queries through the regex cache.
+// Ok(None) means the query is not a valid regex and should be matched fuzzily.
+fn compile_search_regex(query: &str, pattern_info: &PatternInfo, state: &AppState) -> Result<Option<Regex>, String> {
+    let regex_pattern = match pattern_info.pattern_type {
+        PatternType::PrefixSuffix => {
+            if query.starts_with('^') {
+                query.to_string()
+            } else {
+                format!("^{}$", query)
             }
         }
-    }
-
-    let (files, recent, favorites) = {
-        let db = state.db.lock().map_err(|e| e.to_string())?;
+        PatternType::ComplexRegex => pattern_info.regex_pattern.clone(),
+        _ => unreachable!()
+    };
+    // Check regex cache first, then compile if needed
+    let cached = state.regex_cache.read().map_err(|e| e.to_string())?.get(&regex_pattern).cloned();
+    let re = match cached {
+        Some(cached_regex) => {
+            debug!("REGEX CACHE HIT for pattern '{}'", regex_pattern);
+            cached_regex
+        }
+        // Compiled outside the lock so other searches can keep reading the cache
+        None => match compile_user_regex(&regex_pattern) {
+            Ok(new_regex) => {
+                let mut regex_cache = state.regex_cache.write().map_err(|e| e.to_string())?;
+
+                // Clean cache if it gets too large (keep only 50 recent patterns)
+                if regex_cache.len() > 50 {
+                    regex_cache.clear();
+                }
+                regex_cache.insert(regex_pattern.clone(), new_regex.clone());
+                debug!("REGEX COMPILED and cached for pattern '{}'", regex_pattern);
+                new_regex
+            }
+            Err(regex::Error::CompiledTooBig(limit)) => {
+                warn!("Regex '{}' exceeds the {} byte size limit", regex_pattern, limit);
+                return Err(format!("Pattern too expensive: '{}' compiles to more than {} KB. Try a simpler pattern.", query, limit / 1024));
+            }
+            Err(e) => {
+                warn!("Invalid regex '{}': {}", regex_pattern, e);
+                return Ok(None);
+            }
+        },
+    };
+    Ok(Some(re))
+}
+
+// A query made only of `type:`, `tag:`, `category:`, `modified:` or `in:backups` filters lists the most recently modified matching files
+fn list_filtered_files(filter: &SqlFilter, state: &AppState) -> Result<Vec<FileEntry>, String> {
+    let db = state.readers.get()?;
+    let mut stmt = db
+        .prepare_cached(&format!(
+            "SELECT path, name, modified_at FROM files WHERE 1 = 1{} ORDER BY modified_at DESC LIMIT ?",
+            filter.sql
+        ))
+        .map_err(|e| e.to_string())?;
+    let mut files: Vec<FileEntry> = stmt
+        .query_map(rusqlite::params_from_iter(filter.bind(vec![], vec![Value::Integer(100)])), |row| {
+            Ok(FileEntry {
+                path: row.get(0)?,
+                name: row.get(1)?,
+                last_accessed: None,
+                access_count: 0,
+                modified_at: row.get(2)?,
+                age_bucket: None,
+                in_backup: false,
+                match_source: None,
+                content_snippet: None,
+            })
+        })
+        .map_err(|e| e.to_string())?
+        .filter_map(|r| r.ok())
+        .collect();
+    mirrors::mark_backup_entries(&db, &mut files);
+    assign_age_buckets(&mut files);
+    Ok(files)
+}
+
+// Cached result sets are keyed by the query together with its options, the active directory
+// that relative `in:` folders resolve against and the active project that gets a boost
+fn search_cache_key(query: &str, options: &SearchOptions, state: &AppState) -> Result<String, String> {
+    let options = SearchOptions { force_refresh: false, debug: false, ..options.clone() };
+    let db = state.readers.get()?;
+    let active_directory: Option<String> = db
+        .query_row("SELECT path FROM indexed_directories WHERE is_active = 1", [], |row| row.get(0))
+        .optional()
+        .map_err(|e| e.to_string())?;
+    let active_project = projects::active_project(&db).map_err(|e| e.to_string())?;
+    Ok(format!("{}:{:?}:{:?}:{:?}", query, options, active_directory, active_project))
+}
+
+#[tauri::command]
+#[specta::specta]
+async fn search_files(query: String, options: Option<SearchOptions>, window: WebviewWindow, state: State<'_, AppState>) -> Result<SearchResponse, AppError> {
+    // Wait briefly for the next keystroke; only the latest search of a window runs (see coalesce.rs)
+    let ticket = state.search_queue.enter(window.label())?;
+    // Thumbnails prefetched for the previous results won't be shown
+    state.thumbnail_prefetch.cancel(window.label());
+    let debounce_ms = state.search_budget.read().map_err(|e| e.to_string())?.debounce_ms;
+    if debounce_ms > 0 {
+        tokio::time::sleep(Duration::from_millis(debounce_ms)).await;
+    }
+    let superseded = SearchResponse { superseded: true, ..SearchResponse::default() };
+    if ticket.superseded() {
+        return Ok(superseded);
+    }
+
+    let response = natural_search(&query, &options.unwrap_or_default(), &state).await?;
+    if response.results.is_empty() && !query.trim().is_empty() && db::blocking(|| index_is_empty(&state))? {
+        return Err(AppError::new(ErrorCode::IndexEmpty, "Nothing is indexed yet. Index a folder to search it."));
+    }
+    Ok(if ticket.superseded() { superseded } else { response })
+}
+
+// Whether no files are indexed at all, so an empty result says nothing about the query
+fn index_is_empty(state: &AppState) -> Result<bool, AppError> {
+    let db = state.readers.get()?;
+    let any: bool = db.query_row("SELECT EXISTS(SELECT 1 FROM files)", [], |row| row.get(0))?;
+    Ok(!any)
+}
+
+// run_search after rewriting natural-language queries; shared with the headless server
+async fn natural_search(query: &str, search_opts: &SearchOptions, state: &AppState) -> Result<SearchResponse, String> {
+    match nl::rewrite(query, search_opts, state).await? {
+        None => db::blocking(|| run_search(query, search_opts, state)),
+        Some(rewrite) => {
+            debug!("Natural-language query '{}' rewritten to '{}' ({:?})", query, rewrite.query, rewrite.source);
+            let mut response = db::blocking(|| run_search(&rewrite.query, search_opts, state))?;
+            response.plan = Some(nl::plan(&rewrite));
+            response.rewritten_query = Some(rewrite.query);
+            Ok(response)
+        }
+    }
+}
+
+// The search itself, for queries that need no natural-language rewriting
+fn run_search(query: &str, search_opts: &SearchOptions, state: &AppState) -> Result<SearchResponse, String> {
+    if query.trim().is_empty() {
+        return Ok(SearchResponse::default());
+    }
+
+    let started = Instant::now();
+
+    // Check cache first (for exact queries, cached for `cache_ttl_secs`) unless asked not to
+    let cache_key = search_cache_key(query, search_opts, state)?;
+    let (cache_ttl_secs, cache_max_entries) = {
+        let general = state.general.read().map_err(|e| e.to_string())?;
+        (general.cache_ttl_secs, general.cache_max_entries)
+    };
+    if !search_opts.force_refresh {
+        let cache = state.search_cache.read().map_err(|e| e.to_string())?;
+
+        // Check for cached result
+        if let Some((timestamp, cached_results)) = cache.get(&cache_key) {
+            if timestamp.elapsed().as_secs() < cache_ttl_secs {
+                debug!("CACHE HIT: Returning {} cached results for '{}'", cached_results.len(), query);
+                let sample = metrics::SearchSample::new(query, started.elapsed(), &planner::PlanStats::default(), cached_results.len(), true, false);
+                let timings = search_opts.debug.then(|| metrics::SearchTimings::new(&sample, started.elapsed(), &planner::PlanStats::default()));
+                state.metrics.lock().map_err(|e| e.to_string())?.record(sample);
+                return Ok(SearchResponse { results: cached_results.clone(), timings, ..SearchResponse::default() });
+            }
+        }
+    }
+    let cache_time = started.elapsed();
+
+    let ranking = state.ranking.read().map_err(|e| e.to_string())?.clone();
+    let (final_results, degraded, plan_stats) = match experiments::candidate(state)? {
+        None => watched_search(query, search_opts, &ranking, state)?,
+        Some(candidate) => {
+            // Ranking experiment: score the same search both ways, show one of them
+            let (results_a, degraded_a, mut stats) = watched_search(query, search_opts, &ranking, state)?;
+            let (results_b, degraded_b, stats_b) = watched_search(query, search_opts, &candidate, state)?;
+            stats.add(&stats_b);
+            (experiments::record_impression(state, query, results_a, results_b)?, degraded_a || degraded_b, stats)
+        }
+    };
+    let sample = metrics::SearchSample::new(query, started.elapsed(), &plan_stats, final_results.len(), false, degraded);
+    let timings = search_opts.debug.then(|| metrics::SearchTimings::new(&sample, cache_time, &plan_stats));
+    state.metrics.lock().map_err(|e| e.to_string())?.record(sample);
+
+    // Cache the results for future queries (limit cache size to `cache_max_entries`).
+    // Degraded results are not cached so the next attempt gets the full plan again.
+    if !degraded && cache_ttl_secs > 0 {
+        let mut cache = state.search_cache.write().map_err(|e| e.to_string())?;
+
+        // Clean old entries (simple cleanup - remove entries past their lifetime)
+        cache.retain(|_, (timestamp, _)| timestamp.elapsed().as_secs() < cache_ttl_secs);
+
+        cache.remove(&cache_key);
+        while cache.len() >= cache_max_entries {
+            // Remove oldest entries if cache is full
+            let oldest_key = cache.iter()
+                .min_by_key(|(_, (timestamp, _))| timestamp)
+                .map(|(key, _)| key.clone());
+            match oldest_key {
+                Some(key) => cache.remove(&key),
+                None => break,
+            };
+        }
+        cache.insert(cache_key, (Instant::now(), final_results.clone()));
+    }
+
+    Ok(SearchResponse { results: final_results, degraded, timings, ..SearchResponse::default() })
+}
+
+// Run one search under the watchdog. Degraded means the full plan ran out of time or was
+// replaced by the cheap plan, so the results may be incomplete.
+fn watched_search(
+    query: &str,
+    search_opts: &SearchOptions,
+    ranking: &RankingConfig,
+    state: &AppState,
+) -> Result<(Vec<FileEntry>, bool, planner::PlanStats), String> {
+    let budget = state.search_budget.read().map_err(|e| e.to_string())?.clone();
+    let ((results, out_of_time, stats), fell_back) = planner::run_watched(&budget, |planner| {
+        let results = ranked_search(query, search_opts, ranking, state, planner)?;
+        Ok((results, planner.out_of_time(), planner.stats))
+    })?;
+    Ok((results, out_of_time || fell_back, stats))
+}
+
+// Name condition (on ?1) and its bound pattern for the LIKE prefilter of a query
+fn like_prefilter(pattern_info: &PatternInfo, like_pattern: &str, query: &str, search_opts: &SearchOptions) -> (NameMatch, String) {
+    // LIKE only ignores ASCII case, so names are compared folded (see locale.rs) once the pattern
+    // has other letters; plain `name LIKE` keeps using the index. Case-sensitive searches match
+    // with GLOB instead.
+    let folded = locale::fold(like_pattern);
+    let name_like = if folded.is_ascii() { NameMatch::Like } else { NameMatch::FoldedLike };
+    let (name_match, lower_name_match, sql_pattern) = if search_opts.case_sensitive {
+        (NameMatch::Glob, NameMatch::Glob, like_to_glob(like_pattern))
+    } else if search_opts.transliterate && query.is_ascii() {
+        // Romanized names are stored lowercase, so pinyin/romaji input can find CJK names
+        (name_like, NameMatch::Romanized, folded)
+    } else {
+        (name_like, NameMatch::FoldedLike, folded)
+    };
+
+    // Extension and prefix patterns match on the name as typed; the rest ignore case
+    let where_sql = match pattern_info.pattern_type {
+        PatternType::SimpleGlob | PatternType::SimplePrefix => name_match,
+        _ => lower_name_match,
+    };
+    (where_sql, sql_pattern)
+}
+
+// Open history, favorites and the active project, which get a ranking boost
+fn boost_lists(db: &Connection) -> Result<Boosts, String> {
+    let usage = frecency::load(db).map_err(|e| e.to_string())?;
+    let active_project = projects::active_project(db).map_err(|e| e.to_string())?;
+    Ok(Boosts { usage, now: Utc::now().timestamp(), active_project })
+}
+
+// Mirror and folder conditions for a search. A folder named with `@root` or `in:` is searched
+// even when it lies in a backup mirror.
+fn scope_filter(root_scope: mirrors::RootScope, path_scope: Option<&path_scope::PathScope>) -> SqlFilter {
+    match path_scope {
+        Some(scope) => {
+            let mut filter = if root_scope == mirrors::RootScope::Primary { SqlFilter::default() } else { SqlFilter::new(root_scope.sql_clause()) };
+            filter.and_bound(scope.sql_clause());
+            filter
+        }
+        None => SqlFilter::new(root_scope.sql_clause()),
+    }
+}
+
+// Run one search with the given scoring weights
+fn ranked_search(
+    query: &str,
+    search_opts: &SearchOptions,
+    ranking: &RankingConfig,
+    state: &AppState,
+    planner: &mut planner::SearchPlanner,
+) -> Result<Vec<FileEntry>, String> {
+    // `in:backups` searches mirror roots instead of the primary ones, `@root` and `in:path`
+    // limit it to a root or folder, `type:image` tokens become an extension filter,
+    // `tag:`/`#tag`/`category:` tokens a tag filter, `modified:week` a time filter and `content:todo`
+    // a search of file text; the rest is the actual query
+    let typed_query = query;
+    let (query, root_scope) = mirrors::extract_scope(query);
+    let (query, path_scope) = {
+        let db = state.readers.get()?;
+        path_scope::extract_path_scope(&query, &db)?
+    };
+    let (query, type_filter, tag_filter) = {
+        let groups = state.extension_groups.read().map_err(|e| e.to_string())?;
+        let (query, type_filter) = groups.extract_type_filter(&query);
+        let (query, tag_filter) = tags::extract_tag_filter(&query, &groups);
+        (query, type_filter, tag_filter)
+    };
+    let (query, time_filter) = time_filter::extract_time_filter(&query, Local::now());
+    let (query, content_terms) = content_search::extract_terms(&query);
+    let mut sql_filter = scope_filter(root_scope, path_scope.as_ref());
+    // Applied in SQL like the query's own scope, so ranking and the result limit only see files inside it
+    let fixed_scope = search_opts.scope.as_deref().map(path_scope::PathScope::under);
+    if let Some(scope) = &fixed_scope {
+        sql_filter.and_bound(scope.sql_clause());
+    }
+    if let Some(filter) = &type_filter {
+        sql_filter.and(&filter.sql_clause());
+    }
+    if let Some(filter) = &tag_filter {
+        sql_filter.and(&filter.sql_clause());
+    }
+    if let Some(filter) = &time_filter {
+        sql_filter.and_bound(filter.sql_clause());
+    }
+    let content_hits = if content_terms.is_empty() {
+        None
+    } else {
+        Some(content_search::find(&content_terms, &sql_filter, search_opts, state, planner)?)
+    };
+    if query.trim().is_empty() {
+        if let Some(mut hits) = content_hits {
+            if root_scope == mirrors::RootScope::Backups {
+                hits.iter_mut().for_each(|entry| entry.in_backup = true);
+            }
+            assign_age_buckets(&mut hits);
+            return Ok(hits);
+        }
+        let filtered = path_scope.is_some() || fixed_scope.is_some() || type_filter.is_some() || tag_filter.is_some() || time_filter.is_some();
+        if filtered || root_scope == mirrors::RootScope::Backups {
+            return list_filtered_files(&sql_filter, state);
+        }
+        return Ok(vec![]);
+    }
+
+    // Intelligent pattern analysis and optimization
+    let pattern_info = analyze_regex_pattern(&query);
+    debug!("PATTERN ANALYSIS: {:?}", pattern_info);
+
+    let glob_query = match pattern_info.pattern_type {
+        PatternType::SimpleGlob | PatternType::Glob => {
+            Some(globs::GlobQuery::new(&pattern_info.regex_pattern, search_opts.case_sensitive)?)
+        }
+        _ => None,
+    };
+
+    // Regex patterns are compiled up front so rows can be matched while the scan reads them
+    let regex = match pattern_info.pattern_type {
+        PatternType::PrefixSuffix | PatternType::ComplexRegex => compile_search_regex(&query, &pattern_info, state)?,
+        _ => None,
+    };
+    let regex_keep = |(path, name, _): &(String, String, Option<i64>)| {
+        regex.as_ref().is_none_or(|re| re.is_match(name) || (search_opts.matches_folders() && re.is_match(path)))
+    };
+    let row_to_file = |row: &rusqlite::Row<'_>| Ok((row.get(0)?, row.get(1)?, row.get(2)?));
+
+    let (files, boosts) = {
+        let db = state.readers.get()?;
 
-        // Intelligent pattern analysis and optimization
-        let pattern_info = analyze_regex_pattern(&query);
-        println!("PATTERN ANALYSIS: {:?}", pattern_info);
-        
         // SEARCH FILES - use optimized strategy based on pattern analysis
         let files: Vec<(String, String, Option<i64>)> = if pattern_info.can_use_sql_optimization {
             // OPTIMIZED PATH: Use SQL LIKE for pre-filtering
             let start_time = Instant::now();
             
-            if let Some(sql_pattern) = &pattern_info.sql_like_pattern {
-                let (query_sql, limit) = match pattern_info.pattern_type {
-                    PatternType::SimpleGlob if pattern_info.suffix.is_some() => {
-                        // For *.ext patterns, very restrictive limit for 1.5M files
-                        ("SELECT path, name, modified_at FROM files WHERE name LIKE ?1 ORDER BY length(name) LIMIT ?2", 500)
-                    },
-                    PatternType::SimplePrefix => {
-                        // For prefix patterns, moderate limit with fast exact matching
-                        ("SELECT path, name, modified_at FROM files WHERE name LIKE ?1 ORDER BY CASE WHEN name LIKE ?1 THEN 0 ELSE 1 END, length(name) LIMIT ?2", 1000)
-                    },
-                    PatternType::LiteralSearch if query.contains(' ') => {
-                        // For multi-word literal searches, very conservative limit
-                        ("SELECT path, name, modified_at FROM files WHERE LOWER(name) LIKE LOWER(?1) ORDER BY length(name) LIMIT ?2", 300)
-                    },
-                    _ => {
-                        // For other patterns, ultra-conservative limit
-                        ("SELECT path, name, modified_at FROM files WHERE LOWER(name) LIKE LOWER(?1) ORDER BY length(name) LIMIT ?2", 200)
+            if let Some(like_pattern) = &pattern_info.sql_like_pattern {
+                let (name_match, sql_pattern) = like_prefilter(&pattern_info, like_pattern, &query, search_opts);
+
+                // A word that extends the last one re-scores that search's candidates (see incremental.rs)
+                let word = like_pattern
+                    .strip_prefix('%')
+                    .and_then(|rest| rest.strip_suffix('%'))
+                    .and_then(name_index::like_literal)
+                    .filter(|word| !word.is_empty() && matches!(pattern_info.pattern_type, PatternType::LiteralSearch));
+                let reuse_key = incremental::key(name_match, &sql_filter, search_opts);
+                let reused = word
+                    .as_deref()
+                    .filter(|_| !search_opts.force_refresh)
+                    .and_then(|word| incremental::reuse(&state.last_candidates, &reuse_key, name_match, word));
+                if let Some(results) = reused {
+                    debug!("INCREMENTAL: Pattern '{}' re-scores {} candidates of the last search", query, results.len());
+                    planner.stats.candidate_source = Some("incremental");
+                    results
+                } else {
+                    // `abc%` patterns take their candidates from the name index, and plain words read
+                    // the names starting with them before the wider scan (see name_index.rs)
+                    let mut results: Vec<(String, String, Option<i64>)> = Vec::new();
+                    let name_prefix = like_pattern.strip_suffix('%').and_then(name_index::like_literal).filter(|prefix| !prefix.is_empty());
+                    let lookup_started = Instant::now();
+                    let name_ids = match &name_prefix {
+                        Some(prefix) => name_index::lookup(state, &db, prefix)?,
+                        None => None,
+                    };
+                    let word_ids = word.as_deref().map(|word| name_index::lookup(state, &db, word)).transpose()?.flatten();
+                    planner.stats.index_lookup_time += lookup_started.elapsed();
+                    if let Some(ids) = word_ids {
+                        let query_sql = search_sql::prefilter(name_match, Candidates::Names, &sql_filter);
+                        let sql_params = sql_filter.bind(vec![Value::Text(sql_pattern.clone()), Value::Text(ids)], vec![]);
+                        results = planner.query(&db, &query_sql, rusqlite::params_from_iter(sql_params), row_to_file, regex_keep)?;
                     }
-                };
-                
-                let mut stmt = db.prepare(query_sql).map_err(|e| e.to_string())?;
-                let results: Vec<(String, String, Option<i64>)> = stmt.query_map([sql_pattern, &limit.to_string()], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))
-                    .map_err(|e| e.to_string())?
-                    .filter_map(|r| r.ok())
+
+                    let trigram_match = trigram::match_expression(std::slice::from_ref(like_pattern))
+                        .filter(|_| name_ids.is_none() && trigram::is_current(&db));
+                    let candidates = match (&name_ids, &trigram_match) {
+                        (Some(_), _) => Candidates::Names,
+                        (None, Some(_)) => Candidates::Trigram,
+                        (None, None) => Candidates::Scan,
+                    };
+                    let query_sql = search_sql::prefilter(name_match, candidates, &sql_filter);
+                    planner.stats.candidate_source = Some(candidates.label());
+
+                    let mut sql_params = vec![Value::Text(sql_pattern.clone())];
+                    sql_params.extend(name_ids.or_else(|| trigram_match.clone()).map(Value::Text));
+                    let sql_params = sql_filter.bind(sql_params, vec![]);
+                    let seen: HashSet<String> = results.iter().map(|(path, _, _)| path.clone()).collect();
+                    let rows = planner.query(&db, &query_sql, rusqlite::params_from_iter(sql_params), row_to_file, regex_keep)?;
+                    results.extend(rows.into_iter().filter(|(path, _, _)| !seen.contains(path)));
+                    let duration = start_time.elapsed();
+                    debug!("OPTIMIZED SQL: Pattern '{}' → SQL '{}' ({:?}) found {} files in {}ms", 
+                             query, sql_pattern, candidates, results.len(), duration.as_millis());
+                    if let Some(word) = word.as_deref().filter(|_| !planner.truncated) {
+                        incremental::remember(&state.last_candidates, reuse_key, name_match, word, &results);
+                    }
+                    results
+                }
+            } else if let Some(glob) = &glob_query {
+                // LIKE is only a superset here; globset decides the actual matches
+                let clauses: Vec<String> = (1..=glob.name_like_patterns.len())
+                    .map(|i| format!("name LIKE ?{} {}", i, LIKE_ESCAPE))
                     .collect();
+                let mut sql_params = glob.name_like_patterns.clone();
+                let mut where_sql = format!("({})", clauses.join(" OR "));
+                if let Some(path_literal) = &glob.path_literal {
+                    sql_params.push(path_literal.clone());
+                    where_sql.push_str(&format!(" AND path LIKE ?{} {}", sql_params.len(), LIKE_ESCAPE));
+                }
+                planner.stats.candidate_source = Some(Candidates::Scan.label());
+                if let Some(expression) = trigram::match_expression(&glob.name_like_patterns).filter(|_| trigram::is_current(&db)) {
+                    planner.stats.candidate_source = Some(Candidates::Trigram.label());
+                    sql_params.push(expression);
+                    where_sql.push_str(&format!(" AND id IN (SELECT rowid FROM files_fts WHERE files_fts MATCH ?{})", sql_params.len()));
+                }
+                // The filter's placeholders come after the numbered ones
+                where_sql.push_str(&sql_filter.sql);
+
+                let query_sql = format!("SELECT path, name, modified_at FROM files WHERE {}", where_sql);
+                let sql_params = sql_filter.bind(sql_params.into_iter().map(Value::Text).collect(), vec![]);
+                let results = planner.query(&db, &query_sql, rusqlite::params_from_iter(sql_params), row_to_file, |(path, name, _)| {
+                    glob.is_match(name, path)
+                })?;
                 let duration = start_time.elapsed();
-                println!("OPTIMIZED SQL: Pattern '{}' → SQL '{}' found {} files in {}ms", 
-                         query, sql_pattern, results.len(), duration.as_millis());
+                debug!("GLOB PREFILTER: Pattern '{}' → SQL {:?} found {} files in {}ms",
+                         query, sql_params, results.len(), duration.as_millis());
                 results
             } else {
                 vec![]
             }
         } else {
-            // COMPLEX REGEX PATH: Load files for full regex matching - very limited for 1.5M files
+            // COMPLEX REGEX PATH: scan the index, matching the regex as rows are read
             let start_time = Instant::now();
-            let limit = if pattern_info.prefix.is_some() { 2000 } else { 1000 };
-            
-            let mut stmt = db
-                .prepare(&format!("SELECT path, name, modified_at FROM files LIMIT {}", limit))
-                .map_err(|e| e.to_string())?;
-            let results: Vec<(String, String, Option<i64>)> = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))
-                .map_err(|e| e.to_string())?
-                .filter_map(|r| r.ok())
-                .collect();
+            let query_sql = search_sql::scan(&sql_filter);
+            planner.stats.candidate_source = Some(Candidates::Scan.label());
+            let results = planner.query(&db, &query_sql, rusqlite::params_from_iter(sql_filter.bind(vec![], vec![])), row_to_file, regex_keep)?;
             let duration = start_time.elapsed();
-            println!("COMPLEX REGEX: Loaded {} files for pattern '{}' in {}ms", results.len(), query, duration.as_millis());
+            debug!("COMPLEX REGEX: Matched {} files for pattern '{}' in {}ms", results.len(), query, duration.as_millis());
             results
         };
 
-        // Get recent files for boost
-        let mut recent_stmt = db
-            .prepare("SELECT path FROM recent_files ORDER BY access_count DESC, last_accessed DESC LIMIT 50")
-            .map_err(|e| e.to_string())?;
-
-        let recent: Vec<String> = recent_stmt
-            .query_map([], |row| row.get(0))
-            .map_err(|e| e.to_string())?
-            .filter_map(|r| r.ok())
-            .collect();
-
-        // Get favorite files for boost
-        let mut fav_stmt = db
-            .prepare("SELECT path FROM favorite_files")
-            .map_err(|e| e.to_string())?;
-
-        let favorites: Vec<String> = fav_stmt
-            .query_map([], |row| row.get(0))
-            .map_err(|e| e.to_string())?
-            .filter_map(|r| r.ok())
-            .collect();
-
-        (files, recent, favorites)
+        let boosts = boost_lists(&db)?;
+        (files, boosts)
     }; // Database lock is automatically released here
 
-    // Analyze the query pattern using our unified pattern analyzer
-    let pattern_info = analyze_regex_pattern(&query);
-    
-    println!("Pattern analysis for '{}': type={:?}, can_use_sql={}, prefix={:?}, suffix={:?}", 
+    debug!("Pattern analysis for '{}': type={:?}, can_use_sql={}, prefix={:?}, suffix={:?}", 
              query, pattern_info.pattern_type, pattern_info.can_use_sql_optimization, 
              pattern_info.prefix, pattern_info.suffix);
     
     // Process files based on pattern analysis
-    let mut results: Vec<(i64, FileEntry)> = match pattern_info.pattern_type {
+    planner.stats.candidates += files.len();
+    let scoring_started = Instant::now();
+    let results: Vec<(i64, FileEntry)> = match pattern_info.pattern_type {
         PatternType::SimplePrefix => {
             // For simple prefix patterns like "log*" or "^log.*"
             let prefix = pattern_info.prefix.as_deref().unwrap_or("");
-            println!("Processing {} files for simple prefix pattern '{}'", files.len(), prefix);
+            debug!("Processing {} files for simple prefix pattern '{}'", files.len(), prefix);
             
-            let mut exact_results: Vec<(i64, FileEntry)> = files.into_iter()
-                .take(200) // Early termination for 1.5M files - stop after 200 good results
+            // The expensive fuzzy fallback is skipped here for 1.5M files performance
+            let exact_results: Vec<(i64, FileEntry)> = files.into_iter()
                 .map(|(path, name, modified_at)| {
-                    let prefix = pattern_info.prefix.as_deref().unwrap_or("");
-                    let name_lower = name.to_lowercase();
-                    let prefix_lower = prefix.to_lowercase();
-                    
-                    let mut score = if name_lower == prefix_lower {
-                        15000 // Exact filename match - highest priority!
-                    } else {
-                        // Check if prefix matches filename without extension
-                        let name_without_ext = if let Some(dot_pos) = name_lower.rfind('.') {
-                            &name_lower[..dot_pos]
-                        } else {
-                            &name_lower
-                        };
-                        
-                        if name_without_ext == prefix_lower {
-                            14000 // Exact match without extension - very high priority!
-                        } else {
-                            5000 // Regular prefix match
-                        }
-                    };
-                
-                    // Boost if file is recent or favorite
-                    if recent.contains(&path) {
-                        score += 1000;
-                    }
-                    if favorites.contains(&path) {
-                        score += 2000;
-                    }
-                    
+                    // Exact filename (with or without extension) beats a regular prefix match,
+                    // then recent and favorite files get a boost
+                    let score = matched_pattern_score(
+                        &path, &name, prefix, ranking.prefix_match, PREFIX_RULE,
+                        search_opts, &boosts, ranking, &mut ScoreTrace::off(),
+                    );
+
                     (score, FileEntry {
                         path,
                         name,
                         last_accessed: None,
                         access_count: 0,
                         modified_at,
+                        age_bucket: None,
+                        in_backup: false,
+                        match_source: None,
+                        content_snippet: None,
                     })
                 })
             .collect();
 
-            
-            // Skip expensive fuzzy search fallback for 1.5M files performance
-            if false && exact_results.len() < 50 && prefix.len() >= 3 {
-                println!("Adding fuzzy search for broader coverage");
-                
-                let fuzzy_files: Vec<(String, String, Option<i64>)> = {
-                    let db = state.db.lock().map_err(|e| e.to_string())?;
-                    let mut stmt = db
-                        .prepare("SELECT path, name, modified_at FROM files WHERE name LIKE ?1 OR path LIKE ?2 LIMIT 2000")
-                        .map_err(|e| e.to_string())?;
-                    let broad_pattern = format!("%{}%", prefix);
-                    let results: Vec<(String, String, Option<i64>)> = stmt.query_map([&broad_pattern, &broad_pattern], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))
-                        .map_err(|e| e.to_string())?
-                        .filter_map(|r| r.ok())
-                        .collect();
-                    results
-                };
-            
-                
-                let fuzzy_results: Vec<(i64, FileEntry)> = fuzzy_files.into_iter()
-                    .filter_map(|(path, name, modified_at)| {
-                        if exact_results.iter().any(|(_, entry)| entry.path == path) {
-                            return None;
-                        }
-                        
-                        let name_score = fuzzy_match_score(&name.to_lowercase(), &prefix.to_lowercase());
-                        let path_score = fuzzy_match_score(&path.to_lowercase(), &prefix.to_lowercase());
-                        let best_score = name_score.max(path_score);
-                        
-                        if best_score > 0.6 {
-                            let mut score = (best_score * 3000.0) as i64;
-                            
-                            if recent.contains(&path) {
-                                score += 1000;
-                            }
-                            if favorites.contains(&path) {
-                                score += 2000;
-                            }
-                            
-                            Some((score, FileEntry {
-                                path,
-                                name,
-                                last_accessed: None,
-                                access_count: 0,
-                                modified_at,
-                            }))
-                        } else {
-                            None
-                        }
-                    })
-                    .collect();
-            
-                
-                println!("Added {} fuzzy matches to {} exact matches", fuzzy_results.len(), exact_results.len());
-                exact_results.extend(fuzzy_results);
-            }
-            
             exact_results
         }
         
-        PatternType::SimpleGlob | PatternType::PrefixSuffix | PatternType::ComplexRegex => {
-            // For patterns that need regex matching
-            let regex_pattern = match pattern_info.pattern_type {
-                PatternType::SimpleGlob => build_glob_regex(&query),
-                PatternType::PrefixSuffix => {
-                    if query.starts_with('^') {
-                        query.to_string()
-                    } else {
-                        format!("^{}$", query)
-                    }
-                }
-                PatternType::ComplexRegex => {
-                    if query.starts_with('/') && query.ends_with('/') {
-                        query[1..query.len()-1].to_string()
-                    } else {
-                        query.to_string()
-                    }
-                }
-                _ => unreachable!()
-            };
-            
-            println!("Processing {} files with regex '{}' for pattern type {:?}", 
-                     files.len(), regex_pattern, pattern_info.pattern_type);
-            
-            // Check regex cache first, then compile if needed
-            let re = {
-                let mut regex_cache = state.regex_cache.lock().map_err(|e| e.to_string())?;
-                
-                // Clean cache if it gets too large (keep only 50 recent patterns)
-                if regex_cache.len() > 50 {
-                    regex_cache.clear();
-                }
-                
-                if let Some(cached_regex) = regex_cache.get(&regex_pattern) {
-                    println!("REGEX CACHE HIT for pattern '{}'", regex_pattern);
-                    cached_regex.clone()
-                } else {
-                    match Regex::new(&regex_pattern) {
-                        Ok(new_regex) => {
-                            regex_cache.insert(regex_pattern.clone(), new_regex.clone());
-                            println!("REGEX COMPILED and cached for pattern '{}'", regex_pattern);
-                            new_regex
-                        }
-                        Err(e) => {
-                            println!("Invalid regex '{}': {}", regex_pattern, e);
-                            let files_2tuple: Vec<(String, String)> = files.into_iter().map(|(path, name, _)| (path, name)).collect();
-                            let fuzzy_results = fuzzy_search_files(files_2tuple, &query, &recent, &favorites, &search_opts);
-                            return Ok(fuzzy_results.into_iter().map(|(_, entry)| entry).collect());
-                        }
-                    }
-                }
-            };
-            
-            // Now use the cached/compiled regex
-            // Use parallel processing for large file sets (>1000 files) with early termination
-            let matched_files: Vec<(i64, FileEntry)> = if files.len() > 1000 {
-                files.into_par_iter()
-                    .take(300) // Early termination - only process first 300 files for regex
-                    .filter_map(|(path, name, modified_at)| {
-                        if re.is_match(&name) || re.is_match(&path) {
-                            let name_lower = name.to_lowercase();
-                            let query_lower = query.to_lowercase();
-                            
-                            let mut score = if name_lower == query_lower {
-                                15000 // Exact filename match - highest priority!
-                            } else {
-                                // Check if query matches filename without extension
-                                let name_without_ext = if let Some(dot_pos) = name_lower.rfind('.') {
-                                    &name_lower[..dot_pos]
-                                } else {
-                                    &name_lower
-                                };
-                                
-                                if name_without_ext == query_lower {
-                                    14000 // Exact match without extension - very high priority!
-                                } else {
-                                    4000 // Regular regex match
-                                }
-                            };
-                            
-                            if recent.contains(&path) {
-                                score += 1000;
-                            }
-                            if favorites.contains(&path) {
-                                score += 2000;
-                            }
-                            
-                            Some((score, FileEntry {
-                                path,
-                                name,
-                                last_accessed: None,
-                                access_count: 0,
-                                modified_at,
-                            }))
-                        } else {
-                            None
-                        }
+        PatternType::SimpleGlob | PatternType::Glob => {
+            let glob = glob_query.as_ref().ok_or("Glob pattern was not compiled")?;
+            debug!("Processing {} files with glob '{}'", files.len(), pattern_info.regex_pattern);
+
+            files.into_par_iter()
+                .filter(|(path, name, _)| glob.is_match(name, path))
+                .map(|(path, name, modified_at)| {
+                    let score = matched_pattern_score(
+                        &path, &name, &query, ranking.regex_match, GLOB_RULE,
+                        search_opts, &boosts, ranking, &mut ScoreTrace::off(),
+                    );
+
+                    (score, FileEntry {
+                        path,
+                        name,
+                        last_accessed: None,
+                        access_count: 0,
+                        modified_at,
+                        age_bucket: None,
+                        in_backup: false,
+                        match_source: None,
+                        content_snippet: None,
                     })
-                    .collect()
+                })
+                .collect()
+        }
+
+        // Not a valid regex: rank the scanned files fuzzily instead, through the same selection
+        PatternType::PrefixSuffix | PatternType::ComplexRegex if regex.is_none() => {
+            if planner.allows_fuzzy() {
+                let files_2tuple: Vec<(String, String)> = files.into_iter().map(|(path, name, _)| (path, name)).collect();
+                timed_fuzzy_search(&mut planner.stats, files_2tuple, &query, &boosts, search_opts, ranking)
             } else {
-                // For smaller sets, sequential processing is faster due to reduced overhead
-                files.into_iter()
-                    .take(200) // Early termination for sequential processing too
-                    .filter_map(|(path, name, modified_at)| {
-                        if re.is_match(&name) || re.is_match(&path) {
-                            let name_lower = name.to_lowercase();
-                            let query_lower = query.to_lowercase();
-                            
-                            let mut score = if name_lower == query_lower {
-                                15000 // Exact filename match - highest priority!
-                            } else {
-                                // Check if query matches filename without extension
-                                let name_without_ext = if let Some(dot_pos) = name_lower.rfind('.') {
-                                    &name_lower[..dot_pos]
-                                } else {
-                                    &name_lower
-                                };
-                                
-                                if name_without_ext == query_lower {
-                                    14000 // Exact match without extension - very high priority!
-                                } else {
-                                    4000 // Regular regex match
-                                }
-                            };
-                            
-                            if recent.contains(&path) {
-                                score += 1000;
-                            }
-                            if favorites.contains(&path) {
-                                score += 2000;
-                            }
-                            
-                            Some((score, FileEntry {
-                                path,
-                                name,
-                                last_accessed: None,
-                                access_count: 0,
-                                modified_at,
-                            }))
-                        } else {
-                            None
-                        }
+                vec![]
+            }
+        }
+
+        PatternType::PrefixSuffix | PatternType::ComplexRegex => {
+            if files.is_empty() && planner.out_of_time() {
+                debug!("Regex for '{}' matched nothing within the {}ms budget", query, planner.total().as_millis());
+                return Err(format!("Pattern too expensive: '{}' matched nothing within {}ms. Try a simpler pattern.", query, planner.total().as_millis()));
+            }
+            debug!("Scoring {} regex matches for pattern type {:?}", files.len(), pattern_info.pattern_type);
+
+            // Rows were matched against the regex while they were read
+            let mut matched_files: Vec<(i64, FileEntry)> = files
+                .into_par_iter()
+                .map(|(path, name, modified_at)| {
+                    let score = matched_pattern_score(
+                        &path, &name, &query, ranking.regex_match, REGEX_RULE,
+                        search_opts, &boosts, ranking, &mut ScoreTrace::off(),
+                    );
+
+                    (score, FileEntry {
+                        path,
+                        name,
+                        last_accessed: None,
+                        access_count: 0,
+                        modified_at,
+                        age_bucket: None,
+                        in_backup: false,
+                        match_source: None,
+                        content_snippet: None,
                     })
-                    .collect()
-            };
+                })
+                .collect();
             
-            println!("Regex matched {} files", matched_files.len());
+            debug!("Regex matched {} files", matched_files.len());
             
             // Add fuzzy search fallback for complex patterns with few matches
-            let mut matched_files = matched_files; // Make mutable for potential extension
-            if matches!(pattern_info.pattern_type, PatternType::PrefixSuffix | PatternType::ComplexRegex) && matched_files.len() < 20 {
+            let fuzzy_fallback = search_opts.enable_fuzzy && !search_opts.strict_mode && planner.allows_fuzzy();
+            if matches!(pattern_info.pattern_type, PatternType::PrefixSuffix | PatternType::ComplexRegex) && matched_files.len() < 20 && fuzzy_fallback {
                 let clean_query = query.replace("^", "").replace(".*", "").replace("$", "").replace(r"\.", ".");
-                if clean_query.len() >= 3 {
-                    println!("Adding fuzzy search fallback for '{}'", clean_query);
+                if clean_query.chars().count() >= 3 {
+                    debug!("Adding fuzzy search fallback for '{}'", clean_query);
                     
+                    // Whatever remains of the time budget goes to the fallback scan
                     let files_2tuple: Vec<(String, String)> = {
-                        let db = state.db.lock().map_err(|e| e.to_string())?;
-                        let mut stmt = db
-                            .prepare("SELECT path, name FROM files WHERE name LIKE ?1 OR path LIKE ?2 LIMIT 2000")
-                            .map_err(|e| e.to_string())?;
-                        let broad_pattern = format!("%{}%", clean_query);
-                        let results: Vec<(String, String)> = stmt.query_map([&broad_pattern, &broad_pattern], |row| Ok((row.get(0)?, row.get(1)?)))
-                            .map_err(|e| e.to_string())?
-                            .filter_map(|r| r.ok())
-                            .collect();
-                        results
+                        let db = state.readers.get()?;
+                        let path_sql = if search_opts.matches_folders() { format!(" OR fold(path) LIKE ?1 {}", LIKE_ESCAPE) } else { String::new() };
+                        let query_sql = format!("SELECT path, name FROM files WHERE (fold(name) LIKE ?1 {}{}){}", LIKE_ESCAPE, path_sql, sql_filter.sql);
+                        let broad_pattern = format!("%{}%", escape_like(&locale::fold(&clean_query)));
+                        let sql_params = sql_filter.bind(vec![Value::Text(broad_pattern)], vec![]);
+                        planner.query(&db, &query_sql, rusqlite::params_from_iter(sql_params), |row| Ok((row.get(0)?, row.get(1)?)), |_| true)?
                     };
                     
-                    let fuzzy_results = fuzzy_search_files(files_2tuple, &clean_query, &recent, &favorites, &search_opts);
+                    let fuzzy_results = timed_fuzzy_search(&mut planner.stats, files_2tuple, &clean_query, &boosts, search_opts, ranking);
                     
                     for (score, entry) in fuzzy_results {
                         if !matched_files.iter().any(|(_, existing)| existing.path == entry.path) {
-                            matched_files.push((score / 2, entry));
+                            matched_files.push((score / ranking.fuzzy_fallback_divisor, entry));
                         }
                     }
                     
-                    println!("Added fuzzy matches, total now: {}", matched_files.len());
+                    debug!("Added fuzzy matches, total now: {}", matched_files.len());
                 }
             }
             
@@ -1350,65 +2159,14 @@ async fn search_files(query: String, options: Option<SearchOptions>, state: Stat
         PatternType::LiteralSearch => {
             // For simple text searches, use SQL optimization if available, otherwise fuzzy search
             if pattern_info.can_use_sql_optimization && !files.is_empty() {
-                println!("Using SQL-optimized literal search for pattern '{}' on {} pre-filtered files", query, files.len());
-                // Convert SQL-optimized results to scored FileEntry format with early termination
+                debug!("Using SQL-optimized literal search for pattern '{}' on {} pre-filtered files", query, files.len());
+                // Strict mode only takes names that start with the query, as in fuzzy_score
+                let strict_prefix = search_opts.strict_mode.then(|| fold_case(&query, search_opts.case_sensitive));
                 files.into_iter()
-                    .take(150) // Early termination - only process first 150 SQL-optimized results
+                    .filter(|(_, name, _)| strict_prefix.as_ref().is_none_or(|prefix| fold_case(name, search_opts.case_sensitive).starts_with(prefix)))
                     .map(|(path, name, modified_at)| {
                         // Score based on how well the query matches (case-insensitive substring match)
-                        let name_lower = name.to_lowercase();
-                        let path_lower = path.to_lowercase();
-                        let query_lower = query.to_lowercase();
-                        
-                        let mut score = if name_lower.contains(&query_lower) {
-                            if name_lower == query_lower {
-                                15000 // Exact filename match - highest priority!
-                            } else {
-                                // Check if query matches filename without extension
-                                let name_without_ext = if let Some(dot_pos) = name_lower.rfind('.') {
-                                    &name_lower[..dot_pos]
-                                } else {
-                                    &name_lower
-                                };
-                                
-                                if name_without_ext == query_lower {
-                                    14000 // Exact match without extension - very high priority!
-                                } else if name_lower.starts_with(&query_lower) {
-                                    4000 // Starts with query
-                                } else {
-                                    3000 // Contains query
-                                }
-                            }
-                        } else if path_lower.contains(&query_lower) {
-                            2000 // Path contains query
-                        } else {
-                            // For multi-word queries, check if all words are present in the filename
-                            let words: Vec<&str> = query_lower.split_whitespace().collect();
-                            if words.len() > 1 {
-                                let all_words_in_name = words.iter().all(|word| name_lower.contains(word));
-                                let all_words_in_path = words.iter().all(|word| path_lower.contains(word));
-                                
-                                if all_words_in_name {
-                                    // All words found in filename - good match for multi-word queries
-                                    2800
-                                } else if all_words_in_path {
-                                    // All words found in path
-                                    1800  
-                                } else {
-                                    1000 // Partial match
-                                }
-                            } else {
-                                1000 // SQL matched but we're not sure why
-                            }
-                        };
-                        
-                        // Boost for recent/favorite files
-                        if recent.contains(&path) {
-                            score += 1000;
-                        }
-                        if favorites.contains(&path) {
-                            score += 2000;
-                        }
+                        let score = literal_score(&path, &name, &query, search_opts, &boosts, ranking, &mut ScoreTrace::off());
                         
                         (score, FileEntry {
                             path,
@@ -1416,162 +2174,234 @@ async fn search_files(query: String, options: Option<SearchOptions>, state: Stat
                             last_accessed: None,
                             access_count: 0,
                             modified_at,
+                            age_bucket: None,
+                            in_backup: false,
+                            match_source: None,
+                            content_snippet: None,
                         })
                     })
                     .collect()
-            } else {
-                println!("Using fuzzy search for literal pattern '{}'", query);
+            } else if planner.allows_fuzzy() {
+                debug!("Using fuzzy search for literal pattern '{}'", query);
                 let files_2tuple: Vec<(String, String)> = files.into_iter().map(|(path, name, _)| (path, name)).collect();
-                fuzzy_search_files(files_2tuple, &query, &recent, &favorites, &search_opts)
+                timed_fuzzy_search(&mut planner.stats, files_2tuple, &query, &boosts, search_opts, ranking)
+            } else {
+                vec![]
             }
         }
     };
 
-    // Optimized sorting for 1.5M files - use partial sort for better performance
-    let final_results: Vec<FileEntry> = if results.len() > 1000 {
-        // For large result sets, use partial sort to get only top 500 results
-        let k = 500.min(results.len());
-        results.select_nth_unstable_by(k - 1, |a, b| b.0.cmp(&a.0));
-        results.into_iter().take(k).map(|(_, entry)| entry).collect()
-    } else if results.len() > 100 {
-        // For medium result sets, use partial sort to get top 300
-        let k = 300.min(results.len());
-        results.select_nth_unstable_by(k - 1, |a, b| b.0.cmp(&a.0));
-        results.into_iter().take(k).map(|(_, entry)| entry).collect()
-    } else {
-        // For small result sets, full sort is fine
-        results.sort_unstable_by(|a, b| b.0.cmp(&a.0));
-        results.into_iter().take(100).map(|(_, entry)| entry).collect()
-    };
-    
-    // Cache the results for future queries (limit cache size to 100 entries)
+    planner.stats.scoring_time += scoring_started.elapsed();
+
+    // Every scored file competes for the result list, best first (see top_k.rs)
+    let sorting_started = Instant::now();
+    let max_results = state.general.read().map_err(|e| e.to_string())?.max_results;
+    let scored = results
+        .into_iter()
+        .filter(|(_, entry)| type_filter.as_ref().is_none_or(|filter| filter.matches(&entry.name)));
+    let mut final_results: Vec<FileEntry> = top_k::top_k(scored, max_results).into_iter().map(|(_, entry)| entry).collect();
+    planner.stats.sort_time += sorting_started.elapsed();
+    if let Some(hits) = content_hits {
+        final_results = content_search::merge(final_results, hits);
+    }
     {
-        let mut cache = state.search_cache.lock().map_err(|e| e.to_string())?;
-        if cache.len() >= 100 {
-            // Remove oldest entries if cache is full
-            let oldest_key = cache.iter()
-                .min_by_key(|(_, (timestamp, _))| timestamp)
-                .map(|(key, _)| key.clone());
-            if let Some(key) = oldest_key {
-                cache.remove(&key);
-            }
+        let db = state.readers.get()?;
+        // Plain words also look through the notes attached to files
+        if matches!(pattern_info.pattern_type, PatternType::LiteralSearch) && notes::config(&db).search_notes {
+            let hits = notes::find(&db, &query, &sql_filter)?;
+            final_results = notes::merge(final_results, hits);
         }
-        cache.insert(cache_key, (Instant::now(), final_results.clone()));
+        // Pinned results go first, whatever their score
+        final_results = pins::apply(&db, typed_query, &sql_filter, final_results)?;
     }
-
+    
+    final_results.truncate(max_results);
+    if root_scope == mirrors::RootScope::Backups {
+        final_results.iter_mut().for_each(|entry| entry.in_backup = true);
+    }
+    assign_age_buckets(&mut final_results);
     Ok(final_results)
 }
 
 #[tauri::command]
-async fn get_recent_files(state: State<'_, AppState>) -> Result<Vec<FileEntry>, String> {
-    let db = state.db.lock().map_err(|e| e.to_string())?;
+#[specta::specta]
+async fn get_recent_files(state: State<'_, AppState>) -> Result<Vec<FileEntry>, AppError> {
+    db::write(&state.db, |db| Ok(recent_entries(db, None)?))
+}
 
+// Recent files opened under one indexed root, so each root keeps its own list
+#[tauri::command]
+#[specta::specta]
+async fn get_recent_files_for_root(root: String, state: State<'_, AppState>) -> Result<Vec<FileEntry>, AppError> {
+    db::write(&state.db, |db| Ok(recent_entries(db, Some(&root))?))
+}
+
+// Most used recent files, from every root or from one
+fn recent_entries(db: &Connection, root: Option<&str>) -> Result<Vec<FileEntry>, String> {
     let mut stmt = db
         .prepare("SELECT rf.path, rf.name, rf.last_accessed, rf.access_count, f.modified_at 
                   FROM recent_files rf 
                   LEFT JOIN files f ON rf.path = f.path 
+                  WHERE ?1 IS NULL OR rf.root_directory = ?1
                   ORDER BY rf.access_count DESC, rf.last_accessed DESC LIMIT 20")
         .map_err(|e| e.to_string())?;
 
-    let files: Vec<FileEntry> = stmt
-        .query_map([], |row| {
+    let mut files: Vec<FileEntry> = stmt
+        .query_map([root], |row| {
             Ok(FileEntry {
                 path: row.get(0)?,
                 name: row.get(1)?,
                 last_accessed: Some(row.get(2)?),
                 access_count: row.get(3)?,
                 modified_at: row.get(4)?,
+                age_bucket: None,
+                in_backup: false,
+                match_source: None,
+                content_snippet: None,
             })
         })
         .map_err(|e| e.to_string())?
         .filter_map(|r| r.ok())
         .collect();
 
+    mirrors::mark_backup_entries(db, &mut files);
+    assign_age_buckets(&mut files);
     Ok(files)
 }
 
+// Files changed on disk, newest first, whether or not they were ever opened from the app.
+// `root` limits the feed to a folder, `since` to changes at or after a Unix time.
 #[tauri::command]
-async fn open_file(path: String, state: State<'_, AppState>) -> Result<(), String> {
-    // Update recent files
-    let db = state.db.lock().map_err(|e| e.to_string())?;
-    let now = Utc::now().timestamp();
+#[specta::specta]
+async fn get_recently_modified(
+    root: Option<String>,
+    limit: Option<u32>,
+    since: Option<i64>,
+    state: State<'_, AppState>,
+) -> Result<Vec<FileEntry>, AppError> {
+    let limit = limit.unwrap_or(50).clamp(1, 1000);
+    let scope = root.as_deref().map(path_scope::PathScope::under);
+    let mut filter = scope_filter(mirrors::RootScope::Primary, scope.as_ref());
+    if let Some(since) = since {
+        filter.and_bound(("modified_at >= ?".to_string(), vec![since]));
+    }
 
-    let path_obj = PathBuf::from(&path);
-    let name = path_obj
-        .file_name()
-        .and_then(|n| n.to_str())
-        .unwrap_or(&path);
+    db::blocking(|| {
+        let db = state.readers.get()?;
+        let mut stmt = db
+            .prepare_cached(&format!(
+                "SELECT path, name, modified_at FROM files WHERE modified_at IS NOT NULL{} ORDER BY modified_at DESC LIMIT ?",
+                filter.sql
+            ))?;
+        let map_row = |row: &rusqlite::Row| {
+            Ok(FileEntry {
+                path: row.get(0)?,
+                name: row.get(1)?,
+                last_accessed: None,
+                access_count: 0,
+                modified_at: row.get(2)?,
+                age_bucket: None,
+                in_backup: false,
+                match_source: None,
+                content_snippet: None,
+            })
+        };
+        let rows = stmt.query_map(rusqlite::params_from_iter(filter.bind(vec![], vec![Value::Integer(limit.into())])), map_row)?;
+        let mut files: Vec<FileEntry> = rows.filter_map(|r| r.ok()).collect();
 
-    db.execute(
-        "INSERT INTO recent_files (path, name, last_accessed, access_count)
-         VALUES (?1, ?2, ?3, 1)
-         ON CONFLICT(path) DO UPDATE SET
-            last_accessed = ?3,
-            access_count = access_count + 1",
-        params![path, name, now],
-    )
-    .map_err(|e| e.to_string())?;
+        mirrors::mark_backup_entries(&db, &mut files);
+        assign_age_buckets(&mut files);
+        Ok(files)
+    })
+}
 
-    drop(db); // Release lock before opening file
+#[tauri::command]
+#[specta::specta]
+async fn open_file(path: String, state: State<'_, AppState>) -> Result<(), AppError> {
+    db::blocking(|| {
+        record_open(&path, &state)?;
+
+        // Entries inside archives are opened from an extracted copy
+        if archives::split(&path).is_some() {
+            let extracted = archives::extract_for_opening(&path)?;
+            return opener::open(native_path::openable(extracted)).map_err(|e| e.to_string().into());
+        }
 
-    // Open file with default application
-    opener::open(&path).map_err(|e| e.to_string())?;
+        // Open file with default application, by its exact path if the name isn't valid Unicode,
+        // prefixed past MAX_PATH on Windows
+        let native = native_path::resolve(&*state.readers.get()?, &path)?;
+        opener::open(native_path::openable(native)).map_err(|e| e.to_string())?;
 
-    Ok(())
+        Ok(())
+    })
 }
 
-#[tauri::command]
-async fn open_file_with(path: String, program: String, state: State<'_, AppState>) -> Result<(), String> {
-    // Update recent files
+// Bookkeeping for a file the user opened: recent files and ranking experiment clicks
+fn record_open(path: &str, state: &AppState) -> Result<(), AppError> {
     let db = state.db.lock().map_err(|e| e.to_string())?;
     let now = Utc::now().timestamp();
 
-    let path_obj = PathBuf::from(&path);
+    let path_obj = PathBuf::from(path);
     let name = path_obj
         .file_name()
         .and_then(|n| n.to_str())
-        .unwrap_or(&path);
+        .unwrap_or(path);
+
+    // The root it was opened under, for per-root recents
+    let root = file_ops::indexed_root(&db, &path_obj)?;
 
     db.execute(
-        "INSERT INTO recent_files (path, name, last_accessed, access_count)
-         VALUES (?1, ?2, ?3, 1)
+        "INSERT INTO recent_files (path, name, last_accessed, access_count, root_directory)
+         VALUES (?1, ?2, ?3, 1, ?4)
          ON CONFLICT(path) DO UPDATE SET
             last_accessed = ?3,
-            access_count = access_count + 1",
-        params![path, name, now],
-    )
-    .map_err(|e| e.to_string())?;
+            access_count = access_count + 1,
+            root_directory = ?4",
+        params![path, name, now, root],
+    )?;
+    if let Err(e) = projects::touch_active(&db, path, now) {
+        warn!("Failed to update the active project: {}", e);
+    }
 
     drop(db);
+    Ok(experiments::record_click(state, path)?)
+}
 
-    // Open file with specified program
-    #[cfg(target_os = "windows")]
-    {
-        std::process::Command::new("cmd")
-            .args(&["/C", "start", "", &program, &path])
-            .spawn()
-            .map_err(|e| e.to_string())?;
-    }
-    
-    #[cfg(not(target_os = "windows"))]
-    {
-        std::process::Command::new(&program)
-            .arg(&path)
-            .spawn()
-            .map_err(|e| e.to_string())?;
-    }
+#[tauri::command]
+#[specta::specta]
+async fn open_file_with(path: String, program: String, state: State<'_, AppState>) -> Result<(), AppError> {
+    db::blocking(|| record_open(&path, &state))?;
+    // The next Open With for this extension offers the same program first
+    db::write(&state.db, |db| Ok(open_with::remember(db, &path, &program)?))?;
+
+    db::blocking(|| {
+        // Entries inside archives are opened from an extracted copy
+        if archives::split(&path).is_some() {
+            let extracted = archives::extract_for_opening(&path)?;
+            return Ok(open_with::launch(&program, &native_path::openable(extracted))?);
+        }
 
-    Ok(())
+        // By its exact path if the name isn't valid Unicode, as open_file does
+        let native = native_path::resolve(&*state.readers.get()?, &path)?;
+        Ok(open_with::launch(&program, &native_path::openable(native))?)
+    })
 }
 
-#[derive(Serialize)]
+#[derive(Serialize, Type)]
 struct FileInfo {
     extension: String,
+    // Extension group (image, code, ...) usable as a `type:` filter
+    category: Option<String>,
+    // The programs of `apps`, for frontends that only show what to pass to open_file_with
     suggested_programs: Vec<String>,
+    apps: Vec<open_with::OpenWithApp>,
+    // Program last picked for this extension, listed first in `apps`
+    preferred_program: Option<String>,
 }
 
 #[tauri::command]
-async fn get_file_info(path: String) -> Result<FileInfo, String> {
+#[specta::specta]
+async fn get_file_info(path: String, state: State<'_, AppState>) -> Result<FileInfo, AppError> {
     let path_obj = PathBuf::from(&path);
     let extension = path_obj
         .extension()
@@ -1579,38 +2409,35 @@ async fn get_file_info(path: String) -> Result<FileInfo, String> {
         .unwrap_or("")
         .to_lowercase();
 
-    // Common program suggestions based on extension
-    let suggested_programs = match extension.as_str() {
-        "py" => vec!["notepad++.exe", "code.exe", "pycharm64.exe", "notepad.exe"],
-        "java" => vec!["notepad++.exe", "code.exe", "idea64.exe", "notepad.exe"],
-        "js" | "ts" | "jsx" | "tsx" => vec!["code.exe", "notepad++.exe", "webstorm64.exe", "notepad.exe"],
-        "txt" | "md" | "log" => vec!["notepad++.exe", "notepad.exe", "code.exe"],
-        "json" | "xml" | "yaml" | "yml" => vec!["notepad++.exe", "code.exe", "notepad.exe"],
-        "html" | "css" => vec!["code.exe", "notepad++.exe", "chrome.exe", "notepad.exe"],
-        "pdf" => vec!["AcroRd32.exe", "chrome.exe", "msedge.exe"],
-        "jpg" | "jpeg" | "png" | "gif" | "bmp" => vec!["mspaint.exe", "PhotosApp.exe", "chrome.exe"],
-        "mp4" | "avi" | "mkv" => vec!["vlc.exe", "wmplayer.exe"],
-        "mp3" | "wav" | "flac" => vec!["vlc.exe", "wmplayer.exe"],
-        "zip" | "rar" | "7z" => vec!["7zFM.exe", "WinRAR.exe"],
-        "doc" | "docx" => vec!["WINWORD.EXE", "notepad.exe"],
-        "xls" | "xlsx" => vec!["EXCEL.EXE", "notepad.exe"],
-        "ppt" | "pptx" => vec!["POWERPNT.EXE"],
-        _ => vec!["notepad.exe", "code.exe", "notepad++.exe"],
-    };
+    // Applications the OS associates with the file, default first, after the user's own choice
+    let mut apps = open_with::apps_for(&path_obj);
+    let preferred_program = db::write(&state.db, |db| Ok(open_with::preferred(db, &extension)?))?;
+    if let Some(program) = &preferred_program {
+        open_with::put_preferred_first(&mut apps, program);
+    }
+
+    let category = state.extension_groups.read().map_err(|e| e.to_string())?.group_of(&extension);
 
     Ok(FileInfo {
         extension: extension.to_string(),
-        suggested_programs: suggested_programs.iter().map(|s| s.to_string()).collect(),
+        category,
+        suggested_programs: apps.iter().map(|app| app.program.clone()).collect(),
+        apps,
+        preferred_program,
     })
 }
 
 #[tauri::command]
-async fn get_index_status(state: State<'_, AppState>) -> Result<IndexStatus, String> {
-    let db = state.db.lock().map_err(|e| e.to_string())?;
+#[specta::specta]
+async fn get_index_status(state: State<'_, AppState>) -> Result<IndexStatus, AppError> {
+    db::blocking(|| index_status(&state))
+}
+
+fn index_status(state: &AppState) -> Result<IndexStatus, AppError> {
+    let db = state.readers.get()?;
 
     let count: i64 = db
-        .query_row("SELECT COUNT(*) FROM files", [], |row| row.get(0))
-        .map_err(|e| e.to_string())?;
+        .query_row("SELECT COUNT(*) FROM files", [], |row| row.get(0))?;
 
     let last_indexed: Option<i64> = db
         .query_row(
@@ -1626,171 +2453,346 @@ async fn get_index_status(state: State<'_, AppState>) -> Result<IndexStatus, Str
     })
 }
 
+#[derive(Serialize, Type)]
+struct SearchScore {
+    name: String,
+    score: i64,
+    path: String,
+}
+
 #[tauri::command]
-async fn debug_search_scores(state: State<'_, AppState>, query: String) -> Result<Vec<(String, i64, String)>, String> {
-    let db = state.db.lock().map_err(|e| e.to_string())?;
-    
-    let mut stmt = db.prepare("SELECT path, name FROM files WHERE LOWER(name) LIKE ? LIMIT 20")
-        .map_err(|e| e.to_string())?;
-    
-    let pattern = format!("%{}%", query.to_lowercase());
-    let files: Vec<(String, String)> = stmt
-        .query_map([&pattern], |row| {
-            Ok((row.get(0)?, row.get(1)?))
-        })
-        .map_err(|e| e.to_string())?
-        .filter_map(|r| r.ok())
-        .collect();
+#[specta::specta]
+async fn debug_search_scores(state: State<'_, AppState>, query: String) -> Result<Vec<SearchScore>, AppError> {
+    let files = db::blocking(|| -> Result<Vec<(String, String)>, AppError> {
+        let db = state.readers.get()?;
+        let mut stmt = db.prepare(&format!("SELECT path, name FROM files WHERE fold(name) LIKE ? {} LIMIT 20", LIKE_ESCAPE))?;
+        let pattern = format!("%{}%", escape_like(&locale::fold(&query)));
+        let files = stmt
+            .query_map([&pattern], |row| {
+                Ok((row.get(0)?, row.get(1)?))
+            })?
+            .filter_map(|r| r.ok())
+            .collect();
+        Ok(files)
+    })?;
     
     let options = SearchOptions {
         search_folders: false,
         enable_fuzzy: true,
         strict_mode: false,
         filename_only: true,
+        ..Default::default()
     };
     
-    let results = fuzzy_search_files(files, &query, &[], &[], &options);
+    let ranking = state.ranking.read().map_err(|e| e.to_string())?.clone();
+    let results = fuzzy_search_files(files, &query, &Boosts::none(), &options, &ranking);
     
-    let debug_output: Vec<(String, i64, String)> = results.iter()
-        .map(|(score, entry)| (entry.name.clone(), *score, entry.path.clone()))
+    let debug_output: Vec<SearchScore> = results.iter()
+        .map(|(score, entry)| SearchScore { name: entry.name.clone(), score: *score, path: entry.path.clone() })
         .collect();
     
     Ok(debug_output)
 }
 
 #[tauri::command]
-async fn toggle_favorite(state: State<'_, AppState>, path: String) -> Result<bool, String> {
-    let db = state.db.lock().map_err(|e| e.to_string())?;
+#[specta::specta]
+async fn toggle_favorite(state: State<'_, AppState>, path: String) -> Result<bool, AppError> {
+    db::write(&state.db, |db| {
     
-    // Check if already favorited
-    let is_favorited: bool = db
-        .query_row(
-            "SELECT 1 FROM favorite_files WHERE path = ?1",
-            [&path],
-            |_| Ok(true),
-        )
-        .unwrap_or(false);
+        // Check if already favorited
+        let is_favorited: bool = db
+            .query_row(
+                "SELECT 1 FROM favorite_files WHERE path = ?1",
+                [&path],
+                |_| Ok(true),
+            )
+            .unwrap_or(false);
     
-    if is_favorited {
-        // Remove from favorites
-        db.execute("DELETE FROM favorite_files WHERE path = ?1", [&path])
-            .map_err(|e| e.to_string())?;
-        Ok(false)
-    } else {
-        // Add to favorites
-        let name = Path::new(&path)
-            .file_name()
-            .and_then(|n| n.to_str())
-            .unwrap_or("")
-            .to_string();
+        if is_favorited {
+            // Remove from favorites
+            db.execute("DELETE FROM favorite_files WHERE path = ?1", [&path])?;
+            Ok(false)
+        } else {
+            // Add to favorites
+            let name = Path::new(&path)
+                .file_name()
+                .and_then(|n| n.to_str())
+                .unwrap_or("")
+                .to_string();
         
-        let now = SystemTime::now()
-            .duration_since(SystemTime::UNIX_EPOCH)
-            .unwrap()
-            .as_secs() as i64;
+            let now = SystemTime::now()
+                .duration_since(SystemTime::UNIX_EPOCH)
+                .unwrap()
+                .as_secs() as i64;
         
-        db.execute(
-            "INSERT OR REPLACE INTO favorite_files (path, name, favorited_at) VALUES (?1, ?2, ?3)",
-            params![&path, &name, now],
-        )
-        .map_err(|e| e.to_string())?;
-        Ok(true)
-    }
+            // New favorites go to the end of the ungrouped list
+            let sort_order = favorites::next_sort_order(db, None)?;
+            db.execute(
+                "INSERT OR REPLACE INTO favorite_files (path, name, favorited_at, sort_order) VALUES (?1, ?2, ?3, ?4)",
+                params![&path, &name, now, sort_order],
+            )?;
+            Ok(true)
+        }
+    })
 }
 
 #[tauri::command]
-async fn get_favorites(state: State<'_, AppState>) -> Result<Vec<String>, String> {
-    let db = state.db.lock().map_err(|e| e.to_string())?;
+#[specta::specta]
+async fn get_favorites(state: State<'_, AppState>) -> Result<Vec<String>, AppError> {
+    db::write(&state.db, |db| {
     
-    let mut stmt = db
-        .prepare("SELECT path FROM favorite_files ORDER BY favorited_at DESC")
-        .map_err(|e| e.to_string())?;
+        let mut stmt = db
+            .prepare(
+                "SELECT f.path FROM favorite_files f LEFT JOIN favorite_groups g ON g.id = f.group_id
+                 ORDER BY g.sort_order IS NOT NULL, g.sort_order, f.sort_order, f.id",
+            )?;
     
-    let favorites: Vec<String> = stmt
-        .query_map([], |row| row.get(0))
-        .map_err(|e| e.to_string())?
-        .filter_map(|r| r.ok())
-        .collect();
+        let favorites: Vec<String> = stmt
+            .query_map([], |row| row.get(0))?
+            .filter_map(|r| r.ok())
+            .collect();
     
-    Ok(favorites)
+        Ok(favorites)
+    })
 }
 
-#[derive(Serialize)]
+#[derive(Serialize, Type)]
 struct IndexedDirectory {
     path: String,
     name: String,
     is_active: bool,
     indexed_at: i64,
+    is_mirror: bool,
+    // Levels below the folder that are indexed, None for all
+    max_depth: Option<u32>,
+    // Whether hidden folders are indexed too
+    include_hidden: bool,
 }
 
 #[tauri::command]
-async fn get_indexed_directories(state: State<'_, AppState>) -> Result<Vec<IndexedDirectory>, String> {
-    let db = state.db.lock().map_err(|e| e.to_string())?;
+#[specta::specta]
+async fn get_indexed_directories(state: State<'_, AppState>) -> Result<Vec<IndexedDirectory>, AppError> {
+    db::write(&state.db, |db| {
     
-    let mut stmt = db
-        .prepare("SELECT path, name, is_active, indexed_at FROM indexed_directories ORDER BY indexed_at DESC")
-        .map_err(|e| e.to_string())?;
+        let mut stmt = db
+            .prepare("SELECT path, name, is_active, indexed_at, is_mirror, max_depth, include_hidden FROM indexed_directories ORDER BY indexed_at DESC")?;
     
-    let dirs: Vec<IndexedDirectory> = stmt
-        .query_map([], |row| {
-            Ok(IndexedDirectory {
-                path: row.get(0)?,
-                name: row.get(1)?,
-                is_active: row.get::<_, i32>(2)? == 1,
-                indexed_at: row.get(3)?,
-            })
-        })
-        .map_err(|e| e.to_string())?
-        .filter_map(|r| r.ok())
-        .collect();
+        let dirs: Vec<IndexedDirectory> = stmt
+            .query_map([], |row| {
+                Ok(IndexedDirectory {
+                    path: row.get(0)?,
+                    name: row.get(1)?,
+                    is_active: row.get::<_, i32>(2)? == 1,
+                    indexed_at: row.get(3)?,
+                    is_mirror: row.get::<_, Option<i32>>(4)? == Some(1),
+                    max_depth: row.get(5)?,
+                    include_hidden: row.get::<_, Option<i32>>(6)? == Some(1),
+                })
+            })?
+            .filter_map(|r| r.ok())
+            .collect();
     
-    Ok(dirs)
+        Ok(dirs)
+    })
 }
 
 #[tauri::command]
-async fn set_active_directory(state: State<'_, AppState>, path: String) -> Result<(), String> {
-    let db = state.db.lock().map_err(|e| e.to_string())?;
+#[specta::specta]
+async fn set_active_directory(state: State<'_, AppState>, path: String) -> Result<(), AppError> {
+    db::write(&state.db, |db| {
     
-    // Set all to inactive
-    db.execute("UPDATE indexed_directories SET is_active = 0", [])
-        .map_err(|e| e.to_string())?;
+        // Set all to inactive
+        db.execute("UPDATE indexed_directories SET is_active = 0", [])?;
     
-    // Set the selected one to active
-    db.execute("UPDATE indexed_directories SET is_active = 1 WHERE path = ?1", [&path])
-        .map_err(|e| e.to_string())?;
-    
-    Ok(())
+        // Set the selected one to active
+        db.execute("UPDATE indexed_directories SET is_active = 1 WHERE path = ?1", [&path])?;
+
+        Ok(())
+    })
 }
 
-#[derive(Serialize)]
+#[derive(Serialize, Type)]
 struct IndexStatus {
     total_files: i64,
     last_indexed: Option<i64>,
 }
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
+// Every command exposed to the frontend; the TypeScript bindings are generated from this list
+fn specta_builder() -> tauri_specta::Builder<tauri::Wry> {
+    tauri_specta::Builder::<tauri::Wry>::new().commands(tauri_specta::collect_commands![
+        start_indexing,
+        index_custom_folder,
+        search_files,
+        compat::search_files_v1,
+        get_recent_files,
+        get_recent_files_for_root,
+        get_recently_modified,
+        recents::clear_recent_files,
+        recents::get_recents_config,
+        recents::set_recents_config,
+        open_file,
+        open_file_with,
+        get_file_info,
+        open_with::get_open_with_prefs,
+        open_with::clear_open_with_prefs,
+        drag_out::start_drag,
+        overlay::hide_overlay,
+        overlay::show_in_main_window,
+        overlay::get_overlay_config,
+        overlay::set_overlay_config,
+        icons::get_file_icon,
+        thumbnails::get_thumbnail,
+        thumbnails::prefetch_thumbnails,
+        get_index_status,
+        debug_search_scores,
+        compat::debug_search_scores_v1,
+        compat::get_api_version,
+        toggle_favorite,
+        get_favorites,
+        favorites::get_favorite_groups,
+        favorites::create_favorite_group,
+        favorites::rename_favorite_group,
+        favorites::delete_favorite_group,
+        favorites::move_favorite,
+        favorites::reorder_favorite_groups,
+        get_indexed_directories,
+        set_active_directory,
+        settings::get_settings,
+        settings::update_settings,
+        ranking::get_ranking_config,
+        ranking::set_ranking_config,
+        planner::get_search_budget,
+        planner::set_search_budget,
+        metrics::get_performance_stats,
+        logging::set_log_level,
+        logging::get_recent_logs,
+        llm::get_llm_config,
+        llm::set_llm_config,
+        llm::test_llm_connection,
+        llm::get_llm_status,
+        projects::get_recent_projects,
+        projects::get_project_context,
+        exclusions::get_exclusion_rules,
+        exclusions::set_exclusion_rules,
+        exclusions::reset_exclusion_rules,
+        archives::get_archive_indexing,
+        archives::set_archive_indexing,
+        archives::extract_archive_entry,
+        refine::refine_search,
+        copies::find_copies,
+        experiments::start_ranking_experiment,
+        experiments::stop_ranking_experiment,
+        experiments::get_ranking_experiment_report,
+        mirrors::set_mirror_root,
+        file_types::get_extension_groups,
+        file_types::set_extension_groups,
+        saved_searches::create_saved_search,
+        saved_searches::list_saved_searches,
+        saved_searches::run_saved_search,
+        saved_searches::delete_saved_search,
+        smart_collections::create_smart_collection,
+        smart_collections::list_smart_collections,
+        smart_collections::evaluate_smart_collection,
+        smart_collections::delete_smart_collection,
+        tags::import_structure_as_tags,
+        tags::list_tags,
+        tags::add_tag,
+        tags::remove_tag,
+        tags::get_file_tags,
+        tags::get_files_by_tag,
+        notes::get_note,
+        notes::set_note,
+        notes::get_notes_config,
+        notes::set_notes_config,
+        pins::pin_result,
+        pins::unpin_result,
+        pins::list_pinned_results,
+        properties::get_file_properties,
+        preview::preview_text,
+        preview::preview_document,
+        file_ops::create_entry,
+        file_ops::rename_file,
+        file_ops::move_files,
+        file_ops::copy_files,
+        trash::delete_to_trash,
+        trash::list_trash,
+        trash::restore_from_trash,
+        snapshot::get_snapshot_config,
+        snapshot::set_snapshot_config,
+        snapshot::list_snapshots,
+        snapshot::create_snapshot,
+        snapshot::restore_snapshot,
+        semantic::build_semantic_index,
+        semantic::semantic_search,
+        summarize::summarize_file,
+        ask::ask_my_files,
+        explain::explain_search,
+        local_llm::test_local_llm,
+        categorizer::categorize_path,
+        categorizer::get_category,
+        trigram::rebuild_fts,
+    ])
+}
+
 pub fn run() {
+    logging::init(&logging::log_dir(&default_db_path()));
     let state = AppState::new().expect("Failed to initialize app state");
 
+    // Editors and other tools drive the finder over stdin/stdout, without a window
+    if std::env::args().any(|arg| arg == headless::FLAG) {
+        headless::serve_stdio(state);
+        return;
+    }
+
+    let builder = specta_builder();
+
+    // Regenerate the frontend bindings on every debug run so drift shows up as a type error
+    #[cfg(debug_assertions)]
+    builder
+        .export(
+            // Rust integers are well within JS number range for timestamps, counts and scores
+            specta_typescript::Typescript::default().bigint(specta_typescript::BigIntExportBehavior::Number),
+            "../src/bindings.ts",
+        )
+        .expect("Failed to export TypeScript bindings");
+
     tauri::Builder::default()
         .plugin(tauri_plugin_opener::init())
         .plugin(tauri_plugin_dialog::init())
+        .plugin(
+            tauri_plugin_global_shortcut::Builder::new()
+                .with_handler(|app, _, event| {
+                    if event.state() == tauri_plugin_global_shortcut::ShortcutState::Pressed {
+                        overlay::toggle(app);
+                    }
+                })
+                .build(),
+        )
         .manage(state)
-        .invoke_handler(tauri::generate_handler![
-            start_indexing,
-            index_custom_folder,
-            search_files,
-            get_recent_files,
-            open_file,
-            open_file_with,
-            get_file_info,
-            get_index_status,
-            debug_search_scores,
-            toggle_favorite,
-            get_favorites,
-            get_indexed_directories,
-            set_active_directory
-        ])
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        .invoke_handler(builder.invoke_handler())
+        .setup(|app| {
+            tray::create(app.handle())?;
+            if let Err(e) = overlay::register_shortcut(app.handle()) {
+                warn!("Overlay shortcut unavailable: {}", e);
+            }
+            config_file::watch(app.handle().clone());
+            Ok(())
+        })
+        // Closing the window keeps the app in the tray; Quit in the tray menu exits
+        .on_window_event(|window, event| {
+            if let tauri::WindowEvent::CloseRequested { api, .. } = event {
+                api.prevent_close();
+                if let Err(e) = window.hide() {
+                    warn!("Failed to hide window: {}", e);
+                }
+            }
+        })
+        .build(tauri::generate_context!())
+        .expect("error while building tauri application")
+        .run(|_, event| {
+            if let tauri::RunEvent::Exit = event {
+                local_llm::shutdown();
+            }
+        });
 }
@@ -0,0 +1,575 @@
+// LLM backend for natural-language features: Ollama's native API or any OpenAI-compatible
+// chat completions server (LM Studio, vLLM, llamafile, hosted APIs). Provider, endpoint, model
+// and timeouts are settings-backed, and every request carries its own deadline so a slow or
+// missing server costs a search a bounded amount of time.
+
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use specta::Type;
+use std::collections::VecDeque;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tauri::State;
+
+use crate::error::{AppError, ErrorCode};
+use crate::local_llm::{self, LocalLlmConfig};
+use crate::{db, settings, AppState};
+
+pub const SETTINGS_KEY: &str = "llm";
+
+// Natural-language rewrites averaged into the parse latency
+const PARSE_SAMPLES: usize = 50;
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default, Type)]
+#[serde(rename_all = "snake_case")]
+pub enum LlmProvider {
+    // `/api/generate` and `/api/tags`
+    #[default]
+    Ollama,
+    // `/v1/chat/completions` and `/v1/models`
+    OpenaiCompatible,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Type)]
+#[serde(default)]
+pub struct LlmConfig {
+    // Off until the user points it at a running server
+    pub enabled: bool,
+    pub provider: LlmProvider,
+    // Base URL of the server; for OpenAI-compatible servers a trailing `/v1` is optional
+    pub endpoint: String,
+    pub model: String,
+    // Model used for semantic search embeddings
+    pub embedding_model: String,
+    // Sent as a bearer token; local servers usually don't need one
+    pub api_key: Option<String>,
+    pub connect_timeout_ms: u64,
+    // Deadline for calls made while a search is running
+    pub search_timeout_ms: u64,
+    // Deadline for calls the user waits on explicitly, e.g. a summary
+    pub request_timeout_ms: u64,
+    // How sentence-like a query must be (0 to 1) to be rewritten by the LLM without an `nl:`
+    // prefix; 1 turns the detection off
+    pub nl_confidence_threshold: f32,
+    // llama.cpp on this machine, used for completions when the server above can't be reached
+    pub local: LocalLlmConfig,
+}
+
+impl Default for LlmConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            provider: LlmProvider::Ollama,
+            endpoint: "http://127.0.0.1:11434".to_string(),
+            model: "llama3.1:8b".to_string(),
+            embedding_model: "nomic-embed-text".to_string(),
+            api_key: None,
+            connect_timeout_ms: 500,
+            search_timeout_ms: 800,
+            request_timeout_ms: 30_000,
+            nl_confidence_threshold: 0.75,
+            local: LocalLlmConfig::default(),
+        }
+    }
+}
+
+impl LlmConfig {
+    pub fn sanitized(mut self) -> Self {
+        let defaults = Self::default();
+        self.endpoint = self.endpoint.trim().trim_end_matches('/').to_string();
+        if self.endpoint.is_empty() {
+            self.endpoint = defaults.endpoint;
+        }
+        self.model = self.model.trim().to_string();
+        if self.model.is_empty() {
+            self.model = defaults.model;
+        }
+        self.embedding_model = self.embedding_model.trim().to_string();
+        if self.embedding_model.is_empty() {
+            self.embedding_model = defaults.embedding_model;
+        }
+        self.api_key = self.api_key.map(|key| key.trim().to_string()).filter(|key| !key.is_empty());
+        self.connect_timeout_ms = self.connect_timeout_ms.clamp(50, 10_000);
+        self.search_timeout_ms = self.search_timeout_ms.clamp(100, 10_000);
+        self.request_timeout_ms = self.request_timeout_ms.clamp(1_000, 600_000);
+        if !self.nl_confidence_threshold.is_finite() {
+            self.nl_confidence_threshold = defaults.nl_confidence_threshold;
+        }
+        self.nl_confidence_threshold = self.nl_confidence_threshold.clamp(0.0, 1.0);
+        self.local = self.local.sanitized();
+        self
+    }
+
+    pub fn search_timeout(&self) -> Duration {
+        Duration::from_millis(self.search_timeout_ms)
+    }
+
+    pub fn request_timeout(&self) -> Duration {
+        Duration::from_millis(self.request_timeout_ms)
+    }
+
+    fn client(&self, timeout: Duration) -> Result<reqwest::Client, AppError> {
+        reqwest::Client::builder()
+            .connect_timeout(Duration::from_millis(self.connect_timeout_ms))
+            .timeout(timeout)
+            .build()
+            .map_err(|e| e.to_string().into())
+    }
+
+    // URL of an OpenAI API path such as "/chat/completions", whether or not the endpoint ends in /v1
+    fn openai_url(&self, path: &str) -> String {
+        if self.endpoint.ends_with("/v1") {
+            format!("{}{}", self.endpoint, path)
+        } else {
+            format!("{}/v1{}", self.endpoint, path)
+        }
+    }
+
+    fn authorize(&self, request: reqwest::RequestBuilder) -> reqwest::RequestBuilder {
+        match &self.api_key {
+            Some(key) => request.bearer_auth(key),
+            None => request,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Clone, Default, Type)]
+pub struct LlmConnectionStatus {
+    pub reachable: bool,
+    // Whether the configured model is installed on the server
+    pub model_available: bool,
+    pub models: Vec<String>,
+    // Time for a one-word completion, if the model answered
+    pub reply_ms: Option<u64>,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Serialize, Clone, Default, Type)]
+pub struct LlmStatus {
+    pub enabled: bool,
+    // The configured server answered just now
+    pub reachable: bool,
+    // The model completions go to: the configured one, or the local GGUF file when the server is down
+    pub model: Option<String>,
+    pub model_available: bool,
+    // The last completion came from the local model
+    pub using_local: bool,
+    // Average time the LLM took to rewrite recent natural-language queries
+    pub avg_parse_ms: Option<u64>,
+    pub parse_samples: u32,
+    // Natural-language queries the fallback parser handled since the app started because the LLM failed
+    pub fallback_parses: u32,
+    pub last_error: Option<String>,
+    pub last_error_at: Option<i64>,
+    // Natural-language queries can reach a model; when false they get the fallback parser
+    pub smart_search: bool,
+}
+
+// What recent LLM calls saw, shared by every caller in the process
+#[derive(Default)]
+struct Health {
+    parse_ms: VecDeque<u64>,
+    fallback_parses: u32,
+    last_error: Option<String>,
+    last_error_at: Option<i64>,
+    served_locally: bool,
+}
+
+static HEALTH: Mutex<Option<Health>> = Mutex::new(None);
+
+fn update_health(update: impl FnOnce(&mut Health)) {
+    if let Ok(mut health) = HEALTH.lock() {
+        update(health.get_or_insert_with(Health::default));
+    }
+}
+
+fn record_completion(result: &Result<String, AppError>, local: bool) {
+    update_health(|health| match result {
+        Ok(_) => health.served_locally = local,
+        Err(e) => {
+            health.last_error = Some(e.message.clone());
+            health.last_error_at = Some(chrono::Utc::now().timestamp());
+        }
+    });
+}
+
+// A natural-language rewrite: how long the LLM took, or None when the fallback parser stood in
+pub fn record_parse(elapsed: Option<Duration>) {
+    update_health(|health| match elapsed {
+        Some(elapsed) => {
+            if health.parse_ms.len() == PARSE_SAMPLES {
+                health.parse_ms.pop_front();
+            }
+            health.parse_ms.push_back(elapsed.as_millis() as u64);
+        }
+        None => health.fallback_parses += 1,
+    });
+}
+
+#[derive(Deserialize)]
+struct TagsResponse {
+    models: Vec<ModelEntry>,
+}
+
+#[derive(Deserialize)]
+struct ModelEntry {
+    name: String,
+}
+
+#[derive(Deserialize)]
+struct GenerateResponse {
+    response: String,
+}
+
+#[derive(Deserialize)]
+struct EmbedResponse {
+    embeddings: Vec<Vec<f32>>,
+}
+
+#[derive(Deserialize)]
+struct OpenAiEmbeddings {
+    data: Vec<OpenAiEmbedding>,
+}
+
+#[derive(Deserialize)]
+struct OpenAiEmbedding {
+    index: usize,
+    embedding: Vec<f32>,
+}
+
+#[derive(Deserialize)]
+struct OpenAiModels {
+    data: Vec<OpenAiModel>,
+}
+
+#[derive(Deserialize)]
+struct OpenAiModel {
+    id: String,
+}
+
+#[derive(Deserialize)]
+struct ChatResponse {
+    choices: Vec<ChatChoice>,
+}
+
+#[derive(Deserialize)]
+struct ChatChoice {
+    message: ChatMessage,
+}
+
+#[derive(Deserialize)]
+struct ChatMessage {
+    content: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct ChatStreamChunk {
+    choices: Vec<ChatStreamChoice>,
+}
+
+#[derive(Deserialize)]
+struct ChatStreamChoice {
+    delta: ChatMessage,
+}
+
+fn unreachable_error(endpoint: &str) -> AppError {
+    AppError::new(ErrorCode::LlmOffline, format!("Could not reach the LLM server at {}", endpoint))
+}
+
+// The server is down or didn't answer in time (LlmOffline), as opposed to rejecting the request
+fn request_error(e: reqwest::Error, endpoint: &str) -> AppError {
+    if e.is_timeout() {
+        AppError::new(ErrorCode::LlmOffline, format!("LLM request to {} timed out", endpoint))
+    } else if e.is_connect() {
+        unreachable_error(endpoint)
+    } else {
+        e.to_string().into()
+    }
+}
+
+async fn send_json<T: DeserializeOwned>(config: &LlmConfig, request: reqwest::RequestBuilder) -> Result<T, AppError> {
+    let response = config
+        .authorize(request)
+        .send()
+        .await
+        .map_err(|e| request_error(e, &config.endpoint))?
+        .error_for_status()
+        .map_err(|e| e.to_string())?;
+    response.json().await.map_err(|e| request_error(e, &config.endpoint))
+}
+
+// One non-streaming completion from the configured model, or from the local model when the
+// server is down and the local fallback is enabled
+pub async fn generate(config: &LlmConfig, prompt: &str, timeout: Duration) -> Result<String, AppError> {
+    let started = Instant::now();
+    let (result, local) = match generate_from_server(config, prompt, timeout).await {
+        Err(e) if use_local(config, &e) => {
+            let result = async {
+                let (local, remaining) = local_llm::start(config, timeout.saturating_sub(started.elapsed())).await?;
+                generate_from_server(&local, prompt, remaining).await
+            };
+            (result.await, true)
+        }
+        result => (result, false),
+    };
+    record_completion(&result, local);
+    result
+}
+
+// The configured server is down and the local model may stand in for it
+fn use_local(config: &LlmConfig, error: &AppError) -> bool {
+    config.local.enabled && error.message == unreachable_error(&config.endpoint).message
+}
+
+// One non-streaming completion from the configured server only
+pub async fn generate_from_server(config: &LlmConfig, prompt: &str, timeout: Duration) -> Result<String, AppError> {
+    let client = config.client(timeout)?;
+    let text = match config.provider {
+        LlmProvider::Ollama => {
+            let request = client
+                .post(format!("{}/api/generate", config.endpoint))
+                .json(&json!({ "model": config.model, "prompt": prompt, "stream": false }));
+            send_json::<GenerateResponse>(config, request).await?.response
+        }
+        LlmProvider::OpenaiCompatible => {
+            let request = client.post(config.openai_url("/chat/completions")).json(&json!({
+                "model": config.model,
+                "messages": [{ "role": "user", "content": prompt }],
+                "stream": false,
+            }));
+            let body: ChatResponse = send_json(config, request).await?;
+            body.choices
+                .into_iter()
+                .next()
+                .and_then(|choice| choice.message.content)
+                .ok_or("The LLM server returned no completion")?
+        }
+    };
+    Ok(text.trim().to_string())
+}
+
+// Like generate, but `on_text` gets each piece of the reply as the model produces it
+pub async fn generate_stream<F>(config: &LlmConfig, prompt: &str, timeout: Duration, mut on_text: F) -> Result<String, AppError>
+where
+    F: FnMut(&str) + Send,
+{
+    let started = Instant::now();
+    let (result, local) = match stream_from_server(config, prompt, timeout, &mut on_text).await {
+        Err(e) if use_local(config, &e) => {
+            let result = async {
+                let (local, remaining) = local_llm::start(config, timeout.saturating_sub(started.elapsed())).await?;
+                stream_from_server(&local, prompt, remaining, &mut on_text).await
+            };
+            (result.await, true)
+        }
+        result => (result, false),
+    };
+    record_completion(&result, local);
+    result
+}
+
+async fn stream_from_server<F>(config: &LlmConfig, prompt: &str, timeout: Duration, on_text: &mut F) -> Result<String, AppError>
+where
+    F: FnMut(&str) + Send,
+{
+    let client = config.client(timeout)?;
+    let request = match config.provider {
+        LlmProvider::Ollama => client
+            .post(format!("{}/api/generate", config.endpoint))
+            .json(&json!({ "model": config.model, "prompt": prompt, "stream": true })),
+        LlmProvider::OpenaiCompatible => client.post(config.openai_url("/chat/completions")).json(&json!({
+            "model": config.model,
+            "messages": [{ "role": "user", "content": prompt }],
+            "stream": true,
+        })),
+    };
+    let mut response = config
+        .authorize(request)
+        .send()
+        .await
+        .map_err(|e| request_error(e, &config.endpoint))?
+        .error_for_status()
+        .map_err(|e| e.to_string())?;
+
+    // Pieces arrive one per line, but network chunks can end mid-line
+    let mut text = String::new();
+    let mut pending: Vec<u8> = Vec::new();
+    let mut take_line = |line: &[u8], text: &mut String| {
+        if let Some(piece) = stream_piece(config.provider, String::from_utf8_lossy(line).trim()) {
+            on_text(&piece);
+            text.push_str(&piece);
+        }
+    };
+    while let Some(chunk) = response.chunk().await.map_err(|e| request_error(e, &config.endpoint))? {
+        pending.extend_from_slice(&chunk);
+        while let Some(newline) = pending.iter().position(|&byte| byte == b'\n') {
+            let line: Vec<u8> = pending.drain(..=newline).collect();
+            take_line(&line, &mut text);
+        }
+    }
+    take_line(&pending, &mut text);
+    Ok(text.trim().to_string())
+}
+
+// Text in one line of a streamed reply: JSON lines from Ollama, server-sent events from
+// OpenAI-compatible servers
+fn stream_piece(provider: LlmProvider, line: &str) -> Option<String> {
+    let piece = match provider {
+        LlmProvider::Ollama => serde_json::from_str::<GenerateResponse>(line).ok()?.response,
+        LlmProvider::OpenaiCompatible => {
+            let data = line.strip_prefix("data:")?.trim();
+            if data == "[DONE]" {
+                return None;
+            }
+            let chunk: ChatStreamChunk = serde_json::from_str(data).ok()?;
+            chunk.choices.into_iter().next()?.delta.content?
+        }
+    };
+    Some(piece).filter(|piece| !piece.is_empty())
+}
+
+// Embedding vectors for a batch of texts, in input order, from the configured embedding model
+pub async fn embed(config: &LlmConfig, inputs: &[String], timeout: Duration) -> Result<Vec<Vec<f32>>, AppError> {
+    let client = config.client(timeout)?;
+    let body = json!({ "model": config.embedding_model, "input": inputs });
+    let vectors = match config.provider {
+        LlmProvider::Ollama => {
+            let request = client.post(format!("{}/api/embed", config.endpoint)).json(&body);
+            send_json::<EmbedResponse>(config, request).await?.embeddings
+        }
+        LlmProvider::OpenaiCompatible => {
+            let request = client.post(config.openai_url("/embeddings")).json(&body);
+            let mut data = send_json::<OpenAiEmbeddings>(config, request).await?.data;
+            data.sort_by_key(|item| item.index);
+            data.into_iter().map(|item| item.embedding).collect()
+        }
+    };
+    if vectors.len() != inputs.len() {
+        return Err(format!("Expected {} embeddings, the server returned {}", inputs.len(), vectors.len()).into());
+    }
+    Ok(vectors)
+}
+
+async fn list_models(config: &LlmConfig, timeout: Duration) -> Result<Vec<String>, AppError> {
+    let client = config.client(timeout)?;
+    match config.provider {
+        LlmProvider::Ollama => {
+            let request = client.get(format!("{}/api/tags", config.endpoint));
+            let body: TagsResponse = send_json(config, request).await?;
+            Ok(body.models.into_iter().map(|model| model.name).collect())
+        }
+        LlmProvider::OpenaiCompatible => {
+            let body: OpenAiModels = send_json(config, client.get(config.openai_url("/models"))).await?;
+            Ok(body.data.into_iter().map(|model| model.id).collect())
+        }
+    }
+}
+
+// Ollama lists untagged models as "name:latest"
+fn has_model(models: &[String], model: &str) -> bool {
+    models.iter().any(|name| name == model || *name == format!("{}:latest", model))
+}
+
+pub fn load(conn: &rusqlite::Connection) -> LlmConfig {
+    settings::load::<LlmConfig>(conn, SETTINGS_KEY).sanitized()
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn get_llm_config(state: State<'_, AppState>) -> Result<LlmConfig, AppError> {
+    let config = state.llm.read().map_err(|e| e.to_string())?;
+    Ok(config.clone())
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn set_llm_config(config: LlmConfig, state: State<'_, AppState>) -> Result<LlmConfig, AppError> {
+    let config = config.sanitized();
+    db::write(&state.db, |db| settings::save(db, SETTINGS_KEY, &config))?;
+    *state.llm.write().map_err(|e| e.to_string())? = config.clone();
+    Ok(config)
+}
+
+// Check a config (the saved one if none is given) without saving it: is the server up, is the
+// model installed, and how long does it take to answer
+#[tauri::command]
+#[specta::specta]
+pub async fn test_llm_connection(config: Option<LlmConfig>, state: State<'_, AppState>) -> Result<LlmConnectionStatus, AppError> {
+    let config = match config {
+        Some(config) => config.sanitized(),
+        None => state.llm.read().map_err(|e| e.to_string())?.clone(),
+    };
+
+    let mut status = LlmConnectionStatus::default();
+    match list_models(&config, config.request_timeout()).await {
+        Ok(models) => {
+            status.reachable = true;
+            status.model_available = has_model(&models, &config.model);
+            status.models = models;
+        }
+        Err(e) => {
+            status.error = Some(e.message);
+            return Ok(status);
+        }
+    }
+    if !status.model_available {
+        status.error = Some(format!("Model '{}' is not installed on the server", config.model));
+        return Ok(status);
+    }
+
+    let started = Instant::now();
+    match generate(&config, "Reply with the single word OK.", config.request_timeout()).await {
+        Ok(_) => status.reply_ms = Some(started.elapsed().as_millis() as u64),
+        Err(e) => status.error = Some(e.message),
+    }
+    Ok(status)
+}
+
+// Whether natural-language search currently reaches a model, and how recent calls went. Checks
+// the server with the search timeout, so it answers quickly even when the server is down.
+#[tauri::command]
+#[specta::specta]
+pub async fn get_llm_status(state: State<'_, AppState>) -> Result<LlmStatus, AppError> {
+    let config = state.llm.read().map_err(|e| e.to_string())?.clone();
+    let mut status = LlmStatus {
+        enabled: config.enabled,
+        ..LlmStatus::default()
+    };
+    if let Ok(health) = HEALTH.lock() {
+        if let Some(health) = health.as_ref() {
+            if !health.parse_ms.is_empty() {
+                status.avg_parse_ms = Some(health.parse_ms.iter().sum::<u64>() / health.parse_ms.len() as u64);
+            }
+            status.parse_samples = health.parse_ms.len() as u32;
+            status.fallback_parses = health.fallback_parses;
+            status.last_error = health.last_error.clone();
+            status.last_error_at = health.last_error_at;
+            status.using_local = health.served_locally;
+        }
+    }
+    if !config.enabled {
+        return Ok(status);
+    }
+
+    match list_models(&config, config.search_timeout()).await {
+        Ok(models) => {
+            status.reachable = true;
+            status.model_available = has_model(&models, &config.model);
+            status.model = Some(config.model.clone());
+            status.using_local = false;
+        }
+        Err(e) if config.local.enabled => {
+            // Completions go to the local model instead, if it has one to load
+            status.model = local_llm::model_name(&config.local);
+            status.model_available = status.model.is_some();
+            status.using_local = status.model_available;
+            if status.last_error.is_none() {
+                status.last_error = Some(e);
+            }
+        }
+        Err(e) => status.last_error = Some(e),
+    }
+    status.smart_search = status.model_available;
+    Ok(status)
+}
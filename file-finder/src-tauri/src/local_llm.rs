@@ -0,0 +1,272 @@
+// Local inference fallback: when the configured LLM server can't be reached, completions go to
+// llama.cpp's `llama-server` running a GGUF model on this machine. The app starts the server on
+// first use, keeps it running for later calls and stops it on exit, so natural-language search
+// and summaries work without Ollama. Embeddings still need the configured server.
+
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, Stdio};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tauri::State;
+use tracing::info;
+
+use crate::error::AppError;
+use crate::llm::{self, LlmConfig, LlmConnectionStatus, LlmProvider};
+use crate::{default_db_path, AppState};
+
+const HEALTH_POLL: Duration = Duration::from_millis(250);
+
+#[cfg(target_os = "windows")]
+const SERVER_BINARY: &str = "llama-server.exe";
+#[cfg(not(target_os = "windows"))]
+const SERVER_BINARY: &str = "llama-server";
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Type)]
+#[serde(default)]
+pub struct LocalLlmConfig {
+    pub enabled: bool,
+    // llama.cpp's server binary; when unset, `bin/llama-server` in the app's data folder, then PATH
+    pub server_path: Option<String>,
+    // GGUF model file; when unset, the first .gguf file in the app's `models` folder
+    pub model_path: Option<String>,
+    pub port: u16,
+    pub context_size: u32,
+    // CPU threads; llama.cpp picks when unset
+    pub threads: Option<u32>,
+    // How long loading the model may take before calls give up waiting for it
+    pub startup_timeout_ms: u64,
+}
+
+impl Default for LocalLlmConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            server_path: None,
+            model_path: None,
+            port: 8089,
+            context_size: 4096,
+            threads: None,
+            startup_timeout_ms: 60_000,
+        }
+    }
+}
+
+impl LocalLlmConfig {
+    pub fn sanitized(mut self) -> Self {
+        let defaults = Self::default();
+        let trimmed = |value: Option<String>| value.map(|v| v.trim().to_string()).filter(|v| !v.is_empty());
+        self.server_path = trimmed(self.server_path);
+        self.model_path = trimmed(self.model_path);
+        if self.port < 1024 {
+            self.port = defaults.port;
+        }
+        self.context_size = self.context_size.clamp(512, 131_072);
+        self.threads = self.threads.filter(|&threads| threads > 0);
+        self.startup_timeout_ms = self.startup_timeout_ms.clamp(1_000, 600_000);
+        self
+    }
+
+    fn server(&self) -> PathBuf {
+        if let Some(path) = &self.server_path {
+            return PathBuf::from(path);
+        }
+        let bundled = default_db_path().with_file_name("bin").join(SERVER_BINARY);
+        if bundled.is_file() {
+            bundled
+        } else {
+            // Resolved through PATH when started
+            PathBuf::from(SERVER_BINARY)
+        }
+    }
+
+    fn model(&self) -> Result<PathBuf, String> {
+        if let Some(path) = &self.model_path {
+            return Ok(PathBuf::from(path));
+        }
+        let models = default_db_path().with_file_name("models");
+        let mut found: Vec<PathBuf> = std::fs::read_dir(&models)
+            .map(|entries| entries.filter_map(|entry| entry.ok()).map(|entry| entry.path()).collect())
+            .unwrap_or_default();
+        found.retain(|path| path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("gguf")));
+        found.sort();
+        found
+            .into_iter()
+            .next()
+            .ok_or_else(|| format!("No local model: set model_path or put a .gguf file in {}", models.display()))
+    }
+
+    // The config llm uses to talk to the local server, which speaks the OpenAI API
+    fn client_config(&self, remote: &LlmConfig) -> LlmConfig {
+        LlmConfig {
+            enabled: true,
+            provider: LlmProvider::OpenaiCompatible,
+            endpoint: format!("http://127.0.0.1:{}", self.port),
+            api_key: None,
+            local: LocalLlmConfig::default(),
+            ..remote.clone()
+        }
+    }
+}
+
+// File name of the model the local server runs, if there is one to load
+pub fn model_name(config: &LocalLlmConfig) -> Option<String> {
+    let model = config.model().ok().filter(|model| model.is_file())?;
+    Some(model.file_name()?.to_string_lossy().to_string())
+}
+
+struct LocalServer {
+    child: Child,
+    // Command line it was started with; a settings change restarts it
+    command: Vec<String>,
+}
+
+// One server for the whole process
+static SERVER: Mutex<Option<LocalServer>> = Mutex::new(None);
+
+fn command_line(config: &LocalLlmConfig, model: &Path) -> Vec<String> {
+    let mut args = vec![
+        config.server().to_string_lossy().to_string(),
+        "-m".to_string(),
+        model.to_string_lossy().to_string(),
+        "--host".to_string(),
+        "127.0.0.1".to_string(),
+        "--port".to_string(),
+        config.port.to_string(),
+        "-c".to_string(),
+        config.context_size.to_string(),
+    ];
+    if let Some(threads) = config.threads {
+        args.extend(["-t".to_string(), threads.to_string()]);
+    }
+    args
+}
+
+// Start the server unless it is already running with these settings
+fn ensure_started(config: &LocalLlmConfig) -> Result<(), String> {
+    let model = config.model()?;
+    if !model.is_file() {
+        return Err(format!("Local model not found: {}", model.display()));
+    }
+    let command = command_line(config, &model);
+
+    let mut server = SERVER.lock().map_err(|e| e.to_string())?;
+    if let Some(running) = server.as_mut() {
+        let alive = matches!(running.child.try_wait(), Ok(None));
+        if alive && running.command == command {
+            return Ok(());
+        }
+        let _ = running.child.kill();
+        let _ = running.child.wait();
+    }
+
+    let mut process = Command::new(&command[0]);
+    process.args(&command[1..]).stdin(Stdio::null()).stdout(Stdio::null()).stderr(Stdio::null());
+    #[cfg(target_os = "windows")]
+    {
+        use std::os::windows::process::CommandExt;
+        // CREATE_NO_WINDOW: no console window next to the app
+        process.creation_flags(0x0800_0000);
+    }
+    let child = process
+        .spawn()
+        .map_err(|e| format!("Could not start {}: {}", command[0], e))?;
+    info!("Started local LLM server on port {} with {}", config.port, model.display());
+    *server = Some(LocalServer { child, command });
+    Ok(())
+}
+
+// Wait until the server has loaded its model, at most until `deadline`
+async fn wait_until_ready(config: &LocalLlmConfig, deadline: Instant) -> Result<(), String> {
+    let client = reqwest::Client::builder()
+        .timeout(HEALTH_POLL)
+        .build()
+        .map_err(|e| e.to_string())?;
+    let url = format!("http://127.0.0.1:{}/health", config.port);
+    loop {
+        // 503 while the model is loading, 200 once it is ready
+        if let Ok(response) = client.get(&url).send().await {
+            if response.status().is_success() {
+                return Ok(());
+            }
+        }
+        {
+            let mut server = SERVER.lock().map_err(|e| e.to_string())?;
+            if let Some(running) = server.as_mut() {
+                if let Ok(Some(status)) = running.child.try_wait() {
+                    *server = None;
+                    return Err(format!("The local LLM server exited while starting ({})", status));
+                }
+            }
+        }
+        if Instant::now() + HEALTH_POLL > deadline {
+            return Err("The local LLM server is still loading its model".to_string());
+        }
+        tokio::time::sleep(HEALTH_POLL).await;
+    }
+}
+
+// Start the local model if needed and wait for it, at most `timeout`. Returns the config that
+// reaches it and how much of the timeout is left for the request.
+pub async fn start(remote: &LlmConfig, timeout: Duration) -> Result<(LlmConfig, Duration), String> {
+    let config = &remote.local;
+    let started = Instant::now();
+    ensure_started(config)?;
+    wait_until_ready(config, started + timeout).await?;
+    let remaining = timeout.saturating_sub(started.elapsed()).max(HEALTH_POLL);
+    Ok((config.client_config(remote), remaining))
+}
+
+// Stop the server when the app exits; llama-server doesn't notice its parent going away
+pub fn shutdown() {
+    if let Ok(mut server) = SERVER.lock() {
+        if let Some(mut running) = server.take() {
+            let _ = running.child.kill();
+            let _ = running.child.wait();
+        }
+    }
+}
+
+// Start the local model (from the saved config if none is given) and time a short reply
+#[tauri::command]
+#[specta::specta]
+pub async fn test_local_llm(config: Option<LlmConfig>, state: State<'_, AppState>) -> Result<LlmConnectionStatus, AppError> {
+    let config = match config {
+        Some(config) => config.sanitized(),
+        None => state.llm.read().map_err(|e| e.to_string())?.clone(),
+    };
+    let local = &config.local;
+
+    let mut status = LlmConnectionStatus::default();
+    match local.model() {
+        Ok(model) if model.is_file() => {
+            status.model_available = true;
+            status.models = vec![model.file_name().unwrap_or_default().to_string_lossy().to_string()];
+        }
+        Ok(model) => {
+            status.error = Some(format!("Local model not found: {}", model.display()));
+            return Ok(status);
+        }
+        Err(e) => {
+            status.error = Some(e);
+            return Ok(status);
+        }
+    }
+
+    let client_config = match start(&config, Duration::from_millis(local.startup_timeout_ms)).await {
+        Ok((client_config, _)) => client_config,
+        Err(e) => {
+            status.error = Some(e);
+            return Ok(status);
+        }
+    };
+    status.reachable = true;
+
+    let started = Instant::now();
+    match llm::generate_from_server(&client_config, "Reply with the single word OK.", config.request_timeout()).await {
+        Ok(_) => status.reply_ms = Some(started.elapsed().as_millis() as u64),
+        Err(e) => status.error = Some(e.message),
+    }
+    Ok(status)
+}
@@ -0,0 +1,122 @@
+// Case folding for matching and the order of names in sorted lists. SQLite's LOWER() and LIKE
+// only know ASCII, so "ÄPFEL.txt" never matched "äpfel" in the prefilter. Searches now fold
+// names with `fold`, in Rust and as the `fold()` SQL function, and lists sorted by name use
+// the `LOCALE` collation. Both follow the `locale` settings:
+// - `case_rules`: "turkish" folds I to ı (dotless), for Turkish file names; İ folds to i either way
+// - `collation`: "alphabetical" sorts accented letters next to their base letter and ignores case;
+//   "code_point" sorts by the folded names as they are, the old NOCASE order
+
+use rusqlite::functions::FunctionFlags;
+use rusqlite::{Connection, Result as SqlResult};
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use std::cmp::Ordering;
+use std::sync::atomic::{AtomicBool, Ordering as AtomicOrdering};
+use unicode_normalization::char::is_combining_mark;
+use unicode_normalization::UnicodeNormalization;
+
+use crate::settings;
+
+pub const SETTINGS_KEY: &str = "locale";
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default, PartialEq, Eq, Type)]
+#[serde(rename_all = "snake_case")]
+pub enum CaseRules {
+    #[default]
+    Default,
+    Turkish,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default, PartialEq, Eq, Type)]
+#[serde(rename_all = "snake_case")]
+pub enum Collation {
+    #[default]
+    Alphabetical,
+    CodePoint,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq, Type)]
+#[serde(default)]
+pub struct LocaleConfig {
+    pub case_rules: CaseRules,
+    pub collation: Collation,
+}
+
+// Read by `fold` and `compare`, which run inside SQLite and in scoring without the app state
+static TURKISH_CASE: AtomicBool = AtomicBool::new(false);
+static CODE_POINT_ORDER: AtomicBool = AtomicBool::new(false);
+
+pub fn load(conn: &Connection) -> LocaleConfig {
+    settings::load::<LocaleConfig>(conn, SETTINGS_KEY)
+}
+
+pub fn apply(config: &LocaleConfig) {
+    TURKISH_CASE.store(config.case_rules == CaseRules::Turkish, AtomicOrdering::Relaxed);
+    CODE_POINT_ORDER.store(config.collation == Collation::CodePoint, AtomicOrdering::Relaxed);
+}
+
+// Names folded under other rules don't match, so folded copies record which applied
+pub fn turkish_case() -> bool {
+    TURKISH_CASE.load(AtomicOrdering::Relaxed)
+}
+
+// Lowercase for matching. Names are composed first, so the decomposed é macOS stores matches
+// the é that was typed. ß folds to ss and final ς to σ, as full case folding does.
+pub fn fold(s: &str) -> String {
+    let turkish = TURKISH_CASE.load(AtomicOrdering::Relaxed);
+    if s.is_ascii() && !turkish {
+        return s.to_ascii_lowercase();
+    }
+    let mut folded = String::with_capacity(s.len());
+    for c in s.nfc() {
+        match c {
+            // Plain lowercase turns İ into i followed by a combining dot
+            'İ' => folded.push('i'),
+            'I' if turkish => folded.push('ı'),
+            'ß' | 'ẞ' => folded.push_str("ss"),
+            'ς' => folded.push('σ'),
+            c => folded.extend(c.to_lowercase()),
+        }
+    }
+    folded
+}
+
+// Folded, with accents dropped and letters that don't decompose spelled out, so Ärger sorts
+// among the A's and Øre among the O's
+fn sort_key(s: &str) -> String {
+    let mut key = String::with_capacity(s.len());
+    for c in fold(s).nfd().filter(|c| !is_combining_mark(*c)) {
+        match c {
+            'æ' => key.push_str("ae"),
+            'œ' => key.push_str("oe"),
+            'ø' => key.push('o'),
+            'đ' | 'ð' => key.push('d'),
+            'ł' => key.push('l'),
+            'þ' => key.push_str("th"),
+            'ı' => key.push('i'),
+            c => key.push(c),
+        }
+    }
+    key
+}
+
+// Order of two names under the current collation. Names that only differ in accents or case
+// still get a fixed order, so sorting is stable across runs.
+pub fn compare(a: &str, b: &str) -> Ordering {
+    if CODE_POINT_ORDER.load(AtomicOrdering::Relaxed) {
+        return fold(a).cmp(&fold(b)).then_with(|| a.cmp(b));
+    }
+    sort_key(a)
+        .cmp(&sort_key(b))
+        .then_with(|| fold(a).cmp(&fold(b)))
+        .then_with(|| a.cmp(b))
+}
+
+// `fold(text)` and `COLLATE LOCALE` for queries on this connection
+pub fn register(conn: &Connection) -> SqlResult<()> {
+    conn.create_scalar_function("fold", 1, FunctionFlags::SQLITE_UTF8, |ctx| {
+        Ok(ctx.get::<Option<String>>(0)?.map(|text| fold(&text)))
+    })?;
+    conn.create_collation("LOCALE", compare)?;
+    Ok(())
+}
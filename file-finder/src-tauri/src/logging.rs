@@ -0,0 +1,164 @@
+// Logging through `tracing`, to stderr as before and to a daily log file in the `logs` folder
+// next to the database, keeping the last `MAX_LOG_FILES` days. The level can be changed while
+// the app runs with `set_log_level` and is remembered in the settings, and `get_recent_logs`
+// returns the last lines, so logs can be attached to a bug report without running the app
+// from a terminal.
+
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use tauri::State;
+use tracing_appender::non_blocking::WorkerGuard;
+use tracing_appender::rolling::{RollingFileAppender, Rotation};
+use tracing_subscriber::filter::LevelFilter;
+use tracing_subscriber::prelude::*;
+use tracing_subscriber::{fmt, reload, Registry};
+
+use crate::error::AppError;
+use crate::{db, settings, AppState};
+
+pub const SETTINGS_KEY: &str = "logging";
+
+const FILE_PREFIX: &str = "file-finder";
+const FILE_SUFFIX: &str = "log";
+const MAX_LOG_FILES: usize = 7;
+
+// Most lines get_recent_logs returns
+const MAX_RECENT_LINES: usize = 10_000;
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default, PartialEq, Eq, Type)]
+#[serde(rename_all = "lowercase")]
+pub enum LogLevel {
+    Error,
+    Warn,
+    #[default]
+    Info,
+    Debug,
+    Trace,
+}
+
+impl LogLevel {
+    fn filter(self) -> LevelFilter {
+        match self {
+            LogLevel::Error => LevelFilter::ERROR,
+            LogLevel::Warn => LevelFilter::WARN,
+            LogLevel::Info => LevelFilter::INFO,
+            LogLevel::Debug => LevelFilter::DEBUG,
+            LogLevel::Trace => LevelFilter::TRACE,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq, Type)]
+#[serde(default)]
+pub struct LoggingConfig {
+    pub level: LogLevel,
+}
+
+struct Logger {
+    level: reload::Handle<LevelFilter, Registry>,
+    // None when the log file could not be opened
+    dir: Option<PathBuf>,
+    // Keeps the background file writer running
+    _guard: Option<WorkerGuard>,
+}
+
+static LOGGER: OnceLock<Logger> = OnceLock::new();
+
+pub fn log_dir(db_path: &Path) -> PathBuf {
+    db_path.with_file_name("logs")
+}
+
+// Start logging at the default level; `apply` switches to the saved one once the database is open
+pub fn init(dir: &Path) {
+    if let Err(e) = fs::create_dir_all(dir) {
+        eprintln!("Could not create log folder {}: {}", dir.display(), e);
+    }
+    let appender = RollingFileAppender::builder()
+        .rotation(Rotation::DAILY)
+        .filename_prefix(FILE_PREFIX)
+        .filename_suffix(FILE_SUFFIX)
+        .max_log_files(MAX_LOG_FILES)
+        .build(dir);
+    let file_layer = match appender {
+        Ok(appender) => {
+            let (writer, guard) = tracing_appender::non_blocking(appender);
+            Some((fmt::layer().with_ansi(false).with_writer(writer), guard))
+        }
+        Err(e) => {
+            eprintln!("Logging to stderr only, the log file could not be opened: {}", e);
+            None
+        }
+    };
+    let (file_layer, guard) = match file_layer {
+        Some((layer, guard)) => (Some(layer), Some(guard)),
+        None => (None, None),
+    };
+
+    let (level_layer, level) = reload::Layer::new(LogLevel::default().filter());
+    let installed = tracing_subscriber::registry()
+        .with(level_layer)
+        .with(fmt::layer().with_writer(std::io::stderr))
+        .with(file_layer)
+        .try_init();
+    if installed.is_ok() {
+        let dir = guard.is_some().then(|| dir.to_path_buf());
+        let _ = LOGGER.set(Logger { level, dir, _guard: guard });
+    }
+}
+
+pub fn load(conn: &rusqlite::Connection) -> LoggingConfig {
+    settings::load::<LoggingConfig>(conn, SETTINGS_KEY)
+}
+
+pub fn apply(config: &LoggingConfig) {
+    if let Some(logger) = LOGGER.get() {
+        if let Err(e) = logger.level.modify(|filter| *filter = config.level.filter()) {
+            tracing::warn!("Could not change the log level: {}", e);
+        }
+    }
+}
+
+// Last `count` lines over the log files, oldest first
+fn recent_lines(dir: &Path, count: usize) -> Result<Vec<String>, String> {
+    let mut files: Vec<PathBuf> = fs::read_dir(dir)
+        .map_err(|e| e.to_string())?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.file_name().is_some_and(|name| name.to_string_lossy().starts_with(FILE_PREFIX)))
+        .collect();
+    // Dated names sort oldest to newest
+    files.sort();
+
+    let mut lines: Vec<String> = Vec::new();
+    for file in files.iter().rev() {
+        let text = fs::read_to_string(file).map_err(|e| e.to_string())?;
+        let mut earlier: Vec<String> = text.lines().map(str::to_string).collect();
+        earlier.append(&mut lines);
+        lines = earlier;
+        if lines.len() >= count {
+            break;
+        }
+    }
+    let skip = lines.len().saturating_sub(count);
+    Ok(lines.split_off(skip))
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn set_log_level(level: LogLevel, state: State<'_, AppState>) -> Result<LoggingConfig, AppError> {
+    let config = LoggingConfig { level };
+    db::write(&state.db, |db| settings::save(db, SETTINGS_KEY, &config))?;
+    apply(&config);
+    tracing::info!("Log level set to {:?}", level);
+    Ok(config)
+}
+
+// The last `n` lines written to the log files (200 by default), oldest first
+#[tauri::command]
+#[specta::specta]
+pub async fn get_recent_logs(n: Option<usize>) -> Result<Vec<String>, AppError> {
+    let dir = LOGGER.get().and_then(|logger| logger.dir.as_ref()).ok_or("Logging to a file is not available")?;
+    Ok(recent_lines(dir, n.unwrap_or(200).clamp(1, MAX_RECENT_LINES))?)
+}
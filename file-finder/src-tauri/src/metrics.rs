@@ -0,0 +1,269 @@
+// Local performance counters for searches, kept in memory since startup and never sent
+// anywhere. `get_performance_stats` shows where the time of recent searches went (reading
+// candidates from SQLite vs matching and scoring them) and how often the cache answered, so a
+// slow query can be reported with numbers instead of "it feels slow".
+
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use std::collections::VecDeque;
+use std::time::Duration;
+use tauri::State;
+
+use crate::error::AppError;
+use crate::planner::PlanStats;
+use crate::AppState;
+
+// Upper bounds of the latency histogram buckets, in milliseconds; slower searches go in the last
+const BUCKET_BOUNDS_MS: &[u64] = &[1, 5, 10, 25, 50, 100, 250, 500, 1000];
+
+// Searches kept for the recent list and the percentiles
+const RECENT_SEARCHES: usize = 50;
+
+// One search as it was answered
+#[derive(Debug, Serialize, Clone, Type)]
+pub struct SearchSample {
+    pub query: String,
+    pub at: i64,
+    pub total_ms: f64,
+    // Reading candidate rows from SQLite
+    pub sql_ms: f64,
+    // Everything else: matching, scoring, sorting and merging
+    pub scoring_ms: f64,
+    // Candidate rows read from the index
+    pub candidates: usize,
+    pub results: usize,
+    pub cache_hit: bool,
+    // Ran out of time or fell back to the cheap plan
+    pub degraded: bool,
+}
+
+impl SearchSample {
+    pub fn new(query: &str, total: Duration, plan: &PlanStats, results: usize, cache_hit: bool, degraded: bool) -> Self {
+        let total_ms = ms(total);
+        let sql_ms = ms(plan.sql_time).min(total_ms);
+        Self {
+            query: query.to_string(),
+            at: chrono::Utc::now().timestamp(),
+            total_ms,
+            sql_ms,
+            scoring_ms: total_ms - sql_ms,
+            candidates: plan.rows_scanned,
+            results,
+            cache_hit,
+            degraded,
+        }
+    }
+}
+
+// Where the time of one search went, returned with the response when it ran with `debug`
+#[derive(Debug, Serialize, Deserialize, Clone, Type)]
+pub struct SearchTimings {
+    pub total_ms: f64,
+    // Building the cache key and looking it up
+    pub cache_ms: f64,
+    pub cache_hit: bool,
+    // Reading candidate rows from SQLite, trigram (FTS) lookups included
+    pub sql_ms: f64,
+    // Looking names up in the name index
+    pub index_lookup_ms: f64,
+    // "trigram", "name_index", "scan" or "incremental"
+    pub candidate_source: Option<String>,
+    // Matching and scoring the candidates
+    pub scoring_ms: f64,
+    // The part of scoring spent in fuzzy matching
+    pub fuzzy_ms: f64,
+    pub sort_ms: f64,
+    pub rows_scanned: usize,
+    pub candidates: usize,
+    pub results: usize,
+    pub degraded: bool,
+}
+
+impl SearchTimings {
+    pub fn new(sample: &SearchSample, cache_time: Duration, plan: &PlanStats) -> Self {
+        Self {
+            total_ms: sample.total_ms,
+            cache_ms: ms(cache_time),
+            cache_hit: sample.cache_hit,
+            sql_ms: sample.sql_ms,
+            index_lookup_ms: ms(plan.index_lookup_time),
+            candidate_source: plan.candidate_source.map(str::to_string),
+            scoring_ms: ms(plan.scoring_time),
+            fuzzy_ms: ms(plan.fuzzy_time),
+            sort_ms: ms(plan.sort_time),
+            rows_scanned: plan.rows_scanned,
+            candidates: plan.candidates,
+            results: sample.results,
+            degraded: sample.degraded,
+        }
+    }
+}
+
+fn ms(duration: Duration) -> f64 {
+    duration.as_secs_f64() * 1000.0
+}
+
+#[derive(Debug, Serialize, Clone, Type)]
+pub struct HistogramBucket {
+    // Searches up to this many milliseconds, None for the ones slower than every bound
+    pub le_ms: Option<u64>,
+    pub count: u64,
+}
+
+#[derive(Debug, Clone)]
+struct Histogram {
+    counts: Vec<u64>,
+    sum_ms: f64,
+    max_ms: f64,
+}
+
+impl Default for Histogram {
+    fn default() -> Self {
+        Self { counts: vec![0; BUCKET_BOUNDS_MS.len() + 1], sum_ms: 0.0, max_ms: 0.0 }
+    }
+}
+
+impl Histogram {
+    fn record(&mut self, value_ms: f64) {
+        let bucket = BUCKET_BOUNDS_MS.iter().position(|&bound| value_ms <= bound as f64).unwrap_or(BUCKET_BOUNDS_MS.len());
+        self.counts[bucket] += 1;
+        self.sum_ms += value_ms;
+        self.max_ms = self.max_ms.max(value_ms);
+    }
+
+    fn summary(&self, recent: &[f64]) -> TimingStats {
+        let count: u64 = self.counts.iter().sum();
+        TimingStats {
+            mean_ms: if count > 0 { self.sum_ms / count as f64 } else { 0.0 },
+            p50_ms: percentile(recent, 0.5),
+            p95_ms: percentile(recent, 0.95),
+            max_ms: self.max_ms,
+            buckets: self
+                .counts
+                .iter()
+                .enumerate()
+                .map(|(i, &count)| HistogramBucket { le_ms: BUCKET_BOUNDS_MS.get(i).copied(), count })
+                .collect(),
+        }
+    }
+}
+
+// Nearest-rank percentile of the recent values
+fn percentile(values: &[f64], fraction: f64) -> f64 {
+    if values.is_empty() {
+        return 0.0;
+    }
+    let mut sorted = values.to_vec();
+    sorted.sort_by(|a, b| a.total_cmp(b));
+    let rank = ((fraction * sorted.len() as f64).ceil() as usize).clamp(1, sorted.len());
+    sorted[rank - 1]
+}
+
+#[derive(Debug, Serialize, Clone, Type)]
+pub struct TimingStats {
+    // Over every search since startup
+    pub mean_ms: f64,
+    // Over the recent searches
+    pub p50_ms: f64,
+    pub p95_ms: f64,
+    pub max_ms: f64,
+    pub buckets: Vec<HistogramBucket>,
+}
+
+#[derive(Debug, Serialize, Clone, Type)]
+pub struct PerformanceStats {
+    // When counting started, at app startup
+    pub since: i64,
+    pub searches: u64,
+    pub cache_hits: u64,
+    // Share of searches the cache answered, 0 to 1
+    pub cache_hit_rate: f64,
+    pub degraded: u64,
+    // Candidate rows read, in total and for the largest search
+    pub candidates_scanned: u64,
+    pub max_candidates: usize,
+    // Searches that ran, cache hits left out
+    pub total: TimingStats,
+    pub sql: TimingStats,
+    pub scoring: TimingStats,
+    // Newest first
+    pub recent: Vec<SearchSample>,
+}
+
+#[derive(Debug)]
+pub struct Metrics {
+    since: i64,
+    searches: u64,
+    cache_hits: u64,
+    degraded: u64,
+    candidates_scanned: u64,
+    max_candidates: usize,
+    total: Histogram,
+    sql: Histogram,
+    scoring: Histogram,
+    recent: VecDeque<SearchSample>,
+}
+
+impl Default for Metrics {
+    fn default() -> Self {
+        Self {
+            since: chrono::Utc::now().timestamp(),
+            searches: 0,
+            cache_hits: 0,
+            degraded: 0,
+            candidates_scanned: 0,
+            max_candidates: 0,
+            total: Histogram::default(),
+            sql: Histogram::default(),
+            scoring: Histogram::default(),
+            recent: VecDeque::with_capacity(RECENT_SEARCHES),
+        }
+    }
+}
+
+impl Metrics {
+    pub fn record(&mut self, sample: SearchSample) {
+        self.searches += 1;
+        if sample.cache_hit {
+            self.cache_hits += 1;
+        } else {
+            self.total.record(sample.total_ms);
+            self.sql.record(sample.sql_ms);
+            self.scoring.record(sample.scoring_ms);
+            self.candidates_scanned += sample.candidates as u64;
+            self.max_candidates = self.max_candidates.max(sample.candidates);
+        }
+        if sample.degraded {
+            self.degraded += 1;
+        }
+        if self.recent.len() == RECENT_SEARCHES {
+            self.recent.pop_back();
+        }
+        self.recent.push_front(sample);
+    }
+
+    fn stats(&self) -> PerformanceStats {
+        let ran: Vec<&SearchSample> = self.recent.iter().filter(|sample| !sample.cache_hit).collect();
+        let recent = |value: fn(&SearchSample) -> f64| ran.iter().map(|sample| value(sample)).collect::<Vec<f64>>();
+        PerformanceStats {
+            since: self.since,
+            searches: self.searches,
+            cache_hits: self.cache_hits,
+            cache_hit_rate: if self.searches > 0 { self.cache_hits as f64 / self.searches as f64 } else { 0.0 },
+            degraded: self.degraded,
+            candidates_scanned: self.candidates_scanned,
+            max_candidates: self.max_candidates,
+            total: self.total.summary(&recent(|sample| sample.total_ms)),
+            sql: self.sql.summary(&recent(|sample| sample.sql_ms)),
+            scoring: self.scoring.summary(&recent(|sample| sample.scoring_ms)),
+            recent: self.recent.iter().cloned().collect(),
+        }
+    }
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn get_performance_stats(state: State<'_, AppState>) -> Result<PerformanceStats, AppError> {
+    let metrics = state.metrics.lock().map_err(|e| e.to_string())?;
+    Ok(metrics.stats())
+}
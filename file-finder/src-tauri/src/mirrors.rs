@@ -0,0 +1,104 @@
+// Backup and archive drives indexed in "mirror" mode. Their files stay out of default
+// searches so primary results aren't crowded by backup copies, and are searched with `in:backups`.
+
+use rusqlite::{Connection, Result as SqlResult};
+use std::path::Path;
+use tauri::State;
+
+use crate::error::{AppError, ErrorCode};
+use crate::{db, incremental, AppState, FileEntry};
+
+// Files under a mirror root; the root itself may end in a separator (E:\ or /)
+const UNDER_MIRROR_ROOT: &str = "EXISTS (SELECT 1 FROM indexed_directories d WHERE d.is_mirror = 1
+    AND substr(files.path, 1, length(d.path)) = d.path
+    AND (substr(d.path, -1) IN ('/', '\\') OR substr(files.path, length(d.path) + 1, 1) IN ('/', '\\')))";
+
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum RootScope {
+    #[default]
+    Primary,
+    Backups,
+}
+
+impl RootScope {
+    // Condition appended to queries over the `files` table
+    pub fn sql_clause(&self) -> String {
+        match self {
+            RootScope::Primary => format!(" AND NOT {}", UNDER_MIRROR_ROOT),
+            RootScope::Backups => format!(" AND {}", UNDER_MIRROR_ROOT),
+        }
+    }
+}
+
+pub fn add_column(conn: &Connection) {
+    // Ignore error if column already exists
+    let _ = conn.execute("ALTER TABLE indexed_directories ADD COLUMN is_mirror INTEGER DEFAULT 0", []);
+}
+
+// Split an `in:backups` token out of a query
+pub fn extract_scope(query: &str) -> (String, RootScope) {
+    let mut scope = RootScope::Primary;
+    let rest: Vec<&str> = query
+        .split_whitespace()
+        .filter(|token| {
+            let is_scope = matches!(token.to_lowercase().as_str(), "in:backups" | "in:backup");
+            if is_scope {
+                scope = RootScope::Backups;
+            }
+            !is_scope
+        })
+        .collect();
+
+    if scope == RootScope::Primary {
+        return (query.to_string(), scope);
+    }
+    (rest.join(" "), scope)
+}
+
+pub fn is_mirror(conn: &Connection, path: &str) -> bool {
+    conn.query_row(
+        "SELECT is_mirror FROM indexed_directories WHERE path = ?1",
+        [path],
+        |row| row.get::<_, Option<i32>>(0),
+    )
+    .map(|value| value == Some(1))
+    .unwrap_or(false)
+}
+
+fn mirror_roots(conn: &Connection) -> SqlResult<Vec<String>> {
+    let mut stmt = conn.prepare_cached("SELECT path FROM indexed_directories WHERE is_mirror = 1")?;
+    let roots = stmt.query_map([], |row| row.get(0))?.filter_map(|r| r.ok()).collect();
+    Ok(roots)
+}
+
+// Badge entries that live under a mirror root
+pub fn mark_backup_entries(conn: &Connection, entries: &mut [FileEntry]) {
+    let roots = mirror_roots(conn).unwrap_or_default();
+    if roots.is_empty() {
+        return;
+    }
+    for entry in entries {
+        entry.in_backup = roots.iter().any(|root| Path::new(&entry.path).starts_with(root));
+    }
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn set_mirror_root(path: String, mirror: bool, state: State<'_, AppState>) -> Result<(), AppError> {
+    db::write(&state.db, |db| {
+        let updated = db
+            .execute(
+                "UPDATE indexed_directories SET is_mirror = ?1 WHERE path = ?2",
+                rusqlite::params![mirror as i32, path],
+            )?;
+        if updated == 0 {
+            return Err(AppError::new(ErrorCode::NotFound, format!("Directory is not indexed: {}", path)));
+        }
+        Ok(())
+    })?;
+
+    // Cached results were scoped with the old set of mirrors
+    state.search_cache.write().map_err(|e| e.to_string())?.clear();
+    incremental::clear(&state.last_candidates);
+    Ok(())
+}
@@ -0,0 +1,296 @@
+// Name index: an `fst` map from folded file names to `files` row ids, memory-mapped from the
+// `name_index` folder next to the database. Prefix queries (`log*`, `^log.*`) take their
+// candidates from it instead of a LIKE scan, and plain words get the names that start with them
+// first, exact names ahead of longer ones, so they are found even when the scan runs out of time.
+//
+// An fst can't be changed once written, so each sync after indexing adds a segment with the
+// rows added since the last one, and every `MAX_SEGMENTS` syncs they are merged by rebuilding.
+// Renames, and deletes that could let SQLite reuse a row id, bump a counter through triggers
+// on `files`; a changed counter means the next sync rebuilds. Until a sync catches up with the
+// table, searches use the other plans, and ids the index still holds for deleted rows are
+// dropped by the SQL that reads the candidates.
+
+use fst::automaton::{Automaton, Str};
+use fst::map::OpBuilder;
+use fst::{Map, MapBuilder, Streamer};
+use memmap2::Mmap;
+use rusqlite::{Connection, Result as SqlResult};
+use serde::{Deserialize, Serialize};
+use std::fs::{self, File};
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use tracing::{info, warn};
+
+use crate::{db, locale, AppState};
+
+const DIR_NAME: &str = "name_index";
+const MANIFEST: &str = "manifest.json";
+
+// Segments kept before they are merged into one
+const MAX_SEGMENTS: usize = 8;
+
+// Most ids a lookup returns, the most candidates the search budget allows
+const MAX_IDS: usize = 20_000;
+
+// Bumped by every sync, so searches know to map the new segments
+static BUILDS: AtomicU64 = AtomicU64::new(1);
+
+// Indexing and background syncs can finish at the same time
+static SYNC_LOCK: Mutex<()> = Mutex::new(());
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+struct Manifest {
+    // `name_index_state.changes` when the first segment was built
+    changes: i64,
+    // Names were folded with the Turkish case rules
+    turkish: bool,
+    // Highest row id in the segments
+    last_id: i64,
+    segments: Vec<String>,
+}
+
+// The mapped segments, kept in the app state
+#[derive(Default)]
+pub struct NameIndex {
+    build: u64,
+    manifest: Option<Manifest>,
+    maps: Vec<Map<Mmap>>,
+}
+
+pub fn dir(db_path: &Path) -> PathBuf {
+    db_path.with_file_name(DIR_NAME)
+}
+
+pub fn create_table(conn: &Connection) -> SqlResult<()> {
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS name_index_state (
+             id INTEGER PRIMARY KEY CHECK (id = 1),
+             changes INTEGER NOT NULL
+         );
+         INSERT OR IGNORE INTO name_index_state (id, changes) VALUES (1, 0);
+         CREATE TRIGGER IF NOT EXISTS name_index_rename AFTER UPDATE OF name ON files
+         BEGIN
+             UPDATE name_index_state SET changes = changes + 1;
+         END;
+         -- Only deleting the highest id lets SQLite hand it out again
+         CREATE TRIGGER IF NOT EXISTS name_index_delete AFTER DELETE ON files
+         WHEN OLD.id > (SELECT COALESCE(MAX(id), 0) FROM files)
+         BEGIN
+             UPDATE name_index_state SET changes = changes + 1;
+         END;",
+    )
+}
+
+fn table_state(conn: &Connection) -> SqlResult<(i64, i64)> {
+    conn.query_row(
+        "SELECT (SELECT COALESCE(MAX(id), 0) FROM files), (SELECT changes FROM name_index_state)",
+        [],
+        |row| Ok((row.get(0)?, row.get(1)?)),
+    )
+}
+
+fn read_manifest(dir: &Path) -> Option<Manifest> {
+    let text = fs::read_to_string(dir.join(MANIFEST)).ok()?;
+    serde_json::from_str(&text).ok()
+}
+
+fn write_manifest(dir: &Path, manifest: &Manifest) -> Result<(), String> {
+    let json = serde_json::to_string(manifest).map_err(|e| e.to_string())?;
+    let temp = dir.join(format!("{}.tmp", MANIFEST));
+    fs::write(&temp, json).map_err(|e| e.to_string())?;
+    fs::rename(&temp, dir.join(MANIFEST)).map_err(|e| e.to_string())
+}
+
+// Key of a row: its folded name, then a NUL and the id so equal names stay distinct. NUL sorts
+// first and is never part of a file name, so an exact name comes before the longer names it
+// starts.
+fn key(folded_name: &str, id: i64) -> Vec<u8> {
+    let mut key = Vec::with_capacity(folded_name.len() + 9);
+    key.extend_from_slice(folded_name.as_bytes());
+    key.push(0);
+    key.extend_from_slice(&id.to_be_bytes());
+    key
+}
+
+// Write the rows with ids in (first_id, last_id] to a segment. Returns the number of names.
+fn write_segment(conn: &Connection, path: &Path, first_id: i64, last_id: i64) -> Result<usize, String> {
+    let mut entries: Vec<(Vec<u8>, u64)> = {
+        let mut stmt = conn
+            .prepare("SELECT id, name FROM files WHERE id > ?1 AND id <= ?2")
+            .map_err(|e| e.to_string())?;
+        let rows = stmt
+            .query_map([first_id, last_id], |row| Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?)))
+            .map_err(|e| e.to_string())?;
+        rows.filter_map(|r| r.ok()).map(|(id, name)| (key(&locale::fold(&name), id), id as u64)).collect()
+    };
+    entries.sort_unstable_by(|a, b| a.0.cmp(&b.0));
+
+    let temp = path.with_extension("tmp");
+    let writer = BufWriter::new(File::create(&temp).map_err(|e| e.to_string())?);
+    let mut builder = MapBuilder::new(writer).map_err(|e| e.to_string())?;
+    for (key, id) in &entries {
+        builder.insert(key, *id).map_err(|e| e.to_string())?;
+    }
+    let mut writer = builder.into_inner().map_err(|e| e.to_string())?;
+    writer.flush().map_err(|e| e.to_string())?;
+    fs::rename(&temp, path).map_err(|e| e.to_string())?;
+    Ok(entries.len())
+}
+
+// Segment files the manifest no longer lists; ones still mapped on Windows go on a later sync
+fn remove_unlisted(dir: &Path, manifest: &Manifest) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    for path in entries.filter_map(|entry| entry.ok().map(|entry| entry.path())) {
+        let Some(name) = path.file_name().map(|name| name.to_string_lossy().to_string()) else {
+            continue;
+        };
+        if name.ends_with(".fst") && !manifest.segments.contains(&name) {
+            let _ = fs::remove_file(&path);
+        }
+    }
+}
+
+// Bring the index up to date with `files`. Returns the number of names added.
+pub fn sync(conn: &Connection, dir: &Path) -> Result<usize, String> {
+    let _lock = SYNC_LOCK.lock().map_err(|e| e.to_string())?;
+    fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+    let (max_id, changes) = table_state(conn).map_err(|e| e.to_string())?;
+    let turkish = locale::turkish_case();
+
+    let mut manifest = match read_manifest(dir) {
+        Some(manifest)
+            if manifest.changes == changes
+                && manifest.turkish == turkish
+                && manifest.last_id <= max_id
+                && manifest.segments.len() < MAX_SEGMENTS =>
+        {
+            if manifest.last_id == max_id {
+                return Ok(0);
+            }
+            manifest
+        }
+        // Renamed or reused rows, other case rules, or time to merge: start over
+        _ => Manifest { changes, turkish, last_id: 0, segments: vec![] },
+    };
+
+    let file_name = format!("{}-{}-{}-{}.fst", changes, u8::from(turkish), manifest.last_id, max_id);
+    let added = write_segment(conn, &dir.join(&file_name), manifest.last_id, max_id)?;
+    manifest.segments.push(file_name);
+    manifest.last_id = max_id;
+    write_manifest(dir, &manifest)?;
+    remove_unlisted(dir, &manifest);
+    BUILDS.fetch_add(1, Ordering::Relaxed);
+    Ok(added)
+}
+
+// Used at startup and after file operations so searches don't wait for the index
+pub fn sync_in_background(db_path: &Path) {
+    let db_path = db_path.to_path_buf();
+    std::thread::spawn(move || {
+        let result = db::open(&db_path).map_err(|e| e.to_string()).and_then(|conn| sync(&conn, &dir(&db_path)));
+        match result {
+            Ok(0) => {}
+            Ok(added) => info!("Name index: added {} files", added),
+            Err(e) => warn!("Failed to build name index: {}", e),
+        }
+    });
+}
+
+impl NameIndex {
+    fn load(dir: &Path, build: u64) -> Self {
+        let Some(manifest) = read_manifest(dir) else {
+            return Self { build, ..Self::default() };
+        };
+        let mut maps = Vec::with_capacity(manifest.segments.len());
+        for segment in &manifest.segments {
+            let map = File::open(dir.join(segment))
+                .map_err(|e| e.to_string())
+                // SAFETY: the map is only sound while nobody truncates or writes the file. Nothing
+                // does: write_segment builds each segment in a `.tmp` file and renames it into
+                // place, so a segment path never names a file that is still being written, and
+                // no code opens a segment for writing afterwards. Replacing or removing one
+                // (a rename over it, remove_unlisted) only unlinks the name; on Unix the mapped
+                // inode lives on until it is unmapped, and Windows refuses to do either while
+                // the file is mapped.
+                .and_then(|file| unsafe { Mmap::map(&file) }.map_err(|e| e.to_string()))
+                .and_then(|mmap| Map::new(mmap).map_err(|e| e.to_string()));
+            match map {
+                Ok(map) => maps.push(map),
+                Err(e) => {
+                    warn!("Could not map name index segment {}: {}", segment, e);
+                    return Self { build, ..Self::default() };
+                }
+            }
+        }
+        Self { build, manifest: Some(manifest), maps }
+    }
+
+    // The segments cover every row of `files` with the names as they are now
+    fn is_current(&self, conn: &Connection) -> bool {
+        let Some(manifest) = &self.manifest else {
+            return false;
+        };
+        match table_state(conn) {
+            Ok((max_id, changes)) => {
+                manifest.last_id == max_id && manifest.changes == changes && manifest.turkish == locale::turkish_case()
+            }
+            Err(_) => false,
+        }
+    }
+}
+
+// Map the segments again if a sync wrote new ones since
+fn refresh(state: &AppState) -> Result<(), String> {
+    let build = BUILDS.load(Ordering::Relaxed);
+    if state.name_index.read().map_err(|e| e.to_string())?.build == build {
+        return Ok(());
+    }
+    let mut index = state.name_index.write().map_err(|e| e.to_string())?;
+    if index.build != build {
+        *index = NameIndex::load(&dir(&state.db_path), build);
+    }
+    Ok(())
+}
+
+// Ids of the rows whose names start with `prefix`, exact names first, as a JSON array for
+// `json_each`. None when the index is behind the table and can't be trusted.
+pub fn lookup(state: &AppState, conn: &Connection, prefix: &str) -> Result<Option<String>, String> {
+    refresh(state)?;
+    let index = state.name_index.read().map_err(|e| e.to_string())?;
+    if !index.is_current(conn) {
+        return Ok(None);
+    }
+    let folded = locale::fold(prefix);
+    let mut union = index
+        .maps
+        .iter()
+        .map(|map| map.search(Str::new(&folded).starts_with()))
+        .collect::<OpBuilder>()
+        .union();
+    let mut ids: Vec<u64> = Vec::new();
+    while let Some((_, values)) = union.next() {
+        ids.extend(values.iter().map(|value| value.value));
+        if ids.len() >= MAX_IDS {
+            break;
+        }
+    }
+    serde_json::to_string(&ids).map(Some).map_err(|e| e.to_string())
+}
+
+// Unescaped text of a LIKE pattern written with escape_like(), None if it has wildcards
+pub fn like_literal(pattern: &str) -> Option<String> {
+    let mut literal = String::with_capacity(pattern.len());
+    let mut chars = pattern.chars();
+    while let Some(ch) = chars.next() {
+        match ch {
+            '\\' => literal.push(chars.next()?),
+            '%' | '_' => return None,
+            c => literal.push(c),
+        }
+    }
+    Some(literal)
+}
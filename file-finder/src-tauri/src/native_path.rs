@@ -0,0 +1,180 @@
+// Paths as they are on disk. File names that aren't valid Unicode, e.g. from a legacy code page
+// on an old NTFS or ext volume, are indexed under a display form, which is what searches match
+// and the UI shows, and the exact path is kept in `files.path_raw` so the file can still be
+// opened. The display form writes each undecodable byte (an unpaired UTF-16 unit on Windows) as
+// U+FFFD and its hex value, and doubles a U+FFFD the name has itself, so no two paths share one:
+// rows stay keyed on the exact path. Paths without either keep their text and a NULL `path_raw`.
+//
+// Windows paths longer than MAX_PATH are indexed like any other; std::fs handles them itself, but
+// the shell and the programs it starts need the `\\?\` prefix to open them (see `openable`).
+
+use rusqlite::{Connection, OptionalExtension, Result as SqlResult};
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+
+// Longest path the shell and most programs open without the `\\?\` prefix, in UTF-16 units
+#[cfg(windows)]
+const MAX_PATH: usize = 260;
+
+pub fn add_column(conn: &Connection) {
+    // Fails once the column exists
+    let _ = conn.execute("ALTER TABLE files ADD COLUMN path_raw BLOB", []);
+}
+
+// Display form of a path and, when that isn't the path itself, its exact bytes
+pub fn split(path: &Path) -> (String, Option<Vec<u8>>) {
+    match path.to_str() {
+        Some(text) if !text.contains(REPLACEMENT) => (text.to_string(), None),
+        _ => (escaped(path), Some(to_raw(path))),
+    }
+}
+
+// The display form alone, for paths compared with or stored next to `files.path`
+pub fn display(path: &Path) -> String {
+    split(path).0
+}
+
+const REPLACEMENT: char = '\u{FFFD}';
+
+fn push_char(display: &mut String, c: char) {
+    display.push(c);
+    if c == REPLACEMENT {
+        display.push(REPLACEMENT);
+    }
+}
+
+#[cfg(unix)]
+fn escaped(path: &Path) -> String {
+    use std::os::unix::ffi::OsStrExt;
+    let mut display = String::new();
+    for chunk in path.as_os_str().as_bytes().utf8_chunks() {
+        for c in chunk.valid().chars() {
+            push_char(&mut display, c);
+        }
+        for byte in chunk.invalid() {
+            display.push_str(&format!("{}{:02X}", REPLACEMENT, byte));
+        }
+    }
+    display
+}
+
+#[cfg(windows)]
+fn escaped(path: &Path) -> String {
+    use std::os::windows::ffi::OsStrExt;
+    let mut display = String::new();
+    for unit in char::decode_utf16(path.as_os_str().encode_wide()) {
+        match unit {
+            Ok(c) => push_char(&mut display, c),
+            Err(e) => display.push_str(&format!("{}{:04X}", REPLACEMENT, e.unpaired_surrogate())),
+        }
+    }
+    display
+}
+
+#[cfg(unix)]
+fn to_raw(path: &Path) -> Vec<u8> {
+    use std::os::unix::ffi::OsStrExt;
+    path.as_os_str().as_bytes().to_vec()
+}
+
+#[cfg(unix)]
+fn from_raw(raw: Vec<u8>) -> OsString {
+    use std::os::unix::ffi::OsStringExt;
+    OsString::from_vec(raw)
+}
+
+// UTF-16 units, unpaired surrogates included, little-endian
+#[cfg(windows)]
+fn to_raw(path: &Path) -> Vec<u8> {
+    use std::os::windows::ffi::OsStrExt;
+    path.as_os_str().encode_wide().flat_map(u16::to_le_bytes).collect()
+}
+
+#[cfg(windows)]
+fn from_raw(raw: Vec<u8>) -> OsString {
+    use std::os::windows::ffi::OsStringExt;
+    let wide: Vec<u16> = raw.chunks_exact(2).map(|pair| u16::from_le_bytes([pair[0], pair[1]])).collect();
+    OsString::from_wide(&wide)
+}
+
+// The path on disk of an indexed path as the UI shows it
+pub fn resolve(conn: &Connection, path: &str) -> SqlResult<PathBuf> {
+    let raw: Option<Vec<u8>> = conn
+        .query_row("SELECT path_raw FROM files WHERE path = ?1", [path], |row| row.get(0))
+        .optional()?
+        .flatten();
+    Ok(stored(path, raw))
+}
+
+// The path on disk of a `files` row from its `path` and `path_raw`
+pub fn stored(path: &str, raw: Option<Vec<u8>>) -> PathBuf {
+    raw.map_or_else(|| PathBuf::from(path), |raw| PathBuf::from(from_raw(raw)))
+}
+
+// A path the shell can open: on Windows, one past MAX_PATH gets the `\\?\` prefix that lifts the
+// limit, or `\\?\UNC\` for a network share. Other paths are returned as they are.
+pub fn openable(path: PathBuf) -> PathBuf {
+    #[cfg(windows)]
+    {
+        use std::os::windows::ffi::{OsStrExt, OsStringExt};
+        use std::path::{Component, Prefix};
+
+        let wide: Vec<u16> = path.as_os_str().encode_wide().collect();
+        let prefix = match path.components().next() {
+            _ if wide.len() < MAX_PATH => None,
+            Some(Component::Prefix(prefix)) => match prefix.kind() {
+                Prefix::Disk(_) => Some((r"\\?\", 0)),
+                // `\\server\share` becomes `\\?\UNC\server\share`
+                Prefix::UNC(..) => Some((r"\\?\UNC\", 2)),
+                // Already verbatim, or a device path
+                _ => None,
+            },
+            _ => None,
+        };
+        if let Some((prefix, skip)) = prefix {
+            // Verbatim paths are passed on as they are, so they need backslashes throughout
+            let slash = u16::from(b'/');
+            let rest = wide[skip..].iter().map(|&unit| if unit == slash { u16::from(b'\\') } else { unit });
+            let prefixed: Vec<u16> = prefix.encode_utf16().chain(rest).collect();
+            return PathBuf::from(OsString::from_wide(&prefixed));
+        }
+    }
+    path
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use std::os::unix::ffi::OsStrExt;
+
+    fn path(bytes: &[u8]) -> &Path {
+        Path::new(std::ffi::OsStr::from_bytes(bytes))
+    }
+
+    #[test]
+    fn valid_paths_keep_their_text() {
+        assert_eq!(split(Path::new("/docs/report.txt")), ("/docs/report.txt".to_string(), None));
+    }
+
+    #[test]
+    fn undecodable_bytes_are_written_in_hex() {
+        let raw = b"/docs/caf\xe9.txt";
+        assert_eq!(split(path(raw)), ("/docs/caf\u{FFFD}E9.txt".to_string(), Some(raw.to_vec())));
+    }
+
+    #[test]
+    fn names_that_differ_only_in_undecodable_bytes_stay_apart() {
+        assert_ne!(display(path(b"/a\xe9")), display(path(b"/a\xe8")));
+        // The name's own U+FFFD is doubled, so it can't pass for an escaped byte
+        assert_ne!(display(path("/a\u{FFFD}E9".as_bytes())), display(path(b"/a\xe9")));
+        assert_eq!(split(path("/a\u{FFFD}".as_bytes())).0, "/a\u{FFFD}\u{FFFD}");
+    }
+
+    #[test]
+    fn stored_paths_come_back_exactly() {
+        let raw = b"/docs/caf\xe9.txt";
+        let (display, stored_raw) = split(path(raw));
+        assert_eq!(stored(&display, stored_raw), path(raw));
+        assert_eq!(stored("/docs/report.txt", None), Path::new("/docs/report.txt"));
+    }
+}
@@ -0,0 +1,412 @@
+// Natural-language queries ("documents about taxes from my accountant"). The LLM rewrites them
+// into the finder's own query syntax (words plus type:/tag:/category: filters), which then runs
+// as a normal search, and the search response carries it taken apart into keywords and filters.
+// A query takes this path when it starts with `nl:` or `ask:`, when
+// SearchOptions.natural_language says so, or - with the LLM enabled - when it reads enough like a
+// sentence to clear the configured confidence threshold. Without a reachable LLM, stop words are
+// dropped and file-type, time and "containing ..." words become filters instead. Requests in
+// German, Spanish and Japanese are recognised and parsed with their own words (see languages).
+
+use chrono::Local;
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use std::time::Instant;
+use tracing::warn;
+
+use crate::file_types::ExtensionGroups;
+use crate::languages::{self, Language};
+use crate::{content_search, llm, time_filter, AppState, SearchOptions};
+
+pub const PREFIXES: &[&str] = &["nl:", "ask:"];
+
+// Remembered rewrites; a full cache is emptied rather than trimmed
+const MAX_CACHED_REWRITES: usize = 200;
+const MAX_REWRITE_CHARS: usize = 200;
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Type)]
+#[serde(rename_all = "snake_case")]
+pub enum RewriteSource {
+    Llm,
+    // The LLM is off or didn't answer in time
+    Fallback,
+}
+
+#[derive(Debug, Clone)]
+pub struct Rewrite {
+    pub query: String,
+    pub source: RewriteSource,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Type)]
+pub struct TimeRange {
+    // The `modified:` value, e.g. "week"
+    pub value: String,
+    // Unix seconds; `after` is inclusive, `before` exclusive
+    pub after: Option<i64>,
+    pub before: Option<i64>,
+}
+
+// A rewritten query taken apart for display. Each part maps back to one query token, so the UI
+// can drop or change a part and search again with the edited query.
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq, Type)]
+pub struct QueryPlan {
+    // Words matched against file names
+    pub keywords: Vec<String>,
+    // One entry per filter token; comma-separated alternatives such as "pdf,docx" stay together
+    pub file_types: Vec<String>,
+    pub tags: Vec<String>,
+    pub categories: Vec<String>,
+    pub time_range: Option<TimeRange>,
+    // `content:` words looked for in file text
+    pub content: Vec<String>,
+    // `in:backups`
+    pub backups: bool,
+    pub source: Option<RewriteSource>,
+}
+
+pub fn strip_prefix(query: &str) -> Option<&str> {
+    let trimmed = query.trim_start();
+    PREFIXES.iter().find_map(|prefix| {
+        let head = trimmed.get(..prefix.len())?;
+        head.eq_ignore_ascii_case(prefix).then(|| trimmed[prefix.len()..].trim())
+    })
+}
+
+// How much a query reads like a sentence rather than a file name, from 0 to 1. Anything with
+// file-name or filter syntax scores 0, so "python report.txt" never goes to the LLM.
+pub fn confidence(query: &str) -> f32 {
+    let file_syntax = |word: &str| word.chars().any(|c| matches!(c, '.' | '/' | '\\' | '*' | '?' | '^' | '$' | ':' | '_' | '[' | '('));
+    if query.split_whitespace().any(file_syntax) {
+        return 0.0;
+    }
+    let language = languages::detect(query);
+    let vocabulary = language.vocabulary();
+    let words = languages::words(query, language);
+    if words.len() < 3 {
+        return 0.0;
+    }
+
+    let stop_words = words.iter().filter(|word| vocabulary.is_stop_word(word)).count();
+    let mut score = (stop_words as f32 / words.len() as f32 * 2.0).min(1.0) * 0.5;
+    if words.len() >= 4 {
+        score += 0.3;
+    }
+    // Japanese requests end with the verb: "先週の写真を見せて"
+    let lead = if language == Language::Japanese { words.last() } else { words.first() };
+    if lead.is_some_and(|word| vocabulary.is_lead_word(word)) {
+        score += 0.2;
+    }
+    score
+}
+
+// The natural-language text of a query, or None when it should be searched as typed
+pub fn natural_text<'a>(query: &'a str, options: &SearchOptions, llm_enabled: bool, threshold: f32) -> Option<&'a str> {
+    match options.natural_language {
+        Some(false) => None,
+        Some(true) => Some(strip_prefix(query).unwrap_or(query.trim())),
+        None => strip_prefix(query).or_else(|| {
+            // A threshold of 1 turns detection off; only the prefixes and the option remain
+            let detected = llm_enabled && threshold < 1.0 && confidence(query) >= threshold;
+            detected.then(|| query.trim())
+        }),
+    }
+    .filter(|text| !text.is_empty())
+}
+
+fn file_type_filter(word: &str, groups: &ExtensionGroups) -> Option<String> {
+    let merged = groups.merged();
+    let singular = word.strip_suffix('s').unwrap_or(word);
+    for candidate in [word, singular] {
+        let name = groups.canonical_name(candidate);
+        if merged.contains_key(&name) {
+            return Some(format!("type:{}", name));
+        }
+        // A known extension, e.g. "pdfs"
+        if groups.group_of(candidate).is_some() {
+            return Some(format!("type:{}", candidate));
+        }
+    }
+    None
+}
+
+// Without the LLM: drop stop words and turn file-type words ("photos", "pdfs") into type: filters
+// and date phrases ("yesterday", "last march", "past 3 days") into a modified: filter. The word after "containing"
+// or "mentioning" is searched for in file text. Other languages' date words are translated into
+// the English date grammar first ("letzten Monat" reads as "last month").
+pub fn fallback_parse(text: &str, groups: &ExtensionGroups) -> String {
+    let mut filters: Vec<String> = Vec::new();
+    let mut words: Vec<String> = Vec::new();
+    let mut time: Option<String> = None;
+    let today = Local::now().date_naive();
+    let mut in_content = false;
+    let language = languages::detect(text);
+    let vocabulary = language.vocabulary();
+    let translated = vocabulary.translate_dates(&languages::words(text, language));
+    let bare_words: Vec<String> = translated.iter().map(|(word, _)| word.clone()).collect();
+    let mut i = 0;
+    while i < bare_words.len() {
+        if let Some((value, used)) = time_filter::parse_phrase(&bare_words[i..], today) {
+            time = Some(value);
+            i += used;
+            continue;
+        }
+        let (bare, is_date_word) = &translated[i];
+        i += 1;
+        // A date word that didn't make a phrase, e.g. German "seit" before a word
+        if *is_date_word {
+            continue;
+        }
+        if vocabulary.is_content_word(bare) {
+            if !vocabulary.content_after_term() {
+                in_content = true;
+            } else if let Some(term) = words.pop() {
+                filters.push(format!("{}{}", content_search::PREFIX, term));
+            }
+            continue;
+        }
+        if vocabulary.is_stop_word(bare) {
+            continue;
+        }
+        if in_content {
+            in_content = false;
+            filters.push(format!("{}{}", content_search::PREFIX, bare));
+            continue;
+        }
+        let filter = vocabulary
+            .type_group(bare)
+            .map(|group| format!("type:{}", group))
+            .or_else(|| file_type_filter(bare, groups));
+        match filter {
+            Some(filter) if !filters.contains(&filter) => filters.push(filter),
+            Some(_) => {}
+            None => words.push(bare.clone()),
+        }
+    }
+    filters.extend(time.map(|value| format!("modified:{}", value)));
+    let parsed = filters.into_iter().chain(words).collect::<Vec<_>>().join(" ");
+    if parsed.is_empty() {
+        text.trim().to_string()
+    } else {
+        parsed
+    }
+}
+
+fn prompt(text: &str, groups: &ExtensionGroups) -> String {
+    let types = groups.merged().into_keys().collect::<Vec<_>>().join(", ");
+    let language = match languages::detect(text) {
+        Language::English => String::new(),
+        language => format!(
+            "The request is in {}. Keep the name words in that language, as they would appear in file names, \
+             but write the filters exactly as shown, in English. ",
+            language.name()
+        ),
+    };
+    format!(
+        "You turn requests for files into a search query for a file name search tool. \
+         The query is a few words that would appear in the file or folder names, plus optional filters: \
+         type:<group or extension> (groups: {}), tag:<tag>, category:<category>, \
+         modified:<value> for when the file last changed (values like {}), \
+         and content:<word> for a word that must appear inside the file. \
+         Leave out words that would not be in a file name. {}Answer with the query only, on one line.\n\n\
+         Request: {}\nQuery:",
+        types,
+        time_filter::SYNTAX,
+        language,
+        text
+    )
+}
+
+// First line of the reply without quotes, code fences or a repeated prefix
+fn clean_reply(reply: &str) -> String {
+    let line = reply.lines().map(str::trim).find(|line| !line.is_empty() && !line.starts_with("```")).unwrap_or("");
+    let line = line.strip_prefix("Query:").unwrap_or(line).trim().trim_matches(|c| c == '"' || c == '`' || c == '\'');
+    let line = strip_prefix(line).unwrap_or(line);
+    line.chars().take(MAX_REWRITE_CHARS).collect::<String>().trim().to_string()
+}
+
+// The query to search for instead of `query`, or None when it is searched as typed
+pub async fn rewrite(query: &str, options: &SearchOptions, state: &AppState) -> Result<Option<Rewrite>, String> {
+    let config = state.llm.read().map_err(|e| e.to_string())?.clone();
+    let Some(text) = natural_text(query, options, config.enabled, config.nl_confidence_threshold) else {
+        return Ok(None);
+    };
+    let groups = state.extension_groups.read().map_err(|e| e.to_string())?.clone();
+    let fallback = || Rewrite {
+        query: fallback_parse(text, &groups),
+        source: RewriteSource::Fallback,
+    };
+    if !config.enabled {
+        return Ok(Some(fallback()));
+    }
+
+    let cache_key = format!("{}\n{}", config.model, text.to_lowercase());
+    if let Some(cached) = state.nl_rewrites.read().map_err(|e| e.to_string())?.get(&cache_key) {
+        return Ok(Some(Rewrite {
+            query: cached.clone(),
+            source: RewriteSource::Llm,
+        }));
+    }
+
+    let started = Instant::now();
+    let reply = match llm::generate(&config, &prompt(text, &groups), config.search_timeout()).await {
+        Ok(reply) => clean_reply(&reply),
+        Err(e) => {
+            warn!("Natural-language rewrite failed, using the fallback parser: {}", e);
+            llm::record_parse(None);
+            return Ok(Some(fallback()));
+        }
+    };
+    if reply.is_empty() {
+        llm::record_parse(None);
+        return Ok(Some(fallback()));
+    }
+    llm::record_parse(Some(started.elapsed()));
+
+    let mut cache = state.nl_rewrites.write().map_err(|e| e.to_string())?;
+    if cache.len() >= MAX_CACHED_REWRITES {
+        cache.clear();
+    }
+    cache.insert(cache_key, reply.clone());
+    Ok(Some(Rewrite {
+        query: reply,
+        source: RewriteSource::Llm,
+    }))
+}
+
+// The parts of a rewritten query. Filter tokens the search would not recognize stay keywords,
+// because that is how they are searched.
+pub fn plan(rewrite: &Rewrite) -> QueryPlan {
+    let mut plan = QueryPlan {
+        source: Some(rewrite.source),
+        ..QueryPlan::default()
+    };
+    let now = Local::now();
+    for token in rewrite.query.split_whitespace() {
+        let lowered = token.to_lowercase();
+        let value = |prefix: &str| lowered.strip_prefix(prefix).filter(|value| !value.is_empty()).map(str::to_string);
+        if let Some(value) = value("type:") {
+            plan.file_types.push(value);
+        } else if let Some(value) = value("tag:") {
+            plan.tags.push(value);
+        } else if let Some(value) = value("category:") {
+            plan.categories.push(value);
+        } else if value(content_search::PREFIX).is_some() {
+            // As typed, for case-sensitive searches
+            plan.content.push(token[content_search::PREFIX.len()..].to_string());
+        } else if let Some(filter) = lowered.strip_prefix("modified:").and_then(|value| time_filter::parse_value(value, now)) {
+            plan.time_range = Some(TimeRange {
+                value: filter.value,
+                after: filter.after,
+                before: filter.before,
+            });
+        } else if matches!(lowered.as_str(), "in:backups" | "in:backup") {
+            plan.backups = true;
+        } else {
+            plan.keywords.push(token.to_string());
+        }
+    }
+    plan
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn options(natural_language: Option<bool>) -> SearchOptions {
+        SearchOptions { natural_language, ..SearchOptions::default() }
+    }
+
+    fn llm_rewrite(query: &str) -> Rewrite {
+        Rewrite { query: query.to_string(), source: RewriteSource::Llm }
+    }
+
+    #[test]
+    fn prefixes_are_stripped_in_any_case() {
+        assert_eq!(strip_prefix("nl: photos from last week"), Some("photos from last week"));
+        assert_eq!(strip_prefix("  ASK:taxes"), Some("taxes"));
+        assert_eq!(strip_prefix("nlp notes"), None);
+        assert_eq!(strip_prefix("report nl:"), None);
+    }
+
+    #[test]
+    fn file_names_and_filters_are_never_sentences() {
+        assert_eq!(confidence("find my report.txt please"), 0.0);
+        assert_eq!(confidence("show me all type:image files"), 0.0);
+        assert_eq!(confidence("src/main rust code"), 0.0);
+        assert_eq!(confidence("budget 2024"), 0.0);
+    }
+
+    #[test]
+    fn requests_read_as_sentences() {
+        assert!(confidence("find all my tax documents") > 0.9);
+        assert!(confidence("quarterly budget spreadsheet draft") < 0.5);
+    }
+
+    #[test]
+    fn the_option_overrides_detection() {
+        assert_eq!(natural_text("nl: taxes", &options(Some(false)), true, 0.0), None);
+        assert_eq!(natural_text("ask: taxes", &options(Some(true)), false, 1.0), Some("taxes"));
+        assert_eq!(natural_text("  report  ", &options(Some(true)), false, 1.0), Some("report"));
+        assert_eq!(natural_text("nl:   ", &options(Some(true)), true, 0.0), None);
+    }
+
+    #[test]
+    fn detection_needs_the_llm_and_a_threshold_below_one() {
+        let sentence = "find all my tax documents";
+        assert_eq!(natural_text(sentence, &options(None), true, 0.6), Some(sentence));
+        assert_eq!(natural_text(sentence, &options(None), false, 0.6), None);
+        assert_eq!(natural_text(sentence, &options(None), true, 1.0), None);
+        // The prefixes work either way
+        assert_eq!(natural_text("nl: taxes", &options(None), false, 1.0), Some("taxes"));
+    }
+
+    #[test]
+    fn fallback_turns_type_and_content_words_into_filters() {
+        let groups = ExtensionGroups::default();
+        assert_eq!(fallback_parse("show me photos containing invoice", &groups), "type:image content:invoice");
+        assert_eq!(fallback_parse("pdfs about taxes", &groups), "type:pdf taxes");
+    }
+
+    #[test]
+    fn fallback_turns_date_phrases_into_a_time_filter() {
+        let groups = ExtensionGroups::default();
+        let parsed = fallback_parse("budget from last week", &groups);
+        let (filter, rest) = parsed.split_once(' ').unwrap();
+        assert_eq!(rest, "budget");
+        let value = filter.strip_prefix("modified:").unwrap();
+        assert!(time_filter::parse_value(value, Local::now()).is_some(), "{}", parsed);
+    }
+
+    #[test]
+    fn fallback_keeps_the_text_when_nothing_is_left() {
+        assert_eq!(fallback_parse("show me the", &ExtensionGroups::default()), "show me the");
+    }
+
+    #[test]
+    fn replies_are_cleaned_to_one_query() {
+        assert_eq!(clean_reply("```\nQuery: \"type:image beach\"\n```"), "type:image beach");
+        assert_eq!(clean_reply("\n  `nl: report`  \nsome explanation"), "report");
+        assert_eq!(clean_reply(&"x".repeat(500)).len(), MAX_REWRITE_CHARS);
+    }
+
+    #[test]
+    fn plans_take_the_query_apart() {
+        let plan = plan(&llm_rewrite("budget type:pdf tag:work category:finance content:TODO modified:2023 in:backups"));
+        assert_eq!(plan.keywords, vec!["budget"]);
+        assert_eq!(plan.file_types, vec!["pdf"]);
+        assert_eq!(plan.tags, vec!["work"]);
+        assert_eq!(plan.categories, vec!["finance"]);
+        assert_eq!(plan.content, vec!["TODO"]);
+        assert_eq!(plan.time_range.map(|range| range.value).as_deref(), Some("2023"));
+        assert!(plan.backups);
+        assert_eq!(plan.source, Some(RewriteSource::Llm));
+    }
+
+    #[test]
+    fn unrecognized_filters_stay_keywords() {
+        let plan = plan(&llm_rewrite("modified:someday type: notes"));
+        assert_eq!(plan.keywords, vec!["modified:someday", "type:", "notes"]);
+        assert!(plan.file_types.is_empty());
+        assert_eq!(plan.time_range, None);
+    }
+}
@@ -0,0 +1,216 @@
+// Notes attached to files: free text such as "router admin password hint is in here". Note
+// text is kept in a word index (`notes_fts`, stemmed, unlike the trigram index of names) so
+// plain searches also find files by what their notes say, unless that is turned off.
+
+use rusqlite::types::Value;
+use rusqlite::{params, params_from_iter, Connection, OptionalExtension, Result as SqlResult};
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use tauri::State;
+
+use crate::content_search::{ContentSnippet, MatchSource};
+use crate::error::{AppError, ErrorCode};
+use crate::search_sql::SqlFilter;
+use crate::{db, invalidate_search_cache, languages, settings, AppState, FileEntry};
+
+pub const SETTINGS_KEY: &str = "notes";
+
+// Longest note accepted
+const MAX_NOTE_CHARS: usize = 10_000;
+// Note matches added to a search at most
+const MAX_HITS: usize = 50;
+const SNIPPET_CHARS: usize = 160;
+
+#[derive(Debug, Serialize, Clone, Type)]
+pub struct FileNote {
+    pub path: String,
+    pub text: String,
+    pub updated_at: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Type)]
+#[serde(default)]
+pub struct NotesConfig {
+    // Whether plain searches also match note text
+    pub search_notes: bool,
+}
+
+impl Default for NotesConfig {
+    fn default() -> Self {
+        Self { search_notes: true }
+    }
+}
+
+pub fn create_tables(conn: &Connection) -> SqlResult<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS file_notes (
+            id INTEGER PRIMARY KEY,
+            path TEXT UNIQUE NOT NULL,
+            text TEXT NOT NULL,
+            updated_at INTEGER NOT NULL
+        )",
+        [],
+    )?;
+    conn.execute(
+        "CREATE VIRTUAL TABLE IF NOT EXISTS notes_fts USING fts5(
+            text, content = 'file_notes', content_rowid = 'id', tokenize = 'porter unicode61'
+        )",
+        [],
+    )?;
+    // Keep the word index in step with the notes, including when renames and snapshot restores
+    // rewrite the table
+    conn.execute_batch(
+        "CREATE TRIGGER IF NOT EXISTS file_notes_ai AFTER INSERT ON file_notes BEGIN
+            INSERT INTO notes_fts (rowid, text) VALUES (new.id, new.text);
+         END;
+         CREATE TRIGGER IF NOT EXISTS file_notes_ad AFTER DELETE ON file_notes BEGIN
+            INSERT INTO notes_fts (notes_fts, rowid, text) VALUES ('delete', old.id, old.text);
+         END;
+         CREATE TRIGGER IF NOT EXISTS file_notes_au AFTER UPDATE ON file_notes BEGIN
+            INSERT INTO notes_fts (notes_fts, rowid, text) VALUES ('delete', old.id, old.text);
+            INSERT INTO notes_fts (rowid, text) VALUES (new.id, new.text);
+         END;",
+    )?;
+    Ok(())
+}
+
+pub fn config(conn: &Connection) -> NotesConfig {
+    settings::load::<NotesConfig>(conn, SETTINGS_KEY)
+}
+
+// FTS5 expression matching notes with any of the query's meaningful words. bm25 ranks notes
+// with more of them first, so one stray word doesn't matter much.
+fn match_expression(query: &str) -> Option<String> {
+    let language = languages::detect(query);
+    let vocabulary = language.vocabulary();
+    let terms: Vec<String> = languages::words(query, language)
+        .into_iter()
+        .filter(|word| word.chars().count() >= 2 && !vocabulary.is_stop_word(word))
+        .map(|word| format!("\"{}\"", word.replace('"', "\"\"")))
+        .collect();
+    if terms.is_empty() {
+        return None;
+    }
+    Some(terms.join(" OR "))
+}
+
+// First line of the note with a query word in it, or its first line
+fn snippet(text: &str, query: &str) -> ContentSnippet {
+    let words = languages::words(query, languages::detect(query));
+    let lines: Vec<&str> = text.lines().collect();
+    let index = lines
+        .iter()
+        .position(|line| {
+            let line = line.to_lowercase();
+            words.iter().any(|word| line.contains(word.as_str()))
+        })
+        .unwrap_or(0);
+    ContentSnippet {
+        line: index as u32 + 1,
+        text: lines.get(index).unwrap_or(&"").trim().chars().take(SNIPPET_CHARS).collect(),
+    }
+}
+
+// Indexed files whose notes match `query`, best match first. `filter` holds the query's
+// other filters, as ranked_search builds them.
+pub fn find(conn: &Connection, query: &str, filter: &SqlFilter) -> Result<Vec<FileEntry>, String> {
+    let Some(expression) = match_expression(query) else {
+        return Ok(vec![]);
+    };
+    let mut stmt = conn
+        .prepare_cached(&format!(
+            "SELECT files.path, files.name, files.modified_at, hits.note FROM (
+                SELECT n.path AS note_path, n.text AS note, bm25(notes_fts) AS score
+                FROM notes_fts JOIN file_notes n ON n.id = notes_fts.rowid
+                WHERE notes_fts MATCH ?1
+             ) hits JOIN files ON files.path = hits.note_path
+             WHERE 1 = 1{} ORDER BY hits.score LIMIT ?",
+            filter.sql
+        ))
+        .map_err(|e| e.to_string())?;
+    let hits = stmt
+        .query_map(params_from_iter(filter.bind(vec![Value::Text(expression)], vec![Value::Integer(MAX_HITS as i64)])), |row| {
+            let note: String = row.get(3)?;
+            Ok(FileEntry {
+                path: row.get(0)?,
+                name: row.get(1)?,
+                last_accessed: None,
+                access_count: 0,
+                modified_at: row.get(2)?,
+                age_bucket: None,
+                in_backup: false,
+                match_source: Some(MatchSource::Note),
+                content_snippet: Some(snippet(&note, query)),
+            })
+        })
+        .map_err(|e| e.to_string())?
+        .filter_map(|r| r.ok())
+        .collect();
+    Ok(hits)
+}
+
+// Files found only through their notes go after the other results
+pub fn merge(mut results: Vec<FileEntry>, hits: Vec<FileEntry>) -> Vec<FileEntry> {
+    for hit in hits {
+        if !results.iter().any(|entry| entry.path == hit.path) {
+            results.push(hit);
+        }
+    }
+    results
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn get_note(path: String, state: State<'_, AppState>) -> Result<Option<FileNote>, AppError> {
+    db::write(&state.db, |db| {
+        db.query_row("SELECT path, text, updated_at FROM file_notes WHERE path = ?1", [&path], |row| {
+            Ok(FileNote {
+                path: row.get(0)?,
+                text: row.get(1)?,
+                updated_at: row.get(2)?,
+            })
+        })
+        .optional()
+        .map_err(AppError::from)
+    })
+}
+
+// Set the note of a file, replacing any earlier one. Blank text deletes the note.
+#[tauri::command]
+#[specta::specta]
+pub async fn set_note(path: String, text: String, state: State<'_, AppState>) -> Result<Option<FileNote>, AppError> {
+    let text = text.trim().to_string();
+    if text.chars().count() > MAX_NOTE_CHARS {
+        return Err(AppError::new(ErrorCode::InvalidInput, format!("Notes are limited to {} characters", MAX_NOTE_CHARS)));
+    }
+    let note = db::write(&state.db, |db| {
+        if text.is_empty() {
+            db.execute("DELETE FROM file_notes WHERE path = ?1", [&path])?;
+            Ok(None)
+        } else {
+            let now = chrono::Utc::now().timestamp();
+            db.execute(
+                "INSERT INTO file_notes (path, text, updated_at) VALUES (?1, ?2, ?3)
+                 ON CONFLICT(path) DO UPDATE SET text = ?2, updated_at = ?3",
+                params![path, text, now],
+            )?;
+            Ok(Some(FileNote { path, text, updated_at: now }))
+        }
+    })?;
+    invalidate_search_cache(&state);
+    Ok(note)
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn get_notes_config(state: State<'_, AppState>) -> Result<NotesConfig, AppError> {
+    db::write(&state.db, |db| Ok(config(db)))
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn set_notes_config(config: NotesConfig, state: State<'_, AppState>) -> Result<NotesConfig, AppError> {
+    db::write(&state.db, |db| settings::save(db, SETTINGS_KEY, &config))?;
+    invalidate_search_cache(&state);
+    Ok(config)
+}
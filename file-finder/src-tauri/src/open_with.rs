@@ -0,0 +1,510 @@
+// Applications that can open a file, as the OS knows them: the registry's OpenWithList,
+// OpenWithProgids and App Paths on Windows, LaunchServices on macOS, and .desktop files
+// associated with the file's MIME type on Linux. The default application comes first.
+// Programs picked in Open With are remembered per extension (open_with_prefs) and offered
+// ahead of the OS default from then on.
+
+use rusqlite::{params, Connection, OptionalExtension, Result as SqlResult};
+use serde::Serialize;
+use specta::Type;
+use std::path::Path;
+use tauri::State;
+
+use crate::error::AppError;
+use crate::{db, AppState};
+
+#[derive(Debug, Serialize, Clone, Type)]
+pub struct OpenWithApp {
+    pub name: String,
+    // What open_file_with takes: an executable path, an .app bundle or a .desktop file id
+    pub program: String,
+    pub is_default: bool,
+}
+
+#[derive(Debug, Serialize, Clone, Type)]
+pub struct OpenWithPref {
+    // Lowercase, without the dot
+    pub extension: String,
+    pub program: String,
+    pub updated_at: i64,
+}
+
+pub fn create_table(conn: &Connection) -> SqlResult<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS open_with_prefs (
+            extension TEXT PRIMARY KEY,
+            program TEXT NOT NULL,
+            updated_at INTEGER NOT NULL
+        )",
+        [],
+    )?;
+    Ok(())
+}
+
+// Lowercase extension of a path, None for names without one
+pub fn extension_of(path: &str) -> Option<String> {
+    Path::new(path).extension().map(|ext| ext.to_string_lossy().to_lowercase()).filter(|ext| !ext.is_empty())
+}
+
+// Remember `program` as the choice for files with `path`'s extension
+pub fn remember(conn: &Connection, path: &str, program: &str) -> SqlResult<()> {
+    let Some(extension) = extension_of(path) else {
+        return Ok(());
+    };
+    conn.execute(
+        "INSERT INTO open_with_prefs (extension, program, updated_at) VALUES (?1, ?2, ?3)
+         ON CONFLICT(extension) DO UPDATE SET program = ?2, updated_at = ?3",
+        params![extension, program, chrono::Utc::now().timestamp()],
+    )?;
+    Ok(())
+}
+
+pub fn preferred(conn: &Connection, extension: &str) -> SqlResult<Option<String>> {
+    conn.query_row("SELECT program FROM open_with_prefs WHERE extension = ?1", [extension], |row| row.get(0))
+        .optional()
+}
+
+// Move the remembered program to the front, adding it if the OS doesn't list it (a custom path)
+pub fn put_preferred_first(apps: &mut Vec<OpenWithApp>, program: &str) {
+    let app = match apps.iter().position(|app| app.program.eq_ignore_ascii_case(program)) {
+        Some(i) => apps.remove(i),
+        None => OpenWithApp {
+            name: Path::new(program).file_stem().map(|n| n.to_string_lossy().to_string()).unwrap_or_else(|| program.to_string()),
+            program: program.to_string(),
+            is_default: false,
+        },
+    };
+    apps.insert(0, app);
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn get_open_with_prefs(state: State<'_, AppState>) -> Result<Vec<OpenWithPref>, AppError> {
+    db::write(&state.db, |db| {
+        let mut stmt = db
+            .prepare("SELECT extension, program, updated_at FROM open_with_prefs ORDER BY extension")?;
+        let prefs = stmt
+            .query_map([], |row| {
+                Ok(OpenWithPref {
+                    extension: row.get(0)?,
+                    program: row.get(1)?,
+                    updated_at: row.get(2)?,
+                })
+            })?
+            .filter_map(|r| r.ok())
+            .collect();
+        Ok(prefs)
+    })
+}
+
+// Forget the choice for one extension, or for all of them. Returns how many were removed.
+#[tauri::command]
+#[specta::specta]
+pub async fn clear_open_with_prefs(extension: Option<String>, state: State<'_, AppState>) -> Result<usize, AppError> {
+    db::write(&state.db, |db| {
+        let removed = match extension {
+            Some(extension) => {
+                let extension = extension.trim().trim_start_matches('.').to_lowercase();
+                db.execute("DELETE FROM open_with_prefs WHERE extension = ?1", [extension])
+            }
+            None => db.execute("DELETE FROM open_with_prefs", []),
+        }?;
+        Ok(removed)
+    })
+}
+
+// Applications for a file, default first, each program once
+pub fn apps_for(path: &Path) -> Vec<OpenWithApp> {
+    let mut apps = platform::apps_for(path);
+    let mut seen = std::collections::HashSet::new();
+    apps.retain(|app| seen.insert(app.program.to_lowercase()));
+    apps
+}
+
+// Start `program` on `path`, passed as the OS path so names that aren't valid Unicode still open
+pub fn launch(program: &str, path: &Path) -> Result<(), String> {
+    platform::launch(program, path)
+}
+
+#[cfg(target_os = "windows")]
+mod platform {
+    use std::os::windows::process::CommandExt;
+    use std::path::Path;
+    use std::process::Command;
+
+    use super::OpenWithApp;
+
+    const CREATE_NO_WINDOW: u32 = 0x0800_0000;
+    const FILE_EXTS: &str = "HKCU\\Software\\Microsoft\\Windows\\CurrentVersion\\Explorer\\FileExts";
+    const APP_PATHS: &[&str] = &[
+        "HKCU\\Software\\Microsoft\\Windows\\CurrentVersion\\App Paths",
+        "HKLM\\Software\\Microsoft\\Windows\\CurrentVersion\\App Paths",
+    ];
+
+    // Values (name, data) and subkey names of a registry key, through reg.exe
+    fn reg_query(key: &str, args: &[&str]) -> (Vec<(String, String)>, Vec<String>) {
+        let Ok(output) = Command::new("reg").arg("query").arg(key).args(args).creation_flags(CREATE_NO_WINDOW).output() else {
+            return (Vec::new(), Vec::new());
+        };
+        let mut values = Vec::new();
+        let mut subkeys = Vec::new();
+        // reg.exe prints subkeys with the hive spelled out ("HKEY_CLASSES_ROOT\\..."), so compare below it
+        let below_hive = |key: &str| key.split_once('\\').map(|(_, rest)| rest.to_lowercase());
+        let parent = below_hive(key).unwrap_or_default();
+        for line in String::from_utf8_lossy(&output.stdout).lines() {
+            if let Some(value) = line.strip_prefix("    ") {
+                // "    Name    REG_SZ    Data"
+                let mut fields = value.splitn(3, "    ");
+                if let (Some(name), Some(kind), data) = (fields.next(), fields.next(), fields.next()) {
+                    if kind.starts_with("REG_") {
+                        values.push((name.to_string(), data.unwrap_or_default().to_string()));
+                    }
+                }
+            } else if let Some(child) = below_hive(line).and_then(|rest| rest.strip_prefix(&format!("{}\\", parent)).map(String::from)) {
+                // Keep the name's case from the output line
+                subkeys.push(line.get(line.len().saturating_sub(child.len())..).unwrap_or(&child).to_string());
+            }
+        }
+        (values, subkeys)
+    }
+
+    fn default_value(key: &str) -> Option<String> {
+        reg_query(key, &["/ve"]).0.into_iter().next().map(|(_, data)| data).filter(|data| !data.is_empty())
+    }
+
+    // %SystemRoot% and friends
+    fn expand_env(text: &str) -> String {
+        let mut out = String::new();
+        let mut parts = text.split('%');
+        out.push_str(parts.next().unwrap_or_default());
+        let mut in_var = true;
+        for part in parts {
+            if in_var {
+                match std::env::var(part) {
+                    Ok(value) => out.push_str(&value),
+                    Err(_) => {
+                        out.push('%');
+                        out.push_str(part);
+                        out.push('%');
+                    }
+                }
+            } else {
+                out.push_str(part);
+            }
+            in_var = !in_var;
+        }
+        out
+    }
+
+    // The executable of a command line such as "\"C:\\Program Files\\App\\app.exe\" \"%1\""
+    fn executable(command: &str) -> Option<String> {
+        let command = command.trim();
+        let exe = match command.strip_prefix('"') {
+            Some(quoted) => quoted.split('"').next()?,
+            None => command.split(' ').next()?,
+        };
+        let exe = expand_env(exe);
+        Path::new(&exe).is_file().then_some(exe)
+    }
+
+    fn progid_executable(progid: &str) -> Option<String> {
+        default_value(&format!("HKCR\\{}\\shell\\open\\command", progid)).and_then(|command| executable(&command))
+    }
+
+    // Full path of an executable name from OpenWithList ("code.exe")
+    fn resolve_exe(name: &str) -> Option<String> {
+        APP_PATHS
+            .iter()
+            .find_map(|root| default_value(&format!("{}\\{}", root, name)).map(|path| expand_env(path.trim_matches('"'))))
+            .filter(|path| Path::new(path).is_file())
+            .or_else(|| progid_executable(&format!("Applications\\{}", name)))
+    }
+
+    fn app(program: String, is_default: bool) -> OpenWithApp {
+        let file_name = Path::new(&program).file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+        // FriendlyAppName is often an "@dll,-id" resource reference, which reg.exe can't resolve
+        let friendly = reg_query(&format!("HKCR\\Applications\\{}", file_name), &["/v", "FriendlyAppName"])
+            .0
+            .into_iter()
+            .map(|(_, name)| name)
+            .find(|name| !name.is_empty() && !name.starts_with('@'));
+        let stem = Path::new(&program).file_stem().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+        OpenWithApp { name: friendly.unwrap_or(stem), program, is_default }
+    }
+
+    pub fn apps_for(path: &Path) -> Vec<OpenWithApp> {
+        let Some(ext) = path.extension().map(|ext| format!(".{}", ext.to_string_lossy().to_lowercase())) else {
+            return Vec::new();
+        };
+        let mut apps = Vec::new();
+
+        // The user's choice in Explorer, then the class registered for the extension
+        let user_choice = reg_query(&format!("{}\\{}\\UserChoice", FILE_EXTS, ext), &["/v", "ProgId"]).0.into_iter().next();
+        let default = user_choice
+            .and_then(|(_, progid)| progid_executable(&progid))
+            .or_else(|| default_value(&format!("HKCR\\{}", ext)).and_then(|progid| progid_executable(&progid)));
+        if let Some(program) = default {
+            apps.push(app(program, true));
+        }
+
+        let recent = reg_query(&format!("{}\\{}\\OpenWithList", FILE_EXTS, ext), &[]).0;
+        let names = recent.into_iter().filter(|(name, _)| name != "MRUList").map(|(_, exe)| exe);
+        let registered = reg_query(&format!("HKCR\\{}\\OpenWithList", ext), &[]).1;
+        for name in names.chain(registered) {
+            if let Some(program) = resolve_exe(&name) {
+                apps.push(app(program, false));
+            }
+        }
+        for (progid, _) in reg_query(&format!("HKCR\\{}\\OpenWithProgids", ext), &[]).0 {
+            if let Some(program) = progid_executable(&progid) {
+                apps.push(app(program, false));
+            }
+        }
+        apps
+    }
+
+    // Started directly, not through `cmd /C start`: cmd.exe would read `&`, `|` or `%VAR%` in a
+    // file name as its own syntax. A console program gets a console of its own, as we have none.
+    pub fn launch(program: &str, path: &Path) -> Result<(), String> {
+        Command::new(program).arg(path).spawn().map_err(|e| format!("Could not start {}: {}", program, e))?;
+        Ok(())
+    }
+}
+
+#[cfg(target_os = "macos")]
+mod platform {
+    use std::path::Path;
+    use std::process::Command;
+
+    use super::OpenWithApp;
+
+    // LaunchServices through the JavaScript for Automation bridge: the default application first,
+    // then every application that declares it can open the file
+    const SCRIPT: &str = "ObjC.import('AppKit');
+        const env = $.NSProcessInfo.processInfo.environment;
+        const url = $.NSURL.fileURLWithPath(env.objectForKey('FILE_FINDER_OPEN_WITH'));
+        const workspace = $.NSWorkspace.sharedWorkspace;
+        const preferred = workspace.URLForApplicationToOpenURL(url);
+        const all = workspace.URLsForApplicationsToOpenURL(url);
+        const paths = [preferred.isNil() ? '' : preferred.path.js];
+        for (let i = 0; i < all.count; i++) { paths.push(all.objectAtIndex(i).path.js); }
+        paths.join('\\n');";
+
+    pub fn apps_for(path: &Path) -> Vec<OpenWithApp> {
+        let Ok(output) = Command::new("osascript").args(["-l", "JavaScript", "-e", SCRIPT]).env("FILE_FINDER_OPEN_WITH", path).output() else {
+            return Vec::new();
+        };
+        let listing = String::from_utf8_lossy(&output.stdout);
+        listing
+            .lines()
+            .enumerate()
+            .filter(|(_, app)| !app.trim().is_empty())
+            .map(|(i, app)| OpenWithApp {
+                name: Path::new(app.trim()).file_stem().map(|n| n.to_string_lossy().to_string()).unwrap_or_default(),
+                program: app.trim().to_string(),
+                is_default: i == 0,
+            })
+            .collect()
+    }
+
+    pub fn launch(program: &str, path: &Path) -> Result<(), String> {
+        let mut command = if program.ends_with(".app") {
+            let mut open = Command::new("open");
+            open.arg("-a").arg(program);
+            open
+        } else {
+            Command::new(program)
+        };
+        command.arg(path).spawn().map_err(|e| e.to_string())?;
+        Ok(())
+    }
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+mod platform {
+    use std::ffi::OsString;
+    use std::fs;
+    use std::path::{Path, PathBuf};
+    use std::process::Command;
+
+    use super::OpenWithApp;
+    use crate::icons::linux::{data_dirs, mime_type};
+
+    struct DesktopEntry {
+        name: String,
+        exec: String,
+    }
+
+    fn config_dirs() -> Vec<PathBuf> {
+        let mut dirs: Vec<PathBuf> = dirs::config_dir().into_iter().collect();
+        let system = std::env::var("XDG_CONFIG_DIRS").unwrap_or_else(|_| "/etc/xdg".to_string());
+        dirs.extend(system.split(':').filter(|dir| !dir.is_empty()).map(PathBuf::from));
+        dirs
+    }
+
+    // `key=a.desktop;b.desktop;` entries for `mime` in one section of an INI-style file
+    fn section_ids(contents: &str, section: &str, mime: &str) -> Vec<String> {
+        let mut in_section = false;
+        let mut ids = Vec::new();
+        for line in contents.lines().map(str::trim) {
+            if line.starts_with('[') {
+                in_section = line == section;
+            } else if in_section {
+                if let Some((key, value)) = line.split_once('=') {
+                    if key.trim() == mime {
+                        ids.extend(value.split(';').map(str::trim).filter(|id| !id.is_empty()).map(String::from));
+                    }
+                }
+            }
+        }
+        ids
+    }
+
+    // Desktop file ids for a MIME type: defaults and added associations from mimeapps.list, then
+    // everything installed applications declare (mimeinfo.cache), minus removed associations
+    fn associated_ids(mime: &str) -> (Vec<String>, Vec<String>) {
+        let lists: Vec<String> = config_dirs()
+            .into_iter()
+            .chain(data_dirs().into_iter().map(|dir| dir.join("applications")))
+            .filter_map(|dir| fs::read_to_string(dir.join("mimeapps.list")).ok())
+            .collect();
+        let caches: Vec<String> = data_dirs()
+            .into_iter()
+            .filter_map(|dir| fs::read_to_string(dir.join("applications").join("mimeinfo.cache")).ok())
+            .collect();
+
+        let removed: Vec<String> = lists.iter().flat_map(|list| section_ids(list, "[Removed Associations]", mime)).collect();
+        let defaults: Vec<String> = lists.iter().flat_map(|list| section_ids(list, "[Default Applications]", mime)).collect();
+        let others = lists
+            .iter()
+            .flat_map(|list| section_ids(list, "[Added Associations]", mime))
+            .chain(caches.iter().flat_map(|cache| section_ids(cache, "[MIME Cache]", mime)))
+            .filter(|id| !removed.contains(id))
+            .collect();
+        (defaults, others)
+    }
+
+    // "org.kde.kate.desktop" in applications/, or "kde-kate.desktop" in applications/kde/
+    fn find_desktop_file(id: &str) -> Option<PathBuf> {
+        data_dirs().into_iter().map(|dir| dir.join("applications")).find_map(|dir| {
+            let direct = dir.join(id);
+            if direct.is_file() {
+                return Some(direct);
+            }
+            let nested = dir.join(id.replacen('-', "/", 1));
+            nested.is_file().then_some(nested)
+        })
+    }
+
+    fn read_desktop_entry(id: &str) -> Option<DesktopEntry> {
+        let contents = fs::read_to_string(find_desktop_file(id)?).ok()?;
+        let mut in_entry = false;
+        let (mut name, mut exec) = (None, None);
+        for line in contents.lines().map(str::trim) {
+            if line.starts_with('[') {
+                in_entry = line == "[Desktop Entry]";
+                continue;
+            }
+            if !in_entry {
+                continue;
+            }
+            match line.split_once('=').map(|(key, value)| (key.trim(), value.trim())) {
+                Some(("Name", value)) => name = Some(value.to_string()),
+                Some(("Exec", value)) => exec = Some(value.to_string()),
+                Some(("Hidden", "true")) => return None,
+                Some(("Type", value)) if value != "Application" => return None,
+                _ => {}
+            }
+        }
+        Some(DesktopEntry { name: name?, exec: exec? })
+    }
+
+    // Exec= split into arguments: double quotes group, backslash escapes inside them
+    fn exec_arguments(exec: &str) -> Vec<String> {
+        let mut args = Vec::new();
+        let mut current = String::new();
+        let mut quoted = false;
+        let mut has_arg = false;
+        let mut chars = exec.chars();
+        while let Some(c) = chars.next() {
+            match c {
+                '"' => {
+                    quoted = !quoted;
+                    has_arg = true;
+                }
+                '\\' if quoted => current.extend(chars.next()),
+                ' ' | '\t' if !quoted => {
+                    if has_arg || !current.is_empty() {
+                        args.push(std::mem::take(&mut current));
+                    }
+                    has_arg = false;
+                }
+                _ => current.push(c),
+            }
+        }
+        if has_arg || !current.is_empty() {
+            args.push(current);
+        }
+        args
+    }
+
+    // The command line for a desktop entry with `path` in place of its field codes
+    fn command_line(exec: &str, path: &Path) -> Vec<OsString> {
+        let mut used_path = false;
+        let mut argv: Vec<OsString> = exec_arguments(exec)
+            .into_iter()
+            .filter_map(|arg| match arg.as_str() {
+                "%f" | "%F" | "%u" | "%U" => {
+                    used_path = true;
+                    Some(path.as_os_str().to_owned())
+                }
+                // Icon, name and location codes; nothing useful to pass
+                "%i" | "%c" | "%k" => None,
+                _ => Some(arg.replace("%%", "%").into()),
+            })
+            .collect();
+        if !used_path {
+            argv.push(path.as_os_str().to_owned());
+        }
+        argv
+    }
+
+    pub fn apps_for(path: &Path) -> Vec<OpenWithApp> {
+        let Some(mime) = path.extension().and_then(|ext| mime_type(&ext.to_string_lossy().to_lowercase())) else {
+            return Vec::new();
+        };
+        let (defaults, mut others) = associated_ids(&mime);
+        // Source files have specific types (text/rust) that most editors only declare as text/plain
+        if mime.starts_with("text/") && mime != "text/plain" {
+            let (plain_defaults, plain_others) = associated_ids("text/plain");
+            others.extend(plain_defaults);
+            others.extend(plain_others);
+        }
+        let has_default = !defaults.is_empty();
+
+        defaults
+            .into_iter()
+            .chain(others)
+            .enumerate()
+            .filter_map(|(i, id)| {
+                let entry = read_desktop_entry(&id)?;
+                // A desktop file pointing at a program that was uninstalled
+                let program = exec_arguments(&entry.exec).into_iter().next()?;
+                let installed = (program.contains('/') && Path::new(&program).is_file())
+                    || std::env::var_os("PATH").is_some_and(|paths| std::env::split_paths(&paths).any(|dir| dir.join(&program).is_file()));
+                installed.then(|| OpenWithApp { name: entry.name, program: id, is_default: has_default && i == 0 })
+            })
+            .collect()
+    }
+
+    pub fn launch(program: &str, path: &Path) -> Result<(), String> {
+        let argv = match program.strip_suffix(".desktop").map(|_| read_desktop_entry(program)) {
+            Some(Some(entry)) => command_line(&entry.exec, path),
+            Some(None) => return Err(format!("No application {}", program)),
+            None => vec![program.into(), path.as_os_str().to_owned()],
+        };
+        let (program, args) = argv.split_first().ok_or("Empty command")?;
+        Command::new(program).args(args).spawn().map_err(|e| e.to_string())?;
+        Ok(())
+    }
+}
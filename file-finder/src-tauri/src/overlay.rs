@@ -0,0 +1,190 @@
+// Spotlight-style overlay: a small frameless, always-on-top window with a search box and the
+// top few results, toggled by a global shortcut. It is created on first use, placed near the
+// top of the screen the mouse is on, and hidden again on Escape, on opening a result or when
+// it loses focus. The main window stays for longer browsing.
+
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use tauri::{AppHandle, Emitter, Manager, PhysicalPosition, State, WebviewUrl, WebviewWindow, WebviewWindowBuilder, WindowEvent};
+use tauri_plugin_global_shortcut::{GlobalShortcutExt, Shortcut};
+use tracing::warn;
+
+use crate::error::AppError;
+use crate::{db, settings, tray, AppState};
+
+pub const SETTINGS_KEY: &str = "overlay";
+
+const LABEL: &str = "overlay";
+const WIDTH: f64 = 640.0;
+const HEIGHT: f64 = 420.0;
+// Sent to the overlay page each time it is shown, so it starts from an empty search box
+const SHOWN_EVENT: &str = "overlay-shown";
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Type)]
+#[serde(default)]
+pub struct OverlayConfig {
+    pub enabled: bool,
+    // Accelerator such as "CommandOrControl+Shift+Space"
+    pub shortcut: String,
+    // Results listed in the overlay
+    pub max_results: u32,
+}
+
+impl Default for OverlayConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            shortcut: "CommandOrControl+Shift+Space".to_string(),
+            max_results: 8,
+        }
+    }
+}
+
+impl OverlayConfig {
+    fn sanitized(mut self) -> Self {
+        self.shortcut = self.shortcut.trim().to_string();
+        if self.shortcut.is_empty() {
+            self.shortcut = OverlayConfig::default().shortcut;
+        }
+        self.max_results = self.max_results.clamp(1, 20);
+        self
+    }
+}
+
+fn parse_shortcut(shortcut: &str) -> Result<Shortcut, String> {
+    shortcut.parse::<Shortcut>().map_err(|e| format!("Invalid shortcut {}: {}", shortcut, e))
+}
+
+fn load(app: &AppHandle) -> Result<OverlayConfig, String> {
+    let state = app.state::<AppState>();
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    Ok(settings::load::<OverlayConfig>(&db, SETTINGS_KEY).sanitized())
+}
+
+// (Re)register the global shortcut from the saved config. Another application may own the
+// shortcut already, in which case the overlay is only reachable once it is changed.
+pub fn register_shortcut(app: &AppHandle) -> Result<(), String> {
+    let config = load(app)?;
+    let shortcuts = app.global_shortcut();
+    shortcuts.unregister_all().map_err(|e| e.to_string())?;
+    if config.enabled {
+        let shortcut = parse_shortcut(&config.shortcut)?;
+        shortcuts
+            .register(shortcut)
+            .map_err(|e| format!("Could not register {}: {}", config.shortcut, e))?;
+    }
+    Ok(())
+}
+
+// Change only the shortcut, as config.toml does
+pub fn set_shortcut(app: &AppHandle, shortcut: &str) -> Result<(), String> {
+    let mut config = load(app)?;
+    if config.shortcut == shortcut.trim() {
+        return Ok(());
+    }
+    config.shortcut = shortcut.to_string();
+    let config = config.sanitized();
+    parse_shortcut(&config.shortcut)?;
+    {
+        let state = app.state::<AppState>();
+        let db = state.db.lock().map_err(|e| e.to_string())?;
+        settings::save(&db, SETTINGS_KEY, &config).map_err(|e| e.message)?;
+    }
+    register_shortcut(app)
+}
+
+// Called by the global shortcut: show the overlay, or hide it if it is already up
+pub fn toggle(app: &AppHandle) {
+    let result = match app.get_webview_window(LABEL) {
+        Some(window) if window.is_visible().unwrap_or(false) => window.hide().map_err(|e| e.to_string()),
+        Some(window) => show(app, &window),
+        None => create(app).and_then(|window| show(app, &window)),
+    };
+    if let Err(e) = result {
+        warn!("Failed to toggle the overlay: {}", e);
+    }
+}
+
+fn create(app: &AppHandle) -> Result<WebviewWindow, String> {
+    let window = WebviewWindowBuilder::new(app, LABEL, WebviewUrl::App("overlay.html".into()))
+        .title("File Finder")
+        .inner_size(WIDTH, HEIGHT)
+        .decorations(false)
+        .always_on_top(true)
+        .skip_taskbar(true)
+        .resizable(false)
+        .visible(false)
+        .build()
+        .map_err(|e| e.to_string())?;
+
+    // Clicking anywhere else dismisses it, like a menu
+    let handle = window.clone();
+    window.on_window_event(move |event| {
+        if let WindowEvent::Focused(false) = event {
+            if let Err(e) = handle.hide() {
+                warn!("Failed to hide the overlay: {}", e);
+            }
+        }
+    });
+    Ok(window)
+}
+
+fn show(app: &AppHandle, window: &WebviewWindow) -> Result<(), String> {
+    place(app, window)?;
+    window.show().map_err(|e| e.to_string())?;
+    window.set_focus().map_err(|e| e.to_string())?;
+    app.emit_to(LABEL, SHOWN_EVENT, ()).map_err(|e| e.to_string())
+}
+
+// Centered horizontally, a fifth of the way down the monitor under the mouse
+fn place(app: &AppHandle, window: &WebviewWindow) -> Result<(), String> {
+    let monitor = app
+        .cursor_position()
+        .ok()
+        .and_then(|cursor| app.monitor_from_point(cursor.x, cursor.y).ok().flatten())
+        .or_else(|| app.primary_monitor().ok().flatten());
+    let Some(monitor) = monitor else {
+        return window.center().map_err(|e| e.to_string());
+    };
+    let size = window.outer_size().map_err(|e| e.to_string())?;
+    let area = monitor.size();
+    let origin = monitor.position();
+    let x = origin.x + (area.width as i32 - size.width as i32) / 2;
+    let y = origin.y + area.height as i32 / 5;
+    window.set_position(PhysicalPosition::new(x, y)).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn hide_overlay(app: AppHandle) -> Result<(), AppError> {
+    match app.get_webview_window(LABEL) {
+        Some(window) => Ok(window.hide()?),
+        None => Ok(()),
+    }
+}
+
+// Continue an overlay search in the main window, with all of its results
+#[tauri::command]
+#[specta::specta]
+pub async fn show_in_main_window(query: String, app: AppHandle) -> Result<(), AppError> {
+    hide_overlay(app.clone()).await?;
+    tray::show_search(&app, Some(query));
+    Ok(())
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn get_overlay_config(state: State<'_, AppState>) -> Result<OverlayConfig, AppError> {
+    db::write(&state.db, |db| Ok(settings::load::<OverlayConfig>(db, SETTINGS_KEY).sanitized()))
+}
+
+// Saves the config and registers the new shortcut right away
+#[tauri::command]
+#[specta::specta]
+pub async fn set_overlay_config(config: OverlayConfig, app: AppHandle, state: State<'_, AppState>) -> Result<OverlayConfig, AppError> {
+    let config = config.sanitized();
+    parse_shortcut(&config.shortcut)?;
+    db::write(&state.db, |db| settings::save(db, SETTINGS_KEY, &config))?;
+    db::blocking(|| register_shortcut(&app))?;
+    Ok(config)
+}
@@ -0,0 +1,249 @@
+// `@root` and `in:path` tokens that limit a search to part of the index: `@work report` only
+// searches the indexed root named "work", `in:src/ util` only files under a src folder. Relative
+// `in:` paths start at the active directory (set_active_directory, or the root indexed last).
+// Several scope tokens widen the scope rather than narrow it.
+
+use rusqlite::{Connection, Result as SqlResult};
+use std::path::{Path, MAIN_SEPARATOR};
+
+use crate::{escape_like, LIKE_ESCAPE};
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PathScope {
+    // Folders whose contents are searched, with the platform's separators
+    pub prefixes: Vec<String>,
+    // Relative folder paths matched anywhere, when no directory is active
+    pub segments: Vec<String>,
+}
+
+impl PathScope {
+    // Condition on `files.path`, without a leading AND, and the values for its placeholders
+    pub fn sql_clause(&self) -> (String, Vec<String>) {
+        let mut conditions = Vec::new();
+        let mut params = Vec::new();
+        for prefix in &self.prefixes {
+            if prefix.ends_with(MAIN_SEPARATOR) {
+                // A drive or filesystem root
+                conditions.push(format!("files.path LIKE ? {}", LIKE_ESCAPE));
+                params.push(format!("{}%", escape_like(prefix)));
+            } else {
+                conditions.push(format!("(files.path = ? OR files.path LIKE ? {})", LIKE_ESCAPE));
+                params.push(prefix.clone());
+                params.push(format!("{}{}%", escape_like(prefix), MAIN_SEPARATOR));
+            }
+        }
+        for segment in &self.segments {
+            conditions.push(format!("files.path LIKE ? {}", LIKE_ESCAPE));
+            params.push(format!("%{0}{1}{0}%", MAIN_SEPARATOR, escape_like(segment)));
+        }
+        (format!("({})", conditions.join(" OR ")), params)
+    }
+
+    // Everything under one folder
+    pub fn under(folder: &str) -> Self {
+        Self::under_any([folder])
+    }
+
+    // Everything under any of the folders
+    pub fn under_any<'a>(folders: impl IntoIterator<Item = &'a str>) -> Self {
+        Self { prefixes: folders.into_iter().map(normalize).collect(), segments: vec![] }
+    }
+
+    pub fn describe(&self) -> Vec<String> {
+        self.prefixes.iter().chain(&self.segments).map(|folder| format!("in: {}", folder)).collect()
+    }
+}
+
+struct Root {
+    path: String,
+    name: String,
+    is_active: bool,
+}
+
+fn indexed_roots(conn: &Connection) -> SqlResult<Vec<Root>> {
+    let mut stmt = conn.prepare("SELECT path, name, is_active FROM indexed_directories")?;
+    let roots = stmt
+        .query_map([], |row| {
+            Ok(Root {
+                path: row.get(0)?,
+                name: row.get(1)?,
+                is_active: row.get::<_, Option<i32>>(2)? == Some(1),
+            })
+        })?
+        .filter_map(|r| r.ok())
+        .collect();
+    Ok(roots)
+}
+
+// Either separator, as typed, becomes the platform's; trailing separators go except on roots
+fn normalize(path: &str) -> String {
+    let normalized: String = path.chars().map(|c| if c == '/' || c == '\\' { MAIN_SEPARATOR } else { c }).collect();
+    let trimmed = normalized.trim_end_matches(MAIN_SEPARATOR);
+    if trimmed.is_empty() || (trimmed.len() == 2 && trimmed.ends_with(':')) {
+        // "/" or "C:\"
+        format!("{}{}", trimmed, MAIN_SEPARATOR)
+    } else {
+        trimmed.to_string()
+    }
+}
+
+fn is_absolute(path: &str) -> bool {
+    // Drive paths count on every platform, so a Windows path in a shared query still reads as one
+    Path::new(path).is_absolute() || path.starts_with(MAIN_SEPARATOR) || path.as_bytes().get(1) == Some(&b':')
+}
+
+// The folder of an `in:` token
+fn in_folder(token: &str) -> Option<&str> {
+    let prefix = token.get(..3)?;
+    (prefix.eq_ignore_ascii_case("in:") && token.len() > 3).then(|| &token[3..])
+}
+
+fn root_name(token: &str) -> Option<&str> {
+    token.strip_prefix('@').filter(|name| !name.is_empty())
+}
+
+// Split `@root` and `in:path` tokens out of a query. `@name` tokens that name no indexed root
+// stay in the query, since `@` also appears in file names. `in:backups` is handled by mirrors.
+pub fn extract_path_scope(query: &str, conn: &Connection) -> Result<(String, Option<PathScope>), String> {
+    if !query.split_whitespace().any(|token| root_name(token).is_some() || in_folder(token).is_some()) {
+        return Ok((query.to_string(), None));
+    }
+    let roots = indexed_roots(conn).map_err(|e| e.to_string())?;
+    let active = roots.iter().find(|root| root.is_active);
+
+    let mut scope = PathScope::default();
+    let mut rest: Vec<&str> = Vec::new();
+    for token in query.split_whitespace() {
+        if let Some(name) = root_name(token) {
+            let matching: Vec<&Root> = roots
+                .iter()
+                .filter(|root| root.name.eq_ignore_ascii_case(name) || normalize(&root.path).eq_ignore_ascii_case(&normalize(name)))
+                .collect();
+            if matching.is_empty() {
+                rest.push(token);
+            }
+            scope.prefixes.extend(matching.into_iter().map(|root| normalize(&root.path)));
+            continue;
+        }
+        let Some(folder) = in_folder(token) else {
+            rest.push(token);
+            continue;
+        };
+        if is_absolute(folder) {
+            scope.prefixes.push(normalize(folder));
+        } else {
+            let relative = normalize(folder.trim_start_matches("./"));
+            match active {
+                Some(root) => scope.prefixes.push(normalize(&Path::new(&root.path).join(&relative).to_string_lossy())),
+                None => scope.segments.push(relative),
+            }
+        }
+    }
+
+    if scope == PathScope::default() {
+        return Ok((query.to_string(), None));
+    }
+    Ok((rest.join(" "), Some(scope)))
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    fn roots() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            "CREATE TABLE indexed_directories (path TEXT, name TEXT, is_active INTEGER);
+             INSERT INTO indexed_directories VALUES ('/home/me/work', 'work', 1), ('/home/me/play/', 'play', 0);",
+        )
+        .unwrap();
+        conn
+    }
+
+    fn scope(prefixes: &[&str], segments: &[&str]) -> Option<PathScope> {
+        Some(PathScope {
+            prefixes: prefixes.iter().map(|prefix| prefix.to_string()).collect(),
+            segments: segments.iter().map(|segment| segment.to_string()).collect(),
+        })
+    }
+
+    // Paths of `files` that the scope's condition keeps
+    fn kept(scope: &PathScope, files: &[&str]) -> Vec<String> {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute("CREATE TABLE files (path TEXT)", []).unwrap();
+        for path in files {
+            conn.execute("INSERT INTO files (path) VALUES (?1)", [path]).unwrap();
+        }
+        let (clause, params) = scope.sql_clause();
+        let mut stmt = conn.prepare(&format!("SELECT path FROM files WHERE {} ORDER BY path", clause)).unwrap();
+        let rows = stmt.query_map(rusqlite::params_from_iter(params), |row| row.get(0)).unwrap();
+        rows.collect::<SqlResult<_>>().unwrap()
+    }
+
+    #[test]
+    fn folders_are_normalized() {
+        assert_eq!(normalize("/home/me/"), "/home/me");
+        assert_eq!(normalize("docs\\reports\\"), "docs/reports");
+        assert_eq!(normalize("/"), "/");
+        assert_eq!(normalize("C:\\"), "C:/");
+    }
+
+    #[test]
+    fn paths_are_bound_not_pasted() {
+        let (clause, params) = PathScope::under("/home/me/it's").sql_clause();
+        assert_eq!(clause, format!("((files.path = ? OR files.path LIKE ? {}))", LIKE_ESCAPE));
+        assert_eq!(params, vec!["/home/me/it's", "/home/me/it's/%"]);
+    }
+
+    #[test]
+    fn a_folder_keeps_itself_and_its_contents_only() {
+        let files = ["/foo", "/foo/a.txt", "/foo/sub/b.txt", "/foobar/c.txt", "/other/foo/d.txt"];
+        assert_eq!(kept(&PathScope::under("/foo/"), &files), vec!["/foo", "/foo/a.txt", "/foo/sub/b.txt"]);
+        assert_eq!(kept(&PathScope::under("/"), &files).len(), files.len());
+    }
+
+    #[test]
+    fn like_wildcards_and_quotes_in_folders_are_literal() {
+        let files = ["/data/100%_done/x", "/data/100 and_done/y", "/data/it's/z", "/data/its/w"];
+        assert_eq!(kept(&PathScope::under("/data/100%_done"), &files), vec!["/data/100%_done/x"]);
+        assert_eq!(kept(&PathScope::under("/data/it's"), &files), vec!["/data/it's/z"]);
+    }
+
+    #[test]
+    fn segments_match_whole_folders_anywhere() {
+        let segments = PathScope { prefixes: vec![], segments: vec!["src".to_string()] };
+        let files = ["/a/src/main.rs", "/b/c/src/lib.rs", "/a/srcx/main.rs", "/a/src.rs"];
+        assert_eq!(kept(&segments, &files), vec!["/a/src/main.rs", "/b/c/src/lib.rs"]);
+    }
+
+    #[test]
+    fn root_names_scope_the_query() {
+        let conn = roots();
+        assert_eq!(extract_path_scope("@work report", &conn).unwrap(), ("report".to_string(), scope(&["/home/me/work"], &[])));
+        assert_eq!(
+            extract_path_scope("@WORK @play report", &conn).unwrap(),
+            ("report".to_string(), scope(&["/home/me/work", "/home/me/play"], &[]))
+        );
+        // Names of no root stay in the query, since `@` also appears in file names
+        assert_eq!(extract_path_scope("@nobody report", &conn).unwrap(), ("@nobody report".to_string(), None));
+    }
+
+    #[test]
+    fn in_folders_scope_the_query() {
+        let conn = roots();
+        assert_eq!(extract_path_scope("IN:/tmp/x/ notes", &conn).unwrap(), ("notes".to_string(), scope(&["/tmp/x"], &[])));
+        // Relative folders start at the active root
+        assert_eq!(
+            extract_path_scope("in:./src/ util", &conn).unwrap(),
+            ("util".to_string(), scope(&["/home/me/work/src"], &[]))
+        );
+        assert_eq!(extract_path_scope("plain query", &conn).unwrap(), ("plain query".to_string(), None));
+    }
+
+    #[test]
+    fn relative_folders_without_an_active_root_match_anywhere() {
+        let conn = roots();
+        conn.execute("UPDATE indexed_directories SET is_active = 0", []).unwrap();
+        assert_eq!(extract_path_scope("in:src util", &conn).unwrap(), ("util".to_string(), scope(&[], &["src"])));
+    }
+}
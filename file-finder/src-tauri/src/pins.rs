@@ -0,0 +1,167 @@
+// Results pinned to the top for a query: pinning `~/notes/notes.md` for "notes" puts it first
+// for "notes", "notes 2024" and any other query starting with "notes", whatever the scores
+// say. A pinned file that the query didn't match is added, as long as it is indexed and passes
+// the query's filters. Longer prefixes go first when several pins apply.
+
+use rusqlite::types::Value;
+use rusqlite::{params, params_from_iter, Connection, OptionalExtension, Result as SqlResult};
+use serde::Serialize;
+use specta::Type;
+use tauri::State;
+
+use crate::error::{AppError, ErrorCode};
+use crate::search_sql::SqlFilter;
+use crate::{db, invalidate_search_cache, AppState, FileEntry};
+
+#[derive(Debug, Serialize, Clone, Type)]
+pub struct PinnedResult {
+    pub query_prefix: String,
+    pub path: String,
+    pub pinned_at: i64,
+}
+
+pub fn create_table(conn: &Connection) -> SqlResult<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS pinned_results (
+            id INTEGER PRIMARY KEY,
+            query_prefix TEXT NOT NULL,
+            path TEXT NOT NULL,
+            pinned_at INTEGER NOT NULL,
+            UNIQUE (query_prefix, path)
+        )",
+        [],
+    )?;
+    Ok(())
+}
+
+// Lowercase with single spaces, so "Notes  2024" and "notes 2024" pin alike
+fn normalize(query: &str) -> String {
+    query.split_whitespace().collect::<Vec<_>>().join(" ").to_lowercase()
+}
+
+fn list(conn: &Connection) -> SqlResult<Vec<PinnedResult>> {
+    let mut stmt = conn.prepare(
+        "SELECT query_prefix, path, pinned_at FROM pinned_results ORDER BY length(query_prefix) DESC, pinned_at",
+    )?;
+    let pins = stmt
+        .query_map([], |row| {
+            Ok(PinnedResult {
+                query_prefix: row.get(0)?,
+                path: row.get(1)?,
+                pinned_at: row.get(2)?,
+            })
+        })?
+        .filter_map(|r| r.ok())
+        .collect();
+    Ok(pins)
+}
+
+// The prefix `path` is pinned under that applies to `query`, if any
+pub fn pin_for(conn: &Connection, query: &str, path: &str) -> SqlResult<Option<String>> {
+    let query = normalize(query);
+    Ok(list(conn)?
+        .into_iter()
+        .find(|pin| pin.path == path && query.starts_with(&pin.query_prefix))
+        .map(|pin| pin.query_prefix))
+}
+
+// Move the files pinned for `query` to the front of `results`. `query` is the search as typed,
+// filter tokens included, and `filter` its filters, as ranked_search builds them.
+pub fn apply(conn: &Connection, query: &str, filter: &SqlFilter, results: Vec<FileEntry>) -> Result<Vec<FileEntry>, String> {
+    let query = normalize(query);
+    if query.is_empty() {
+        return Ok(results);
+    }
+    let pins: Vec<PinnedResult> = list(conn)
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .filter(|pin| query.starts_with(&pin.query_prefix))
+        .collect();
+    if pins.is_empty() {
+        return Ok(results);
+    }
+
+    let mut rest = results;
+    let mut pinned: Vec<FileEntry> = Vec::with_capacity(pins.len());
+    for pin in pins {
+        if pinned.iter().any(|entry| entry.path == pin.path) {
+            continue;
+        }
+        if let Some(position) = rest.iter().position(|entry| entry.path == pin.path) {
+            pinned.push(rest.remove(position));
+            continue;
+        }
+        let entry = conn
+            .query_row(
+                &format!("SELECT path, name, modified_at FROM files WHERE path = ?1{}", filter.sql),
+                params_from_iter(filter.bind(vec![Value::Text(pin.path.clone())], vec![])),
+                |row| {
+                    Ok(FileEntry {
+                        path: row.get(0)?,
+                        name: row.get(1)?,
+                        last_accessed: None,
+                        access_count: 0,
+                        modified_at: row.get(2)?,
+                        age_bucket: None,
+                        in_backup: false,
+                        match_source: None,
+                        content_snippet: None,
+                    })
+                },
+            )
+            .optional()
+            .map_err(|e| e.to_string())?;
+        pinned.extend(entry);
+    }
+    pinned.extend(rest);
+    Ok(pinned)
+}
+
+// Pin `path` for `query` and everything that starts with it
+#[tauri::command]
+#[specta::specta]
+pub async fn pin_result(query: String, path: String, state: State<'_, AppState>) -> Result<PinnedResult, AppError> {
+    let query_prefix = normalize(&query);
+    if query_prefix.is_empty() {
+        return Err(AppError::new(ErrorCode::InvalidInput, "Type a query to pin a result for"));
+    }
+    let pinned_at = chrono::Utc::now().timestamp();
+    db::write(&state.db, |db| {
+        db.execute(
+            "INSERT INTO pinned_results (query_prefix, path, pinned_at) VALUES (?1, ?2, ?3)
+             ON CONFLICT(query_prefix, path) DO UPDATE SET pinned_at = ?3",
+            params![query_prefix, path, pinned_at],
+        )?;
+        Ok(())
+    })?;
+    invalidate_search_cache(&state);
+    Ok(PinnedResult { query_prefix, path, pinned_at })
+}
+
+// Unpin `path` from the pins that apply to `query`; returns how many were removed
+#[tauri::command]
+#[specta::specta]
+pub async fn unpin_result(query: String, path: String, state: State<'_, AppState>) -> Result<usize, AppError> {
+    let query = normalize(&query);
+    let removed = db::write(&state.db, |db| {
+        let prefixes: Vec<String> = list(db)?
+            .into_iter()
+            .filter(|pin| pin.path == path && query.starts_with(&pin.query_prefix))
+            .map(|pin| pin.query_prefix)
+            .collect();
+        let mut removed = 0;
+        for prefix in prefixes {
+            removed += db
+                .execute("DELETE FROM pinned_results WHERE query_prefix = ?1 AND path = ?2", params![prefix, path])?;
+        }
+        Ok(removed)
+    })?;
+    invalidate_search_cache(&state);
+    Ok(removed)
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn list_pinned_results(state: State<'_, AppState>) -> Result<Vec<PinnedResult>, AppError> {
+    db::write(&state.db, |db| list(db).map_err(AppError::from))
+}
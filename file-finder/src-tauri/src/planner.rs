@@ -0,0 +1,281 @@
+// Time-budgeted candidate collection for search_files. Instead of fixed row LIMITs, a search
+// keeps pulling candidates until it has enough to answer well or runs out of time, and reports
+// when it stopped early so callers know the results are partial. A watchdog cancels plans that
+// overrun the budget anyway and falls back to a cheap plan.
+
+use rusqlite::{Connection, Params, Row};
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{mpsc, Arc};
+use std::time::{Duration, Instant};
+use tauri::State;
+use tracing::warn;
+
+use crate::error::{AppError, ErrorCode};
+use crate::{db, settings, AppState};
+
+pub const SETTINGS_KEY: &str = "search_budget";
+
+// Candidates that are plenty to fill the result list once the first batch time has passed
+const FIRST_BATCH_CANDIDATES: usize = 500;
+
+// SQLite VM instructions between deadline checks while a statement runs
+const PROGRESS_CHECK_OPS: i32 = 10_000;
+
+// Slack past the total budget before the watchdog gives up on a plan
+const WATCHDOG_GRACE: Duration = Duration::from_millis(100);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SearchPlan {
+    // Every strategy, within the configured budget
+    Full,
+    // Watchdog fallback: small candidate set and budget, no fuzzy matching
+    Cheap,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Type)]
+#[serde(default)]
+pub struct SearchBudget {
+    // After this long, stop as soon as there are enough candidates to fill the results
+    pub first_batch_ms: u64,
+    // Hard limit for one search; whatever was found by then is returned
+    pub total_ms: u64,
+    // Upper bound on candidates collected for scoring
+    pub max_candidates: usize,
+    // How long search_files waits for a newer keystroke before running (see coalesce.rs)
+    pub debounce_ms: u64,
+}
+
+impl Default for SearchBudget {
+    fn default() -> Self {
+        Self {
+            first_batch_ms: 50,
+            total_ms: 500,
+            max_candidates: 20000,
+            debounce_ms: 30,
+        }
+    }
+}
+
+impl SearchBudget {
+    fn cheap() -> Self {
+        Self {
+            first_batch_ms: 20,
+            total_ms: 150,
+            max_candidates: 1000,
+            ..Self::default()
+        }
+    }
+
+    pub fn sanitized(mut self) -> Self {
+        self.total_ms = self.total_ms.clamp(10, 60_000);
+        self.first_batch_ms = self.first_batch_ms.min(self.total_ms);
+        self.max_candidates = self.max_candidates.clamp(100, 1_000_000);
+        self.debounce_ms = self.debounce_ms.min(1_000);
+        self
+    }
+}
+
+// Where a plan's time went, for the performance stats
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PlanStats {
+    // Rows read from SQLite, whether or not they were kept
+    pub rows_scanned: usize,
+    // Time spent inside `query`
+    pub sql_time: Duration,
+    // Rows kept as candidates for scoring
+    pub candidates: usize,
+    // Where the candidates came from (see search_sql::Candidates::label)
+    pub candidate_source: Option<&'static str>,
+    // Looking names up in the name index
+    pub index_lookup_time: Duration,
+    // Matching and scoring the candidates, fuzzy matching included
+    pub scoring_time: Duration,
+    pub fuzzy_time: Duration,
+    pub sort_time: Duration,
+}
+
+impl PlanStats {
+    pub fn add(&mut self, other: &PlanStats) {
+        self.rows_scanned += other.rows_scanned;
+        self.sql_time += other.sql_time;
+        self.candidates += other.candidates;
+        self.candidate_source = self.candidate_source.or(other.candidate_source);
+        self.index_lookup_time += other.index_lookup_time;
+        self.scoring_time += other.scoring_time;
+        self.fuzzy_time += other.fuzzy_time;
+        self.sort_time += other.sort_time;
+    }
+}
+
+pub struct SearchPlanner {
+    started: Instant,
+    budget: SearchBudget,
+    plan: SearchPlan,
+    // Set by the watchdog when it has given up on this plan
+    cancel: Arc<AtomicBool>,
+    // Set once any collection stopped before exhausting its rows
+    pub truncated: bool,
+    pub stats: PlanStats,
+}
+
+impl SearchPlanner {
+    fn new(budget: SearchBudget, plan: SearchPlan, cancel: Arc<AtomicBool>) -> Self {
+        Self {
+            started: Instant::now(),
+            budget,
+            plan,
+            cancel,
+            truncated: false,
+            stats: PlanStats::default(),
+        }
+    }
+
+    pub fn cancelled(&self) -> bool {
+        self.cancel.load(Ordering::Relaxed)
+    }
+
+    // Fuzzy fallbacks are the first thing dropped when a search is in trouble
+    pub fn allows_fuzzy(&self) -> bool {
+        self.plan == SearchPlan::Full && !self.cancelled()
+    }
+
+    pub fn elapsed(&self) -> Duration {
+        self.started.elapsed()
+    }
+
+    pub fn total(&self) -> Duration {
+        Duration::from_millis(self.budget.total_ms)
+    }
+
+    pub fn out_of_time(&self) -> bool {
+        self.elapsed() >= self.total() || self.cancelled()
+    }
+
+    // Whether to keep pulling candidates given how many have been kept so far
+    fn should_continue(&mut self, collected: usize) -> bool {
+        let elapsed = self.elapsed();
+        let stop = collected >= self.budget.max_candidates
+            || elapsed >= self.total()
+            || self.cancelled()
+            || (elapsed >= Duration::from_millis(self.budget.first_batch_ms) && collected >= FIRST_BATCH_CANDIDATES);
+        if stop {
+            self.truncated = true;
+        }
+        !stop
+    }
+
+    // Run a query and keep the mapped rows that pass `keep`, within the budget.
+    // The deadline is also enforced inside SQLite, so a long scan between two rows is interrupted.
+    pub fn query<T, P, F, K>(&mut self, conn: &Connection, sql: &str, params: P, map: F, keep: K) -> Result<Vec<T>, String>
+    where
+        P: Params,
+        F: FnMut(&Row<'_>) -> rusqlite::Result<T>,
+        K: Fn(&T) -> bool,
+    {
+        if self.out_of_time() {
+            self.truncated = true;
+            return Ok(vec![]);
+        }
+
+        let deadline = self.started + self.total();
+        let cancel = self.cancel.clone();
+        conn.progress_handler(
+            PROGRESS_CHECK_OPS,
+            Some(move || Instant::now() >= deadline || cancel.load(Ordering::Relaxed)),
+        );
+        let started = Instant::now();
+        let collected = self.collect_rows(conn, sql, params, map, keep);
+        self.stats.sql_time += started.elapsed();
+        conn.progress_handler(0, None::<fn() -> bool>);
+        collected
+    }
+
+    fn collect_rows<T, P, F, K>(&mut self, conn: &Connection, sql: &str, params: P, map: F, keep: K) -> Result<Vec<T>, String>
+    where
+        P: Params,
+        F: FnMut(&Row<'_>) -> rusqlite::Result<T>,
+        K: Fn(&T) -> bool,
+    {
+        let mut stmt = conn.prepare_cached(sql).map_err(|e| e.to_string())?;
+        let rows = stmt.query_map(params, map).map_err(|e| e.to_string())?;
+
+        let mut collected = Vec::new();
+        for row in rows {
+            self.stats.rows_scanned += 1;
+            match row {
+                Ok(row) => {
+                    if keep(&row) {
+                        collected.push(row);
+                    }
+                }
+                Err(rusqlite::Error::SqliteFailure(e, _)) if e.code == rusqlite::ErrorCode::OperationInterrupted => {
+                    self.truncated = true;
+                    break;
+                }
+                Err(_) => continue,
+            }
+            if !self.should_continue(collected.len()) {
+                break;
+            }
+        }
+        Ok(collected)
+    }
+}
+
+// Run `search` with the full plan on a worker thread. If it has not answered shortly after the
+// budget, cancel it and run the cheap plan instead; the flag says whether that happened.
+pub fn run_watched<T, F>(budget: &SearchBudget, search: F) -> Result<(T, bool), String>
+where
+    T: Send,
+    F: Fn(&mut SearchPlanner) -> Result<T, String> + Sync,
+{
+    let cancel = Arc::new(AtomicBool::new(false));
+    let timeout = Duration::from_millis(budget.total_ms) + WATCHDOG_GRACE;
+
+    std::thread::scope(|scope| {
+        let (sender, receiver) = mpsc::channel();
+        let full_cancel = cancel.clone();
+        let search = &search;
+        scope.spawn(move || {
+            let mut planner = SearchPlanner::new(budget.clone(), SearchPlan::Full, full_cancel);
+            let _ = sender.send(search(&mut planner));
+        });
+
+        match receiver.recv_timeout(timeout) {
+            Ok(result) => result.map(|results| (results, false)),
+            Err(_) => {
+                // The abandoned plan sees the flag at its next check and winds down while the cheap plan runs
+                cancel.store(true, Ordering::Relaxed);
+                warn!("WATCHDOG: search exceeded {}ms, rerunning with the cheap plan", timeout.as_millis());
+                let mut planner = SearchPlanner::new(SearchBudget::cheap(), SearchPlan::Cheap, Arc::new(AtomicBool::new(false)));
+                search(&mut planner).map(|results| (results, true))
+            }
+        }
+    })
+}
+
+pub fn load(conn: &Connection) -> SearchBudget {
+    settings::load::<SearchBudget>(conn, SETTINGS_KEY).sanitized()
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn get_search_budget(state: State<'_, AppState>) -> Result<SearchBudget, AppError> {
+    let budget = state.search_budget.read().map_err(|e| e.to_string())?;
+    Ok(budget.clone())
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn set_search_budget(budget: SearchBudget, state: State<'_, AppState>) -> Result<SearchBudget, AppError> {
+    let budget = budget.sanitized();
+    db::write(&state.db, |db| settings::save(db, SETTINGS_KEY, &budget))?;
+    *state.search_budget.write().map_err(|e| e.to_string())? = budget.clone();
+
+    // Cached results may have been cut short by the old budget
+    state.search_cache.write().map_err(|e| e.to_string())?.clear();
+
+    Ok(budget)
+}
@@ -0,0 +1,448 @@
+// Quick previews of files without opening them in another program. Text is read from the start
+// of the file with its encoding detected: a byte-order mark first, then the zero-byte pattern of
+// UTF-16 without one, then UTF-8 validity; anything else is left to chardetng, which tells the
+// legacy encodings apart (Windows-1252, Shift_JIS, GBK, KOI8-R and the like) by their byte
+// statistics. Binary files are refused rather than shown as noise.
+//
+// Documents get their text instead: the start of a PDF, a Word document, the first sheet of a
+// workbook or the first slide of a presentation.
+
+use chardetng::EncodingDetector;
+use encoding_rs::{Encoding, UTF_16BE, UTF_16LE, UTF_8};
+use serde::Serialize;
+use specta::Type;
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+
+use crate::error::{AppError, ErrorCode};
+use crate::text_extract;
+use crate::zip::ZipArchive;
+
+const DEFAULT_PREVIEW_BYTES: usize = 64 * 1024;
+const MAX_PREVIEW_BYTES: usize = 1024 * 1024;
+
+// Share of control characters (besides tab, newline, carriage return and form feed) above which
+// a file counts as binary
+const MAX_CONTROL_RATIO: f32 = 0.05;
+
+const DEFAULT_DOCUMENT_CHARS: usize = 4000;
+const MAX_DOCUMENT_CHARS: usize = 100_000;
+
+// Largest XML part read from an Office document
+const MAX_PART_BYTES: u64 = 16 * 1024 * 1024;
+
+#[derive(Debug, Serialize, Clone, Type)]
+pub struct TextPreview {
+    pub path: String,
+    // The encoding's name in lowercase, e.g. "utf-8", "utf-16le", "windows-1252" or "shift_jis"
+    pub encoding: String,
+    pub text: String,
+    // Where each line of `text` starts, in UTF-16 code units so JavaScript can slice with them
+    pub line_offsets: Vec<u32>,
+    // The file continues past the preview
+    pub truncated: bool,
+    pub file_size: u64,
+}
+
+// The encoding and the length of its byte-order mark. `complete` is false when the read limit cut
+// the file short, so a character split at the end doesn't rule an encoding out.
+fn detect(bytes: &[u8], complete: bool) -> (&'static Encoding, usize) {
+    if let Some(found) = Encoding::for_bom(bytes) {
+        return found;
+    }
+    // Mostly-ASCII UTF-16 has a zero in every other byte
+    let sample = &bytes[..bytes.len().min(4096) & !1];
+    if sample.len() >= 4 {
+        let pairs = sample.len() / 2;
+        let zero_odd = sample.chunks_exact(2).filter(|pair| pair[1] == 0 && pair[0] != 0).count();
+        let zero_even = sample.chunks_exact(2).filter(|pair| pair[0] == 0 && pair[1] != 0).count();
+        if zero_odd * 10 >= pairs * 7 {
+            return (UTF_16LE, 0);
+        }
+        if zero_even * 10 >= pairs * 7 {
+            return (UTF_16BE, 0);
+        }
+    }
+    match std::str::from_utf8(bytes) {
+        Ok(_) => (UTF_8, 0),
+        // A character cut off by the read limit still counts as UTF-8
+        Err(e) if e.error_len().is_none() => (UTF_8, 0),
+        Err(_) => {
+            let mut detector = EncodingDetector::new();
+            detector.feed(bytes, complete);
+            (detector.guess(None, false), 0)
+        }
+    }
+}
+
+fn decode(bytes: &[u8], encoding: &'static Encoding) -> String {
+    let (text, _) = encoding.decode_without_bom_handling(bytes);
+    // A character cut off by the read limit decodes as a replacement character
+    text.trim_end_matches('\u{FFFD}').to_string()
+}
+
+fn looks_binary(text: &str) -> bool {
+    let mut total = 0;
+    let mut control = 0;
+    for c in text.chars() {
+        total += 1;
+        if c == '\0' {
+            return true;
+        }
+        if (c as u32) < 0x20 && !matches!(c, '\t' | '\n' | '\r' | '\u{c}') {
+            control += 1;
+        }
+    }
+    total > 0 && control as f32 / total as f32 > MAX_CONTROL_RATIO
+}
+
+fn line_offsets(text: &str) -> Vec<u32> {
+    let mut offsets = vec![0];
+    let mut position = 0u32;
+    for c in text.chars() {
+        position += c.len_utf16() as u32;
+        if c == '\n' {
+            offsets.push(position);
+        }
+    }
+    // A trailing newline doesn't start another line
+    if text.ends_with('\n') {
+        offsets.pop();
+    }
+    offsets
+}
+
+// The start of a text file, decoded, with where each line begins. `max_bytes` defaults to 64 KB
+// and is capped at 1 MB.
+#[tauri::command]
+#[specta::specta]
+pub async fn preview_text(path: String, max_bytes: Option<u32>) -> Result<TextPreview, AppError> {
+    let file_path = Path::new(&path);
+    if file_path.is_dir() {
+        return Err(AppError::new(ErrorCode::InvalidInput, format!("{} is a folder", path)));
+    }
+    let file = File::open(file_path).map_err(|e| format!("Could not read {}: {}", path, e))?;
+    let file_size = file.metadata()?.len();
+    let limit = max_bytes.map_or(DEFAULT_PREVIEW_BYTES, |n| n as usize).clamp(1, MAX_PREVIEW_BYTES);
+    let mut bytes = Vec::with_capacity(limit);
+    file.take(limit as u64).read_to_end(&mut bytes).map_err(|e| format!("Could not read {}: {}", path, e))?;
+
+    let truncated = file_size > bytes.len() as u64;
+    let (encoding, bom) = detect(&bytes, !truncated);
+    let text = decode(&bytes[bom..], encoding);
+    if looks_binary(&text) {
+        return Err(AppError::new(ErrorCode::InvalidInput, format!("{} is not a text file", path)));
+    }
+    Ok(TextPreview {
+        path,
+        encoding: encoding.name().to_lowercase(),
+        line_offsets: line_offsets(&text),
+        text,
+        truncated,
+        file_size,
+    })
+}
+
+#[derive(Debug, Serialize, Clone, Type)]
+pub struct DocumentPreview {
+    pub path: String,
+    // "pdf", "docx", "xlsx" or "pptx"
+    pub kind: String,
+    // Which part the text is from: the sheet name or "Slide 1"; None for the start of a document
+    pub section: Option<String>,
+    pub text: String,
+    pub truncated: bool,
+}
+
+// The value of `name="..."` in a tag's attribute text
+fn attribute<'a>(attributes: &'a str, name: &str) -> Option<&'a str> {
+    let mut rest = attributes;
+    while let Some(at) = rest.find(name) {
+        let before = rest[..at].chars().next_back();
+        let after = rest[at + name.len()..].trim_start();
+        if before.is_none_or(char::is_whitespace) {
+            if let Some(value) = after.strip_prefix('=') {
+                let value = value.trim_start();
+                let quote = value.chars().next()?;
+                let value = &value[1..];
+                return value.find(quote).map(|end| &value[..end]);
+            }
+        }
+        rest = &rest[at + name.len()..];
+    }
+    None
+}
+
+fn unescape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(at) = rest.find('&') {
+        out.push_str(&rest[..at]);
+        rest = &rest[at..];
+        let Some(end) = rest.find(';').filter(|&end| end <= 10) else {
+            out.push('&');
+            rest = &rest[1..];
+            continue;
+        };
+        let entity = &rest[1..end];
+        let decoded = match entity {
+            "amp" => Some('&'),
+            "lt" => Some('<'),
+            "gt" => Some('>'),
+            "quot" => Some('"'),
+            "apos" => Some('\''),
+            _ => entity
+                .strip_prefix("#x")
+                .map(|hex| u32::from_str_radix(hex, 16))
+                .or_else(|| entity.strip_prefix('#').map(|dec| dec.parse()))
+                .and_then(|code| code.ok())
+                .and_then(char::from_u32),
+        };
+        match decoded {
+            Some(c) => {
+                out.push(c);
+                rest = &rest[end + 1..];
+            }
+            None => {
+                out.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+enum XmlEvent<'a> {
+    // Tag name with any namespace prefix, its attribute text, and whether it closes itself
+    Start(&'a str, &'a str, bool),
+    End(&'a str),
+    Text(&'a str),
+}
+
+// Tags and text of an XML document in order; comments, declarations and CDATA markers are skipped
+fn xml_events(xml: &str, mut visit: impl FnMut(XmlEvent) -> bool) {
+    let mut rest = xml;
+    while !rest.is_empty() {
+        let Some(open) = rest.find('<') else {
+            visit(XmlEvent::Text(rest));
+            return;
+        };
+        if open > 0 && !visit(XmlEvent::Text(&rest[..open])) {
+            return;
+        }
+        let Some(close) = rest[open..].find('>') else {
+            return;
+        };
+        let tag = &rest[open + 1..open + close];
+        rest = &rest[open + close + 1..];
+        if let Some(name) = tag.strip_prefix('/') {
+            if !visit(XmlEvent::End(name.trim())) {
+                return;
+            }
+        } else if !tag.starts_with('?') && !tag.starts_with('!') {
+            let self_closing = tag.ends_with('/');
+            let tag = tag.trim_end_matches('/');
+            let (name, attributes) = tag.split_once(char::is_whitespace).unwrap_or((tag, ""));
+            if !visit(XmlEvent::Start(name, attributes, self_closing)) {
+                return;
+            }
+        }
+    }
+}
+
+// Text of a WordprocessingML or DrawingML part: runs of `text_tag`, a line per `paragraph_tag`
+fn paragraph_text(xml: &str, text_tag: &str, paragraph_tag: &str, max_chars: usize) -> String {
+    let mut out = String::new();
+    let mut in_text = false;
+    xml_events(xml, |event| {
+        match event {
+            XmlEvent::Start(name, _, self_closing) => {
+                in_text = name == text_tag && !self_closing;
+                match name {
+                    "w:tab" => out.push('\t'),
+                    "w:br" | "w:cr" | "a:br" => out.push('\n'),
+                    _ => {}
+                }
+            }
+            XmlEvent::End(name) => {
+                in_text = false;
+                if name == paragraph_tag {
+                    out.push('\n');
+                }
+            }
+            XmlEvent::Text(text) if in_text => out.push_str(&unescape(text)),
+            XmlEvent::Text(_) => {}
+        }
+        out.len() <= max_chars * 4
+    });
+    out
+}
+
+// Target of the relationship `id` in a .rels part, relative to the package root
+fn relationship_target(rels: &str, id: &str, base: &str) -> Option<String> {
+    let mut target = None;
+    xml_events(rels, |event| {
+        if let XmlEvent::Start("Relationship", attributes, _) = event {
+            if attribute(attributes, "Id") == Some(id) {
+                target = attribute(attributes, "Target").map(|t| t.to_string());
+                return false;
+            }
+        }
+        true
+    });
+    let target = target?;
+    Some(match target.strip_prefix('/') {
+        Some(absolute) => absolute.to_string(),
+        None => format!("{}{}", base, target),
+    })
+}
+
+fn read_part(archive: &mut ZipArchive, name: &str) -> Result<String, String> {
+    let bytes = archive.read(name, MAX_PART_BYTES)?;
+    Ok(String::from_utf8_lossy(&bytes).to_string())
+}
+
+// The first entry of a list in a main part (sheets, slides), with its name attribute, resolved to a part
+fn first_listed_part(
+    archive: &mut ZipArchive,
+    main: &str,
+    rels: &str,
+    base: &str,
+    list_tag: &str,
+) -> Result<(String, Option<String>), String> {
+    let main_xml = read_part(archive, main)?;
+    let mut first = None;
+    xml_events(&main_xml, |event| {
+        if let XmlEvent::Start(name, attributes, _) = event {
+            if name == list_tag {
+                first = Some((attribute(attributes, "r:id").map(|id| id.to_string()), attribute(attributes, "name").map(unescape)));
+                return false;
+            }
+        }
+        true
+    });
+    let (Some(id), label) = first.ok_or("The document is empty")? else {
+        return Err("The document has no parts to show".to_string());
+    };
+    let rels_xml = read_part(archive, rels)?;
+    let part = relationship_target(&rels_xml, &id, base).ok_or("Damaged document relationships")?;
+    Ok((part, label))
+}
+
+fn docx_text(archive: &mut ZipArchive, max_chars: usize) -> Result<(Option<String>, String), String> {
+    let xml = read_part(archive, "word/document.xml")?;
+    Ok((None, paragraph_text(&xml, "w:t", "w:p", max_chars)))
+}
+
+fn pptx_text(archive: &mut ZipArchive, max_chars: usize) -> Result<(Option<String>, String), String> {
+    let (slide, _) = first_listed_part(archive, "ppt/presentation.xml", "ppt/_rels/presentation.xml.rels", "ppt/", "p:sldId")?;
+    let xml = read_part(archive, &slide)?;
+    Ok((Some("Slide 1".to_string()), paragraph_text(&xml, "a:t", "a:p", max_chars)))
+}
+
+// The first sheet as tab-separated rows. Shared strings are looked up; formulas show their cached values.
+fn xlsx_text(archive: &mut ZipArchive, max_chars: usize) -> Result<(Option<String>, String), String> {
+    let (sheet, name) = first_listed_part(archive, "xl/workbook.xml", "xl/_rels/workbook.xml.rels", "xl/", "sheet")?;
+
+    let mut shared = Vec::new();
+    if let Ok(xml) = read_part(archive, "xl/sharedStrings.xml") {
+        let mut current = String::new();
+        let mut in_text = false;
+        xml_events(&xml, |event| {
+            match event {
+                XmlEvent::Start("si", _, _) => current.clear(),
+                XmlEvent::Start(name, _, self_closing) => in_text = name == "t" && !self_closing,
+                XmlEvent::End("si") => shared.push(std::mem::take(&mut current)),
+                XmlEvent::End(_) => in_text = false,
+                XmlEvent::Text(text) if in_text => current.push_str(&unescape(text)),
+                XmlEvent::Text(_) => {}
+            }
+            true
+        });
+    }
+
+    let xml = read_part(archive, &sheet)?;
+    let mut out = String::new();
+    let mut cell_type = String::new();
+    let mut row: Vec<String> = Vec::new();
+    let mut in_value = false;
+    xml_events(&xml, |event| {
+        match event {
+            XmlEvent::Start("c", attributes, _) => {
+                cell_type = attribute(attributes, "t").unwrap_or("n").to_string();
+                row.push(String::new());
+            }
+            XmlEvent::Start(name, _, self_closing) => in_value = matches!(name, "v" | "t") && !self_closing,
+            XmlEvent::End("row") => {
+                while row.last().is_some_and(|cell| cell.is_empty()) {
+                    row.pop();
+                }
+                out.push_str(&row.join("\t"));
+                out.push('\n');
+                row.clear();
+            }
+            XmlEvent::End(_) => in_value = false,
+            XmlEvent::Text(text) if in_value => {
+                let value = unescape(text);
+                let value = match cell_type.as_str() {
+                    "s" => value.trim().parse::<usize>().ok().and_then(|i| shared.get(i).cloned()).unwrap_or_default(),
+                    "b" => if value == "1" { "TRUE" } else { "FALSE" }.to_string(),
+                    _ => value,
+                };
+                if let Some(cell) = row.last_mut() {
+                    cell.push_str(&value);
+                }
+            }
+            XmlEvent::Text(_) => {}
+        }
+        out.len() <= max_chars * 4
+    });
+    Ok((name, out))
+}
+
+// Text from the start of a PDF or Office document, for hover previews. `max_chars` defaults to
+// 4000 and is capped at 100,000.
+#[tauri::command]
+#[specta::specta]
+pub async fn preview_document(path: String, max_chars: Option<u32>) -> Result<DocumentPreview, AppError> {
+    let file = Path::new(&path);
+    let kind = file.extension().map(|ext| ext.to_string_lossy().to_lowercase()).unwrap_or_default();
+    let max_chars = max_chars.map_or(DEFAULT_DOCUMENT_CHARS, |n| n as usize).clamp(1, MAX_DOCUMENT_CHARS);
+
+    let mut truncated = false;
+    let (section, text) = match kind.as_str() {
+        "pdf" => {
+            let extracted = text_extract::read_text(file, max_chars)?;
+            truncated = extracted.truncated;
+            (None, extracted.text)
+        }
+        "docx" | "xlsx" | "pptx" => {
+            let mut archive = ZipArchive::open(file)?;
+            match kind.as_str() {
+                "docx" => docx_text(&mut archive, max_chars)?,
+                "xlsx" => xlsx_text(&mut archive, max_chars)?,
+                _ => pptx_text(&mut archive, max_chars)?,
+            }
+        }
+        _ => return Err(AppError::new(ErrorCode::InvalidInput, format!("{} is not a PDF or Office document", path))),
+    };
+
+    // Blank lines from empty paragraphs and rows add nothing to a preview
+    let mut lines: Vec<&str> = text.lines().map(str::trim_end).collect();
+    lines.dedup_by(|a, b| a.is_empty() && b.is_empty());
+    let text = lines.join("\n").trim().to_string();
+    if text.is_empty() {
+        return Err(format!("No text to preview in {}", path).into());
+    }
+    let mut chars = text.chars();
+    let kept: String = chars.by_ref().take(max_chars).collect();
+    Ok(DocumentPreview {
+        path,
+        kind,
+        section,
+        truncated: truncated || chars.next().is_some(),
+        text: kept,
+    })
+}
@@ -0,0 +1,405 @@
+// Project root detection from manifests, used to group recent files into projects and to rank
+// files in the project the user is working in above the rest. Projects found in indexed roots are
+// stored in `project_contexts`; opening a file marks its project active.
+
+use chrono::Utc;
+use rusqlite::{params, Connection, OptionalExtension, Result as SqlResult};
+use serde::Serialize;
+use specta::Type;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use tauri::State;
+
+use crate::error::{AppError, ErrorCode};
+use crate::{db, is_library_file, AppState};
+
+// A project stays active this long after a file in it was opened
+const ACTIVE_WINDOW_SECS: i64 = 8 * 60 * 60;
+
+// Indexed dotfiles whose folder may hold a .git directory (the index skips .git itself)
+const GIT_HINTS: &[&str] = &[".gitignore", ".gitattributes", ".gitmodules"];
+
+// Manifest files that mark a project root, with the language they imply
+const MANIFESTS: &[(&str, &str)] = &[
+    ("Cargo.toml", "Rust"),
+    ("package.json", "JavaScript"),
+    ("pyproject.toml", "Python"),
+    ("setup.py", "Python"),
+    ("requirements.txt", "Python"),
+    ("go.mod", "Go"),
+    ("pom.xml", "Java"),
+    ("build.gradle", "Java"),
+    ("build.gradle.kts", "Kotlin"),
+    ("composer.json", "PHP"),
+    ("Gemfile", "Ruby"),
+    ("mix.exs", "Elixir"),
+    ("CMakeLists.txt", "C++"),
+];
+
+#[derive(Debug, Serialize, Clone)]
+pub struct ProjectRoot {
+    pub root: String,
+    pub name: String,
+    // Manifest-derived language, None when the root was found via .git only
+    pub language: Option<String>,
+}
+
+#[derive(Debug, Serialize, Clone, Type)]
+pub struct RecentProject {
+    pub root: String,
+    pub name: String,
+    pub last_activity: i64,
+    pub access_count: i32,
+    pub recent_file_count: usize,
+    pub main_language: Option<String>,
+}
+
+#[derive(Debug, Serialize, Clone, Type)]
+pub struct ProjectContext {
+    pub root: String,
+    pub name: String,
+    pub language: Option<String>,
+    // Manifests and .git found at the root, e.g. ["Cargo.toml", ".git"]
+    pub markers: Vec<String>,
+    // When a file in the project was last opened
+    pub last_active: Option<i64>,
+    pub detected_at: i64,
+}
+
+pub fn create_table(conn: &Connection) -> SqlResult<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS project_contexts (
+            root TEXT PRIMARY KEY,
+            name TEXT NOT NULL,
+            language TEXT,
+            markers TEXT NOT NULL,
+            indexed_root TEXT,
+            detected_at INTEGER NOT NULL,
+            last_active INTEGER
+        )",
+        [],
+    )?;
+    Ok(())
+}
+
+// Inspect a single directory for project markers
+pub fn detect_project_at(dir: &Path) -> Option<ProjectRoot> {
+    let name = dir
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_else(|| dir.to_string_lossy().to_string());
+
+    for (manifest, language) in MANIFESTS {
+        if dir.join(manifest).is_file() {
+            // TypeScript projects are still package.json projects
+            let language = if *manifest == "package.json" && dir.join("tsconfig.json").is_file() {
+                "TypeScript"
+            } else {
+                language
+            };
+            return Some(ProjectRoot {
+                root: dir.to_string_lossy().to_string(),
+                name,
+                language: Some(language.to_string()),
+            });
+        }
+    }
+
+    // .NET projects are identified by *.csproj / *.sln files rather than a fixed name
+    if let Ok(entries) = std::fs::read_dir(dir) {
+        for entry in entries.flatten() {
+            let file_name = entry.file_name().to_string_lossy().to_lowercase();
+            if file_name.ends_with(".csproj") || file_name.ends_with(".sln") {
+                return Some(ProjectRoot {
+                    root: dir.to_string_lossy().to_string(),
+                    name,
+                    language: Some("C#".to_string()),
+                });
+            }
+        }
+    }
+
+    if dir.join(".git").exists() {
+        return Some(ProjectRoot {
+            root: dir.to_string_lossy().to_string(),
+            name,
+            language: None,
+        });
+    }
+
+    None
+}
+
+// Every marker present in a project root, manifests first
+fn markers_at(dir: &Path) -> Vec<String> {
+    let mut markers: Vec<String> = MANIFESTS
+        .iter()
+        .map(|(manifest, _)| manifest.to_string())
+        .filter(|manifest| dir.join(manifest).is_file())
+        .collect();
+    if let Ok(entries) = std::fs::read_dir(dir) {
+        let mut dotnet: Vec<String> = entries
+            .flatten()
+            .map(|entry| entry.file_name().to_string_lossy().to_string())
+            .filter(|name| {
+                let lower = name.to_lowercase();
+                lower.ends_with(".csproj") || lower.ends_with(".sln")
+            })
+            .collect();
+        dotnet.sort();
+        markers.extend(dotnet);
+    }
+    if dir.join(".git").exists() {
+        markers.push(".git".to_string());
+    }
+    markers
+}
+
+pub fn detect_context(dir: &Path) -> Option<ProjectContext> {
+    let project = detect_project_at(dir)?;
+    Some(ProjectContext {
+        root: project.root,
+        name: project.name,
+        language: project.language,
+        markers: markers_at(dir),
+        last_active: None,
+        detected_at: Utc::now().timestamp(),
+    })
+}
+
+fn save_context(conn: &Connection, context: &ProjectContext, indexed_root: Option<&str>) -> SqlResult<()> {
+    let markers = serde_json::to_string(&context.markers).unwrap_or_else(|_| "[]".to_string());
+    conn.execute(
+        "INSERT INTO project_contexts (root, name, language, markers, indexed_root, detected_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6)
+         ON CONFLICT(root) DO UPDATE SET
+            name = ?2, language = ?3, markers = ?4,
+            indexed_root = COALESCE(?5, indexed_root), detected_at = ?6",
+        params![context.root, context.name, context.language, markers, indexed_root, context.detected_at],
+    )?;
+    Ok(())
+}
+
+fn load_context(conn: &Connection, root: &str) -> SqlResult<Option<ProjectContext>> {
+    conn.query_row(
+        "SELECT root, name, language, markers, last_active, detected_at FROM project_contexts WHERE root = ?1",
+        [root],
+        |row| {
+            let markers: String = row.get(3)?;
+            Ok(ProjectContext {
+                root: row.get(0)?,
+                name: row.get(1)?,
+                language: row.get(2)?,
+                markers: serde_json::from_str(&markers).unwrap_or_default(),
+                last_active: row.get(4)?,
+                detected_at: row.get(5)?,
+            })
+        },
+    )
+    .optional()
+}
+
+// Find the projects in an indexed root from the manifests and git files the index holds, and
+// forget projects there that are gone. Returns how many projects the root has.
+pub fn detect_in_root(conn: &Connection, indexed_root: &str) -> SqlResult<usize> {
+    let started = Utc::now().timestamp();
+    let mut marker_names: Vec<&str> = MANIFESTS.iter().map(|(manifest, _)| *manifest).collect();
+    marker_names.extend(GIT_HINTS);
+    let placeholders = vec!["?"; marker_names.len()].join(", ");
+
+    let mut candidates: HashSet<PathBuf> = HashSet::from([PathBuf::from(indexed_root)]);
+    {
+        let mut stmt = conn.prepare(&format!(
+            "SELECT path FROM files WHERE root_directory = ? AND (name IN ({}) OR name LIKE '%.csproj' OR name LIKE '%.sln')",
+            placeholders
+        ))?;
+        let mut values: Vec<&str> = vec![indexed_root];
+        values.extend(&marker_names);
+        let paths = stmt.query_map(rusqlite::params_from_iter(values), |row| row.get::<_, String>(0))?;
+        for path in paths.flatten() {
+            // Dependencies and build output carry manifests of their own
+            if is_library_file(&path) {
+                continue;
+            }
+            if let Some(dir) = Path::new(&path).parent() {
+                candidates.insert(dir.to_path_buf());
+            }
+        }
+    }
+
+    let mut found = 0;
+    for dir in candidates {
+        if let Some(context) = detect_context(&dir) {
+            save_context(conn, &context, Some(indexed_root))?;
+            found += 1;
+        }
+    }
+    conn.execute(
+        "DELETE FROM project_contexts WHERE indexed_root = ?1 AND detected_at < ?2",
+        params![indexed_root, started],
+    )?;
+    Ok(found)
+}
+
+// Mark the project holding `path` as active; called when a file is opened
+pub fn touch_active(conn: &Connection, path: &str, now: i64) -> SqlResult<()> {
+    let mut cache = HashMap::new();
+    let Some(project) = find_project_root(Path::new(path), &mut cache) else {
+        return Ok(());
+    };
+    if load_context(conn, &project.root)?.is_none() {
+        if let Some(context) = detect_context(Path::new(&project.root)) {
+            save_context(conn, &context, None)?;
+        }
+    }
+    conn.execute("UPDATE project_contexts SET last_active = ?1 WHERE root = ?2", params![now, project.root])?;
+    Ok(())
+}
+
+// Root of the project a file was most recently opened in, if that was recent enough
+pub fn active_project(conn: &Connection) -> SqlResult<Option<String>> {
+    conn.query_row(
+        "SELECT root FROM project_contexts WHERE last_active >= ?1 ORDER BY last_active DESC LIMIT 1",
+        [Utc::now().timestamp() - ACTIVE_WINDOW_SECS],
+        |row| row.get(0),
+    )
+    .optional()
+}
+
+// Walk up from a file to the nearest enclosing project root, stopping at the home directory
+pub fn find_project_root(path: &Path, cache: &mut HashMap<PathBuf, Option<ProjectRoot>>) -> Option<ProjectRoot> {
+    let home = dirs::home_dir();
+    let mut visited: Vec<PathBuf> = Vec::new();
+    let mut found = None;
+
+    for dir in path.ancestors().skip(1) {
+        if home.as_deref() == Some(dir) {
+            break;
+        }
+        if let Some(cached) = cache.get(dir) {
+            found = cached.clone();
+            break;
+        }
+        visited.push(dir.to_path_buf());
+        if let Some(project) = detect_project_at(dir) {
+            found = Some(project);
+            break;
+        }
+    }
+
+    for dir in visited {
+        cache.insert(dir, found.clone());
+    }
+    found
+}
+
+// Guess a language from file extensions when no manifest says so
+pub fn language_for_extension(extension: &str) -> Option<&'static str> {
+    let language = match extension {
+        "rs" => "Rust",
+        "py" => "Python",
+        "js" | "jsx" | "mjs" => "JavaScript",
+        "ts" | "tsx" => "TypeScript",
+        "go" => "Go",
+        "java" => "Java",
+        "kt" | "kts" => "Kotlin",
+        "cs" => "C#",
+        "c" | "h" => "C",
+        "cpp" | "cc" | "hpp" => "C++",
+        "rb" => "Ruby",
+        "php" => "PHP",
+        "swift" => "Swift",
+        "ex" | "exs" => "Elixir",
+        _ => return None,
+    };
+    Some(language)
+}
+
+// Project detected at `root`, or the project that contains it. Detection runs again on every
+// call so the answer reflects the folder as it is now.
+#[tauri::command]
+#[specta::specta]
+pub async fn get_project_context(root: String, state: State<'_, AppState>) -> Result<Option<ProjectContext>, AppError> {
+    let dir = Path::new(&root);
+    if !dir.is_dir() {
+        return Err(AppError::new(ErrorCode::InvalidInput, format!("Not a folder: {}", root)));
+    }
+    let detected = detect_context(dir).or_else(|| {
+        let project = find_project_root(dir, &mut HashMap::new())?;
+        detect_context(Path::new(&project.root))
+    });
+    let Some(context) = detected else {
+        return Ok(None);
+    };
+
+    db::write(&state.db, |db| {
+        save_context(db, &context, None)?;
+        load_context(db, &context.root).map_err(AppError::from)
+    })
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn get_recent_projects(limit: Option<usize>, state: State<'_, AppState>) -> Result<Vec<RecentProject>, AppError> {
+    let recent: Vec<(String, i64, i32)> = db::write(&state.db, |db| {
+        let mut stmt = db
+            .prepare("SELECT path, last_accessed, access_count FROM recent_files ORDER BY last_accessed DESC LIMIT 500")?;
+        let rows = stmt
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))?
+            .filter_map(|r| r.ok())
+            .collect();
+        Ok(rows)
+    })?; // Release the lock before touching the filesystem
+
+    let mut cache: HashMap<PathBuf, Option<ProjectRoot>> = HashMap::new();
+    let mut projects: HashMap<String, (RecentProject, HashMap<&'static str, usize>)> = HashMap::new();
+
+    for (path, last_accessed, access_count) in &recent {
+        let Some(project) = find_project_root(Path::new(path), &mut cache) else {
+            continue;
+        };
+
+        let (entry, languages) = projects.entry(project.root.clone()).or_insert_with(|| {
+            (
+                RecentProject {
+                    root: project.root.clone(),
+                    name: project.name.clone(),
+                    last_activity: 0,
+                    access_count: 0,
+                    recent_file_count: 0,
+                    main_language: project.language.clone(),
+                },
+                HashMap::new(),
+            )
+        });
+        entry.last_activity = entry.last_activity.max(*last_accessed);
+        entry.access_count += access_count;
+        entry.recent_file_count += 1;
+
+        let extension = Path::new(path)
+            .extension()
+            .map(|e| e.to_string_lossy().to_lowercase())
+            .unwrap_or_default();
+        if let Some(language) = language_for_extension(&extension) {
+            *languages.entry(language).or_insert(0) += 1;
+        }
+    }
+
+    let mut result: Vec<RecentProject> = projects
+        .into_values()
+        .map(|(mut project, languages)| {
+            // Fall back to the most common language among the recently opened files
+            if project.main_language.is_none() {
+                project.main_language = languages
+                    .into_iter()
+                    .max_by_key(|(_, count)| *count)
+                    .map(|(language, _)| language.to_string());
+            }
+            project
+        })
+        .collect();
+
+    result.sort_by_key(|project| std::cmp::Reverse(project.last_activity));
+    result.truncate(limit.unwrap_or(20));
+    Ok(result)
+}
@@ -0,0 +1,159 @@
+// File properties for the details panel: sizes, times, permissions and attributes read from the
+// file system at the moment they are asked for. Nothing here comes from the index, so it is
+// current even for files indexed long ago.
+
+use serde::Serialize;
+use specta::Type;
+use std::fs::{self, Metadata};
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::error::AppError;
+
+#[derive(Debug, Serialize, Clone, Type)]
+pub struct FileProperties {
+    pub path: String,
+    pub name: String,
+    pub is_dir: bool,
+    pub is_symlink: bool,
+    // Bytes; for a directory, the size of the entry itself, not its contents
+    pub size: u64,
+    // Unix seconds; None where the file system doesn't record them
+    pub created_at: Option<i64>,
+    pub modified_at: Option<i64>,
+    pub accessed_at: Option<i64>,
+    pub readonly: bool,
+    pub hidden: bool,
+    // "rwxr-xr-x" and octal "755" on Unix-like systems
+    pub permissions: Option<String>,
+    pub mode: Option<String>,
+    pub owner: Option<String>,
+    pub group: Option<String>,
+    // Where a symbolic link points
+    pub link_target: Option<String>,
+    // Direct children of a directory
+    pub child_count: Option<u64>,
+}
+
+// What each platform adds about ownership and visibility
+struct Details {
+    permissions: Option<String>,
+    mode: Option<String>,
+    owner: Option<String>,
+    group: Option<String>,
+    hidden: bool,
+}
+
+fn unix_seconds(time: std::io::Result<SystemTime>) -> Option<i64> {
+    let time = time.ok()?;
+    Some(match time.duration_since(UNIX_EPOCH) {
+        Ok(elapsed) => elapsed.as_secs() as i64,
+        Err(before) => -(before.duration().as_secs() as i64),
+    })
+}
+
+#[cfg(unix)]
+mod platform {
+    use std::fs::{self, Metadata};
+    use std::os::unix::fs::MetadataExt;
+    use std::path::Path;
+
+    use super::Details;
+
+    fn mode_string(mode: u32) -> String {
+        let bits = ['r', 'w', 'x'];
+        (0..9)
+            .map(|i| if mode & (0o400 >> i) != 0 { bits[i % 3] } else { '-' })
+            .collect()
+    }
+
+    // Name from /etc/passwd or /etc/group, else the number. Directory-service accounts (macOS,
+    // LDAP) aren't listed there and show as numbers.
+    fn lookup(file: &str, id: u32) -> String {
+        fs::read_to_string(file)
+            .ok()
+            .and_then(|contents| {
+                contents.lines().find_map(|line| {
+                    let mut fields = line.split(':');
+                    let name = fields.next()?;
+                    (fields.nth(1)?.parse::<u32>().ok()? == id).then(|| name.to_string())
+                })
+            })
+            .unwrap_or_else(|| id.to_string())
+    }
+
+    pub fn details(path: &Path, metadata: &Metadata) -> Details {
+        let mode = metadata.mode() & 0o777;
+        let dotfile = path.file_name().is_some_and(|name| name.to_string_lossy().starts_with('.'));
+        // Finder's "hidden" flag (UF_HIDDEN)
+        #[cfg(target_os = "macos")]
+        let flagged = std::os::macos::fs::MetadataExt::st_flags(metadata) & 0x8000 != 0;
+        #[cfg(not(target_os = "macos"))]
+        let flagged = false;
+        Details {
+            permissions: Some(mode_string(mode)),
+            mode: Some(format!("{:o}", mode)),
+            owner: Some(lookup("/etc/passwd", metadata.uid())),
+            group: Some(lookup("/etc/group", metadata.gid())),
+            hidden: dotfile || flagged,
+        }
+    }
+}
+
+#[cfg(windows)]
+mod platform {
+    use std::fs::Metadata;
+    use std::os::windows::fs::MetadataExt;
+    use std::path::Path;
+
+    use super::Details;
+
+    const FILE_ATTRIBUTE_HIDDEN: u32 = 0x2;
+
+    // Windows permissions are ACLs; only the read-only and hidden attributes are reported
+    pub fn details(_path: &Path, metadata: &Metadata) -> Details {
+        Details {
+            permissions: None,
+            mode: None,
+            owner: None,
+            group: None,
+            hidden: metadata.file_attributes() & FILE_ATTRIBUTE_HIDDEN != 0,
+        }
+    }
+}
+
+fn properties(path: &Path, link_metadata: &Metadata) -> FileProperties {
+    let is_symlink = link_metadata.is_symlink();
+    // Everything but the link target describes what a link points to, when it resolves
+    let metadata = if is_symlink { fs::metadata(path).unwrap_or_else(|_| link_metadata.clone()) } else { link_metadata.clone() };
+    let details = platform::details(path, &metadata);
+    FileProperties {
+        path: path.to_string_lossy().to_string(),
+        name: path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_else(|| path.to_string_lossy().to_string()),
+        is_dir: metadata.is_dir(),
+        is_symlink,
+        size: metadata.len(),
+        created_at: unix_seconds(metadata.created()),
+        modified_at: unix_seconds(metadata.modified()),
+        accessed_at: unix_seconds(metadata.accessed()),
+        readonly: metadata.permissions().readonly(),
+        hidden: details.hidden,
+        permissions: details.permissions,
+        mode: details.mode,
+        owner: details.owner,
+        group: details.group,
+        link_target: is_symlink
+            .then(|| fs::read_link(path).ok())
+            .flatten()
+            .map(|target| target.to_string_lossy().to_string()),
+        child_count: metadata.is_dir().then(|| fs::read_dir(path).map(|entries| entries.count() as u64).ok()).flatten(),
+    }
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn get_file_properties(path: String) -> Result<FileProperties, AppError> {
+    let file = Path::new(&path);
+    let metadata = fs::symlink_metadata(file).map_err(|e| format!("{}: {}", path, e))?;
+    Ok(properties(file, &metadata))
+}
@@ -0,0 +1,165 @@
+// Tunable scoring weights shared by every search path
+
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use tauri::State;
+
+use crate::error::AppError;
+use crate::{db, settings, AppState};
+
+pub const SETTINGS_KEY: &str = "ranking";
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Type)]
+#[serde(default)]
+pub struct RankingConfig {
+    // Name match tiers used when scoring SQL-prefiltered candidates
+    pub exact_name: i64,
+    pub exact_name_without_extension: i64,
+    pub prefix_match: i64,
+    pub regex_match: i64,
+    pub name_starts_with: i64,
+    pub name_contains: i64,
+    pub all_words_in_name: i64,
+    pub romanized_match: i64,
+    pub path_contains: i64,
+    pub all_words_in_path: i64,
+    pub weak_match: i64,
+    // Fuzzy matches merged into regex results are divided by this
+    pub fuzzy_fallback_divisor: i64,
+
+    // Tiers used by fuzzy_search_files
+    pub fuzzy_exact_name: i64,
+    pub extension_full_name: i64,
+    pub extension_substring: i64,
+    pub extension_prefix_bonus: i64,
+    pub extension_boundary_bonus: i64,
+    pub normalized_match: i64,
+    pub normalized_prefix_bonus: i64,
+    pub token_match: i64,
+    pub contiguous_bonus: i64,
+    pub path_component_match: i64,
+    pub path_contiguous_bonus: i64,
+    pub fuzzy_name_min_score: i64,
+    pub fuzzy_name_bonus: i64,
+    pub fuzzy_path_min_score: i64,
+    pub fuzzy_path_divisor: i64,
+
+    // Penalties and boosts
+    pub library_dir_divisor: i64,
+    // Files inside hidden folders, for roots indexed with them (see hidden.rs)
+    pub hidden_dir_divisor: i64,
+    // Most a file's history (recent opens and favorites, see frecency.rs) adds to its score
+    pub frecency_bonus: i64,
+    // Days after which an open counts half as much
+    pub frecency_half_life_days: i64,
+    // A favorite counts like this many opens just now, and doesn't age
+    pub favorite_frecency: i64,
+    // Files inside the project a file was recently opened in
+    pub active_project_multiplier: i64,
+    pub active_project_bonus: i64,
+}
+
+impl Default for RankingConfig {
+    fn default() -> Self {
+        Self {
+            exact_name: 15000,
+            exact_name_without_extension: 14000,
+            prefix_match: 5000,
+            regex_match: 4000,
+            name_starts_with: 4000,
+            name_contains: 3000,
+            all_words_in_name: 2800,
+            romanized_match: 2500,
+            path_contains: 2000,
+            all_words_in_path: 1800,
+            weak_match: 1000,
+            fuzzy_fallback_divisor: 2,
+
+            fuzzy_exact_name: 10000,
+            extension_full_name: 9500,
+            extension_substring: 3000,
+            extension_prefix_bonus: 1500,
+            extension_boundary_bonus: 800,
+            normalized_match: 2900,
+            normalized_prefix_bonus: 500,
+            token_match: 3000,
+            contiguous_bonus: 1200,
+            path_component_match: 2000,
+            path_contiguous_bonus: 800,
+            fuzzy_name_min_score: 60,
+            fuzzy_name_bonus: 500,
+            fuzzy_path_min_score: 80,
+            fuzzy_path_divisor: 2,
+
+            library_dir_divisor: 4,
+            hidden_dir_divisor: 2,
+            frecency_bonus: 3000,
+            frecency_half_life_days: 14,
+            favorite_frecency: 5,
+            active_project_multiplier: 2,
+            active_project_bonus: 800,
+        }
+    }
+}
+
+impl RankingConfig {
+    // Divisors of zero would panic while scoring, so clamp them to at least 1
+    pub fn sanitized(mut self) -> Self {
+        self.fuzzy_fallback_divisor = self.fuzzy_fallback_divisor.max(1);
+        self.fuzzy_path_divisor = self.fuzzy_path_divisor.max(1);
+        self.library_dir_divisor = self.library_dir_divisor.max(1);
+        self.hidden_dir_divisor = self.hidden_dir_divisor.max(1);
+        self.frecency_bonus = self.frecency_bonus.max(0);
+        self.frecency_half_life_days = self.frecency_half_life_days.max(1);
+        self
+    }
+
+    // The active-project boost multiplies fuzzy scores and adds to SQL-prefiltered ones
+    pub fn apply_active_project_boost(&self, score: i64, additive: bool) -> i64 {
+        if additive {
+            score + self.active_project_bonus
+        } else {
+            score * self.active_project_multiplier
+        }
+    }
+
+    // Score for a name that matched the query exactly, without its extension, or otherwise
+    pub fn exact_name_score(&self, name_folded: &str, query_folded: &str, otherwise: i64) -> i64 {
+        if name_folded == query_folded {
+            return self.exact_name;
+        }
+        let name_without_ext = match name_folded.rfind('.') {
+            Some(dot_pos) => &name_folded[..dot_pos],
+            None => name_folded,
+        };
+        if name_without_ext == query_folded {
+            self.exact_name_without_extension
+        } else {
+            otherwise
+        }
+    }
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn get_ranking_config(state: State<'_, AppState>) -> Result<RankingConfig, AppError> {
+    let ranking = state.ranking.read().map_err(|e| e.to_string())?;
+    Ok(ranking.clone())
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn set_ranking_config(config: RankingConfig, state: State<'_, AppState>) -> Result<RankingConfig, AppError> {
+    let config = config.sanitized();
+    db::write(&state.db, |db| settings::save(db, SETTINGS_KEY, &config))?;
+    *state.ranking.write().map_err(|e| e.to_string())? = config.clone();
+
+    // Cached results were ranked with the old weights
+    state.search_cache.write().map_err(|e| e.to_string())?.clear();
+
+    Ok(config)
+}
+
+pub fn load(conn: &rusqlite::Connection) -> RankingConfig {
+    settings::load::<RankingConfig>(conn, SETTINGS_KEY).sanitized()
+}
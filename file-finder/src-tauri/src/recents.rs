@@ -0,0 +1,147 @@
+// Retention for `recent_files`, and the indexed root each entry belongs to. Without it the table only grows, and entries for files that
+// are long gone (or came from another machine with the database) keep taking places in the
+// ranking boost lists. A background job trims it to the newest `max_entries`, drops entries
+// older than `max_age_days` and, optionally, ones whose file no longer exists.
+
+use rusqlite::{params, Connection, Result as SqlResult};
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use std::path::Path;
+use std::time::Duration;
+use tauri::State;
+use tracing::{info, warn};
+
+use crate::error::AppError;
+use crate::{archives, db, invalidate_search_cache, settings, AppState};
+
+pub const SETTINGS_KEY: &str = "recent_files";
+
+const PRUNE_INTERVAL: Duration = Duration::from_secs(6 * 60 * 60);
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Type)]
+#[serde(default)]
+pub struct RecentsConfig {
+    // Most recently opened entries kept
+    pub max_entries: u32,
+    // Entries not opened for this many days are dropped; 0 keeps them regardless of age
+    pub max_age_days: u32,
+    // Drop entries whose file is gone
+    pub remove_missing: bool,
+}
+
+impl Default for RecentsConfig {
+    fn default() -> Self {
+        Self {
+            max_entries: 500,
+            max_age_days: 180,
+            remove_missing: true,
+        }
+    }
+}
+
+impl RecentsConfig {
+    fn sanitized(mut self) -> Self {
+        self.max_entries = self.max_entries.clamp(10, 100_000);
+        self.max_age_days = self.max_age_days.min(3650);
+        self
+    }
+}
+
+// Recent files remember the indexed root they were opened under, for per-root recents
+pub fn add_root_column(conn: &Connection) -> SqlResult<()> {
+    if conn.execute("ALTER TABLE recent_files ADD COLUMN root_directory TEXT", []).is_ok() {
+        // Column is new - fill it in for entries that are still indexed
+        conn.execute(
+            "UPDATE recent_files SET root_directory = (
+                SELECT f.root_directory FROM files f WHERE f.path = recent_files.path AND f.root_directory != ''
+            )",
+            [],
+        )?;
+    }
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_recent_root ON recent_files(root_directory, last_accessed DESC)",
+        [],
+    )?;
+    Ok(())
+}
+
+fn load(conn: &Connection) -> RecentsConfig {
+    settings::load::<RecentsConfig>(conn, SETTINGS_KEY).sanitized()
+}
+
+// Apply the retention settings; returns how many entries were removed
+pub fn prune(conn: &Connection, config: &RecentsConfig) -> SqlResult<usize> {
+    let mut removed = 0;
+    if config.max_age_days > 0 {
+        let cutoff = chrono::Utc::now().timestamp() - i64::from(config.max_age_days) * 24 * 60 * 60;
+        removed += conn.execute("DELETE FROM recent_files WHERE last_accessed < ?1", [cutoff])?;
+    }
+    removed += conn.execute(
+        "DELETE FROM recent_files WHERE id NOT IN (SELECT id FROM recent_files ORDER BY last_accessed DESC LIMIT ?1)",
+        [config.max_entries],
+    )?;
+    if config.remove_missing {
+        let paths: Vec<String> = {
+            let mut stmt = conn.prepare("SELECT path FROM recent_files")?;
+            let rows = stmt.query_map([], |row| row.get(0))?.filter_map(|r| r.ok()).collect();
+            rows
+        };
+        for path in paths {
+            // Entries inside archives name the archive's path followed by the entry
+            let file = archives::split(&path).map_or(path.as_str(), |(archive, _)| archive);
+            if !Path::new(file).exists() {
+                removed += conn.execute("DELETE FROM recent_files WHERE path = ?1", [&path])?;
+            }
+        }
+    }
+    Ok(removed)
+}
+
+// Background pruning with its own connection, at startup and every few hours after
+pub fn schedule(db_path: &Path) {
+    let db_path = db_path.to_path_buf();
+    std::thread::spawn(move || loop {
+        let result = db::open(&db_path).and_then(|conn| prune(&conn, &load(&conn)));
+        match result {
+            Ok(0) => {}
+            Ok(removed) => info!("Pruned {} recent files", removed),
+            Err(e) => warn!("Pruning recent files failed: {}", e),
+        }
+        std::thread::sleep(PRUNE_INTERVAL);
+    });
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn get_recents_config(state: State<'_, AppState>) -> Result<RecentsConfig, AppError> {
+    db::write(&state.db, |db| Ok(load(db)))
+}
+
+// Saves the settings and prunes with them right away
+#[tauri::command]
+#[specta::specta]
+pub async fn set_recents_config(config: RecentsConfig, state: State<'_, AppState>) -> Result<RecentsConfig, AppError> {
+    let config = config.sanitized();
+    db::write(&state.db, |db| {
+        settings::save(db, SETTINGS_KEY, &config)?;
+        prune(db, &config)?;
+        Ok(())
+    })?;
+    invalidate_search_cache(&state);
+    Ok(config)
+}
+
+// Forget one recent file, or all of them when no path is given. Returns how many were removed.
+#[tauri::command]
+#[specta::specta]
+pub async fn clear_recent_files(path: Option<String>, state: State<'_, AppState>) -> Result<usize, AppError> {
+    let removed = db::write(&state.db, |db| {
+        Ok(match &path {
+            Some(path) => db.execute("DELETE FROM recent_files WHERE path = ?1", params![path]),
+            None => db.execute("DELETE FROM recent_files", []),
+        }?)
+    })?;
+    // Recent files are part of the ranking boosts
+    invalidate_search_cache(&state);
+    Ok(removed)
+}
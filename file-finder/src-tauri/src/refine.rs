@@ -0,0 +1,90 @@
+// Narrow an earlier result set with a second query instead of searching the whole index again
+
+use rusqlite::OptionalExtension;
+use serde::Deserialize;
+use specta::Type;
+use std::collections::HashMap;
+use tauri::State;
+
+use crate::error::AppError;
+use crate::{db, AppState, FileEntry, SearchOptions};
+
+#[derive(Debug, Deserialize, Type)]
+#[serde(rename_all = "snake_case")]
+pub enum RefineBase {
+    // A previous search, identified by its query and options
+    Search { query: String, options: Option<SearchOptions> },
+    // An explicit set of paths, e.g. a selection made in the UI
+    Paths(Vec<String>),
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn refine_search(
+    within: RefineBase,
+    query: String,
+    options: Option<SearchOptions>,
+    state: State<'_, AppState>,
+) -> Result<Vec<FileEntry>, AppError> {
+    let search_opts = options.unwrap_or_default();
+    if query.trim().is_empty() {
+        return Ok(vec![]);
+    }
+
+    // Known modification times, so refined results keep them
+    let mut modified: HashMap<String, Option<i64>> = HashMap::new();
+    let candidates: Vec<(String, String)> = match within {
+        RefineBase::Search { query: base_query, options: base_options } => {
+            let base_options = base_options.unwrap_or_default();
+            let cache_key = crate::search_cache_key(&base_query, &base_options, &state)?;
+            let cached = {
+                let cache = state.search_cache.read().map_err(|e| e.to_string())?;
+                cache.get(&cache_key).map(|(_, entries)| entries.clone())
+            };
+            // Re-run the base search if it has already dropped out of the cache
+            let base = match cached {
+                Some(entries) => entries,
+                None => crate::natural_search(&base_query, &base_options, &state).await?.results,
+            };
+            base.into_iter()
+                .map(|entry| {
+                    modified.insert(entry.path.clone(), entry.modified_at);
+                    (entry.path, entry.name)
+                })
+                .collect()
+        }
+        RefineBase::Paths(paths) => paths
+            .into_iter()
+            .map(|path| {
+                let name = std::path::Path::new(&path)
+                    .file_name()
+                    .map(|n| n.to_string_lossy().to_string())
+                    .unwrap_or_else(|| path.clone());
+                (path, name)
+            })
+            .collect(),
+    };
+
+    let ranking = state.ranking.read().map_err(|e| e.to_string())?.clone();
+    let boosts = db::write(&state.db, |db| Ok(crate::boost_lists(db)?))?;
+
+    let results = crate::fuzzy_search_files(candidates, &query, &boosts, &search_opts, &ranking);
+
+    let mut refined: Vec<FileEntry> = crate::top_k::top_k(results, 100).into_iter().map(|(_, entry)| entry).collect();
+    db::write(&state.db, |db| {
+        for entry in refined.iter_mut() {
+            entry.modified_at = match modified.get(&entry.path) {
+                Some(modified_at) => *modified_at,
+                None => db
+                    .query_row("SELECT modified_at FROM files WHERE path = ?1", [&entry.path], |row| row.get(0))
+                    .optional()?
+                    .flatten(),
+            };
+        }
+        crate::mirrors::mark_backup_entries(db, &mut refined);
+        Ok(())
+    })?;
+    crate::assign_age_buckets(&mut refined);
+
+    Ok(refined)
+}
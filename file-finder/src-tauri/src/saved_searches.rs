@@ -0,0 +1,129 @@
+// Named searches (query + options + optional scope) that can be re-run on demand
+
+use rusqlite::{params, Connection, OptionalExtension, Result as SqlResult};
+use serde::Serialize;
+use specta::Type;
+use tauri::State;
+
+use crate::error::{AppError, ErrorCode};
+use crate::{db, AppState, FileEntry, SearchOptions};
+
+#[derive(Debug, Serialize, Clone, Type)]
+pub struct SavedSearch {
+    pub id: i64,
+    pub name: String,
+    pub query: String,
+    pub options: SearchOptions,
+    // Only results under this directory are returned, None searches everything
+    pub scope: Option<String>,
+    pub created_at: i64,
+    pub last_run_at: Option<i64>,
+}
+
+pub fn create_table(conn: &Connection) -> SqlResult<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS saved_searches (
+            id INTEGER PRIMARY KEY,
+            name TEXT UNIQUE NOT NULL,
+            query TEXT NOT NULL,
+            options TEXT NOT NULL,
+            scope TEXT,
+            created_at INTEGER NOT NULL,
+            last_run_at INTEGER
+        )",
+        [],
+    )?;
+    Ok(())
+}
+
+fn row_to_saved_search(row: &rusqlite::Row) -> SqlResult<SavedSearch> {
+    let options_json: String = row.get(3)?;
+    Ok(SavedSearch {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        query: row.get(2)?,
+        // Options saved by an older version may lack newer fields - serde(default) fills them in
+        options: serde_json::from_str(&options_json).unwrap_or_default(),
+        scope: row.get(4)?,
+        created_at: row.get(5)?,
+        last_run_at: row.get(6)?,
+    })
+}
+
+const SELECT_COLUMNS: &str = "SELECT id, name, query, options, scope, created_at, last_run_at FROM saved_searches";
+
+// Saving under an existing name replaces that search
+#[tauri::command]
+#[specta::specta]
+pub async fn create_saved_search(
+    name: String,
+    query: String,
+    options: Option<SearchOptions>,
+    scope: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<SavedSearch, AppError> {
+    let name = name.trim().to_string();
+    if name.is_empty() {
+        return Err(AppError::new(ErrorCode::InvalidInput, "Saved search name cannot be empty"));
+    }
+    if query.trim().is_empty() {
+        return Err(AppError::new(ErrorCode::InvalidInput, "Saved search query cannot be empty"));
+    }
+    let scope = scope.filter(|s| !s.trim().is_empty());
+    let options_json = serde_json::to_string(&options.unwrap_or_default()).map_err(|e| e.to_string())?;
+    let now = chrono::Utc::now().timestamp();
+
+    db::write(&state.db, |db| {
+        db.execute(
+            "INSERT INTO saved_searches (name, query, options, scope, created_at) VALUES (?1, ?2, ?3, ?4, ?5)
+             ON CONFLICT(name) DO UPDATE SET query = ?2, options = ?3, scope = ?4",
+            params![name, query, options_json, scope, now],
+        )?;
+
+        db.query_row(&format!("{} WHERE name = ?1", SELECT_COLUMNS), [&name], row_to_saved_search)
+            .map_err(AppError::from)
+    })
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn list_saved_searches(state: State<'_, AppState>) -> Result<Vec<SavedSearch>, AppError> {
+    db::write(&state.db, |db| {
+        let mut stmt = db
+            .prepare(&format!("{} ORDER BY name COLLATE LOCALE", SELECT_COLUMNS))?;
+        let searches = stmt
+            .query_map([], row_to_saved_search)?
+            .filter_map(|r| r.ok())
+            .collect();
+        Ok(searches)
+    })
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn run_saved_search(id: i64, state: State<'_, AppState>) -> Result<Vec<FileEntry>, AppError> {
+    let saved = db::write(&state.db, |db| {
+        let saved = db
+            .query_row(&format!("{} WHERE id = ?1", SELECT_COLUMNS), [id], row_to_saved_search)
+            .optional()?
+            .ok_or_else(|| AppError::new(ErrorCode::NotFound, format!("Saved search {} not found", id)))?;
+        db.execute(
+            "UPDATE saved_searches SET last_run_at = ?1 WHERE id = ?2",
+            params![chrono::Utc::now().timestamp(), id],
+        )?;
+        Ok(saved)
+    })?; // Release the lock before searching
+
+    let options = SearchOptions { scope: saved.scope, ..saved.options };
+    Ok(crate::natural_search(&saved.query, &options, &state).await?.results)
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn delete_saved_search(id: i64, state: State<'_, AppState>) -> Result<bool, AppError> {
+    db::write(&state.db, |db| {
+        let deleted = db
+            .execute("DELETE FROM saved_searches WHERE id = ?1", [id])?;
+        Ok(deleted > 0)
+    })
+}
@@ -0,0 +1,167 @@
+// SQL of the search queries. The candidate queries of common searches, which have no filter
+// beyond leaving out backup mirrors, are built once at startup instead of with format! on every
+// keystroke, and searches prepare them with `prepare_cached`, so a repeated search reuses the
+// compiled statement and its plan (see `db::STATEMENT_CACHE_CAPACITY`). Filters bind their
+// values as parameters rather than writing them into the SQL, so the text of a search stays the
+// same from one day to the next and hits the cache.
+
+use rusqlite::types::Value;
+use rusqlite::Connection;
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::sync::OnceLock;
+
+use crate::{mirrors, LIKE_ESCAPE};
+
+// How a prefilter compares names with the pattern bound to ?1
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum NameMatch {
+    // `name LIKE`, which ignores ASCII case only
+    Like,
+    // `fold(name) LIKE`, for patterns with other letters
+    FoldedLike,
+    // Folded names or their romanized form, for pinyin/romaji input
+    Romanized,
+    // `name GLOB`, for case-sensitive searches
+    Glob,
+}
+
+const NAME_MATCHES: [NameMatch; 4] = [NameMatch::Like, NameMatch::FoldedLike, NameMatch::Romanized, NameMatch::Glob];
+
+// Where a prefilter's candidate rows come from; the trigram and name index bind ?2
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Candidates {
+    Scan,
+    // An FTS5 match expression (see trigram.rs)
+    Trigram,
+    // A JSON array of row ids (see name_index.rs)
+    Names,
+}
+
+const CANDIDATES: [Candidates; 3] = [Candidates::Scan, Candidates::Trigram, Candidates::Names];
+
+impl Candidates {
+    // Name of the source in debug timings
+    pub fn label(self) -> &'static str {
+        match self {
+            Candidates::Scan => "scan",
+            Candidates::Trigram => "trigram",
+            Candidates::Names => "name_index",
+        }
+    }
+}
+
+impl NameMatch {
+    pub fn sql(self) -> String {
+        match self {
+            NameMatch::Like => format!("name LIKE ?1 {}", LIKE_ESCAPE),
+            NameMatch::FoldedLike => format!("fold(name) LIKE ?1 {}", LIKE_ESCAPE),
+            NameMatch::Romanized => format!("(fold(name) LIKE ?1 {0} OR name_romanized LIKE ?1 {0})", LIKE_ESCAPE),
+            NameMatch::Glob => "name GLOB ?1".to_string(),
+        }
+    }
+}
+
+// Filter conditions of a search, appended to a WHERE clause, with the values bound to their
+// `?` placeholders. Statements put the filter after their own numbered parameters and write any
+// parameter that follows it as a bare `?`, so the values bind in the order of the text.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SqlFilter {
+    // Each condition starts with " AND "
+    pub sql: String,
+    pub params: Vec<Value>,
+}
+
+impl SqlFilter {
+    pub fn new(sql: String) -> Self {
+        Self { sql, params: vec![] }
+    }
+
+    pub fn and(&mut self, clause: &str) {
+        self.sql.push_str(" AND ");
+        self.sql.push_str(clause);
+    }
+
+    // A condition with placeholders and the values for them
+    pub fn and_bound<T: Into<Value>>(&mut self, (clause, params): (String, Vec<T>)) {
+        self.and(&clause);
+        self.params.extend(params.into_iter().map(Into::into));
+    }
+
+    // Parameters of a statement: `before` for its numbered ones ahead of the filter, the
+    // filter's, then `after` for the placeholders following it
+    pub fn bind(&self, before: Vec<Value>, after: Vec<Value>) -> Vec<Value> {
+        let mut values = before;
+        values.extend(self.params.iter().cloned());
+        values.extend(after);
+        values
+    }
+}
+
+struct Prebuilt {
+    // The filter of a search with no filter tokens
+    plain_filter: String,
+    prefilters: HashMap<(NameMatch, Candidates), String>,
+    scan: String,
+}
+
+static PREBUILT: OnceLock<Prebuilt> = OnceLock::new();
+
+fn select(where_sql: &str, candidates_sql: &str, filter_sql: &str) -> String {
+    format!("SELECT path, name, modified_at FROM files WHERE {}{}{}", where_sql, candidates_sql, filter_sql)
+}
+
+fn candidate_sql(candidates: Candidates) -> &'static str {
+    match candidates {
+        Candidates::Scan => "",
+        Candidates::Trigram => " AND id IN (SELECT rowid FROM files_fts WHERE files_fts MATCH ?2)",
+        Candidates::Names => " AND id IN (SELECT value FROM json_each(?2))",
+    }
+}
+
+fn prebuilt() -> &'static Prebuilt {
+    PREBUILT.get_or_init(|| {
+        let plain_filter = mirrors::RootScope::Primary.sql_clause();
+        let mut prefilters = HashMap::new();
+        for name_match in NAME_MATCHES {
+            for candidates in CANDIDATES {
+                prefilters.insert((name_match, candidates), select(&name_match.sql(), candidate_sql(candidates), &plain_filter));
+            }
+        }
+        let scan = select("1 = 1", "", &plain_filter);
+        Prebuilt { plain_filter, prefilters, scan }
+    })
+}
+
+pub fn init() {
+    prebuilt();
+}
+
+// Compile the prebuilt statements into a new connection's cache ahead of its first search
+pub fn warm(conn: &Connection) {
+    let prebuilt = prebuilt();
+    for sql in prebuilt.prefilters.values().chain([&prebuilt.scan]) {
+        // Fails before the first index creates files_fts; the search prepares it then
+        let _ = conn.prepare_cached(sql);
+    }
+}
+
+// Candidate query of a LIKE or GLOB prefilter
+pub fn prefilter(name_match: NameMatch, candidates: Candidates, filter: &SqlFilter) -> Cow<'static, str> {
+    let prebuilt = prebuilt();
+    if filter.sql == prebuilt.plain_filter {
+        Cow::Borrowed(prebuilt.prefilters[&(name_match, candidates)].as_str())
+    } else {
+        Cow::Owned(select(&name_match.sql(), candidate_sql(candidates), &filter.sql))
+    }
+}
+
+// Candidate query reading every file that passes the filter
+pub fn scan(filter: &SqlFilter) -> Cow<'static, str> {
+    let prebuilt = prebuilt();
+    if filter.sql == prebuilt.plain_filter {
+        Cow::Borrowed(prebuilt.scan.as_str())
+    } else {
+        Cow::Owned(select("1 = 1", "", &filter.sql))
+    }
+}
@@ -0,0 +1,409 @@
+// Semantic file search. Each file gets a short description (name words, type, folders and
+// optionally the start of its text), embedded with the configured LLM backend and stored in
+// `file_embeddings`. Queries are embedded the same way and matched by cosine similarity, so
+// "spreadsheet about hiring budget" finds FY25_headcount_plan.xlsx without a shared keyword.
+
+use rayon::prelude::*;
+use rusqlite::{params, Connection, Result as SqlResult};
+use serde::Serialize;
+use specta::Type;
+use std::collections::HashMap;
+use std::path::Path;
+use tauri::State;
+use tracing::{info, warn};
+
+use crate::error::{AppError, ErrorCode};
+use crate::llm::{self, LlmConfig};
+use crate::{db, mirrors, text_extract, AppState, FileEntry};
+
+// Texts per embeddings request
+const BATCH_SIZE: usize = 32;
+// Files embedded per build_semantic_index call unless a limit is given
+const DEFAULT_BUILD_LIMIT: u32 = 5000;
+const DEFAULT_RESULTS: u32 = 50;
+// Leading text included in a description when content is requested
+const CONTENT_CHARS: usize = 1000;
+// Read a little more than is kept, since whitespace runs are collapsed
+const CONTENT_READ_CHARS: usize = 4096;
+
+// Unit-length vectors for one embedding model, flattened for fast scoring
+pub struct VectorIndex {
+    model: String,
+    dims: usize,
+    paths: InternedPaths,
+    vectors: Vec<f32>,
+}
+
+// Paths of the embedded files with each folder stored once, since most files share their folder
+// with many others. Full paths are put back together when results are returned.
+#[derive(Default)]
+struct InternedPaths {
+    dirs: Vec<String>,
+    // Folder id and file name of each path
+    entries: Vec<(u32, Box<str>)>,
+}
+
+impl InternedPaths {
+    // `dir_ids` maps the folders seen so far to their ids while the paths are added
+    fn push(&mut self, dir_ids: &mut HashMap<String, u32>, path: &str) {
+        // Keep the separator with the folder so the path is rebuilt exactly as stored
+        let split = path.rfind(['/', '\\']).map_or(0, |pos| pos + 1);
+        let (dir, name) = path.split_at(split);
+        let id = match dir_ids.get(dir) {
+            Some(&id) => id,
+            None => {
+                let id = self.dirs.len() as u32;
+                self.dirs.push(dir.to_string());
+                dir_ids.insert(dir.to_string(), id);
+                id
+            }
+        };
+        self.entries.push((id, name.into()));
+    }
+
+    fn get(&self, i: usize) -> String {
+        let (dir, name) = &self.entries[i];
+        format!("{}{}", self.dirs[*dir as usize], name)
+    }
+
+    fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+#[derive(Debug, Serialize, Clone, Type)]
+pub struct SemanticIndexSummary {
+    pub model: String,
+    pub embedded: i64,
+    pub failed: i64,
+    // Indexed files that still have no embedding for this model
+    pub remaining: i64,
+}
+
+#[derive(Debug, Serialize, Clone, Type)]
+pub struct SemanticMatch {
+    pub file: FileEntry,
+    // Cosine similarity between the query and the file description, -1 to 1
+    pub similarity: f32,
+}
+
+pub fn create_table(conn: &Connection) -> SqlResult<()> {
+    // vector holds little-endian f32s, normalized to unit length
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS file_embeddings (
+            path TEXT PRIMARY KEY,
+            model TEXT NOT NULL,
+            vector BLOB NOT NULL,
+            embedded_at INTEGER NOT NULL
+        )",
+        [],
+    )?;
+    Ok(())
+}
+
+pub fn normalize(mut vector: Vec<f32>) -> Vec<f32> {
+    let norm = vector.iter().map(|x| x * x).sum::<f32>().sqrt();
+    if norm > 0.0 {
+        vector.iter_mut().for_each(|x| *x /= norm);
+    }
+    vector
+}
+
+pub fn to_blob(vector: &[f32]) -> Vec<u8> {
+    vector.iter().flat_map(|x| x.to_le_bytes()).collect()
+}
+
+pub fn from_blob(blob: &[u8]) -> Vec<f32> {
+    blob.chunks_exact(4)
+        .map(|bytes| f32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+        .collect()
+}
+
+// "FY25_headcount-planV2" -> "FY 25 headcount plan V 2"
+fn name_words(stem: &str) -> String {
+    let mut words = String::new();
+    let mut previous: Option<char> = None;
+    for ch in stem.chars() {
+        if !ch.is_alphanumeric() {
+            words.push(' ');
+        } else {
+            let boundary = previous.is_some_and(|prev| {
+                (prev.is_lowercase() && ch.is_uppercase())
+                    || (prev.is_alphabetic() && ch.is_numeric())
+                    || (prev.is_numeric() && ch.is_alphabetic())
+            });
+            if boundary {
+                words.push(' ');
+            }
+            words.push(ch);
+        }
+        previous = Some(ch);
+    }
+    words.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+// Start of a file's text, if it has any (text, code and PDF files)
+fn leading_text(path: &Path) -> Option<String> {
+    let text = text_extract::read_text(path, CONTENT_READ_CHARS).ok()?.text;
+    Some(text.split_whitespace().collect::<Vec<_>>().join(" ").chars().take(CONTENT_CHARS).collect())
+}
+
+fn describe(path: &str, name: &str, group: Option<String>, include_content: bool) -> String {
+    let path_obj = Path::new(path);
+    let stem = path_obj.file_stem().map(|s| s.to_string_lossy().to_string()).unwrap_or_else(|| name.to_string());
+    let mut description = name_words(&stem);
+
+    if let Some(ext) = path_obj.extension() {
+        let ext = ext.to_string_lossy();
+        match group {
+            Some(group) => description.push_str(&format!(". Type: {} ({})", group, ext)),
+            None => description.push_str(&format!(". Type: {}", ext)),
+        }
+    }
+    // The nearest folders say most about what a file is for
+    let folders: Vec<String> = path_obj
+        .parent()
+        .map(|parent| {
+            parent
+                .components()
+                .rev()
+                .take(3)
+                .map(|c| name_words(&c.as_os_str().to_string_lossy()))
+                .filter(|words| !words.is_empty())
+                .collect()
+        })
+        .unwrap_or_default();
+    if !folders.is_empty() {
+        description.push_str(&format!(". Folder: {}", folders.into_iter().rev().collect::<Vec<_>>().join(" / ")));
+    }
+    if include_content {
+        if let Some(text) = leading_text(path_obj).filter(|text| !text.is_empty()) {
+            description.push_str(&format!(". Content: {}", text));
+        }
+    }
+    description
+}
+
+fn load_index(conn: &Connection, model: &str) -> Result<VectorIndex, String> {
+    let mut stmt = conn
+        .prepare("SELECT path, vector FROM file_embeddings WHERE model = ?1")
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map([model], |row| Ok((row.get::<_, String>(0)?, row.get::<_, Vec<u8>>(1)?)))
+        .map_err(|e| e.to_string())?;
+
+    let mut index = VectorIndex {
+        model: model.to_string(),
+        dims: 0,
+        paths: InternedPaths::default(),
+        vectors: Vec::new(),
+    };
+    let mut dir_ids = HashMap::new();
+    for (path, blob) in rows.filter_map(|r| r.ok()) {
+        let vector = from_blob(&blob);
+        if index.dims == 0 {
+            index.dims = vector.len();
+        }
+        if vector.len() == index.dims {
+            index.paths.push(&mut dir_ids, &path);
+            index.vectors.extend(vector);
+        }
+    }
+    Ok(index)
+}
+
+// Embed indexed files that have no embedding for the current model yet, optionally only under
+// `root`. Backup mirrors are skipped. Call again to continue when `remaining` is not zero.
+#[tauri::command]
+#[specta::specta]
+pub async fn build_semantic_index(
+    root: Option<String>,
+    limit: Option<u32>,
+    include_content: Option<bool>,
+    state: State<'_, AppState>,
+) -> Result<SemanticIndexSummary, AppError> {
+    let config = state.llm.read().map_err(|e| e.to_string())?.clone();
+    if !config.enabled {
+        return Err(AppError::new(ErrorCode::LlmDisabled, "Semantic search needs the LLM backend; enable it in the LLM settings"));
+    }
+    let limit = limit.unwrap_or(DEFAULT_BUILD_LIMIT).clamp(1, 100_000);
+    let include_content = include_content.unwrap_or(false);
+    let scope_sql = match &root {
+        Some(_) => " AND substr(files.path, 1, length(?2)) = ?2",
+        None => "",
+    };
+    let pending_sql = format!(
+        "FROM files LEFT JOIN file_embeddings e ON e.path = files.path AND e.model = ?1
+         WHERE e.path IS NULL{}{}",
+        mirrors::RootScope::Primary.sql_clause(),
+        scope_sql
+    );
+
+    let candidates: Vec<(String, String)> = db::write(&state.db, |db| {
+        // Embeddings of files that have left the index are dead weight
+        db.execute("DELETE FROM file_embeddings WHERE path NOT IN (SELECT path FROM files)", [])?;
+        let mut stmt = db
+            .prepare(&format!("SELECT files.path, files.name {} LIMIT ?", pending_sql))?;
+        let path_and_name = |row: &rusqlite::Row| Ok((row.get(0)?, row.get(1)?));
+        let rows = match &root {
+            Some(root) => stmt.query_map(params![config.embedding_model, root, limit], path_and_name),
+            None => stmt.query_map(params![config.embedding_model, limit], path_and_name),
+        }?
+        .filter_map(|r| r.ok())
+        .collect();
+        Ok(rows)
+    })?;
+
+    let descriptions: Vec<String> = {
+        let groups = state.extension_groups.read().map_err(|e| e.to_string())?;
+        candidates
+            .iter()
+            .map(|(path, name)| {
+                let group = Path::new(path).extension().and_then(|ext| groups.group_of(&ext.to_string_lossy()));
+                describe(path, name, group, include_content)
+            })
+            .collect()
+    };
+
+    let mut summary = SemanticIndexSummary {
+        model: config.embedding_model.clone(),
+        embedded: 0,
+        failed: 0,
+        remaining: 0,
+    };
+    for (batch, texts) in candidates.chunks(BATCH_SIZE).zip(descriptions.chunks(BATCH_SIZE)) {
+        let vectors = match llm::embed(&config, texts, config.request_timeout()).await {
+            Ok(vectors) => vectors,
+            // Nothing embedded yet means the backend is not usable at all
+            Err(e) if summary.embedded == 0 => return Err(e),
+            Err(e) => {
+                warn!("Embedding batch failed: {}", e);
+                summary.failed += batch.len() as i64;
+                continue;
+            }
+        };
+
+        let now = chrono::Utc::now().timestamp();
+        db::write(&state.db, |db| {
+            for ((path, _), vector) in batch.iter().zip(vectors) {
+                db.execute(
+                    "INSERT OR REPLACE INTO file_embeddings (path, model, vector, embedded_at) VALUES (?1, ?2, ?3, ?4)",
+                    params![path, config.embedding_model, to_blob(&normalize(vector)), now],
+                )?;
+                summary.embedded += 1;
+            }
+            Ok(())
+        })?;
+    }
+
+    db::write(&state.db, |db| {
+        let count_sql = format!("SELECT COUNT(*) {}", pending_sql);
+        summary.remaining = match &root {
+            Some(root) => db.query_row(&count_sql, params![config.embedding_model, root], |row| row.get(0)),
+            None => db.query_row(&count_sql, params![config.embedding_model], |row| row.get(0)),
+        }?;
+        Ok(())
+    })?;
+    // The in-memory index is reloaded with the new vectors on the next search
+    *state.semantic_index.write().map_err(|e| e.to_string())? = None;
+
+    info!(
+        "Semantic index: embedded {} files ({} failed, {} remaining) with {}",
+        summary.embedded, summary.failed, summary.remaining, summary.model
+    );
+    Ok(summary)
+}
+
+// Unit-length embedding of one text
+pub async fn embed_text(config: &LlmConfig, text: &str) -> Result<Vec<f32>, AppError> {
+    llm::embed(config, &[text.to_string()], config.request_timeout())
+        .await?
+        .pop()
+        .map(normalize)
+        .ok_or_else(|| "The LLM server returned no embedding".into())
+}
+
+// Paths of the `limit` files whose descriptions are closest to a unit-length query vector, with
+// their similarity. Loads the index for the current embedding model on first use.
+pub fn nearest(query_vector: &[f32], limit: usize, config: &LlmConfig, state: &AppState) -> Result<Vec<(String, f32)>, AppError> {
+    let loaded = state
+        .semantic_index
+        .read()
+        .map_err(|e| e.to_string())?
+        .as_ref()
+        .is_some_and(|index| index.model == config.embedding_model);
+    if !loaded {
+        let index = db::write(&state.db, |db| Ok(load_index(db, &config.embedding_model)?))?;
+        *state.semantic_index.write().map_err(|e| e.to_string())? = Some(index);
+    }
+
+    let guard = state.semantic_index.read().map_err(|e| e.to_string())?;
+    let Some(index) = guard.as_ref().filter(|index| !index.paths.is_empty()) else {
+        return Err(AppError::new(ErrorCode::IndexEmpty, "The semantic index is empty; build it first"));
+    };
+    if index.dims != query_vector.len() {
+        return Err("The semantic index was built with a different embedding size; rebuild it".into());
+    }
+    let mut scored: Vec<(usize, f32)> = index
+        .vectors
+        .par_chunks(index.dims)
+        .enumerate()
+        .map(|(i, vector)| (i, vector.iter().zip(query_vector).map(|(a, b)| a * b).sum()))
+        .collect();
+    scored.sort_by(|a, b| b.1.total_cmp(&a.1));
+    Ok(scored
+        .into_iter()
+        .take(limit)
+        .map(|(i, similarity)| (index.paths.get(i), similarity))
+        .collect())
+}
+
+// Files whose description is closest in meaning to the query
+#[tauri::command]
+#[specta::specta]
+pub async fn semantic_search(query: String, limit: Option<u32>, state: State<'_, AppState>) -> Result<Vec<SemanticMatch>, AppError> {
+    let query = query.trim().to_string();
+    if query.is_empty() {
+        return Ok(vec![]);
+    }
+    let config = state.llm.read().map_err(|e| e.to_string())?.clone();
+    if !config.enabled {
+        return Err(AppError::new(ErrorCode::LlmDisabled, "Semantic search needs the LLM backend; enable it in the LLM settings"));
+    }
+    let limit = limit.unwrap_or(DEFAULT_RESULTS).clamp(1, 500) as usize;
+
+    let query_vector = embed_text(&config, &query).await?;
+    let top = nearest(&query_vector, limit, &config, &state)?;
+
+    db::write(&state.db, |db| {
+        let mut files = Vec::new();
+        let mut similarities = Vec::new();
+        for (path, similarity) in top {
+            let entry = db.query_row("SELECT path, name, modified_at FROM files WHERE path = ?1", [&path], |row| {
+                Ok(FileEntry {
+                    path: row.get(0)?,
+                    name: row.get(1)?,
+                    last_accessed: None,
+                    access_count: 0,
+                    modified_at: row.get(2)?,
+                    age_bucket: None,
+                    in_backup: false,
+                    match_source: None,
+                    content_snippet: None,
+                })
+            });
+            if let Ok(entry) = entry {
+                files.push(entry);
+                similarities.push(similarity);
+            }
+        }
+        mirrors::mark_backup_entries(db, &mut files);
+        crate::assign_age_buckets(&mut files);
+
+        Ok(files
+            .into_iter()
+            .zip(similarities)
+            .map(|(file, similarity)| SemanticMatch { file, similarity })
+            .collect())
+    })
+}
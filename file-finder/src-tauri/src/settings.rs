@@ -0,0 +1,197 @@
+// Persistent key/value settings stored as JSON in the `settings` table, and one place to read
+// and change the main ones: `get_settings` gathers the sections the features keep under their
+// own keys, `update_settings` saves the sections it is given and applies them right away.
+
+use rusqlite::{params, Connection, OptionalExtension, Result as SqlResult};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use tauri::State;
+use tracing::warn;
+
+use crate::error::AppError;
+use crate::exclusions::{self, ExclusionRules};
+use crate::llm::{self, LlmConfig};
+use crate::locale::{self, LocaleConfig};
+use crate::planner::{self, SearchBudget};
+use crate::ranking::{self, RankingConfig};
+use crate::{db, invalidate_search_cache, name_index, AppState};
+
+pub const SETTINGS_KEY: &str = "general";
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Type)]
+#[serde(default)]
+pub struct GeneralSettings {
+    // Most results one search returns
+    pub max_results: usize,
+    // Seconds the results of a search are reused when the same search comes again, 0 for never
+    pub cache_ttl_secs: u64,
+    // Most searches kept in the cache; the oldest is dropped to make room
+    pub cache_max_entries: usize,
+    pub reindex: ReindexSchedule,
+}
+
+// Nightly reindexing, started by the main window while it is open
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Type)]
+#[serde(default)]
+pub struct ReindexSchedule {
+    pub enabled: bool,
+    // Local hours; a window such as 23 to 2 runs past midnight
+    pub start_hour: u32,
+    pub end_hour: u32,
+}
+
+impl Default for GeneralSettings {
+    fn default() -> Self {
+        Self {
+            max_results: 500,
+            cache_ttl_secs: 30,
+            cache_max_entries: 100,
+            reindex: ReindexSchedule::default(),
+        }
+    }
+}
+
+impl Default for ReindexSchedule {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            start_hour: 2,
+            end_hour: 5,
+        }
+    }
+}
+
+impl GeneralSettings {
+    fn sanitized(mut self) -> Self {
+        self.max_results = self.max_results.clamp(10, 5000);
+        self.cache_ttl_secs = self.cache_ttl_secs.min(3600);
+        self.cache_max_entries = self.cache_max_entries.clamp(1, 10_000);
+        self.reindex.start_hour = self.reindex.start_hour.min(23);
+        self.reindex.end_hour = self.reindex.end_hour.min(23);
+        if self.reindex.end_hour == self.reindex.start_hour {
+            self.reindex.end_hour = (self.reindex.start_hour + 1) % 24;
+        }
+        self
+    }
+}
+
+pub fn general(conn: &Connection) -> GeneralSettings {
+    load::<GeneralSettings>(conn, SETTINGS_KEY).sanitized()
+}
+
+#[derive(Debug, Serialize, Clone, Type)]
+pub struct Settings {
+    pub general: GeneralSettings,
+    // Scoring weights, the fuzzy thresholds among them
+    pub ranking: RankingConfig,
+    pub search_budget: SearchBudget,
+    pub llm: LlmConfig,
+    // Used from the next indexing run
+    pub exclusions: ExclusionRules,
+    // Case folding for matching and the order of sorted lists
+    pub locale: LocaleConfig,
+}
+
+// The sections to change; the others are left as they are
+#[derive(Debug, Deserialize, Clone, Default, Type)]
+#[serde(default)]
+pub struct SettingsUpdate {
+    pub general: Option<GeneralSettings>,
+    pub ranking: Option<RankingConfig>,
+    pub search_budget: Option<SearchBudget>,
+    pub llm: Option<LlmConfig>,
+    pub exclusions: Option<ExclusionRules>,
+    pub locale: Option<LocaleConfig>,
+}
+
+pub fn create_table(conn: &Connection) -> SqlResult<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS settings (
+            key TEXT PRIMARY KEY,
+            value TEXT NOT NULL
+        )",
+        [],
+    )?;
+    Ok(())
+}
+
+// Load a setting, falling back to its default if it is missing or no longer parses
+pub fn load<T: DeserializeOwned + Default>(conn: &Connection, key: &str) -> T {
+    let stored: Option<String> = conn
+        .query_row("SELECT value FROM settings WHERE key = ?1", [key], |row| row.get(0))
+        .optional()
+        .unwrap_or(None);
+
+    match stored {
+        Some(json) => serde_json::from_str(&json).unwrap_or_else(|e| {
+            warn!("Ignoring invalid setting '{}': {}", key, e);
+            T::default()
+        }),
+        None => T::default(),
+    }
+}
+
+pub fn save<T: Serialize>(conn: &Connection, key: &str, value: &T) -> Result<(), AppError> {
+    let json = serde_json::to_string(value).map_err(|e| e.to_string())?;
+    conn.execute(
+        "INSERT INTO settings (key, value) VALUES (?1, ?2)
+         ON CONFLICT(key) DO UPDATE SET value = ?2",
+        params![key, json],
+    )?;
+    Ok(())
+}
+
+fn current(conn: &Connection) -> Settings {
+    Settings {
+        general: general(conn),
+        ranking: ranking::load(conn),
+        search_budget: planner::load(conn),
+        llm: llm::load(conn),
+        exclusions: exclusions::load(conn),
+        locale: locale::load(conn),
+    }
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn get_settings(state: State<'_, AppState>) -> Result<Settings, AppError> {
+    db::write(&state.db, |db| Ok(current(db)))
+}
+
+// Saves the given sections, cleaned up the same way their own set_ commands do, and applies
+// them to the running app
+#[tauri::command]
+#[specta::specta]
+pub async fn update_settings(update: SettingsUpdate, state: State<'_, AppState>) -> Result<Settings, AppError> {
+    let locale_changed = update.locale.is_some();
+    let settings = db::write(&state.db, |db| {
+        if let Some(general) = update.general {
+            save(db, SETTINGS_KEY, &general.sanitized())?;
+        }
+        if let Some(config) = update.ranking {
+            save(db, ranking::SETTINGS_KEY, &config.sanitized())?;
+        }
+        if let Some(budget) = update.search_budget {
+            save(db, planner::SETTINGS_KEY, &budget.sanitized())?;
+        }
+        if let Some(config) = update.llm {
+            save(db, llm::SETTINGS_KEY, &config.sanitized())?;
+        }
+        if let Some(rules) = update.exclusions {
+            save(db, exclusions::SETTINGS_KEY, &rules)?;
+        }
+        if let Some(config) = update.locale {
+            save(db, locale::SETTINGS_KEY, &config)?;
+        }
+        state.reload_settings(db)?;
+        Ok(current(db))
+    })?;
+    // Cached results were ranked and limited with the old settings
+    invalidate_search_cache(&state);
+    if locale_changed {
+        // Names in the name index were folded with the old case rules
+        name_index::sync_in_background(&state.db_path);
+    }
+    Ok(settings)
+}
@@ -0,0 +1,203 @@
+// Smart collections: named rule sets that work as virtual folders, such as "screenshots from
+// this month" or "every Cargo.toml under ~/code". Only the rules are stored. Each time a
+// collection is opened they become the same filters as `type:`, `in:`, `tag:` and `modified:`
+// tokens, so it always lists what is indexed right now.
+
+use chrono::Local;
+use rusqlite::{params, Connection, OptionalExtension, Result as SqlResult};
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use tauri::State;
+
+use crate::error::{AppError, ErrorCode};
+use crate::path_scope::PathScope;
+use crate::search_sql::SqlFilter;
+use crate::{db, escape_like, list_filtered_files, mirrors, scope_filter, tags, time_filter, AppState, FileEntry, LIKE_ESCAPE};
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq, Type)]
+#[serde(default)]
+pub struct CollectionRules {
+    // Extensions or type groups, e.g. "png" or "image"; any of them matches
+    pub types: Vec<String>,
+    // Folders to look in, `~` for the home folder; none looks in every indexed root
+    pub folders: Vec<String>,
+    // Tags the files must all have, counting auto tags as `tag:` does
+    pub tags: Vec<String>,
+    // File names, with `*` and `?` wildcards and ignoring case; any of them matches
+    pub names: Vec<String>,
+    // A `modified:` value such as "month" or "30d", measured from when the collection is opened
+    pub modified: Option<String>,
+}
+
+#[derive(Debug, Serialize, Clone, Type)]
+pub struct SmartCollection {
+    pub id: i64,
+    pub name: String,
+    pub rules: CollectionRules,
+    pub created_at: i64,
+}
+
+pub fn create_table(conn: &Connection) -> SqlResult<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS smart_collections (
+            id INTEGER PRIMARY KEY,
+            name TEXT UNIQUE NOT NULL,
+            rules TEXT NOT NULL,
+            created_at INTEGER NOT NULL
+        )",
+        [],
+    )?;
+    Ok(())
+}
+
+fn row_to_collection(row: &rusqlite::Row) -> SqlResult<SmartCollection> {
+    let rules_json: String = row.get(2)?;
+    Ok(SmartCollection {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        rules: serde_json::from_str(&rules_json).unwrap_or_default(),
+        created_at: row.get(3)?,
+    })
+}
+
+const SELECT_COLUMNS: &str = "SELECT id, name, rules, created_at FROM smart_collections";
+
+// Blank entries dropped and the rest trimmed
+fn cleaned(values: Vec<String>) -> Vec<String> {
+    values.into_iter().map(|value| value.trim().to_string()).filter(|value| !value.is_empty()).collect()
+}
+
+impl CollectionRules {
+    fn sanitized(self) -> Self {
+        Self {
+            types: cleaned(self.types),
+            folders: cleaned(self.folders),
+            tags: cleaned(self.tags).into_iter().map(|tag| tag.trim_start_matches('#').to_lowercase()).collect(),
+            names: cleaned(self.names),
+            modified: self.modified.map(|value| value.trim().to_string()).filter(|value| !value.is_empty()),
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.types.is_empty() && self.folders.is_empty() && self.tags.is_empty() && self.names.is_empty() && self.modified.is_none()
+    }
+
+    // Conditions on the `files` table, each with a leading AND, as ranked_search builds them
+    fn sql_filter(&self, state: &AppState) -> Result<SqlFilter, String> {
+        let home = dirs::home_dir();
+        let folders: Vec<String> = self
+            .folders
+            .iter()
+            .map(|folder| match (folder.strip_prefix('~'), &home) {
+                (Some(rest), Some(home)) => home.join(rest.trim_start_matches(['/', '\\'])).to_string_lossy().to_string(),
+                _ => folder.clone(),
+            })
+            .collect();
+        let scope = (!folders.is_empty()).then(|| PathScope::under_any(folders.iter().map(String::as_str)));
+        let mut sql_filter = scope_filter(mirrors::RootScope::Primary, scope.as_ref());
+
+        {
+            let groups = state.extension_groups.read().map_err(|e| e.to_string())?;
+            if !self.types.is_empty() {
+                let (_, type_filter) = groups.extract_type_filter(&format!("type:{}", self.types.join(",")));
+                if let Some(filter) = type_filter {
+                    sql_filter.and(&filter.sql_clause());
+                }
+            }
+            let tag_tokens: Vec<String> = self.tags.iter().map(|tag| format!("tag:{}", tag)).collect();
+            if let (_, Some(filter)) = tags::extract_tag_filter(&tag_tokens.join(" "), &groups) {
+                sql_filter.and(&filter.sql_clause());
+            }
+        }
+
+        if !self.names.is_empty() {
+            let conditions: Vec<String> = self
+                .names
+                .iter()
+                .map(|pattern| {
+                    let like = escape_like(pattern).replace('*', "%").replace('?', "_").replace('\'', "''");
+                    format!("name LIKE '{}' {}", like, LIKE_ESCAPE)
+                })
+                .collect();
+            sql_filter.and(&format!("({})", conditions.join(" OR ")));
+        }
+
+        if let Some(value) = &self.modified {
+            let filter = time_filter::parse_value(value, Local::now())
+                .ok_or_else(|| format!("Unknown date window '{}'. Try {}", value, time_filter::SYNTAX))?;
+            sql_filter.and_bound(filter.sql_clause());
+        }
+        Ok(sql_filter)
+    }
+}
+
+// Saving under an existing name replaces that collection's rules
+#[tauri::command]
+#[specta::specta]
+pub async fn create_smart_collection(
+    name: String,
+    rules: CollectionRules,
+    state: State<'_, AppState>,
+) -> Result<SmartCollection, AppError> {
+    let name = name.trim().to_string();
+    if name.is_empty() {
+        return Err(AppError::new(ErrorCode::InvalidInput, "Collection name cannot be empty"));
+    }
+    let rules = rules.sanitized();
+    if rules.is_empty() {
+        return Err(AppError::new(ErrorCode::InvalidInput, "A collection needs at least one rule"));
+    }
+    // Catches unknown date windows before they are saved
+    rules.sql_filter(&state)?;
+    let rules_json = serde_json::to_string(&rules).map_err(|e| e.to_string())?;
+
+    db::write(&state.db, |db| {
+        db.execute(
+            "INSERT INTO smart_collections (name, rules, created_at) VALUES (?1, ?2, ?3)
+             ON CONFLICT(name) DO UPDATE SET rules = ?2",
+            params![name, rules_json, chrono::Utc::now().timestamp()],
+        )?;
+
+        db.query_row(&format!("{} WHERE name = ?1", SELECT_COLUMNS), [&name], row_to_collection)
+            .map_err(AppError::from)
+    })
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn list_smart_collections(state: State<'_, AppState>) -> Result<Vec<SmartCollection>, AppError> {
+    db::write(&state.db, |db| {
+        let mut stmt = db
+            .prepare(&format!("{} ORDER BY name COLLATE LOCALE", SELECT_COLUMNS))?;
+        let collections = stmt
+            .query_map([], row_to_collection)?
+            .filter_map(|r| r.ok())
+            .collect();
+        Ok(collections)
+    })
+}
+
+// The files a collection holds right now, most recently modified first
+#[tauri::command]
+#[specta::specta]
+pub async fn evaluate_smart_collection(id: i64, state: State<'_, AppState>) -> Result<Vec<FileEntry>, AppError> {
+    let collection = db::write(&state.db, |db| {
+        db.query_row(&format!("{} WHERE id = ?1", SELECT_COLUMNS), [id], row_to_collection)
+            .optional()?
+            .ok_or_else(|| AppError::new(ErrorCode::NotFound, format!("Smart collection {} not found", id)))
+    })?; // Release the lock before listing
+    db::blocking(|| {
+        let sql_filter = collection.rules.sql_filter(&state)?;
+        Ok(list_filtered_files(&sql_filter, &state)?)
+    })
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn delete_smart_collection(id: i64, state: State<'_, AppState>) -> Result<bool, AppError> {
+    db::write(&state.db, |db| {
+        let deleted = db
+            .execute("DELETE FROM smart_collections WHERE id = ?1", [id])?;
+        Ok(deleted > 0)
+    })
+}
@@ -0,0 +1,339 @@
+// Disaster-recovery snapshots of user data (favorites, tags, notes, saved searches, pinned
+// results, settings, Open With choices). The index itself can always be rebuilt, so only what the user curated is
+// exported, as one JSON file per snapshot. A background thread writes one every `interval_days` and keeps the newest
+// `keep` files; restore replaces the user tables with a snapshot's contents.
+
+use rusqlite::types::{Value as SqlValue, ValueRef};
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use specta::Type;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tauri::State;
+use tracing::{info, warn};
+
+use crate::error::{AppError, ErrorCode};
+use crate::{db, incremental, settings, AppState};
+
+pub const SETTINGS_KEY: &str = "snapshots";
+
+// User data tables, in restore order (tags before the file_tags that reference them)
+const TABLES: &[&str] = &["settings", "favorite_groups", "favorite_files", "tags", "file_tags", "file_notes", "saved_searches", "smart_collections", "pinned_results", "open_with_prefs"];
+
+const FILE_PREFIX: &str = "file-finder-snapshot-";
+const FORMAT_VERSION: i64 = 1;
+
+// How often the scheduler checks whether a snapshot is due
+const CHECK_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Type)]
+#[serde(default)]
+pub struct SnapshotConfig {
+    pub enabled: bool,
+    // Folder to write snapshots to, None for `snapshots` next to the database
+    pub folder: Option<String>,
+    pub interval_days: u32,
+    // Older snapshots beyond this many are deleted
+    pub keep: u32,
+}
+
+impl Default for SnapshotConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            folder: None,
+            interval_days: 7,
+            keep: 8,
+        }
+    }
+}
+
+impl SnapshotConfig {
+    fn sanitized(mut self) -> Self {
+        self.folder = self.folder.map(|f| f.trim().to_string()).filter(|f| !f.is_empty());
+        self.interval_days = self.interval_days.clamp(1, 365);
+        self.keep = self.keep.clamp(1, 100);
+        self
+    }
+
+    fn folder(&self) -> PathBuf {
+        match &self.folder {
+            Some(folder) => PathBuf::from(folder),
+            None => dirs::data_local_dir()
+                .unwrap_or_else(|| PathBuf::from("."))
+                .join("file-finder")
+                .join("snapshots"),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Clone, Type)]
+pub struct SnapshotInfo {
+    pub path: String,
+    pub created_at: i64,
+    pub size: u64,
+}
+
+#[derive(Serialize, Deserialize)]
+struct SnapshotFile {
+    version: i64,
+    created_at: i64,
+    // Table name -> rows as column/value objects
+    tables: Map<String, Value>,
+}
+
+fn sql_to_json(value: ValueRef) -> Value {
+    match value {
+        ValueRef::Null => Value::Null,
+        ValueRef::Integer(i) => Value::from(i),
+        ValueRef::Real(f) => Value::from(f),
+        ValueRef::Text(text) => Value::from(String::from_utf8_lossy(text).to_string()),
+        ValueRef::Blob(bytes) => Value::from(bytes.to_vec()),
+    }
+}
+
+fn json_to_sql(value: &Value) -> SqlValue {
+    match value {
+        Value::Null => SqlValue::Null,
+        Value::Bool(b) => SqlValue::Integer(*b as i64),
+        Value::Number(n) => match n.as_i64() {
+            Some(i) => SqlValue::Integer(i),
+            None => SqlValue::Real(n.as_f64().unwrap_or(0.0)),
+        },
+        Value::String(s) => SqlValue::Text(s.clone()),
+        Value::Array(items) => SqlValue::Blob(items.iter().filter_map(|b| b.as_u64()).map(|b| b as u8).collect()),
+        Value::Object(_) => SqlValue::Text(value.to_string()),
+    }
+}
+
+fn dump_table(conn: &Connection, table: &str) -> Result<Value, String> {
+    let mut stmt = conn.prepare(&format!("SELECT * FROM {}", table)).map_err(|e| e.to_string())?;
+    let columns: Vec<String> = stmt.column_names().into_iter().map(String::from).collect();
+    let rows = stmt
+        .query_map([], |row| {
+            let mut object = Map::new();
+            for (i, column) in columns.iter().enumerate() {
+                object.insert(column.clone(), sql_to_json(row.get_ref(i)?));
+            }
+            Ok(Value::Object(object))
+        })
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+    Ok(Value::Array(rows))
+}
+
+// Snapshot files sorted oldest first; the timestamp in the name orders them
+fn snapshot_files(folder: &Path) -> Vec<PathBuf> {
+    let Ok(entries) = fs::read_dir(folder) else {
+        return vec![];
+    };
+    let mut files: Vec<PathBuf> = entries
+        .filter_map(|e| e.ok())
+        .map(|e| e.path())
+        .filter(|path| {
+            path.file_name()
+                .and_then(|n| n.to_str())
+                .is_some_and(|n| n.starts_with(FILE_PREFIX) && n.ends_with(".json"))
+        })
+        .collect();
+    files.sort();
+    files
+}
+
+fn created_at(path: &Path) -> Option<i64> {
+    let stamp = path.file_stem()?.to_str()?.strip_prefix(FILE_PREFIX)?;
+    chrono::NaiveDateTime::parse_from_str(stamp, "%Y%m%d-%H%M%S")
+        .ok()
+        .map(|date| date.and_utc().timestamp())
+}
+
+// Write a snapshot of the user tables and prune old ones. Returns the new file.
+fn export(conn: &Connection, config: &SnapshotConfig) -> Result<PathBuf, String> {
+    let mut tables = Map::new();
+    for table in TABLES {
+        tables.insert(table.to_string(), dump_table(conn, table)?);
+    }
+    let now = chrono::Utc::now();
+    let snapshot = SnapshotFile {
+        version: FORMAT_VERSION,
+        created_at: now.timestamp(),
+        tables,
+    };
+
+    let folder = config.folder();
+    fs::create_dir_all(&folder).map_err(|e| e.to_string())?;
+    let path = folder.join(format!("{}{}.json", FILE_PREFIX, now.format("%Y%m%d-%H%M%S")));
+    // Written next to the target and renamed, so a crash never leaves a truncated snapshot
+    let partial = path.with_extension("json.partial");
+    let json = serde_json::to_vec_pretty(&snapshot).map_err(|e| e.to_string())?;
+    fs::write(&partial, json).map_err(|e| e.to_string())?;
+    fs::rename(&partial, &path).map_err(|e| e.to_string())?;
+
+    let files = snapshot_files(&folder);
+    let excess = files.len().saturating_sub(config.keep as usize);
+    for old in &files[..excess] {
+        if let Err(e) = fs::remove_file(old) {
+            warn!("Failed to remove old snapshot {}: {}", old.display(), e);
+        }
+    }
+
+    info!("Wrote snapshot {}", path.display());
+    Ok(path)
+}
+
+fn due(config: &SnapshotConfig) -> bool {
+    let interval = i64::from(config.interval_days) * 24 * 60 * 60;
+    let newest = snapshot_files(&config.folder()).last().and_then(|path| created_at(path));
+    newest.is_none_or(|at| chrono::Utc::now().timestamp() - at >= interval)
+}
+
+// Background scheduler with its own connection; it rereads the config on every check
+pub fn schedule(db_path: &Path) {
+    let db_path = db_path.to_path_buf();
+    std::thread::spawn(move || loop {
+        let result = db::open(&db_path).map_err(|e| e.to_string()).and_then(|conn| {
+            let config = settings::load::<SnapshotConfig>(&conn, SETTINGS_KEY).sanitized();
+            if config.enabled && due(&config) {
+                export(&conn, &config)?;
+            }
+            Ok(())
+        });
+        if let Err(e) = result {
+            warn!("Scheduled snapshot failed: {}", e);
+        }
+        std::thread::sleep(CHECK_INTERVAL);
+    });
+}
+
+// Replace the user tables with the snapshot's rows. Columns the current schema doesn't have
+// are ignored, so snapshots from older versions still restore.
+fn restore(conn: &mut Connection, snapshot: &SnapshotFile) -> Result<(), String> {
+    if snapshot.version > FORMAT_VERSION {
+        return Err("This snapshot was written by a newer version of the app".to_string());
+    }
+
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+    for table in TABLES {
+        let Some(Value::Array(rows)) = snapshot.tables.get(*table) else {
+            continue;
+        };
+        let columns: Vec<String> = {
+            let mut stmt = tx.prepare(&format!("PRAGMA table_info({})", table)).map_err(|e| e.to_string())?;
+            let names = stmt
+                .query_map([], |row| row.get(1))
+                .map_err(|e| e.to_string())?
+                .filter_map(|r| r.ok())
+                .collect();
+            names
+        };
+
+        tx.execute(&format!("DELETE FROM {}", table), []).map_err(|e| e.to_string())?;
+        for row in rows {
+            let Value::Object(row) = row else {
+                continue;
+            };
+            let (names, values): (Vec<&String>, Vec<SqlValue>) = row
+                .iter()
+                .filter(|(column, _)| columns.contains(column))
+                .map(|(column, value)| (column, json_to_sql(value)))
+                .unzip();
+            if names.is_empty() {
+                continue;
+            }
+            let sql = format!(
+                "INSERT OR REPLACE INTO {} ({}) VALUES ({})",
+                table,
+                names.iter().map(|name| format!("\"{}\"", name)).collect::<Vec<_>>().join(", "),
+                (1..=names.len()).map(|i| format!("?{}", i)).collect::<Vec<_>>().join(", ")
+            );
+            tx.execute(&sql, rusqlite::params_from_iter(values)).map_err(|e| e.to_string())?;
+        }
+    }
+    tx.commit().map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn get_snapshot_config(state: State<'_, AppState>) -> Result<SnapshotConfig, AppError> {
+    db::write(&state.db, |db| Ok(settings::load::<SnapshotConfig>(db, SETTINGS_KEY).sanitized()))
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn set_snapshot_config(config: SnapshotConfig, state: State<'_, AppState>) -> Result<SnapshotConfig, AppError> {
+    let config = config.sanitized();
+    db::write(&state.db, |db| {
+        settings::save(db, SETTINGS_KEY, &config)?;
+        Ok(config)
+    })
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn list_snapshots(state: State<'_, AppState>) -> Result<Vec<SnapshotInfo>, AppError> {
+    let config = db::write(&state.db, |db| Ok(settings::load::<SnapshotConfig>(db, SETTINGS_KEY).sanitized()))?;
+    let mut snapshots: Vec<SnapshotInfo> = snapshot_files(&config.folder())
+        .into_iter()
+        .filter_map(|path| {
+            Some(SnapshotInfo {
+                created_at: created_at(&path)?,
+                size: fs::metadata(&path).ok()?.len(),
+                path: path.to_string_lossy().to_string(),
+            })
+        })
+        .collect();
+    snapshots.reverse();
+    Ok(snapshots)
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn create_snapshot(state: State<'_, AppState>) -> Result<SnapshotInfo, AppError> {
+    db::write(&state.db, |db| {
+        let config = settings::load::<SnapshotConfig>(db, SETTINGS_KEY).sanitized();
+        let path = export(db, &config)?;
+        Ok(SnapshotInfo {
+            created_at: created_at(&path).unwrap_or_default(),
+            size: fs::metadata(&path).map(|m| m.len()).unwrap_or(0),
+            path: path.to_string_lossy().to_string(),
+        })
+    })
+}
+
+// Restore a snapshot (the newest one if no path is given). The current data is snapshotted
+// first, so a restore can itself be undone.
+#[tauri::command]
+#[specta::specta]
+pub async fn restore_snapshot(path: Option<String>, state: State<'_, AppState>) -> Result<SnapshotInfo, AppError> {
+    let (source, created_at) = db::write(&state.db, |db| {
+        let config = settings::load::<SnapshotConfig>(db, SETTINGS_KEY).sanitized();
+        let source = match path {
+            Some(path) => PathBuf::from(path),
+            None => snapshot_files(&config.folder())
+                .pop()
+                .ok_or_else(|| AppError::new(ErrorCode::NotFound, format!("No snapshots in {}", config.folder().display())))?,
+        };
+        let snapshot: SnapshotFile = serde_json::from_slice(&fs::read(&source)?)
+            .map_err(|e| format!("Not a valid snapshot: {}", e))?;
+
+        let backup = export(db, &config)?;
+        info!("Saved current data to {} before restoring", backup.display());
+        restore(db, &snapshot)?;
+
+        // Settings were replaced too, so reload the copies kept in memory
+        state.reload_settings(db)?;
+        Ok((source, snapshot.created_at))
+    })?;
+    state.search_cache.write().map_err(|e| e.to_string())?.clear();
+    incremental::clear(&state.last_candidates);
+
+    info!("Restored snapshot {}", source.display());
+    Ok(SnapshotInfo {
+        created_at,
+        size: fs::metadata(&source).map(|m| m.len()).unwrap_or(0),
+        path: source.to_string_lossy().to_string(),
+    })
+}
@@ -0,0 +1,223 @@
+// "What is this file?" summaries. The start of a text, code or PDF file goes to the configured
+// LLM, which answers with a short summary and the key entities it mentions. Answers are cached
+// per path and modification time, so asking again is free until the file changes. Streamed
+// summaries arrive as `summary-chunk` events while the model writes them.
+
+use rusqlite::{params, Connection, OptionalExtension, Result as SqlResult};
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use std::path::Path;
+use tauri::{AppHandle, Emitter, State};
+use tracing::warn;
+
+use crate::error::{AppError, ErrorCode};
+use crate::{db, llm, text_extract, AppState};
+
+// Characters of the file sent to the model; about 3k tokens
+const INPUT_CHARS: usize = 12_000;
+const MAX_ENTITIES: usize = 8;
+
+pub const CHUNK_EVENT: &str = "summary-chunk";
+
+// Payload of a summary-chunk event: the next piece of the summary text for `path`
+#[derive(Debug, Serialize, Clone)]
+pub struct SummaryChunk {
+    pub path: String,
+    pub text: String,
+}
+
+#[derive(Debug, Serialize, Clone, Type)]
+pub struct FileSummary {
+    pub path: String,
+    pub summary: String,
+    // People, organizations, products, places, dates and identifiers the file mentions
+    pub entities: Vec<String>,
+    // Only the start of the file was summarized
+    pub truncated: bool,
+    // Served from the cache rather than asked now
+    pub cached: bool,
+}
+
+#[derive(Deserialize)]
+struct SummaryReply {
+    summary: String,
+    #[serde(default)]
+    entities: Vec<String>,
+}
+
+pub fn create_table(conn: &Connection) -> SqlResult<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS file_summaries (
+            path TEXT PRIMARY KEY,
+            modified_at INTEGER,
+            model TEXT NOT NULL,
+            summary TEXT NOT NULL,
+            entities TEXT NOT NULL,
+            truncated INTEGER NOT NULL,
+            created_at INTEGER NOT NULL
+        )",
+        [],
+    )?;
+    Ok(())
+}
+
+fn cached_summary(conn: &Connection, path: &str, modified_at: Option<i64>, model: &str) -> SqlResult<Option<FileSummary>> {
+    conn.query_row(
+        "SELECT summary, entities, truncated FROM file_summaries
+         WHERE path = ?1 AND modified_at IS ?2 AND model = ?3",
+        params![path, modified_at, model],
+        |row| {
+            let entities: String = row.get(1)?;
+            Ok(FileSummary {
+                path: path.to_string(),
+                summary: row.get(0)?,
+                entities: serde_json::from_str(&entities).unwrap_or_default(),
+                truncated: row.get(2)?,
+                cached: true,
+            })
+        },
+    )
+    .optional()
+}
+
+fn prompt(name: &str, text: &str, truncated: bool) -> String {
+    format!(
+        "Summarize the file below in two or three sentences for someone deciding whether to open it. \
+         Also list up to {} key entities it mentions (people, organizations, products, places, dates, identifiers). \
+         Answer with JSON only, in the form {{\"summary\": \"...\", \"entities\": [\"...\"]}}.\n\n\
+         File name: {}{}\n---\n{}",
+        MAX_ENTITIES,
+        name,
+        if truncated { " (only the beginning is shown)" } else { "" },
+        text
+    )
+}
+
+// The part of the "summary" string that has arrived so far in a JSON reply being streamed.
+// Stops before an escape sequence that is still incomplete, so the result only ever grows.
+fn partial_summary(reply: &str) -> Option<String> {
+    let after_key = &reply[reply.find("\"summary\"")? + "\"summary\"".len()..];
+    let value = after_key.trim_start().strip_prefix(':')?.trim_start().strip_prefix('"')?;
+    let mut summary = String::new();
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' => break,
+            '\\' => match chars.next() {
+                Some('n') => summary.push('\n'),
+                Some('t') => summary.push('\t'),
+                Some('r') | Some('b') | Some('f') => {}
+                Some('u') => {
+                    let hex: String = chars.by_ref().take(4).collect();
+                    match u32::from_str_radix(&hex, 16).ok().filter(|_| hex.len() == 4) {
+                        Some(code) => summary.push(char::from_u32(code).unwrap_or('\u{FFFD}')),
+                        None => break,
+                    }
+                }
+                Some(other) => summary.push(other),
+                None => break,
+            },
+            c => summary.push(c),
+        }
+    }
+    Some(summary)
+}
+
+// Models wrap JSON in prose or code fences now and then; fall back to the whole reply as the summary
+fn parse_reply(reply: &str) -> (String, Vec<String>) {
+    let json = match (reply.find('{'), reply.rfind('}')) {
+        (Some(start), Some(end)) if start < end => serde_json::from_str::<SummaryReply>(&reply[start..=end]).ok(),
+        _ => None,
+    };
+    match json {
+        Some(parsed) => {
+            let mut entities: Vec<String> = Vec::new();
+            for entity in parsed.entities.into_iter().map(|e| e.trim().to_string()).filter(|e| !e.is_empty()) {
+                if !entities.iter().any(|seen| seen.eq_ignore_ascii_case(&entity)) {
+                    entities.push(entity);
+                }
+            }
+            entities.truncate(MAX_ENTITIES);
+            (parsed.summary.trim().to_string(), entities)
+        }
+        None => (reply.trim().to_string(), vec![]),
+    }
+}
+
+// Short summary and key entities of a text, code or PDF file, from the configured LLM. With
+// `stream`, the summary text is also sent as summary-chunk events while it is written; cached
+// summaries are returned without events.
+#[tauri::command]
+#[specta::specta]
+pub async fn summarize_file(
+    path: String,
+    stream: Option<bool>,
+    app: AppHandle,
+    state: State<'_, AppState>,
+) -> Result<FileSummary, AppError> {
+    let config = state.llm.read().map_err(|e| e.to_string())?.clone();
+    if !config.enabled {
+        return Err(AppError::new(ErrorCode::LlmDisabled, "Summaries need the LLM backend; enable it in the LLM settings"));
+    }
+    let file = Path::new(&path);
+    if !file.is_file() {
+        return Err(AppError::new(ErrorCode::NotFound, format!("File not found: {}", path)));
+    }
+
+    let modified = text_extract::modified_at(file);
+    if let Some(summary) = db::write(&state.db, |db| Ok(cached_summary(db, &path, modified, &config.model)?))? {
+        return Ok(summary);
+    }
+
+    let extracted = text_extract::read_text(file, INPUT_CHARS)?;
+    let name = file.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_else(|| path.clone());
+    let prompt = prompt(&name, &extracted.text, extracted.truncated);
+    let reply = if stream.unwrap_or(false) {
+        let mut reply = String::new();
+        let mut sent = 0;
+        llm::generate_stream(&config, &prompt, config.request_timeout(), |piece| {
+            reply.push_str(piece);
+            let Some(summary) = partial_summary(&reply) else {
+                return;
+            };
+            if summary.len() > sent {
+                let chunk = SummaryChunk { path: path.clone(), text: summary[sent..].to_string() };
+                sent = summary.len();
+                if let Err(e) = app.emit(CHUNK_EVENT, chunk) {
+                    warn!("Failed to send {} event: {}", CHUNK_EVENT, e);
+                }
+            }
+        })
+        .await?
+    } else {
+        llm::generate(&config, &prompt, config.request_timeout()).await?
+    };
+    let (summary, entities) = parse_reply(&reply);
+    if summary.is_empty() {
+        return Err("The LLM returned an empty summary".into());
+    }
+
+    db::write(&state.db, |db| {
+        db.execute(
+            "INSERT OR REPLACE INTO file_summaries (path, modified_at, model, summary, entities, truncated, created_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            params![
+                path,
+                modified,
+                config.model,
+                summary,
+                serde_json::to_string(&entities).map_err(|e| e.to_string())?,
+                extracted.truncated,
+                chrono::Utc::now().timestamp()
+            ],
+        )?;
+
+        Ok(FileSummary {
+            path,
+            summary,
+            entities,
+            truncated: extracted.truncated,
+            cached: false,
+        })
+    })
+}
@@ -0,0 +1,404 @@
+// File tags. Tags are stored once in `tags` and attached to paths in `file_tags`, with the
+// source recording how each one got there (added by hand, or imported from a folder structure).
+// Searches can filter on them, and on the categorizer's auto tags and categories, with
+// `tag:` (or `#`) and `category:` tokens. Renames and moves carry a file's tags along.
+
+use rusqlite::{params, Connection, Result as SqlResult};
+use serde::Serialize;
+use specta::Type;
+use std::collections::HashMap;
+use std::path::Path;
+use tauri::State;
+use tracing::info;
+
+use crate::error::{AppError, ErrorCode};
+use crate::file_types::ExtensionGroups;
+use crate::{assign_age_buckets, db, invalidate_search_cache, locale, mirrors, AppState, FileEntry};
+
+pub const SOURCE_IMPORT: &str = "import";
+pub const SOURCE_MANUAL: &str = "manual";
+
+#[derive(Debug, Serialize, Clone, Default, Type)]
+pub struct TagImportSummary {
+    pub files_tagged: i64,
+    pub tags_created: i64,
+    pub tags_applied: i64,
+}
+
+#[derive(Debug, Serialize, Clone, PartialEq, Type)]
+#[serde(rename_all = "snake_case")]
+pub enum FacetKind {
+    // Manual, imported and auto tags; searched with `tag:`
+    Tag,
+    // Primary and secondary categories; searched with `category:`
+    Category,
+}
+
+#[derive(Debug, Serialize, Clone, Type)]
+pub struct TagFacet {
+    pub name: String,
+    pub kind: FacetKind,
+    // Number of files that have it
+    pub files: i64,
+}
+
+// `tag:` and `category:` tokens of a query. Every token must match; commas inside a token
+// are alternatives, as with `type:`.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct TagFilter {
+    tags: Vec<Vec<String>>,
+    categories: Vec<Vec<String>>,
+}
+
+fn sql_list(values: &[String]) -> String {
+    values
+        .iter()
+        .map(|value| format!("'{}'", value.replace('\'', "''")))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+impl TagFilter {
+    // One line per token, e.g. "tag: 2023 or 2024", for explain_search
+    pub fn describe(&self) -> Vec<String> {
+        let tags = self.tags.iter().map(|alternatives| format!("tag: {}", alternatives.join(" or ")));
+        let categories = self.categories.iter().map(|alternatives| format!("category: {}", alternatives.join(" or ")));
+        tags.chain(categories).collect()
+    }
+
+    // SQL condition on the `files` table; values are inlined as quoted literals
+    pub fn sql_clause(&self) -> String {
+        let mut conditions = Vec::new();
+        for alternatives in &self.tags {
+            let list = sql_list(alternatives);
+            conditions.push(format!(
+                "(EXISTS (SELECT 1 FROM file_tags ft JOIN tags t ON t.id = ft.tag_id WHERE ft.path = files.path AND t.name IN ({0}))
+                  OR EXISTS (SELECT 1 FROM file_auto_tags a WHERE a.path = files.path AND a.tag IN ({0})))",
+                list
+            ));
+        }
+        for alternatives in &self.categories {
+            conditions.push(format!(
+                "EXISTS (SELECT 1 FROM file_categories c WHERE c.path = files.path
+                  AND (c.primary_category IN ({0}) OR c.secondary_category IN ({0})))",
+                sql_list(alternatives)
+            ));
+        }
+        format!("({})", conditions.join(" AND "))
+    }
+}
+
+// `#name` is short for `tag:name`. Names with another `#` in them are left alone, so
+// editor backups like `#notes.txt#` can still be searched for.
+fn hash_tag(token: &str) -> Option<&str> {
+    token.strip_prefix('#').filter(|name| !name.is_empty() && !name.contains('#'))
+}
+
+// Split `tag:`, `#tag` and `category:` tokens out of a query. Category names go through the
+// same aliases as `type:`, so `category:Documents` finds the "doc" group.
+pub fn extract_tag_filter(query: &str, groups: &ExtensionGroups) -> (String, Option<TagFilter>) {
+    let mut filter = TagFilter::default();
+    let mut rest: Vec<&str> = Vec::new();
+
+    for token in query.split_whitespace() {
+        let lowered = token.to_lowercase();
+        let tag_value = lowered.strip_prefix("tag:").or_else(|| hash_tag(&lowered));
+        if let Some(value) = tag_value.filter(|v| !v.is_empty()) {
+            // Auto tags are stored lowercase and manual tag names compare case-insensitively
+            filter.tags.push(value.split(',').filter(|v| !v.is_empty()).map(str::to_string).collect());
+        } else if let Some(value) = lowered.strip_prefix("category:").filter(|v| !v.is_empty()) {
+            let mut names: Vec<String> = Vec::new();
+            for name in value.split(',').filter(|v| !v.is_empty()) {
+                for candidate in [name.to_string(), groups.canonical_name(name)] {
+                    if !names.contains(&candidate) {
+                        names.push(candidate);
+                    }
+                }
+            }
+            filter.categories.push(names);
+        } else {
+            rest.push(token);
+        }
+    }
+
+    if filter == TagFilter::default() {
+        return (query.to_string(), None);
+    }
+    (rest.join(" "), Some(filter))
+}
+
+pub fn create_tables(conn: &Connection) -> SqlResult<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS tags (
+            id INTEGER PRIMARY KEY,
+            name TEXT UNIQUE NOT NULL COLLATE NOCASE,
+            created_at INTEGER NOT NULL
+        )",
+        [],
+    )?;
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS file_tags (
+            path TEXT NOT NULL,
+            tag_id INTEGER NOT NULL REFERENCES tags(id) ON DELETE CASCADE,
+            source TEXT NOT NULL,
+            added_at INTEGER NOT NULL,
+            PRIMARY KEY (path, tag_id)
+        )",
+        [],
+    )?;
+    conn.execute("CREATE INDEX IF NOT EXISTS idx_file_tags_tag ON file_tags(tag_id)", [])?;
+    Ok(())
+}
+
+// Folder names make tags as they are, apart from surrounding whitespace
+fn normalize_tag(name: &str) -> Option<String> {
+    let name = name.trim();
+    if name.is_empty() {
+        None
+    } else {
+        Some(name.to_string())
+    }
+}
+
+// Names typed by hand have to work as `#name` search tokens, so they can't hold whitespace,
+// commas (alternatives in `tag:a,b`) or `#`
+fn validate_manual_tag(name: &str) -> Result<String, String> {
+    let name = name.trim().trim_start_matches('#');
+    if name.is_empty() {
+        return Err("Tag name cannot be empty".to_string());
+    }
+    if name.chars().any(|c| c.is_whitespace() || c == ',' || c == '#') {
+        return Err(format!("Tag names can't contain spaces, commas or #: {}", name));
+    }
+    Ok(name.to_string())
+}
+
+// Id of the tag with this name (case-insensitive), creating it if needed. The flag is true if created.
+fn ensure_tag(conn: &Connection, name: &str, now: i64) -> SqlResult<(i64, bool)> {
+    let created = conn.execute(
+        "INSERT OR IGNORE INTO tags (name, created_at) VALUES (?1, ?2)",
+        params![name, now],
+    )? > 0;
+    let id = conn.query_row("SELECT id FROM tags WHERE name = ?1", [name], |row| row.get(0))?;
+    Ok((id, created))
+}
+
+// Turn a curated folder hierarchy into tags: every directory level between `root` and an
+// indexed file becomes a tag on that file. Existing tags on a file are kept.
+#[tauri::command]
+#[specta::specta]
+pub async fn import_structure_as_tags(root: String, state: State<'_, AppState>) -> Result<TagImportSummary, AppError> {
+    let root_path = Path::new(&root);
+    if !root_path.is_dir() {
+        return Err(AppError::new(ErrorCode::InvalidInput, format!("Not a folder: {}", root)));
+    }
+
+    let summary = db::write(&state.db, |db| {
+        let files: Vec<String> = {
+            let mut stmt = db
+                .prepare("SELECT path FROM files WHERE substr(path, 1, length(?1)) = ?1")?;
+            let rows = stmt
+                .query_map([&root], |row| row.get(0))?
+                .filter_map(|r| r.ok())
+                .collect();
+            rows
+        };
+        if files.is_empty() {
+            return Err(AppError::new(ErrorCode::IndexEmpty, format!("No indexed files under {}. Index the folder first.", root)));
+        }
+
+        let now = chrono::Utc::now().timestamp();
+        let mut summary = TagImportSummary::default();
+        let mut tag_ids: HashMap<String, i64> = HashMap::new();
+
+        let tx = db.transaction()?;
+        for file in &files {
+            // The prefix match above also catches siblings like "/photos2" for root "/photos"
+            let Ok(relative) = Path::new(file).strip_prefix(root_path) else {
+                continue;
+            };
+            let Some(parent) = relative.parent() else {
+                continue;
+            };
+
+            let mut tagged = false;
+            for component in parent.components() {
+                let Some(tag) = normalize_tag(&component.as_os_str().to_string_lossy()) else {
+                    continue;
+                };
+                let tag_id = match tag_ids.get(&tag.to_lowercase()) {
+                    Some(id) => *id,
+                    None => {
+                        let (id, created) = ensure_tag(&tx, &tag, now)?;
+                        if created {
+                            summary.tags_created += 1;
+                        }
+                        tag_ids.insert(tag.to_lowercase(), id);
+                        id
+                    }
+                };
+                let applied = tx
+                    .execute(
+                        "INSERT OR IGNORE INTO file_tags (path, tag_id, source, added_at) VALUES (?1, ?2, ?3, ?4)",
+                        params![file, tag_id, SOURCE_IMPORT, now],
+                    )?;
+                if applied > 0 {
+                    summary.tags_applied += 1;
+                    tagged = true;
+                }
+            }
+            if tagged {
+                summary.files_tagged += 1;
+            }
+        }
+        tx.commit()?;
+        Ok(summary)
+    })?;
+    invalidate_search_cache(&state);
+
+    info!(
+        "Imported folder tags under {}: {} tags on {} files ({} new tags)",
+        root, summary.tags_applied, summary.files_tagged, summary.tags_created
+    );
+    Ok(summary)
+}
+
+// Tags and categories with how many files have each, most used first, for filter chips
+#[tauri::command]
+#[specta::specta]
+pub async fn list_tags(state: State<'_, AppState>) -> Result<Vec<TagFacet>, AppError> {
+    db::write(&state.db, |db| {
+        let queries = [
+            (
+                FacetKind::Tag,
+                "SELECT MIN(name), COUNT(DISTINCT path) FROM (
+                    SELECT t.name AS name, ft.path AS path FROM file_tags ft JOIN tags t ON t.id = ft.tag_id
+                    UNION ALL SELECT tag, path FROM file_auto_tags
+                 ) GROUP BY lower(name)",
+            ),
+            (
+                FacetKind::Category,
+                "SELECT name, COUNT(DISTINCT path) FROM (
+                    SELECT primary_category AS name, path FROM file_categories
+                    UNION ALL SELECT secondary_category, path FROM file_categories WHERE secondary_category IS NOT NULL
+                 ) GROUP BY name",
+            ),
+        ];
+
+        let mut facets = Vec::new();
+        for (kind, sql) in queries {
+            let mut stmt = db.prepare(sql)?;
+            let rows = stmt
+                .query_map([], |row| {
+                    Ok(TagFacet {
+                        name: row.get(0)?,
+                        kind: kind.clone(),
+                        files: row.get(1)?,
+                    })
+                })?
+                .filter_map(|r| r.ok());
+            facets.extend(rows);
+        }
+        facets.sort_by(|a, b| b.files.cmp(&a.files).then_with(|| locale::compare(&a.name, &b.name)));
+        Ok(facets)
+    })
+}
+
+// Tag a file by hand. Returns false if it already had the tag.
+#[tauri::command]
+#[specta::specta]
+pub async fn add_tag(path: String, tag: String, state: State<'_, AppState>) -> Result<bool, AppError> {
+    let tag = validate_manual_tag(&tag)?;
+    let added = db::write(&state.db, |db| {
+        let now = chrono::Utc::now().timestamp();
+        let (tag_id, _) = ensure_tag(db, &tag, now)?;
+        let inserted = db.execute(
+            "INSERT OR IGNORE INTO file_tags (path, tag_id, source, added_at) VALUES (?1, ?2, ?3, ?4)",
+            params![path, tag_id, SOURCE_MANUAL, now],
+        )?;
+        Ok(inserted > 0)
+    })?;
+    if added {
+        invalidate_search_cache(&state);
+    }
+    Ok(added)
+}
+
+// Take a tag off a file, however it got there. A tag left on no file is deleted.
+#[tauri::command]
+#[specta::specta]
+pub async fn remove_tag(path: String, tag: String, state: State<'_, AppState>) -> Result<bool, AppError> {
+    let tag = tag.trim().trim_start_matches('#');
+    let removed = db::write(&state.db, |db| {
+        let removed = db
+            .execute(
+                "DELETE FROM file_tags WHERE path = ?1 AND tag_id = (SELECT id FROM tags WHERE name = ?2)",
+                params![path, tag],
+            )?
+            > 0;
+        db.execute(
+            "DELETE FROM tags WHERE name = ?1 AND NOT EXISTS (SELECT 1 FROM file_tags WHERE tag_id = tags.id)",
+            [tag],
+        )?;
+        Ok(removed)
+    })?;
+    if removed {
+        invalidate_search_cache(&state);
+    }
+    Ok(removed)
+}
+
+// Manual and imported tags of one file, by name
+#[tauri::command]
+#[specta::specta]
+pub async fn get_file_tags(path: String, state: State<'_, AppState>) -> Result<Vec<String>, AppError> {
+    db::write(&state.db, |db| {
+        let mut stmt = db
+            .prepare("SELECT t.name FROM file_tags ft JOIN tags t ON t.id = ft.tag_id WHERE ft.path = ?1 ORDER BY t.name COLLATE LOCALE")?;
+        let tags = stmt
+            .query_map([&path], |row| row.get(0))?
+            .filter_map(|r| r.ok())
+            .collect();
+        Ok(tags)
+    })
+}
+
+// Indexed files with a tag, counting auto tags as `#tag` searches do, sorted by name
+#[tauri::command]
+#[specta::specta]
+pub async fn get_files_by_tag(tag: String, state: State<'_, AppState>) -> Result<Vec<FileEntry>, AppError> {
+    let tag = tag.trim().trim_start_matches('#').to_lowercase();
+    if tag.is_empty() {
+        return Err(AppError::new(ErrorCode::InvalidInput, "Tag name cannot be empty"));
+    }
+    let filter = TagFilter {
+        tags: vec![vec![tag]],
+        categories: vec![],
+    };
+    db::write(&state.db, |db| {
+        let mut stmt = db
+            .prepare(&format!(
+                "SELECT path, name, modified_at FROM files WHERE {} ORDER BY name COLLATE LOCALE",
+                filter.sql_clause()
+            ))?;
+        let mut files: Vec<FileEntry> = stmt
+            .query_map([], |row| {
+                Ok(FileEntry {
+                    path: row.get(0)?,
+                    name: row.get(1)?,
+                    last_accessed: None,
+                    access_count: 0,
+                    modified_at: row.get(2)?,
+                    age_bucket: None,
+                    in_backup: false,
+                    match_source: None,
+                    content_snippet: None,
+                })
+            })?
+            .filter_map(|r| r.ok())
+            .collect();
+        mirrors::mark_backup_entries(db, &mut files);
+        assign_age_buckets(&mut files);
+        Ok(files)
+    })
+}
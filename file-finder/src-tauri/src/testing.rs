@@ -0,0 +1,34 @@
+// Hooks for the concurrency stress test in tests/stress.rs. Integration tests only see the
+// library's public items, so these wrap the private calls the commands are built on. Not part of
+// the app's API.
+
+use std::path::{Path, PathBuf};
+
+use crate::{index_directory, invalidate_search_cache, run_search, AppState, SearchOptions};
+
+// Open (and migrate) the database at `db_path` without starting the app's background jobs
+pub fn open(db_path: PathBuf) -> Result<AppState, String> {
+    AppState::open(db_path).map_err(|e| e.to_string())
+}
+
+// What an indexing run does: index `tree`, then drop the results cached from before it
+pub fn index(state: &AppState, tree: &Path, clear_existing: bool) {
+    index_directory(&state.db_path, tree, clear_existing, None, None, None);
+    invalidate_search_cache(state);
+}
+
+pub fn search(state: &AppState, query: &str, case_sensitive: bool) -> Result<(), String> {
+    let options = SearchOptions { case_sensitive, ..SearchOptions::default() };
+    run_search(query, &options, state).map(|_| ())
+}
+
+pub fn record_open(state: &AppState, path: &str) -> Result<(), String> {
+    crate::record_open(path, state).map_err(|e| e.message)
+}
+
+// What saving a setting does: reload every setting under the db lock, drop cached results
+pub fn reload_settings(state: &AppState) -> Result<(), String> {
+    let result = state.db.lock().map_err(|e| e.to_string()).and_then(|db| state.reload_settings(&db));
+    invalidate_search_cache(state);
+    result
+}
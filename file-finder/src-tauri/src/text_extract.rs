@@ -0,0 +1,259 @@
+// Readable text of a file, bounded in size: plain text and source files as they are, PDFs
+// through a small content-stream reader (Flate-compressed streams, Tj/TJ text operators).
+// Enough for summaries and semantic descriptions; scanned PDFs and fonts with custom
+// encodings yield little or nothing.
+
+use flate2::read::ZlibDecoder;
+use std::fs::{self, File};
+use std::io::Read;
+use std::path::Path;
+
+// PDFs are read whole because the text can be anywhere in the file
+const MAX_PDF_BYTES: u64 = 32 * 1024 * 1024;
+
+// Kerning (thousandths of an em) that TJ arrays use in place of a space character
+const WORD_SPACE_OFFSET: f32 = 200.0;
+
+pub struct ExtractedText {
+    pub text: String,
+    // The file has more text than was returned
+    pub truncated: bool,
+}
+
+// Up to `max_chars` characters of text. Errors for binary files and PDFs without text.
+pub fn read_text(path: &Path, max_chars: usize) -> Result<ExtractedText, String> {
+    let is_pdf = path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("pdf"));
+    if is_pdf {
+        return read_pdf(path, max_chars);
+    }
+
+    // UTF-8 needs at most 4 bytes per character; the extra byte tells whether there is more
+    let limit = (max_chars * 4 + 1) as u64;
+    let mut buffer = Vec::new();
+    File::open(path)
+        .and_then(|file| file.take(limit).read_to_end(&mut buffer))
+        .map_err(|e| format!("Could not read {}: {}", path.display(), e))?;
+    if buffer.contains(&0) {
+        return Err(format!("{} is not a text file", path.display()));
+    }
+    let text = String::from_utf8_lossy(&buffer);
+    let mut chars = text.chars();
+    let kept: String = chars.by_ref().take(max_chars).collect();
+    Ok(ExtractedText {
+        text: kept,
+        truncated: chars.next().is_some(),
+    })
+}
+
+// Modification time in seconds, the key for caches of what was read from a file
+pub fn modified_at(path: &Path) -> Option<i64> {
+    let modified = path.metadata().ok()?.modified().ok()?;
+    Some(modified.duration_since(std::time::UNIX_EPOCH).ok()?.as_secs() as i64)
+}
+
+fn read_pdf(path: &Path, max_chars: usize) -> Result<ExtractedText, String> {
+    let size = fs::metadata(path).map_err(|e| e.to_string())?.len();
+    if size > MAX_PDF_BYTES {
+        return Err(format!("{} is too large to read ({} MB)", path.display(), size / (1024 * 1024)));
+    }
+    let bytes = fs::read(path).map_err(|e| format!("Could not read {}: {}", path.display(), e))?;
+
+    let mut text = String::new();
+    let mut truncated = false;
+    for content in content_streams(&bytes) {
+        extract_text_operators(&content, &mut text);
+        if text.chars().count() > max_chars {
+            truncated = true;
+            break;
+        }
+    }
+
+    let text: String = text.chars().take(max_chars).collect();
+    if text.trim().is_empty() {
+        return Err(format!("No extractable text in {}", path.display()));
+    }
+    Ok(ExtractedText { text, truncated })
+}
+
+fn find(haystack: &[u8], needle: &[u8], from: usize) -> Option<usize> {
+    haystack.get(from..)?.windows(needle.len()).position(|window| window == needle).map(|i| i + from)
+}
+
+// Decoded page content streams. Images, fonts and other binary streams are skipped by
+// their dictionary; streams that inflate to garbage just contribute no text.
+fn content_streams(pdf: &[u8]) -> Vec<Vec<u8>> {
+    let mut streams = Vec::new();
+    let mut at = 0;
+    while let Some(keyword) = find(pdf, b"stream", at) {
+        // "endstream" also contains "stream"
+        if keyword >= 3 && &pdf[keyword - 3..keyword] == b"end" {
+            at = keyword + 6;
+            continue;
+        }
+        let mut start = keyword + 6;
+        if pdf.get(start) == Some(&b'\r') {
+            start += 1;
+        }
+        if pdf.get(start) == Some(&b'\n') {
+            start += 1;
+        }
+        let Some(end) = find(pdf, b"endstream", start) else {
+            break;
+        };
+        at = end + 9;
+
+        let dict_start = keyword.saturating_sub(512);
+        let dict = String::from_utf8_lossy(&pdf[dict_start..keyword]);
+        let dict = dict.rsplit("obj").next().unwrap_or_default();
+        let skip = ["/Image", "/FontFile", "/Length1", "/XRef", "/ObjStm", "/Metadata", "/DCTDecode", "/JPXDecode"];
+        if skip.iter().any(|marker| dict.contains(marker)) {
+            continue;
+        }
+
+        let data = &pdf[start..end];
+        if dict.contains("/FlateDecode") {
+            let mut decoded = Vec::new();
+            // A truncated or damaged stream still gives the text before the damage
+            let _ = ZlibDecoder::new(data).read_to_end(&mut decoded);
+            streams.push(decoded);
+        } else if !dict.contains("/Filter") {
+            streams.push(data.to_vec());
+        }
+    }
+    streams
+}
+
+// PDF string bytes as text: UTF-16 with a byte order mark, otherwise treated as Latin-1
+fn decode_pdf_string(bytes: &[u8]) -> String {
+    if bytes.starts_with(&[0xFE, 0xFF]) {
+        let units: Vec<u16> = bytes[2..].chunks_exact(2).map(|pair| u16::from_be_bytes([pair[0], pair[1]])).collect();
+        return String::from_utf16_lossy(&units);
+    }
+    // Two-byte glyph codes of simple CID fonts often equal the Unicode value
+    if bytes.len() >= 2 && bytes.len().is_multiple_of(2) && bytes.chunks_exact(2).all(|pair| pair[0] == 0) {
+        return bytes.chunks_exact(2).map(|pair| pair[1] as char).collect();
+    }
+    bytes.iter().map(|&b| b as char).filter(|c| !c.is_control() || c.is_whitespace()).collect()
+}
+
+// `(...)` literal string starting at `at`; returns its bytes and the index after it
+fn literal_string(content: &[u8], at: usize) -> (Vec<u8>, usize) {
+    let mut bytes = Vec::new();
+    let mut depth = 0;
+    let mut i = at + 1;
+    while i < content.len() {
+        match content[i] {
+            b'\\' => {
+                i += 1;
+                match content.get(i) {
+                    Some(b'n') => bytes.push(b'\n'),
+                    Some(b'r') => bytes.push(b'\r'),
+                    Some(b't') => bytes.push(b'\t'),
+                    Some(b'b') | Some(b'f') => {}
+                    Some(digit @ b'0'..=b'7') => {
+                        let mut value = (digit - b'0') as u32;
+                        for _ in 0..2 {
+                            match content.get(i + 1) {
+                                Some(next @ b'0'..=b'7') => {
+                                    value = value * 8 + (next - b'0') as u32;
+                                    i += 1;
+                                }
+                                _ => break,
+                            }
+                        }
+                        bytes.push(value as u8);
+                    }
+                    // Line continuation
+                    Some(b'\r') | Some(b'\n') => {}
+                    Some(other) => bytes.push(*other),
+                    None => break,
+                }
+            }
+            b'(' => {
+                depth += 1;
+                bytes.push(b'(');
+            }
+            b')' if depth == 0 => return (bytes, i + 1),
+            b')' => {
+                depth -= 1;
+                bytes.push(b')');
+            }
+            byte => bytes.push(byte),
+        }
+        i += 1;
+    }
+    (bytes, i)
+}
+
+// `<...>` hex string starting at `at`; returns its bytes and the index after it
+fn hex_string(content: &[u8], at: usize) -> (Vec<u8>, usize) {
+    let end = find(content, b">", at).unwrap_or(content.len());
+    let digits: Vec<u8> = content[at + 1..end]
+        .iter()
+        .filter_map(|&c| (c as char).to_digit(16).map(|d| d as u8))
+        .collect();
+    let bytes = digits
+        .chunks(2)
+        .map(|pair| (pair[0] << 4) | pair.get(1).copied().unwrap_or(0))
+        .collect();
+    (bytes, end + 1)
+}
+
+// Append the strings shown by text operators, with line breaks where the text moves to a new line
+fn extract_text_operators(content: &[u8], out: &mut String) {
+    let mut pending: Vec<String> = Vec::new();
+    let mut i = 0;
+    while i < content.len() {
+        match content[i] {
+            b'(' => {
+                let (bytes, next) = literal_string(content, i);
+                pending.push(decode_pdf_string(&bytes));
+                i = next;
+            }
+            b'<' if content.get(i + 1) != Some(&b'<') => {
+                let (bytes, next) = hex_string(content, i);
+                pending.push(decode_pdf_string(&bytes));
+                i = next;
+            }
+            // In TJ arrays a large negative offset between strings is usually a word space
+            c if c.is_ascii_digit() || c == b'-' || c == b'.' => {
+                let start = i;
+                i += 1;
+                while i < content.len() && (content[i].is_ascii_digit() || content[i] == b'.') {
+                    i += 1;
+                }
+                let offset: f32 = String::from_utf8_lossy(&content[start..i]).parse().unwrap_or(0.0);
+                if offset <= -WORD_SPACE_OFFSET && !pending.is_empty() {
+                    pending.push(" ".to_string());
+                }
+            }
+            // Comments run to the end of the line
+            b'%' => {
+                while i < content.len() && content[i] != b'\n' && content[i] != b'\r' {
+                    i += 1;
+                }
+            }
+            c if c.is_ascii_alphabetic() || c == b'\'' || c == b'"' || c == b'*' => {
+                let start = i;
+                while i < content.len() && (content[i].is_ascii_alphabetic() || matches!(content[i], b'\'' | b'"' | b'*')) {
+                    i += 1;
+                }
+                match &content[start..i] {
+                    b"Tj" | b"TJ" => out.extend(pending.drain(..)),
+                    b"'" | b"\"" => {
+                        out.push('\n');
+                        out.extend(pending.drain(..));
+                    }
+                    b"T*" | b"Td" | b"TD" | b"ET" => {
+                        pending.clear();
+                        if !out.ends_with('\n') && !out.is_empty() {
+                            out.push('\n');
+                        }
+                    }
+                    _ => pending.clear(),
+                }
+            }
+            _ => i += 1,
+        }
+    }
+}
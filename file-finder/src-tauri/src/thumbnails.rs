@@ -0,0 +1,391 @@
+// Image thumbnails for result rows. PNGs are decoded and scaled here; JPEGs use the thumbnail
+// the camera embedded in their EXIF data when there is one. Other formats go to the OS (System.Drawing
+// on Windows, sips on macOS, gdk-pixbuf or ImageMagick on Linux). Rendered thumbnails are cached
+// on disk keyed by path and modification time, so an edited image gets a fresh one.
+// prefetch_thumbnails renders them ahead of time for rows about to scroll into view.
+
+use base64::Engine;
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use specta::Type;
+use std::fs::{self, File};
+use std::io::{BufWriter, Read};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::UNIX_EPOCH;
+use tauri::{State, WebviewWindow};
+use tracing::debug;
+
+use crate::error::{AppError, ErrorCode};
+use crate::AppState;
+
+const IMAGE_EXTENSIONS: &[&str] =
+    &["png", "jpg", "jpeg", "gif", "bmp", "webp", "tif", "tiff", "heic", "heif", "ico"];
+
+const DEFAULT_THUMBNAIL_SIZE: u32 = 128;
+const MAX_THUMBNAIL_SIZE: u32 = 512;
+
+// Embedded EXIF thumbnails are about 160 pixels wide; larger requests are rendered from the image
+const MAX_EMBEDDED_SIZE: u32 = 256;
+
+// Paths one prefetch call renders; the rest of a long list is ignored
+const MAX_PREFETCH: usize = 200;
+
+// The EXIF block sits in the first APP1 segment, which is at most 64 KB
+const EXIF_SCAN_BYTES: u64 = 128 * 1024;
+
+#[derive(Debug, Serialize, Clone, Type)]
+pub struct Thumbnail {
+    pub path: String,
+    // The cached thumbnail on disk
+    pub file: String,
+    // "data:image/png;base64,..." ready for an <img> src
+    pub data_url: String,
+}
+
+fn cache_dir() -> PathBuf {
+    dirs::data_local_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join("file-finder")
+        .join("thumbnails")
+}
+
+// Cached files are named "<path hash>-<mtime>-<size>.<ext>"; the hash prefix finds stale ones
+fn path_hash(path: &Path) -> String {
+    let digest = Sha256::digest(path.to_string_lossy().as_bytes());
+    digest.iter().take(12).map(|byte| format!("{:02x}", byte)).collect()
+}
+
+fn is_image(path: &Path) -> bool {
+    path.extension()
+        .map(|ext| ext.to_string_lossy().to_lowercase())
+        .is_some_and(|ext| IMAGE_EXTENSIONS.contains(&ext.as_str()))
+}
+
+fn mime_for(file: &Path) -> &'static str {
+    match file.extension().and_then(|ext| ext.to_str()) {
+        Some("jpg") => "image/jpeg",
+        _ => "image/png",
+    }
+}
+
+// Width and height that fit within `size` keeping the aspect ratio, never enlarging
+fn fit(width: u32, height: u32, size: u32) -> (u32, u32) {
+    let longest = width.max(height);
+    if longest <= size {
+        return (width, height);
+    }
+    let scale = |side: u32| ((side as u64 * size as u64 / longest as u64) as u32).max(1);
+    (scale(width), scale(height))
+}
+
+// Average of the source pixels each target pixel covers
+fn downscale(pixels: &[u8], width: u32, height: u32, target_width: u32, target_height: u32) -> Vec<u8> {
+    let mut out = Vec::with_capacity((target_width * target_height * 4) as usize);
+    for ty in 0..target_height {
+        let y0 = ty as u64 * height as u64 / target_height as u64;
+        let y1 = (((ty + 1) as u64 * height as u64).div_ceil(target_height as u64)).max(y0 + 1);
+        for tx in 0..target_width {
+            let x0 = tx as u64 * width as u64 / target_width as u64;
+            let x1 = (((tx + 1) as u64 * width as u64).div_ceil(target_width as u64)).max(x0 + 1);
+            let mut sums = [0u64; 4];
+            for y in y0..y1 {
+                for x in x0..x1 {
+                    let at = ((y * width as u64 + x) * 4) as usize;
+                    for (sum, value) in sums.iter_mut().zip(&pixels[at..at + 4]) {
+                        *sum += *value as u64;
+                    }
+                }
+            }
+            let count = (y1 - y0) * (x1 - x0);
+            out.extend(sums.iter().map(|sum| (sum / count) as u8));
+        }
+    }
+    out
+}
+
+// Decode a PNG to 8-bit RGBA, scale it to fit `size` and write it to `target`
+fn render_png(source: &Path, target: &Path, size: u32) -> Result<(), String> {
+    let mut decoder = png::Decoder::new(File::open(source).map_err(|e| e.to_string())?);
+    decoder.set_transformations(png::Transformations::normalize_to_color8());
+    let mut reader = decoder.read_info().map_err(|e| e.to_string())?;
+    let mut buffer = vec![0; reader.output_buffer_size()];
+    let frame = reader.next_frame(&mut buffer).map_err(|e| e.to_string())?;
+    let pixels = &buffer[..frame.buffer_size()];
+
+    let rgba: Vec<u8> = match frame.color_type {
+        png::ColorType::Rgba => pixels.to_vec(),
+        png::ColorType::Rgb => pixels.chunks_exact(3).flat_map(|p| [p[0], p[1], p[2], 255]).collect(),
+        png::ColorType::GrayscaleAlpha => pixels.chunks_exact(2).flat_map(|p| [p[0], p[0], p[0], p[1]]).collect(),
+        png::ColorType::Grayscale => pixels.iter().flat_map(|&g| [g, g, g, 255]).collect(),
+        png::ColorType::Indexed => return Err("Palette was not expanded".to_string()),
+    };
+    let (width, height) = fit(frame.width, frame.height, size);
+    let scaled = downscale(&rgba, frame.width, frame.height, width, height);
+
+    let writer = BufWriter::new(File::create(target).map_err(|e| e.to_string())?);
+    let mut encoder = png::Encoder::new(writer, width, height);
+    encoder.set_color(png::ColorType::Rgba);
+    encoder.set_depth(png::BitDepth::Eight);
+    let mut writer = encoder.write_header().map_err(|e| e.to_string())?;
+    writer.write_image_data(&scaled).map_err(|e| e.to_string())
+}
+
+// The JPEG thumbnail in IFD1 of a JPEG's EXIF block, if the camera stored one
+fn embedded_jpeg_thumbnail(source: &Path) -> Option<Vec<u8>> {
+    let mut head = Vec::new();
+    File::open(source).ok()?.take(EXIF_SCAN_BYTES).read_to_end(&mut head).ok()?;
+    if !head.starts_with(&[0xFF, 0xD8]) {
+        return None;
+    }
+    let mut at = 2;
+    while at + 4 <= head.len() && head[at] == 0xFF {
+        let marker = head[at + 1];
+        let length = u16::from_be_bytes([head[at + 2], head[at + 3]]) as usize;
+        // Start of scan: image data follows and there are no more headers
+        if marker == 0xDA {
+            return None;
+        }
+        let segment = head.get(at + 4..at + 2 + length)?;
+        if marker == 0xE1 && segment.starts_with(b"Exif\0\0") {
+            return exif_thumbnail(&segment[6..]);
+        }
+        at += 2 + length;
+    }
+    None
+}
+
+fn exif_thumbnail(tiff: &[u8]) -> Option<Vec<u8>> {
+    let little_endian = match tiff.get(..2)? {
+        b"II" => true,
+        b"MM" => false,
+        _ => return None,
+    };
+    let u16_at = |at: usize| -> Option<u32> {
+        let bytes = [*tiff.get(at)?, *tiff.get(at + 1)?];
+        Some(if little_endian { u16::from_le_bytes(bytes) } else { u16::from_be_bytes(bytes) } as u32)
+    };
+    let u32_at = |at: usize| -> Option<u32> {
+        let bytes: [u8; 4] = tiff.get(at..at + 4)?.try_into().ok()?;
+        Some(if little_endian { u32::from_le_bytes(bytes) } else { u32::from_be_bytes(bytes) })
+    };
+
+    // IFD0 describes the main image; the offset after its entries leads to IFD1, the thumbnail
+    let ifd0 = u32_at(4)? as usize;
+    let ifd1 = u32_at(ifd0 + 2 + u16_at(ifd0)? as usize * 12)? as usize;
+    if ifd1 == 0 {
+        return None;
+    }
+    let (mut offset, mut length) = (None, None);
+    for entry in 0..u16_at(ifd1)? as usize {
+        let at = ifd1 + 2 + entry * 12;
+        match u16_at(at)? {
+            // JPEGInterchangeFormat and JPEGInterchangeFormatLength
+            0x0201 => offset = u32_at(at + 8),
+            0x0202 => length = u32_at(at + 8),
+            _ => {}
+        }
+    }
+    let (offset, length) = (offset? as usize, length? as usize);
+    let thumbnail = tiff.get(offset..offset.checked_add(length)?)?;
+    thumbnail.starts_with(&[0xFF, 0xD8]).then(|| thumbnail.to_vec())
+}
+
+#[cfg(target_os = "windows")]
+fn render_with_os(source: &Path, target: &Path, size: u32) -> Result<(), String> {
+    use std::os::windows::process::CommandExt;
+
+    let script = format!(
+        "Add-Type -AssemblyName System.Drawing; \
+         $image = [System.Drawing.Image]::FromFile($env:FILE_FINDER_THUMB_SOURCE); \
+         $scale = [Math]::Min(1, {0} / [Math]::Max($image.Width, $image.Height)); \
+         $width = [Math]::Max(1, [int]($image.Width * $scale)); $height = [Math]::Max(1, [int]($image.Height * $scale)); \
+         $bitmap = New-Object System.Drawing.Bitmap $image, $width, $height; \
+         $bitmap.Save($env:FILE_FINDER_THUMB_TARGET, [System.Drawing.Imaging.ImageFormat]::Png)",
+        size
+    );
+    // Keep PowerShell from flashing a console window
+    const CREATE_NO_WINDOW: u32 = 0x0800_0000;
+
+    let status = std::process::Command::new("powershell")
+        .args(["-NoProfile", "-NonInteractive", "-Command", &script])
+        .env("FILE_FINDER_THUMB_SOURCE", source)
+        .env("FILE_FINDER_THUMB_TARGET", target)
+        .creation_flags(CREATE_NO_WINDOW)
+        .status()
+        .map_err(|e| e.to_string())?;
+    if !status.success() {
+        return Err(format!("Could not make a thumbnail of {}", source.display()));
+    }
+    Ok(())
+}
+
+#[cfg(target_os = "macos")]
+fn render_with_os(source: &Path, target: &Path, size: u32) -> Result<(), String> {
+    let status = std::process::Command::new("sips")
+        .arg("-Z")
+        .arg(size.to_string())
+        .args(["-s", "format", "png"])
+        .arg(source)
+        .arg("--out")
+        .arg(target)
+        .stdout(std::process::Stdio::null())
+        .status()
+        .map_err(|e| e.to_string())?;
+    if !status.success() {
+        return Err(format!("Could not make a thumbnail of {}", source.display()));
+    }
+    Ok(())
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+fn render_with_os(source: &Path, target: &Path, size: u32) -> Result<(), String> {
+    use std::process::{Command, Stdio};
+
+    // The thumbnailer file managers use, then ImageMagick; PNG output either way
+    let pixbuf = Command::new("gdk-pixbuf-thumbnailer")
+        .arg("-s")
+        .arg(size.to_string())
+        .arg(source)
+        .arg(target)
+        .stderr(Stdio::null())
+        .status();
+    if pixbuf.is_ok_and(|status| status.success()) && target.exists() {
+        return Ok(());
+    }
+    let mut input = source.as_os_str().to_owned();
+    // First frame only, for GIFs and multi-page TIFFs
+    input.push("[0]");
+    let mut output = std::ffi::OsString::from("png:");
+    output.push(target);
+    let magick = Command::new("convert")
+        .arg(input)
+        .arg("-thumbnail")
+        .arg(format!("{0}x{0}>", size))
+        .arg(output)
+        .stderr(Stdio::null())
+        .status();
+    if magick.is_ok_and(|status| status.success()) && target.exists() {
+        return Ok(());
+    }
+    Err(format!("Could not make a thumbnail of {} (needs gdk-pixbuf or ImageMagick)", source.display()))
+}
+
+// Drop thumbnails made from earlier versions of the file, at any size
+fn remove_stale(hash: &str, modified: u64) {
+    let Ok(entries) = fs::read_dir(cache_dir()) else {
+        return;
+    };
+    let (file, current) = (format!("{}-", hash), format!("{}-{}-", hash, modified));
+    for entry in entries.filter_map(|e| e.ok()) {
+        let name = entry.file_name().to_string_lossy().to_string();
+        let stale = name.starts_with(&file) && !name.starts_with(&current);
+        if stale {
+            let _ = fs::remove_file(entry.path());
+        }
+    }
+}
+
+// Where a thumbnail is written before it is renamed into place, so one still being rendered (by
+// a prefetch, say) is never read as a cached one
+fn partial(target: &Path) -> PathBuf {
+    static NEXT: AtomicU64 = AtomicU64::new(0);
+    let mut name = target.as_os_str().to_owned();
+    name.push(format!(".{}-{}.part", std::process::id(), NEXT.fetch_add(1, Ordering::Relaxed)));
+    PathBuf::from(name)
+}
+
+fn thumbnail(path: String, file: &Path) -> Result<Thumbnail, String> {
+    let bytes = fs::read(file).map_err(|e| format!("No thumbnail for {}: {}", path, e))?;
+    let data_url = format!("data:{};base64,{}", mime_for(file), base64::engine::general_purpose::STANDARD.encode(bytes));
+    Ok(Thumbnail { path, file: file.to_string_lossy().to_string(), data_url })
+}
+
+// A thumbnail at most `size` pixels on its longest side (default 128, at most 512). Errors mean
+// the image could not be read, and the row keeps its icon.
+#[tauri::command]
+#[specta::specta]
+pub async fn get_thumbnail(path: String, size: Option<u32>) -> Result<Thumbnail, AppError> {
+    let file = render(&path, size)?;
+    Ok(thumbnail(path, &file)?)
+}
+
+// Render thumbnails of the images among `paths` in the background, in order, so get_thumbnail
+// finds them cached. Returns at once. Each window has one batch at a time: a newer batch, or a new
+// search from the window, cancels what is left of the older one.
+#[tauri::command]
+#[specta::specta]
+pub async fn prefetch_thumbnails(paths: Vec<String>, size: Option<u32>, window: WebviewWindow, state: State<'_, AppState>) -> Result<(), AppError> {
+    let ticket = state.thumbnail_prefetch.enter(window.label())?;
+    let paths: Vec<String> = paths.into_iter().filter(|path| is_image(Path::new(path))).take(MAX_PREFETCH).collect();
+    // One image at a time on a single blocking thread, so prefetching never takes more than a
+    // core from searches
+    tauri::async_runtime::spawn_blocking(move || {
+        for path in paths {
+            if ticket.superseded() {
+                break;
+            }
+            if let Err(e) = render(&path, size) {
+                debug!("No thumbnail prefetched for {}: {}", path, e.message);
+            }
+        }
+    });
+    Ok(())
+}
+
+// The cached thumbnail file of `path`, rendered first if there is none
+fn render(path: &str, size: Option<u32>) -> Result<PathBuf, AppError> {
+    let source = PathBuf::from(path);
+    if !is_image(&source) {
+        return Err(AppError::new(ErrorCode::InvalidInput, format!("{} is not an image", path)));
+    }
+    let metadata = fs::metadata(&source).map_err(|e| format!("{}: {}", path, e))?;
+    let modified = metadata
+        .modified()
+        .ok()
+        .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+        .map_or(0, |elapsed| elapsed.as_secs());
+    let size = size.unwrap_or(DEFAULT_THUMBNAIL_SIZE).clamp(16, MAX_THUMBNAIL_SIZE);
+    let hash = path_hash(&source);
+    let stem = format!("{}-{}-{}", hash, modified, size);
+
+    for ext in ["png", "jpg"] {
+        let cached = cache_dir().join(format!("{}.{}", stem, ext));
+        if cached.exists() {
+            return Ok(cached);
+        }
+    }
+
+    fs::create_dir_all(cache_dir())?;
+    let extension = source.extension().map(|ext| ext.to_string_lossy().to_lowercase()).unwrap_or_default();
+    let embedded = (matches!(extension.as_str(), "jpg" | "jpeg") && size <= MAX_EMBEDDED_SIZE)
+        .then(|| embedded_jpeg_thumbnail(&source))
+        .flatten();
+
+    let target = match embedded {
+        Some(jpeg) => {
+            let target = cache_dir().join(format!("{}.jpg", stem));
+            let written = partial(&target);
+            fs::write(&written, jpeg)?;
+            fs::rename(&written, &target)?;
+            target
+        }
+        None => {
+            let target = cache_dir().join(format!("{}.png", stem));
+            let written = partial(&target);
+            let rendered = if extension == "png" {
+                // Unusual PNGs the decoder rejects can still go through the OS
+                render_png(&source, &written, size).or_else(|_| render_with_os(&source, &written, size))
+            } else {
+                render_with_os(&source, &written, size)
+            };
+            if let Err(e) = rendered.and_then(|_| fs::rename(&written, &target).map_err(|e| e.to_string())) {
+                let _ = fs::remove_file(&written);
+                return Err(e.into());
+            }
+            target
+        }
+    };
+    remove_stale(&hash, modified);
+    Ok(target)
+}
@@ -0,0 +1,461 @@
+// Modification-time filter: `modified:today report` only matches files changed since midnight.
+// Values name a period in local time: `today`, `yesterday`, `week` (since Monday), `month` (since
+// the 1st), `year` (since January 1st), a rolling span (`3d`, `2w`, `6m`, `1y`), a calendar
+// period (`2023`, `2023-03`, `2023-03-05`, `march`), or a bound on one: `<2022` (before it),
+// `>2023-06` (after it), `2023-01..2023-06` and `2023..` (from the start of one to the end of
+// the other). Date phrases such as "last march" or "past 3 days" are turned into these values.
+
+use chrono::{DateTime, Datelike, Days, Local, Months, NaiveDate, TimeZone};
+
+// For the LLM prompt
+pub const SYNTAX: &str = "today, yesterday, week, month, year, 3d, 2w, 6m, 1y, 2023, 2023-03, 2023-03-05, <2022, >2023-06, 2023-01..2023-06";
+
+const MONTHS: &[&str] = &[
+    "january", "february", "march", "april", "may", "june", "july", "august", "september", "october", "november", "december",
+];
+
+const NUMBER_WORDS: &[&str] = &["one", "two", "three", "four", "five", "six", "seven", "eight", "nine", "ten", "eleven", "twelve"];
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TimeFilter {
+    // The value after `modified:`, e.g. "week"
+    pub value: String,
+    // Unix seconds; `after` is inclusive, `before` exclusive
+    pub after: Option<i64>,
+    pub before: Option<i64>,
+}
+
+// Unix time of local midnight at the start of `date`
+pub fn start_of(date: NaiveDate) -> i64 {
+    let midnight = date.and_hms_opt(0, 0, 0).unwrap_or_default();
+    Local
+        .from_local_datetime(&midnight)
+        .earliest()
+        .map(|dt| dt.timestamp())
+        .unwrap_or_else(|| midnight.and_utc().timestamp()) // midnight skipped by DST
+}
+
+fn month_number(word: &str) -> Option<u32> {
+    // Full names and three-letter abbreviations ("sept" too)
+    let word = word.strip_suffix('.').unwrap_or(word);
+    if word.len() < 3 {
+        return None;
+    }
+    MONTHS
+        .iter()
+        .position(|month| *month == word || (word.len() <= 4 && month.starts_with(word)))
+        .map(|i| i as u32 + 1)
+}
+
+fn first_of_month(year: i32, month: u32) -> Option<NaiveDate> {
+    NaiveDate::from_ymd_opt(year, month, 1)
+}
+
+fn year_number(word: &str) -> Option<i32> {
+    let year: i32 = word.parse().ok().filter(|_| word.len() == 4)?;
+    (1970..=2100).contains(&year).then_some(year)
+}
+
+// The most recent `month` that has started, this year's if it has
+fn recent_month(month: u32, today: NaiveDate) -> Option<NaiveDate> {
+    let year = if month <= today.month() { today.year() } else { today.year() - 1 };
+    first_of_month(year, month)
+}
+
+// A period as [start, end) dates; an open end runs to now
+fn period(value: &str, today: NaiveDate) -> Option<(NaiveDate, Option<NaiveDate>)> {
+    let next_day = |date: NaiveDate| date.succ_opt();
+    let period = match value {
+        "today" => (today, next_day(today)),
+        "yesterday" => (today.pred_opt()?, Some(today)),
+        "week" => (today - Days::new(today.weekday().num_days_from_monday() as u64), None),
+        "month" => (today.with_day(1)?, None),
+        "year" => (today.with_ordinal(1)?, None),
+        _ => {
+            if let Some(month) = month_number(value) {
+                let start = recent_month(month, today)?;
+                return Some((start, start.checked_add_months(Months::new(1))));
+            }
+            if let Some(year) = year_number(value) {
+                return Some((first_of_month(year, 1)?, first_of_month(year + 1, 1)));
+            }
+            let parts: Vec<&str> = value.split('-').collect();
+            match parts.as_slice() {
+                [year, month] => {
+                    let start = first_of_month(year_number(year)?, month.parse().ok()?)?;
+                    (start, start.checked_add_months(Months::new(1)))
+                }
+                [year, month, day] => {
+                    let date = NaiveDate::from_ymd_opt(year_number(year)?, month.parse().ok()?, day.parse().ok()?)?;
+                    (date, next_day(date))
+                }
+                _ => return rolling(value, today).map(|start| (start, None)),
+            }
+        }
+    };
+    Some(period)
+}
+
+// Start of a rolling span such as "3d" or "6m", counted in whole days back from today
+fn rolling(value: &str, today: NaiveDate) -> Option<NaiveDate> {
+    let unit = value.chars().last()?;
+    let count: u32 = value[..value.len() - unit.len_utf8()].parse().ok().filter(|&count| count > 0)?;
+    match unit {
+        'd' => today.checked_sub_days(Days::new(count as u64)),
+        'w' => today.checked_sub_days(Days::new(count as u64 * 7)),
+        'm' => today.checked_sub_months(Months::new(count)),
+        'y' => today.checked_sub_months(Months::new(count * 12)),
+        _ => None,
+    }
+}
+
+// The range a `modified:` value stands for, or None for values it doesn't know
+pub fn parse_value(value: &str, now: DateTime<Local>) -> Option<TimeFilter> {
+    let value = value.to_lowercase();
+    let today = now.date_naive();
+    let (after, before) = if let Some(rest) = value.strip_prefix('<') {
+        (None, Some(period(rest, today)?.0))
+    } else if let Some(rest) = value.strip_prefix('>') {
+        (Some(period(rest, today)?.1?), None)
+    } else if let Some((from, to)) = value.split_once("..") {
+        let after = if from.is_empty() { None } else { Some(period(from, today)?.0) };
+        let before = if to.is_empty() { None } else { period(to, today)?.1 };
+        if after.is_none() && before.is_none() {
+            return None;
+        }
+        (after, before)
+    } else {
+        let (start, end) = period(&value, today)?;
+        (Some(start), end)
+    };
+    if let (Some(after), Some(before)) = (after, before) {
+        if after >= before {
+            return None;
+        }
+    }
+    Some(TimeFilter {
+        value,
+        after: after.map(start_of),
+        before: before.map(start_of),
+    })
+}
+
+// Split `modified:` tokens out of a query. Unknown values are left in the query as text; with
+// several tokens the last one wins.
+pub fn extract_time_filter(query: &str, now: DateTime<Local>) -> (String, Option<TimeFilter>) {
+    let mut filter = None;
+    let mut rest: Vec<&str> = Vec::new();
+
+    for token in query.split_whitespace() {
+        let parsed = token
+            .get(.."modified:".len())
+            .filter(|head| head.eq_ignore_ascii_case("modified:"))
+            .and_then(|_| parse_value(&token["modified:".len()..], now));
+        match parsed {
+            Some(parsed) => filter = Some(parsed),
+            None => rest.push(token),
+        }
+    }
+
+    if filter.is_none() {
+        return (query.to_string(), None);
+    }
+    (rest.join(" "), filter)
+}
+
+fn count(word: &str) -> Option<u32> {
+    if word == "a" || word == "an" {
+        return Some(1);
+    }
+    word.parse()
+        .ok()
+        .or_else(|| NUMBER_WORDS.iter().position(|number| *number == word).map(|i| i as u32 + 1))
+        .filter(|&count| count > 0 && count <= 1000)
+}
+
+fn unit_letter(word: &str) -> Option<char> {
+    match word.strip_suffix('s').unwrap_or(word) {
+        "day" => Some('d'),
+        "week" => Some('w'),
+        "month" => Some('m'),
+        "year" => Some('y'),
+        _ => None,
+    }
+}
+
+fn month_value(date: NaiveDate) -> String {
+    date.format("%Y-%m").to_string()
+}
+
+// A calendar period at the start of `words`: "march", "march 2023", "2023", "last year".
+// Bare month names and years only count after a preposition, since they are common in file names.
+fn period_phrase(words: &[String], today: NaiveDate, bare: bool) -> Option<(String, usize)> {
+    let word = |i: usize| words.get(i).map(String::as_str);
+    if let Some(month) = word(0).and_then(month_number) {
+        if let Some(year) = word(1).and_then(year_number) {
+            return Some((month_value(first_of_month(year, month)?), 2));
+        }
+        if bare {
+            return Some((month_value(recent_month(month, today)?), 1));
+        }
+    }
+    if bare {
+        if let Some(year) = word(0).and_then(year_number) {
+            return Some((year.to_string(), 1));
+        }
+    }
+    match (word(0)?, word(1)) {
+        ("last" | "previous", Some(next)) => {
+            if let Some(month) = month_number(next) {
+                // The most recent one before this month
+                let this_month = today.with_day(1)?;
+                let mut start = recent_month(month, today)?;
+                if start >= this_month {
+                    start = start.checked_sub_months(Months::new(12))?;
+                }
+                return Some((month_value(start), 2));
+            }
+            let value = match unit_letter(next)? {
+                'd' => today.pred_opt()?.format("%Y-%m-%d").to_string(),
+                'w' => {
+                    let monday = today - Days::new(today.weekday().num_days_from_monday() as u64 + 7);
+                    format!("{}..{}", monday.format("%Y-%m-%d"), (monday + Days::new(6)).format("%Y-%m-%d"))
+                }
+                'm' => month_value(today.with_day(1)?.checked_sub_months(Months::new(1))?),
+                _ => (today.year() - 1).to_string(),
+            };
+            Some((value, 2))
+        }
+        ("this", Some(next)) => {
+            let value = match unit_letter(next)? {
+                'd' => "today",
+                'w' => "week",
+                'm' => "month",
+                _ => "year",
+            };
+            Some((value.to_string(), 2))
+        }
+        _ => None,
+    }
+}
+
+// A date phrase at the start of `words` (lowercase, punctuation trimmed): the `modified:` value it
+// stands for and how many words it takes up. Understands "today", "recently", "this week", "last
+// month", "past 3 days", "2 weeks ago", "last march", "in march 2023", "from 2021", "before
+// 2022", "after june 2023" and "since last year".
+pub fn parse_phrase(words: &[String], today: NaiveDate) -> Option<(String, usize)> {
+    let word = |i: usize| words.get(i).map(String::as_str);
+    match word(0)? {
+        "today" => return Some(("today".to_string(), 1)),
+        "yesterday" => return Some(("yesterday".to_string(), 1)),
+        "recent" | "recently" | "lately" => return Some(("1w".to_string(), 1)),
+        "in" | "from" | "during" | "on" => {
+            let (value, used) = period_phrase(&words[1..], today, true)?;
+            return Some((value, used + 1));
+        }
+        // A range such as last week's is bounded by its first or last day
+        "before" | "until" | "till" => {
+            let (value, used) = period_phrase(&words[1..], today, true)?;
+            let start = value.split("..").next().unwrap_or(&value);
+            return Some((format!("<{}", start), used + 1));
+        }
+        "after" => {
+            let (value, used) = period_phrase(&words[1..], today, true)?;
+            let end = value.rsplit("..").next().unwrap_or(&value);
+            return Some((format!(">{}", end), used + 1));
+        }
+        "since" => {
+            let (value, used) = period_phrase(&words[1..], today, true)?;
+            let start = value.split("..").next().unwrap_or(&value);
+            return Some((format!("{}..", start), used + 1));
+        }
+        _ => {}
+    }
+
+    // "past 3 days", "last two weeks", "past week"
+    if matches!(word(0)?, "past" | "last") {
+        if let (Some(count), Some(unit)) = (word(1).and_then(count), word(2).and_then(unit_letter)) {
+            return Some((format!("{}{}", count, unit), 3));
+        }
+        if word(0) == Some("past") {
+            if let Some(unit) = word(1).and_then(unit_letter) {
+                return Some((format!("1{}", unit), 2));
+            }
+        }
+    }
+    // "3 days ago": the day, week, month or year it falls in
+    if let (Some(count), Some(unit), Some("ago")) = (word(0).and_then(count), word(1).and_then(unit_letter), word(2)) {
+        let date = rolling(&format!("{}{}", count, unit), today)?;
+        let value = match unit {
+            'd' => date.format("%Y-%m-%d").to_string(),
+            'w' => {
+                let monday = date - Days::new(date.weekday().num_days_from_monday() as u64);
+                format!("{}..{}", monday.format("%Y-%m-%d"), (monday + Days::new(6)).format("%Y-%m-%d"))
+            }
+            'm' => month_value(date),
+            _ => date.year().to_string(),
+        };
+        return Some((value, 3));
+    }
+    period_phrase(words, today, false)
+}
+
+impl TimeFilter {
+    // For explain_search, e.g. "modified: week"
+    pub fn describe(&self) -> String {
+        format!("modified: {}", self.value)
+    }
+
+    // SQL condition on the `modified_at` column and the bounds to bind to its placeholders;
+    // files without a modification time never match
+    pub fn sql_clause(&self) -> (String, Vec<i64>) {
+        let mut conditions = vec!["modified_at IS NOT NULL"];
+        let mut bounds = Vec::new();
+        if let Some(after) = self.after {
+            conditions.push("modified_at >= ?");
+            bounds.push(after);
+        }
+        if let Some(before) = self.before {
+            conditions.push("modified_at < ?");
+            bounds.push(before);
+        }
+        (format!("({})", conditions.join(" AND ")), bounds)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // A Wednesday
+    fn now() -> DateTime<Local> {
+        Local.with_ymd_and_hms(2024, 5, 15, 10, 30, 0).unwrap()
+    }
+
+    fn day(year: i32, month: u32, day: u32) -> Option<i64> {
+        Some(start_of(NaiveDate::from_ymd_opt(year, month, day).unwrap()))
+    }
+
+    fn range(value: &str) -> Option<(Option<i64>, Option<i64>)> {
+        parse_value(value, now()).map(|filter| (filter.after, filter.before))
+    }
+
+    fn phrase(text: &str) -> Option<(String, usize)> {
+        let words: Vec<String> = text.split_whitespace().map(str::to_string).collect();
+        parse_phrase(&words, now().date_naive())
+    }
+
+    #[test]
+    fn named_periods_start_at_local_midnight() {
+        assert_eq!(range("today"), Some((day(2024, 5, 15), day(2024, 5, 16))));
+        assert_eq!(range("yesterday"), Some((day(2024, 5, 14), day(2024, 5, 15))));
+        assert_eq!(range("week"), Some((day(2024, 5, 13), None)));
+        assert_eq!(range("month"), Some((day(2024, 5, 1), None)));
+        assert_eq!(range("Year"), Some((day(2024, 1, 1), None)));
+    }
+
+    #[test]
+    fn rolling_spans_count_back_from_today() {
+        assert_eq!(range("3d"), Some((day(2024, 5, 12), None)));
+        assert_eq!(range("2w"), Some((day(2024, 5, 1), None)));
+        assert_eq!(range("6m"), Some((day(2023, 11, 15), None)));
+        assert_eq!(range("1y"), Some((day(2023, 5, 15), None)));
+        assert_eq!(range("0d"), None);
+        assert_eq!(range("3x"), None);
+    }
+
+    #[test]
+    fn calendar_periods_cover_the_whole_period() {
+        assert_eq!(range("2023"), Some((day(2023, 1, 1), day(2024, 1, 1))));
+        assert_eq!(range("2023-03"), Some((day(2023, 3, 1), day(2023, 4, 1))));
+        assert_eq!(range("2023-03-05"), Some((day(2023, 3, 5), day(2023, 3, 6))));
+        assert_eq!(range("2023-13"), None);
+        assert_eq!(range("1850"), None);
+    }
+
+    #[test]
+    fn month_names_mean_the_most_recent_one() {
+        assert_eq!(range("march"), Some((day(2024, 3, 1), day(2024, 4, 1))));
+        assert_eq!(range("may"), Some((day(2024, 5, 1), day(2024, 6, 1))));
+        assert_eq!(range("dec"), Some((day(2023, 12, 1), day(2024, 1, 1))));
+        assert_eq!(range("sept"), Some((day(2023, 9, 1), day(2023, 10, 1))));
+        assert_eq!(range("ma"), None);
+    }
+
+    #[test]
+    fn bounds_and_ranges() {
+        assert_eq!(range("<2022"), Some((None, day(2022, 1, 1))));
+        assert_eq!(range(">2023-06"), Some((day(2023, 7, 1), None)));
+        assert_eq!(range("2023-01..2023-06"), Some((day(2023, 1, 1), day(2023, 7, 1))));
+        assert_eq!(range("2023.."), Some((day(2023, 1, 1), None)));
+        assert_eq!(range("..2023"), Some((None, day(2024, 1, 1))));
+        // Empty or backwards ranges match nothing, so they aren't filters
+        assert_eq!(range(".."), None);
+        assert_eq!(range("2023-06..2023-01"), None);
+    }
+
+    #[test]
+    fn modified_tokens_are_split_out_of_the_query() {
+        let (rest, filter) = extract_time_filter("report Modified:week final", now());
+        assert_eq!(rest, "report final");
+        assert_eq!(filter.map(|filter| filter.value).as_deref(), Some("week"));
+
+        let (rest, filter) = extract_time_filter("modified:today modified:2023 notes", now());
+        assert_eq!(rest, "notes");
+        assert_eq!(filter.map(|filter| filter.value).as_deref(), Some("2023"));
+    }
+
+    #[test]
+    fn unknown_values_stay_in_the_query() {
+        assert_eq!(extract_time_filter("modified:someday report", now()), ("modified:someday report".to_string(), None));
+        assert_eq!(extract_time_filter("modified: report", now()), ("modified: report".to_string(), None));
+    }
+
+    #[test]
+    fn relative_phrases() {
+        assert_eq!(phrase("past 3 days"), Some(("3d".to_string(), 3)));
+        assert_eq!(phrase("last two weeks"), Some(("2w".to_string(), 3)));
+        assert_eq!(phrase("past month"), Some(("1m".to_string(), 2)));
+        assert_eq!(phrase("recently"), Some(("1w".to_string(), 1)));
+        assert_eq!(phrase("this week"), Some(("week".to_string(), 2)));
+        assert_eq!(phrase("last week"), Some(("2024-05-06..2024-05-12".to_string(), 2)));
+        assert_eq!(phrase("2 weeks ago"), Some(("2024-04-29..2024-05-05".to_string(), 3)));
+        assert_eq!(phrase("3 days ago"), Some(("2024-05-12".to_string(), 3)));
+    }
+
+    #[test]
+    fn calendar_phrases() {
+        assert_eq!(phrase("last march"), Some(("2024-03".to_string(), 2)));
+        // This month hasn't finished, so "last may" is a year ago
+        assert_eq!(phrase("last may"), Some(("2023-05".to_string(), 2)));
+        assert_eq!(phrase("in march 2023"), Some(("2023-03".to_string(), 3)));
+        assert_eq!(phrase("from 2021"), Some(("2021".to_string(), 2)));
+        assert_eq!(phrase("before 2022"), Some(("<2022".to_string(), 2)));
+        assert_eq!(phrase("after june 2023"), Some((">2023-06".to_string(), 3)));
+        assert_eq!(phrase("since last year"), Some(("2023..".to_string(), 3)));
+        assert_eq!(phrase("before last week"), Some(("<2024-05-06".to_string(), 3)));
+    }
+
+    #[test]
+    fn bare_months_and_years_are_not_phrases() {
+        assert_eq!(phrase("march report"), None);
+        assert_eq!(phrase("2023 taxes"), None);
+        assert_eq!(phrase("taxes"), None);
+    }
+
+    #[test]
+    fn phrase_values_are_valid_modified_values() {
+        for text in ["last week", "2 weeks ago", "since last year", "before last week", "after june 2023", "last may"] {
+            let (value, _) = phrase(text).unwrap();
+            assert!(parse_value(&value, now()).is_some(), "{} -> {}", text, value);
+        }
+    }
+
+    #[test]
+    fn sql_binds_only_the_bounds_it_has() {
+        let filter = parse_value("<2022", now()).unwrap();
+        assert_eq!(filter.sql_clause(), ("(modified_at IS NOT NULL AND modified_at < ?)".to_string(), vec![day(2022, 1, 1).unwrap()]));
+        let filter = parse_value("2023", now()).unwrap();
+        assert_eq!(filter.sql_clause().1, vec![day(2023, 1, 1).unwrap(), day(2024, 1, 1).unwrap()]);
+    }
+}
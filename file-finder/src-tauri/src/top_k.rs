@@ -0,0 +1,96 @@
+// Selection of the best-scoring results. Every scored candidate goes through a min-heap that
+// holds at most k of them, the weakest at the root, so all candidates are compared without
+// sorting the whole set, and the kept ones come out best first.
+
+use std::cmp::{Ordering, Reverse};
+use std::collections::BinaryHeap;
+
+struct Scored<T> {
+    score: i64,
+    // Arrival order; the earlier of two equal scores ranks higher, so ties are stable
+    seq: usize,
+    item: T,
+}
+
+impl<T> Ord for Scored<T> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.score.cmp(&other.score).then_with(|| other.seq.cmp(&self.seq))
+    }
+}
+
+impl<T> PartialOrd for Scored<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<T> PartialEq for Scored<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl<T> Eq for Scored<T> {}
+
+// The `k` highest-scoring items, best first
+pub fn top_k<T>(scored: impl IntoIterator<Item = (i64, T)>, k: usize) -> Vec<(i64, T)> {
+    if k == 0 {
+        return vec![];
+    }
+    let mut heap: BinaryHeap<Reverse<Scored<T>>> = BinaryHeap::with_capacity(k + 1);
+    for (seq, (score, item)) in scored.into_iter().enumerate() {
+        let candidate = Scored { score, seq, item };
+        if heap.len() < k {
+            heap.push(Reverse(candidate));
+        } else if let Some(mut weakest) = heap.peek_mut() {
+            if candidate > weakest.0 {
+                *weakest = Reverse(candidate);
+            }
+        }
+    }
+    // Ascending by Reverse is descending by score
+    heap.into_sorted_vec().into_iter().map(|Reverse(scored)| (scored.score, scored.item)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn the_best_come_out_best_first() {
+        let scored = vec![(1, "a"), (5, "b"), (3, "c"), (4, "d"), (2, "e")];
+        assert_eq!(top_k(scored, 3), vec![(5, "b"), (4, "d"), (3, "c")]);
+    }
+
+    #[test]
+    fn ties_keep_arrival_order() {
+        let scored = vec![(1, "a"), (2, "b"), (1, "c"), (2, "d"), (1, "e")];
+        assert_eq!(top_k(scored, 3), vec![(2, "b"), (2, "d"), (1, "a")]);
+        assert_eq!(top_k(vec![(7, "x"), (7, "y"), (7, "z")], 2), vec![(7, "x"), (7, "y")]);
+    }
+
+    #[test]
+    fn small_inputs_and_empty_selections() {
+        assert_eq!(top_k(vec![(1, "a"), (3, "b")], 10), vec![(3, "b"), (1, "a")]);
+        assert!(top_k(vec![(1, "a")], 0).is_empty());
+        assert!(top_k(Vec::<(i64, &str)>::new(), 5).is_empty());
+    }
+
+    // Same result as a stable sort of everything
+    #[test]
+    fn matches_a_full_sort() {
+        let mut seed: u64 = 42;
+        let scored: Vec<(i64, usize)> = (0..2_000)
+            .map(|i| {
+                seed = seed.wrapping_mul(6_364_136_223_846_793_005).wrapping_add(1_442_695_040_888_963_407);
+                ((seed >> 33) as i64 % 100 - 50, i)
+            })
+            .collect();
+        let mut sorted = scored.clone();
+        sorted.sort_by(|a, b| b.0.cmp(&a.0));
+        for k in [1, 10, 300, 2_000, 5_000] {
+            let expected: Vec<(i64, usize)> = sorted.iter().take(k).copied().collect();
+            assert_eq!(top_k(scored.clone(), k), expected, "k = {}", k);
+        }
+    }
+}
@@ -0,0 +1,110 @@
+// Romanized forms of CJK file names, so "zhongguo" finds 中国.txt and "sakura" finds さくら.png.
+// Han characters use their Mandarin pinyin reading (without tones); hiragana and
+// katakana use Hepburn romaji. Everything else is lowercased and passed through.
+
+use pinyin::ToPinyin;
+
+// Returns None when the name has nothing to romanize (e.g. plain ASCII names)
+pub fn romanize(name: &str) -> Option<String> {
+    if name.is_ascii() {
+        return None;
+    }
+
+    let mut out = String::with_capacity(name.len() * 2);
+    let mut changed = false;
+    let mut double_next = false; // set by the small tsu (っ/ッ)
+
+    for ch in name.chars() {
+        if let Some(reading) = ch.to_pinyin() {
+            out.push_str(reading.plain());
+            changed = true;
+            continue;
+        }
+
+        let kana = katakana_to_hiragana(ch);
+        match kana {
+            'っ' => {
+                double_next = true;
+                changed = true;
+            }
+            // Small ya/yu/yo combine with the previous syllable: き+ゃ -> kya, し+ゃ -> sha
+            'ゃ' | 'ゅ' | 'ょ' if out.ends_with('i') => {
+                out.pop();
+                let vowel = match kana {
+                    'ゃ' => 'a',
+                    'ゅ' => 'u',
+                    _ => 'o',
+                };
+                if !(out.ends_with("sh") || out.ends_with("ch") || out.ends_with('j')) {
+                    out.push('y');
+                }
+                out.push(vowel);
+            }
+            // Small vowels replace the previous vowel: ふ+ぁ -> fa, て+ぃ -> ti
+            'ぁ' | 'ぃ' | 'ぅ' | 'ぇ' | 'ぉ' if out.ends_with(is_vowel) => {
+                out.pop();
+                out.push_str(kana_syllable(kana).unwrap_or(""));
+            }
+            // The long vowel mark repeats the previous vowel: コーヒー -> koohii
+            'ー' => {
+                if let Some(vowel) = out.chars().last().filter(|c| is_vowel(*c)) {
+                    out.push(vowel);
+                }
+            }
+            _ => match kana_syllable(kana) {
+                Some(syllable) => {
+                    if double_next {
+                        if let Some(first) = syllable.chars().next().filter(|c| !is_vowel(*c)) {
+                            out.push(first);
+                        }
+                        double_next = false;
+                    }
+                    out.push_str(syllable);
+                    changed = true;
+                }
+                None => {
+                    double_next = false;
+                    out.extend(ch.to_lowercase());
+                }
+            },
+        }
+    }
+
+    changed.then_some(out)
+}
+
+fn is_vowel(c: char) -> bool {
+    matches!(c, 'a' | 'i' | 'u' | 'e' | 'o')
+}
+
+// Katakana and hiragana share a layout offset by 0x60 code points
+fn katakana_to_hiragana(ch: char) -> char {
+    if ('\u{30A1}'..='\u{30F6}').contains(&ch) {
+        char::from_u32(ch as u32 - 0x60).unwrap_or(ch)
+    } else {
+        ch
+    }
+}
+
+fn kana_syllable(kana: char) -> Option<&'static str> {
+    let romaji = match kana {
+        'あ' | 'ぁ' => "a", 'い' | 'ぃ' => "i", 'う' | 'ぅ' => "u", 'え' | 'ぇ' => "e", 'お' | 'ぉ' => "o",
+        'か' => "ka", 'き' => "ki", 'く' => "ku", 'け' => "ke", 'こ' => "ko",
+        'が' => "ga", 'ぎ' => "gi", 'ぐ' => "gu", 'げ' => "ge", 'ご' => "go",
+        'さ' => "sa", 'し' => "shi", 'す' => "su", 'せ' => "se", 'そ' => "so",
+        'ざ' => "za", 'じ' => "ji", 'ず' => "zu", 'ぜ' => "ze", 'ぞ' => "zo",
+        'た' => "ta", 'ち' => "chi", 'つ' => "tsu", 'て' => "te", 'と' => "to",
+        'だ' => "da", 'ぢ' => "ji", 'づ' => "zu", 'で' => "de", 'ど' => "do",
+        'な' => "na", 'に' => "ni", 'ぬ' => "nu", 'ね' => "ne", 'の' => "no",
+        'は' => "ha", 'ひ' => "hi", 'ふ' => "fu", 'へ' => "he", 'ほ' => "ho",
+        'ば' => "ba", 'び' => "bi", 'ぶ' => "bu", 'べ' => "be", 'ぼ' => "bo",
+        'ぱ' => "pa", 'ぴ' => "pi", 'ぷ' => "pu", 'ぺ' => "pe", 'ぽ' => "po",
+        'ま' => "ma", 'み' => "mi", 'む' => "mu", 'め' => "me", 'も' => "mo",
+        'や' | 'ゃ' => "ya", 'ゆ' | 'ゅ' => "yu", 'よ' | 'ょ' => "yo",
+        'ら' => "ra", 'り' => "ri", 'る' => "ru", 'れ' => "re", 'ろ' => "ro",
+        'わ' | 'ゎ' => "wa", 'ゐ' => "wi", 'ゑ' => "we", 'を' => "wo", 'ん' => "n",
+        'ゔ' => "vu",
+        _ => return None,
+    };
+    Some(romaji)
+}
@@ -0,0 +1,197 @@
+// OS trash through the `trash` crate: move files into the Recycle Bin / Trash, list what is
+// there and move items back to where they were deleted from. Listing and restoring use the
+// crate's `os_limited` API, which Windows and the freedesktop.org trash (Linux, BSD) have; macOS
+// has no API for reading the trash, so there only trashing works.
+
+use rusqlite::{Connection, OptionalExtension};
+use serde::Serialize;
+use specta::Type;
+use std::fs;
+use std::path::{Path, MAIN_SEPARATOR};
+use tauri::State;
+use tracing::{info, warn};
+
+use crate::error::{AppError, ErrorCode};
+use crate::{db, escape_like, invalidate_index, AppState, LIKE_ESCAPE};
+
+#[derive(Debug, Serialize, Clone, Type)]
+pub struct TrashEntry {
+    // The trash's id for the item; pass it to restore_from_trash
+    pub id: String,
+    pub name: String,
+    // Where it was deleted from
+    pub original_path: Option<String>,
+    pub deleted_at: Option<i64>,
+    pub size: Option<u64>,
+    pub is_dir: bool,
+    // The original path is still in the index, i.e. search can find it but it is missing on disk
+    pub indexed: bool,
+}
+
+#[derive(Debug, Serialize, Clone, Type)]
+pub struct TrashOutcome {
+    pub path: String,
+    pub success: bool,
+    pub error: Option<String>,
+}
+
+fn is_indexed(conn: &Connection, path: &Path) -> bool {
+    conn.query_row("SELECT 1 FROM files WHERE path = ?1", [path.to_string_lossy()], |_| Ok(()))
+        .optional()
+        .map(|found| found.is_some())
+        .unwrap_or(false)
+}
+
+fn to_entry(conn: &Connection, item: &trash::TrashItem) -> TrashEntry {
+    let original = item.original_path();
+    let (size, is_dir) = os::size(item);
+    TrashEntry {
+        id: item.id.to_string_lossy().to_string(),
+        name: original.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default(),
+        indexed: is_indexed(conn, &original),
+        original_path: Some(original.to_string_lossy().to_string()),
+        deleted_at: Some(item.time_deleted),
+        size,
+        is_dir,
+    }
+}
+
+// Drop a trashed path, and everything under it when it was a folder, from the index, recent
+// files, favorites and the embeddings of its contents. Tags stay, for when it is restored.
+fn forget(conn: &Connection, path: &str) -> rusqlite::Result<()> {
+    let under = format!("{}%", escape_like(&format!("{}{}", path.trim_end_matches(MAIN_SEPARATOR), MAIN_SEPARATOR)));
+    let matches = format!("(path = ?1 OR path LIKE ?2 {})", LIKE_ESCAPE);
+    for table in ["files", "recent_files", "favorite_files", "file_embeddings", "content_chunks"] {
+        conn.execute(&format!("DELETE FROM {} WHERE {}", table, matches), [path, &under])?;
+    }
+    Ok(())
+}
+
+// Move files and folders to the OS trash and forget them in the index. Each path succeeds or
+// fails on its own.
+#[tauri::command]
+#[specta::specta]
+pub async fn delete_to_trash(paths: Vec<String>, state: State<'_, AppState>) -> Result<Vec<TrashOutcome>, AppError> {
+    let mut outcomes = Vec::with_capacity(paths.len());
+    let mut trashed = Vec::new();
+    db::blocking(|| {
+        for path in paths {
+            let result = match fs::symlink_metadata(&path) {
+                Ok(_) => trash::delete(&path).map_err(|e| e.to_string()),
+                Err(e) => Err(format!("{}: {}", path, e)),
+            };
+            if let Err(e) = &result {
+                warn!("Could not move {} to the trash: {}", path, e);
+            } else {
+                trashed.push(path.clone());
+            }
+            outcomes.push(TrashOutcome {
+                path,
+                success: result.is_ok(),
+                error: result.err(),
+            });
+        }
+    });
+    if trashed.is_empty() {
+        return Ok(outcomes);
+    }
+
+    db::write(&state.db, |db| {
+        for path in &trashed {
+            if let Err(e) = forget(db, path) {
+                warn!("Moved {} to the trash but could not remove it from the index: {}", path, e);
+            }
+        }
+        Ok(())
+    })?;
+    invalidate_index(&state);
+    info!("Moved {} items to the trash", trashed.len());
+    Ok(outcomes)
+}
+
+// List trashed items, newest first. `query` keeps items whose name contains it (case-insensitive).
+#[tauri::command]
+#[specta::specta]
+pub async fn list_trash(query: Option<String>, state: State<'_, AppState>) -> Result<Vec<TrashEntry>, AppError> {
+    let needle = query.map(|q| q.trim().to_lowercase()).filter(|q| !q.is_empty());
+    let items = db::blocking(os::list).map_err(|e| e.to_string())?;
+
+    db::write(&state.db, |db| {
+        let mut entries: Vec<TrashEntry> = items
+            .iter()
+            .map(|item| to_entry(db, item))
+            .filter(|entry| needle.as_ref().is_none_or(|needle| entry.name.to_lowercase().contains(needle)))
+            .collect();
+        entries.sort_by_key(|entry| std::cmp::Reverse(entry.deleted_at));
+        Ok(entries)
+    })
+}
+
+// Move a trashed item back to its original location and return that path
+#[tauri::command]
+#[specta::specta]
+pub async fn restore_from_trash(id: String) -> Result<String, AppError> {
+    db::blocking(|| {
+        // Only ids that are actually in the trash are accepted, so this can't move arbitrary files
+        let item = os::list()
+            .map_err(|e| e.to_string())?
+            .into_iter()
+            .find(|item| item.id.to_string_lossy() == id)
+            .ok_or_else(|| AppError::new(ErrorCode::NotFound, format!("Not in the trash: {}", id)))?;
+
+        let original = item.original_path();
+        if fs::symlink_metadata(&original).is_ok() {
+            return Err(AppError::new(ErrorCode::InvalidInput, format!("Something already exists at {}", original.display())));
+        }
+        if let Some(parent) = original.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        os::restore(item).map_err(|e| format!("Could not restore {}: {}", original.display(), e))?;
+
+        info!("Restored {} from the trash", original.display());
+        Ok(original.to_string_lossy().to_string())
+    })
+}
+
+#[cfg(not(target_os = "macos"))]
+mod os {
+    use trash::{os_limited, Error, TrashItem, TrashItemSize};
+
+    pub fn list() -> Result<Vec<TrashItem>, Error> {
+        os_limited::list()
+    }
+
+    // Size of a file, and whether the item is a folder
+    pub fn size(item: &TrashItem) -> (Option<u64>, bool) {
+        match os_limited::metadata(item).map(|metadata| metadata.size) {
+            Ok(TrashItemSize::Bytes(bytes)) => (Some(bytes), false),
+            Ok(TrashItemSize::Entries(_)) => (None, true),
+            Err(_) => (None, false),
+        }
+    }
+
+    pub fn restore(item: TrashItem) -> Result<(), Error> {
+        os_limited::restore_all([item])
+    }
+}
+
+#[cfg(target_os = "macos")]
+mod os {
+    use trash::{Error, TrashItem};
+
+    fn unsupported() -> Error {
+        Error::Unknown { description: "macOS doesn't let apps read the trash; use Put Back in Finder".to_string() }
+    }
+
+    pub fn list() -> Result<Vec<TrashItem>, Error> {
+        Err(unsupported())
+    }
+
+    pub fn size(_item: &TrashItem) -> (Option<u64>, bool) {
+        (None, false)
+    }
+
+    pub fn restore(_item: TrashItem) -> Result<(), Error> {
+        Err(unsupported())
+    }
+}
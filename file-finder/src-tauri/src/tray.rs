@@ -0,0 +1,88 @@
+// Tray icon with quick actions (search, pause or rerun indexing) and the index size as its
+// tooltip. Closing the main window hides it to the tray; Quit in the tray menu exits.
+
+use tauri::menu::{CheckMenuItem, Menu, MenuItem, PredefinedMenuItem};
+use tauri::tray::{MouseButton, MouseButtonState, TrayIconBuilder, TrayIconEvent};
+use tauri::{AppHandle, Emitter, Manager};
+use tracing::warn;
+
+use crate::AppState;
+
+const TRAY_ID: &str = "main";
+// Sent to the window so the search box takes focus when it is brought up from the tray or overlay
+const FOCUS_SEARCH_EVENT: &str = "focus-search";
+
+pub fn create(app: &AppHandle) -> tauri::Result<()> {
+    let search = MenuItem::with_id(app, "search", "Search…", true, None::<&str>)?;
+    let pause = CheckMenuItem::with_id(app, "pause", "Pause indexing", true, crate::indexing_paused(), None::<&str>)?;
+    let reindex = MenuItem::with_id(app, "reindex", "Reindex now", true, None::<&str>)?;
+    let separator = PredefinedMenuItem::separator(app)?;
+    let quit = MenuItem::with_id(app, "quit", "Quit", true, None::<&str>)?;
+    let menu = Menu::with_items(app, &[&search, &pause, &reindex, &separator, &quit])?;
+
+    let mut builder = TrayIconBuilder::with_id(TRAY_ID)
+        .menu(&menu)
+        .show_menu_on_left_click(false)
+        .on_menu_event(move |app, event| match event.id.as_ref() {
+            "search" => show_search(app, None),
+            "pause" => {
+                let paused = !crate::indexing_paused();
+                crate::set_indexing_paused(paused);
+                if let Err(e) = pause.set_checked(paused) {
+                    warn!("Failed to update tray menu: {}", e);
+                }
+                refresh_tooltip(app);
+            }
+            "reindex" => {
+                let app = app.clone();
+                tauri::async_runtime::spawn(async move {
+                    if let Err(e) = crate::start_indexing(app).await {
+                        warn!("Failed to start indexing from the tray: {}", e);
+                    }
+                });
+            }
+            "quit" => app.exit(0),
+            _ => {}
+        })
+        .on_tray_icon_event(|tray, event| {
+            if let TrayIconEvent::Click { button: MouseButton::Left, button_state: MouseButtonState::Up, .. } = event {
+                show_search(tray.app_handle(), None);
+            }
+        });
+    if let Some(icon) = app.default_window_icon() {
+        builder = builder.icon(icon.clone());
+    }
+    builder.build(app)?;
+
+    refresh_tooltip(app);
+    Ok(())
+}
+
+// Bring the main window back and put the cursor in the search box, searching for `query` if given
+pub fn show_search(app: &AppHandle, query: Option<String>) {
+    let Some(window) = app.get_webview_window("main") else {
+        return;
+    };
+    let shown = window.unminimize().and_then(|_| window.show()).and_then(|_| window.set_focus());
+    if let Err(e) = shown {
+        warn!("Failed to show the main window: {}", e);
+    }
+    if let Err(e) = window.emit(FOCUS_SEARCH_EVENT, query) {
+        warn!("Failed to send {} event: {}", FOCUS_SEARCH_EVENT, e);
+    }
+}
+
+// "N files indexed", updated at startup, after each indexing run and on pause
+pub fn refresh_tooltip(app: &AppHandle) {
+    let Some(tray) = app.tray_by_id(TRAY_ID) else {
+        return;
+    };
+    let tooltip = match crate::index_status(&app.state::<AppState>()) {
+        Ok(status) => format!("File Finder: {} files indexed", status.total_files),
+        Err(_) => "File Finder".to_string(),
+    };
+    let tooltip = if crate::indexing_paused() { format!("{} (indexing paused)", tooltip) } else { tooltip };
+    if let Err(e) = tray.set_tooltip(Some(tooltip)) {
+        warn!("Failed to update tray tooltip: {}", e);
+    }
+}
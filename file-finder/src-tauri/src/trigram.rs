@@ -0,0 +1,267 @@
+// Trigram index over file names (SQLite FTS5 `trigram` tokenizer). Substring, prefix and
+// suffix queries look up candidate rows here instead of scanning `files` with LIKE '%x%',
+// so large indexes no longer need the small emergency LIMITs that dropped valid matches.
+//
+// New files are added in batches by `sync`. Deleted and renamed files are taken out and
+// re-added by triggers on `files`, so they stop matching as soon as the row changes.
+
+use rusqlite::{Connection, Result as SqlResult};
+use std::path::Path;
+use tauri::State;
+use tracing::{info, warn};
+
+use crate::error::AppError;
+use crate::{db, invalidate_search_cache, AppState};
+
+// The trigram tokenizer cannot match fragments shorter than this
+const MIN_FRAGMENT_CHARS: usize = 3;
+
+// Renames only touch rows the index already has: re-adding one that `sync` hasn't reached yet
+// would raise MAX(rowid) past the missing ones and make `is_current` wrong
+const TRIGGERS: &str = "
+    CREATE TRIGGER IF NOT EXISTS files_fts_delete AFTER DELETE ON files BEGIN
+        DELETE FROM files_fts WHERE rowid = old.id;
+    END;
+    CREATE TRIGGER IF NOT EXISTS files_fts_update AFTER UPDATE OF name, name_romanized ON files
+    WHEN EXISTS (SELECT 1 FROM files_fts WHERE rowid = old.id) BEGIN
+        DELETE FROM files_fts WHERE rowid = old.id;
+        INSERT INTO files_fts (rowid, name, name_romanized) VALUES (new.id, new.name, new.name_romanized);
+    END;";
+
+pub fn create_table(conn: &Connection) -> SqlResult<()> {
+    // Indexes built before the triggers still hold deleted and renamed files. Dropping one is
+    // quick, and the startup sync builds it again in the background.
+    let has_triggers: bool = conn.query_row(
+        "SELECT EXISTS (SELECT 1 FROM sqlite_master WHERE type = 'trigger' AND name = 'files_fts_update')",
+        [],
+        |row| row.get(0),
+    )?;
+    if !has_triggers {
+        conn.execute("DROP TABLE IF EXISTS files_fts", [])?;
+    }
+
+    // rowid mirrors files.id; romanized names are indexed so pinyin/romaji input finds CJK names
+    conn.execute(
+        "CREATE VIRTUAL TABLE IF NOT EXISTS files_fts USING fts5(name, name_romanized, tokenize = 'trigram')",
+        [],
+    )?;
+    conn.execute_batch(TRIGGERS)
+}
+
+// Add files that are not in the index yet. Returns the number of rows added.
+pub fn sync(conn: &Connection) -> SqlResult<usize> {
+    conn.execute(
+        "INSERT INTO files_fts (rowid, name, name_romanized)
+         SELECT id, name, name_romanized FROM files WHERE id NOT IN (SELECT rowid FROM files_fts)",
+        [],
+    )
+}
+
+// Build the index again from `files`. Returns the number of files indexed.
+fn rebuild(conn: &mut Connection) -> SqlResult<usize> {
+    let tx = conn.transaction()?;
+    tx.execute("DELETE FROM files_fts", [])?;
+    let indexed = sync(&tx)?;
+    // Merge the b-trees the inserts left behind into one
+    tx.execute("INSERT INTO files_fts (files_fts) VALUES ('optimize')", [])?;
+    tx.commit()?;
+    Ok(indexed)
+}
+
+// Maintenance for an index that drifted from `files`, e.g. after the database was edited by hand
+#[tauri::command]
+#[specta::specta]
+pub async fn rebuild_fts(state: State<'_, AppState>) -> Result<usize, AppError> {
+    let indexed = db::write(&state.db, |db| Ok(rebuild(db)?))?;
+    invalidate_search_cache(&state);
+    info!("Trigram index: rebuilt with {} files", indexed);
+    Ok(indexed)
+}
+
+// Used at startup so building the index for an existing database doesn't block the window
+pub fn sync_in_background(db_path: &Path) {
+    let db_path = db_path.to_path_buf();
+    std::thread::spawn(move || {
+        let result = db::open(&db_path).and_then(|conn| sync(&conn));
+        match result {
+            Ok(0) => {}
+            Ok(added) => info!("Trigram index: added {} files", added),
+            Err(e) => warn!("Failed to build trigram index: {}", e),
+        }
+    });
+}
+
+// Files added since the last sync are invisible to the index, so searches fall back to
+// LIKE scans until it catches up (first launch after an upgrade, or mid-indexing)
+pub fn is_current(conn: &Connection) -> bool {
+    conn.query_row(
+        "SELECT NOT EXISTS (SELECT 1 FROM files WHERE id > (SELECT COALESCE(MAX(rowid), 0) FROM files_fts))",
+        [],
+        |row| row.get(0),
+    )
+    .unwrap_or(false)
+}
+
+// FTS5 MATCH expression whose hits are a superset of rows matching any of the LIKE patterns.
+// None when some pattern has no literal run long enough to look up.
+pub fn match_expression(like_patterns: &[String]) -> Option<String> {
+    let alternatives: Option<Vec<String>> = like_patterns
+        .iter()
+        .map(|pattern| {
+            let phrases: Vec<String> = like_fragments(pattern)
+                .into_iter()
+                .filter(|fragment| fragment.chars().count() >= MIN_FRAGMENT_CHARS)
+                .map(|fragment| format!("\"{}\"", fragment.replace('"', "\"\"")))
+                .collect();
+            if phrases.is_empty() {
+                None
+            } else {
+                Some(format!("({})", phrases.join(" AND ")))
+            }
+        })
+        .collect();
+
+    let alternatives = alternatives?;
+    if alternatives.is_empty() {
+        return None;
+    }
+    Some(alternatives.join(" OR "))
+}
+
+// Literal runs between the wildcards of a LIKE pattern written with escape_like()
+fn like_fragments(pattern: &str) -> Vec<String> {
+    let mut fragments = Vec::new();
+    let mut current = String::new();
+    let mut chars = pattern.chars();
+
+    while let Some(ch) = chars.next() {
+        match ch {
+            '\\' => {
+                if let Some(escaped) = chars.next() {
+                    current.push(escaped);
+                }
+            }
+            '%' | '_' => {
+                if !current.is_empty() {
+                    fragments.push(std::mem::take(&mut current));
+                }
+            }
+            c => current.push(c),
+        }
+    }
+    if !current.is_empty() {
+        fragments.push(current);
+    }
+    fragments
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::escape_like;
+
+    // The LIKE pattern a substring search builds for `query`
+    fn substring(query: &str) -> String {
+        format!("%{}%", escape_like(query))
+    }
+
+    fn expression(query: &str) -> Option<String> {
+        match_expression(&[substring(query)])
+    }
+
+    #[test]
+    fn escape_like_escapes_wildcards_and_the_escape_character() {
+        assert_eq!(escape_like("100%"), "100\\%");
+        assert_eq!(escape_like("my_file"), "my\\_file");
+        assert_eq!(escape_like("C:\\docs"), "C:\\\\docs");
+        assert_eq!(escape_like("%_\\"), "\\%\\_\\\\");
+    }
+
+    #[test]
+    fn escape_like_leaves_other_characters_alone() {
+        assert_eq!(escape_like("it's \"quoted\" *.txt a-b"), "it's \"quoted\" *.txt a-b");
+        assert_eq!(escape_like(""), "");
+    }
+
+    #[test]
+    fn fragments_are_quoted_phrases() {
+        assert_eq!(expression("report").as_deref(), Some("(\"report\")"));
+        assert_eq!(match_expression(&["rep_ort%".to_string()]).as_deref(), Some("(\"rep\" AND \"ort\")"));
+    }
+
+    #[test]
+    fn double_quotes_are_doubled() {
+        assert_eq!(expression("say \"hi\"").as_deref(), Some("(\"say \"\"hi\"\"\")"));
+        assert_eq!(expression("\"\"\"").as_deref(), Some("(\"\"\"\"\"\"\"\")"));
+    }
+
+    #[test]
+    fn fts_syntax_stays_inside_the_phrase() {
+        assert_eq!(expression("it's").as_deref(), Some("(\"it's\")"));
+        assert_eq!(expression("-draft").as_deref(), Some("(\"-draft\")"));
+        assert_eq!(expression("*.txt").as_deref(), Some("(\"*.txt\")"));
+        assert_eq!(expression("NEAR").as_deref(), Some("(\"NEAR\")"));
+        assert_eq!(expression("NEAR(a b)").as_deref(), Some("(\"NEAR(a b)\")"));
+        assert_eq!(expression("cats AND dogs").as_deref(), Some("(\"cats AND dogs\")"));
+        assert_eq!(expression("OR").as_deref(), None);
+        assert_eq!(expression("NOT").as_deref(), Some("(\"NOT\")"));
+    }
+
+    #[test]
+    fn escaped_wildcards_are_literal() {
+        assert_eq!(expression("100%").as_deref(), Some("(\"100%\")"));
+        assert_eq!(expression("my_file").as_deref(), Some("(\"my_file\")"));
+        assert_eq!(expression("a\\b").as_deref(), Some("(\"a\\b\")"));
+    }
+
+    #[test]
+    fn short_fragments_give_no_expression() {
+        assert_eq!(expression("ab"), None);
+        assert_eq!(match_expression(&["ab%cd".to_string()]), None);
+        assert_eq!(match_expression(&[substring("report"), substring("ab")]), None);
+        assert_eq!(match_expression(&[]), None);
+    }
+
+    #[test]
+    fn patterns_are_alternatives() {
+        assert_eq!(
+            match_expression(&[substring("report"), substring("notes")]).as_deref(),
+            Some("(\"report\") OR (\"notes\")")
+        );
+    }
+
+    // Every expression must parse in FTS5 and find the name it came from
+    #[test]
+    fn expressions_match_in_fts5() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute("CREATE VIRTUAL TABLE names USING fts5(name, tokenize = 'trigram')", []).unwrap();
+        let names = [
+            "say \"hi\".txt",
+            "it's mine.md",
+            "-draft.doc",
+            "notes*.txt",
+            "NEAR(a b).txt",
+            "cats AND dogs.png",
+            "NOT this.rs",
+            "100% done.txt",
+            "my_file.txt",
+            "a\\b.txt",
+        ];
+        for name in names {
+            conn.execute("INSERT INTO names (name) VALUES (?1)", [name]).unwrap();
+        }
+        let queries =
+            ["say \"hi\"", "it's", "-draft", "s*.txt", "NEAR(a b)", "cats AND dogs", "NOT", "100%", "my_file", "a\\b"];
+        for (query, name) in queries.iter().zip(names) {
+            let expression = expression(query).unwrap();
+            let found: Vec<String> = conn
+                .prepare("SELECT name FROM names WHERE names MATCH ?1")
+                .unwrap()
+                .query_map([&expression], |row| row.get(0))
+                .unwrap()
+                .collect::<SqlResult<_>>()
+                .unwrap_or_else(|e| panic!("{:?} -> {}: {}", query, expression, e));
+            assert!(found.iter().any(|found| found == name), "{:?} -> {} found {:?}", query, expression, found);
+        }
+    }
+}
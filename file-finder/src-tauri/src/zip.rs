@@ -0,0 +1,260 @@
+// Just enough of the ZIP format to list an archive and read single members: the central
+// directory, stored and deflated entries. Office documents (docx, xlsx, pptx) are ZIP files of
+// XML parts. ZIP64, encryption and multi-disk archives are not supported.
+
+use flate2::read::DeflateDecoder;
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::Path;
+
+const END_OF_CENTRAL_DIRECTORY: u32 = 0x0605_4b50;
+const CENTRAL_DIRECTORY_HEADER: u32 = 0x0201_4b50;
+const LOCAL_FILE_HEADER: u32 = 0x0403_4b50;
+
+// The end record is 22 bytes plus a comment of up to 64 KB
+const END_SEARCH_BYTES: u64 = 22 + 65_535;
+
+const STORED: u16 = 0;
+const DEFLATED: u16 = 8;
+
+pub struct ZipEntry {
+    pub name: String,
+    pub compressed_size: u64,
+    // DOS date and time, as stored
+    pub modified: (u16, u16),
+    pub is_dir: bool,
+    method: u16,
+    encrypted: bool,
+    header_offset: u64,
+}
+
+pub struct ZipArchive {
+    file: File,
+    pub entries: Vec<ZipEntry>,
+}
+
+fn u16_at(bytes: &[u8], at: usize) -> Option<u16> {
+    Some(u16::from_le_bytes(bytes.get(at..at + 2)?.try_into().ok()?))
+}
+
+fn u32_at(bytes: &[u8], at: usize) -> Option<u32> {
+    Some(u32::from_le_bytes(bytes.get(at..at + 4)?.try_into().ok()?))
+}
+
+impl ZipArchive {
+    pub fn open(path: &Path) -> Result<ZipArchive, String> {
+        let not_zip = || format!("{} is not a ZIP archive", path.display());
+        let mut file = File::open(path).map_err(|e| format!("Could not read {}: {}", path.display(), e))?;
+        let length = file.metadata().map_err(|e| e.to_string())?.len();
+        let tail_start = length.saturating_sub(END_SEARCH_BYTES);
+        let mut tail = Vec::new();
+        file.seek(SeekFrom::Start(tail_start)).map_err(|e| e.to_string())?;
+        file.read_to_end(&mut tail).map_err(|e| e.to_string())?;
+
+        let end = (0..tail.len().saturating_sub(21))
+            .rev()
+            .find(|&at| u32_at(&tail, at) == Some(END_OF_CENTRAL_DIRECTORY))
+            .ok_or_else(not_zip)?;
+        let count = u16_at(&tail, end + 10).ok_or_else(not_zip)? as usize;
+        let directory_size = u32_at(&tail, end + 12).ok_or_else(not_zip)? as u64;
+        let directory_offset = u32_at(&tail, end + 16).ok_or_else(not_zip)? as u64;
+        if directory_offset == u32::MAX as u64 || directory_offset + directory_size > length {
+            return Err(format!("{} needs ZIP64, which is not supported", path.display()));
+        }
+
+        let mut directory = vec![0; directory_size as usize];
+        file.seek(SeekFrom::Start(directory_offset)).map_err(|e| e.to_string())?;
+        file.read_exact(&mut directory).map_err(|e| e.to_string())?;
+
+        let mut entries = Vec::with_capacity(count);
+        let mut at = 0;
+        while entries.len() < count && u32_at(&directory, at) == Some(CENTRAL_DIRECTORY_HEADER) {
+            let field = |offset: usize| u16_at(&directory, at + offset).ok_or_else(not_zip);
+            let wide = |offset: usize| u32_at(&directory, at + offset).ok_or_else(not_zip);
+            let flags = field(8)?;
+            let (name_length, extra_length, comment_length) = (field(28)? as usize, field(30)? as usize, field(32)? as usize);
+            let name_bytes = directory.get(at + 46..at + 46 + name_length).ok_or_else(not_zip)?;
+            // Bit 11 marks UTF-8 names; older tools wrote code page 437, which is ASCII for common names
+            let name = String::from_utf8_lossy(name_bytes).replace('\\', "/");
+            entries.push(ZipEntry {
+                is_dir: name.ends_with('/'),
+                name,
+                compressed_size: wide(20)? as u64,
+                modified: (field(14)?, field(12)?),
+                method: field(10)?,
+                encrypted: flags & 1 != 0,
+                header_offset: wide(42)? as u64,
+            });
+            at += 46 + name_length + extra_length + comment_length;
+        }
+        Ok(ZipArchive { file, entries })
+    }
+
+    // A member's uncompressed contents as a stream
+    pub fn reader(&mut self, name: &str) -> Result<Box<dyn Read + '_>, String> {
+        let entry = self
+            .entries
+            .iter()
+            .find(|entry| entry.name == name)
+            .ok_or_else(|| format!("{} is not in the archive", name))?;
+        if entry.encrypted {
+            return Err(format!("{} is encrypted", name));
+        }
+        let mut header = [0; 30];
+        self.file.seek(SeekFrom::Start(entry.header_offset)).map_err(|e| e.to_string())?;
+        self.file.read_exact(&mut header).map_err(|e| e.to_string())?;
+        if u32_at(&header, 0) != Some(LOCAL_FILE_HEADER) {
+            return Err(format!("Damaged archive entry {}", name));
+        }
+        // The local header repeats the name and may have a different extra field
+        let skip = u16_at(&header, 26).unwrap_or(0) as i64 + u16_at(&header, 28).unwrap_or(0) as i64;
+        self.file.seek(SeekFrom::Current(skip)).map_err(|e| e.to_string())?;
+
+        let data = (&self.file).take(entry.compressed_size);
+        match entry.method {
+            STORED => Ok(Box::new(data)),
+            DEFLATED => Ok(Box::new(DeflateDecoder::new(data))),
+            method => Err(format!("{} uses unsupported compression method {}", name, method)),
+        }
+    }
+
+    // Up to `limit` bytes of a member, uncompressed
+    pub fn read(&mut self, name: &str, limit: u64) -> Result<Vec<u8>, String> {
+        let mut out = Vec::new();
+        self.reader(name)?
+            .take(limit)
+            .read_to_end(&mut out)
+            .map_err(|e| format!("Could not read {}: {}", name, e))?;
+        Ok(out)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flate2::write::DeflateEncoder;
+    use flate2::Compression;
+    use std::io::Write;
+
+    const DOS_DATE: u16 = 0x5821; // 2024-01-01
+    const DOS_TIME: u16 = 0x6000; // 12:00:00
+
+    // A ZIP of (name, data, method) members with `comment` after the end record
+    fn build(members: &[(&str, &str, u16)], comment: &[u8]) -> Vec<u8> {
+        let mut out = Vec::new();
+        let mut directory = Vec::new();
+        for &(name, data, method) in members {
+            let data = data.as_bytes();
+            let packed = if method == DEFLATED {
+                let mut encoder = DeflateEncoder::new(Vec::new(), Compression::default());
+                encoder.write_all(data).unwrap();
+                encoder.finish().unwrap()
+            } else {
+                data.to_vec()
+            };
+            let offset = out.len() as u32;
+
+            out.extend(LOCAL_FILE_HEADER.to_le_bytes());
+            out.extend([20, 0, 0, 0]); // version needed, flags
+            out.extend(method.to_le_bytes());
+            out.extend(DOS_TIME.to_le_bytes());
+            out.extend(DOS_DATE.to_le_bytes());
+            out.extend([0; 4]); // CRC, not checked
+            out.extend((packed.len() as u32).to_le_bytes());
+            out.extend((data.len() as u32).to_le_bytes());
+            out.extend((name.len() as u16).to_le_bytes());
+            out.extend(0u16.to_le_bytes());
+            out.extend(name.as_bytes());
+            out.extend(&packed);
+
+            directory.extend(CENTRAL_DIRECTORY_HEADER.to_le_bytes());
+            directory.extend([20, 0, 20, 0]); // version made by, version needed
+            directory.extend(0u16.to_le_bytes());
+            directory.extend(method.to_le_bytes());
+            directory.extend(DOS_TIME.to_le_bytes());
+            directory.extend(DOS_DATE.to_le_bytes());
+            directory.extend([0; 4]);
+            directory.extend((packed.len() as u32).to_le_bytes());
+            directory.extend((data.len() as u32).to_le_bytes());
+            directory.extend((name.len() as u16).to_le_bytes());
+            directory.extend([0; 12]); // extra and comment lengths, disk, attributes
+            directory.extend(offset.to_le_bytes());
+            directory.extend(name.as_bytes());
+        }
+        let directory_offset = out.len() as u32;
+        out.extend(&directory);
+        out.extend(END_OF_CENTRAL_DIRECTORY.to_le_bytes());
+        out.extend([0; 4]); // disk numbers
+        out.extend((members.len() as u16).to_le_bytes());
+        out.extend((members.len() as u16).to_le_bytes());
+        out.extend((directory.len() as u32).to_le_bytes());
+        out.extend(directory_offset.to_le_bytes());
+        out.extend((comment.len() as u16).to_le_bytes());
+        out.extend(comment);
+        out
+    }
+
+    // Open `bytes` as an archive; `name` keeps the temp files of parallel tests apart
+    fn open(name: &str, bytes: &[u8]) -> Result<ZipArchive, String> {
+        let path = std::env::temp_dir().join(format!("file-finder-zip-test-{}-{}.zip", std::process::id(), name));
+        std::fs::write(&path, bytes).unwrap();
+        let archive = ZipArchive::open(&path);
+        let _ = std::fs::remove_file(&path);
+        archive
+    }
+
+    fn sample() -> Vec<u8> {
+        let todo = "todo ".repeat(200);
+        build(&[("docs/", "", STORED), ("docs/readme.txt", "hello", STORED), ("notes\\todo.md", todo.as_str(), DEFLATED)], b"")
+    }
+
+    #[test]
+    fn members_are_listed_from_the_central_directory() {
+        let archive = open("list", &sample()).unwrap();
+        let names: Vec<&str> = archive.entries.iter().map(|entry| entry.name.as_str()).collect();
+        assert_eq!(names, vec!["docs/", "docs/readme.txt", "notes/todo.md"]);
+        let dirs: Vec<bool> = archive.entries.iter().map(|entry| entry.is_dir).collect();
+        assert_eq!(dirs, vec![true, false, false]);
+        assert_eq!(archive.entries[1].modified, (DOS_DATE, DOS_TIME));
+        assert_eq!(archive.entries[1].compressed_size, 5);
+    }
+
+    #[test]
+    fn stored_and_deflated_members_read_back() {
+        let mut archive = open("read", &sample()).unwrap();
+        assert_eq!(archive.read("docs/readme.txt", 100).unwrap(), b"hello");
+        assert_eq!(archive.read("notes/todo.md", 10_000).unwrap(), "todo ".repeat(200).into_bytes());
+        assert_eq!(archive.read("notes/todo.md", 7).unwrap(), b"todo to");
+    }
+
+    #[test]
+    fn the_end_record_is_found_behind_a_comment() {
+        let bytes = build(&[("a.txt", "a", STORED)], b"made by a test");
+        let mut archive = open("comment", &bytes).unwrap();
+        assert_eq!(archive.read("a.txt", 10).unwrap(), b"a");
+    }
+
+    #[test]
+    fn missing_and_unsupported_members_are_errors() {
+        let bytes = build(&[("a.txt", "a", STORED), ("b.bz2", "b", 12)], b"");
+        let mut archive = open("errors", &bytes).unwrap();
+        assert_eq!(archive.read("c.txt", 10).unwrap_err(), "c.txt is not in the archive");
+        assert_eq!(archive.read("b.bz2", 10).unwrap_err(), "b.bz2 uses unsupported compression method 12");
+    }
+
+    #[test]
+    fn other_files_are_not_zip_archives() {
+        let error = open("plain", b"just some text, no archive here").err().unwrap();
+        assert!(error.ends_with("is not a ZIP archive"), "{}", error);
+        assert!(open("empty", b"").is_err());
+    }
+
+    #[test]
+    fn zip64_archives_are_refused() {
+        let mut bytes = build(&[("a.txt", "a", STORED)], b"");
+        let offset_field = bytes.len() - 6;
+        bytes[offset_field..offset_field + 4].copy_from_slice(&u32::MAX.to_le_bytes());
+        let error = open("zip64", &bytes).err().unwrap();
+        assert!(error.ends_with("needs ZIP64, which is not supported"), "{}", error);
+    }
+}
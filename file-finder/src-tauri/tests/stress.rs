@@ -0,0 +1,196 @@
+// Concurrency stress test: hundreds of searches, indexing runs, opens and settings reloads
+// fired from many threads at once against a throwaway database and folder tree. Any failed
+// call, poisoned lock or stall (a likely deadlock) fails the test.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use file_finder_lib::testing;
+use file_finder_lib::AppState;
+
+const SEARCH_THREADS: usize = 8;
+const SEARCHES_PER_THREAD: usize = 50;
+const INDEX_THREADS: usize = 2;
+const INDEX_RUNS_PER_THREAD: usize = 5;
+const OPEN_THREADS: usize = 4;
+const OPENS_PER_THREAD: usize = 50;
+const SETTINGS_THREADS: usize = 2;
+const RELOADS_PER_THREAD: usize = 25;
+
+// No call finishing for this long is reported as a deadlock
+const STALL_TIMEOUT: Duration = Duration::from_secs(60);
+
+const FOLDERS: usize = 40;
+const FILES_PER_FOLDER: usize = 50;
+const FILE_STEMS: &[&str] = &["report", "draft", "budget_2024", "notes", "main"];
+const FILE_EXTENSIONS: &[&str] = &["txt", "md", "xlsx", "txt", "rs"];
+
+// A mix of the planner's paths: fuzzy, glob, regex, filters and misses
+const QUERIES: &[&str] = &[
+    "report",
+    "rpt",
+    "*.txt",
+    "notes_1*",
+    "^draft_.*\\.md$",
+    "budget 2024",
+    "main.rs",
+    "project_3",
+    "tag:finance",
+    "category:docs report",
+    "zzz-no-such-file",
+    "no",
+];
+
+struct Run {
+    state: AppState,
+    tree: PathBuf,
+    files: Vec<String>,
+    completed: AtomicUsize,
+    failures: Mutex<Vec<String>>,
+}
+
+impl Run {
+    fn record(&self, what: &str, result: Result<(), String>) {
+        if let Err(e) = result {
+            if let Ok(mut failures) = self.failures.lock() {
+                failures.push(format!("{}: {}", what, e));
+            }
+        }
+        self.completed.fetch_add(1, Ordering::SeqCst);
+    }
+}
+
+// Folders of plain empty files: enough rows that searches and indexing take measurable time
+fn create_tree(tree: &Path) -> Result<Vec<String>, String> {
+    let mut files = Vec::new();
+    for folder in 0..FOLDERS {
+        let dir = tree.join(format!("project_{}", folder));
+        fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+        for i in 0..FILES_PER_FOLDER {
+            let kind = i % FILE_STEMS.len();
+            let file = dir.join(format!("{}_{}.{}", FILE_STEMS[kind], i, FILE_EXTENSIONS[kind]));
+            fs::write(&file, b"").map_err(|e| e.to_string())?;
+            files.push(file.to_string_lossy().to_string());
+        }
+    }
+    Ok(files)
+}
+
+fn search_worker(run: &Run, worker: usize) {
+    for i in 0..SEARCHES_PER_THREAD {
+        let query = QUERIES[(worker + i) % QUERIES.len()];
+        // Alternate case sensitivity so both cache keys see traffic
+        run.record(&format!("search {:?}", query), testing::search(&run.state, query, i % 7 == 0));
+    }
+}
+
+fn index_worker(run: &Run) {
+    for i in 0..INDEX_RUNS_PER_THREAD {
+        // Clearing runs leave the index briefly empty, which searches must tolerate
+        testing::index(&run.state, &run.tree, i % 2 == 0);
+        run.record("index", Ok(()));
+    }
+}
+
+fn open_worker(run: &Run, worker: usize) {
+    for i in 0..OPENS_PER_THREAD {
+        let path = &run.files[(worker * OPENS_PER_THREAD + i * 13) % run.files.len()];
+        run.record(&format!("open {}", path), testing::record_open(&run.state, path));
+    }
+}
+
+fn settings_worker(run: &Run) {
+    for _ in 0..RELOADS_PER_THREAD {
+        run.record("reload settings", testing::reload_settings(&run.state));
+    }
+}
+
+fn stress(dir: &Path) -> Result<String, String> {
+    let tree = dir.join("tree");
+    let files = create_tree(&tree)?;
+    let state = testing::open(dir.join("index.db"))?;
+    testing::index(&state, &tree, true);
+
+    let run = Arc::new(Run {
+        state,
+        tree,
+        files,
+        completed: AtomicUsize::new(0),
+        failures: Mutex::new(Vec::new()),
+    });
+    let total = SEARCH_THREADS * SEARCHES_PER_THREAD
+        + INDEX_THREADS * INDEX_RUNS_PER_THREAD
+        + OPEN_THREADS * OPENS_PER_THREAD
+        + SETTINGS_THREADS * RELOADS_PER_THREAD;
+
+    let started = Instant::now();
+    let mut workers = Vec::new();
+    for worker in 0..SEARCH_THREADS {
+        let run = run.clone();
+        workers.push(thread::spawn(move || search_worker(&run, worker)));
+    }
+    for _ in 0..INDEX_THREADS {
+        let run = run.clone();
+        workers.push(thread::spawn(move || index_worker(&run)));
+    }
+    for worker in 0..OPEN_THREADS {
+        let run = run.clone();
+        workers.push(thread::spawn(move || open_worker(&run, worker)));
+    }
+    for _ in 0..SETTINGS_THREADS {
+        let run = run.clone();
+        workers.push(thread::spawn(move || settings_worker(&run)));
+    }
+
+    // Deadlocked threads never finish, so watch progress instead of joining blindly
+    let mut last_completed = 0;
+    let mut last_progress = Instant::now();
+    while !workers.iter().all(|worker| worker.is_finished()) {
+        thread::sleep(Duration::from_millis(100));
+        let completed = run.completed.load(Ordering::SeqCst);
+        if completed != last_completed {
+            last_completed = completed;
+            last_progress = Instant::now();
+        } else if last_progress.elapsed() > STALL_TIMEOUT {
+            return Err(format!(
+                "No call finished in {}s after {} of {}; likely a deadlock",
+                STALL_TIMEOUT.as_secs(),
+                completed,
+                total
+            ));
+        }
+    }
+    for worker in workers {
+        // A panic poisons the lock it held; later calls report that as a failure too
+        if worker.join().is_err() {
+            return Err("A worker thread panicked".to_string());
+        }
+    }
+
+    let completed = run.completed.load(Ordering::SeqCst);
+    let failures = run.failures.lock().map_err(|e| e.to_string())?;
+    if let Some(first) = failures.first() {
+        return Err(format!("{} of {} calls failed, first: {}", failures.len(), completed, first));
+    }
+    if completed != total {
+        return Err(format!("Only {} of {} calls finished", completed, total));
+    }
+    Ok(format!("{} concurrent calls finished in {} ms", completed, started.elapsed().as_millis()))
+}
+
+#[test]
+fn concurrent_calls_finish() {
+    let dir = std::env::temp_dir().join(format!("file-finder-stress-{}", std::process::id()));
+    let result = stress(&dir);
+    if let Err(e) = fs::remove_dir_all(&dir) {
+        eprintln!("Could not remove {}: {}", dir.display(), e);
+    }
+    match result {
+        Ok(summary) => eprintln!("{}", summary),
+        Err(e) => panic!("{}", e),
+    }
+}
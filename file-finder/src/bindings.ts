@@ -0,0 +1,1051 @@
+
+// This file was generated by [tauri-specta](https://github.com/oscartbeaumont/tauri-specta). Do not edit this file manually.
+
+/** user-defined commands **/
+
+
+export const commands = {
+async startIndexing() : Promise<Result<string, AppError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("start_indexing") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async indexCustomFolder(path: string, mirror: boolean | null, maxDepth: number | null, includeHidden: boolean | null) : Promise<Result<string, AppError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("index_custom_folder", { path, mirror, maxDepth, includeHidden }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async searchFiles(query: string, options: SearchOptions | null) : Promise<Result<SearchResponse, AppError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("search_files", { query, options }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async searchFilesV1(query: string, options: SearchOptions | null) : Promise<Result<FileEntry[], string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("search_files_v1", { query, options }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async getRecentFiles() : Promise<Result<FileEntry[], AppError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_recent_files") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async getRecentFilesForRoot(root: string) : Promise<Result<FileEntry[], AppError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_recent_files_for_root", { root }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async getRecentlyModified(root: string | null, limit: number | null, since: number | null) : Promise<Result<FileEntry[], AppError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_recently_modified", { root, limit, since }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async clearRecentFiles(path: string | null) : Promise<Result<number, AppError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("clear_recent_files", { path }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async getRecentsConfig() : Promise<Result<RecentsConfig, AppError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_recents_config") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async setRecentsConfig(config: RecentsConfig) : Promise<Result<RecentsConfig, AppError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("set_recents_config", { config }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async openFile(path: string) : Promise<Result<null, AppError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("open_file", { path }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async openFileWith(path: string, program: string) : Promise<Result<null, AppError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("open_file_with", { path, program }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async getFileInfo(path: string) : Promise<Result<FileInfo, AppError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_file_info", { path }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async getOpenWithPrefs() : Promise<Result<OpenWithPref[], AppError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_open_with_prefs") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async clearOpenWithPrefs(extension: string | null) : Promise<Result<number, AppError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("clear_open_with_prefs", { extension }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async startDrag(paths: string[]) : Promise<Result<null, AppError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("start_drag", { paths }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async hideOverlay() : Promise<Result<null, AppError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("hide_overlay") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async showInMainWindow(query: string) : Promise<Result<null, AppError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("show_in_main_window", { query }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async getOverlayConfig() : Promise<Result<OverlayConfig, AppError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_overlay_config") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async setOverlayConfig(config: OverlayConfig) : Promise<Result<OverlayConfig, AppError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("set_overlay_config", { config }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async getFileIcon(path: string) : Promise<Result<number[], AppError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_file_icon", { path }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async getThumbnail(path: string, size: number | null) : Promise<Result<Thumbnail, AppError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_thumbnail", { path, size }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async prefetchThumbnails(paths: string[], size: number | null) : Promise<Result<null, AppError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("prefetch_thumbnails", { paths, size }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async getIndexStatus() : Promise<Result<IndexStatus, AppError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_index_status") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async debugSearchScores(query: string) : Promise<Result<SearchScore[], AppError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("debug_search_scores", { query }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async debugSearchScoresV1(query: string) : Promise<Result<([string, number, string])[], string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("debug_search_scores_v1", { query }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async getApiVersion(clientVersion: number | null) : Promise<Result<ApiVersionInfo, AppError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_api_version", { clientVersion }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async toggleFavorite(path: string) : Promise<Result<boolean, AppError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("toggle_favorite", { path }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async getFavorites() : Promise<Result<string[], AppError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_favorites") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async getFavoriteGroups() : Promise<Result<FavoriteGroups, AppError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_favorite_groups") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async createFavoriteGroup(name: string) : Promise<Result<FavoriteGroup, AppError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("create_favorite_group", { name }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async renameFavoriteGroup(id: number, name: string) : Promise<Result<null, AppError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("rename_favorite_group", { id, name }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async deleteFavoriteGroup(id: number) : Promise<Result<boolean, AppError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("delete_favorite_group", { id }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async moveFavorite(path: string, groupId: number | null, position: number | null) : Promise<Result<null, AppError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("move_favorite", { path, groupId, position }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async reorderFavoriteGroups(ids: number[]) : Promise<Result<null, AppError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("reorder_favorite_groups", { ids }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async getIndexedDirectories() : Promise<Result<IndexedDirectory[], AppError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_indexed_directories") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async setActiveDirectory(path: string) : Promise<Result<null, AppError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("set_active_directory", { path }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async getSettings() : Promise<Result<Settings, AppError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_settings") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async updateSettings(update: SettingsUpdate) : Promise<Result<Settings, AppError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("update_settings", { update }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async getRankingConfig() : Promise<Result<RankingConfig, AppError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_ranking_config") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async setRankingConfig(config: RankingConfig) : Promise<Result<RankingConfig, AppError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("set_ranking_config", { config }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async getSearchBudget() : Promise<Result<SearchBudget, AppError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_search_budget") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async setSearchBudget(budget: SearchBudget) : Promise<Result<SearchBudget, AppError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("set_search_budget", { budget }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async getPerformanceStats() : Promise<Result<PerformanceStats, AppError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_performance_stats") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async setLogLevel(level: LogLevel) : Promise<Result<LoggingConfig, AppError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("set_log_level", { level }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async getRecentLogs(n: number | null) : Promise<Result<string[], AppError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_recent_logs", { n }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async getLlmConfig() : Promise<Result<LlmConfig, AppError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_llm_config") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async setLlmConfig(config: LlmConfig) : Promise<Result<LlmConfig, AppError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("set_llm_config", { config }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async testLlmConnection(config: LlmConfig | null) : Promise<Result<LlmConnectionStatus, AppError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("test_llm_connection", { config }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async getLlmStatus() : Promise<Result<LlmStatus, AppError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_llm_status") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async getRecentProjects(limit: number | null) : Promise<Result<RecentProject[], AppError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_recent_projects", { limit }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async getProjectContext(root: string) : Promise<Result<ProjectContext | null, AppError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_project_context", { root }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async getExclusionRules() : Promise<Result<ExclusionRules, AppError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_exclusion_rules") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async setExclusionRules(rules: ExclusionRules) : Promise<Result<ExclusionRules, AppError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("set_exclusion_rules", { rules }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async resetExclusionRules() : Promise<Result<ExclusionRules, AppError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("reset_exclusion_rules") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async getArchiveIndexing() : Promise<Result<ArchiveIndexing, AppError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_archive_indexing") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async setArchiveIndexing(settings: ArchiveIndexing) : Promise<Result<ArchiveIndexing, AppError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("set_archive_indexing", { settings }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async extractArchiveEntry(path: string, destDir: string) : Promise<Result<string, AppError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("extract_archive_entry", { path, destDir }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async refineSearch(within: RefineBase, query: string, options: SearchOptions | null) : Promise<Result<FileEntry[], AppError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("refine_search", { within, query, options }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async findCopies(path: string) : Promise<Result<FileEntry[], AppError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("find_copies", { path }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async startRankingExperiment(name: string, candidate: RankingConfig) : Promise<Result<RankingExperiment, AppError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("start_ranking_experiment", { name, candidate }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async stopRankingExperiment() : Promise<Result<ExperimentReport, AppError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("stop_ranking_experiment") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async getRankingExperimentReport(name: string | null) : Promise<Result<ExperimentReport, AppError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_ranking_experiment_report", { name }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async setMirrorRoot(path: string, mirror: boolean) : Promise<Result<null, AppError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("set_mirror_root", { path, mirror }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async getExtensionGroups() : Promise<Result<Partial<{ [key in string]: string[] }>, AppError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_extension_groups") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async setExtensionGroups(groups: ExtensionGroups) : Promise<Result<Partial<{ [key in string]: string[] }>, AppError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("set_extension_groups", { groups }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async createSavedSearch(name: string, query: string, options: SearchOptions | null, scope: string | null) : Promise<Result<SavedSearch, AppError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("create_saved_search", { name, query, options, scope }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async listSavedSearches() : Promise<Result<SavedSearch[], AppError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("list_saved_searches") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async runSavedSearch(id: number) : Promise<Result<FileEntry[], AppError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("run_saved_search", { id }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async deleteSavedSearch(id: number) : Promise<Result<boolean, AppError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("delete_saved_search", { id }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async createSmartCollection(name: string, rules: CollectionRules) : Promise<Result<SmartCollection, AppError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("create_smart_collection", { name, rules }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async listSmartCollections() : Promise<Result<SmartCollection[], AppError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("list_smart_collections") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async evaluateSmartCollection(id: number) : Promise<Result<FileEntry[], AppError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("evaluate_smart_collection", { id }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async deleteSmartCollection(id: number) : Promise<Result<boolean, AppError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("delete_smart_collection", { id }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async importStructureAsTags(root: string) : Promise<Result<TagImportSummary, AppError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("import_structure_as_tags", { root }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async listTags() : Promise<Result<TagFacet[], AppError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("list_tags") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async addTag(path: string, tag: string) : Promise<Result<boolean, AppError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("add_tag", { path, tag }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async removeTag(path: string, tag: string) : Promise<Result<boolean, AppError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("remove_tag", { path, tag }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async getFileTags(path: string) : Promise<Result<string[], AppError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_file_tags", { path }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async getFilesByTag(tag: string) : Promise<Result<FileEntry[], AppError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_files_by_tag", { tag }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async getNote(path: string) : Promise<Result<FileNote | null, AppError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_note", { path }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async setNote(path: string, text: string) : Promise<Result<FileNote | null, AppError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("set_note", { path, text }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async getNotesConfig() : Promise<Result<NotesConfig, AppError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_notes_config") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async setNotesConfig(config: NotesConfig) : Promise<Result<NotesConfig, AppError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("set_notes_config", { config }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async pinResult(query: string, path: string) : Promise<Result<PinnedResult, AppError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("pin_result", { query, path }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async unpinResult(query: string, path: string) : Promise<Result<number, AppError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("unpin_result", { query, path }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async listPinnedResults() : Promise<Result<PinnedResult[], AppError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("list_pinned_results") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async getFileProperties(path: string) : Promise<Result<FileProperties, AppError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_file_properties", { path }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async previewText(path: string, maxBytes: number | null) : Promise<Result<TextPreview, AppError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("preview_text", { path, maxBytes }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async previewDocument(path: string, maxChars: number | null) : Promise<Result<DocumentPreview, AppError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("preview_document", { path, maxChars }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async createEntry(parentDir: string, name: string, kind: EntryKind) : Promise<Result<FileEntry, AppError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("create_entry", { parentDir, name, kind }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async renameFile(path: string, newName: string) : Promise<Result<FileEntry, AppError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("rename_file", { path, newName }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async moveFiles(paths: string[], destDir: string, onCollision: CollisionPolicy | null) : Promise<Result<TransferOutcome[], AppError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("move_files", { paths, destDir, onCollision }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async copyFiles(paths: string[], destDir: string, onCollision: CollisionPolicy | null) : Promise<Result<TransferOutcome[], AppError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("copy_files", { paths, destDir, onCollision }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async deleteToTrash(paths: string[]) : Promise<Result<TrashOutcome[], AppError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("delete_to_trash", { paths }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async listTrash(query: string | null) : Promise<Result<TrashEntry[], AppError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("list_trash", { query }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async restoreFromTrash(id: string) : Promise<Result<string, AppError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("restore_from_trash", { id }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async getSnapshotConfig() : Promise<Result<SnapshotConfig, AppError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_snapshot_config") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async setSnapshotConfig(config: SnapshotConfig) : Promise<Result<SnapshotConfig, AppError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("set_snapshot_config", { config }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async listSnapshots() : Promise<Result<SnapshotInfo[], AppError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("list_snapshots") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async createSnapshot() : Promise<Result<SnapshotInfo, AppError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("create_snapshot") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async restoreSnapshot(path: string | null) : Promise<Result<SnapshotInfo, AppError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("restore_snapshot", { path }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async buildSemanticIndex(root: string | null, limit: number | null, includeContent: boolean | null) : Promise<Result<SemanticIndexSummary, AppError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("build_semantic_index", { root, limit, includeContent }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async semanticSearch(query: string, limit: number | null) : Promise<Result<SemanticMatch[], AppError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("semantic_search", { query, limit }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async summarizeFile(path: string, stream: boolean | null) : Promise<Result<FileSummary, AppError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("summarize_file", { path, stream }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async askMyFiles(question: string) : Promise<Result<FileAnswer, AppError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("ask_my_files", { question }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async explainSearch(query: string, path: string, options: SearchOptions | null) : Promise<Result<SearchExplanation, AppError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("explain_search", { query, path, options }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async testLocalLlm(config: LlmConfig | null) : Promise<Result<LlmConnectionStatus, AppError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("test_local_llm", { config }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async categorizePath(path: string) : Promise<Result<FileCategory, AppError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("categorize_path", { path }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async getCategory(path: string) : Promise<Result<FileCategory | null, AppError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_category", { path }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async rebuildFts() : Promise<Result<number, AppError>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("rebuild_fts") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+}
+}
+
+/** user-defined events **/
+
+
+
+/** user-defined constants **/
+
+
+
+/** user-defined types **/
+
+export type AgeBucket = "today" | "this_week" | "this_month" | "older"
+export type ApiVersionInfo = { version: number; min_supported: number; compatible: boolean; deprecated: DeprecatedCommand[] }
+export type AppError = { code: ErrorCode; message: string; recoverable: boolean }
+export type ArchiveIndexing = { enabled: boolean; max_entries: number; max_tar_mb: number }
+export type CaseRules = "default" | "turkish"
+export type Citation = { number: number; path: string; start_line: number; end_line: number; snippet: string; cited: boolean }
+export type Collation = "alphabetical" | "code_point"
+export type CollectionRules = { types: string[]; folders: string[]; tags: string[]; names: string[]; modified: string | null }
+export type CollisionPolicy = "skip" | "overwrite" | "rename"
+export type ContentSnippet = { line: number; text: string }
+export type DeprecatedCommand = { command: string; replacement: string; since: number }
+export type DocumentPreview = { path: string; kind: string; section: string | null; text: string; truncated: boolean }
+export type EntryKind = "file" | "folder"
+export type ErrorCode = "index_empty" | "llm_disabled" | "llm_offline" | "permission_denied" | "db_locked" | "not_found" | "invalid_input" | "internal"
+export type ExclusionRules = { dir_names: string[]; paths: string[] }
+export type ExperimentReport = { experiment: RankingExperiment | null; name: string | null; clicks: number; tied_top_results: number; variants: VariantStats[] }
+export type ExtensionGroups = { groups: Partial<{ [key in string]: string[] }> }
+export type FacetKind = "tag" | "category"
+export type Favorite = { path: string; name: string; favorited_at: number }
+export type FavoriteGroup = { id: number; name: string; favorites: Favorite[] }
+export type FavoriteGroups = { ungrouped: Favorite[]; groups: FavoriteGroup[] }
+export type FileAnswer = { question: string; answer: string; sources: Citation[] }
+export type FileCategory = { primary: string; secondary: string | null; auto_tags: string[]; confidence: number }
+export type FileEntry = { path: string; name: string; last_accessed: number | null; access_count: number; modified_at: number | null; age_bucket?: AgeBucket | null; in_backup?: boolean; match_source?: MatchSource | null; content_snippet?: ContentSnippet | null }
+export type FileInfo = { extension: string; category: string | null; suggested_programs: string[]; apps: OpenWithApp[]; preferred_program: string | null }
+export type FileNote = { path: string; text: string; updated_at: number }
+export type FileProperties = { path: string; name: string; is_dir: boolean; is_symlink: boolean; size: number; created_at: number | null; modified_at: number | null; accessed_at: number | null; readonly: boolean; hidden: boolean; permissions: string | null; mode: string | null; owner: string | null; group: string | null; link_target: string | null; child_count: number | null }
+export type FileSummary = { path: string; summary: string; entities: string[]; truncated: boolean; cached: boolean }
+export type GeneralSettings = { max_results: number; cache_ttl_secs: number; cache_max_entries: number; reindex: ReindexSchedule }
+export type HistogramBucket = { le_ms: number | null; count: number }
+export type IndexStatus = { total_files: number; last_indexed: number | null }
+export type IndexedDirectory = { path: string; name: string; is_active: boolean; indexed_at: number; is_mirror: boolean; max_depth: number | null; include_hidden: boolean }
+export type LlmConfig = { enabled: boolean; provider: LlmProvider; endpoint: string; model: string; embedding_model: string; api_key: string | null; connect_timeout_ms: number; search_timeout_ms: number; request_timeout_ms: number; nl_confidence_threshold: number; local: LocalLlmConfig }
+export type LlmConnectionStatus = { reachable: boolean; model_available: boolean; models: string[]; reply_ms: number | null; error: string | null }
+export type LlmProvider = "ollama" | "openai_compatible"
+export type LlmStatus = { enabled: boolean; reachable: boolean; model: string | null; model_available: boolean; using_local: boolean; avg_parse_ms: number | null; parse_samples: number; fallback_parses: number; last_error: string | null; last_error_at: number | null; smart_search: boolean }
+export type LocalLlmConfig = { enabled: boolean; server_path: string | null; model_path: string | null; port: number; context_size: number; threads: number | null; startup_timeout_ms: number }
+export type LocaleConfig = { case_rules: CaseRules; collation: Collation }
+export type LogLevel = "error" | "warn" | "info" | "debug" | "trace"
+export type LoggingConfig = { level: LogLevel }
+export type MatchSource = "name" | "content" | "both" | "note"
+export type NotesConfig = { search_notes: boolean }
+export type OpenWithApp = { name: string; program: string; is_default: boolean }
+export type OpenWithPref = { extension: string; program: string; updated_at: number }
+export type OverlayConfig = { enabled: boolean; shortcut: string; max_results: number }
+export type PerformanceStats = { since: number; searches: number; cache_hits: number; cache_hit_rate: number; degraded: number; candidates_scanned: number; max_candidates: number; total: TimingStats; sql: TimingStats; scoring: TimingStats; recent: SearchSample[] }
+export type PinnedResult = { query_prefix: string; path: string; pinned_at: number }
+export type ProjectContext = { root: string; name: string; language: string | null; markers: string[]; last_active: number | null; detected_at: number }
+export type QueryPlan = { keywords: string[]; file_types: string[]; tags: string[]; categories: string[]; time_range: TimeRange | null; content: string[]; backups: boolean; source: RewriteSource | null }
+export type RankedAbove = { path: string; score: number | null }
+export type RankingConfig = { exact_name: number; exact_name_without_extension: number; prefix_match: number; regex_match: number; name_starts_with: number; name_contains: number; all_words_in_name: number; romanized_match: number; path_contains: number; all_words_in_path: number; weak_match: number; fuzzy_fallback_divisor: number; fuzzy_exact_name: number; extension_full_name: number; extension_substring: number; extension_prefix_bonus: number; extension_boundary_bonus: number; normalized_match: number; normalized_prefix_bonus: number; token_match: number; contiguous_bonus: number; path_component_match: number; path_contiguous_bonus: number; fuzzy_name_min_score: number; fuzzy_name_bonus: number; fuzzy_path_min_score: number; fuzzy_path_divisor: number; library_dir_divisor: number; hidden_dir_divisor: number; frecency_bonus: number; frecency_half_life_days: number; favorite_frecency: number; active_project_multiplier: number; active_project_bonus: number }
+export type RankingExperiment = { name: string; candidate: RankingConfig; started_at: number }
+export type RecentProject = { root: string; name: string; last_activity: number; access_count: number; recent_file_count: number; main_language: string | null }
+export type RecentsConfig = { max_entries: number; max_age_days: number; remove_missing: boolean }
+export type RefineBase = { search: { query: string; options: SearchOptions | null } } | { paths: string[] }
+export type ReindexSchedule = { enabled: boolean; start_hour: number; end_hour: number }
+export type RewriteSource = "llm" | "fallback"
+export type SavedSearch = { id: number; name: string; query: string; options: SearchOptions; scope: string | null; created_at: number; last_run_at: number | null }
+export type ScoreStep = { rule: string; score: number }
+export type SearchBudget = { first_batch_ms: number; total_ms: number; max_candidates: number; debounce_ms: number }
+export type SearchExplanation = { query: string; search_text: string; filters: string[]; strategy: string; rewritten_query: string | null; rank: number | null; result_count: number; score: number | null; steps: ScoreStep[]; ranked_above: RankedAbove[]; notes: string[] }
+export type SearchOptions = { search_folders: boolean; enable_fuzzy: boolean; strict_mode: boolean; filename_only: boolean; case_sensitive: boolean; transliterate: boolean; natural_language: boolean | null; force_refresh: boolean; debug: boolean; scope: string | null }
+export type SearchResponse = { results: FileEntry[]; degraded: boolean; rewritten_query: string | null; plan: QueryPlan | null; superseded: boolean; timings: SearchTimings | null }
+export type SearchSample = { query: string; at: number; total_ms: number; sql_ms: number; scoring_ms: number; candidates: number; results: number; cache_hit: boolean; degraded: boolean }
+export type SearchScore = { name: string; score: number; path: string }
+export type SearchTimings = { total_ms: number; cache_ms: number; cache_hit: boolean; sql_ms: number; index_lookup_ms: number; candidate_source: string | null; scoring_ms: number; fuzzy_ms: number; sort_ms: number; rows_scanned: number; candidates: number; results: number; degraded: boolean }
+export type SemanticIndexSummary = { model: string; embedded: number; failed: number; remaining: number }
+export type SemanticMatch = { file: FileEntry; similarity: number }
+export type Settings = { general: GeneralSettings; ranking: RankingConfig; search_budget: SearchBudget; llm: LlmConfig; exclusions: ExclusionRules; locale: LocaleConfig }
+export type SettingsUpdate = { general: GeneralSettings | null; ranking: RankingConfig | null; search_budget: SearchBudget | null; llm: LlmConfig | null; exclusions: ExclusionRules | null; locale: LocaleConfig | null }
+export type SmartCollection = { id: number; name: string; rules: CollectionRules; created_at: number }
+export type SnapshotConfig = { enabled: boolean; folder: string | null; interval_days: number; keep: number }
+export type SnapshotInfo = { path: string; created_at: number; size: number }
+export type TagFacet = { name: string; kind: FacetKind; files: number }
+export type TagImportSummary = { files_tagged: number; tags_created: number; tags_applied: number }
+export type TextPreview = { path: string; encoding: string; text: string; line_offsets: number[]; truncated: boolean; file_size: number }
+export type Thumbnail = { path: string; file: string; data_url: string }
+export type TimeRange = { value: string; after: number | null; before: number | null }
+export type TimingStats = { mean_ms: number; p50_ms: number; p95_ms: number; max_ms: number; buckets: HistogramBucket[] }
+export type TransferOutcome = { path: string; success: boolean; skipped: boolean; destination: string | null; error: string | null }
+export type TrashEntry = { id: string; name: string; original_path: string | null; deleted_at: number | null; size: number | null; is_dir: boolean; indexed: boolean }
+export type TrashOutcome = { path: string; success: boolean; error: string | null }
+export type Variant = "a" | "b"
+export type VariantStats = { variant: Variant; shown_clicks: number; top_result_clicks: number; mean_reciprocal_rank: number }
+
+/** tauri-specta globals **/
+
+import {
+	invoke as TAURI_INVOKE,
+	Channel as TAURI_CHANNEL,
+} from "@tauri-apps/api/core";
+import * as TAURI_API_EVENT from "@tauri-apps/api/event";
+import { type WebviewWindow as __WebviewWindow__ } from "@tauri-apps/api/webviewWindow";
+
+type __EventObj__<T> = {
+	listen: (
+		cb: TAURI_API_EVENT.EventCallback<T>,
+	) => ReturnType<typeof TAURI_API_EVENT.listen<T>>;
+	once: (
+		cb: TAURI_API_EVENT.EventCallback<T>,
+	) => ReturnType<typeof TAURI_API_EVENT.once<T>>;
+	emit: null extends T
+		? (payload?: T) => ReturnType<typeof TAURI_API_EVENT.emit>
+		: (payload: T) => ReturnType<typeof TAURI_API_EVENT.emit>;
+};
+
+export type Result<T, E> =
+	| { status: "ok"; data: T }
+	| { status: "error"; error: E };
+
+function __makeEvents__<T extends Record<string, any>>(
+	mappings: Record<keyof T, string>,
+) {
+	return new Proxy(
+		{} as unknown as {
+			[K in keyof T]: __EventObj__<T[K]> & {
+				(handle: __WebviewWindow__): __EventObj__<T[K]>;
+			};
+		},
+		{
+			get: (_, event) => {
+				const name = mappings[event as keyof T];
+
+				return new Proxy((() => {}) as any, {
+					apply: (_, __, [window]: [__WebviewWindow__]) => ({
+						listen: (arg: any) => window.listen(name, arg),
+						once: (arg: any) => window.once(name, arg),
+						emit: (arg: any) => window.emit(name, arg),
+					}),
+					get: (_, command: keyof __EventObj__<any>) => {
+						switch (command) {
+							case "listen":
+								return (arg: any) => TAURI_API_EVENT.listen(name, arg);
+							case "once":
+								return (arg: any) => TAURI_API_EVENT.once(name, arg);
+							case "emit":
+								return (arg: any) => TAURI_API_EVENT.emit(name, arg);
+						}
+					},
+				});
+			},
+		},
+	);
+}
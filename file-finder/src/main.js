@@ -1,5 +1,8 @@
 const { invoke } = window.__TAURI__.core;
 
+// Backend command API this frontend is written against (see get_api_version)
+const API_VERSION = 3;
+
 let searchInput;
 let resultsList;
 let recentList;
@@ -12,23 +15,36 @@ let activeTab = 'search';
 let lastKeyTime = 0;
 let lastKey = null;
 let lastReindexDate = null; // Track last reindex date
-let autoReindexEnabled = true; // Auto reindex enabled by default
+let reindexSchedule = { enabled: true, start_hour: 2, end_hour: 5 }; // Replaced by the saved settings on startup
 
 // Search options
 let searchOptions = {
   search_folders: true,
   enable_fuzzy: true,
   strict_mode: false,
-  filename_only: false
+  filename_only: false,
+  case_sensitive: false
 };
 
 // Sort options
 let currentSort = {
   search: 'relevance',  // relevance, date, usage
   recent: 'usage',      // usage, date
-  favorites: 'name'     // name, date, usage
+  favorites: 'groups'   // groups (manual order), name, date, usage
 };
 
+// Warn in the console when the backend no longer serves the commands this file calls
+async function checkApiVersion() {
+  try {
+    const info = await invoke("get_api_version", { clientVersion: API_VERSION });
+    if (!info.compatible) {
+      console.warn(`Frontend API version ${API_VERSION} is not supported by the backend (version ${info.version}, oldest ${info.min_supported})`);
+    }
+  } catch (error) {
+    console.warn("Backend has no get_api_version command:", error);
+  }
+}
+
 // Initialize app
 window.addEventListener("DOMContentLoaded", async () => {
   searchInput = document.querySelector("#search-input");
@@ -36,6 +52,8 @@ window.addEventListener("DOMContentLoaded", async () => {
   recentList = document.querySelector("#recent-list");
   indexStatusEl = document.querySelector("#index-status");
   
+  checkApiVersion();
+  
   // Setup event listeners
   searchInput.addEventListener("input", handleSearch);
   searchInput.addEventListener("keydown", handleKeyboard);
@@ -65,6 +83,12 @@ window.addEventListener("DOMContentLoaded", async () => {
         });
         
         if (selected) {
+          // New folders can be indexed as a backup mirror: kept out of normal results, searchable with in:backups
+          const known = await invoke("get_indexed_directories");
+          const mirror = known.some(dir => dir.path === selected)
+            ? null
+            : confirm("Is this a backup or archive copy?\n\nBackup folders are hidden from normal searches and can be searched with in:backups.");
+
           // Start indexing
           indexStatusEl.textContent = `Indexing: ${selected}`;
           indexFolderBtn.disabled = true;
@@ -75,11 +99,11 @@ window.addEventListener("DOMContentLoaded", async () => {
           const initialCount = initialStatus.total_files;
           
           // Start the indexing process (non-blocking)
-          invoke("index_custom_folder", { path: selected }).then(() => {
+          invoke("index_custom_folder", { path: selected, mirror }).then(() => {
             console.log("Indexing completed");
           }).catch((error) => {
             console.error("Indexing failed:", error);
-            indexStatusEl.textContent = `Indexing failed: ${error}`;
+            indexStatusEl.textContent = `Indexing failed: ${errorMessage(error)}`;
             indexFolderBtn.disabled = false;
             indexFolderBtn.textContent = "Re-index";
           });
@@ -146,7 +170,7 @@ window.addEventListener("DOMContentLoaded", async () => {
         }
       } catch (error) {
         console.error("Failed to index folder:", error);
-        alert("Failed to index folder: " + error);
+        alert("Failed to index folder: " + errorMessage(error));
         indexFolderBtn.disabled = false;
         indexFolderBtn.textContent = "Re-index";
       }
@@ -247,18 +271,30 @@ window.addEventListener("DOMContentLoaded", async () => {
     performSearch(searchInput.value.trim());
   });
   
-  // Auto reindex checkbox listener
-  document.getElementById("auto-reindex").addEventListener("change", (e) => {
-    autoReindexEnabled = e.target.checked;
-    localStorage.setItem('autoReindexEnabled', autoReindexEnabled);
-    console.log(`Auto reindex ${autoReindexEnabled ? 'enabled' : 'disabled'}`);
+  document.getElementById("search-case-sensitive").addEventListener("change", (e) => {
+    searchOptions.case_sensitive = e.target.checked;
+    performSearch(searchInput.value.trim());
   });
   
-  // Load saved auto-reindex preference
-  const savedAutoReindex = localStorage.getItem('autoReindexEnabled');
-  if (savedAutoReindex !== null) {
-    autoReindexEnabled = savedAutoReindex === 'true';
-    document.getElementById("auto-reindex").checked = autoReindexEnabled;
+  // Auto reindex checkbox listener; the schedule is kept with the other settings in the backend
+  document.getElementById("auto-reindex").addEventListener("change", async (e) => {
+    try {
+      const { general } = await invoke("get_settings");
+      general.reindex.enabled = e.target.checked;
+      const settings = await invoke("update_settings", { update: { general } });
+      showReindexSchedule(settings.general.reindex);
+      console.log(`Auto reindex ${reindexSchedule.enabled ? 'enabled' : 'disabled'}`);
+    } catch (error) {
+      showError("Failed to save the reindex setting: " + errorMessage(error));
+    }
+  });
+  
+  // Load the saved reindex schedule
+  try {
+    const settings = await invoke("get_settings");
+    showReindexSchedule(settings.general.reindex);
+  } catch (error) {
+    console.error("Failed to load settings:", error);
   }
   
   // Setup tab listeners
@@ -269,6 +305,18 @@ window.addEventListener("DOMContentLoaded", async () => {
     });
   });
 
+  document.querySelector("#clear-recent-btn").addEventListener("click", async () => {
+    if (!confirm("Forget all recent files? They also stop boosting search results.")) {
+      return;
+    }
+    try {
+      await invoke("clear_recent_files", { path: null });
+      await loadRecentFiles();
+    } catch (error) {
+      showError("Failed to clear recent files: " + errorMessage(error));
+    }
+  });
+
   // Setup sort tab listeners
   document.querySelectorAll(".sort-btn").forEach(btn => {
     btn.addEventListener("click", (e) => {
@@ -303,7 +351,35 @@ window.addEventListener("DOMContentLoaded", async () => {
 
   // Auto-refresh status (less frequent to avoid development interruptions)
   setInterval(updateStatus, 15000);
+
+  // Large files being moved or copied report their progress in the status line
+  window.__TAURI__.event.listen("transfer-progress", (event) => {
+    const { path, copied, total } = event.payload;
+    const name = path.split(/[/\\]/).pop();
+    indexStatusEl.textContent = copied < total ? `Copying ${name}: ${Math.floor(copied * 100 / total)}%` : `Copied ${name}`;
+  });
+
+  // Results shown before an indexing run finished may be missing the new files
+  window.__TAURI__.event.listen("index-finished", async () => {
+    await updateStatus();
+    const query = searchInput.value.trim();
+    if (query && activeTab === 'search') {
+      performSearch(query);
+    }
+  });
   
+  // "Search…" in the tray menu brings the window back ready to type; the overlay hands over
+  // its query with Ctrl+Enter
+  window.__TAURI__.event.listen("focus-search", (event) => {
+    switchTab('search');
+    if (event.payload) {
+      searchInput.value = event.payload;
+      performSearch(event.payload);
+    }
+    searchInput.focus();
+    searchInput.select();
+  });
+
   // Check for scheduled reindexing every hour
   checkScheduledReindex();
   setInterval(checkScheduledReindex, 60 * 60 * 1000); // Check every hour
@@ -379,8 +455,8 @@ function handleSearch() {
 // Track the current search to prevent race conditions
 let currentSearchId = 0;
 
-// Perform search
-async function performSearch(query) {
+// Perform search; forceRefresh skips the backend's search cache
+async function performSearch(query, { forceRefresh = false } = {}) {
   const searchId = ++currentSearchId;
   
   try {
@@ -398,7 +474,8 @@ async function performSearch(query) {
         }
       }, 200); // 200ms delay before showing "Searching..."
       
-      const results = await invoke("search_files", { query, options: searchOptions });
+      const response = await invoke("search_files", { query, options: { ...searchOptions, force_refresh: forceRefresh } });
+      const results = response.results;
       
       // Clear the searching timeout since we got results
       clearTimeout(searchingTimeout);
@@ -417,6 +494,26 @@ async function performSearch(query) {
         currentResults = results;
         selectedIndex = 0;
         renderSearchResults(results);
+        if (response.degraded) {
+          // The backend gave up on the full search to stay responsive
+          resultsList.insertAdjacentHTML('afterbegin',
+            '<div class="degraded-notice">Search took too long - showing partial results. Try a more specific query.</div>');
+        }
+        if (response.rewritten_query) {
+          // Natural-language queries run as the rewritten search; show what was searched for
+          const notice = renderQueryPlan(response.rewritten_query, response.plan);
+          resultsList.prepend(notice);
+          invoke("get_llm_status").then((status) => {
+            // The words were picked out without the model; say so rather than degrade silently
+            if (status.enabled && !status.smart_search && notice.isConnected) {
+              const warning = document.createElement('span');
+              warning.className = 'plan-warning';
+              warning.textContent = 'smart search unavailable, using basic parsing';
+              warning.title = status.last_error || '';
+              notice.appendChild(warning);
+            }
+          }).catch((error) => console.error("LLM status error:", error));
+        }
       }
     } else {
       // Only update if this is still the current search
@@ -427,14 +524,92 @@ async function performSearch(query) {
     }
   } catch (error) {
     // Only handle error if this is still the current search
-    if (searchId === currentSearchId) {
+    if (searchId === currentSearchId && error?.code === "index_empty") {
+      // Nothing to search yet; point at indexing rather than alerting
+      currentResults = [];
+      resultsList.innerHTML = `<div class="empty-state"><p>${errorMessage(error)}</p></div>`;
+    } else if (searchId === currentSearchId) {
       console.error("Search error:", error);
-      showError("Search failed: " + error);
+      showError("Search failed: " + errorMessage(error));
       renderSearchResults([]);
     }
   }
 }
 
+// NAME/CONTENT badges for results of `content:` searches, NOTE for files found by their note
+function matchBadges(file) {
+  const name = '<span class="match-badge" title="The name matched">NAME</span>';
+  const content = '<span class="match-badge content" title="The file\'s text matched">CONTENT</span>';
+  switch (file.match_source) {
+    case 'name': return name;
+    case 'content': return content;
+    case 'both': return name + content;
+    case 'note': return '<span class="match-badge note" title="Your note on this file matched">NOTE</span>';
+    default: return '';
+  }
+}
+
+// Query tokens of a natural-language plan, one per chip
+function planParts(plan) {
+  const parts = [];
+  if (plan.backups) parts.push({ label: 'backups', token: 'in:backups' });
+  plan.file_types.forEach(value => parts.push({ label: `type: ${value}`, token: `type:${value}` }));
+  plan.tags.forEach(value => parts.push({ label: `tag: ${value}`, token: `tag:${value}` }));
+  plan.categories.forEach(value => parts.push({ label: `category: ${value}`, token: `category:${value}` }));
+  if (plan.time_range) {
+    parts.push({ label: `modified: ${plan.time_range.value}`, token: `modified:${plan.time_range.value}` });
+  }
+  plan.content.forEach(value => parts.push({ label: `containing: ${value}`, token: `content:${value}` }));
+  plan.keywords.forEach(value => parts.push({ label: value, token: value }));
+  return parts;
+}
+
+// Show how a natural-language query was understood, as chips the user can remove or edit.
+// The edited query is searched as typed, since filter syntax is never rewritten again.
+function renderQueryPlan(rewrittenQuery, plan) {
+  const notice = document.createElement('div');
+  notice.className = 'rewrite-notice';
+  notice.appendChild(document.createTextNode('Searched for:'));
+
+  const searchParts = (parts) => {
+    searchInput.value = parts.map(part => part.token).join(' ');
+    searchInput.focus();
+    performSearch(searchInput.value.trim());
+  };
+
+  if (!plan) {
+    notice.appendChild(document.createTextNode(` ${rewrittenQuery}`));
+    return notice;
+  }
+  const parts = planParts(plan);
+  parts.forEach((part, index) => {
+    const chip = document.createElement('span');
+    chip.className = 'plan-chip';
+    chip.textContent = part.label;
+    const remove = document.createElement('button');
+    remove.className = 'plan-chip-remove';
+    remove.title = 'Remove from the search';
+    remove.textContent = '×';
+    remove.addEventListener('click', (e) => {
+      e.stopPropagation();
+      searchParts(parts.filter((_, i) => i !== index));
+    });
+    chip.appendChild(remove);
+    notice.appendChild(chip);
+  });
+
+  const edit = document.createElement('button');
+  edit.className = 'plan-edit';
+  edit.textContent = 'Edit';
+  edit.title = 'Put this query in the search box to change it';
+  edit.addEventListener('click', () => {
+    searchInput.value = rewrittenQuery;
+    searchInput.focus();
+  });
+  notice.appendChild(edit);
+  return notice;
+}
+
 // Sort recent files based on the selected criteria
 function sortRecentFiles(results, sortType) {
   switch (sortType) {
@@ -454,11 +629,19 @@ function sortRecentFiles(results, sortType) {
   }
 }
 
-// Load recent files
+// Start of today in local time, as a Unix timestamp
+function startOfToday() {
+  const today = new Date();
+  today.setHours(0, 0, 0, 0);
+  return Math.floor(today.getTime() / 1000);
+}
+
+// Load recent files, or the files changed on disk today for "Changed Today"
 async function loadRecentFiles() {
   try {
-    const results = await invoke("get_recent_files");
-    const sortedResults = sortRecentFiles(results, currentSort.recent);
+    const sortedResults = currentSort.recent === 'modified'
+      ? await invoke("get_recently_modified", { root: null, limit: 100, since: startOfToday() })
+      : sortRecentFiles(await invoke("get_recent_files"), currentSort.recent);
     
     if (activeTab === 'recent') {
       currentResults = sortedResults;
@@ -516,8 +699,39 @@ async function sortFavorites(results, sortType) {
   }
 }
 
+// Groups shown in the favorites tab, or null when it is sorted another way
+let favoriteSections = null;
+
+// Favorites in their groups and manual order, flattened for keyboard navigation. Each file
+// remembers its group and position so the move controls know where it is.
+async function loadFavoriteGroups() {
+  const { ungrouped, groups } = await invoke("get_favorite_groups");
+  const sections = [{ id: null, name: 'Ungrouped', favorites: ungrouped }, ...groups];
+  const results = sections.flatMap(section =>
+    section.favorites.map((favorite, position) => ({
+      path: favorite.path,
+      name: favorite.name,
+      groupId: section.id,
+      position,
+      groupSize: section.favorites.length,
+    }))
+  );
+  return { sections, results };
+}
+
 async function loadFavorites() {
   try {
+    if (currentSort.favorites === 'groups') {
+      const { sections, results } = await loadFavoriteGroups();
+      favoriteSections = sections;
+      if (activeTab === 'favorites') {
+        currentResults = results;
+        selectedIndex = 0;
+      }
+      renderFavorites(results, sections);
+      return;
+    }
+    favoriteSections = null;
     const favorites = await invoke("get_favorites");
     // Convert favorite paths to file objects with names
     const results = favorites.map(path => {
@@ -573,6 +787,18 @@ function sortSearchResults(results, sortType, recentFilesData) {
 }
 
 // Render search results
+// Paths pinned for a query; a pin applies to every query that starts with its prefix
+async function pinnedPathsFor(query) {
+  const normalized = query.trim().split(/\s+/).join(' ').toLowerCase();
+  try {
+    const pins = await invoke("list_pinned_results");
+    return new Set(pins.filter(pin => normalized.startsWith(pin.query_prefix)).map(pin => pin.path));
+  } catch (error) {
+    console.error("Failed to load pinned results:", error);
+    return new Set();
+  }
+}
+
 async function renderSearchResults(results) {
   if (results.length === 0) {
     if (searchInput.value.trim()) {
@@ -597,7 +823,7 @@ async function renderSearchResults(results) {
       resultsList.innerHTML = `
         <div class="empty-state">
           <h3>Enter a search term</h3>
-          <p>Search for files and folders, supports glob (*.js) and regex patterns</p>
+          <p>Search for files and folders, supports globs (*.js, src/**/*.{rs,toml}) and regex patterns</p>
           <p><kbd>F5</kbd> or <kbd>Ctrl+R</kbd> to refresh • Click Re-index to add new files</p>
         </div>
       `;
@@ -618,6 +844,7 @@ async function renderSearchResults(results) {
   } catch (error) {
     console.error("Failed to load recent files/favorites for badges:", error);
   }
+  const pinnedPaths = await pinnedPathsFor(searchInput.value);
 
   // Sort results based on current sort option
   const sortedResults = sortSearchResults(results, currentSort.search, recentFilesData);
@@ -643,35 +870,49 @@ async function renderSearchResults(results) {
       
       const isRecent = recentPaths.has(file.path);
       const isFavorite = favoritePaths.has(file.path);
+      const isPinned = pinnedPaths.has(file.path);
 
       return `
-        <div class="file-item ${isSelected ? 'selected' : ''}" data-index="${index}" data-path="${escapeHtml(file.path)}">
+        <div class="file-item ${isSelected ? 'selected' : ''} ${ageClass(file)}" data-index="${index}" data-path="${escapeHtml(file.path)}">
           <div class="file-info-row">
             <button class="favorite-btn ${isFavorite ? 'favorited' : ''}" data-path="${escapeHtml(file.path)}" title="${isFavorite ? 'Remove from favorites' : 'Add to favorites'}">
               ${isFavorite ? '★' : '☆'}
             </button>
+            <img class="file-icon" data-path="${escapeHtml(file.path)}" alt="" hidden>
             <div class="file-name">${escapeHtml(file.name)}</div>
             ${isFavorite ? '<span class="fav-badge">FAV</span>' : ''}
             ${isRecent ? '<span class="recent-badge">RECENT</span>' : ''}
+            ${file.in_backup ? '<span class="backup-badge" title="From a backup mirror">BACKUP</span>' : ''}
+            ${isInArchive(file.path) ? '<span class="archive-badge" title="Inside an archive (F7 extracts)">IN ARCHIVE</span>' : ''}
+            ${matchBadges(file)}
             <span class="file-ext-badge ${isFolder ? 'folder-badge' : ''}">${ext}</span>
+            <button class="pin-btn ${isPinned ? 'pinned' : ''}" data-path="${escapeHtml(file.path)}" title="${isPinned ? 'Unpin' : 'Pin to the top for this search'}">📌</button>
             <button class="open-with-btn" data-path="${escapeHtml(file.path)}" title="Open with...">⚙</button>
+            <button class="trash-btn" data-path="${escapeHtml(file.path)}" title="Move to trash (Delete)">🗑</button>
           </div>
           <div class="file-path">${escapeHtml(file.path)}</div>
+          ${file.content_snippet ? `<div class="content-snippet">${file.content_snippet.line}: ${escapeHtml(file.content_snippet.text)}</div>` : ''}
         </div>
       `;
     })
     .join("");
 
   resultsList.innerHTML = html;
+  loadFileIcons(resultsList);
 
   // Add click listeners for file items
   resultsList.querySelectorAll(".file-item").forEach((item) => {
     item.addEventListener("click", (e) => {
       // Don't open if clicking buttons or badges
       if (e.target.classList.contains('open-with-btn') || 
+          e.target.classList.contains('trash-btn') ||
+          e.target.classList.contains('pin-btn') ||
           e.target.classList.contains('favorite-btn') ||
           e.target.classList.contains('fav-badge') ||
           e.target.classList.contains('recent-badge') ||
+          e.target.classList.contains('backup-badge') ||
+          e.target.classList.contains('archive-badge') ||
+          e.target.classList.contains('match-badge') ||
           e.target.classList.contains('file-ext-badge')) {
         return;
       }
@@ -688,6 +929,21 @@ async function renderSearchResults(results) {
     });
   });
 
+  // Pin or unpin a result for the current search, then search again to show it in place
+  resultsList.querySelectorAll(".pin-btn").forEach((btn) => {
+    btn.addEventListener("click", async (e) => {
+      e.stopPropagation();
+      const query = searchInput.value;
+      const command = btn.classList.contains('pinned') ? "unpin_result" : "pin_result";
+      try {
+        await invoke(command, { query, path: btn.dataset.path });
+        await performSearch(query.trim());
+      } catch (error) {
+        showError("Failed to pin: " + errorMessage(error));
+      }
+    });
+  });
+
   // Add click listeners for favorite buttons
   resultsList.querySelectorAll(".favorite-btn").forEach((btn) => {
     btn.addEventListener("click", async (e) => {
@@ -732,16 +988,75 @@ async function renderSearchResults(results) {
     });
   });
 
+  resultsList.querySelectorAll(".trash-btn").forEach((btn) => {
+    btn.addEventListener("click", async (e) => {
+      e.stopPropagation();
+      await moveToTrash([btn.dataset.path]);
+    });
+  });
+
+  attachHoverPreviews(resultsList);
+  attachDragOut(resultsList);
+
   // Scroll selected item into view
   if (activeTab === 'search') {
     scrollToSelected();
   }
 }
 
+// System icons, as object URLs per path, and thumbnails for images, as data URLs. Only rows
+// whose icon loads show it; the rest keep just the extension badge.
+const fileIconUrls = new Map();
+const IMAGE_EXTENSIONS = ["png", "jpg", "jpeg", "gif", "bmp", "webp", "tif", "tiff", "heic", "heif", "ico"];
+// Twice the displayed size, for high-DPI screens
+const THUMBNAIL_SIZE = 64;
+
+function systemIcon(path) {
+  return invoke("get_file_icon", { path })
+    .then((bytes) => ({ url: URL.createObjectURL(new Blob([new Uint8Array(bytes)], { type: "image/png" })), thumbnail: false }))
+    .catch(() => null);
+}
+
+async function fileIconUrl(path) {
+  if (!fileIconUrls.has(path)) {
+    if (fileIconUrls.size > 500) {
+      fileIconUrls.forEach((icon) => icon.then((i) => i && !i.thumbnail && URL.revokeObjectURL(i.url)));
+      fileIconUrls.clear();
+    }
+    const ext = path.includes('.') ? path.split('.').pop().toLowerCase() : '';
+    const icon = IMAGE_EXTENSIONS.includes(ext)
+      ? invoke("get_thumbnail", { path, size: THUMBNAIL_SIZE })
+          .then((thumb) => ({ url: thumb.data_url, thumbnail: true }))
+          .catch(() => systemIcon(path))
+      : systemIcon(path);
+    fileIconUrls.set(path, icon);
+  }
+  return fileIconUrls.get(path);
+}
+
+function loadFileIcons(container) {
+  container.querySelectorAll("img.file-icon").forEach(async (img) => {
+    const icon = await fileIconUrl(img.dataset.path);
+    if (icon) {
+      img.src = icon.url;
+      img.classList.toggle("file-thumbnail", icon.thumbnail);
+      img.hidden = false;
+    }
+  });
+}
+
 // Render recent files
 function renderRecentResults(results) {
+  const changedToday = currentSort.recent === 'modified';
   if (results.length === 0) {
-    recentList.innerHTML = `
+    recentList.innerHTML = changedToday
+      ? `
+      <div class="empty-state">
+        <h3>Nothing changed today</h3>
+        <p>Files modified on disk today show up here after indexing</p>
+      </div>
+    `
+      : `
       <div class="empty-state">
         <h3>No recent files</h3>
         <p>Start opening files to see them here</p>
@@ -759,11 +1074,14 @@ function renderRecentResults(results) {
         : '';
 
       return `
-        <div class="file-item ${isSelected ? 'selected' : ''}" data-index="${index}" data-path="${escapeHtml(file.path)}">
+        <div class="file-item ${isSelected ? 'selected' : ''} ${ageClass(file)}" data-index="${index}" data-path="${escapeHtml(file.path)}">
           <div class="file-info-row">
+            <img class="file-icon" data-path="${escapeHtml(file.path)}" alt="" hidden>
             <div class="file-name">${escapeHtml(file.name)}</div>
+            ${file.in_backup ? '<span class="backup-badge" title="From a backup mirror">BACKUP</span>' : ''}
             <span class="file-ext-badge">${ext}</span>
             <button class="open-with-btn" data-path="${escapeHtml(file.path)}" title="Open with...">⚙</button>
+            ${changedToday ? '' : `<button class="forget-recent-btn" data-path="${escapeHtml(file.path)}" title="Remove from recent files">✕</button>`}
           </div>
           <div class="file-path">${escapeHtml(file.path)}</div>
           ${recentBadge ? `<div class="file-meta">${recentBadge}</div>` : ''}
@@ -773,11 +1091,13 @@ function renderRecentResults(results) {
     .join("");
 
   recentList.innerHTML = html;
+  loadFileIcons(recentList);
+  attachDragOut(recentList);
 
   // Add click listeners for file items
   recentList.querySelectorAll(".file-item").forEach((item) => {
     item.addEventListener("click", (e) => {
-      if (e.target.classList.contains('open-with-btn')) {
+      if (e.target.classList.contains('open-with-btn') || e.target.classList.contains('forget-recent-btn')) {
         return;
       }
       const path = item.dataset.path;
@@ -785,6 +1105,18 @@ function renderRecentResults(results) {
     });
   });
 
+  recentList.querySelectorAll(".forget-recent-btn").forEach((btn) => {
+    btn.addEventListener("click", async (e) => {
+      e.stopPropagation();
+      try {
+        await invoke("clear_recent_files", { path: btn.dataset.path });
+        await loadRecentFiles();
+      } catch (error) {
+        console.error("Failed to remove recent file:", error);
+      }
+    });
+  });
+
   // Add click listeners for "Open with" buttons
   recentList.querySelectorAll(".open-with-btn").forEach((btn) => {
     btn.addEventListener("click", async (e) => {
@@ -800,10 +1132,15 @@ function renderRecentResults(results) {
   }
 }
 
-function renderFavorites(results) {
+function renderFavorites(results, sections = null) {
   const favoritesList = document.getElementById("favorites-list");
   if (!favoritesList) return;
 
+  if (sections) {
+    renderFavoriteGroups(favoritesList, results, sections);
+    return;
+  }
+
   if (results.length === 0) {
     favoritesList.innerHTML = `
       <div class="empty-state">
@@ -824,8 +1161,9 @@ function renderFavorites(results) {
       const badge = isFolder ? 'FOLDER' : ext;
 
       return `
-        <div class="file-item ${isSelected ? 'selected' : ''}" data-index="${index}" data-path="${escapeHtml(file.path)}">
+        <div class="file-item ${isSelected ? 'selected' : ''} ${ageClass(file)}" data-index="${index}" data-path="${escapeHtml(file.path)}">
           <div class="file-info-row">
+            <img class="file-icon" data-path="${escapeHtml(file.path)}" alt="" hidden>
             <div class="file-name">${escapeHtml(file.name)}</div>
             <span class="file-ext-badge ${isFolder ? 'folder-badge' : ''}">${badge}</span>
             <button class="favorite-btn favorited" data-path="${escapeHtml(file.path)}" title="Remove from favorites">
@@ -841,6 +1179,8 @@ function renderFavorites(results) {
     .join("");
 
   favoritesList.innerHTML = html;
+  loadFileIcons(favoritesList);
+  attachDragOut(favoritesList);
 
   // Add click listeners for file items
   favoritesList.querySelectorAll(".file-item").forEach((item) => {
@@ -883,6 +1223,168 @@ function renderFavorites(results) {
   }
 }
 
+function favoriteRow(file, index, sections) {
+  const isSelected = index === selectedIndex && activeTab === 'favorites';
+  const options = sections
+    .map(section => `<option value="${section.id ?? ''}" ${section.id === file.groupId ? 'selected' : ''}>${escapeHtml(section.name)}</option>`)
+    .join("");
+  return `
+    <div class="file-item ${isSelected ? 'selected' : ''}" data-index="${index}" data-path="${escapeHtml(file.path)}">
+      <div class="file-info-row">
+        <img class="file-icon" data-path="${escapeHtml(file.path)}" alt="" hidden>
+        <div class="file-name">${escapeHtml(file.name)}</div>
+        <button class="fav-move-btn" data-index="${index}" data-step="-1" title="Move up" ${file.position === 0 ? 'disabled' : ''}>▲</button>
+        <button class="fav-move-btn" data-index="${index}" data-step="1" title="Move down" ${file.position === file.groupSize - 1 ? 'disabled' : ''}>▼</button>
+        <select class="fav-group-select" data-index="${index}" title="Move to group">${options}</select>
+        <button class="favorite-btn favorited" data-path="${escapeHtml(file.path)}" title="Remove from favorites">★</button>
+        <button class="open-with-btn" data-path="${escapeHtml(file.path)}" title="Open with...">⚙</button>
+      </div>
+      <div class="file-path">${escapeHtml(file.path)}</div>
+    </div>
+  `;
+}
+
+// Favorites tab in "Groups" order: a header per group with rename/delete, and per-row controls
+// to move a favorite up, down or into another group
+function renderFavoriteGroups(favoritesList, results, sections) {
+  let index = 0;
+  const html = sections
+    .map(section => {
+      // Only show the ungrouped header once there are groups to tell it apart from
+      const rows = section.favorites.map(() => favoriteRow(results[index], index++, sections)).join("");
+      if (section.id === null && (sections.length === 1 || section.favorites.length === 0)) {
+        return rows;
+      }
+      const actions = section.id === null ? '' : `
+        <button class="fav-group-rename" data-id="${section.id}" title="Rename group">✎</button>
+        <button class="fav-group-delete" data-id="${section.id}" title="Delete group (its favorites stay, ungrouped)">✕</button>`;
+      return `
+        <div class="fav-group-header" data-id="${section.id ?? ''}">
+          <span class="fav-group-name">${escapeHtml(section.name)}</span>
+          <span class="fav-group-count">${section.favorites.length}</span>
+          ${actions}
+        </div>
+        ${rows || '<div class="fav-group-empty">Empty group</div>'}
+      `;
+    })
+    .join("");
+
+  favoritesList.innerHTML = `
+    <form class="fav-group-new">
+      <input type="text" placeholder="New group name" maxlength="100">
+      <button type="submit">New group</button>
+    </form>
+    ${results.length === 0 && sections.length === 1 ? `
+      <div class="empty-state">
+        <h3>No favorite files</h3>
+        <p>Star files in search results to see them here</p>
+      </div>` : html}
+  `;
+  loadFileIcons(favoritesList);
+  attachDragOut(favoritesList);
+
+  const run = async (command, args) => {
+    try {
+      await invoke(command, args);
+    } catch (error) {
+      alert(errorMessage(error));
+    }
+    await loadFavorites();
+  };
+
+  // Keep typing in the name fields away from the list's keyboard navigation
+  favoritesList.querySelector(".fav-group-new input").addEventListener("keydown", (e) => e.stopPropagation());
+  favoritesList.querySelector(".fav-group-new").addEventListener("submit", async (e) => {
+    e.preventDefault();
+    const input = e.target.querySelector("input");
+    if (input.value.trim()) {
+      await run("create_favorite_group", { name: input.value });
+    }
+  });
+
+  favoritesList.querySelectorAll(".file-item").forEach((item) => {
+    item.addEventListener("click", (e) => {
+      if (e.target.closest('button, select')) {
+        return;
+      }
+      openFile(item.dataset.path);
+    });
+  });
+
+  favoritesList.querySelectorAll(".fav-move-btn").forEach((btn) => {
+    btn.addEventListener("click", async (e) => {
+      e.stopPropagation();
+      const file = results[Number(btn.dataset.index)];
+      const position = file.position + Number(btn.dataset.step);
+      await run("move_favorite", { path: file.path, groupId: file.groupId, position });
+    });
+  });
+
+  favoritesList.querySelectorAll(".fav-group-select").forEach((select) => {
+    select.addEventListener("change", async () => {
+      const file = results[Number(select.dataset.index)];
+      const groupId = select.value === '' ? null : Number(select.value);
+      await run("move_favorite", { path: file.path, groupId, position: null });
+    });
+  });
+
+  favoritesList.querySelectorAll(".fav-group-rename").forEach((btn) => {
+    btn.addEventListener("click", () => {
+      const header = btn.closest(".fav-group-header");
+      const nameEl = header.querySelector(".fav-group-name");
+      const input = document.createElement("input");
+      input.type = "text";
+      input.value = nameEl.textContent;
+      nameEl.replaceWith(input);
+      input.focus();
+      input.select();
+      let done = false;
+      const finish = async (save) => {
+        if (done) return;
+        done = true;
+        if (save && input.value.trim() && input.value !== nameEl.textContent) {
+          await run("rename_favorite_group", { id: Number(btn.dataset.id), name: input.value });
+        } else {
+          await loadFavorites();
+        }
+      };
+      input.addEventListener("keydown", (e) => {
+        e.stopPropagation();
+        if (e.key === "Enter") finish(true);
+        if (e.key === "Escape") finish(false);
+      });
+      input.addEventListener("blur", () => finish(true));
+    });
+  });
+
+  favoritesList.querySelectorAll(".fav-group-delete").forEach((btn) => {
+    btn.addEventListener("click", async () => {
+      const name = btn.closest(".fav-group-header").querySelector(".fav-group-name").textContent;
+      if (confirm(`Delete the group "${name}"? Its favorites stay, ungrouped.`)) {
+        await run("delete_favorite_group", { id: Number(btn.dataset.id) });
+      }
+    });
+  });
+
+  favoritesList.querySelectorAll(".favorite-btn").forEach((btn) => {
+    btn.addEventListener("click", async (e) => {
+      e.stopPropagation();
+      await run("toggle_favorite", { path: btn.dataset.path });
+    });
+  });
+
+  favoritesList.querySelectorAll(".open-with-btn").forEach((btn) => {
+    btn.addEventListener("click", async (e) => {
+      e.stopPropagation();
+      await showOpenWithDialog(btn.dataset.path);
+    });
+  });
+
+  if (activeTab === 'favorites') {
+    scrollToSelected();
+  }
+}
+
 // Handle keyboard navigation
 function handleKeyboard(e) {
   // Don't handle vim keys if user is typing in the search input (except for special keys)
@@ -897,7 +1399,7 @@ function handleKeyboard(e) {
     const currentQuery = searchInput.value.trim();
     if (currentQuery) {
       console.log("Force refreshing search for:", currentQuery);
-      performSearch(currentQuery);
+      performSearch(currentQuery, { forceRefresh: true });
     }
     return;
   }
@@ -979,7 +1481,69 @@ function handleKeyboard(e) {
     case "Enter":
       e.preventDefault();
       if (currentResults[selectedIndex]) {
-        openFile(currentResults[selectedIndex].path);
+        // Alt+Enter - properties, as in Explorer
+        if (e.altKey) {
+          showPropertiesDialog(currentResults[selectedIndex].path);
+        } else {
+          openFile(currentResults[selectedIndex].path);
+        }
+      }
+      break;
+
+    case "F2":
+      if (activeTab === 'search' && currentResults[selectedIndex]) {
+        e.preventDefault();
+        showRenameDialog(currentResults[selectedIndex].path);
+      }
+      break;
+
+    case "e":
+    case "E":
+      if (e.ctrlKey && currentResults[selectedIndex]) {
+        // Ctrl+E - edit the note on the selected file
+        e.preventDefault();
+        showNoteDialog(currentResults[selectedIndex].path);
+      }
+      break;
+
+    case "n":
+    case "N":
+      if (e.ctrlKey && activeTab === 'search' && currentResults[selectedIndex]) {
+        // Ctrl+N - new file, Ctrl+Shift+N - new folder, next to the selected result
+        e.preventDefault();
+        createEntryNear(currentResults[selectedIndex].path, e.shiftKey ? "folder" : "file");
+      }
+      break;
+
+    case "F6":
+    case "F7":
+      if (activeTab === 'search' && currentResults[selectedIndex]) {
+        e.preventDefault();
+        const path = currentResults[selectedIndex].path;
+        // Entries inside archives can only be copied out
+        if (isInArchive(path)) {
+          if (e.key === "F7") {
+            extractTo(path);
+          }
+        } else {
+          transferTo(e.key === "F6" ? "move" : "copy", [path]);
+        }
+      }
+      break;
+
+    case " ":
+      // Space - quick look at a text file; in the search box it types a space
+      if (!isTypingInSearch && activeTab === 'search' && currentResults[selectedIndex]) {
+        e.preventDefault();
+        showTextPreview(currentResults[selectedIndex].path);
+      }
+      break;
+
+    case "Delete":
+      // In the search box Delete edits the query
+      if (!isTypingInSearch && activeTab === 'search' && currentResults[selectedIndex]) {
+        e.preventDefault();
+        moveToTrash([currentResults[selectedIndex].path]);
       }
       break;
 
@@ -999,7 +1563,7 @@ function renderCurrentTab() {
   if (activeTab === 'search') {
     renderSearchResults(currentResults);
   } else if (activeTab === 'favorites') {
-    renderFavorites(currentResults);
+    renderFavorites(currentResults, favoriteSections);
   } else {
     renderRecentResults(currentResults);
   }
@@ -1013,7 +1577,455 @@ async function openFile(path) {
     await loadRecentFiles();
   } catch (error) {
     console.error("Failed to open file:", error);
-    showError("Failed to open file: " + error);
+    showError("Failed to open file: " + errorMessage(error));
+  }
+}
+
+// Move files to the OS trash and drop them from the results
+async function moveToTrash(paths) {
+  const names = paths.map((path) => path.split(/[/\\]/).pop());
+  if (!confirm(`Move ${names.length === 1 ? `"${names[0]}"` : `${names.length} items`} to the trash?`)) {
+    return;
+  }
+  try {
+    const outcomes = await invoke("delete_to_trash", { paths });
+    const trashed = new Set(outcomes.filter((o) => o.success).map((o) => o.path));
+    const failed = outcomes.filter((o) => !o.success);
+    currentResults = currentResults.filter((file) => !trashed.has(file.path));
+    selectedIndex = Math.min(selectedIndex, Math.max(currentResults.length - 1, 0));
+    renderCurrentTab();
+    await loadRecentFiles();
+    if (failed.length > 0) {
+      showError(`Could not move to the trash: ${failed.map((o) => `${o.path} (${o.error})`).join(", ")}`);
+    } else {
+      showSuccess(trashed.size === 1 ? `Moved ${names[0]} to the trash` : `Moved ${trashed.size} items to the trash`);
+    }
+  } catch (error) {
+    console.error("Failed to move to trash:", error);
+    showError("Failed to move to trash: " + errorMessage(error));
+  }
+}
+
+// Move or copy files into a folder picked in a dialog. Items whose name is taken there are
+// skipped first; the user then chooses between replacing them and keeping both.
+async function transferTo(mode, paths) {
+  const command = mode === "move" ? "move_files" : "copy_files";
+  const verb = mode === "move" ? "Move" : "Copy";
+  const destDir = await window.__TAURI__.dialog.open({
+    directory: true,
+    multiple: false,
+    title: `${verb} to folder`
+  });
+  if (!destDir) {
+    return;
+  }
+  try {
+    let outcomes = await invoke(command, { paths, destDir, onCollision: "skip" });
+    const skipped = outcomes.filter((o) => o.skipped).map((o) => o.path);
+    if (skipped.length > 0) {
+      const replace = confirm(`${skipped.length} item(s) already exist in ${destDir}.\n\nOK replaces them, Cancel keeps both.`);
+      const retried = await invoke(command, { paths: skipped, destDir, onCollision: replace ? "overwrite" : "rename" });
+      outcomes = outcomes.filter((o) => !o.skipped).concat(retried);
+    }
+    const failed = outcomes.filter((o) => !o.success);
+    if (mode === "move") {
+      const moved = new Map(outcomes.filter((o) => o.success).map((o) => [o.path, o.destination]));
+      currentResults = currentResults.map((file) => moved.has(file.path)
+        ? { ...file, path: moved.get(file.path) }
+        : file);
+      renderCurrentTab();
+      await loadRecentFiles();
+      await loadFavorites();
+    }
+    if (failed.length > 0) {
+      showError(`Could not ${mode}: ${failed.map((o) => `${o.path} (${o.error})`).join(", ")}`);
+    } else {
+      showSuccess(`${mode === "move" ? "Moved" : "Copied"} ${outcomes.length} item(s) to ${destDir}`);
+    }
+  } catch (error) {
+    console.error(`Failed to ${mode}:`, error);
+    showError(`Failed to ${mode}: ` + errorMessage(error));
+  }
+}
+
+// Indexed entries inside archives look like "backup.zip!/src/main.rs"
+function isInArchive(path) {
+  return /\.(zip|7z|tar|tgz|tar\.gz)!\//i.test(path);
+}
+
+async function extractTo(path) {
+  const destDir = await window.__TAURI__.dialog.open({
+    directory: true,
+    multiple: false,
+    title: "Extract to folder"
+  });
+  if (!destDir) {
+    return;
+  }
+  try {
+    const extracted = await invoke("extract_archive_entry", { path, destDir });
+    showSuccess(`Extracted to ${extracted}`);
+  } catch (error) {
+    console.error("Failed to extract:", error);
+    showError("Failed to extract: " + errorMessage(error));
+  }
+}
+
+function formatBytes(bytes) {
+  const units = ["bytes", "KB", "MB", "GB", "TB"];
+  let value = bytes;
+  let unit = 0;
+  while (value >= 1024 && unit < units.length - 1) {
+    value /= 1024;
+    unit++;
+  }
+  return unit === 0 ? `${bytes.toLocaleString()} bytes` : `${value.toFixed(1)} ${units[unit]} (${bytes.toLocaleString()} bytes)`;
+}
+
+const DOCUMENT_EXTENSIONS = ["pdf", "docx", "xlsx", "pptx"];
+
+function isDocument(path) {
+  const ext = path.includes('.') ? path.split('.').pop().toLowerCase() : '';
+  return DOCUMENT_EXTENSIONS.includes(ext);
+}
+
+// What's inside a PDF or Office document, shown after the pointer rests on a result
+let hoverPreviewTimer = null;
+let hoverPreview = null;
+
+function hideHoverPreview() {
+  clearTimeout(hoverPreviewTimer);
+  if (hoverPreview) {
+    hoverPreview.remove();
+    hoverPreview = null;
+  }
+}
+
+// Pixels the pointer must move with the button down before a row is dragged rather than clicked
+const DRAG_THRESHOLD = 6;
+
+// Dragging a row hands the file to the OS, so it can be dropped into Explorer, a mail or an
+// upload dialog. The backend starts the native drag while the button is still held.
+function attachDragOut(container) {
+  container.querySelectorAll(".file-item").forEach((item) => {
+    item.addEventListener("mousedown", (e) => {
+      if (e.button !== 0 || e.target.closest('button')) {
+        return;
+      }
+      const startX = e.clientX;
+      const startY = e.clientY;
+      const onMove = (move) => {
+        if (Math.abs(move.clientX - startX) + Math.abs(move.clientY - startY) < DRAG_THRESHOLD) {
+          return;
+        }
+        stop();
+        hideHoverPreview();
+        invoke("start_drag", { paths: [item.dataset.path] }).catch((error) => {
+          console.error("Failed to drag file:", error);
+        });
+      };
+      const stop = () => {
+        document.removeEventListener("mousemove", onMove);
+        document.removeEventListener("mouseup", stop);
+      };
+      document.addEventListener("mousemove", onMove);
+      document.addEventListener("mouseup", stop);
+    });
+  });
+}
+
+function attachHoverPreviews(container) {
+  // Rows being replaced never see the pointer leave
+  hideHoverPreview();
+  container.querySelectorAll(".file-item").forEach((item) => {
+    if (!isDocument(item.dataset.path)) {
+      return;
+    }
+    item.addEventListener("mouseenter", () => {
+      hideHoverPreview();
+      hoverPreviewTimer = setTimeout(async () => {
+        try {
+          const preview = await invoke("preview_document", { path: item.dataset.path, maxChars: 600 });
+          if (!item.matches(":hover")) {
+            return;
+          }
+          hideHoverPreview();
+          hoverPreview = document.createElement('div');
+          hoverPreview.className = 'hover-preview';
+          hoverPreview.innerHTML = `
+            ${preview.section ? `<div class="hover-preview-section">${escapeHtml(preview.section)}</div>` : ''}
+            <div class="hover-preview-text">${escapeHtml(preview.text)}${preview.truncated ? '…' : ''}</div>
+          `;
+          const rect = item.getBoundingClientRect();
+          hoverPreview.style.left = `${rect.left + 24}px`;
+          // Below the row, or above it when the row is near the bottom of the window
+          if (rect.bottom + 220 < window.innerHeight) {
+            hoverPreview.style.top = `${rect.bottom + 4}px`;
+          } else {
+            hoverPreview.style.bottom = `${window.innerHeight - rect.top + 4}px`;
+          }
+          document.body.appendChild(hoverPreview);
+        } catch (error) {
+          // Scanned PDFs and damaged documents just show no preview
+          console.debug("No document preview:", error);
+        }
+      }, 500);
+    });
+    item.addEventListener("mouseleave", hideHoverPreview);
+  });
+}
+
+// The start of a text file with line numbers, or the text of a document, without opening another program
+async function showTextPreview(path) {
+  hideHoverPreview();
+  try {
+    let lines;
+    let note;
+    if (isDocument(path)) {
+      const preview = await invoke("preview_document", { path, maxChars: null });
+      lines = preview.text.split('\n');
+      note = [preview.kind.toUpperCase(), preview.section, preview.truncated && 'the start of the text'].filter(Boolean).join(' · ');
+    } else {
+      const preview = await invoke("preview_text", { path, maxBytes: null });
+      const offsets = preview.line_offsets;
+      lines = offsets.map((start, i) => preview.text.slice(start, i + 1 < offsets.length ? offsets[i + 1] : undefined).replace(/\r?\n$/, ''));
+      note = preview.encoding + (preview.truncated ? ` · the start of ${formatBytes(preview.file_size)}` : '');
+    }
+
+    const modal = document.createElement('div');
+    modal.className = 'modal-overlay';
+    modal.innerHTML = `
+      <div class="modal-content text-preview-modal">
+        <h3>${escapeHtml(path.split(/[/\\]/).pop())}</h3>
+        <div class="text-preview-info">${escapeHtml(note)}</div>
+        <div class="text-preview">
+          ${lines.map((line, i) => `<div class="text-preview-line"><span class="text-preview-number">${i + 1}</span><span>${escapeHtml(line)}</span></div>`).join('')}
+        </div>
+        <div class="modal-actions">
+          <button class="btn-secondary" id="modal-cancel">Close</button>
+        </div>
+      </div>
+    `;
+    document.body.appendChild(modal);
+    const close = () => {
+      modal.remove();
+      document.removeEventListener('keydown', onKey, true);
+    };
+    // Space or Escape closes it again, as in Quick Look
+    const onKey = (e) => {
+      if (e.key === ' ' || e.key === 'Escape') {
+        e.preventDefault();
+        e.stopPropagation();
+        close();
+      }
+    };
+    document.addEventListener('keydown', onKey, true);
+    modal.querySelector('#modal-cancel').addEventListener('click', close);
+    modal.addEventListener('click', (e) => {
+      if (e.target === modal) {
+        close();
+      }
+    });
+  } catch (error) {
+    console.error("Failed to preview file:", error);
+    showError("Failed to preview file: " + errorMessage(error));
+  }
+}
+
+// Details of a file as the file system reports them now
+async function showPropertiesDialog(path) {
+  try {
+    const props = await invoke("get_file_properties", { path });
+    const time = (seconds) => seconds == null ? null : new Date(seconds * 1000).toLocaleString();
+    const attributes = [props.readonly && "read-only", props.hidden && "hidden", props.is_symlink && "link"].filter(Boolean).join(", ");
+    const rows = [
+      ["Location", props.path],
+      ["Type", props.is_dir ? "Folder" : "File"],
+      [props.is_dir ? "Size of entry" : "Size", formatBytes(props.size)],
+      ["Items", props.child_count == null ? null : props.child_count.toLocaleString()],
+      ["Created", time(props.created_at)],
+      ["Modified", time(props.modified_at)],
+      ["Accessed", time(props.accessed_at)],
+      ["Permissions", props.permissions && `${props.permissions} (${props.mode})`],
+      ["Owner", props.owner && (props.group ? `${props.owner}:${props.group}` : props.owner)],
+      ["Link target", props.link_target],
+      ["Attributes", attributes || null],
+    ].filter(([, value]) => value != null);
+
+    const modal = document.createElement('div');
+    modal.className = 'modal-overlay';
+    modal.innerHTML = `
+      <div class="modal-content">
+        <h3>${escapeHtml(props.name)}</h3>
+        <table class="properties-table">
+          ${rows.map(([label, value]) => `<tr><th>${label}</th><td>${escapeHtml(String(value))}</td></tr>`).join('')}
+        </table>
+        <div class="modal-actions">
+          <button class="btn-secondary" id="modal-cancel">Close</button>
+        </div>
+      </div>
+    `;
+    document.body.appendChild(modal);
+    const close = () => modal.remove();
+    modal.querySelector('#modal-cancel').addEventListener('click', close);
+    modal.addEventListener('click', (e) => {
+      if (e.target === modal) {
+        close();
+      }
+    });
+  } catch (error) {
+    console.error("Failed to read properties:", error);
+    showError("Failed to read properties: " + errorMessage(error));
+  }
+}
+
+// Ask for a file or folder name in a small dialog. Resolves to the trimmed name, or null when
+// cancelled. With `selectStem` the name is preselected without its extension.
+function askForName(title, initialValue, actionLabel, selectStem) {
+  return new Promise((resolve) => {
+    const modal = document.createElement('div');
+    modal.className = 'modal-overlay';
+    modal.innerHTML = `
+      <div class="modal-content">
+        <h3>${escapeHtml(title)}</h3>
+        <div class="modal-actions">
+          <input type="text" id="name-input" class="custom-program-input" />
+          <div style="display: flex; gap: 8px; margin-top: 8px;">
+            <button class="btn-secondary" id="modal-cancel">Cancel</button>
+            <button class="btn-primary" id="modal-confirm">${escapeHtml(actionLabel)}</button>
+          </div>
+        </div>
+      </div>
+    `;
+    document.body.appendChild(modal);
+
+    const input = modal.querySelector('#name-input');
+    input.value = initialValue;
+    input.focus();
+    const dot = initialValue.lastIndexOf('.');
+    input.setSelectionRange(0, selectStem && dot > 0 ? dot : initialValue.length);
+
+    const close = (value) => {
+      modal.remove();
+      resolve(value);
+    };
+    modal.querySelector('#modal-cancel').addEventListener('click', () => close(null));
+    modal.querySelector('#modal-confirm').addEventListener('click', () => close(input.value.trim() || null));
+    input.addEventListener('keydown', (e) => {
+      e.stopPropagation();
+      if (e.key === 'Enter') {
+        close(input.value.trim() || null);
+      } else if (e.key === 'Escape') {
+        close(null);
+      }
+    });
+    modal.addEventListener('click', (e) => {
+      if (e.target === modal) {
+        close(null);
+      }
+    });
+  });
+}
+
+// Rename a result in place; the index, recents and favorites follow the new name
+async function showRenameDialog(path) {
+  const fileName = path.split(/[/\\]/).pop();
+  const newName = await askForName(`Rename "${fileName}"`, fileName, "Rename", true);
+  if (!newName || newName === fileName) {
+    return;
+  }
+  try {
+    const entry = await invoke("rename_file", { path, newName });
+    currentResults = currentResults.map((file) => file.path === path ? { ...file, ...entry } : file);
+    renderCurrentTab();
+    await loadRecentFiles();
+    await loadFavorites();
+    showSuccess(`Renamed to ${entry.name}`);
+  } catch (error) {
+    console.error("Failed to rename:", error);
+    showError("Failed to rename: " + errorMessage(error));
+  }
+}
+
+// Write or change the note on a file. Saving an empty note deletes it.
+async function showNoteDialog(path) {
+  const fileName = path.split(/[/\\]/).pop();
+  let note = null;
+  try {
+    note = await invoke("get_note", { path });
+  } catch (error) {
+    showError("Failed to load note: " + errorMessage(error));
+    return;
+  }
+
+  const modal = document.createElement('div');
+  modal.className = 'modal-overlay';
+  modal.innerHTML = `
+    <div class="modal-content">
+      <h3>Note on "${escapeHtml(fileName)}"</h3>
+      <p class="modal-subtitle">Searches find this file by the words in its note.</p>
+      <div class="modal-actions">
+        <textarea id="note-input" class="custom-program-input note-input" rows="6" maxlength="10000"></textarea>
+        <div style="display: flex; gap: 8px; margin-top: 8px;">
+          <button class="btn-secondary" id="modal-cancel">Cancel</button>
+          <button class="btn-primary" id="modal-confirm">Save</button>
+        </div>
+      </div>
+    </div>
+  `;
+  document.body.appendChild(modal);
+
+  const input = modal.querySelector('#note-input');
+  input.value = note ? note.text : '';
+  input.focus();
+
+  const save = async () => {
+    modal.remove();
+    try {
+      const saved = await invoke("set_note", { path, text: input.value });
+      showSuccess(saved ? "Note saved" : "Note deleted");
+    } catch (error) {
+      console.error("Failed to save note:", error);
+      showError("Failed to save note: " + errorMessage(error));
+    }
+  };
+  modal.querySelector('#modal-cancel').addEventListener('click', () => modal.remove());
+  modal.querySelector('#modal-confirm').addEventListener('click', save);
+  input.addEventListener('keydown', (e) => {
+    e.stopPropagation();
+    // Enter starts a new line; Ctrl+Enter saves
+    if (e.key === 'Enter' && e.ctrlKey) {
+      save();
+    } else if (e.key === 'Escape') {
+      modal.remove();
+    }
+  });
+}
+
+// Create a file or folder in the selected result's folder. New files are opened right away, so
+// a search can end in a fresh note in the right project.
+async function createEntryNear(path, kind) {
+  let parentDir = path.slice(0, Math.max(path.lastIndexOf('/'), path.lastIndexOf('\\'))) || '/';
+  // "C:" alone is the drive's current folder, not its root
+  if (parentDir.endsWith(':')) {
+    parentDir += '\\';
+  }
+  const name = await askForName(`New ${kind} in ${parentDir}`, kind === "file" ? "notes.md" : "New folder", "Create", true);
+  if (!name) {
+    return;
+  }
+  try {
+    const entry = await invoke("create_entry", { parentDir, name, kind });
+    currentResults = [entry, ...currentResults];
+    selectedIndex = 0;
+    renderCurrentTab();
+    showSuccess(`Created ${entry.path}`);
+    if (kind === "file") {
+      await openFile(entry.path);
+    }
+  } catch (error) {
+    console.error("Failed to create:", error);
+    showError("Failed to create: " + errorMessage(error));
   }
 }
 
@@ -1025,7 +2037,7 @@ async function openFileWith(path, program) {
     showSuccess(`Opened with ${program}`);
   } catch (error) {
     console.error("Failed to open file:", error);
-    showError("Failed to open file: " + error);
+    showError("Failed to open file: " + errorMessage(error));
   }
 }
 
@@ -1043,13 +2055,16 @@ async function showOpenWithDialog(path) {
         <h3>Open "${fileName}"</h3>
         <p class="modal-subtitle">File type: .${fileInfo.extension}</p>
         <div class="program-list">
-          ${fileInfo.suggested_programs.map(program => `
-            <button class="program-btn" data-program="${program}">
+          ${fileInfo.apps.map(app => `
+            <button class="program-btn" data-program="${escapeHtml(app.program)}" title="${escapeHtml(app.program)}">
               <span class="program-icon">📝</span>
-              <span class="program-name">${program}</span>
+              <span class="program-name">${escapeHtml(app.name)}</span>
+              ${app.program === fileInfo.preferred_program ? '<span class="program-default">Last used</span>' : ''}
+              ${app.is_default ? '<span class="program-default">Default</span>' : ''}
             </button>
-          `).join('')}
+          `).join('') || `<p class="modal-subtitle">No applications are registered for this file type.</p>`}
         </div>
+        ${fileInfo.preferred_program ? `<button class="btn-secondary" id="modal-forget">Forget choice for .${escapeHtml(fileInfo.extension)}</button>` : ''}
         <div class="modal-actions">
           <input type="text" id="custom-program" placeholder="Or enter custom program path..." class="custom-program-input" />
           <div style="display: flex; gap: 8px; margin-top: 8px;">
@@ -1074,6 +2089,12 @@ async function showOpenWithDialog(path) {
     modal.querySelector('#modal-cancel').addEventListener('click', () => {
       document.body.removeChild(modal);
     });
+
+    modal.querySelector('#modal-forget')?.addEventListener('click', async () => {
+      document.body.removeChild(modal);
+      await invoke("clear_open_with_prefs", { extension: fileInfo.extension });
+      showOpenWithDialog(path);
+    });
     
     // Add Enter key support for custom program input
     const customInput = modal.querySelector('#custom-program');
@@ -1108,7 +2129,7 @@ async function showOpenWithDialog(path) {
     });
   } catch (error) {
     console.error("Failed to get file info:", error);
-    showError("Failed to get file info: " + error);
+    showError("Failed to get file info: " + errorMessage(error));
   }
 }
 
@@ -1137,7 +2158,7 @@ async function startIndexing() {
     }, 120000);
   } catch (error) {
     console.error("Failed to start indexing:", error);
-    alert("Failed to start indexing: " + error);
+    alert("Failed to start indexing: " + errorMessage(error));
     indexBtn.disabled = false;
     indexBtn.textContent = "Index Home Directory";
     indexStatusEl.textContent = "Indexing failed";
@@ -1171,15 +2192,24 @@ async function updateStatus() {
   } catch (error) {
     console.error("Failed to get status:", error);
     if (indexStatusEl) {
-      indexStatusEl.textContent = "Status error: " + error;
+      indexStatusEl.textContent = "Status error: " + errorMessage(error);
     }
   }
 }
 
+// Use a reindex schedule and show it in the settings panel
+function showReindexSchedule(schedule) {
+  reindexSchedule = schedule;
+  document.getElementById("auto-reindex").checked = schedule.enabled;
+  const hour = (h) => new Date(2000, 0, 1, h).toLocaleTimeString([], { hour: 'numeric', minute: '2-digit' });
+  document.querySelector(".time-range-info small").textContent =
+    `Reindexes daily between ${hour(schedule.start_hour)} - ${hour(schedule.end_hour)}`;
+}
+
 // Check if it's time for scheduled reindexing
 async function checkScheduledReindex() {
   // Check if auto-reindex is enabled
-  if (!autoReindexEnabled) {
+  if (!reindexSchedule.enabled) {
     return;
   }
   
@@ -1187,8 +2217,11 @@ async function checkScheduledReindex() {
   const hour = now.getHours();
   const currentDate = now.toDateString();
   
-  // Define nighttime as 2 AM to 5 AM (customize as needed)
-  const isNightTime = hour >= 2 && hour < 5;
+  // The window can run past midnight, e.g. 23:00 - 2:00
+  const { start_hour, end_hour } = reindexSchedule;
+  const isNightTime = start_hour < end_hour
+    ? hour >= start_hour && hour < end_hour
+    : hour >= start_hour || hour < end_hour;
   
   // Check if we already reindexed today
   const alreadyReindexedToday = lastReindexDate === currentDate;
@@ -1233,6 +2266,11 @@ function scrollToSelected() {
   }
 }
 
+// Message of an error a command returned, `{ code, message, recoverable }`, or of any other error
+function errorMessage(error) {
+  return error?.message ?? String(error);
+}
+
 // Show error message
 function showError(message) {
   // You can implement a toast notification here
@@ -1252,7 +2290,7 @@ async function loadIndexedDirectories() {
     }
     
     selector.innerHTML = directories.map(dir => {
-      const label = dir.name || dir.path;
+      const label = dir.is_mirror ? `${dir.name || dir.path} (backup)` : (dir.name || dir.path);
       return `<option value="${dir.path}" ${dir.is_active ? 'selected' : ''}>${label}</option>`;
     }).join('');
   } catch (error) {
@@ -1267,6 +2305,11 @@ function showSuccess(message) {
   alert(message);
 }
 
+// Freshness bucket computed by the backend (today / this_week / this_month / older)
+function ageClass(file) {
+  return file.age_bucket ? `age-${file.age_bucket.replace(/_/g, '-')}` : '';
+}
+
 // Escape HTML to prevent XSS
 function escapeHtml(text) {
   const div = document.createElement("div");
@@ -1319,7 +2362,7 @@ async function debugCheckFile(filePath) {
     
     for (const pattern of patterns) {
       try {
-        const results = await invoke("search_files", { query: pattern, options: searchOptions });
+        const { results } = await invoke("search_files", { query: pattern, options: searchOptions });
         console.log(`📋 Pattern "${pattern}": ${results.length} results`);
         
         // Check if our specific file is in the results
@@ -1353,7 +2396,7 @@ async function debugCheckFile(filePath) {
           // Try to find any files specifically in the buildSrc/src path
           console.log(`🔍 Searching for files in buildSrc/src path...`);
           try {
-            const buildSrcSrcResults = await invoke("search_files", { 
+            const { results: buildSrcSrcResults } = await invoke("search_files", { 
               query: "buildSrc/src", 
               options: searchOptions 
             });
@@ -1390,7 +2433,7 @@ async function debugCheckFile(filePath) {
       ];
       
       for (const pathPattern of pathPatterns) {
-        const pathResults = await invoke("search_files", { 
+        const { results: pathResults } = await invoke("search_files", { 
           query: pathPattern, 
           options: searchOptions 
         });
@@ -0,0 +1,110 @@
+// Quick-search overlay window, shown by the global shortcut. Lists the top few results;
+// Enter opens one, Ctrl+Enter continues the search in the main window.
+const { invoke } = window.__TAURI__.core;
+
+let input;
+let resultsEl;
+let results = [];
+let selected = 0;
+let maxResults = 8;
+let searchTimeout;
+// Responses to older keystrokes are dropped
+let searchGeneration = 0;
+
+window.addEventListener("DOMContentLoaded", async () => {
+  input = document.querySelector("#overlay-input");
+  resultsEl = document.querySelector("#overlay-results");
+
+  try {
+    maxResults = (await invoke("get_overlay_config")).max_results;
+  } catch (error) {
+    console.error("Failed to load overlay settings:", error);
+  }
+
+  input.addEventListener("input", () => {
+    clearTimeout(searchTimeout);
+    searchTimeout = setTimeout(search, 100);
+  });
+  input.addEventListener("keydown", onKey);
+
+  // The window is reused, so every appearance starts over
+  window.__TAURI__.event.listen("overlay-shown", () => {
+    input.value = "";
+    results = [];
+    render();
+    input.focus();
+  });
+  input.focus();
+});
+
+async function search() {
+  const query = input.value.trim();
+  const generation = ++searchGeneration;
+  if (!query) {
+    results = [];
+    render();
+    return;
+  }
+  try {
+    const response = await invoke("search_files", { query, options: null });
+    if (generation !== searchGeneration) {
+      return;
+    }
+    results = response.results.slice(0, maxResults);
+    selected = 0;
+    render();
+  } catch (error) {
+    console.error("Overlay search failed:", error);
+  }
+}
+
+function render() {
+  resultsEl.innerHTML = results.map((file, index) => `
+    <div class="overlay-item ${index === selected ? 'selected' : ''}" data-index="${index}">
+      <div class="file-name">${escapeHtml(file.name)}</div>
+      <div class="file-path">${escapeHtml(file.path)}</div>
+    </div>
+  `).join('');
+  resultsEl.querySelectorAll(".overlay-item").forEach((item) => {
+    item.addEventListener("click", () => open(parseInt(item.dataset.index)));
+  });
+}
+
+async function onKey(e) {
+  if (e.key === "Escape") {
+    e.preventDefault();
+    await invoke("hide_overlay");
+  } else if (e.key === "ArrowDown" || e.key === "ArrowUp") {
+    e.preventDefault();
+    if (results.length) {
+      selected = (selected + (e.key === "ArrowDown" ? 1 : results.length - 1)) % results.length;
+      render();
+    }
+  } else if (e.key === "Enter") {
+    e.preventDefault();
+    if (e.ctrlKey || e.metaKey) {
+      await invoke("show_in_main_window", { query: input.value.trim() });
+    } else {
+      await open(selected);
+    }
+  }
+}
+
+async function open(index) {
+  const file = results[index];
+  if (!file) {
+    return;
+  }
+  try {
+    await invoke("open_file", { path: file.path });
+    await invoke("hide_overlay");
+  } catch (error) {
+    console.error("Failed to open file:", error);
+  }
+}
+
+function escapeHtml(text) {
+  const div = document.createElement("div");
+  div.textContent = text;
+  return div.innerHTML;
+}